@@ -24,8 +24,11 @@ Architecture:
 """
 
 from starlette.applications import Starlette
+from starlette.middleware import Middleware
 from starlette.responses import JSONResponse
 from starlette.routing import Route
+import hashlib
+import hmac
 import random
 import time
 import threading
@@ -141,6 +144,102 @@ async def health_handler(request):
     """
     return JSONResponse({"status": "ok"})
 
+# ==================== Trace Context ====================
+
+TRACE_ID_HEADER = b"x-trace-id"
+
+# Polled every second; not worth a log line each
+QUIET_PATHS = {"/status", "/health"}
+
+
+class TraceIdMiddleware:
+    """
+    Echoes the X-Trace-Id request header in the response and logs it.
+    The Rust backend sends one with every request (plus X-Request-Id and
+    X-Session-Id when known) and reports a missing or different echo as a
+    protocol warning.
+    """
+    def __init__(self, app):
+        self.app = app
+
+    async def __call__(self, scope, receive, send):
+        if scope["type"] != "http":
+            await self.app(scope, receive, send)
+            return
+
+        headers = dict(scope.get("headers") or [])
+        trace_id = headers.get(TRACE_ID_HEADER)
+        if trace_id is None:
+            await self.app(scope, receive, send)
+            return
+
+        if scope["path"] not in QUIET_PATHS:
+            request_id = headers.get(b"x-request-id", b"-").decode("latin-1")
+            session_id = headers.get(b"x-session-id", b"-").decode("latin-1")
+            print(f"[trace {trace_id.decode('latin-1')}] {scope['method']} {scope['path']} "
+                  f"request={request_id} session={session_id}")
+
+        async def send_with_trace_id(message):
+            if message["type"] == "http.response.start":
+                message["headers"] = list(message.get("headers", [])) + [(TRACE_ID_HEADER, trace_id)]
+            await send(message)
+
+        await self.app(scope, receive, send_with_trace_id)
+
+# ==================== Token Authentication ====================
+
+# Set by the Rust backend for every engine it spawns
+ENGINE_SECRET = os.getenv("AI_ENGINE_TOKEN")
+
+PROOF_HEADER = b"x-engine-auth"
+
+
+def derive_key(label):
+    return hmac.new(ENGINE_SECRET.encode("latin-1"), label, hashlib.sha256).digest()
+
+
+# Sent by the backend with every request; the proof key never leaves the process
+REQUEST_KEY = derive_key(b"request").hex() if ENGINE_SECRET is not None else None
+PROOF_KEY = derive_key(b"response") if ENGINE_SECRET is not None else None
+
+
+class TokenAuthMiddleware:
+    """
+    Derives two keys from the AI_ENGINE_TOKEN secret:
+      request = hex HMAC-SHA256(secret, "request")
+      proof   = HMAC-SHA256(secret, "response")
+    Requires `Authorization: Bearer <request key>` on every request and
+    proves to the backend that it holds the secret: each response carries
+    X-Engine-Auth = hex HMAC-SHA256(proof key, X-Trace-Id of the request).
+    Without AI_ENGINE_TOKEN (an engine started by hand) every request is
+    accepted.
+    """
+    def __init__(self, app):
+        self.app = app
+
+    async def __call__(self, scope, receive, send):
+        if scope["type"] != "http" or ENGINE_SECRET is None:
+            await self.app(scope, receive, send)
+            return
+
+        headers = dict(scope.get("headers") or [])
+        expected = b"Bearer " + REQUEST_KEY.encode("ascii")
+        if not hmac.compare_digest(headers.get(b"authorization", b""), expected):
+            print(f"Rejected unauthenticated request: {scope['method']} {scope['path']}")
+            response = JSONResponse({"error": "unauthorized"}, status_code=401)
+            await response(scope, receive, send)
+            return
+
+        trace_id = headers.get(TRACE_ID_HEADER, b"")
+        proof = hmac.new(PROOF_KEY, trace_id, hashlib.sha256).hexdigest()
+
+        async def send_with_proof(message):
+            if message["type"] == "http.response.start":
+                message["headers"] = list(message.get("headers", [])) + [(PROOF_HEADER, proof.encode("ascii"))]
+            await send(message)
+
+        await self.app(scope, receive, send_with_proof)
+
 # ==================== Starlette App Setup ====================
 
 # Define routes for Unix socket communication
@@ -151,7 +250,7 @@ routes = [
     Route('/health', health_handler, methods=['GET']),
 ]
 
-app = Starlette(routes=routes)
+app = Starlette(routes=routes, middleware=[Middleware(TokenAuthMiddleware), Middleware(TraceIdMiddleware)])
 
 # ==================== Unix Socket Configuration ====================
 
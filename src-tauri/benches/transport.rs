@@ -0,0 +1,162 @@
+//! =============================================================================
+//! Transport Benchmarks
+//! =============================================================================
+//!
+//! Measures the IPC path (connect, HTTP/1.1 over the engine socket, JSON and
+//! NDJSON decoding) against a mock engine served on a Unix socket in the temp
+//! directory, so regressions show up before a release:
+//!
+//!   roundtrip_small        connect + POST of a small JSON body
+//!   roundtrip_1mb          1 MB request body echoed back
+//!   concurrent_100         100 small requests in flight at once
+//!   stream_10k_chunks      one streamed response of 10,000 NDJSON chunks
+//!
+//! Run with `cargo bench --features bench --bench transport` from src-tauri
+//! (Unix only, as the mock listens on a domain socket; elsewhere the bench
+//! does nothing). The mock answers immediately, so the numbers are the
+//! backend's own overhead.
+
+#[cfg(unix)]
+criterion::criterion_main!(ipc::benches);
+
+#[cfg(not(unix))]
+fn main() {
+    println!("The transport benchmarks need a Unix domain socket; skipped on this platform");
+}
+
+#[cfg(unix)]
+mod ipc {
+    use std::convert::Infallible;
+    use std::path::PathBuf;
+
+    use criterion::{criterion_group, Criterion, Throughput};
+    use hyper::{Body, Request, Response};
+    use serde_json::json;
+    use tokio::runtime::Runtime;
+
+    use backend_trial_lib::bench_support;
+
+    /// Payload of the large roundtrip
+    const LARGE_PAYLOAD_BYTES: usize = 1024 * 1024;
+
+    /// Requests in flight in the concurrency benchmark
+    const CONCURRENT_REQUESTS: usize = 100;
+
+    /// Records in the streamed response
+    const STREAM_CHUNKS: usize = 10_000;
+
+    // ==================== Mock Engine ====================
+
+    /// Answer one request: `/stream` streams `chunks` NDJSON records, anything else echoes the JSON body.
+    async fn handle(request: Request<Body>) -> Result<Response<Body>, Infallible> {
+        let trace_id = request.headers().get("x-trace-id").cloned();
+        let path = request.uri().path().to_string();
+        let body = hyper::body::to_bytes(request.into_body()).await.unwrap_or_default();
+
+        let mut response = if path == "/stream" {
+            let chunks = serde_json::from_slice::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|b| b.get("chunks").and_then(|c| c.as_u64()))
+                .unwrap_or(0);
+            let (mut sender, body) = Body::channel();
+            tokio::spawn(async move {
+                for i in 0..chunks {
+                    let line = format!("{{\"token\":\"t{}\"}}\n", i);
+                    if sender.send_data(line.into()).await.is_err() {
+                        return;
+                    }
+                }
+            });
+            Response::builder().header("content-type", "application/x-ndjson").body(body)
+        } else {
+            Response::builder().header("content-type", "application/json").body(Body::from(body))
+        }
+        .expect("valid mock response");
+
+        // Echo the trace id like the engine, so the client doesn't log protocol warnings
+        if let Some(trace_id) = trace_id {
+            response.headers_mut().insert("x-trace-id", trace_id);
+        }
+        Ok(response)
+    }
+
+    /// Serve the mock engine on a fresh socket in the temp directory; returns its path.
+    fn start_mock_engine(runtime: &Runtime) -> String {
+        let path: PathBuf = std::env::temp_dir().join(format!("transport-bench-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = runtime
+            .block_on(async { tokio::net::UnixListener::bind(&path) })
+            .expect("bind mock engine socket");
+
+        runtime.spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else {
+                    continue;
+                };
+                tokio::spawn(async move {
+                    let service = hyper::service::service_fn(handle);
+                    let _ = hyper::server::conn::Http::new().http1_only(true).serve_connection(stream, service).await;
+                });
+            }
+        });
+        path.to_string_lossy().into_owned()
+    }
+
+    // ==================== Benchmarks ====================
+
+    fn transport(c: &mut Criterion) {
+        let runtime = Runtime::new().expect("tokio runtime");
+        // Connection tasks are spawned on tauri's runtime; share the bench's
+        tauri::async_runtime::set(runtime.handle().clone());
+        let socket = start_mock_engine(&runtime);
+
+        let small = json!({ "input": "hello", "request_id": "bench" });
+        let large = json!({ "data": "x".repeat(LARGE_PAYLOAD_BYTES) });
+        let stream = json!({ "chunks": STREAM_CHUNKS });
+
+        let mut group = c.benchmark_group("transport");
+
+        group.bench_function("roundtrip_small", |b| {
+            b.to_async(&runtime).iter(|| async {
+                bench_support::json_roundtrip(&socket, "POST", "/echo", Some(&small)).await.expect("small roundtrip")
+            })
+        });
+
+        group.throughput(Throughput::Bytes(LARGE_PAYLOAD_BYTES as u64));
+        group.bench_function("roundtrip_1mb", |b| {
+            b.to_async(&runtime).iter(|| async {
+                bench_support::json_roundtrip(&socket, "POST", "/echo", Some(&large)).await.expect("1 MB roundtrip")
+            })
+        });
+
+        group.throughput(Throughput::Elements(CONCURRENT_REQUESTS as u64));
+        group.bench_function("concurrent_100", |b| {
+            b.to_async(&runtime).iter(|| async {
+                let requests: Vec<_> = (0..CONCURRENT_REQUESTS)
+                    .map(|_| {
+                        let socket = socket.clone();
+                        let body = small.clone();
+                        tokio::spawn(async move { bench_support::json_roundtrip(&socket, "POST", "/echo", Some(&body)).await })
+                    })
+                    .collect();
+                for request in requests {
+                    request.await.expect("request task").expect("concurrent roundtrip");
+                }
+            })
+        });
+
+        group.throughput(Throughput::Elements(STREAM_CHUNKS as u64));
+        group.sample_size(20);
+        group.bench_function("stream_10k_chunks", |b| {
+            b.to_async(&runtime).iter(|| async {
+                let records = bench_support::read_stream(&socket, "/stream", &stream).await.expect("stream");
+                assert_eq!(records, STREAM_CHUNKS);
+            })
+        });
+
+        group.finish();
+        let _ = std::fs::remove_file(&socket);
+    }
+
+    criterion_group!(benches, transport);
+}
@@ -0,0 +1,338 @@
+//! =============================================================================
+//! Usage Statistics (opt-in)
+//! =============================================================================
+//!
+//! Coarse, aggregate usage numbers for product decisions, without content.
+//! Nothing is collected unless `settings.analytics.enabled` is on. While it
+//! is, the app only counts, locally in analytics.json:
+//!
+//!   active days   days (UTC) the app was used
+//!   features      uses per feature, named by compile-time constants
+//!                 (send_input_to_python, run_pipeline, ...)
+//!
+//! No inputs, responses, file names, model names or identifiers are kept.
+//! Every `upload_interval_hours` the counts are sent as one batch to
+//! `settings.analytics.endpoint` and reset:
+//!
+//!   POST endpoint  { "version": 1, "period_start_day": 20100, "period_end_day": 20106,
+//!                    "active_days": 6, "features": { "send_input_to_python": 41 } }
+//!
+//! Before leaving the device each count gets Laplace noise of scale
+//! 1 / `noise_epsilon` (rounded, at least 0), and features whose noisy count
+//! is below `min_count` are left out. `preview_analytics_payload` returns
+//! the exact payload the next upload sends; it only changes once something
+//! new is counted. Turning analytics off deletes the local counts.
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri::async_runtime::Mutex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::atomic_file;
+use crate::error::EngineError;
+use crate::settings::SettingsStore;
+
+// ==================== Configuration Constants ====================
+
+/// File holding the counts inside the app data directory
+const ANALYTICS_FILE: &str = "analytics.json";
+
+/// Layout version of the uploaded payload
+const PAYLOAD_VERSION: u32 = 1;
+
+/// How often changed counts are saved and a due upload is attempted
+const ANALYTICS_TICK_SECS: u64 = 60;
+
+/// Timeout of an upload
+const UPLOAD_TIMEOUT_SECS: u64 = 30;
+
+const SECS_PER_DAY: u64 = 86_400;
+
+/// Mirrors `settings.analytics.enabled` for the counting hot path
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// The local counts and the payload built from them
+static STATE: std::sync::Mutex<AnalyticsState> = std::sync::Mutex::new(AnalyticsState {
+    counts: Counts { active_days: BTreeSet::new(), features: BTreeMap::new(), last_upload: 0 },
+    dirty: false,
+    payload: None,
+});
+
+// ==================== Settings ====================
+
+/// Usage statistics, persisted under `settings.analytics`; off by default.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(default)]
+pub struct AnalyticsSettings {
+    /// Count usage and upload it; off unless the user opts in
+    pub enabled: bool,
+    /// URL the batches are POSTed to; None keeps the counts local
+    pub endpoint: Option<String>,
+    #[schemars(range(min = 1))]
+    pub upload_interval_hours: u64,
+    /// Privacy budget per count; smaller adds more noise
+    #[schemars(range(min = 0.01))]
+    pub noise_epsilon: f64,
+    /// Features with a (noisy) count below this are not reported
+    pub min_count: u64,
+}
+
+impl Default for AnalyticsSettings {
+    fn default() -> Self {
+        AnalyticsSettings {
+            enabled: false,
+            endpoint: None,
+            upload_interval_hours: 24 * 7,
+            noise_epsilon: 1.0,
+            min_count: 5,
+        }
+    }
+}
+
+// ==================== Types ====================
+
+/// The local counts, as persisted in analytics.json.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Counts {
+    /// Days since the Unix epoch (UTC) the app was used
+    active_days: BTreeSet<u64>,
+    /// Uses per feature
+    features: BTreeMap<String, u64>,
+    /// Unix seconds of the last upload (or of the first count)
+    last_upload: u64,
+}
+
+struct AnalyticsState {
+    counts: Counts,
+    /// Counts changed since they were last saved
+    dirty: bool,
+    /// Noised payload of the current counts, built once so the preview matches the upload
+    payload: Option<AnalyticsPayload>,
+}
+
+/// Exactly what one upload sends.
+#[derive(Debug, Clone, PartialEq, Serialize, Type)]
+pub struct AnalyticsPayload {
+    pub version: u32,
+    /// First and last day (days since the Unix epoch, UTC) the batch covers
+    pub period_start_day: u64,
+    pub period_end_day: u64,
+    pub active_days: u64,
+    pub features: BTreeMap<String, u64>,
+}
+
+/// Response of `preview_analytics_payload`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct AnalyticsPreview {
+    pub enabled: bool,
+    pub endpoint: Option<String>,
+    /// Unix seconds from which the next upload is due
+    pub next_upload_at: Option<u64>,
+    /// None if nothing was counted since the last upload
+    pub payload: Option<AnalyticsPayload>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn lock_state() -> std::sync::MutexGuard<'static, AnalyticsState> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn analytics_file(app: &AppHandle) -> PathBuf {
+    app.path().app_data_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join("ai-engine"))
+        .join(ANALYTICS_FILE)
+}
+
+// ==================== Counting ====================
+
+/// Count one use of `feature` (a no-op unless analytics is enabled).
+///
+/// Takes only `&'static str` so no runtime data can end up as a feature name.
+pub(crate) fn count(feature: &'static str) {
+    if !ENABLED.load(Ordering::SeqCst) {
+        return;
+    }
+    let now = unix_now();
+    let mut state = lock_state();
+    // The first batch also spans a full interval
+    if state.counts.last_upload == 0 {
+        state.counts.last_upload = now;
+    }
+    state.counts.active_days.insert(now / SECS_PER_DAY);
+    *state.counts.features.entry(feature.to_string()).or_insert(0) += 1;
+    state.dirty = true;
+    state.payload = None;
+}
+
+/// Apply analytics settings (on load and whenever settings change).
+///
+/// Turning analytics off deletes the counts (on disk with the next tick).
+pub(crate) fn configure(settings: &AnalyticsSettings) {
+    let was_enabled = ENABLED.swap(settings.enabled, Ordering::SeqCst);
+    let mut state = lock_state();
+    // Thresholds or noise may have changed
+    state.payload = None;
+    if was_enabled && !settings.enabled {
+        println!("Usage statistics turned off, deleting the local counts");
+        state.counts = Counts::default();
+        state.dirty = true;
+    }
+}
+
+// ==================== Payload ====================
+
+/// A sample of Laplace noise with scale `scale`.
+fn laplace(scale: f64) -> f64 {
+    let u = fastrand::f64() - 0.5;
+    -scale * u.signum() * (1.0 - 2.0 * u.abs()).max(f64::MIN_POSITIVE).ln()
+}
+
+/// `count` plus noise, rounded and at least 0.
+fn noisy(count: u64, scale: f64) -> u64 {
+    (count as f64 + laplace(scale)).round().max(0.0) as u64
+}
+
+/// Build the noised, thresholded payload of `counts`.
+fn build_payload(counts: &Counts, settings: &AnalyticsSettings) -> Option<AnalyticsPayload> {
+    let (first, last) = (counts.active_days.first()?, counts.active_days.last()?);
+    let scale = 1.0 / settings.noise_epsilon.max(0.01);
+    let features = counts.features.iter()
+        .map(|(name, count)| (name.clone(), noisy(*count, scale)))
+        .filter(|(_, count)| *count >= settings.min_count)
+        .collect();
+    Some(AnalyticsPayload {
+        version: PAYLOAD_VERSION,
+        period_start_day: *first,
+        period_end_day: *last,
+        active_days: noisy(counts.active_days.len() as u64, scale),
+        features,
+    })
+}
+
+/// The payload of the current counts (built once per set of counts).
+fn payload(settings: &AnalyticsSettings) -> Option<AnalyticsPayload> {
+    let mut state = lock_state();
+    if state.payload.is_none() {
+        state.payload = build_payload(&state.counts, settings);
+    }
+    state.payload.clone()
+}
+
+/// Unix seconds from which the next upload is due.
+fn next_upload_at(settings: &AnalyticsSettings) -> u64 {
+    lock_state().counts.last_upload + settings.upload_interval_hours.max(1) * 3600
+}
+
+// ==================== Upload ====================
+
+/// POST `payload` to `endpoint`.
+async fn upload(endpoint: &str, payload: &AnalyticsPayload) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(UPLOAD_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let response = client.post(endpoint)
+        .json(payload)
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Server answered {}", response.status()));
+    }
+    Ok(())
+}
+
+/// Save changed counts, and upload them if a batch is due.
+async fn tick(app: &AppHandle, path: &Path) {
+    let settings = app.state::<Mutex<SettingsStore>>().lock().await.settings.analytics.clone();
+    let due = settings.enabled && unix_now() >= next_upload_at(&settings);
+    if let (true, Some(endpoint)) = (due, settings.endpoint.as_deref()) {
+        let batch = payload(&settings).map(|payload| (payload, lock_state().counts.clone()));
+        if let Some((payload, uploaded)) = batch {
+            match upload(endpoint, &payload).await {
+                Ok(()) => {
+                    println!("Uploaded usage statistics for days {}-{}", payload.period_start_day, payload.period_end_day);
+                    let mut state = lock_state();
+                    // What was counted during the upload stays for the next batch
+                    let counts = &mut state.counts;
+                    for (feature, count) in &uploaded.features {
+                        if let Some(current) = counts.features.get_mut(feature) {
+                            *current = current.saturating_sub(*count);
+                        }
+                    }
+                    counts.features.retain(|_, count| *count > 0);
+                    counts.active_days.retain(|day| !uploaded.active_days.contains(day));
+                    counts.last_upload = unix_now();
+                    state.payload = None;
+                    state.dirty = true;
+                }
+                Err(e) => println!("Usage statistics upload failed, retrying later: {}", e),
+            }
+        }
+    }
+
+    let counts = {
+        let mut state = lock_state();
+        if !state.dirty {
+            return;
+        }
+        state.dirty = false;
+        state.counts.clone()
+    };
+    if let Err(e) = atomic_file::write_json(path, &counts) {
+        println!("Failed to save usage statistics: {}", e);
+        lock_state().dirty = true;
+    }
+}
+
+/// Load the counts and start saving and uploading them in the background.
+pub fn init(app: &AppHandle) {
+    let path = analytics_file(app);
+    if let Some(counts) = atomic_file::load_json::<Counts>(&path).report(app) {
+        let mut state = lock_state();
+        if ENABLED.load(Ordering::SeqCst) {
+            state.counts = counts;
+        } else if !counts.active_days.is_empty() {
+            // Left from before opting out: overwritten with empty counts on the next tick
+            state.dirty = true;
+        }
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(ANALYTICS_TICK_SECS)).await;
+            tick(&app, &path).await;
+        }
+    });
+}
+
+// ==================== Tauri Commands ====================
+
+/// Return exactly what the next usage statistics upload would send, and where.
+///
+/// The payload is already noised and thresholded; it stays the same until
+/// something new is counted or the analytics settings change.
+#[tauri::command]
+#[specta::specta]
+pub async fn preview_analytics_payload(app: AppHandle) -> Result<AnalyticsPreview, EngineError> {
+    let settings = app.state::<Mutex<SettingsStore>>().lock().await.settings.analytics.clone();
+    let payload = payload(&settings);
+    Ok(AnalyticsPreview {
+        enabled: settings.enabled,
+        endpoint: settings.endpoint.clone(),
+        next_upload_at: (settings.enabled && settings.endpoint.is_some()).then(|| next_upload_at(&settings)),
+        payload,
+    })
+}
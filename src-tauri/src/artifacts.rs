@@ -0,0 +1,189 @@
+//! =============================================================================
+//! Engine Artifacts
+//! =============================================================================
+//!
+//! The engine produces files for the user (exported fine-tunes, generated
+//! reports). It lists them at GET /artifacts:
+//!
+//!   { "artifacts": [ { "id": "a1", "name": "report.pdf",
+//!                      "size_bytes": 48213, "sha256": "9f86d0…" } ] }
+//!
+//! `save_artifact` downloads GET /artifacts/{id}/content into `<dest>.partial`
+//! while emitting `artifact_save_progress`, verifies size and SHA-256, moves
+//! the file into place, and then asks the engine to delete its copy
+//! (POST /artifacts/delete { "id" }). A failed check leaves nothing at `dest`
+//! and keeps the engine-side artifact.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager, State};
+use tauri::async_runtime::Mutex;
+use hyper::body::HttpBody;
+use tokio::io::AsyncWriteExt;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::error::EngineError;
+use crate::events::{self, ArtifactSaveProgress};
+use crate::hooks::{EngineCall, RequestHooks};
+use crate::temp_files::TempFiles;
+use crate::{drain, error_response, get_socket_path, socket_http_send_with_headers, transport, update_activity_impl, PythonProcess};
+
+// ==================== Configuration Constants ====================
+
+/// Minimum time between progress events
+const PROGRESS_INTERVAL_MS: u64 = 200;
+
+// ==================== Types ====================
+
+/// An artifact the engine has ready for the user.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EngineArtifact {
+    pub id: String,
+    pub name: String,
+    pub size_bytes: u64,
+    /// Lowercase hex SHA-256 of the content
+    pub sha256: String,
+}
+
+/// Result of a successful `save_artifact`.
+#[derive(Debug, Serialize, Type)]
+pub struct SavedArtifact {
+    pub id: String,
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    pub sha256: String,
+    /// False if the engine-side copy could not be removed
+    pub cleaned_up: bool,
+}
+
+// ==================== Download ====================
+
+/// Stream GET `endpoint` into `file`, returning (bytes written, SHA-256 hex).
+///
+/// Request hooks run around it, except post_response ones (see hooks).
+async fn download_to_file(app: &AppHandle, artifact: &EngineArtifact, endpoint: &str, file: &mut tokio::fs::File) -> Result<(u64, String), EngineError> {
+    let call = EngineCall {
+        method: "GET".to_string(),
+        endpoint: endpoint.to_string(),
+        headers: Vec::new(),
+        body: None,
+    };
+    let send = |call: EngineCall| async move { write_artifact(app, artifact, &call, file).await };
+    app.state::<RequestHooks>().run_raw(call, send).await
+}
+
+/// The download of `call` behind `download_to_file`.
+async fn write_artifact(app: &AppHandle, artifact: &EngineArtifact, call: &EngineCall, file: &mut tokio::fs::File) -> Result<(u64, String), EngineError> {
+    let response = socket_http_send_with_headers(&get_socket_path(), &call.method, &call.endpoint, None, "application/octet-stream", &call.headers).await?;
+    if !response.status().is_success() {
+        return Err(error_response(response).await);
+    }
+    let mut body = response.into_body();
+
+    let mut hasher = Sha256::new();
+    let mut written: u64 = 0;
+    let mut last_progress = Instant::now();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| EngineError::socket("Failed to read artifact", &e))?;
+        hasher.update(&chunk);
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| format!("Failed to write artifact: {}", e))?;
+        written += chunk.len() as u64;
+
+        if last_progress.elapsed() >= Duration::from_millis(PROGRESS_INTERVAL_MS) {
+            last_progress = Instant::now();
+            events::emit(app, ArtifactSaveProgress {
+                id: artifact.id.clone(),
+                bytes_written: written,
+                total_bytes: artifact.size_bytes,
+            });
+        }
+    }
+    file.flush().await.map_err(|e| format!("Failed to write artifact: {}", e))?;
+
+    let digest: String = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+    Ok((written, digest))
+}
+
+// ==================== Tauri Commands ====================
+
+/// List the artifacts the engine has ready for saving.
+#[tauri::command]
+#[specta::specta]
+pub async fn list_engine_artifacts(app: AppHandle) -> Result<Vec<EngineArtifact>, EngineError> {
+    let response = transport::engine_request(&app, "GET", "/artifacts", None, None).await?;
+    let artifacts = response.get("artifacts").cloned().unwrap_or(serde_json::json!([]));
+    serde_json::from_value(artifacts)
+        .map_err(|e| EngineError::BadResponse(format!("invalid artifact list: {}", e)))
+}
+
+/// Download an artifact to `dest_path`, verify it, and remove the engine's copy.
+///
+/// This command:
+///   1. Looks up the artifact's size and checksum
+///   2. Streams the content to `<dest_path>.partial`, emitting `artifact_save_progress`
+///   3. Verifies size and SHA-256, then renames the file into place
+///   4. Asks the engine to delete its copy (failure is reported, not fatal)
+#[tauri::command]
+#[specta::specta]
+pub async fn save_artifact(app: AppHandle, artifact_id: String, dest_path: PathBuf, state: State<'_, Mutex<PythonProcess>>) -> Result<SavedArtifact, EngineError> {
+    println!("Saving artifact {} to {:?}", artifact_id, dest_path);
+
+    let proc_state = state.lock().await;
+    update_activity_impl(&proc_state.last_activity).await;
+    let _request = drain::begin_request(&proc_state)?;
+    drop(proc_state);
+
+    let artifact = list_engine_artifacts(app.clone())
+        .await?
+        .into_iter()
+        .find(|a| a.id == artifact_id)
+        .ok_or_else(|| EngineError::InvalidRequest(format!("No engine artifact with id {}", artifact_id)))?;
+
+    let mut partial_name = dest_path.clone().into_os_string();
+    partial_name.push(".partial");
+    // Removed by the guard if the download or its verification fails
+    let owner = format!("artifact-{}", artifact.id);
+    let (partial, mut file) = app.state::<TempFiles>().create(&owner, PathBuf::from(partial_name)).await?;
+
+    let endpoint = format!("/artifacts/{}/content", artifact.id);
+    let download = download_to_file(&app, &artifact, &endpoint, &mut file).await;
+    drop(file);
+
+    let verified = download.and_then(|(size, sha256)| {
+        if size != artifact.size_bytes {
+            Err(EngineError::BadResponse(format!("artifact size mismatch: expected {} bytes, got {}", artifact.size_bytes, size)))
+        } else if !sha256.eq_ignore_ascii_case(&artifact.sha256) {
+            Err(EngineError::BadResponse(format!("artifact checksum mismatch: expected {}, got {}", artifact.sha256, sha256)))
+        } else {
+            Ok(sha256)
+        }
+    });
+    let sha256 = verified?;
+    partial.persist(&dest_path).await?;
+    events::emit(&app, ArtifactSaveProgress {
+        id: artifact.id.clone(),
+        bytes_written: artifact.size_bytes,
+        total_bytes: artifact.size_bytes,
+    });
+
+    let cleanup = serde_json::json!({ "id": artifact.id });
+    let cleaned_up = match transport::engine_request(&app, "POST", "/artifacts/delete", Some(&cleanup), None).await {
+        Ok(_) => true,
+        Err(e) => {
+            println!("Failed to delete engine artifact {}: {}", artifact.id, e);
+            false
+        }
+    };
+
+    Ok(SavedArtifact {
+        id: artifact.id,
+        path: dest_path,
+        size_bytes: artifact.size_bytes,
+        sha256,
+        cleaned_up,
+    })
+}
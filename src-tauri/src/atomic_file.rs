@@ -0,0 +1,316 @@
+//! =============================================================================
+//! Atomic, Checksummed State Files
+//! =============================================================================
+//!
+//! Whole-file JSON state (settings, accepted licenses, the engine variant
+//! selection) must survive a crash in the middle of a save. Every save:
+//!
+//!   1. writes the SHA-256 of the new contents to <file>.sha256.next
+//!      (via a synced temporary file and a rename, like the data)
+//!   2. writes <file>.tmp, fsyncs it, renames it over <file> (atomic on one
+//!      filesystem) and fsyncs the dir
+//!   3. renames <file>.sha256.next over <file>.sha256
+//!   4. writes the same contents to the last-good backup, e.g.
+//!      settings.last-good.json, the same way
+//!
+//! On load a file is corrupt if it is empty, doesn't parse, or matches
+//! neither <file>.sha256 nor <file>.sha256.next, so a crash between any two
+//! steps leaves a file that checks out (a missing checksum is accepted, for
+//! files written before checksums existed). A corrupt file is moved aside as <file>.corrupt and
+//! replaced by the last-good backup, or by defaults if the backup is unusable
+//! too; callers report the `Recovery` as `settings_recovered`.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use specta::Type;
+use tauri::AppHandle;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::events::{self, SettingsRecovered};
+
+// ==================== Types ====================
+
+/// What a corrupt file was replaced with.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoverySource {
+    /// The last-good backup
+    Backup,
+    /// No usable backup; defaults
+    Defaults,
+}
+
+/// A corrupt state file and how it was recovered.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct Recovery {
+    pub file: String,
+    /// Why the file was considered corrupt
+    pub reason: String,
+    pub source: RecoverySource,
+    /// Where the corrupt file was moved to
+    pub corrupt_copy: Option<String>,
+}
+
+/// A loaded state file: None if it doesn't exist (or was reset to defaults).
+pub(crate) struct Loaded<T> {
+    pub value: Option<T>,
+    pub recovery: Option<Recovery>,
+}
+
+impl<T> Loaded<T> {
+    /// Emit `settings_recovered` if the file had to be recovered, and return the value.
+    pub(crate) fn report(self, app: &AppHandle) -> Option<T> {
+        if let Some(recovery) = self.recovery {
+            events::emit(app, SettingsRecovered { recovery });
+        }
+        self.value
+    }
+}
+
+// ==================== Paths ====================
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+fn checksum_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".sha256")
+}
+
+/// Checksum of contents being written, until they are in place.
+fn next_checksum_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".sha256.next")
+}
+
+/// Last-good backup of `path`: settings.json → settings.last-good.json.
+pub(crate) fn backup_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    match path.extension() {
+        Some(extension) => path.with_file_name(format!("{}.last-good.{}", stem, extension.to_string_lossy())),
+        None => path.with_file_name(format!("{}.last-good", stem)),
+    }
+}
+
+/// Whether `path` is one of the files kept next to a state file (last-good
+/// backup, checksum, temporary file or corrupt copy) rather than one itself.
+pub(crate) fn is_companion(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    name.contains(".last-good")
+        || [".sha256", ".sha256.next", ".tmp", ".corrupt"].iter().any(|suffix| name.ends_with(suffix))
+}
+
+fn checksum(contents: &[u8]) -> String {
+    Sha256::digest(contents).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// ==================== Writing ====================
+
+/// Make a rename in `dir` durable.
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> std::io::Result<()> {
+    std::fs::File::open(dir)?.sync_all()
+}
+
+/// Make a rename in `dir` durable (NTFS journals renames, nothing to do).
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Rename `from` over `to` and make it durable.
+fn rename_synced(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::rename(from, to)?;
+    match to.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => sync_dir(dir),
+        _ => Ok(()),
+    }
+}
+
+/// Replace `path` with `contents` via a synced temporary file.
+fn replace(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let temp = with_suffix(path, ".tmp");
+    let mut file = std::fs::File::create(&temp)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+    rename_synced(&temp, path)
+}
+
+/// Atomically replace `path` with `contents`, plus its checksum.
+///
+/// The new checksum is in place (as the `.next` one) before the contents are.
+fn write_checked(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let next = next_checksum_path(path);
+    replace(&next, checksum(contents).as_bytes())?;
+    replace(path, contents)?;
+    rename_synced(&next, &checksum_path(path))
+}
+
+/// Atomically write `contents` to `path` and to its last-good backup.
+pub(crate) fn write(path: &Path, contents: &[u8]) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    }
+    write_checked(path, contents)
+        .map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+    let backup = backup_path(path);
+    if let Err(e) = write_checked(&backup, contents) {
+        println!("Failed to back up {:?} to {:?}: {}", path, backup, e);
+    }
+    Ok(())
+}
+
+/// Serialize `value` as pretty JSON and `write` it.
+pub(crate) fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let contents = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {:?}: {}", path, e))?;
+    write(path, contents.as_bytes())
+}
+
+/// Like `write_json`, but leave the last-good backup alone (for contents
+/// not known to be good, e.g. a repaired file).
+pub(crate) fn write_json_unbacked<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let contents = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {:?}: {}", path, e))?;
+    write_checked(path, contents.as_bytes())
+        .map_err(|e| format!("Failed to write {:?}: {}", path, e))
+}
+
+// ==================== Reading ====================
+
+/// Read and parse `path`; Ok(None) if it doesn't exist, Err(reason) if corrupt.
+pub(crate) fn read_checked<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, String> {
+    let contents = match std::fs::read(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("unreadable: {}", e)),
+    };
+    if contents.is_empty() {
+        return Err("file is empty".to_string());
+    }
+    // Either checksum may be the current one if a save was interrupted
+    let expected: Vec<String> = [checksum_path(path), next_checksum_path(path)]
+        .iter()
+        .filter_map(|checksum_file| std::fs::read_to_string(checksum_file).ok())
+        .collect();
+    let actual = checksum(&contents);
+    if !expected.is_empty() && !expected.iter().any(|expected| expected.trim() == actual) {
+        return Err("checksum mismatch".to_string());
+    }
+    serde_json::from_slice(&contents).map(Some).map_err(|e| format!("invalid JSON: {}", e))
+}
+
+/// Move the corrupt `path` aside and restore it from the last-good backup.
+///
+/// Returns the restored value (None: reset to defaults) and the recovery.
+pub(crate) fn recover<T: Serialize + DeserializeOwned>(path: &Path, reason: String) -> (Option<T>, Recovery) {
+    let corrupt = with_suffix(path, ".corrupt");
+    let corrupt_copy = match std::fs::rename(path, &corrupt) {
+        Ok(()) => Some(corrupt.to_string_lossy().into_owned()),
+        Err(_) => None,
+    };
+    let _ = std::fs::remove_file(checksum_path(path));
+    let _ = std::fs::remove_file(next_checksum_path(path));
+
+    let backup = read_checked::<T>(&backup_path(path)).ok().flatten();
+    if let Some(value) = &backup {
+        let restored = serde_json::to_vec_pretty(value)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| write_checked(path, &contents).map_err(|e| e.to_string()));
+        if let Err(e) = restored {
+            println!("Failed to write restored {:?}: {}", path, e);
+        }
+    }
+    let source = if backup.is_some() { RecoverySource::Backup } else { RecoverySource::Defaults };
+    println!("Recovered corrupt {:?} ({}) from {:?}", path, reason, source);
+    let recovery = Recovery {
+        file: path.to_string_lossy().into_owned(),
+        reason,
+        source,
+        corrupt_copy,
+    };
+    (backup, recovery)
+}
+
+/// Copy the good file at `path` to its last-good backup.
+pub(crate) fn refresh_backup(path: &Path) {
+    if let Ok(contents) = std::fs::read(path) {
+        if let Err(e) = write_checked(&backup_path(path), &contents) {
+            println!("Failed to back up {:?}: {}", path, e);
+        }
+    }
+}
+
+/// Load the JSON state file at `path`, recovering it if corrupt.
+///
+/// A good file also refreshes the last-good backup.
+pub(crate) fn load_json<T: Serialize + DeserializeOwned>(path: &Path) -> Loaded<T> {
+    match read_checked::<T>(path) {
+        Ok(Some(value)) => {
+            refresh_backup(path);
+            Loaded { value: Some(value), recovery: None }
+        }
+        Ok(None) => Loaded { value: None, recovery: None },
+        Err(reason) => {
+            let (value, recovery) = recover(path, reason);
+            Loaded { value, recovery: Some(recovery) }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory for one test.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("atomic-file-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn save_interrupted_before_checksum_rename_still_loads() {
+        let path = test_dir("interrupted").join("state.json");
+        write_json(&path, &serde_json::json!({ "version": 1 })).unwrap();
+
+        // Steps 1 and 2 of a save, then a crash before the checksum rename
+        let contents = serde_json::to_vec_pretty(&serde_json::json!({ "version": 2 })).unwrap();
+        replace(&next_checksum_path(&path), checksum(&contents).as_bytes()).unwrap();
+        replace(&path, &contents).unwrap();
+
+        let loaded = read_checked::<serde_json::Value>(&path).unwrap();
+        assert_eq!(loaded, Some(serde_json::json!({ "version": 2 })));
+    }
+
+    #[test]
+    fn contents_matching_neither_checksum_are_corrupt() {
+        let path = test_dir("corrupt").join("state.json");
+        write_json(&path, &serde_json::json!({ "version": 1 })).unwrap();
+        std::fs::write(&path, br#"{ "version": 3 }"#).unwrap();
+
+        assert_eq!(read_checked::<serde_json::Value>(&path), Err("checksum mismatch".to_string()));
+    }
+
+    #[test]
+    fn companions_of_a_saved_file_are_recognized() {
+        let dir = test_dir("companions");
+        let path = dir.join("terse.json");
+        write(&path, b"{}").unwrap();
+        let mut names: Vec<String> = std::fs::read_dir(&dir).unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| !is_companion(path))
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["terse.json"]);
+        assert!(is_companion(&with_suffix(&path, ".tmp")));
+        assert!(is_companion(&with_suffix(&path, ".corrupt")));
+    }
+}
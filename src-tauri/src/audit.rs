@@ -0,0 +1,52 @@
+//! =============================================================================
+//! Audit Trail
+//! =============================================================================
+//!
+//! Security-relevant decisions (e.g. a response blocked by moderation) are
+//! appended to audit.jsonl in the app data directory, one JSON object per
+//! line:
+//!
+//!   { "timestamp_ms": 1718000000000, "event": "moderation_blocked",
+//!     "details": { "request_id": "req-3", "reason": "rule 'no-slurs'" } }
+//!
+//! The app only appends, except for the retention janitor dropping entries
+//! older than `settings.retention.logs_days` (see retention); shipping the
+//! file elsewhere is left to the deployment.
+
+use tauri::{AppHandle, Manager};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Audit log file name inside the app data directory
+const AUDIT_LOG_FILE: &str = "audit.jsonl";
+
+/// Path of the audit log.
+pub(crate) fn log_path(app: &AppHandle) -> PathBuf {
+    app.path().app_data_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join("ai-engine"))
+        .join(AUDIT_LOG_FILE)
+}
+
+/// Append one entry to the audit trail. Failures are logged, never fatal.
+pub(crate) fn record(app: &AppHandle, event: &str, details: serde_json::Value) {
+    let path = log_path(app);
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let line = serde_json::json!({ "timestamp_ms": timestamp_ms, "event": event, "details": details });
+
+    let result = path.parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+        })
+        .and_then(|mut file| writeln!(file, "{}", line));
+    if let Err(e) = result {
+        println!("Failed to write audit entry {}: {}", event, e);
+    }
+}
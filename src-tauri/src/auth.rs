@@ -0,0 +1,443 @@
+//! =============================================================================
+//! Remote Provider Authentication
+//! =============================================================================
+//!
+//! Remote providers are configured in `settings.remote.providers`, each with
+//! one of three auth methods:
+//!
+//!   { "name": "openai", "base_url": "https://api.openai.com/v1",
+//!     "auth": { "type": "api_key" } }
+//!   { "name": "gateway", "base_url": "https://llm.example.com",
+//!     "auth": { "type": "custom_header", "header": "X-Api-Token" } }
+//!   { "name": "hosted", "base_url": "https://ai.example.com",
+//!     "auth": { "type": "oauth_device", "client_id": "…",
+//!               "device_authorization_url": "…", "token_url": "…" } }
+//!
+//! Secrets never touch settings.json: they are kept in the OS keychain
+//! (Keychain / Credential Manager / Secret Service) under the runtime
+//! namespace. API keys and custom header values are stored with
+//! `set_provider_credentials`; OAuth providers go through the device flow
+//! started by `start_provider_login`, which ends with
+//! `provider_login_finished`. Expired OAuth access tokens are refreshed
+//! transparently by `authorize`.
+//!
+//! `test_provider_credentials(provider)` makes one authenticated request to
+//! the provider's `test_path`, so a bad key shows up in settings instead of
+//! on the user's first prompt.
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use specta::Type;
+use tauri::{AppHandle, State};
+use tauri::async_runtime::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::EngineError;
+use crate::events::{self, ProviderLoginFinished};
+use crate::runtime_identity;
+use crate::settings::SettingsStore;
+
+/// Keychain account prefix for provider credentials
+const KEYCHAIN_ACCOUNT_PREFIX: &str = "remote-provider:";
+
+/// Access tokens expiring within this window are refreshed before use
+const TOKEN_REFRESH_MARGIN_SECS: u64 = 60;
+
+/// Device flow polling interval when the provider doesn't specify one
+const DEVICE_POLL_INTERVAL_SECS: u64 = 5;
+
+/// Timeout for credential checks and token requests
+const PROVIDER_REQUEST_TIMEOUT_SECS: u64 = 15;
+
+// ==================== Settings ====================
+
+/// Remote providers the backend can authenticate against.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(default)]
+pub struct RemoteSettings {
+    pub providers: Vec<RemoteProvider>,
+}
+
+/// One remote provider.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, JsonSchema)]
+pub struct RemoteProvider {
+    pub name: String,
+    pub base_url: String,
+    /// Path requested by `test_provider_credentials`
+    #[serde(default = "default_test_path")]
+    pub test_path: String,
+    pub auth: AuthMethod,
+}
+
+fn default_test_path() -> String {
+    "/models".to_string()
+}
+
+/// How requests to a provider are authenticated.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuthMethod {
+    /// `Authorization: Bearer <key>`
+    ApiKey,
+    /// `<header>: <value>`
+    CustomHeader { header: String },
+    /// OAuth 2.0 device authorization grant (RFC 8628)
+    OauthDevice {
+        client_id: String,
+        device_authorization_url: String,
+        token_url: String,
+        #[serde(default)]
+        scope: Option<String>,
+    },
+}
+
+// ==================== Stored Credentials ====================
+
+/// Credential as stored in the keychain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StoredCredential {
+    /// API key or custom header value
+    Secret { value: String },
+    /// OAuth tokens; `expires_at` in seconds since the Unix epoch
+    Oauth {
+        access_token: String,
+        refresh_token: Option<String>,
+        expires_at: Option<u64>,
+    },
+}
+
+/// Token endpoint response (RFC 6749 §5.1 / §5.2).
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    refresh_token: Option<String>,
+    expires_in: Option<u64>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+/// Device authorization response (RFC 8628 §3.2).
+#[derive(Debug, Deserialize)]
+struct DeviceAuthorization {
+    device_code: String,
+    user_code: String,
+    verification_uri: String,
+    verification_uri_complete: Option<String>,
+    expires_in: u64,
+    interval: Option<u64>,
+}
+
+/// What the user needs to complete a device login, returned by `start_provider_login`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct DeviceLogin {
+    pub provider: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    pub verification_uri_complete: Option<String>,
+    pub expires_in: u64,
+}
+
+/// Result of `test_provider_credentials`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct CredentialCheck {
+    pub provider: String,
+    pub valid: bool,
+    /// HTTP status returned by the provider
+    pub status: u16,
+    pub message: String,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn keychain_entry(app: &AppHandle, provider: &str) -> Result<keyring::Entry, EngineError> {
+    let account = format!("{}{}", KEYCHAIN_ACCOUNT_PREFIX, provider);
+    keyring::Entry::new(&runtime_identity::namespace(app), &account)
+        .map_err(|e| EngineError::Internal(format!("Keychain unavailable: {}", e)))
+}
+
+/// Read a provider's credential from the keychain.
+async fn load_credential(app: &AppHandle, provider: &str) -> Result<Option<StoredCredential>, EngineError> {
+    let entry = keychain_entry(app, provider)?;
+    let stored = tauri::async_runtime::spawn_blocking(move || entry.get_password())
+        .await
+        .map_err(|e| EngineError::Internal(format!("Keychain task failed: {}", e)))?;
+    match stored {
+        Ok(json) => serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|e| EngineError::Internal(format!("Corrupt keychain entry for {}: {}", provider, e))),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(EngineError::Internal(format!("Failed to read keychain: {}", e))),
+    }
+}
+
+/// Write a provider's credential to the keychain.
+async fn save_credential(app: &AppHandle, provider: &str, credential: &StoredCredential) -> Result<(), EngineError> {
+    let entry = keychain_entry(app, provider)?;
+    let json = serde_json::to_string(credential)
+        .map_err(|e| EngineError::Internal(format!("Failed to serialize credential: {}", e)))?;
+    tauri::async_runtime::spawn_blocking(move || entry.set_password(&json))
+        .await
+        .map_err(|e| EngineError::Internal(format!("Keychain task failed: {}", e)))?
+        .map_err(|e| EngineError::Internal(format!("Failed to write keychain: {}", e)))
+}
+
+/// Look up a configured provider by name.
+async fn find_provider(store: &Mutex<SettingsStore>, provider: &str) -> Result<RemoteProvider, EngineError> {
+    store.lock().await.settings.remote.providers.iter()
+        .find(|p| p.name == provider)
+        .cloned()
+        .ok_or_else(|| EngineError::InvalidRequest(format!("Unknown remote provider '{}'", provider)))
+}
+
+fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(PROVIDER_REQUEST_TIMEOUT_SECS))
+        .build()
+        .unwrap_or_default()
+}
+
+// ==================== Authorization ====================
+
+/// Header to send with requests to `provider`, refreshing OAuth tokens as needed.
+pub(crate) async fn authorize(app: &AppHandle, provider: &RemoteProvider) -> Result<(String, String), EngineError> {
+    let Some(credential) = load_credential(app, &provider.name).await? else {
+        return Err(EngineError::InvalidRequest(format!("No credentials stored for provider '{}'", provider.name)));
+    };
+
+    match (&provider.auth, credential) {
+        (AuthMethod::ApiKey, StoredCredential::Secret { value }) => {
+            Ok(("Authorization".to_string(), format!("Bearer {}", value)))
+        }
+        (AuthMethod::CustomHeader { header }, StoredCredential::Secret { value }) => {
+            Ok((header.clone(), value))
+        }
+        (AuthMethod::OauthDevice { client_id, token_url, .. }, StoredCredential::Oauth { access_token, refresh_token, expires_at }) => {
+            let expiring = expires_at.is_some_and(|at| at <= now_secs() + TOKEN_REFRESH_MARGIN_SECS);
+            let access_token = match (expiring, refresh_token) {
+                (true, Some(refresh_token)) => {
+                    refresh_access_token(app, &provider.name, client_id, token_url, &refresh_token).await?
+                }
+                (true, None) => {
+                    return Err(EngineError::InvalidRequest(format!(
+                        "Login for provider '{}' has expired; sign in again", provider.name
+                    )));
+                }
+                (false, _) => access_token,
+            };
+            Ok(("Authorization".to_string(), format!("Bearer {}", access_token)))
+        }
+        _ => Err(EngineError::InvalidRequest(format!(
+            "Stored credentials for provider '{}' don't match its auth method; set them again", provider.name
+        ))),
+    }
+}
+
+/// Exchange a refresh token for a new access token and store it.
+async fn refresh_access_token(
+    app: &AppHandle,
+    provider: &str,
+    client_id: &str,
+    token_url: &str,
+    refresh_token: &str,
+) -> Result<String, EngineError> {
+    println!("Refreshing access token for provider {}", provider);
+    let response: TokenResponse = http_client()
+        .post(token_url)
+        .form(&[("grant_type", "refresh_token"), ("refresh_token", refresh_token), ("client_id", client_id)])
+        .send()
+        .await
+        .map_err(|e| EngineError::Internal(format!("Token refresh for {} failed: {}", provider, e)))?
+        .json()
+        .await
+        .map_err(|e| EngineError::BadResponse(format!("Invalid token response from {}: {}", token_url, e)))?;
+
+    let Some(access_token) = response.access_token else {
+        return Err(EngineError::InvalidRequest(format!(
+            "Provider '{}' rejected the refresh token ({}); sign in again",
+            provider,
+            response.error_description.or(response.error).unwrap_or_default()
+        )));
+    };
+    save_credential(app, provider, &StoredCredential::Oauth {
+        access_token: access_token.clone(),
+        // Providers that don't rotate refresh tokens omit them from the response
+        refresh_token: response.refresh_token.or_else(|| Some(refresh_token.to_string())),
+        expires_at: response.expires_in.map(|secs| now_secs() + secs),
+    }).await?;
+    Ok(access_token)
+}
+
+// ==================== Device Flow ====================
+
+/// Poll the token endpoint until the user approves or denies the login, or it expires.
+async fn poll_device_login(
+    app: &AppHandle,
+    provider: &str,
+    client_id: &str,
+    token_url: &str,
+    authorization: &DeviceAuthorization,
+) -> Result<(), String> {
+    let client = http_client();
+    let deadline = now_secs() + authorization.expires_in;
+    let mut interval = authorization.interval.unwrap_or(DEVICE_POLL_INTERVAL_SECS);
+
+    while now_secs() < deadline {
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+        let response: TokenResponse = client
+            .post(token_url)
+            .form(&[
+                ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+                ("device_code", authorization.device_code.as_str()),
+                ("client_id", client_id),
+            ])
+            .send()
+            .await
+            .map_err(|e| format!("Token request failed: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Invalid token response: {}", e))?;
+
+        if let Some(access_token) = response.access_token {
+            return save_credential(app, provider, &StoredCredential::Oauth {
+                access_token,
+                refresh_token: response.refresh_token,
+                expires_at: response.expires_in.map(|secs| now_secs() + secs),
+            }).await.map_err(String::from);
+        }
+        match response.error.as_deref() {
+            Some("authorization_pending") => {}
+            Some("slow_down") => interval += 5,
+            _ => {
+                return Err(response.error_description
+                    .or(response.error)
+                    .unwrap_or_else(|| "Login failed".to_string()));
+            }
+        }
+    }
+    Err("Login code expired".to_string())
+}
+
+// ==================== Tauri Commands ====================
+
+/// Store the API key or custom header value for a provider in the keychain.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_provider_credentials(
+    app: AppHandle,
+    provider: String,
+    secret: String,
+    store: State<'_, Mutex<SettingsStore>>,
+) -> Result<(), EngineError> {
+    let config = find_provider(&store, &provider).await?;
+    if matches!(config.auth, AuthMethod::OauthDevice { .. }) {
+        return Err(EngineError::InvalidRequest(format!(
+            "Provider '{}' uses OAuth; sign in with start_provider_login", provider
+        )));
+    }
+    if secret.trim().is_empty() {
+        return Err(EngineError::InvalidRequest("Credential must not be empty".to_string()));
+    }
+    save_credential(&app, &provider, &StoredCredential::Secret { value: secret.trim().to_string() }).await
+}
+
+/// Remove a provider's credentials from the keychain.
+#[tauri::command]
+#[specta::specta]
+pub async fn clear_provider_credentials(app: AppHandle, provider: String) -> Result<(), EngineError> {
+    let entry = keychain_entry(&app, &provider)?;
+    let result = tauri::async_runtime::spawn_blocking(move || entry.delete_credential())
+        .await
+        .map_err(|e| EngineError::Internal(format!("Keychain task failed: {}", e)))?;
+    match result {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(EngineError::Internal(format!("Failed to delete keychain entry: {}", e))),
+    }
+}
+
+/// Start an OAuth device login; completion is reported as `provider_login_finished`.
+#[tauri::command]
+#[specta::specta]
+pub async fn start_provider_login(
+    app: AppHandle,
+    provider: String,
+    store: State<'_, Mutex<SettingsStore>>,
+) -> Result<DeviceLogin, EngineError> {
+    let config = find_provider(&store, &provider).await?;
+    let AuthMethod::OauthDevice { client_id, device_authorization_url, token_url, scope } = config.auth else {
+        return Err(EngineError::InvalidRequest(format!(
+            "Provider '{}' doesn't use OAuth; store its key with set_provider_credentials", provider
+        )));
+    };
+
+    let mut form = vec![("client_id", client_id.clone())];
+    if let Some(scope) = scope {
+        form.push(("scope", scope));
+    }
+    let authorization: DeviceAuthorization = http_client()
+        .post(&device_authorization_url)
+        .form(&form)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| EngineError::Internal(format!("Device authorization for {} failed: {}", provider, e)))?
+        .json()
+        .await
+        .map_err(|e| EngineError::BadResponse(format!("Invalid device authorization response: {}", e)))?;
+
+    let login = DeviceLogin {
+        provider: provider.clone(),
+        user_code: authorization.user_code.clone(),
+        verification_uri: authorization.verification_uri.clone(),
+        verification_uri_complete: authorization.verification_uri_complete.clone(),
+        expires_in: authorization.expires_in,
+    };
+
+    tauri::async_runtime::spawn(async move {
+        let result = poll_device_login(&app, &provider, &client_id, &token_url, &authorization).await;
+        match &result {
+            Ok(()) => println!("Signed in to provider {}", provider),
+            Err(e) => println!("Sign-in to provider {} failed: {}", provider, e),
+        }
+        events::emit(&app, ProviderLoginFinished { provider, ok: result.is_ok(), error: result.err() });
+    });
+
+    Ok(login)
+}
+
+/// Make one authenticated request to the provider to check its credentials.
+#[tauri::command]
+#[specta::specta]
+pub async fn test_provider_credentials(
+    app: AppHandle,
+    provider: String,
+    store: State<'_, Mutex<SettingsStore>>,
+) -> Result<CredentialCheck, EngineError> {
+    let config = find_provider(&store, &provider).await?;
+    let (header, value) = authorize(&app, &config).await?;
+    let url = format!("{}{}", config.base_url.trim_end_matches('/'), config.test_path);
+
+    let response = http_client()
+        .get(&url)
+        .header(header, value)
+        .send()
+        .await
+        .map_err(|e| EngineError::Internal(format!("Failed to reach {}: {}", url, e)))?;
+    let status = response.status();
+    let message = match status.as_u16() {
+        200..=299 => "Credentials accepted".to_string(),
+        401 | 403 => "Credentials rejected by the provider".to_string(),
+        _ => format!("Unexpected response from {}: {}", url, status),
+    };
+    Ok(CredentialCheck {
+        provider,
+        valid: status.is_success(),
+        status: status.as_u16(),
+        message,
+    })
+}
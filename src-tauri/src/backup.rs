@@ -0,0 +1,503 @@
+//! =============================================================================
+//! Encrypted Backups
+//! =============================================================================
+//!
+//! Backs up what the user would miss after a disk failure or a bad update:
+//! the settings, the conversation history (see history_store) and the
+//! prompt templates and filter rules (see templates). A backup is a gzipped
+//! tar archive
+//!
+//!   manifest.json     { "version": 1, "created_at": 1718000000 }
+//!   settings.json
+//!   history.json      every chat session, session override, answered message
+//!                     and stored input/response
+//!   templates/*.txt, templates/*.json
+//!   rules/*.json
+//!
+//! encrypted with AES-256-GCM and written as
+//! `<directory>/backup-<unix secs>.aebak`:
+//!
+//!   BACKUP_MAGIC | 12-byte nonce | ciphertext + tag
+//!
+//! The key is generated on the first backup and kept in the OS keychain, so
+//! backups can only be restored by the same user (losing the keychain entry
+//! makes them unreadable).
+//!
+//! With `settings.backup.enabled` (off by default, so nothing is written or
+//! put in the keychain until the user opts in), a backup is made whenever
+//! the newest one is older than `interval_hours` (checked every
+//! BACKUP_CHECK_INTERVAL_SECS), and only the newest `keep` backups are kept. `create_backup_now()` backs
+//! up on demand; each attempt emits `backup_created` or `backup_failed`.
+//!
+//! `restore_backup(path)` verifies and unpacks the whole archive before
+//! changing anything, then replaces the settings and the history and writes
+//! the templates and rules back (files not in the backup are left alone).
+//! The history goes into the current storage backend, whichever that is.
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri::async_runtime::Mutex;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::atomic_file;
+use crate::error::EngineError;
+use crate::events::{self, BackupCreated, BackupFailed};
+use crate::feature_flags;
+use crate::history_store::{HistorySnapshot, SharedHistoryStore};
+use crate::runtime_identity;
+use crate::settings::{Settings, SettingsStore};
+use crate::settings_schema;
+use crate::templates::{RULES_DIR, TEMPLATES_DIR};
+
+/// First bytes of every backup file (format version included)
+const BACKUP_MAGIC: &[u8] = b"AIEBAK01";
+
+/// Version of the archive layout, recorded in manifest.json
+const BACKUP_VERSION: u32 = 1;
+
+/// Backup files are named BACKUP_PREFIX<unix secs>BACKUP_EXTENSION
+const BACKUP_PREFIX: &str = "backup-";
+const BACKUP_EXTENSION: &str = ".aebak";
+
+/// Default backup directory inside the app data dir
+const DEFAULT_BACKUP_DIR: &str = "backups";
+
+/// Keychain account holding the backup key
+const KEYCHAIN_ACCOUNT: &str = "backup-key";
+
+/// Delay before the first schedule check, keeping it out of startup
+const BACKUP_FIRST_CHECK_SECS: u64 = 120;
+
+/// Interval between schedule checks
+const BACKUP_CHECK_INTERVAL_SECS: u64 = 15 * 60;
+
+const NONCE_LEN: usize = 12;
+const SECS_PER_HOUR: u64 = 3600;
+
+/// Serializes backups and restores
+static BACKUP_LOCK: Mutex<()> = Mutex::const_new(());
+
+// ==================== Settings ====================
+
+/// Backup schedule, persisted under `settings.backup`.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(default)]
+pub struct BackupSettings {
+    pub enabled: bool,
+    pub interval_hours: u64,
+    /// Destination directory; None uses `backups` in the app data dir
+    pub directory: Option<String>,
+    /// Backups kept; older ones are deleted (0 keeps all)
+    pub keep: usize,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        BackupSettings {
+            enabled: false,
+            interval_hours: 24,
+            directory: None,
+            keep: 7,
+        }
+    }
+}
+
+// ==================== Types ====================
+
+/// What started a backup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupTrigger {
+    Scheduled,
+    Manual,
+}
+
+/// A backup that was written.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct BackupInfo {
+    pub path: PathBuf,
+    /// Unix seconds
+    pub created_at: u64,
+    pub size_bytes: u64,
+}
+
+/// Response of `restore_backup`.
+#[derive(Debug, Serialize, Type)]
+pub struct RestoredBackup {
+    /// When the backup was made (Unix seconds)
+    pub created_at: u64,
+    pub sessions: u64,
+    pub messages: u64,
+    /// Template and rule files written
+    pub files: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    created_at: u64,
+}
+
+/// Everything read from a backup, validated.
+struct BackupContents {
+    manifest: Manifest,
+    settings: Settings,
+    history: HistorySnapshot,
+    /// (path relative to the config dir, contents)
+    files: Vec<(PathBuf, Vec<u8>)>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn config_dir(app: &AppHandle) -> PathBuf {
+    app.path().app_config_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join("ai-engine"))
+}
+
+fn backup_dir(app: &AppHandle, settings: &BackupSettings) -> PathBuf {
+    match &settings.directory {
+        Some(directory) => PathBuf::from(directory),
+        None => app.path().app_data_dir()
+            .unwrap_or_else(|_| std::env::temp_dir().join("ai-engine"))
+            .join(DEFAULT_BACKUP_DIR),
+    }
+}
+
+/// Backups in `dir` with their creation time, oldest first.
+fn list_backups(dir: &Path) -> Vec<(u64, PathBuf)> {
+    let mut backups: Vec<(u64, PathBuf)> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let created_at = name.strip_prefix(BACKUP_PREFIX)?.strip_suffix(BACKUP_EXTENSION)?.parse().ok()?;
+            Some((created_at, entry.path()))
+        })
+        .collect();
+    backups.sort();
+    backups
+}
+
+// ==================== Key ====================
+
+fn keychain_entry(app: &AppHandle) -> Result<keyring::Entry, EngineError> {
+    keyring::Entry::new(&runtime_identity::namespace(app), KEYCHAIN_ACCOUNT)
+        .map_err(|e| EngineError::Internal(format!("Keychain unavailable: {}", e)))
+}
+
+/// The backup key from the keychain; with `create`, a missing key is generated.
+async fn backup_key(app: &AppHandle, create: bool) -> Result<Key<Aes256Gcm>, EngineError> {
+    let entry = keychain_entry(app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        match entry.get_password() {
+            Ok(hex) => {
+                let bytes: Option<Vec<u8>> = (0..hex.len())
+                    .step_by(2)
+                    .map(|i| hex.get(i..i + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok()))
+                    .collect();
+                match bytes {
+                    Some(bytes) if bytes.len() == 32 => Ok(*Key::<Aes256Gcm>::from_slice(&bytes)),
+                    _ => Err(EngineError::Internal("Corrupt backup key in keychain".to_string())),
+                }
+            }
+            Err(keyring::Error::NoEntry) if create => {
+                let key = Aes256Gcm::generate_key(OsRng);
+                let hex: String = key.iter().map(|b| format!("{:02x}", b)).collect();
+                entry.set_password(&hex)
+                    .map_err(|e| EngineError::Internal(format!("Failed to store backup key: {}", e)))?;
+                println!("Generated a new backup key");
+                Ok(key)
+            }
+            Err(keyring::Error::NoEntry) => Err(EngineError::InvalidRequest("No backup key in the keychain; backups from another account or machine can't be restored".to_string())),
+            Err(e) => Err(EngineError::Internal(format!("Failed to read backup key: {}", e))),
+        }
+    })
+    .await
+    .map_err(|e| EngineError::Internal(format!("Keychain task failed: {}", e)))?
+}
+
+// ==================== Archive ====================
+
+/// The files of `dir` with extension `ext`, as (`prefix`/name, contents).
+fn collect_files(dir: &Path, prefix: &str, ext: &str) -> Vec<(PathBuf, Vec<u8>)> {
+    std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|e| e == ext) && !atomic_file::is_companion(path))
+        .filter_map(|path| {
+            let contents = std::fs::read(&path).ok()?;
+            Some((Path::new(prefix).join(path.file_name()?), contents))
+        })
+        .collect()
+}
+
+fn to_json<T: Serialize>(name: &str, value: &T) -> Result<(PathBuf, Vec<u8>), String> {
+    serde_json::to_vec_pretty(value)
+        .map(|data| (PathBuf::from(name), data))
+        .map_err(|e| format!("Failed to serialize {}: {}", name, e))
+}
+
+fn pack(contents: &BackupContents) -> Result<Vec<u8>, String> {
+    let mut entries: Vec<(PathBuf, Vec<u8>)> = vec![
+        to_json("manifest.json", &contents.manifest)?,
+        to_json("settings.json", &settings_schema::versioned(&contents.settings))?,
+        to_json("history.json", &contents.history)?,
+    ];
+    entries.extend(contents.files.iter().cloned());
+
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), flate2::Compression::default()));
+    for (path, data) in &entries {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o600);
+        header.set_mtime(contents.manifest.created_at);
+        header.set_cksum();
+        builder.append_data(&mut header, path, data.as_slice())
+            .map_err(|e| format!("Failed to add {:?} to the backup: {}", path, e))?;
+    }
+    builder.into_inner()
+        .and_then(|gz| gz.finish())
+        .map_err(|e| format!("Failed to compress the backup: {}", e))
+}
+
+/// Check that an archive path is `<TEMPLATES_DIR or RULES_DIR>/<file name>`.
+fn restorable_file(path: &Path) -> bool {
+    let components: Vec<Component> = path.components().collect();
+    match components.as_slice() {
+        [Component::Normal(dir), Component::Normal(_)] => *dir == TEMPLATES_DIR || *dir == RULES_DIR,
+        _ => false,
+    }
+}
+
+fn unpack(archive: &[u8]) -> Result<BackupContents, String> {
+    let mut manifest = None;
+    let mut settings = None;
+    let mut history = None;
+    let mut files = Vec::new();
+
+    let mut archive = tar::Archive::new(GzDecoder::new(archive));
+    let entries = archive.entries().map_err(|e| format!("Unreadable archive: {}", e))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("Unreadable archive entry: {}", e))?;
+        let path = entry.path().map_err(|e| format!("Invalid archive path: {}", e))?.into_owned();
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        let parse_error = |e: serde_json::Error| format!("Invalid {:?} in backup: {}", path, e);
+        match path.to_str() {
+            Some("manifest.json") => manifest = Some(serde_json::from_slice::<Manifest>(&data).map_err(parse_error)?),
+            Some("settings.json") => {
+                let raw = serde_json::from_slice(&data).map_err(parse_error)?;
+                settings = Some(settings_schema::parse(raw).map_err(|e| format!("Invalid {:?} in backup: {}", path, e))?);
+            }
+            Some("history.json") => history = Some(serde_json::from_slice::<HistorySnapshot>(&data).map_err(parse_error)?),
+            _ if restorable_file(&path) => files.push((path, data)),
+            _ => println!("Ignoring unexpected backup entry {:?}", path),
+        }
+    }
+
+    let manifest = manifest.ok_or("Backup has no manifest")?;
+    if manifest.version > BACKUP_VERSION {
+        return Err(format!("Backup format version {} is newer than this app supports ({})", manifest.version, BACKUP_VERSION));
+    }
+    Ok(BackupContents {
+        manifest,
+        settings: settings.ok_or("Backup has no settings")?,
+        history: history.ok_or("Backup has no history")?,
+        files,
+    })
+}
+
+fn encrypt(key: &Key<Aes256Gcm>, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = Aes256Gcm::new(key)
+        .encrypt(&nonce, Payload { msg: plaintext, aad: BACKUP_MAGIC })
+        .map_err(|_| "Failed to encrypt the backup".to_string())?;
+    let mut out = Vec::with_capacity(BACKUP_MAGIC.len() + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(BACKUP_MAGIC);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+fn decrypt(key: &Key<Aes256Gcm>, data: &[u8]) -> Result<Vec<u8>, String> {
+    let rest = data.strip_prefix(BACKUP_MAGIC).ok_or("Not a backup file (or an unsupported format)")?;
+    if rest.len() < NONCE_LEN {
+        return Err("Backup file is truncated".to_string());
+    }
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    Aes256Gcm::new(key)
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: BACKUP_MAGIC })
+        .map_err(|_| "Backup can't be decrypted: it is corrupted or was made with another key".to_string())
+}
+
+// ==================== Backup / Restore ====================
+
+/// Write a backup and prune old ones.
+async fn create_backup(app: &AppHandle) -> Result<BackupInfo, EngineError> {
+    let _busy = BACKUP_LOCK.lock().await;
+    let (settings, backup_settings) = {
+        let store = app.state::<Mutex<SettingsStore>>();
+        let store = store.lock().await;
+        (store.settings.clone(), store.settings.backup.clone())
+    };
+    let history = app.state::<SharedHistoryStore>().lock().await.export()
+        .map_err(|e| EngineError::Internal(format!("Failed to read history: {}", e)))?;
+    let key = backup_key(app, true).await?;
+
+    let created_at = unix_now();
+    let dir = backup_dir(app, &backup_settings);
+    let config_dir = config_dir(app);
+    let path = dir.join(format!("{}{}{}", BACKUP_PREFIX, created_at, BACKUP_EXTENSION));
+    let target = path.clone();
+    let size_bytes = tauri::async_runtime::spawn_blocking(move || -> Result<u64, String> {
+        let mut files = collect_files(&config_dir.join(TEMPLATES_DIR), TEMPLATES_DIR, "txt");
+        files.extend(collect_files(&config_dir.join(TEMPLATES_DIR), TEMPLATES_DIR, "json"));
+        files.extend(collect_files(&config_dir.join(RULES_DIR), RULES_DIR, "json"));
+        let contents = BackupContents {
+            manifest: Manifest { version: BACKUP_VERSION, created_at },
+            settings,
+            history,
+            files,
+        };
+        let encrypted = encrypt(&key, &pack(&contents)?)?;
+
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+        let partial = target.with_extension("aebak.partial");
+        std::fs::write(&partial, &encrypted)
+            .and_then(|_| std::fs::rename(&partial, &target))
+            .map_err(|e| {
+                let _ = std::fs::remove_file(&partial);
+                format!("Failed to write {:?}: {}", target, e)
+            })?;
+
+        // Keep the newest `keep` backups
+        let backups = list_backups(&dir);
+        let excess = backups.len().saturating_sub(backup_settings.keep);
+        if backup_settings.keep > 0 {
+            for (_, old) in backups.into_iter().take(excess) {
+                match std::fs::remove_file(&old) {
+                    Ok(()) => println!("Removed old backup {:?}", old),
+                    Err(e) => println!("Failed to remove old backup {:?}: {}", old, e),
+                }
+            }
+        }
+        Ok(encrypted.len() as u64)
+    })
+    .await
+    .map_err(|e| EngineError::Internal(format!("Backup task failed: {}", e)))??;
+
+    Ok(BackupInfo { path, created_at, size_bytes })
+}
+
+/// Create a backup and report the outcome as an event.
+async fn run_backup(app: &AppHandle, trigger: BackupTrigger) -> Result<BackupInfo, EngineError> {
+    let result = create_backup(app).await;
+    match &result {
+        Ok(info) => {
+            println!("Backup written to {:?} ({} bytes)", info.path, info.size_bytes);
+            events::emit(app, BackupCreated { backup: info.clone(), trigger });
+        }
+        Err(e) => {
+            println!("Backup failed: {}", e);
+            events::emit(app, BackupFailed { error: e.to_string(), trigger });
+        }
+    }
+    result
+}
+
+/// Start the backup schedule.
+pub fn init(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(BACKUP_FIRST_CHECK_SECS)).await;
+        loop {
+            let settings = app.state::<Mutex<SettingsStore>>().lock().await.settings.backup.clone();
+            // A disabled backup feature skips scheduled backups too, not just the commands
+            if settings.enabled && settings.interval_hours > 0 && feature_flags::check("backup").is_ok() {
+                let newest = list_backups(&backup_dir(&app, &settings)).last().map(|(created_at, _)| *created_at);
+                let due = newest.is_none_or(|created_at| unix_now().saturating_sub(created_at) >= settings.interval_hours * SECS_PER_HOUR);
+                if due {
+                    let _ = run_backup(&app, BackupTrigger::Scheduled).await;
+                }
+            }
+            tokio::time::sleep(Duration::from_secs(BACKUP_CHECK_INTERVAL_SECS)).await;
+        }
+    });
+}
+
+// ==================== Tauri Commands ====================
+
+/// Back up settings, history and templates now.
+#[tauri::command]
+#[specta::specta]
+pub async fn create_backup_now(app: AppHandle) -> Result<BackupInfo, EngineError> {
+    run_backup(&app, BackupTrigger::Manual).await
+}
+
+/// Restore settings, history and templates from the backup at `path`.
+///
+/// Nothing is changed unless the whole backup decrypts and parses.
+#[tauri::command]
+#[specta::specta]
+pub async fn restore_backup(app: AppHandle, path: PathBuf) -> Result<RestoredBackup, EngineError> {
+    let _busy = BACKUP_LOCK.lock().await;
+    println!("Restoring backup {:?}", path);
+    let data = tokio::fs::read(&path)
+        .await
+        .map_err(|e| EngineError::InvalidRequest(format!("Cannot read {:?}: {}", path, e)))?;
+    let key = backup_key(&app, false).await?;
+    let contents = tauri::async_runtime::spawn_blocking(move || decrypt(&key, &data).and_then(|archive| unpack(&archive)))
+        .await
+        .map_err(|e| EngineError::Internal(format!("Restore task failed: {}", e)))?
+        .map_err(EngineError::InvalidRequest)?;
+
+    let restored = RestoredBackup {
+        created_at: contents.manifest.created_at,
+        sessions: contents.history.session_models.len() as u64,
+        messages: contents.history.messages.len() as u64,
+        files: contents.files.len() as u64,
+    };
+    app.state::<SharedHistoryStore>().lock().await.import(contents.history)
+        .map_err(|e| EngineError::Internal(format!("Failed to restore history: {}", e)))?;
+
+    let store = app.state::<Mutex<SettingsStore>>();
+    let mut store = store.lock().await;
+    let mut settings = contents.settings;
+    // The history was restored into the backend in use
+    settings.storage = store.settings.storage.clone();
+    store.replace(settings)?;
+    drop(store);
+
+    let config_dir = config_dir(&app);
+    for (relative, data) in &contents.files {
+        let target = config_dir.join(relative);
+        let written = match target.parent() {
+            Some(dir) => tokio::fs::create_dir_all(dir).await,
+            None => Ok(()),
+        };
+        written.and(tokio::fs::write(&target, data).await)
+            .map_err(|e| EngineError::Internal(format!("Failed to restore {:?}: {}", target, e)))?;
+    }
+
+    println!(
+        "Restored backup from {} ({} session(s), {} message(s), {} file(s))",
+        restored.created_at, restored.sessions, restored.messages, restored.files
+    );
+    Ok(restored)
+}
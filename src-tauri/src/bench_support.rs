@@ -0,0 +1,49 @@
+//! =============================================================================
+//! Transport Benchmark Hooks
+//! =============================================================================
+//!
+//! The IPC helpers are private to the crate; benches/transport.rs measures
+//! them through these thin wrappers, against a mock engine it serves on a
+//! Unix socket:
+//!
+//!   json_roundtrip    one request, JSON response (fresh connection each call)
+//!   read_stream       one streamed request, NDJSON records counted as decoded
+//!
+//! Both go through the same code as the commands (correlation headers,
+//! compression, hyper framing, NdjsonDecoder), without timeouts, hooks or
+//! metrics. Only built with the `bench` feature; not part of the app's API.
+
+use hyper::body::HttpBody;
+
+use crate::error::EngineError;
+use crate::ndjson::{Decoded, NdjsonDecoder};
+
+/// Send a request to `endpoint` on `socket_path` and parse the JSON response.
+pub async fn json_roundtrip(
+    socket_path: &str,
+    method: &str,
+    endpoint: &str,
+    body: Option<&serde_json::Value>,
+) -> Result<serde_json::Value, EngineError> {
+    crate::socket_http_json(socket_path, method, endpoint, body, &[]).await
+}
+
+/// POST `body` to a streaming `endpoint` and decode the response; returns the number of records.
+pub async fn read_stream(socket_path: &str, endpoint: &str, body: &serde_json::Value) -> Result<usize, EngineError> {
+    let response = crate::socket_http_send(socket_path, "POST", endpoint, Some(body), "application/x-ndjson").await?;
+    if !response.status().is_success() {
+        return Err(crate::error_response(response).await);
+    }
+    let mut body = response.into_body();
+
+    let mut decoder = NdjsonDecoder::default();
+    let mut records = 0;
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| EngineError::socket("Failed to read from socket", &e))?;
+        records += decoder.push(&chunk).iter().filter(|d| matches!(d, Decoded::Record(_))).count();
+    }
+    if let Some(Decoded::Record(_)) = decoder.finish() {
+        records += 1;
+    }
+    Ok(records)
+}
@@ -0,0 +1,255 @@
+//! =============================================================================
+//! Automation Broker
+//! =============================================================================
+//!
+//! Scripts that talk to the engine socket directly while the app is open
+//! bypass everything the app keeps track of: the engine goes idle under
+//! them, their inputs don't count against the /input limit and they are
+//! missing from metrics and history. With `settings.broker.enabled` the app
+//! instead exposes a secondary socket for such clients, and forwards what
+//! they send the way it forwards the frontend's requests:
+//!
+//!   POST /input         { "input": "...", "request_id": "...", "timeout_ms": 60000,
+//!                         "route": { "session_id": "...", "generation": { ... } } }
+//!                       →  through process_input: activity, drain, input
+//!                          limiter, templates, metrics, history
+//!   <METHOD> <endpoint> →  through call_engine's checks, so only endpoints in
+//!                          settings.proxy.allowed_endpoints are reachable
+//!
+//! Responses are the engine's JSON; failures are the serialized EngineError
+//! with a matching HTTP status. The broker listens on a per-build socket in
+//! `$XDG_RUNTIME_DIR` (else the app data dir), or a named pipe on Windows,
+//! that only the current user can open, or on `settings.broker.endpoint`;
+//! `get_broker_endpoint` reports where. A file at the endpoint that isn't a
+//! stale socket is never removed.
+//! Enabling or moving it takes effect on the next launch.
+
+use hyper::service::service_fn;
+use hyper::{Body, Request, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri::async_runtime::Mutex;
+use tokio::io::{AsyncRead, AsyncWrite};
+use std::convert::Infallible;
+use std::sync::RwLock;
+
+use crate::error::EngineError;
+use crate::session_models::InputRoute;
+use crate::settings::SettingsStore;
+use crate::{feature_flags, ipc, process_input, proxy, runtime_identity};
+
+/// Largest request body accepted from a broker client
+const MAX_BODY_BYTES: usize = 4 * 1024 * 1024;
+
+/// Endpoint the broker listens on, once it does
+static BROKER_ENDPOINT: RwLock<Option<String>> = RwLock::new(None);
+
+// ==================== Settings ====================
+
+/// Secondary socket for external automations, persisted under `settings.broker`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(default)]
+pub struct BrokerSettings {
+    /// Accept external clients; off by default
+    pub enabled: bool,
+    /// Socket path (pipe name on Windows); None uses a per-build default
+    pub endpoint: Option<String>,
+}
+
+// ==================== Types ====================
+
+/// Body of a broker client's POST /input.
+#[derive(Debug, Deserialize)]
+struct BrokerInput {
+    input: String,
+    request_id: Option<String>,
+    timeout_ms: Option<u64>,
+    route: Option<InputRoute>,
+}
+
+// ==================== Requests ====================
+
+/// HTTP status a broker client gets for `error`.
+fn error_status(error: &EngineError) -> StatusCode {
+    match error {
+        EngineError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+        EngineError::FeatureDisabled(_) => StatusCode::FORBIDDEN,
+        EngineError::Http { status, .. } => StatusCode::from_u16(*status).unwrap_or(StatusCode::BAD_GATEWAY),
+        EngineError::Timeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+        EngineError::NotRunning | EngineError::ShuttingDown => StatusCode::SERVICE_UNAVAILABLE,
+        EngineError::Cancelled(_) => StatusCode::CONFLICT,
+        EngineError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        _ => StatusCode::BAD_GATEWAY,
+    }
+}
+
+fn json(status: StatusCode, body: &impl Serialize) -> Response<Body> {
+    let body = serde_json::to_string(body).unwrap_or_default();
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    response.headers_mut().insert(hyper::header::CONTENT_TYPE, hyper::header::HeaderValue::from_static("application/json"));
+    response
+}
+
+/// Read the JSON body of `request` (None if it is empty).
+async fn read_body(request: Request<Body>) -> Result<Option<serde_json::Value>, EngineError> {
+    let declared = request.headers()
+        .get(hyper::header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+    let too_large = || EngineError::InvalidRequest(format!("Request body exceeds {} bytes", MAX_BODY_BYTES));
+    if declared.is_some_and(|len| len > MAX_BODY_BYTES) {
+        return Err(too_large());
+    }
+    let bytes = hyper::body::to_bytes(request.into_body())
+        .await
+        .map_err(|e| EngineError::InvalidRequest(format!("Failed to read request body: {}", e)))?;
+    if bytes.len() > MAX_BODY_BYTES {
+        return Err(too_large());
+    }
+    if bytes.is_empty() {
+        return Ok(None);
+    }
+    serde_json::from_slice(&bytes)
+        .map(Some)
+        .map_err(|e| EngineError::InvalidRequest(format!("Request body is not valid JSON: {}", e)))
+}
+
+/// Forward one broker request the way the frontend's would be.
+async fn forward(app: &AppHandle, request: Request<Body>) -> Result<serde_json::Value, EngineError> {
+    feature_flags::check("broker")?;
+    let method = request.method().to_string();
+    let endpoint = request.uri().path_and_query().map(|p| p.to_string()).unwrap_or_default();
+    let body = read_body(request).await?;
+    if method == "POST" && endpoint == "/input" {
+        let input: BrokerInput = serde_json::from_value(body.unwrap_or_default())
+            .map_err(|e| EngineError::InvalidRequest(format!("Invalid /input body: {}", e)))?;
+        return process_input(app, "broker_input", input.input, input.timeout_ms, None, input.request_id, input.route).await;
+    }
+    proxy::forward(app, &method, &endpoint, body.as_ref(), None, None).await
+}
+
+/// Serve HTTP/1.1 on one accepted connection.
+fn serve_connection<S>(stream: S, app: AppHandle)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    tauri::async_runtime::spawn(async move {
+        let service = service_fn(move |request| {
+            let app = app.clone();
+            async move {
+                let response = match forward(&app, request).await {
+                    Ok(response) => json(StatusCode::OK, &response),
+                    Err(e) => json(error_status(&e), &e),
+                };
+                Ok::<_, Infallible>(response)
+            }
+        });
+        if let Err(e) = hyper::server::conn::Http::new().serve_connection(stream, service).await {
+            println!("Broker connection error: {}", e);
+        }
+    });
+}
+
+// ==================== Listener ====================
+
+/// Default broker endpoint for this build, in `$XDG_RUNTIME_DIR` or the app data dir.
+#[cfg(unix)]
+fn default_endpoint(app: &AppHandle) -> Result<String, String> {
+    use std::os::unix::fs::DirBuilderExt;
+
+    let dir = ipc::default_socket_dir(app)?;
+    // Only this user may create or replace files next to the socket
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(&dir)
+        .map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    Ok(dir.join(format!("{}.broker.sock", runtime_identity::namespace(app))).to_string_lossy().into_owned())
+}
+
+/// Default broker endpoint for this build.
+#[cfg(windows)]
+fn default_endpoint(app: &AppHandle) -> Result<String, String> {
+    Ok(format!(r"\\.\pipe\{}-broker", runtime_identity::namespace(app)))
+}
+
+/// Accept broker clients on `endpoint` until the app exits.
+#[cfg(unix)]
+async fn listen(app: AppHandle, endpoint: String) -> std::io::Result<()> {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+    // A socket someone still accepts on belongs to another instance of this build
+    if std::os::unix::net::UnixStream::connect(&endpoint).is_ok() {
+        return Err(std::io::Error::new(std::io::ErrorKind::AddrInUse, "another instance is listening"));
+    }
+    // Only a stale socket is ours to replace, never a file the endpoint happens to name
+    match std::fs::symlink_metadata(&endpoint) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(&endpoint)?,
+        Ok(_) => return Err(std::io::Error::new(std::io::ErrorKind::AlreadyExists, "path exists and is not a socket")),
+        Err(_) => {}
+    }
+    let listener = tokio::net::UnixListener::bind(&endpoint)?;
+    // Only this user's scripts may use the app's engine
+    std::fs::set_permissions(&endpoint, std::fs::Permissions::from_mode(0o600))?;
+    *BROKER_ENDPOINT.write().unwrap_or_else(|e| e.into_inner()) = Some(endpoint.clone());
+    println!("Broker accepting automation clients at {}", endpoint);
+    loop {
+        let (stream, _) = listener.accept().await?;
+        serve_connection(stream, app.clone());
+    }
+}
+
+/// Accept broker clients on `endpoint` until the app exits.
+///
+/// Pipes get the default security descriptor, under which only the owner
+/// (and administrators) may write to them.
+#[cfg(windows)]
+async fn listen(app: AppHandle, endpoint: String) -> std::io::Result<()> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .reject_remote_clients(true)
+        .create(&endpoint)?;
+    *BROKER_ENDPOINT.write().unwrap_or_else(|e| e.into_inner()) = Some(endpoint.clone());
+    println!("Broker accepting automation clients at {}", endpoint);
+    loop {
+        server.connect().await?;
+        let connected = std::mem::replace(&mut server, ServerOptions::new().reject_remote_clients(true).create(&endpoint)?);
+        serve_connection(connected, app.clone());
+    }
+}
+
+/// Start the broker if `settings.broker.enabled` is on.
+pub fn init(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let settings = app.state::<Mutex<SettingsStore>>().lock().await.settings.broker.clone();
+        if !settings.enabled {
+            return;
+        }
+        let endpoint = match settings.endpoint.map(Ok).unwrap_or_else(|| default_endpoint(&app)) {
+            Ok(endpoint) => endpoint,
+            Err(e) => {
+                println!("Broker not accepting clients: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = listen(app, endpoint.clone()).await {
+            println!("Broker not accepting clients at {}: {}", endpoint, e);
+        }
+        *BROKER_ENDPOINT.write().unwrap_or_else(|e| e.into_inner()) = None;
+    });
+}
+
+// ==================== Tauri Commands ====================
+
+/// Return the socket (pipe) automation clients connect to, or None if the broker is off.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_broker_endpoint() -> Result<Option<String>, EngineError> {
+    Ok(BROKER_ENDPOINT.read().unwrap_or_else(|e| e.into_inner()).clone())
+}
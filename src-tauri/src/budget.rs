@@ -0,0 +1,115 @@
+//! =============================================================================
+//! Command Execution Budgets
+//! =============================================================================
+//!
+//! Some commands (engine start, long requests) can keep an `invoke()` pending
+//! for a long time. Each command class gets an execution budget; once a command exceeds
+//! it, the command keeps running but the frontend receives periodic
+//! `command_slow` events with the elapsed time and the current phase:
+//!
+//!   { "command": "start_python_script", "phase": "waiting_for_socket",
+//!     "elapsed_ms": 12034, "budget_ms": 10000 }
+//!
+//! Timed commands return their result wrapped in a `Timed` envelope that
+//! includes the total duration.
+
+use serde::Serialize;
+use specta::Type;
+use tauri::AppHandle;
+use tauri::async_runtime::{JoinHandle, Mutex};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::events::{self, CommandSlow};
+
+// ==================== Configuration Constants ====================
+
+/// How often `command_slow` is re-emitted once a budget is exceeded
+const SLOW_COMMAND_REPORT_INTERVAL_MS: u64 = 2000;
+
+// ==================== Command Classes ====================
+
+/// Command classes sharing the same execution budget.
+#[derive(Debug, Clone, Copy)]
+pub enum CommandClass {
+    /// Engine start/stop (process spawn, socket wait)
+    Lifecycle,
+    /// Single user request to the engine
+    Interactive,
+}
+
+impl CommandClass {
+    /// Time after which the command is reported as slow.
+    pub fn budget(&self) -> Duration {
+        match self {
+            CommandClass::Lifecycle => Duration::from_secs(10),
+            CommandClass::Interactive => Duration::from_secs(30),
+        }
+    }
+}
+
+/// Response envelope for timed commands.
+#[derive(Debug, Serialize, Type)]
+pub struct Timed<T: Serialize> {
+    pub data: T,
+    pub duration_ms: u64,
+}
+
+// ==================== Command Timer ====================
+
+/// Tracks one command invocation against its budget.
+///
+/// Dropping the timer (e.g. on an early `?` return) stops the slow reports.
+pub struct CommandTimer {
+    started: Instant,
+    phase: Arc<Mutex<&'static str>>,
+    watcher: JoinHandle<()>,
+}
+
+impl CommandTimer {
+    /// Start timing `command` and spawn its budget watcher.
+    pub fn start(app: &AppHandle, command: &'static str, class: CommandClass) -> CommandTimer {
+        let started = Instant::now();
+        let phase = Arc::new(Mutex::new("starting"));
+        let budget = class.budget();
+
+        let app = app.clone();
+        let watcher_phase = phase.clone();
+        let watcher = tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(budget).await;
+            loop {
+                let current_phase = *watcher_phase.lock().await;
+                let elapsed = started.elapsed();
+                println!("Command {} is slow: {} ms in phase '{}'", command, elapsed.as_millis(), current_phase);
+                events::emit(&app, CommandSlow {
+                    command,
+                    phase: current_phase,
+                    elapsed_ms: elapsed.as_millis() as u64,
+                    budget_ms: budget.as_millis() as u64,
+                });
+                tokio::time::sleep(Duration::from_millis(SLOW_COMMAND_REPORT_INTERVAL_MS)).await;
+            }
+        });
+
+        CommandTimer { started, phase, watcher }
+    }
+
+    /// Record the phase the command is currently in.
+    pub async fn phase(&self, phase: &'static str) {
+        *self.phase.lock().await = phase;
+    }
+
+    /// Stop timing and wrap the command result in a `Timed` envelope.
+    pub fn finish<T: Serialize>(self, data: T) -> Timed<T> {
+        Timed {
+            data,
+            duration_ms: self.started.elapsed().as_millis() as u64,
+        }
+    }
+}
+
+impl Drop for CommandTimer {
+    fn drop(&mut self) {
+        self.watcher.abort();
+    }
+}
@@ -0,0 +1,139 @@
+//! =============================================================================
+//! Engine Capability Discovery
+//! =============================================================================
+//!
+//! Once an engine is healthy (spawned, attached to, or a handoff
+//! replacement), the backend asks what it supports:
+//!
+//!   GET /capabilities
+//!   { "protocol_version": 1, "models": ["small", "large"],
+//!     "max_context_tokens": 8192, "streaming": true }
+//!
+//! The answer is cached until the next engine and returned by
+//! `get_engine_capabilities`. An engine whose protocol version is outside
+//! MIN_PROTOCOL_VERSION..=MAX_PROTOCOL_VERSION is never marked Ready: it is
+//! stopped and the start fails with `incompatible_engine`.
+//!
+//! Engines without the endpoint (404) predate it; they are taken to speak
+//! LEGACY_PROTOCOL_VERSION and report `declared: false` with the other
+//! fields unknown.
+//!
+//! `disabled_features` isn't the engine's: it lists the features switched
+//! off in the settings (see feature_flags) when the capabilities are read.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri::async_runtime::Mutex;
+
+use crate::error::EngineError;
+use crate::{feature_flags, socket_http_get, PythonProcess};
+
+/// Oldest engine protocol this backend talks
+const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// Newest engine protocol this backend talks
+const MAX_PROTOCOL_VERSION: u32 = 1;
+
+/// Protocol assumed for engines without /capabilities
+const LEGACY_PROTOCOL_VERSION: u32 = 1;
+
+// ==================== Types ====================
+
+/// /capabilities response body.
+#[derive(Debug, Deserialize)]
+struct DeclaredCapabilities {
+    protocol_version: u32,
+    #[serde(default)]
+    models: Vec<String>,
+    #[serde(default)]
+    max_context_tokens: Option<u64>,
+    #[serde(default)]
+    streaming: Option<bool>,
+}
+
+/// What the running engine supports, as returned by `get_engine_capabilities`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct EngineCapabilities {
+    pub protocol_version: u32,
+    /// Models the engine can load (empty if not declared)
+    pub models: Vec<String>,
+    /// Longest input context in tokens, if declared
+    pub max_context_tokens: Option<u64>,
+    /// Whether /input can stream tokens, if declared
+    pub streaming: Option<bool>,
+    /// False for engines without /capabilities (the protocol version is assumed)
+    pub declared: bool,
+    /// Features switched off in the settings (see feature_flags), as of the call
+    pub disabled_features: Vec<String>,
+}
+
+/// Capabilities of the current engine.
+#[derive(Default)]
+pub struct EngineCapabilitiesState {
+    current: Option<EngineCapabilities>,
+}
+
+// ==================== Handshake ====================
+
+/// Ask the engine at `socket_path` for its capabilities and check its protocol version.
+///
+/// Fails with `EngineError::IncompatibleEngine` for an unsupported protocol.
+pub(crate) async fn fetch(socket_path: &str) -> Result<EngineCapabilities, EngineError> {
+    let capabilities = match socket_http_get(socket_path, "/capabilities").await {
+        Ok(response) => {
+            let declared = serde_json::from_value::<DeclaredCapabilities>(response)
+                .map_err(|e| EngineError::BadResponse(format!("invalid /capabilities response: {}", e)))?;
+            EngineCapabilities {
+                protocol_version: declared.protocol_version,
+                models: declared.models,
+                max_context_tokens: declared.max_context_tokens,
+                streaming: declared.streaming,
+                declared: true,
+                disabled_features: Vec::new(),
+            }
+        }
+        Err(EngineError::Http { status: 404, .. }) => {
+            println!("Engine has no /capabilities, assuming protocol v{}", LEGACY_PROTOCOL_VERSION);
+            EngineCapabilities {
+                protocol_version: LEGACY_PROTOCOL_VERSION,
+                models: Vec::new(),
+                max_context_tokens: None,
+                streaming: None,
+                declared: false,
+                disabled_features: Vec::new(),
+            }
+        }
+        Err(e) => return Err(e),
+    };
+
+    if !(MIN_PROTOCOL_VERSION..=MAX_PROTOCOL_VERSION).contains(&capabilities.protocol_version) {
+        return Err(EngineError::IncompatibleEngine(format!(
+            "it speaks protocol v{}, this app supports v{} to v{}; install matching versions of the app and engine",
+            capabilities.protocol_version, MIN_PROTOCOL_VERSION, MAX_PROTOCOL_VERSION
+        )));
+    }
+    println!("Engine capabilities: {:?}", capabilities);
+    Ok(capabilities)
+}
+
+/// Cache the capabilities of the engine that is now current.
+pub(crate) async fn store(app: &AppHandle, capabilities: EngineCapabilities) {
+    app.state::<Mutex<EngineCapabilitiesState>>().lock().await.current = Some(capabilities);
+}
+
+// ==================== Tauri Command: get_engine_capabilities ====================
+
+/// Return what the running engine supports (models, context size, streaming, protocol).
+#[tauri::command]
+#[specta::specta]
+pub async fn get_engine_capabilities(app: AppHandle) -> Result<EngineCapabilities, EngineError> {
+    let is_running = *app.state::<Mutex<PythonProcess>>().lock().await.is_running.lock().await;
+    if !is_running {
+        return Err(EngineError::NotRunning);
+    }
+    let state = app.state::<Mutex<EngineCapabilitiesState>>();
+    let current = state.lock().await.current.clone();
+    let capabilities = current.ok_or(EngineError::NotRunning)?;
+    Ok(EngineCapabilities { disabled_features: feature_flags::disabled_features(), ..capabilities })
+}
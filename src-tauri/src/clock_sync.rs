@@ -0,0 +1,188 @@
+//! =============================================================================
+//! Engine Clock Synchronization
+//! =============================================================================
+//!
+//! The engine stamps its status (and pushed events) with its own wall clock,
+//! which can drift from the host's, e.g. when the engine runs in a VM or
+//! container or was attached to on another clock. To merge both into one
+//! timeline the backend measures the offset between the clocks:
+//!
+//!   host_ms   = host wall clock at the midpoint of a GET /status round trip
+//!   engine_ms = the response's "timestamp"
+//!   offset_ms = engine_ms - host_ms
+//!
+//! At the handshake HANDSHAKE_SAMPLES probes are taken and the one with the
+//! shortest round trip wins (the least network noise). Afterwards every
+//! status poll refines the estimate, as long as its round trip is within
+//! ACCEPTED_RTT_FACTOR of the best one seen, so slow drift is followed.
+//!
+//! Before status payloads and engine events are emitted, every numeric
+//! "timestamp" or "*_at" field in them is shifted onto the host clock (epoch
+//! seconds stay seconds, epoch milliseconds stay milliseconds).
+//! `get_clock_sync` reports the measured offset for diagnostics.
+
+use serde::Serialize;
+use specta::Type;
+use std::sync::RwLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::error::EngineError;
+use crate::socket_http_get;
+
+/// Endpoint whose "timestamp" is sampled
+const CLOCK_ENDPOINT: &str = "/status";
+
+/// Probes taken at the handshake
+const HANDSHAKE_SAMPLES: usize = 3;
+
+/// Later samples count if their round trip is at most this many times the best
+const ACCEPTED_RTT_FACTOR: f64 = 2.0;
+
+/// Weight of a later sample in the running estimate
+const SMOOTHING: f64 = 0.1;
+
+/// Epoch values above this are milliseconds rather than seconds (year 5138 in seconds)
+const EPOCH_MS_THRESHOLD: f64 = 1e11;
+
+/// Offset estimate for the current engine
+static CLOCK: RwLock<Option<ClockSync>> = RwLock::new(None);
+
+// ==================== Types ====================
+
+/// Measured offset between the engine's clock and the host's.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ClockSync {
+    /// Engine clock minus host clock; positive if the engine is ahead
+    pub offset_ms: f64,
+    /// Shortest round trip seen, which bounds the error of the offset (± half of it)
+    pub best_rtt_ms: f64,
+    /// Samples that went into the estimate
+    pub samples: u32,
+    /// Host time of the last accepted sample (epoch milliseconds)
+    pub updated_at_ms: u64,
+}
+
+// ==================== Measuring ====================
+
+fn host_now_ms() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs_f64() * 1000.0).unwrap_or(0.0)
+}
+
+/// Engine time in a response, in epoch milliseconds.
+fn engine_time_ms(response: &serde_json::Value) -> Option<f64> {
+    let timestamp = response.get("timestamp")?.as_f64()?;
+    Some(if timestamp > EPOCH_MS_THRESHOLD { timestamp } else { timestamp * 1000.0 })
+}
+
+/// Offset and round trip of one sample answered `rtt` after sending.
+fn sample_from(response: &serde_json::Value, rtt: Duration) -> Option<(f64, f64)> {
+    let rtt_ms = rtt.as_secs_f64() * 1000.0;
+    let host_ms = host_now_ms() - rtt_ms / 2.0;
+    Some((engine_time_ms(response)? - host_ms, rtt_ms))
+}
+
+/// Measure the clock offset of the engine at `socket_path`, replacing the previous estimate.
+///
+/// Engines that don't stamp their status leave timestamps untouched.
+pub(crate) async fn measure(socket_path: &str) {
+    let mut best: Option<(f64, f64)> = None;
+    let mut samples = 0;
+    for _ in 0..HANDSHAKE_SAMPLES {
+        let sent = Instant::now();
+        let Ok(response) = socket_http_get(socket_path, CLOCK_ENDPOINT).await else {
+            break;
+        };
+        let Some((offset_ms, rtt_ms)) = sample_from(&response, sent.elapsed()) else {
+            break;
+        };
+        samples += 1;
+        if best.is_none_or(|(_, best_rtt)| rtt_ms < best_rtt) {
+            best = Some((offset_ms, rtt_ms));
+        }
+    }
+
+    let sync = best.map(|(offset_ms, best_rtt_ms)| ClockSync {
+        offset_ms,
+        best_rtt_ms,
+        samples,
+        updated_at_ms: host_now_ms() as u64,
+    });
+    match &sync {
+        Some(sync) => println!("Engine clock offset: {:+.1} ms (±{:.1} ms)", sync.offset_ms, sync.best_rtt_ms / 2.0),
+        None => println!("Engine clock offset unknown: {} has no timestamp", CLOCK_ENDPOINT),
+    }
+    *CLOCK.write().unwrap_or_else(|e| e.into_inner()) = sync;
+}
+
+/// Refine the estimate from a status poll answered `rtt` after sending.
+pub(crate) fn observe(response: &serde_json::Value, rtt: Duration) {
+    let Some((offset_ms, rtt_ms)) = sample_from(response, rtt) else {
+        return;
+    };
+    let mut clock = CLOCK.write().unwrap_or_else(|e| e.into_inner());
+    let Some(sync) = clock.as_mut() else {
+        return;
+    };
+    if rtt_ms > sync.best_rtt_ms * ACCEPTED_RTT_FACTOR {
+        return;
+    }
+    sync.offset_ms += (offset_ms - sync.offset_ms) * SMOOTHING;
+    sync.best_rtt_ms = sync.best_rtt_ms.min(rtt_ms);
+    sync.samples = sync.samples.saturating_add(1);
+    sync.updated_at_ms = host_now_ms() as u64;
+}
+
+// ==================== Normalizing ====================
+
+/// Whether `key` names an engine timestamp.
+fn is_timestamp_key(key: &str) -> bool {
+    key == "timestamp" || key.ends_with("_at")
+}
+
+fn shift(value: &mut serde_json::Value, offset_ms: f64) {
+    let Some(timestamp) = value.as_f64() else {
+        return;
+    };
+    let shifted = if timestamp > EPOCH_MS_THRESHOLD {
+        serde_json::json!((timestamp - offset_ms).round() as i64)
+    } else {
+        serde_json::json!(timestamp - offset_ms / 1000.0)
+    };
+    *value = shifted;
+}
+
+fn shift_all(value: &mut serde_json::Value, offset_ms: f64) {
+    match value {
+        serde_json::Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                if is_timestamp_key(key) && field.is_number() {
+                    shift(field, offset_ms);
+                } else {
+                    shift_all(field, offset_ms);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(|item| shift_all(item, offset_ms)),
+        _ => {}
+    }
+}
+
+/// Shift every engine timestamp in `value` onto the host clock.
+pub(crate) fn normalize(value: &mut serde_json::Value) {
+    let offset_ms = match CLOCK.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        Some(sync) => sync.offset_ms,
+        None => return,
+    };
+    shift_all(value, offset_ms);
+}
+
+// ==================== Tauri Command: get_clock_sync ====================
+
+/// Return the measured offset between the engine's clock and the host's.
+///
+/// Fails with `not_running` until an engine with timestamped status has been measured.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_clock_sync() -> Result<ClockSync, EngineError> {
+    CLOCK.read().unwrap_or_else(|e| e.into_inner()).clone().ok_or(EngineError::NotRunning)
+}
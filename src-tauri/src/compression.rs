@@ -0,0 +1,107 @@
+//! =============================================================================
+//! Request Body Compression
+//! =============================================================================
+//!
+//! Large request bodies (chunked documents for ingestion) are gzip-compressed
+//! before they go over the socket, when:
+//!
+//!   • `settings.compression.enabled` is on
+//!   • the JSON body is at least `settings.compression.threshold_bytes`
+//!   • the engine advertised gzip support during startup negotiation: its
+//!     GET /health response carries `Accept-Encoding: gzip`
+//!
+//! Compressed requests are sent with `Content-Encoding: gzip`. Byte counts
+//! before and after compression are accumulated here and folded into the
+//! metrics history on each flush (see `take_totals`).
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use specta::Type;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+
+use crate::socket_http_send;
+use crate::transport;
+
+// ==================== Settings ====================
+
+/// Compression settings, persisted under `settings.compression`.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(default)]
+pub struct CompressionSettings {
+    pub enabled: bool,
+    /// Smallest body (in bytes) worth compressing
+    pub threshold_bytes: usize,
+}
+
+impl Default for CompressionSettings {
+    fn default() -> Self {
+        CompressionSettings {
+            enabled: true,
+            threshold_bytes: 64 * 1024,
+        }
+    }
+}
+
+// ==================== State ====================
+
+/// Whether the running engine accepts gzip request bodies
+static ENGINE_ACCEPTS_GZIP: AtomicBool = AtomicBool::new(false);
+
+/// Active threshold; usize::MAX disables compression
+static THRESHOLD_BYTES: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// Bytes of compressed bodies before / after compression since the last take
+static BYTES_UNCOMPRESSED: AtomicU64 = AtomicU64::new(0);
+static BYTES_COMPRESSED: AtomicU64 = AtomicU64::new(0);
+
+/// Apply compression settings (on load and whenever settings change).
+pub(crate) fn configure(settings: &CompressionSettings) {
+    let threshold = if settings.enabled { settings.threshold_bytes } else { usize::MAX };
+    THRESHOLD_BYTES.store(threshold, Ordering::SeqCst);
+}
+
+/// Ask the freshly started engine which request encodings it accepts.
+pub(crate) async fn negotiate(socket_path: &str) {
+    let probe = socket_http_send(socket_path, "GET", "/health", None, "application/json");
+    let accepts_gzip = match transport::with_timeout("/health", transport::timeout_for("/health"), probe).await {
+        Ok(response) => response
+            .headers()
+            .get_all(hyper::header::ACCEPT_ENCODING)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .any(|v| v.split(',').any(|enc| enc.trim().eq_ignore_ascii_case("gzip"))),
+        Err(_) => false,
+    };
+    ENGINE_ACCEPTS_GZIP.store(accepts_gzip, Ordering::SeqCst);
+    println!("Request compression: {}", if accepts_gzip { "gzip" } else { "off (not supported by engine)" });
+}
+
+// ==================== Encoding ====================
+
+/// Compress `body` if it qualifies; returns the bytes to send and their Content-Encoding.
+pub(crate) fn encode(body: Vec<u8>) -> (Vec<u8>, Option<&'static str>) {
+    if !ENGINE_ACCEPTS_GZIP.load(Ordering::SeqCst) || body.len() < THRESHOLD_BYTES.load(Ordering::SeqCst) {
+        return (body, None);
+    }
+
+    let mut encoder = GzEncoder::new(Vec::with_capacity(body.len() / 4), Compression::fast());
+    match encoder.write_all(&body).and_then(|_| encoder.finish()) {
+        Ok(compressed) => {
+            BYTES_UNCOMPRESSED.fetch_add(body.len() as u64, Ordering::SeqCst);
+            BYTES_COMPRESSED.fetch_add(compressed.len() as u64, Ordering::SeqCst);
+            (compressed, Some("gzip"))
+        }
+        Err(e) => {
+            println!("Failed to compress request body, sending uncompressed: {}", e);
+            (body, None)
+        }
+    }
+}
+
+/// Take and reset the (uncompressed, compressed) byte totals.
+pub(crate) fn take_totals() -> (u64, u64) {
+    (BYTES_UNCOMPRESSED.swap(0, Ordering::SeqCst), BYTES_COMPRESSED.swap(0, Ordering::SeqCst))
+}
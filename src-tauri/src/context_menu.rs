@@ -0,0 +1,564 @@
+//! =============================================================================
+//! "Ask AI about selection" OS Integration
+//! =============================================================================
+//!
+//! Optionally registers an entry in the OS context menu that sends text to
+//! the engine without switching to the app first:
+//!
+//!   • macOS   - a Services Quick Action "Ask AI about selection" in
+//!               ~/Library/Services, offered for any selected text
+//!   • Windows - an "Ask AI about this file" entry on files in Explorer
+//!               (HKCU, no admin rights); Windows has no context menu for
+//!               selected text, so the file's text is used instead
+//!
+//! Both run this app's binary with the text:
+//!
+//!   <app> --ask-selection <text>     (`-` reads the text from stdin)
+//!   <app> --ask-file <path>
+//!
+//! If an instance is already running the text is forwarded to it over a
+//! per-user local socket (pipe on Windows) and the new process exits;
+//! otherwise the app starts normally and asks once it is set up. Either way
+//! the quick-prompt pathway starts the engine if needed, brings the main
+//! window to the front, emits `selection_received`, then `selection_answered`
+//! with the response and flags the window for attention.
+//!
+//! Nothing is registered until `install_context_menu` is called;
+//! `uninstall_context_menu` removes the entry again.
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Manager, UserAttentionType};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::error::EngineError;
+use crate::events::{self, SelectionAnswered, SelectionReceived};
+use crate::{jobs, process_input, runtime_identity};
+
+/// Launch argument carrying selected text (`-` reads it from stdin)
+const ASK_SELECTION_ARG: &str = "--ask-selection";
+
+/// Launch argument carrying a file whose text is asked about
+const ASK_FILE_ARG: &str = "--ask-file";
+
+/// Longer selections are cut to this many bytes
+const MAX_SELECTION_BYTES: usize = 32 * 1024;
+
+/// Put in front of the selection sent to the engine
+const SELECTION_PROMPT: &str = "Help me with the following text:";
+
+/// Label of the Services menu entry
+#[cfg(target_os = "macos")]
+const MENU_TITLE: &str = "Ask AI about selection";
+
+/// Window brought to the front with the answer
+const MAIN_WINDOW: &str = "main";
+
+static NEXT_SELECTION_ID: AtomicU64 = AtomicU64::new(1);
+
+// ==================== Types ====================
+
+/// Whether the context-menu entry is registered, as returned by the commands.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ContextMenuStatus {
+    /// This OS has a context-menu integration (macOS, Windows)
+    pub supported: bool,
+    pub installed: bool,
+    /// Quick Action bundle or registry key of the entry
+    pub location: Option<String>,
+}
+
+// ==================== Launch Arguments ====================
+
+/// Cut `text` to MAX_SELECTION_BYTES on a character boundary.
+fn truncate(mut text: String) -> String {
+    if text.len() > MAX_SELECTION_BYTES {
+        let mut end = MAX_SELECTION_BYTES;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+    text
+}
+
+/// Read at most MAX_SELECTION_BYTES of text from `source`.
+fn read_bounded(source: impl Read) -> std::io::Result<String> {
+    let mut bytes = Vec::new();
+    source.take(MAX_SELECTION_BYTES as u64).read_to_end(&mut bytes)?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Text passed with `--ask-selection` or `--ask-file`, if the app was launched from the menu.
+pub(crate) fn selection_from_args() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let text = match arg.as_str() {
+            ASK_SELECTION_ARG => match args.next().as_deref() {
+                None | Some("-") => read_bounded(std::io::stdin().lock()),
+                Some(text) => Ok(text.to_string()),
+            },
+            ASK_FILE_ARG => match args.next() {
+                Some(path) => std::fs::File::open(&path).and_then(read_bounded),
+                None => continue,
+            },
+            _ => continue,
+        };
+        return match text {
+            Ok(text) if !text.trim().is_empty() => Some(truncate(text)),
+            Ok(_) => None,
+            Err(e) => {
+                println!("Failed to read the selection to ask about: {}", e);
+                None
+            }
+        };
+    }
+    None
+}
+
+// ==================== Forwarding to the Running Instance ====================
+
+/// Per-user endpoint the running instance receives selections on.
+#[cfg(unix)]
+fn endpoint(namespace: &str) -> String {
+    let dir = match std::env::var_os("XDG_RUNTIME_DIR").map(std::path::PathBuf::from) {
+        Some(dir) if dir.is_dir() => dir,
+        _ => std::env::temp_dir(),
+    };
+    let user = std::env::var("USER").unwrap_or_else(|_| "default".to_string());
+    dir.join(format!("{}-{}.ask.sock", namespace, user)).to_string_lossy().into_owned()
+}
+
+/// Per-user endpoint the running instance receives selections on.
+#[cfg(windows)]
+fn endpoint(namespace: &str) -> String {
+    let user = std::env::var("USERNAME").unwrap_or_else(|_| "default".to_string());
+    format!(r"\\.\pipe\{}-{}-ask", namespace, user)
+}
+
+/// Hand `text` to an already running instance; false if there is none.
+///
+/// Runs before the Tauri runtime exists, so it uses blocking std I/O.
+#[cfg(unix)]
+pub(crate) fn forward(identifier: &str, text: &str) -> bool {
+    use std::os::unix::net::UnixStream;
+
+    let path = endpoint(&runtime_identity::namespace_for(identifier));
+    let Ok(mut stream) = UnixStream::connect(&path) else {
+        return false;
+    };
+    let sent = stream.write_all(text.as_bytes()).is_ok();
+    if sent {
+        println!("Selection forwarded to the running instance");
+    }
+    sent
+}
+
+/// Hand `text` to an already running instance; false if there is none.
+///
+/// Runs before the Tauri runtime exists, so it uses blocking std I/O.
+#[cfg(windows)]
+pub(crate) fn forward(identifier: &str, text: &str) -> bool {
+    let path = endpoint(&runtime_identity::namespace_for(identifier));
+    let Ok(mut pipe) = std::fs::OpenOptions::new().write(true).open(&path) else {
+        return false;
+    };
+    let sent = pipe.write_all(text.as_bytes()).is_ok();
+    if sent {
+        println!("Selection forwarded to the running instance");
+    }
+    sent
+}
+
+/// Read one forwarded selection and ask about it.
+async fn receive(app: AppHandle, stream: impl AsyncRead + Unpin) {
+    let mut bytes = Vec::new();
+    if let Err(e) = stream.take(MAX_SELECTION_BYTES as u64).read_to_end(&mut bytes).await {
+        println!("Failed to read a forwarded selection: {}", e);
+        return;
+    }
+    let text = String::from_utf8_lossy(&bytes).into_owned();
+    if !text.trim().is_empty() {
+        ask(&app, text).await;
+    }
+}
+
+/// Accept selections forwarded by later launches until the app exits.
+#[cfg(unix)]
+async fn listen(app: AppHandle, path: String) -> std::io::Result<()> {
+    // A socket someone still accepts on belongs to another instance of this build
+    if std::os::unix::net::UnixStream::connect(&path).is_ok() {
+        println!("Another instance receives context-menu selections at {}", path);
+        return Ok(());
+    }
+    let _ = std::fs::remove_file(&path);
+    let listener = tokio::net::UnixListener::bind(&path)?;
+    loop {
+        let (stream, _) = listener.accept().await?;
+        tauri::async_runtime::spawn(receive(app.clone(), stream));
+    }
+}
+
+/// Accept selections forwarded by later launches until the app exits.
+#[cfg(windows)]
+async fn listen(app: AppHandle, path: String) -> std::io::Result<()> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let mut server = ServerOptions::new().first_pipe_instance(true).create(&path)?;
+    loop {
+        server.connect().await?;
+        let connected = server;
+        server = ServerOptions::new().create(&path)?;
+        tauri::async_runtime::spawn(receive(app.clone(), connected));
+    }
+}
+
+/// Listen for forwarded selections, and ask about `selection` from this launch's arguments.
+pub(crate) fn init(app: &AppHandle, selection: Option<String>) {
+    let path = endpoint(&runtime_identity::namespace(app));
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = listen(handle, path.clone()).await {
+            println!("Not receiving context-menu selections at {}: {}", path, e);
+        }
+    });
+
+    if let Some(text) = selection {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move { ask(&app, text).await });
+    }
+}
+
+// ==================== Quick Prompt ====================
+
+/// Show the main window and bring it to the front.
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Ask the engine about `selection` and surface the answer in the main window.
+async fn ask(app: &AppHandle, selection: String) {
+    let request_id = format!("selection-{}", NEXT_SELECTION_ID.fetch_add(1, Ordering::SeqCst));
+    println!("Asking about a {}-byte selection ({})", selection.len(), request_id);
+    show_main_window(app);
+    events::emit(app, SelectionReceived { request_id: request_id.clone(), selection: selection.clone() });
+
+    let prompt = format!("{}\n\n{}", SELECTION_PROMPT, selection);
+    let input = process_input(app, "ask_about_selection", prompt, None, None, Some(request_id.clone()), None);
+    let (response, error) = match jobs::interactive(app, input).await {
+        Ok(response) => (Some(response), None),
+        Err(e) => {
+            println!("Asking about selection failed: {}", e);
+            (None, Some(e.to_string()))
+        }
+    };
+    events::emit(app, SelectionAnswered { request_id, response, error });
+
+    // The user may have moved on while the engine answered
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+        if !window.is_focused().unwrap_or(false) {
+            let _ = window.request_user_attention(Some(UserAttentionType::Informational));
+        }
+    }
+}
+
+// ==================== Registration ====================
+
+/// Quick Action bundle in the user's Services folder.
+#[cfg(target_os = "macos")]
+fn location(app: &AppHandle) -> Result<std::path::PathBuf, EngineError> {
+    let home = app.path().home_dir()
+        .map_err(|e| EngineError::Internal(format!("Failed to resolve home dir: {}", e)))?;
+    Ok(home.join("Library/Services").join(format!("{} ({}).workflow", MENU_TITLE, runtime_identity::namespace(app))))
+}
+
+/// Registry key of the Explorer entry.
+#[cfg(windows)]
+fn location(app: &AppHandle) -> Result<String, EngineError> {
+    Ok(format!(r"HKCU\Software\Classes\*\shell\{}", runtime_identity::namespace(app)))
+}
+
+/// Escape `text` for a plist string.
+#[cfg(target_os = "macos")]
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Info.plist of the Quick Action: a Services entry for selected text.
+#[cfg(target_os = "macos")]
+fn workflow_info_plist() -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>NSServices</key>
+	<array>
+		<dict>
+			<key>NSMenuItem</key>
+			<dict>
+				<key>default</key>
+				<string>{}</string>
+			</dict>
+			<key>NSMessage</key>
+			<string>runWorkflowAsService</string>
+			<key>NSSendTypes</key>
+			<array>
+				<string>public.utf8-plain-text</string>
+			</array>
+		</dict>
+	</array>
+</dict>
+</plist>
+"#,
+        xml_escape(MENU_TITLE)
+    )
+}
+
+/// document.wflow of the Quick Action: one "Run Shell Script" action passing
+/// the selection to `<exe> --ask-selection` in the background (a background
+/// job of `sh` doesn't get the script's stdin, hence the argument).
+#[cfg(target_os = "macos")]
+fn workflow_document(exe: &str) -> String {
+    let script = format!("text=$(cat); '{}' {} \"$text\" >/dev/null 2>&1 &", exe.replace('\'', r"'\''"), ASK_SELECTION_ARG);
+    let command = xml_escape(&script);
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>AMApplicationBuild</key>
+	<string>523</string>
+	<key>AMApplicationVersion</key>
+	<string>2.10</string>
+	<key>AMDocumentVersion</key>
+	<string>2</string>
+	<key>actions</key>
+	<array>
+		<dict>
+			<key>action</key>
+			<dict>
+				<key>AMAccepts</key>
+				<dict>
+					<key>Container</key>
+					<string>List</string>
+					<key>Optional</key>
+					<true/>
+					<key>Types</key>
+					<array>
+						<string>com.apple.cocoa.string</string>
+					</array>
+				</dict>
+				<key>AMActionVersion</key>
+				<string>2.0.3</string>
+				<key>AMApplication</key>
+				<array>
+					<string>Automator</string>
+				</array>
+				<key>AMProvides</key>
+				<dict>
+					<key>Container</key>
+					<string>List</string>
+					<key>Types</key>
+					<array>
+						<string>com.apple.cocoa.string</string>
+					</array>
+				</dict>
+				<key>ActionBundlePath</key>
+				<string>/System/Library/Automator/Run Shell Script.action</string>
+				<key>ActionName</key>
+				<string>Run Shell Script</string>
+				<key>ActionParameters</key>
+				<dict>
+					<key>COMMAND_STRING</key>
+					<string>{command}</string>
+					<key>CheckedForUserDefaultShell</key>
+					<true/>
+					<key>inputMethod</key>
+					<integer>0</integer>
+					<key>shell</key>
+					<string>/bin/sh</string>
+					<key>source</key>
+					<string></string>
+				</dict>
+				<key>BundleIdentifier</key>
+				<string>com.apple.RunShellScript</string>
+				<key>CFBundleVersion</key>
+				<string>2.0.3</string>
+				<key>CanShowSelectedItemsWhenRun</key>
+				<false/>
+				<key>CanShowWhenRun</key>
+				<true/>
+				<key>Class Name</key>
+				<string>RunShellScriptAction</string>
+				<key>InputUUID</key>
+				<string>6E8B1C4A-2F0D-4A53-9C1E-2B7D9F3A5C01</string>
+				<key>OutputUUID</key>
+				<string>6E8B1C4A-2F0D-4A53-9C1E-2B7D9F3A5C02</string>
+				<key>UUID</key>
+				<string>6E8B1C4A-2F0D-4A53-9C1E-2B7D9F3A5C03</string>
+			</dict>
+		</dict>
+	</array>
+	<key>connectors</key>
+	<dict/>
+	<key>workflowMetaData</key>
+	<dict>
+		<key>serviceInputTypeIdentifier</key>
+		<string>com.apple.Automator.text</string>
+		<key>serviceOutputTypeIdentifier</key>
+		<string>com.apple.Automator.nothing</string>
+		<key>serviceProcessesInput</key>
+		<integer>0</integer>
+		<key>workflowTypeIdentifier</key>
+		<string>com.apple.Automator.servicesMenu</string>
+	</dict>
+</dict>
+</plist>
+"#
+    )
+}
+
+/// Run `reg.exe` with `args`; Err carries its stderr.
+#[cfg(windows)]
+fn reg(args: &[&str]) -> Result<(), String> {
+    let output = std::process::Command::new("reg")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run reg.exe: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn status(app: &AppHandle) -> Result<ContextMenuStatus, EngineError> {
+    let location = location(app)?;
+    Ok(ContextMenuStatus {
+        supported: true,
+        installed: location.join("Contents/document.wflow").is_file(),
+        location: Some(location.to_string_lossy().into_owned()),
+    })
+}
+
+#[cfg(windows)]
+fn status(app: &AppHandle) -> Result<ContextMenuStatus, EngineError> {
+    let location = location(app)?;
+    Ok(ContextMenuStatus {
+        supported: true,
+        installed: reg(&["query", &format!(r"{}\command", location)]).is_ok(),
+        location: Some(location),
+    })
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn status(_app: &AppHandle) -> Result<ContextMenuStatus, EngineError> {
+    Ok(ContextMenuStatus { supported: false, installed: false, location: None })
+}
+
+#[cfg(target_os = "macos")]
+fn install(app: &AppHandle, exe: &str) -> Result<(), EngineError> {
+    let contents = location(app)?.join("Contents");
+    std::fs::create_dir_all(&contents)
+        .map_err(|e| EngineError::Internal(format!("Failed to create {:?}: {}", contents, e)))?;
+    for (name, text) in [("Info.plist", workflow_info_plist()), ("document.wflow", workflow_document(exe))] {
+        std::fs::write(contents.join(name), text)
+            .map_err(|e| EngineError::Internal(format!("Failed to write {}: {}", name, e)))?;
+    }
+    refresh_services();
+    Ok(())
+}
+
+#[cfg(windows)]
+fn install(app: &AppHandle, exe: &str) -> Result<(), EngineError> {
+    let key = location(app)?;
+    let command = format!("\"{}\" {} \"%1\"", exe, ASK_FILE_ARG);
+    reg(&["add", &key, "/ve", "/d", "Ask AI about this file", "/f"])
+        .and_then(|_| reg(&["add", &key, "/v", "Icon", "/d", exe, "/f"]))
+        .and_then(|_| reg(&["add", &format!(r"{}\command", key), "/ve", "/d", &command, "/f"]))
+        .map_err(|e| EngineError::Internal(format!("Failed to register {}: {}", key, e)))
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn install(_app: &AppHandle, _exe: &str) -> Result<(), EngineError> {
+    Err(EngineError::InvalidRequest("The context-menu integration is only available on macOS and Windows".to_string()))
+}
+
+#[cfg(target_os = "macos")]
+fn uninstall(app: &AppHandle) -> Result<(), EngineError> {
+    let location = location(app)?;
+    if location.exists() {
+        std::fs::remove_dir_all(&location)
+            .map_err(|e| EngineError::Internal(format!("Failed to remove {:?}: {}", location, e)))?;
+        refresh_services();
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn uninstall(app: &AppHandle) -> Result<(), EngineError> {
+    let key = location(app)?;
+    if status(app)?.installed {
+        reg(&["delete", &key, "/f"]).map_err(|e| EngineError::Internal(format!("Failed to remove {}: {}", key, e)))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn uninstall(_app: &AppHandle) -> Result<(), EngineError> {
+    Ok(())
+}
+
+/// Make the Services menu pick up the changed Quick Action without a re-login.
+#[cfg(target_os = "macos")]
+fn refresh_services() {
+    if let Err(e) = std::process::Command::new("/System/Library/CoreServices/pbs").arg("-update").status() {
+        println!("Failed to refresh the Services menu: {}", e);
+    }
+}
+
+// ==================== Tauri Command: install_context_menu ====================
+
+/// Register "Ask AI about selection" in the OS context menu for this user.
+///
+/// This command:
+///   1. Fails on platforms without an integration (anything but macOS and Windows)
+///   2. Writes the Quick Action (macOS) or HKCU registry entry (Windows)
+///      pointing at the current executable; re-running updates it
+#[tauri::command]
+#[specta::specta]
+pub async fn install_context_menu(app: AppHandle) -> Result<ContextMenuStatus, EngineError> {
+    let exe = std::env::current_exe()
+        .map_err(|e| EngineError::Internal(format!("Failed to resolve the app executable: {}", e)))?;
+    install(&app, &exe.to_string_lossy())?;
+    println!("Context-menu integration installed");
+    status(&app)
+}
+
+// ==================== Tauri Command: uninstall_context_menu ====================
+
+/// Remove the context-menu entry again (a no-op if it isn't registered).
+#[tauri::command]
+#[specta::specta]
+pub async fn uninstall_context_menu(app: AppHandle) -> Result<ContextMenuStatus, EngineError> {
+    uninstall(&app)?;
+    println!("Context-menu integration removed");
+    status(&app)
+}
+
+// ==================== Tauri Command: get_context_menu_status ====================
+
+/// Whether the context-menu entry is supported here and registered.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_context_menu_status(app: AppHandle) -> Result<ContextMenuStatus, EngineError> {
+    status(&app)
+}
@@ -0,0 +1,209 @@
+//! =============================================================================
+//! Engine Crash Supervisor
+//! =============================================================================
+//!
+//! Every spawned engine process gets a watcher on its event channel. It
+//! forwards the engine's stdout/stderr lines to engine_logs and, when the
+//! process exits while the backend still considers it the live engine (it
+//! wasn't stopped via `teardown_engine` and hasn't been replaced), that's a
+//! crash. So is the engine's socket file disappearing while it is live (see
+//! socket_watch), which also covers attached engines without a process:
+//!
+//!   1. The engine is marked stopped (is_running, mux and child cleared; a
+//!      process still alive without its socket is killed)
+//!   2. The crash is counted in the metrics history
+//!   3. `engine_crashed` is emitted with the exit code/signal and restart plan
+//!   4. If `settings.supervisor.auto_restart` is on and fewer than
+//!      `max_restarts` restarts happened within `restart_window_secs`, the
+//!      engine is restarted after an exponential backoff
+//!
+//! A failed restart attempt counts against the same restart budget. Stopping
+//! the engine during the backoff cancels the pending restart.
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri::async_runtime::{Mutex, Receiver};
+use tauri_plugin_shell::process::{CommandChild, CommandEvent, TerminatedPayload};
+use std::collections::VecDeque;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+
+use crate::budget::{CommandClass, CommandTimer};
+use crate::engine_logs::{self, LogStream};
+use crate::engine_state::EngineState;
+use crate::events::{self, EngineCrashed};
+use crate::metrics_history::MetricsHistory;
+use crate::settings::SettingsStore;
+use crate::{start_engine, teardown_engine, PythonProcess};
+
+// ==================== Restart Policy ====================
+
+/// Auto-restart policy, persisted under `settings.supervisor`.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(default)]
+pub struct SupervisorSettings {
+    pub auto_restart: bool,
+    /// Restarts allowed within `restart_window_secs` before giving up
+    pub max_restarts: u32,
+    pub restart_window_secs: u64,
+    /// Delay before the first restart; doubles with each further restart
+    pub backoff_base_ms: u64,
+    pub backoff_max_ms: u64,
+}
+
+impl Default for SupervisorSettings {
+    fn default() -> Self {
+        SupervisorSettings {
+            auto_restart: true,
+            max_restarts: 5,
+            restart_window_secs: 600,
+            backoff_base_ms: 1_000,
+            backoff_max_ms: 30_000,
+        }
+    }
+}
+
+/// Recent restart times, used to enforce the restart budget.
+#[derive(Default)]
+pub struct SupervisorState {
+    restarts: VecDeque<Instant>,
+}
+
+impl SupervisorState {
+    /// Reserve a restart slot; returns the backoff delay, or None if the budget is spent.
+    fn next_restart(&mut self, policy: &SupervisorSettings) -> Option<Duration> {
+        let window = Duration::from_secs(policy.restart_window_secs);
+        self.restarts.retain(|at| at.elapsed() < window);
+        if !policy.auto_restart || self.restarts.len() as u32 >= policy.max_restarts {
+            return None;
+        }
+        let doublings = self.restarts.len().min(16) as u32;
+        let delay_ms = policy.backoff_base_ms.saturating_mul(1 << doublings).min(policy.backoff_max_ms);
+        self.restarts.push_back(Instant::now());
+        Some(Duration::from_millis(delay_ms))
+    }
+}
+
+/// What revealed a crash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum CrashCause {
+    /// The engine process exited
+    Exited,
+    /// The engine's socket file was deleted
+    SocketDeleted,
+}
+
+// ==================== Process Watcher ====================
+
+/// Watch the event channel of engine process `generation`: capture its output and handle its exit.
+pub(crate) fn watch(app: AppHandle, mut events_rx: Receiver<CommandEvent>, generation: u64) {
+    tauri::async_runtime::spawn(async move {
+        while let Some(event) = events_rx.recv().await {
+            match event {
+                CommandEvent::Stdout(line) => engine_logs::record(&app, LogStream::Stdout, &line).await,
+                CommandEvent::Stderr(line) => engine_logs::record(&app, LogStream::Stderr, &line).await,
+                CommandEvent::Terminated(payload) => {
+                    on_exit(&app, generation, payload).await;
+                    return;
+                }
+                _ => {}
+            }
+        }
+    });
+}
+
+/// Handle the exit of process `generation`; restarts it if it crashed.
+async fn on_exit(app: &AppHandle, generation: u64, payload: TerminatedPayload) {
+    {
+        let state = app.state::<Mutex<PythonProcess>>();
+        let mut proc_state = state.lock().await;
+        let current = proc_state.engine_generation.load(Ordering::SeqCst) == generation;
+        if !current || proc_state.child.is_none() {
+            // Stopped on purpose, or an older process that was already replaced
+            return;
+        }
+        proc_state.child = None;
+        *proc_state.mux.lock().await = None;
+        *proc_state.is_running.lock().await = false;
+        proc_state.lifecycle.transition(
+            EngineState::Crashed,
+            &format!("exited unexpectedly (code {:?}, signal {:?})", payload.code, payload.signal),
+        );
+    }
+    println!("AI Engine crashed (code {:?}, signal {:?})", payload.code, payload.signal);
+    recover(app, generation, payload.code, payload.signal, CrashCause::Exited).await;
+}
+
+/// Handle the deletion of the socket of engine `generation` while it is live.
+pub(crate) async fn on_socket_deleted(app: &AppHandle, generation: u64) {
+    {
+        let state = app.state::<Mutex<PythonProcess>>();
+        let mut proc_state = state.lock().await;
+        let current = proc_state.engine_generation.load(Ordering::SeqCst) == generation;
+        if !current || !*proc_state.is_running.lock().await {
+            return;
+        }
+        // Unreachable without its socket; its exit is expected from here on
+        if let Some(child) = proc_state.child.take() {
+            if let Ok(child) = child.downcast::<CommandChild>() {
+                let _ = child.kill();
+            }
+        }
+        *proc_state.mux.lock().await = None;
+        *proc_state.is_running.lock().await = false;
+        proc_state.lifecycle.transition(EngineState::Crashed, "socket file deleted");
+    }
+    println!("AI Engine crashed (socket file deleted)");
+    recover(app, generation, None, None, CrashCause::SocketDeleted).await;
+}
+
+/// Count the crash of engine `generation`, emit `engine_crashed` and restart within the budget.
+async fn recover(app: &AppHandle, generation: u64, code: Option<i32>, signal: Option<i32>, cause: CrashCause) {
+    app.state::<Mutex<MetricsHistory>>().lock().await.record_crash();
+
+    let policy = app.state::<Mutex<SettingsStore>>().lock().await.settings.supervisor.clone();
+    let supervisor = app.state::<Mutex<SupervisorState>>();
+    let mut delay = supervisor.lock().await.next_restart(&policy);
+
+    events::emit(app, EngineCrashed {
+        cause,
+        code,
+        signal,
+        will_restart: delay.is_some(),
+        restart_in_ms: delay.map(|d| d.as_millis() as u64),
+    });
+
+    let mut expected_generation = generation;
+    while let Some(wait) = delay {
+        println!("Restarting AI Engine in {} ms...", wait.as_millis());
+        tokio::time::sleep(wait).await;
+
+        // Stopped, started or replaced in the meantime: nothing left to restart
+        if app.state::<Mutex<PythonProcess>>().lock().await.engine_generation.load(Ordering::SeqCst) != expected_generation {
+            return;
+        }
+
+        let timer = CommandTimer::start(app, "engine_restart", CommandClass::Lifecycle);
+        match start_engine(app, &timer).await {
+            Ok(()) => {
+                println!("AI Engine restarted");
+                return;
+            }
+            Err(e) => {
+                println!("AI Engine restart failed: {}", e);
+                // Kill whatever the failed attempt left running
+                let state = app.state::<Mutex<PythonProcess>>();
+                let mut proc_state = state.lock().await;
+                teardown_engine(&mut proc_state, false).await;
+                expected_generation = proc_state.engine_generation.load(Ordering::SeqCst);
+                drop(proc_state);
+                delay = supervisor.lock().await.next_restart(&policy);
+            }
+        }
+    }
+    println!("AI Engine restart budget exhausted; leaving it stopped");
+    app.state::<Mutex<PythonProcess>>().lock().await.lifecycle.transition(EngineState::Stopped, "restart budget exhausted");
+}
@@ -0,0 +1,290 @@
+//! =============================================================================
+//! Engine Deprecation Notices
+//! =============================================================================
+//!
+//! The engine announces upcoming breaking changes in two ways, both
+//! captured here for every response:
+//!
+//!   headers  on a deprecated endpoint (RFC 9745 / RFC 8594; not on the
+//!            multiplexed connection, whose frames carry only the body):
+//!              Deprecation: @1767225600      (or "true")
+//!              Sunset: Thu, 01 Jan 2027 00:00:00 GMT
+//!              Link: <https://docs/...>; rel="deprecation"
+//!              Warning: 299 - "use /v2/input"
+//!   fields   in a JSON response, one object or a list:
+//!              "deprecation":  { "code": "model-llama2", "kind": "model",
+//!                                "subject": "llama-2-7b", "message": "...",
+//!                                "sunset": "2027-01-01", "replacement": "llama-3-8b",
+//!                                "link": "https://..." }
+//!              "deprecations": [ { ... }, ... ]
+//!
+//! Notices are de-duplicated by `code`, else by kind and subject (a header
+//! notice's subject is the endpoint path), and persisted in
+//! deprecations.json (saved in the background, off the response path) so
+//! each is announced once per install: `deprecation_notice` is emitted the
+//! first time a notice is seen.
+//! `get_deprecation_notices` lists all of them with how often and when they
+//! were last seen (to within LAST_SEEN_SAVE_INTERVAL_SECS).
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::atomic_file;
+use crate::error::EngineError;
+use crate::events::{self, DeprecationNoticed};
+
+// ==================== Configuration Constants ====================
+
+/// File holding the notices inside the app data directory
+const DEPRECATIONS_FILE: &str = "deprecations.json";
+
+/// A notice seen again is saved only if its saved `last_seen` is older than this
+const LAST_SEEN_SAVE_INTERVAL_SECS: u64 = 3600;
+
+/// Set by `init`; responses read before that aren't captured
+static APP: OnceLock<AppHandle> = OnceLock::new();
+
+/// Known notices plus the file they are persisted to
+static STORE: std::sync::Mutex<Option<DeprecationStore>> = std::sync::Mutex::new(None);
+
+/// Held while saving, so an older snapshot never overwrites a newer one
+static SAVING: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// A save is scheduled that hasn't taken its snapshot yet
+static SAVE_QUEUED: AtomicBool = AtomicBool::new(false);
+
+// ==================== Types ====================
+
+/// What a notice deprecates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum DeprecationKind {
+    Endpoint,
+    Model,
+    Field,
+    #[serde(other)]
+    Other,
+}
+
+/// A deprecation announced by the engine.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct DeprecationNotice {
+    /// De-duplication key: the engine's `code`, else "<kind>:<subject>"
+    pub id: String,
+    pub kind: DeprecationKind,
+    /// The deprecated endpoint, model or field
+    pub subject: String,
+    pub message: String,
+    /// Unix seconds it was deprecated at, from a `Deprecation: @<epoch>` header
+    pub deprecated_at: Option<u64>,
+    /// When it stops working, as the engine wrote it
+    pub sunset: Option<String>,
+    pub replacement: Option<String>,
+    pub link: Option<String>,
+    /// Endpoint whose response carried the notice
+    pub endpoint: String,
+    /// Unix seconds
+    pub first_seen: u64,
+    pub last_seen: u64,
+    pub occurrences: u64,
+}
+
+/// A notice's fields as found in a JSON response.
+#[derive(Debug, Deserialize)]
+struct BodyNotice {
+    code: Option<String>,
+    kind: Option<DeprecationKind>,
+    subject: Option<String>,
+    message: Option<String>,
+    sunset: Option<String>,
+    replacement: Option<String>,
+    link: Option<String>,
+}
+
+struct DeprecationStore {
+    path: PathBuf,
+    notices: BTreeMap<String, DeprecationNotice>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// ==================== Recording ====================
+
+/// Save the notices on a blocking thread, off the response path.
+///
+/// Changes made before the save takes its snapshot are written with it, so
+/// a burst of notices is saved once.
+fn save_in_background() {
+    if SAVE_QUEUED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tauri::async_runtime::spawn_blocking(|| {
+        let _saving = SAVING.lock().unwrap_or_else(|e| e.into_inner());
+        SAVE_QUEUED.store(false, Ordering::SeqCst);
+        let snapshot = STORE.lock().unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .map(|store| (store.path.clone(), store.notices.clone()));
+        let Some((path, notices)) = snapshot else {
+            return;
+        };
+        if let Err(e) = atomic_file::write_json(&path, &notices) {
+            println!("Failed to save deprecation notices: {}", e);
+        }
+    });
+}
+
+/// Record `notice`; emits `deprecation_notice` if it wasn't known yet.
+fn record(notice: DeprecationNotice) {
+    let Some(app) = APP.get() else {
+        return;
+    };
+    let now = unix_now();
+    let mut guard = STORE.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(store) = guard.as_mut() else {
+        return;
+    };
+    let (save, new) = match store.notices.get_mut(&notice.id) {
+        Some(known) => {
+            let save = now.saturating_sub(known.last_seen) >= LAST_SEEN_SAVE_INTERVAL_SECS;
+            known.occurrences += 1;
+            if save {
+                known.last_seen = now;
+            }
+            (save, None)
+        }
+        None => {
+            println!("Engine deprecation notice {}: {}", notice.id, notice.message);
+            let notice = DeprecationNotice { first_seen: now, last_seen: now, occurrences: 1, ..notice };
+            store.notices.insert(notice.id.clone(), notice.clone());
+            (true, Some(notice))
+        }
+    };
+    drop(guard);
+    if save {
+        save_in_background();
+    }
+    if let Some(notice) = new {
+        events::emit(app, DeprecationNoticed(notice));
+    }
+}
+
+/// Path of `endpoint` without its query.
+fn endpoint_path(endpoint: &str) -> &str {
+    endpoint.split('?').next().unwrap_or(endpoint)
+}
+
+/// URL of the `rel="deprecation"` entry of a Link header.
+fn deprecation_link(link: &str) -> Option<String> {
+    link.split(',')
+        .find(|entry| entry.contains("rel=\"deprecation\"") || entry.contains("rel=deprecation"))
+        .and_then(|entry| {
+            let start = entry.find('<')? + 1;
+            let end = entry[start..].find('>')? + start;
+            Some(entry[start..end].to_string())
+        })
+}
+
+/// Capture deprecation headers of a response from `endpoint`.
+pub(crate) fn inspect_headers(endpoint: &str, headers: &hyper::HeaderMap) {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+    let Some(deprecation) = header("deprecation") else {
+        return;
+    };
+    if deprecation.eq_ignore_ascii_case("false") {
+        return;
+    }
+    let path = endpoint_path(endpoint);
+    // Deprecation: @1767225600 (RFC 9745), or "true" from older servers
+    let deprecated_at = deprecation.strip_prefix('@').and_then(|epoch| epoch.trim().parse().ok());
+    // Warning: 299 - "text"
+    let warning = header("warning").and_then(|w| {
+        let start = w.find('"')? + 1;
+        let end = w[start..].find('"')? + start;
+        Some(w[start..end].to_string())
+    });
+    record(DeprecationNotice {
+        id: format!("endpoint:{}", path),
+        kind: DeprecationKind::Endpoint,
+        subject: path.to_string(),
+        message: warning.unwrap_or_else(|| format!("Endpoint {} is deprecated", path)),
+        deprecated_at,
+        sunset: header("sunset"),
+        replacement: None,
+        link: header("link").as_deref().and_then(deprecation_link),
+        endpoint: path.to_string(),
+        first_seen: 0,
+        last_seen: 0,
+        occurrences: 0,
+    });
+}
+
+/// Capture `deprecation` / `deprecations` fields of a JSON response from `endpoint`.
+pub(crate) fn inspect_body(endpoint: &str, body: &serde_json::Value) {
+    let entries: Vec<&serde_json::Value> = match (body.get("deprecation"), body.get("deprecations")) {
+        (None, None) => return,
+        (single, list) => single.into_iter()
+            .chain(list.and_then(|l| l.as_array()).into_iter().flatten())
+            .collect(),
+    };
+    let path = endpoint_path(endpoint);
+    for entry in entries {
+        let Ok(found) = serde_json::from_value::<BodyNotice>(entry.clone()) else {
+            println!("Ignoring malformed deprecation notice from {}: {}", path, entry);
+            continue;
+        };
+        let kind = found.kind.unwrap_or(DeprecationKind::Endpoint);
+        let subject = found.subject.unwrap_or_else(|| path.to_string());
+        let kind_name = serde_json::to_value(kind).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default();
+        record(DeprecationNotice {
+            id: found.code.unwrap_or_else(|| format!("{}:{}", kind_name, subject)),
+            kind,
+            message: found.message.unwrap_or_else(|| format!("{} is deprecated", subject)),
+            subject,
+            deprecated_at: None,
+            sunset: found.sunset,
+            replacement: found.replacement,
+            link: found.link,
+            endpoint: path.to_string(),
+            first_seen: 0,
+            last_seen: 0,
+            occurrences: 0,
+        });
+    }
+}
+
+/// Load the known notices and start capturing new ones.
+pub fn init(app: &AppHandle) {
+    let path = app.path().app_data_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join("ai-engine"))
+        .join(DEPRECATIONS_FILE);
+    let notices = atomic_file::load_json::<BTreeMap<String, DeprecationNotice>>(&path)
+        .report(app)
+        .unwrap_or_default();
+    *STORE.lock().unwrap_or_else(|e| e.into_inner()) = Some(DeprecationStore { path, notices });
+    let _ = APP.set(app.clone());
+}
+
+// ==================== Tauri Commands ====================
+
+/// Return every deprecation the engine has announced, most recently seen first.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_deprecation_notices() -> Result<Vec<DeprecationNotice>, EngineError> {
+    let store = STORE.lock().unwrap_or_else(|e| e.into_inner());
+    let mut notices: Vec<DeprecationNotice> = store.as_ref()
+        .map(|store| store.notices.values().cloned().collect())
+        .unwrap_or_default();
+    notices.sort_by_key(|notice| std::cmp::Reverse(notice.last_seen));
+    Ok(notices)
+}
@@ -0,0 +1,117 @@
+//! =============================================================================
+//! Attaching to an Externally Run Engine
+//! =============================================================================
+//!
+//! During development the engine is often run by hand (`python python/app.py`)
+//! to get a debugger or fast reloads. Instead of spawning the bundled binary
+//! the backend can attach to such an engine:
+//!
+//!   attach_to_engine(socket_path)   verify the socket and /health, then wire
+//!                                   up polling exactly as for a spawned engine
+//!
+//!   AI_ENGINE_ATTACH=<socket path>  debug builds only: every start (including
+//!                                   auto-start and supervisor restarts)
+//!                                   attaches to that socket, the binary is
+//!                                   never spawned
+//!
+//! An attached engine isn't ours: stopping it (stop_python_script, the idle
+//! timeout, app exit) only detaches and leaves the process running, and
+//! `restart_python_script` re-attaches rather than spawning a replacement.
+//! A socket given to `attach_to_engine` is used until the engine is stopped.
+
+use tauri::{AppHandle, Manager};
+use tauri::async_runtime::Mutex;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use crate::budget::{CommandClass, CommandTimer, Timed};
+use crate::error::EngineError;
+use crate::{attach_engine, check_health, engine_auth, ipc, start_engine, PythonProcess};
+
+/// Debug builds attach to the engine at this socket instead of spawning one
+const ATTACH_ENV_VAR: &str = "AI_ENGINE_ATTACH";
+
+/// Socket passed to `attach_to_engine`, until the engine is stopped
+static ATTACH_ENDPOINT: RwLock<Option<String>> = RwLock::new(None);
+
+/// Whether the current engine was attached to rather than spawned
+static ATTACHED: AtomicBool = AtomicBool::new(false);
+
+/// Socket `start_engine` should attach to instead of spawning, if any.
+pub(crate) fn attach_endpoint() -> Option<String> {
+    let requested = ATTACH_ENDPOINT.read().unwrap_or_else(|e| e.into_inner()).clone();
+    requested.or_else(|| {
+        cfg!(debug_assertions)
+            .then(|| std::env::var(ATTACH_ENV_VAR).ok())
+            .flatten()
+            .filter(|path| !path.is_empty())
+    })
+}
+
+/// Attach to `socket_path` on the next start (until the engine is stopped).
+pub(crate) fn request_attach(socket_path: Option<String>) {
+    *ATTACH_ENDPOINT.write().unwrap_or_else(|e| e.into_inner()) = socket_path;
+}
+
+/// Whether the running engine is an external one.
+pub(crate) fn is_attached() -> bool {
+    ATTACHED.load(Ordering::SeqCst)
+}
+
+/// Forget an attached engine as it is stopped; returns whether there was one.
+pub(crate) fn detach() -> bool {
+    request_attach(None);
+    let attached = ATTACHED.swap(false, Ordering::SeqCst);
+    if attached {
+        println!("Detached from external AI Engine (left running)");
+    }
+    attached
+}
+
+/// Attach to the healthy engine at `socket_path` (called by `launch_engine`).
+pub(crate) async fn attach(app: &AppHandle, timer: &CommandTimer, socket_path: String) -> Result<(), EngineError> {
+    println!("Attaching to external AI Engine at {}", socket_path);
+    engine_auth::forget(&socket_path);
+    timer.phase("waiting_for_socket").await;
+    ipc::connect(&socket_path)
+        .await
+        .map_err(|e| EngineError::socket(&format!("Failed to connect to {}", socket_path), &e))?;
+    check_health(&socket_path)
+        .await
+        .map_err(|e| EngineError::BadResponse(format!("engine at {} is not healthy: {}", socket_path, e)))?;
+
+    ipc::set_active_endpoint(&socket_path);
+    let state = app.state::<Mutex<PythonProcess>>();
+    let generation = state.lock().await.engine_generation.fetch_add(1, Ordering::SeqCst) + 1;
+    *state.lock().await.last_activity.lock().await = Instant::now();
+    ATTACHED.store(true, Ordering::SeqCst);
+    attach_engine(app, timer, socket_path, generation).await
+}
+
+// ==================== Tauri Command: attach_to_engine ====================
+
+/// Use an engine that is already running at `socket_path` instead of spawning one.
+///
+/// This command:
+///   1. Fails if an engine is already running
+///   2. Connects to `socket_path` and checks GET /health
+///   3. Starts the status polling loop against it
+///
+/// Returns the attach duration; emits `command_slow` past the lifecycle budget.
+#[tauri::command]
+#[specta::specta]
+pub async fn attach_to_engine(app: AppHandle, socket_path: String) -> Result<Timed<()>, EngineError> {
+    let timer = CommandTimer::start(&app, "attach_to_engine", CommandClass::Lifecycle);
+    let is_running = *app.state::<Mutex<PythonProcess>>().lock().await.is_running.lock().await;
+    if is_running {
+        return Err(EngineError::InvalidRequest("AI Engine is already running; stop it before attaching".to_string()));
+    }
+
+    request_attach(Some(socket_path));
+    if let Err(e) = start_engine(&app, &timer).await {
+        request_attach(None);
+        return Err(e);
+    }
+    Ok(timer.finish(()))
+}
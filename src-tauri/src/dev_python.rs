@@ -0,0 +1,156 @@
+//! =============================================================================
+//! Dev Mode: Running the Engine from Python Source
+//! =============================================================================
+//!
+//! Contributors iterating on the engine shouldn't have to rebuild the
+//! PyInstaller binary after every change. In dev mode the engine is started
+//! with a Python interpreter instead of the compiled binary:
+//!
+//!   <python> <script>          default: python/app.py of this checkout
+//!   <python> -m <module>       if a module is configured
+//!
+//! Configured in tauri.conf.json, overridden per variable by the environment:
+//!
+//!   "plugins": { "aiEngine": { "devMode": {
+//!       "enabled": true,            AI_ENGINE_DEV_MODE=1 (0 disables)
+//!       "python": "python3",        AI_ENGINE_DEV_PYTHON
+//!       "module": "ai_engine",      AI_ENGINE_DEV_MODULE
+//!       "script": "../python/app.py",  AI_ENGINE_DEV_SCRIPT
+//!       "workingDir": "../python"   AI_ENGINE_DEV_WORKDIR
+//!   } } }
+//!
+//! Relative paths are resolved against src-tauri/. Debug builds also fall
+//! back to dev mode when no engine binary is found, unless it is disabled
+//! explicitly; release builds only use it when enabled.
+
+use serde::Deserialize;
+use tauri::AppHandle;
+use tauri_plugin_shell::ShellExt;
+use tauri_plugin_shell::process::Command;
+use std::path::{Path, PathBuf};
+
+/// Plugin section of tauri.conf.json holding the dev mode config
+const CONFIG_SECTION: &str = "aiEngine";
+
+/// Enables ("1"/"true") or disables ("0"/"false") dev mode
+pub(crate) const DEV_MODE_ENV_VAR: &str = "AI_ENGINE_DEV_MODE";
+const PYTHON_ENV_VAR: &str = "AI_ENGINE_DEV_PYTHON";
+const MODULE_ENV_VAR: &str = "AI_ENGINE_DEV_MODULE";
+const SCRIPT_ENV_VAR: &str = "AI_ENGINE_DEV_SCRIPT";
+const WORKDIR_ENV_VAR: &str = "AI_ENGINE_DEV_WORKDIR";
+
+/// Interpreter used when none is configured
+const DEFAULT_PYTHON: &str = if cfg!(windows) { "python" } else { "python3" };
+
+/// Engine entry point used when neither a module nor a script is configured
+const DEFAULT_SCRIPT: &str = "../python/app.py";
+
+/// `plugins.aiEngine.devMode` in tauri.conf.json.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct DevModeConfig {
+    enabled: Option<bool>,
+    python: Option<String>,
+    module: Option<String>,
+    script: Option<String>,
+    working_dir: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct PluginSection {
+    dev_mode: DevModeConfig,
+}
+
+/// How the engine is started from source.
+#[derive(Debug, Clone)]
+pub(crate) struct DevEngine {
+    python: String,
+    /// `-m <module>` or `<script>`
+    args: Vec<String>,
+    working_dir: PathBuf,
+}
+
+impl DevEngine {
+    /// Shell command starting the engine.
+    pub(crate) fn command(&self, app: &AppHandle) -> Command {
+        app.shell()
+            .command(&self.python)
+            .args(&self.args)
+            .current_dir(&self.working_dir)
+            // Engine logs reach the log buffer as they are written
+            .env("PYTHONUNBUFFERED", "1")
+    }
+
+    /// e.g. `python3 ../python/app.py`, for logs and errors.
+    pub(crate) fn describe(&self) -> String {
+        format!("{} {}", self.python, self.args.join(" "))
+    }
+}
+
+/// Resolve `path` against src-tauri/ of this checkout.
+fn resolve(path: &str) -> PathBuf {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join(path)
+    }
+}
+
+fn env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// The config file's dev mode section with environment overrides applied.
+fn config(app: &AppHandle) -> DevModeConfig {
+    let file = app.config().plugins.0.get(CONFIG_SECTION)
+        .and_then(|section| serde_json::from_value::<PluginSection>(section.clone()).ok())
+        .map(|section| section.dev_mode)
+        .unwrap_or_default();
+    let enabled = match env(DEV_MODE_ENV_VAR).as_deref() {
+        Some("1") | Some("true") => Some(true),
+        Some("0") | Some("false") => Some(false),
+        _ => file.enabled,
+    };
+    DevModeConfig {
+        enabled,
+        python: env(PYTHON_ENV_VAR).or(file.python),
+        module: env(MODULE_ENV_VAR).or(file.module),
+        script: env(SCRIPT_ENV_VAR).or(file.script),
+        working_dir: env(WORKDIR_ENV_VAR).or(file.working_dir),
+    }
+}
+
+fn dev_engine(config: DevModeConfig) -> DevEngine {
+    let python = config.python.unwrap_or_else(|| DEFAULT_PYTHON.to_string());
+    match config.module {
+        Some(module) => DevEngine {
+            python,
+            args: vec!["-m".to_string(), module],
+            working_dir: resolve(config.working_dir.as_deref().unwrap_or("../python")),
+        },
+        None => {
+            let script = resolve(config.script.as_deref().unwrap_or(DEFAULT_SCRIPT));
+            let working_dir = match &config.working_dir {
+                Some(dir) => resolve(dir),
+                None => script.parent().map(Path::to_path_buf).unwrap_or_else(|| resolve(".")),
+            };
+            DevEngine { python, args: vec![script.to_string_lossy().into_owned()], working_dir }
+        }
+    }
+}
+
+/// The dev engine, if dev mode is enabled.
+pub(crate) fn enabled(app: &AppHandle) -> Option<DevEngine> {
+    let config = config(app);
+    (config.enabled == Some(true)).then(|| dev_engine(config))
+}
+
+/// The dev engine to fall back to when the engine binary is missing.
+///
+/// Debug builds only, and not if dev mode was disabled explicitly.
+pub(crate) fn fallback(app: &AppHandle) -> Option<DevEngine> {
+    let config = config(app);
+    (cfg!(debug_assertions) && config.enabled != Some(false)).then(|| dev_engine(config))
+}
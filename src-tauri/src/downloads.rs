@@ -0,0 +1,251 @@
+//! =============================================================================
+//! Engine Downloads
+//! =============================================================================
+//!
+//! `download_from_python(endpoint, dest_path)` saves a large response body
+//! (a generated file, an exported model) straight to disk:
+//!
+//!   GET <endpoint>  →  <dest_path>.partial  →  rename to <dest_path>
+//!
+//! The body is written chunk by chunk as it arrives, so it is never held in
+//! memory, and `download_progress` is emitted at most every
+//! PROGRESS_INTERVAL_MS with the bytes written and the Content-Length (if the
+//! engine sent one). A failed, cancelled or truncated download leaves nothing
+//! at `dest_path`.
+//!
+//! The endpoint's configured timeout applies to the response headers and to
+//! each gap between chunks, not to the whole transfer, so a slow but steady
+//! download of a multi-gigabyte model isn't cut off.
+//!
+//! Smaller binary answers (synthesized audio, embeddings as raw floats) are
+//! returned directly by `fetch_from_python(endpoint, body)`, with the body
+//! bytes untouched instead of parsed as JSON. They reach the frontend as an
+//! ArrayBuffer, without the JSON encoding of a byte array.
+//!
+//! Both only reach endpoints allowed by `settings.proxy.allowed_endpoints`,
+//! checked like `call_engine` requests (see proxy).
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri::ipc::{InvokeResponseBody, IpcResponse, Response};
+use tauri::async_runtime::Mutex;
+use hyper::body::HttpBody;
+use tokio::io::AsyncWriteExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::error::EngineError;
+use crate::events::{self, DownloadProgress};
+use crate::hooks::{EngineCall, RequestHooks};
+use crate::proxy;
+use crate::requests::ActiveRequests;
+use crate::settings::SettingsStore;
+use crate::temp_files::TempFiles;
+use crate::transport;
+use crate::{drain, error_response, get_socket_path, socket_http_send_with_headers, update_activity_impl, PythonProcess};
+
+/// Minimum time between progress events
+const PROGRESS_INTERVAL_MS: u64 = 200;
+
+// ==================== Types ====================
+
+/// Result of a successful `download_from_python`.
+#[derive(Debug, Serialize, Type)]
+pub struct DownloadedFile {
+    pub request_id: String,
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    /// Content-Type reported by the engine
+    pub content_type: Option<String>,
+}
+
+/// Response of `fetch_from_python`: the body bytes, which reach the frontend
+/// as an ArrayBuffer instead of a JSON array of numbers.
+pub struct EngineBytes(Response);
+
+impl IpcResponse for EngineBytes {
+    fn body(self) -> tauri::Result<InvokeResponseBody> {
+        self.0.body()
+    }
+}
+
+// The bindings can't name ArrayBuffer; the frontend casts the `unknown`
+impl Type for EngineBytes {
+    fn inline(_type_map: &mut specta::TypeCollection, _generics: specta::Generics) -> specta::datatype::DataType {
+        specta::datatype::DataType::Unknown
+    }
+}
+
+// ==================== Download ====================
+
+/// Stream GET `endpoint` into `file`, returning (bytes written, content type).
+///
+/// Request hooks run around it, except post_response ones (see hooks).
+async fn download_to_file(app: &AppHandle, request_id: &str, endpoint: &str, file: &mut tokio::fs::File) -> Result<(u64, Option<String>), EngineError> {
+    let call = EngineCall {
+        method: "GET".to_string(),
+        endpoint: endpoint.to_string(),
+        headers: Vec::new(),
+        body: None,
+    };
+    let send = |call: EngineCall| async move { write_download(app, request_id, &call, file).await };
+    app.state::<RequestHooks>().run_raw(call, send).await
+}
+
+/// The download of `call` behind `download_to_file`.
+async fn write_download(app: &AppHandle, request_id: &str, call: &EngineCall, file: &mut tokio::fs::File) -> Result<(u64, Option<String>), EngineError> {
+    let endpoint = call.endpoint.as_str();
+    let timeout = transport::timeout_for(endpoint);
+    let socket_path = get_socket_path();
+    let response = transport::with_timeout(
+        endpoint,
+        timeout,
+        socket_http_send_with_headers(&socket_path, &call.method, endpoint, None, "*/*", &call.headers),
+    ).await?;
+    if !response.status().is_success() {
+        return Err(error_response(response).await);
+    }
+    let header = |name: hyper::header::HeaderName| {
+        response.headers().get(name).and_then(|v| v.to_str().ok()).map(str::to_string)
+    };
+    let total_bytes = header(hyper::header::CONTENT_LENGTH).and_then(|v| v.parse::<u64>().ok());
+    let content_type = header(hyper::header::CONTENT_TYPE);
+    let mut body = response.into_body();
+
+    let progress = |bytes_written| DownloadProgress {
+        request_id: request_id.to_string(),
+        endpoint: endpoint.to_string(),
+        bytes_written,
+        total_bytes,
+    };
+    let mut written: u64 = 0;
+    let mut last_progress = Instant::now();
+    loop {
+        let chunk = tokio::time::timeout(timeout, body.data())
+            .await
+            .map_err(|_| EngineError::Timeout { endpoint: endpoint.to_string(), timeout_ms: timeout.as_millis() as u64 })?;
+        let Some(chunk) = chunk else {
+            break;
+        };
+        let chunk = chunk.map_err(|e| EngineError::socket("Failed to read download", &e))?;
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| format!("Failed to write download: {}", e))?;
+        written += chunk.len() as u64;
+
+        if last_progress.elapsed() >= Duration::from_millis(PROGRESS_INTERVAL_MS) {
+            last_progress = Instant::now();
+            events::emit(app, progress(written));
+        }
+    }
+    file.flush().await.map_err(|e| format!("Failed to write download: {}", e))?;
+
+    if let Some(total) = total_bytes.filter(|total| *total != written) {
+        return Err(EngineError::BadResponse(format!("download truncated: expected {} bytes, got {}", total, written)));
+    }
+    events::emit(app, progress(written));
+    Ok((written, content_type))
+}
+
+/// `<path>.partial`
+pub(crate) fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path.to_path_buf().into_os_string();
+    name.push(".partial");
+    PathBuf::from(name)
+}
+
+/// Refuse endpoints `call_engine` wouldn't forward either.
+async fn check_endpoint(app: &AppHandle, method: &str, endpoint: &str) -> Result<(), EngineError> {
+    let settings = app.state::<Mutex<SettingsStore>>().lock().await.settings.proxy.clone();
+    proxy::check_endpoint(method, endpoint, &settings)
+}
+
+// ==================== Tauri Command: download_from_python ====================
+
+/// Download the response of GET `endpoint` to `dest_path`, emitting `download_progress`.
+///
+/// This command:
+///   1. Checks `endpoint` against `settings.proxy.allowed_endpoints`
+///   2. Counts the download as in flight, cancellable with
+///      `cancel_request(request_id)` (generated if not given)
+///   3. Streams the body to `<dest_path>.partial`
+///   4. Checks the size against Content-Length, then renames the file into place
+#[tauri::command]
+#[specta::specta]
+pub async fn download_from_python(
+    app: AppHandle,
+    endpoint: String,
+    dest_path: PathBuf,
+    request_id: Option<String>,
+) -> Result<DownloadedFile, EngineError> {
+    check_endpoint(&app, "GET", &endpoint).await?;
+    println!("Downloading {} to {:?}", endpoint, dest_path);
+
+    let state = app.state::<Mutex<PythonProcess>>();
+    let proc_state = state.lock().await;
+    update_activity_impl(&proc_state.last_activity).await;
+    let _request = drain::begin_request(&proc_state)?;
+    drop(proc_state);
+    let mut handle = app.state::<ActiveRequests>().register(request_id)?;
+    let request_id = handle.id().to_string();
+
+    // Removed by the guard if the download fails or is cancelled
+    let (partial, mut file) = app.state::<TempFiles>().create(&request_id, partial_path(&dest_path)).await?;
+    let download = handle.run(download_to_file(&app, &request_id, &endpoint, &mut file)).await;
+    drop(file);
+
+    let (size_bytes, content_type) = download
+        .inspect_err(|e| println!("Download of {} failed: {}", endpoint, e))?;
+    partial.persist(&dest_path).await?;
+
+    println!("Downloaded {} ({} bytes) to {:?}", endpoint, size_bytes, dest_path);
+    Ok(DownloadedFile {
+        request_id,
+        path: dest_path,
+        size_bytes,
+        content_type,
+    })
+}
+
+// ==================== Tauri Command: fetch_from_python ====================
+
+/// Send a request to `endpoint` and return the response body as raw bytes.
+///
+/// This command:
+///   1. Checks `endpoint` against `settings.proxy.allowed_endpoints`
+///   2. Counts the request as in flight, cancellable with
+///      `cancel_request(request_id)` (generated if not given)
+///   3. POSTs `body` to `endpoint` (GET without a body)
+///   4. Returns the body unparsed as an ArrayBuffer, so non-UTF-8 payloads
+///      come through intact
+///
+/// `timeout_ms` overrides the configured timeout for the endpoint's class.
+#[tauri::command]
+#[specta::specta]
+pub async fn fetch_from_python(
+    app: AppHandle,
+    endpoint: String,
+    body: Option<serde_json::Value>,
+    timeout_ms: Option<u64>,
+    request_id: Option<String>,
+) -> Result<EngineBytes, EngineError> {
+    let method = if body.is_some() { "POST" } else { "GET" };
+    check_endpoint(&app, method, &endpoint).await?;
+
+    let state = app.state::<Mutex<PythonProcess>>();
+    let proc_state = state.lock().await;
+    update_activity_impl(&proc_state.last_activity).await;
+    let _request = drain::begin_request(&proc_state)?;
+    drop(proc_state);
+    let mut handle = app.state::<ActiveRequests>().register(request_id)?;
+    let request_id = handle.id().to_string();
+
+    let timeout = timeout_ms.map(Duration::from_millis);
+    let raw = handle.run(transport::engine_request_bytes(&app, method, &endpoint, body.as_ref(), timeout))
+        .await
+        .inspect_err(|e| println!("Request to {} failed: {}", endpoint, e))?;
+
+    println!("Received {} bytes ({:?}) from {} for {}", raw.body.len(), raw.content_type, endpoint, request_id);
+    Ok(EngineBytes(Response::new(raw.body.to_vec())))
+}
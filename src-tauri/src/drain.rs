@@ -0,0 +1,174 @@
+//! =============================================================================
+//! Drain-aware Engine Shutdown
+//! =============================================================================
+//!
+//! `stop_python_script` stops the engine right away, abandoning whatever
+//! requests are still in flight. `stop_engine(options)` adds two modes:
+//!
+//!   • drain (default) - refuse new requests, wait up to a deadline for
+//!     in-flight requests to finish (emitting `engine_drain_progress`),
+//!     then shut down gracefully
+//!   • force - kill the engine immediately
+//!
+//! In-flight requests are counted with `InFlightGuard`s taken by every
+//! command that talks to the engine on the user's behalf.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, State};
+use tauri::async_runtime::Mutex;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::engine_state::{EngineLifecycle, EngineState};
+use crate::error::EngineError;
+use crate::events::{self, EngineDrainProgress};
+use crate::{recycling, teardown_engine, PythonProcess};
+
+// ==================== Configuration Constants ====================
+
+/// Default time to wait for in-flight requests before shutting down anyway
+const DEFAULT_DRAIN_DEADLINE_MS: u64 = 30_000;
+
+/// How often drain progress is checked and reported
+const DRAIN_POLL_INTERVAL_MS: u64 = 250;
+
+// ==================== In-flight Tracking ====================
+
+/// Counts one in-flight request for as long as it is alive.
+///
+/// The first request in flight makes a ready engine busy; the last one to
+/// finish makes it ready again.
+pub struct InFlightGuard {
+    in_flight: Arc<AtomicUsize>,
+    lifecycle: EngineLifecycle,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.lifecycle.try_transition(EngineState::Ready, "no requests in flight");
+        }
+    }
+}
+
+/// Register a new in-flight request, refusing it while the engine drains.
+///
+/// The counter is bumped before the drain flag is checked so a drain that
+/// starts concurrently never misses this request.
+pub(crate) fn begin_request(proc_state: &PythonProcess) -> Result<InFlightGuard, EngineError> {
+    if proc_state.in_flight.fetch_add(1, Ordering::SeqCst) == 0 {
+        proc_state.lifecycle.try_transition(EngineState::Busy, "request in flight");
+    }
+    let guard = InFlightGuard {
+        in_flight: proc_state.in_flight.clone(),
+        lifecycle: proc_state.lifecycle.clone(),
+    };
+    if proc_state.draining.load(Ordering::SeqCst) {
+        return Err(EngineError::ShuttingDown);
+    }
+    recycling::count_request();
+    Ok(guard)
+}
+
+/// Wait up to `deadline` for in-flight requests, emitting `engine_drain_progress`.
+///
+/// Returns the number of requests still in flight when waiting ended.
+pub(crate) async fn wait_for_in_flight(app: &AppHandle, in_flight: &Arc<AtomicUsize>, deadline: Duration) -> usize {
+    let started = Instant::now();
+    loop {
+        let remaining = in_flight.load(Ordering::SeqCst);
+        if remaining == 0 || started.elapsed() >= deadline {
+            return remaining;
+        }
+        events::emit(app, EngineDrainProgress {
+            in_flight: remaining,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            deadline_ms: deadline.as_millis() as u64,
+        });
+        tokio::time::sleep(Duration::from_millis(DRAIN_POLL_INTERVAL_MS)).await;
+    }
+}
+
+// ==================== Stop Options ====================
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum StopMode {
+    #[default]
+    Drain,
+    Force,
+}
+
+/// Options accepted by `stop_engine`.
+#[derive(Debug, Default, Deserialize, Type)]
+pub struct StopOptions {
+    #[serde(default)]
+    pub mode: StopMode,
+    /// Maximum time to wait for in-flight requests (drain mode only)
+    pub deadline_ms: Option<u64>,
+}
+
+/// Outcome of a `stop_engine` call.
+#[derive(Debug, Serialize, Type)]
+pub struct StopReport {
+    pub mode: StopMode,
+    /// Requests still in flight when the engine was stopped
+    pub abandoned: usize,
+    pub duration_ms: u64,
+}
+
+// ==================== Tauri Command: stop_engine ====================
+
+/// Stop the AI Engine, optionally draining in-flight requests first.
+///
+/// This command:
+///   1. Marks the engine as draining so new requests are refused
+///   2. (drain) Waits for in-flight requests up to the deadline,
+///      emitting `engine_drain_progress` while it waits
+///   3. Tears the engine down (graceful /stop for drain, kill for force)
+///   4. Reports how many requests were abandoned
+#[tauri::command]
+#[specta::specta]
+pub async fn stop_engine(app: AppHandle, options: Option<StopOptions>, state: State<'_, Mutex<PythonProcess>>) -> Result<StopReport, EngineError> {
+    let options = options.unwrap_or_default();
+    let started = Instant::now();
+    println!("Stopping AI Engine ({:?} mode)...", options.mode);
+
+    let proc_state = state.lock().await;
+    let is_running = *proc_state.is_running.lock().await;
+    let in_flight = proc_state.in_flight.clone();
+    let draining = proc_state.draining.clone();
+    drop(proc_state);
+
+    if !is_running {
+        println!("AI Engine is not running");
+        return Ok(StopReport { mode: options.mode, abandoned: 0, duration_ms: 0 });
+    }
+
+    draining.store(true, Ordering::SeqCst);
+
+    let abandoned = match options.mode {
+        StopMode::Drain => {
+            let deadline = Duration::from_millis(options.deadline_ms.unwrap_or(DEFAULT_DRAIN_DEADLINE_MS));
+            wait_for_in_flight(&app, &in_flight, deadline).await
+        }
+        StopMode::Force => in_flight.load(Ordering::SeqCst),
+    };
+    if abandoned > 0 {
+        println!("Abandoning {} in-flight request(s)", abandoned);
+    }
+
+    let mut proc_state = state.lock().await;
+    teardown_engine(&mut proc_state, options.mode == StopMode::Drain).await;
+    drop(proc_state);
+
+    draining.store(false, Ordering::SeqCst);
+
+    Ok(StopReport {
+        mode: options.mode,
+        abandoned,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
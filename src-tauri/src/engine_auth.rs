@@ -0,0 +1,175 @@
+//! =============================================================================
+//! Engine Token Authentication
+//! =============================================================================
+//!
+//! The socket's file permissions are the only thing keeping other local
+//! processes away from the engine, and a socket in a shared temp directory
+//! can be replaced by anyone who can write there. Every engine this app
+//! spawns therefore gets a fresh random secret, from which both sides
+//! derive two keys:
+//!
+//!   spawn       AI_ENGINE_TOKEN=<secret, 64 hex digits>
+//!   keys        request = hex HMAC-SHA256(secret, "request")
+//!               proof   = HMAC-SHA256(secret, "response")
+//!   request     Authorization: Bearer <request key>
+//!   response    X-Engine-Auth: <hex HMAC-SHA256(proof key, X-Trace-Id of the request)>
+//!
+//! The engine refuses requests without the request key (401), and proves it
+//! holds the secret by signing each request's trace id (see trace_context).
+//! Only the request key ever crosses the socket, so a process listening in
+//! the engine's place learns nothing that lets it forge a proof. A response
+//! without a valid proof is rejected as `bad_response`: whoever answered
+//! isn't the engine that was spawned. Multiplexed frames carry both in
+//! their `headers` map.
+//!
+//! Keys are kept per endpoint, in memory only, so an old and a new engine
+//! can both be reached during a handoff; they are dropped when their engine
+//! is torn down or replaced. Engines the app didn't spawn
+//! (attached with `attach_to_engine`, or the replay mock) have no keys and
+//! are talked to without authentication.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+
+use crate::error::EngineError;
+
+/// Environment variable passing the secret to a spawned engine
+pub(crate) const TOKEN_ENV_VAR: &str = "AI_ENGINE_TOKEN";
+
+/// Response header carrying the engine's proof of the secret
+pub(crate) const PROOF_HEADER: &str = "x-engine-auth";
+
+/// Random bytes per secret
+const SECRET_BYTES: usize = 32;
+
+/// Keys of the engine spawned at each endpoint
+static KEYS: std::sync::Mutex<BTreeMap<String, EngineKeys>> = std::sync::Mutex::new(BTreeMap::new());
+
+/// The two keys derived from an engine's secret.
+#[derive(Clone)]
+pub(crate) struct EngineKeys {
+    /// Sent with every request
+    request: String,
+    /// Never sent; keys the response proof
+    proof: Vec<u8>,
+}
+
+impl EngineKeys {
+    /// Derive both keys from the `secret` passed to the engine.
+    fn derive(secret: &str) -> EngineKeys {
+        EngineKeys {
+            request: to_hex(&hmac_sha256(secret.as_bytes(), b"request")),
+            proof: hmac_sha256(secret.as_bytes(), b"response"),
+        }
+    }
+}
+
+fn lock_keys() -> std::sync::MutexGuard<'static, BTreeMap<String, EngineKeys>> {
+    KEYS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    // HMAC accepts keys of any length
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC key of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Create the secret for an engine about to be spawned at `endpoint`.
+///
+/// Replaces the keys of any engine spawned there before.
+pub(crate) fn issue(endpoint: &str) -> Result<String, EngineError> {
+    let mut bytes = [0u8; SECRET_BYTES];
+    getrandom::getrandom(&mut bytes)
+        .map_err(|e| EngineError::Internal(format!("Failed to generate engine secret: {}", e)))?;
+    let secret = to_hex(&bytes);
+    lock_keys().insert(endpoint.to_string(), EngineKeys::derive(&secret));
+    Ok(secret)
+}
+
+/// Drop the keys for `endpoint`: its engine is gone, or the app didn't spawn it.
+pub(crate) fn forget(endpoint: &str) {
+    lock_keys().remove(endpoint);
+}
+
+/// Keys of the engine at `endpoint`, if the app spawned it.
+pub(crate) fn keys(endpoint: &str) -> Option<EngineKeys> {
+    lock_keys().get(endpoint).cloned()
+}
+
+/// The Authorization header value for `keys`.
+pub(crate) fn authorization(keys: &EngineKeys) -> String {
+    format!("Bearer {}", keys.request)
+}
+
+/// Check the `proof` of a response to the request to `path` sent with `trace_id`.
+pub(crate) fn verify(keys: &EngineKeys, path: &str, trace_id: &str, proof: Option<&str>) -> Result<(), EngineError> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(&keys.proof)
+        .map_err(|e| EngineError::Internal(format!("Invalid engine proof key: {}", e)))?;
+    mac.update(trace_id.as_bytes());
+    let valid = proof
+        .and_then(|proof| decode_hex(proof.trim()))
+        .is_some_and(|proof| mac.verify_slice(&proof).is_ok());
+    if !valid {
+        println!("Rejecting response from {}: missing or invalid {} proof", path, PROOF_HEADER);
+        return Err(EngineError::BadResponse(format!(
+            "{} was answered without proof of the engine secret; another process may be listening on the socket",
+            path
+        )));
+    }
+    Ok(())
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+
+    #[test]
+    fn proof_keyed_with_the_proof_key_is_accepted() {
+        let keys = EngineKeys::derive(SECRET);
+        let proof = to_hex(&hmac_sha256(&keys.proof, b"trace-1"));
+        assert!(verify(&keys, "/input", "trace-1", Some(&proof)).is_ok());
+        assert!(verify(&keys, "/input", "trace-1", Some(&proof.to_uppercase())).is_ok());
+    }
+
+    #[test]
+    fn forged_or_missing_proofs_are_rejected() {
+        let keys = EngineKeys::derive(SECRET);
+        // Signed with the request key, which any listener on the socket sees
+        let forged = to_hex(&hmac_sha256(keys.request.as_bytes(), b"trace-1"));
+        let other_trace = to_hex(&hmac_sha256(&keys.proof, b"trace-2"));
+        for proof in [Some(forged.as_str()), Some(other_trace.as_str()), Some("zz"), Some("abc"), None] {
+            assert!(
+                matches!(verify(&keys, "/input", "trace-1", proof), Err(EngineError::BadResponse(_))),
+                "{:?} was accepted",
+                proof
+            );
+        }
+    }
+
+    #[test]
+    fn authorization_carries_the_request_key_only() {
+        let keys = EngineKeys::derive(SECRET);
+        let header = authorization(&keys);
+        assert_eq!(header, format!("Bearer {}", keys.request));
+        assert!(!header.contains(SECRET));
+        assert!(!header.contains(&to_hex(&keys.proof)));
+    }
+}
@@ -0,0 +1,118 @@
+//! =============================================================================
+//! Engine Event Subscription
+//! =============================================================================
+//!
+//! Besides answering requests, an engine can push events over a long-lived
+//! connection: GET /events stays open and carries one NDJSON record per
+//! event (decoded incrementally, see ndjson):
+//!
+//!   { "event": "model_loaded", "data": { "model": "small" } }
+//!
+//! Each record is re-emitted as `engine_event`, with its timestamps on the
+//! host clock (see clock_sync); `model_loaded` also marks the model ready
+//! and loaded (see warmup, models). The subscription is opened for every engine
+//! once it is attached, reconnects with backoff if the connection drops
+//! while that engine is current, and ends for good when the engine is
+//! replaced or answers 404 (it doesn't push events).
+
+use hyper::body::HttpBody;
+use tauri::{AppHandle, Manager};
+use tauri::async_runtime::Mutex;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use crate::clock_sync;
+use crate::error::EngineError;
+use crate::events::{self, EngineEvent};
+use crate::ndjson::{self, Decoded, NdjsonDecoder};
+use crate::{models, warmup};
+use crate::{error_response, socket_http_send, PythonProcess};
+
+/// Endpoint of the event stream
+const EVENTS_ENDPOINT: &str = "/events";
+
+/// Engine event reporting a model resident (see warmup)
+const MODEL_LOADED_EVENT: &str = "model_loaded";
+
+/// First reconnect delay; doubles up to MAX_RECONNECT_DELAY_SECS
+const RECONNECT_DELAY_SECS: u64 = 1;
+const MAX_RECONNECT_DELAY_SECS: u64 = 30;
+
+/// Whether engine `generation` is still the current one.
+async fn is_current(app: &AppHandle, generation: u64) -> bool {
+    let state = app.state::<Mutex<PythonProcess>>();
+    let current = state.lock().await.engine_generation.load(Ordering::SeqCst);
+    current == generation
+}
+
+/// Re-emit one record as `engine_event`.
+fn dispatch(app: &AppHandle, record: serde_json::Value) {
+    let Some(name) = record.get("event").and_then(|v| v.as_str()).map(str::to_string) else {
+        ndjson::report_skipped(app, EVENTS_ENDPOINT, None, &Decoded::Malformed("record has no \"event\" name".to_string()));
+        return;
+    };
+    let mut data = record.get("data").cloned().unwrap_or(serde_json::Value::Null);
+    clock_sync::normalize(&mut data);
+    if name == MODEL_LOADED_EVENT {
+        if let Some(model) = data.get("model").and_then(|v| v.as_str()) {
+            warmup::mark_ready(app, model);
+            let (app, model) = (app.clone(), model.to_string());
+            tauri::async_runtime::spawn(async move { models::set_loaded(&app, Some(model)).await });
+        }
+    }
+    events::emit(app, EngineEvent { name, data });
+}
+
+/// Read the event stream until it ends; Ok(false) if the engine has none.
+async fn read_events(app: &AppHandle, socket_path: &str, generation: u64) -> Result<bool, EngineError> {
+    let response = socket_http_send(socket_path, "GET", EVENTS_ENDPOINT, None, "application/x-ndjson").await?;
+    if response.status() == hyper::StatusCode::NOT_FOUND {
+        return Ok(false);
+    }
+    if !response.status().is_success() {
+        return Err(error_response(response).await);
+    }
+    println!("Subscribed to engine events");
+
+    let mut body = response.into_body();
+    let mut decoder = NdjsonDecoder::default();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| EngineError::connection_closed(format!("{} stream: {}", EVENTS_ENDPOINT, e)))?;
+        if !is_current(app, generation).await {
+            return Ok(true);
+        }
+        for decoded in decoder.push(&chunk) {
+            match decoded {
+                Decoded::Record(record) => dispatch(app, record),
+                skipped => ndjson::report_skipped(app, EVENTS_ENDPOINT, None, &skipped),
+            }
+        }
+    }
+    match decoder.finish() {
+        Some(Decoded::Record(record)) => dispatch(app, record),
+        Some(skipped) => ndjson::report_skipped(app, EVENTS_ENDPOINT, None, &skipped),
+        None => {}
+    }
+    Ok(true)
+}
+
+/// Subscribe to the events of engine `generation` at `socket_path` (runs in the background).
+pub(crate) fn subscribe(app: &AppHandle, socket_path: String, generation: u64) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut delay = Duration::from_secs(RECONNECT_DELAY_SECS);
+        while is_current(&app, generation).await {
+            match read_events(&app, &socket_path, generation).await {
+                Ok(false) => {
+                    println!("Engine has no event stream");
+                    return;
+                }
+                // The stream ran, so the next drop starts the backoff over
+                Ok(true) => delay = Duration::from_secs(RECONNECT_DELAY_SECS),
+                Err(e) => println!("Engine event stream failed: {}", e),
+            }
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(Duration::from_secs(MAX_RECONNECT_DELAY_SECS));
+        }
+    });
+}
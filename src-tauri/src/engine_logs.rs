@@ -0,0 +1,86 @@
+//! =============================================================================
+//! Engine Log Capture
+//! =============================================================================
+//!
+//! The engine's stdout and stderr arrive as `CommandEvent`s on the channel
+//! returned by `spawn()` (consumed by crash_supervisor::watch). Every line is:
+//!
+//!   • emitted to the frontend as `engine_log`
+//!       { "seq": 42, "stream": "stderr", "line": "…", "timestamp_ms": … }
+//!   • kept in a ring buffer of the last ENGINE_LOG_CAPACITY lines, so a log
+//!     view opened later can backfill with `get_engine_logs`
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Manager, State};
+use tauri::async_runtime::Mutex;
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::EngineError;
+use crate::events::{self, EngineLog};
+
+/// Number of log lines kept in memory
+const ENGINE_LOG_CAPACITY: usize = 2_000;
+
+// ==================== Types ====================
+
+/// Output stream a log line came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+/// One line of engine output.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct EngineLogLine {
+    /// Increasing sequence number, to merge backfill with live events
+    pub seq: u64,
+    pub stream: LogStream,
+    pub line: String,
+    pub timestamp_ms: u64,
+}
+
+/// The most recent engine log lines.
+#[derive(Default)]
+pub struct EngineLogBuffer {
+    lines: VecDeque<EngineLogLine>,
+    next_seq: u64,
+}
+
+// ==================== Capture ====================
+
+/// Record one line of engine output and forward it to the frontend.
+pub(crate) async fn record(app: &AppHandle, stream: LogStream, bytes: &[u8]) {
+    let line = String::from_utf8_lossy(bytes).trim_end_matches(['\r', '\n']).to_string();
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+
+    let entry = {
+        let buffer = app.state::<Mutex<EngineLogBuffer>>();
+        let mut buffer = buffer.lock().await;
+        let entry = EngineLogLine { seq: buffer.next_seq, stream, line, timestamp_ms };
+        buffer.next_seq += 1;
+        if buffer.lines.len() == ENGINE_LOG_CAPACITY {
+            buffer.lines.pop_front();
+        }
+        buffer.lines.push_back(entry.clone());
+        entry
+    };
+    events::emit(app, EngineLog(entry));
+}
+
+// ==================== Tauri Command: get_engine_logs ====================
+
+/// Return the most recent engine log lines, oldest first (at most `limit`).
+#[tauri::command]
+#[specta::specta]
+pub async fn get_engine_logs(limit: Option<usize>, buffer: State<'_, Mutex<EngineLogBuffer>>) -> Result<Vec<EngineLogLine>, EngineError> {
+    let buffer = buffer.lock().await;
+    let limit = limit.unwrap_or(ENGINE_LOG_CAPACITY).min(buffer.lines.len());
+    Ok(buffer.lines.iter().skip(buffer.lines.len() - limit).cloned().collect())
+}
@@ -0,0 +1,187 @@
+//! =============================================================================
+//! Engine Latency Metrics
+//! =============================================================================
+//!
+//! Rolling request/response statistics of the engine, so users can see how
+//! fast the model is answering right now (metrics_history keeps the long-term
+//! rollups). Every POST to the engine is timed from send to parsed response,
+//! whether it goes through `socket_http_post` or the transport middleware:
+//!
+//!   count, errors, error_rate    requests in the window and failed ones
+//!   p50_ms, p95_ms, p99_ms       nearest-rank latency percentiles
+//!
+//! The window holds the last METRICS_WINDOW_SECS (at most MAX_SAMPLES
+//! requests), overall and per endpoint. `get_engine_metrics` returns the
+//! statistics on demand; `engine_metrics` is emitted every
+//! METRICS_EVENT_INTERVAL_SECS while new requests come in.
+
+use serde::Serialize;
+use specta::Type;
+use tauri::AppHandle;
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::error::EngineError;
+use crate::events::{self, EngineMetricsSampled};
+use crate::metrics_history::percentile;
+
+// ==================== Configuration Constants ====================
+
+/// Requests older than this drop out of the statistics
+const METRICS_WINDOW_SECS: u64 = 300;
+
+/// Most requests kept in the window
+const MAX_SAMPLES: usize = 5000;
+
+/// How often `engine_metrics` is emitted while requests come in
+const METRICS_EVENT_INTERVAL_SECS: u64 = 10;
+
+/// One timed request.
+struct Sample {
+    endpoint: String,
+    latency_ms: u64,
+    ok: bool,
+    at: Instant,
+}
+
+/// Timed requests, oldest first
+static WINDOW: Mutex<VecDeque<Sample>> = Mutex::new(VecDeque::new());
+
+/// Requests timed since startup; tells the emitter whether anything changed
+static RECORDED: AtomicU64 = AtomicU64::new(0);
+
+// ==================== Types ====================
+
+/// Latency statistics of a set of requests.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct LatencyStats {
+    pub count: u64,
+    pub errors: u64,
+    /// errors / count (0 without requests)
+    pub error_rate: f64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub p99_ms: u64,
+}
+
+/// Statistics of one endpoint.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct EndpointLatency {
+    pub endpoint: String,
+    pub stats: LatencyStats,
+}
+
+/// Rolling engine latency, returned by `get_engine_metrics` and emitted as `engine_metrics`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct EngineMetrics {
+    pub window_secs: u64,
+    pub overall: LatencyStats,
+    /// Busiest endpoint first
+    pub endpoints: Vec<EndpointLatency>,
+}
+
+impl LatencyStats {
+    fn of<'a>(samples: impl Iterator<Item = &'a Sample>) -> LatencyStats {
+        let mut latencies = Vec::new();
+        let mut errors = 0;
+        for sample in samples {
+            latencies.push(sample.latency_ms);
+            if !sample.ok {
+                errors += 1;
+            }
+        }
+        latencies.sort_unstable();
+        let count = latencies.len() as u64;
+        LatencyStats {
+            count,
+            errors,
+            error_rate: if count > 0 { errors as f64 / count as f64 } else { 0.0 },
+            p50_ms: percentile(&latencies, 50),
+            p95_ms: percentile(&latencies, 95),
+            p99_ms: percentile(&latencies, 99),
+        }
+    }
+}
+
+// ==================== Recording ====================
+
+/// Drop samples that left the window.
+fn expire(window: &mut VecDeque<Sample>) {
+    let horizon = Duration::from_secs(METRICS_WINDOW_SECS);
+    while window.front().is_some_and(|s| s.at.elapsed() > horizon) {
+        window.pop_front();
+    }
+}
+
+/// Time a request to `endpoint`, counting it as failed if it returns an error.
+pub(crate) async fn measure<T>(endpoint: &str, request: impl Future<Output = Result<T, EngineError>>) -> Result<T, EngineError> {
+    let started = Instant::now();
+    let result = request.await;
+    let mut window = WINDOW.lock().unwrap_or_else(|e| e.into_inner());
+    expire(&mut window);
+    if window.len() == MAX_SAMPLES {
+        window.pop_front();
+    }
+    window.push_back(Sample {
+        endpoint: endpoint.to_string(),
+        latency_ms: started.elapsed().as_millis() as u64,
+        ok: result.is_ok(),
+        at: Instant::now(),
+    });
+    RECORDED.fetch_add(1, Ordering::Relaxed);
+    result
+}
+
+/// Statistics of the requests currently in the window.
+fn snapshot() -> EngineMetrics {
+    let mut window = WINDOW.lock().unwrap_or_else(|e| e.into_inner());
+    expire(&mut window);
+
+    let mut by_endpoint: BTreeMap<&str, Vec<&Sample>> = BTreeMap::new();
+    for sample in window.iter() {
+        by_endpoint.entry(sample.endpoint.as_str()).or_default().push(sample);
+    }
+    let mut endpoints: Vec<EndpointLatency> = by_endpoint
+        .into_iter()
+        .map(|(endpoint, samples)| EndpointLatency {
+            endpoint: endpoint.to_string(),
+            stats: LatencyStats::of(samples.into_iter()),
+        })
+        .collect();
+    endpoints.sort_by_key(|e| std::cmp::Reverse(e.stats.count));
+
+    EngineMetrics {
+        window_secs: METRICS_WINDOW_SECS,
+        overall: LatencyStats::of(window.iter()),
+        endpoints,
+    }
+}
+
+/// Emit `engine_metrics` periodically while new requests are timed (runs in the background).
+pub fn init(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut emitted_at = 0;
+        loop {
+            tokio::time::sleep(Duration::from_secs(METRICS_EVENT_INTERVAL_SECS)).await;
+            let recorded = RECORDED.load(Ordering::Relaxed);
+            if recorded == emitted_at {
+                continue;
+            }
+            emitted_at = recorded;
+            events::emit(&app, EngineMetricsSampled { metrics: snapshot() });
+        }
+    });
+}
+
+// ==================== Tauri Command: get_engine_metrics ====================
+
+/// Return request count, latency percentiles and error rate of the last METRICS_WINDOW_SECS.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_engine_metrics() -> Result<EngineMetrics, EngineError> {
+    Ok(snapshot())
+}
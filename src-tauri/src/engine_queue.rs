@@ -0,0 +1,176 @@
+//! =============================================================================
+//! Engine Task Queue
+//! =============================================================================
+//!
+//! The engine runs long batch tasks from its own internal job queue. The
+//! backend mirrors that queue so the UI can show and manage it:
+//!
+//!   GET  /queue          { "tasks": [ { "id": "t-3", "name": "Index docs",
+//!                                       "state": "running", "position": 0,
+//!                                       "progress": 0.4 } ] }
+//!   POST /queue/reorder  { "id", "position" }
+//!   POST /queue/cancel   { "id" }
+//!
+//! Engines that push their queue inline in /status (a `queue` array with the
+//! same task shape) are mirrored from every poll; otherwise /queue is polled
+//! every QUEUE_POLL_INTERVAL_SECS. `engine_queue_changed` is emitted whenever
+//! the mirrored queue changes.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager, State};
+use tauri::async_runtime::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::EngineError;
+use crate::events::{self, EngineQueueChanged};
+use crate::{transport, PythonProcess};
+
+/// How often /queue is polled when /status doesn't carry the queue
+const QUEUE_POLL_INTERVAL_SECS: u64 = 2;
+
+// ==================== Types ====================
+
+/// A task in the engine's job queue.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct EngineTask {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    /// "queued", "running", ...
+    #[serde(default)]
+    pub state: String,
+    /// 0-based position in the queue
+    #[serde(default)]
+    pub position: usize,
+    /// Completion fraction 0.0 - 1.0, if the engine reports it
+    #[serde(default)]
+    pub progress: Option<f64>,
+}
+
+/// Last known engine queue.
+#[derive(Default)]
+pub struct EngineQueueState {
+    tasks: Vec<EngineTask>,
+    last_fetch: Option<Instant>,
+}
+
+// ==================== Mirroring ====================
+
+/// Parse a task list, ordered by position.
+fn parse_tasks(tasks: &serde_json::Value) -> Result<Vec<EngineTask>, String> {
+    let mut tasks: Vec<EngineTask> = serde_json::from_value(tasks.clone())
+        .map_err(|e| format!("Invalid engine queue: {}", e))?;
+    tasks.sort_by_key(|task| task.position);
+    Ok(tasks)
+}
+
+/// Replace the mirrored queue; emits when it changed.
+async fn store(app: &AppHandle, tasks: Vec<EngineTask>) {
+    let state = app.state::<Mutex<EngineQueueState>>();
+    let mut state = state.lock().await;
+    state.last_fetch = Some(Instant::now());
+    if state.tasks != tasks {
+        state.tasks = tasks.clone();
+        drop(state);
+        events::emit(app, EngineQueueChanged { tasks });
+    }
+}
+
+/// Fetch GET /queue into the mirror.
+async fn fetch(app: &AppHandle) -> Result<Vec<EngineTask>, EngineError> {
+    let response = transport::engine_request(app, "GET", "/queue", None, None).await?;
+    let tasks = parse_tasks(response.get("tasks").unwrap_or(&serde_json::json!([])))
+        .map_err(EngineError::BadResponse)?;
+    store(app, tasks.clone()).await;
+    Ok(tasks)
+}
+
+/// Update the mirror from a /status payload, polling /queue if it isn't inline.
+///
+/// Called by the status polling loop after every successful poll.
+pub(crate) async fn ingest(app: &AppHandle, status: &serde_json::Value) {
+    if let Some(queue) = status.get("queue") {
+        match parse_tasks(queue) {
+            Ok(tasks) => store(app, tasks).await,
+            Err(e) => println!("{}", e),
+        }
+        return;
+    }
+
+    let due = app.state::<Mutex<EngineQueueState>>().lock().await.last_fetch
+        .is_none_or(|at| at.elapsed() >= Duration::from_secs(QUEUE_POLL_INTERVAL_SECS));
+    if due {
+        if let Err(e) = fetch(app).await {
+            println!("Failed to poll engine queue: {}", e);
+            // Don't retry on every status poll while /queue is failing
+            app.state::<Mutex<EngineQueueState>>().lock().await.last_fetch = Some(Instant::now());
+        }
+    }
+}
+
+/// Clear the mirror; the queue of a previous engine process is gone.
+pub(crate) async fn clear(app: &AppHandle) {
+    store(app, Vec::new()).await;
+}
+
+async fn ensure_running(process: &Mutex<PythonProcess>) -> Result<(), EngineError> {
+    if *process.lock().await.is_running.lock().await {
+        Ok(())
+    } else {
+        Err(EngineError::NotRunning)
+    }
+}
+
+// ==================== Tauri Commands ====================
+
+/// Return the engine's task queue, freshly fetched when the engine is running.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_engine_queue(
+    app: AppHandle,
+    queue: State<'_, Mutex<EngineQueueState>>,
+    process: State<'_, Mutex<PythonProcess>>,
+) -> Result<Vec<EngineTask>, EngineError> {
+    if ensure_running(&process).await.is_err() {
+        return Ok(Vec::new());
+    }
+    match fetch(&app).await {
+        Ok(tasks) => Ok(tasks),
+        Err(e) => {
+            println!("Failed to fetch engine queue, returning last known: {}", e);
+            Ok(queue.lock().await.tasks.clone())
+        }
+    }
+}
+
+/// Move engine task `id` to `position` (0 = next to run).
+#[tauri::command]
+#[specta::specta]
+pub async fn reorder_engine_task(
+    app: AppHandle,
+    id: String,
+    position: usize,
+    process: State<'_, Mutex<PythonProcess>>,
+) -> Result<Vec<EngineTask>, EngineError> {
+    ensure_running(&process).await?;
+    println!("Moving engine task {} to position {}", id, position);
+    let body = serde_json::json!({ "id": id, "position": position });
+    transport::engine_request(&app, "POST", "/queue/reorder", Some(&body), None).await?;
+    fetch(&app).await
+}
+
+/// Cancel engine task `id` (queued or running).
+#[tauri::command]
+#[specta::specta]
+pub async fn cancel_engine_task(
+    app: AppHandle,
+    id: String,
+    process: State<'_, Mutex<PythonProcess>>,
+) -> Result<Vec<EngineTask>, EngineError> {
+    ensure_running(&process).await?;
+    println!("Cancelling engine task {}", id);
+    let body = serde_json::json!({ "id": id });
+    transport::engine_request(&app, "POST", "/queue/cancel", Some(&body), None).await?;
+    fetch(&app).await
+}
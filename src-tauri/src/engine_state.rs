@@ -0,0 +1,183 @@
+//! =============================================================================
+//! Engine Lifecycle State Machine
+//! =============================================================================
+//!
+//! The engine's lifecycle as one explicit state, kept in `PythonProcess`:
+//!
+//!   Stopped ──→ Starting ──→ Ready ⇄ Busy
+//!      ↑           │            │      │
+//!      │           ↓            ↓      ↓
+//!      ├──────── Stopping ←─────┴──────┘
+//!      │
+//!      └──────── Crashed ←── (Starting, Ready, Busy)
+//!                   └──→ Starting (supervisor restart)
+//!
+//! A failed start returns to the state it started from. The engine is Busy
+//! while at least one request is in flight (see drain::InFlightGuard).
+//! Every transition is logged and emitted as `engine_state_changed`:
+//!
+//!   { "state": "ready", "previous": "starting", "reason": "engine healthy", "at_ms": … }
+//!
+//! Transitions the diagram doesn't allow are logged and ignored.
+//! `get_engine_state()` returns the current state.
+//!
+//! The last LIFECYCLE_HISTORY_LEN transitions are kept as the lifecycle
+//! history, together with notes on events that keep the state (e.g. a
+//! recycled engine, see recycling); `get_engine_lifecycle_history()` returns
+//! it, oldest first.
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, State};
+use tauri::async_runtime::Mutex;
+use std::collections::VecDeque;
+use std::sync::{Arc, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::EngineError;
+use crate::events::{self, EngineStateChanged};
+use crate::PythonProcess;
+
+/// Entries kept in the lifecycle history
+const LIFECYCLE_HISTORY_LEN: usize = 200;
+
+// ==================== Types ====================
+
+/// Lifecycle state of the engine.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum EngineState {
+    #[default]
+    Stopped,
+    Starting,
+    Ready,
+    Busy,
+    Stopping,
+    Crashed,
+}
+
+impl EngineState {
+    /// Whether the state machine allows moving from `self` to `to`.
+    fn allows(self, to: EngineState) -> bool {
+        use EngineState::*;
+        matches!(
+            (self, to),
+            (Stopped, Starting)
+                | (Starting, Ready | Stopping | Stopped | Crashed)
+                | (Ready, Busy | Stopping | Crashed)
+                | (Busy, Ready | Stopping | Crashed)
+                | (Stopping, Stopped)
+                | (Crashed, Starting | Stopped)
+        )
+    }
+}
+
+/// One entry of the lifecycle history.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct LifecycleRecord {
+    pub state: EngineState,
+    /// Equal to `state` for notes that didn't change the state
+    pub previous: EngineState,
+    pub reason: String,
+    pub at_ms: u64,
+}
+
+/// Current state plus the handle transitions are emitted through.
+#[derive(Clone, Default)]
+pub struct EngineLifecycle {
+    state: Arc<std::sync::Mutex<EngineState>>,
+    app: Arc<OnceLock<AppHandle>>,
+    history: Arc<std::sync::Mutex<VecDeque<LifecycleRecord>>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+impl EngineLifecycle {
+    /// Emit transitions through `app` from now on (set once during setup).
+    pub fn attach(&self, app: &AppHandle) {
+        let _ = self.app.set(app.clone());
+    }
+
+    pub fn current(&self) -> EngineState {
+        *self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Move to `to`; returns false (and changes nothing) if the transition isn't allowed.
+    pub fn transition(&self, to: EngineState, reason: &str) -> bool {
+        let changed = self.apply(to, reason);
+        if changed.is_none() {
+            let from = self.current();
+            if from != to {
+                println!("Engine state: ignoring {:?} → {:?} ({})", from, to, reason);
+            }
+        }
+        changed.is_some()
+    }
+
+    /// Move to `to` if allowed, silently otherwise (for best-effort transitions like Busy).
+    pub fn try_transition(&self, to: EngineState, reason: &str) {
+        self.apply(to, reason);
+    }
+
+    /// Add an event that doesn't change the state to the lifecycle history.
+    pub fn note(&self, reason: &str) {
+        let state = self.current();
+        println!("Engine lifecycle: {} ({:?})", reason, state);
+        self.push(LifecycleRecord { state, previous: state, reason: reason.to_string(), at_ms: now_ms() });
+    }
+
+    /// The lifecycle history, oldest first.
+    pub fn history(&self) -> Vec<LifecycleRecord> {
+        self.history.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned().collect()
+    }
+
+    fn push(&self, record: LifecycleRecord) {
+        let mut history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+        if history.len() == LIFECYCLE_HISTORY_LEN {
+            history.pop_front();
+        }
+        history.push_back(record);
+    }
+
+    fn apply(&self, to: EngineState, reason: &str) -> Option<EngineState> {
+        let previous = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            if !state.allows(to) {
+                return None;
+            }
+            std::mem::replace(&mut *state, to)
+        };
+        println!("Engine state: {:?} → {:?} ({})", previous, to, reason);
+        let at_ms = now_ms();
+        self.push(LifecycleRecord { state: to, previous, reason: reason.to_string(), at_ms });
+        if let Some(app) = self.app.get() {
+            events::emit(app, EngineStateChanged {
+                state: to,
+                previous,
+                reason: reason.to_string(),
+                at_ms,
+            });
+        }
+        Some(previous)
+    }
+}
+
+// ==================== Tauri Command: get_engine_state ====================
+
+/// Return the engine's current lifecycle state.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_engine_state(state: State<'_, Mutex<PythonProcess>>) -> Result<EngineState, EngineError> {
+    Ok(state.lock().await.lifecycle.current())
+}
+
+// ==================== Tauri Command: get_engine_lifecycle_history ====================
+
+/// Return the recent lifecycle transitions and notes, oldest first.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_engine_lifecycle_history(state: State<'_, Mutex<PythonProcess>>) -> Result<Vec<LifecycleRecord>, EngineError> {
+    Ok(state.lock().await.lifecycle.history())
+}
@@ -0,0 +1,256 @@
+//! =============================================================================
+//! Engine Variants
+//! =============================================================================
+//!
+//! Installs can ship several builds of the engine (e.g. CPU-only and CUDA),
+//! described by the bundled resource ENGINE_VARIANTS_MANIFEST:
+//!
+//!   { "default": "cpu",
+//!     "variants": [
+//!       { "name": "cpu",  "binary": "ai-engine",      "size_mb": 310,
+//!         "description": "Runs everywhere" },
+//!       { "name": "cuda", "binary": "ai-engine-cuda", "size_mb": 2150,
+//!         "description": "NVIDIA GPUs",
+//!         "requires": { "gpu": "cuda", "min_memory_mb": 8192 } } ] }
+//!
+//! `binary` is a file next to the app executable (like the sidecar), with
+//! the platform's executable suffix. `requires.gpu` is one of "cuda",
+//! "rocm" or "metal".
+//!
+//! `list_engine_variants()` reports every variant with whether it is
+//! installed and supported by this machine's hardware.
+//! `select_engine_variant(name)` validates the choice, remembers it in the
+//! app's local (per-machine) data dir and restarts a running engine on it.
+//! Without a manifest, or while the default variant is selected, the
+//! engine starts from the regular onedir build / sidecar.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager, State};
+use tauri::async_runtime::Mutex;
+use std::path::PathBuf;
+use sysinfo::{MemoryRefreshKind, RefreshKind, System};
+
+use crate::atomic_file;
+use crate::budget::{CommandClass, CommandTimer, Timed};
+use crate::error::EngineError;
+use crate::{start_engine, teardown_engine, PythonProcess};
+
+/// Resource describing the bundled engine variants
+const ENGINE_VARIANTS_MANIFEST: &str = "binaries/engine-variants.json";
+
+/// File in the local app data dir remembering the selected variant
+const SELECTED_VARIANT_FILE: &str = "engine_variant.json";
+
+// ==================== Types ====================
+
+/// Hardware a variant needs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
+#[serde(default)]
+pub struct HardwareRequirements {
+    /// GPU runtime: "cuda", "rocm" or "metal"
+    pub gpu: Option<String>,
+    /// Total system memory needed
+    pub min_memory_mb: Option<u64>,
+}
+
+/// One variant entry of the manifest.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EngineVariant {
+    pub name: String,
+    /// Executable next to the app executable, without suffix
+    pub binary: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub size_mb: Option<u64>,
+    #[serde(default)]
+    pub requires: HardwareRequirements,
+}
+
+/// The variants manifest.
+#[derive(Debug, Clone, Deserialize)]
+struct VariantManifest {
+    default: String,
+    variants: Vec<EngineVariant>,
+}
+
+/// A variant as reported by `list_engine_variants`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct EngineVariantInfo {
+    #[serde(flatten)]
+    pub variant: EngineVariant,
+    /// Its binary is present
+    pub installed: bool,
+    /// This machine meets its hardware requirements
+    pub supported: bool,
+    /// Why it isn't supported, if it isn't
+    pub unsupported_reason: Option<String>,
+    pub default: bool,
+    pub selected: bool,
+}
+
+/// Persisted choice.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SelectedVariant {
+    variant: Option<String>,
+}
+
+// ==================== Manifest & Hardware ====================
+
+fn load_manifest(app: &AppHandle) -> Option<VariantManifest> {
+    let path = app.path().resource_dir().ok()?.join(ENGINE_VARIANTS_MANIFEST);
+    let contents = std::fs::read_to_string(&path).ok()?;
+    serde_json::from_str(&contents)
+        .map_err(|e| println!("Invalid engine variants manifest {:?}: {}", path, e))
+        .ok()
+}
+
+/// Path of a variant's binary.
+fn variant_binary(variant: &EngineVariant) -> Result<PathBuf, String> {
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("Failed to locate app executable: {}", e))?;
+    let dir = exe.parent()
+        .ok_or_else(|| format!("App executable {:?} has no parent directory", exe))?;
+    Ok(dir.join(format!("{}{}", variant.binary, std::env::consts::EXE_SUFFIX)))
+}
+
+/// Whether the GPU runtime `gpu` is available on this machine.
+fn has_gpu_runtime(gpu: &str) -> Result<bool, String> {
+    match gpu {
+        "cuda" => Ok(if cfg!(target_os = "linux") {
+            std::path::Path::new("/proc/driver/nvidia/version").exists()
+        } else if cfg!(windows) {
+            let system_root = std::env::var("SystemRoot").unwrap_or_else(|_| r"C:\Windows".to_string());
+            std::path::Path::new(&system_root).join("System32").join("nvcuda.dll").exists()
+        } else {
+            false
+        }),
+        "rocm" => Ok(cfg!(target_os = "linux") && std::path::Path::new("/dev/kfd").exists()),
+        "metal" => Ok(cfg!(target_os = "macos")),
+        other => Err(format!("unknown GPU runtime '{}'", other)),
+    }
+}
+
+/// The first GPU runtime available on this machine, if any.
+pub(crate) fn detected_gpu() -> Option<&'static str> {
+    ["cuda", "rocm", "metal"].into_iter().find(|gpu| has_gpu_runtime(gpu).unwrap_or(false))
+}
+
+/// Why this machine can't run a variant, if it can't.
+fn unsupported_reason(requires: &HardwareRequirements, total_memory_mb: u64) -> Option<String> {
+    if let Some(gpu) = &requires.gpu {
+        match has_gpu_runtime(gpu) {
+            Ok(true) => {}
+            Ok(false) => return Some(format!("requires a {} GPU", gpu)),
+            Err(e) => return Some(e),
+        }
+    }
+    match requires.min_memory_mb {
+        Some(required) if total_memory_mb < required => {
+            Some(format!("requires {} MB of memory, this machine has {} MB", required, total_memory_mb))
+        }
+        _ => None,
+    }
+}
+
+fn total_memory_mb() -> u64 {
+    let system = System::new_with_specifics(RefreshKind::nothing().with_memory(MemoryRefreshKind::nothing().with_ram()));
+    system.total_memory() / (1024 * 1024)
+}
+
+// ==================== Selection ====================
+
+fn selection_file(app: &AppHandle) -> PathBuf {
+    app.path().app_local_data_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join("ai-engine"))
+        .join(SELECTED_VARIANT_FILE)
+}
+
+fn load_selection(app: &AppHandle) -> Option<String> {
+    atomic_file::load_json::<SelectedVariant>(&selection_file(app)).report(app)?.variant
+}
+
+fn save_selection(app: &AppHandle, variant: &str) -> Result<(), String> {
+    atomic_file::write_json(&selection_file(app), &SelectedVariant { variant: Some(variant.to_string()) })
+}
+
+/// Binary to start instead of the regular onedir build / sidecar, if a non-default variant is selected.
+pub(crate) fn selected_binary(app: &AppHandle) -> Result<Option<PathBuf>, String> {
+    let (Some(manifest), Some(selected)) = (load_manifest(app), load_selection(app)) else {
+        return Ok(None);
+    };
+    if selected == manifest.default {
+        return Ok(None);
+    }
+    let Some(variant) = manifest.variants.iter().find(|v| v.name == selected) else {
+        println!("Selected engine variant '{}' is no longer bundled, using the default", selected);
+        return Ok(None);
+    };
+    let path = variant_binary(variant)?;
+    if !path.is_file() {
+        return Err(format!("binary of engine variant '{}' not found at {:?}", selected, path));
+    }
+    println!("Engine variant: {}", selected);
+    Ok(Some(path))
+}
+
+pub(crate) fn describe(app: &AppHandle) -> Vec<EngineVariantInfo> {
+    let Some(manifest) = load_manifest(app) else {
+        return Vec::new();
+    };
+    let selected = load_selection(app).unwrap_or_else(|| manifest.default.clone());
+    let memory_mb = total_memory_mb();
+    manifest.variants.into_iter()
+        .map(|variant| {
+            let unsupported_reason = unsupported_reason(&variant.requires, memory_mb);
+            EngineVariantInfo {
+                installed: variant_binary(&variant).is_ok_and(|path| path.is_file()),
+                supported: unsupported_reason.is_none(),
+                unsupported_reason,
+                default: variant.name == manifest.default,
+                selected: variant.name == selected,
+                variant,
+            }
+        })
+        .collect()
+}
+
+// ==================== Tauri Commands ====================
+
+/// List the bundled engine variants (empty without a manifest).
+#[tauri::command]
+#[specta::specta]
+pub async fn list_engine_variants(app: AppHandle) -> Result<Vec<EngineVariantInfo>, EngineError> {
+    Ok(describe(&app))
+}
+
+/// Select the engine variant to run on this machine; restarts a running engine on it.
+#[tauri::command]
+#[specta::specta]
+pub async fn select_engine_variant(
+    app: AppHandle,
+    name: String,
+    state: State<'_, Mutex<PythonProcess>>,
+) -> Result<Timed<EngineVariantInfo>, EngineError> {
+    let timer = CommandTimer::start(&app, "select_engine_variant", CommandClass::Lifecycle);
+    let Some(info) = describe(&app).into_iter().find(|info| info.variant.name == name) else {
+        return Err(EngineError::InvalidRequest(format!("Unknown engine variant '{}'", name)));
+    };
+    if !info.installed {
+        return Err(EngineError::InvalidRequest(format!("Engine variant '{}' is not installed", name)));
+    }
+    if let Some(reason) = &info.unsupported_reason {
+        return Err(EngineError::InvalidRequest(format!("Engine variant '{}' {}", name, reason)));
+    }
+    save_selection(&app, &name)?;
+    println!("Selected engine variant '{}'", name);
+
+    let is_running = *state.lock().await.is_running.lock().await;
+    if is_running && !info.selected {
+        timer.phase("restarting").await;
+        teardown_engine(&mut *state.lock().await, true).await;
+        start_engine(&app, &timer).await?;
+    }
+    Ok(timer.finish(EngineVariantInfo { selected: true, ..info }))
+}
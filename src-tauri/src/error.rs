@@ -0,0 +1,319 @@
+//! =============================================================================
+//! Engine Errors
+//! =============================================================================
+//!
+//! Every Tauri command fails with an `EngineError`, serialized for the
+//! frontend as
+//!
+//!   { "kind": "socket_unavailable", "message": "Engine socket unavailable: …" }
+//!
+//! so the UI can branch on `kind` ("not_running" → offer to start the engine,
+//! "timeout" → offer to retry, ...) and show `message` as is.
+//!
+//! Socket errors also carry the translated platform error and what the user
+//! can do about it, instead of a raw "Connection refused (os error 61)":
+//!
+//!   { "kind": "socket_unavailable", "reason": "refused",
+//!     "message": "The AI Engine isn't accepting connections (…)",
+//!     "action": "Start the engine, or restart it if it is already running." }
+//!
+//! `reason` and `action` are null for the other kinds.
+//!
+//! An error status from the engine (4xx/5xx) becomes `http_error`, with the
+//! status code and, if the engine answered with a JSON body, that body as
+//! `details`; `message` carries the engine's own error text (its "error",
+//! "detail" or "message" field, or the title of an HTML error page):
+//!
+//!   { "kind": "http_error", "status": 422,
+//!     "message": "AI Engine returned HTTP 422: input is empty",
+//!     "details": { "error": "input is empty", "field": "input" } }
+//!
+//! A gated model whose license wasn't accepted fails with `license_required`,
+//! naming the license in `details`:
+//!
+//!   { "kind": "license_required",
+//!     "message": "The license for llama-3-8b has to be accepted first",
+//!     "details": { "model": "llama-3-8b", "version_hash": "ab12…" } }
+//!
+//! The bindings type `kind` as the union of these identifiers.
+//!
+//! Internal helpers that still produce `String` errors convert into
+//! `EngineError::Internal` with `?`; the transport layer produces the
+//! specific variants.
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::io;
+
+/// Win32 ERROR_PIPE_BUSY: every instance of the named pipe is in use
+const ERROR_PIPE_BUSY: i32 = 231;
+
+// ==================== Socket Failures ====================
+
+/// Platform socket error behind an `EngineError::SocketUnavailable`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum SocketFailure {
+    /// Nothing listens on the endpoint (ECONNREFUSED)
+    Refused,
+    /// The socket file or named pipe doesn't exist (ENOENT, ERROR_FILE_NOT_FOUND)
+    NotFound,
+    /// The endpoint belongs to another user or is locked down (EACCES, EPERM, ERROR_ACCESS_DENIED)
+    PermissionDenied,
+    /// The engine closed the connection mid-request (EPIPE, ECONNRESET, ERROR_BROKEN_PIPE)
+    ConnectionClosed,
+    /// Every instance of the named pipe is in use (ERROR_PIPE_BUSY)
+    PipeBusy,
+    /// Anything else; the detail has the platform error
+    Other,
+}
+
+impl SocketFailure {
+    /// Translate a platform I/O error.
+    pub fn from_io(error: &io::Error) -> SocketFailure {
+        if cfg!(windows) && error.raw_os_error() == Some(ERROR_PIPE_BUSY) {
+            return SocketFailure::PipeBusy;
+        }
+        match error.kind() {
+            io::ErrorKind::ConnectionRefused => SocketFailure::Refused,
+            io::ErrorKind::NotFound => SocketFailure::NotFound,
+            io::ErrorKind::PermissionDenied => SocketFailure::PermissionDenied,
+            io::ErrorKind::BrokenPipe
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::UnexpectedEof => SocketFailure::ConnectionClosed,
+            _ => SocketFailure::Other,
+        }
+    }
+
+    /// Translate the first I/O error in `error`'s source chain (hyper wraps them).
+    pub fn classify(error: &(dyn std::error::Error + 'static)) -> SocketFailure {
+        let mut current = Some(error);
+        while let Some(error) = current {
+            if let Some(io_error) = error.downcast_ref::<io::Error>() {
+                return SocketFailure::from_io(io_error);
+            }
+            if let Some(hyper_error) = error.downcast_ref::<hyper::Error>() {
+                // An incomplete message means the engine hung up on us
+                if hyper_error.is_incomplete_message() || hyper_error.is_closed() {
+                    return SocketFailure::ConnectionClosed;
+                }
+            }
+            current = error.source();
+        }
+        SocketFailure::Other
+    }
+
+    /// What went wrong, for the user.
+    pub fn explanation(self) -> &'static str {
+        match self {
+            SocketFailure::Refused => "The AI Engine isn't accepting connections",
+            SocketFailure::NotFound => "The AI Engine's socket doesn't exist",
+            SocketFailure::PermissionDenied => "Not allowed to connect to the AI Engine's socket",
+            SocketFailure::ConnectionClosed => "The AI Engine closed the connection",
+            SocketFailure::PipeBusy => "The AI Engine is busy with other connections",
+            SocketFailure::Other => "Couldn't talk to the AI Engine",
+        }
+    }
+
+    /// What the user can try next.
+    pub fn action(self) -> &'static str {
+        match self {
+            SocketFailure::Refused => "Start the engine, or restart it if it is already running.",
+            SocketFailure::NotFound => "Start the engine. If it is running, check the socket path in the settings.",
+            SocketFailure::PermissionDenied => "Quit other copies of the app (possibly under another user), or choose another socket path.",
+            SocketFailure::ConnectionClosed => "Try again. If it keeps happening, the engine may be crashing; restart it and check its logs.",
+            SocketFailure::PipeBusy => "Wait a moment and try again.",
+            SocketFailure::Other => "Restart the engine. If that doesn't help, run the installation check.",
+        }
+    }
+}
+
+// ==================== Engine Error ====================
+
+/// Error returned by every IPC command.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum EngineError {
+    /// The engine process isn't running
+    #[error("AI Engine is not running")]
+    NotRunning,
+    /// The engine binary couldn't be started or never came up
+    #[error("Failed to start AI Engine: {0}")]
+    SpawnFailed(String),
+    /// Connecting to or talking over the engine socket failed
+    #[error("{} ({detail})", .reason.explanation())]
+    SocketUnavailable { reason: SocketFailure, detail: String },
+    /// The engine didn't answer in time
+    #[error("Request to {endpoint} timed out after {timeout_ms} ms")]
+    Timeout { endpoint: String, timeout_ms: u64 },
+    /// The engine speaks a protocol version this app doesn't support
+    #[error("AI Engine is incompatible with this app: {0}")]
+    IncompatibleEngine(String),
+    /// The engine answered with an error status
+    #[error("AI Engine returned HTTP {status}: {body}")]
+    Http {
+        status: u16,
+        /// The engine's error text (summarized for non-JSON bodies)
+        body: String,
+        /// The error body, if it was JSON
+        details: Option<serde_json::Value>,
+    },
+    /// The engine answered with an unparseable or unexpected body
+    #[error("Invalid response from AI Engine: {0}")]
+    BadResponse(String),
+    /// The request was cancelled with `cancel_request`
+    #[error("Request {0} was cancelled")]
+    Cancelled(String),
+    /// The engine is draining or the app is exiting
+    #[error("AI Engine is shutting down and not accepting new requests")]
+    ShuttingDown,
+    /// The command's arguments were rejected
+    #[error("{0}")]
+    InvalidRequest(String),
+    /// The feature was switched off in `settings.features` (see feature_flags)
+    #[error("{0} is disabled")]
+    FeatureDisabled(String),
+    /// The model is gated and its current license wasn't accepted (see licenses)
+    #[error("The license for {model} has to be accepted first")]
+    LicenseRequired { model: String, version_hash: String },
+    /// Anything else (file system, settings, ...)
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl EngineError {
+    /// A socket error, translated from the platform error in `error`.
+    pub fn socket(context: &str, error: &(dyn std::error::Error + 'static)) -> EngineError {
+        EngineError::SocketUnavailable {
+            reason: SocketFailure::classify(error),
+            detail: format!("{}: {}", context, error),
+        }
+    }
+
+    /// An error status from the engine, with whatever error detail its `body` carries.
+    pub fn http(status: u16, body: &[u8]) -> EngineError {
+        /// Longest error text kept from a non-JSON body
+        const MAX_ERROR_TEXT: usize = 300;
+
+        if let Ok(details) = serde_json::from_slice::<serde_json::Value>(body) {
+            let message = ["error", "detail", "message"].iter()
+                .find_map(|key| details.get(key).and_then(|v| v.as_str()))
+                .map(str::to_string)
+                .unwrap_or_else(|| details.to_string());
+            return EngineError::Http { status, body: message, details: Some(details) };
+        }
+        let text = String::from_utf8_lossy(body);
+        let text = text.trim();
+        let lower = text.to_ascii_lowercase();
+        let summary = if lower.starts_with("<!doctype html") || lower.starts_with("<html") {
+            // Don't show markup; the page title usually names the error
+            let title = lower.find("<title>")
+                .and_then(|start| lower[start..].find("</title>").map(|end| (start + "<title>".len(), start + end)))
+                .map(|(start, end)| text[start..end].trim().to_string());
+            format!("HTML error page{}", title.map(|title| format!(" \"{}\"", title)).unwrap_or_default())
+        } else if text.is_empty() {
+            hyper::StatusCode::from_u16(status).ok().and_then(|s| s.canonical_reason()).unwrap_or("no details").to_string()
+        } else {
+            text.chars().take(MAX_ERROR_TEXT).collect()
+        };
+        EngineError::Http { status, body: summary, details: None }
+    }
+
+    /// The engine dropped a connection we were still using.
+    pub fn connection_closed(detail: String) -> EngineError {
+        EngineError::SocketUnavailable { reason: SocketFailure::ConnectionClosed, detail }
+    }
+
+    /// Stable identifier the frontend branches on.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            EngineError::NotRunning => "not_running",
+            EngineError::SpawnFailed(_) => "spawn_failed",
+            EngineError::SocketUnavailable { .. } => "socket_unavailable",
+            EngineError::Timeout { .. } => "timeout",
+            EngineError::IncompatibleEngine(_) => "incompatible_engine",
+            EngineError::Http { .. } => "http_error",
+            EngineError::BadResponse(_) => "bad_response",
+            EngineError::Cancelled(_) => "cancelled",
+            EngineError::ShuttingDown => "shutting_down",
+            EngineError::InvalidRequest(_) => "invalid_request",
+            EngineError::FeatureDisabled(_) => "feature_disabled",
+            EngineError::LicenseRequired { .. } => "license_required",
+            EngineError::Internal(_) => "internal",
+        }
+    }
+}
+
+impl Serialize for EngineError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let reason = match self {
+            EngineError::SocketUnavailable { reason, .. } => Some(*reason),
+            _ => None,
+        };
+        let (status, details) = match self {
+            EngineError::Http { status, details, .. } => (Some(*status), details.clone()),
+            EngineError::LicenseRequired { model, version_hash } => {
+                (None, Some(serde_json::json!({ "model": model, "version_hash": version_hash })))
+            }
+            _ => (None, None),
+        };
+        let mut state = serializer.serialize_struct("EngineError", 6)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("reason", &reason)?;
+        state.serialize_field("action", &reason.map(SocketFailure::action))?;
+        state.serialize_field("status", &status)?;
+        state.serialize_field("details", &details)?;
+        state.end()
+    }
+}
+
+/// Wire shape of `EngineError` for the TypeScript bindings.
+#[derive(specta::Type)]
+#[specta(remote = EngineError, rename = "EngineError")]
+#[allow(dead_code)]
+struct EngineErrorShape {
+    kind: ErrorKind,
+    message: String,
+    /// Set for `socket_unavailable`
+    reason: Option<SocketFailure>,
+    /// Suggested next step, set for `socket_unavailable`
+    action: Option<String>,
+    /// HTTP status, set for `http_error`
+    status: Option<u16>,
+    /// JSON error body from the engine, set for `http_error` when there is
+    /// one; the license's model and version_hash for `license_required`
+    details: Option<serde_json::Value>,
+}
+
+/// The `kind` values, as returned by `EngineError::kind`.
+#[derive(specta::Type)]
+#[serde(rename_all = "snake_case")]
+#[allow(dead_code)]
+enum ErrorKind {
+    NotRunning,
+    SpawnFailed,
+    SocketUnavailable,
+    Timeout,
+    IncompatibleEngine,
+    HttpError,
+    BadResponse,
+    Cancelled,
+    ShuttingDown,
+    InvalidRequest,
+    FeatureDisabled,
+    LicenseRequired,
+    Internal,
+}
+
+impl From<String> for EngineError {
+    fn from(message: String) -> Self {
+        EngineError::Internal(message)
+    }
+}
+
+impl From<EngineError> for String {
+    fn from(error: EngineError) -> Self {
+        error.to_string()
+    }
+}
@@ -0,0 +1,722 @@
+//! =============================================================================
+//! Event Catalog
+//! =============================================================================
+//!
+//! Every event the backend emits to the frontend is declared here: its name
+//! constant and its serde payload type. Emissions go through `emit()`, which
+//! only accepts types implementing `Event`, so event names can't be typo'd
+//! and payload shapes can't drift between call sites.
+//!
+//! Payloads are sent as structured JSON (not pre-serialized strings).
+//!
+//! The last RECENT_EVENTS_CAPACITY events (except the high-volume status and
+//! log events) are also kept with an increasing `seq`, so a reloaded webview
+//! can catch up on what it missed (see resync).
+//!
+//! Payload types derive `specta::Type`; together with their names they end
+//! up in the generated TypeScript bindings (see lib.rs).
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Emitter};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::atomic_file::Recovery;
+use crate::backup::{BackupInfo, BackupTrigger};
+use crate::crash_supervisor::CrashCause;
+use crate::deprecations::DeprecationNotice;
+use crate::engine_logs::EngineLogLine;
+use crate::engine_metrics::EngineMetrics;
+use crate::engine_queue::EngineTask;
+use crate::engine_state::EngineState;
+use crate::host_requests::HostRequest;
+use crate::input_limiter::QueuePosition;
+use crate::jobs::JobStatus;
+use crate::network_activity::NetworkMode;
+use crate::pipelines::PipelineRun;
+use crate::prefetch::PrefetchStatus;
+use crate::recorder::{self, Frame};
+use crate::repair::RepairStep;
+use crate::replay::ReplaySummary;
+use crate::resources::EngineResources;
+use crate::templates::ValidationError;
+
+/// Events kept for `resync` after a webview reload
+const RECENT_EVENTS_CAPACITY: usize = 200;
+
+// ==================== Event Names ====================
+
+pub const PYTHON_STATUS: &str = "python_status";
+pub const PYTHON_STATUS_DELTA: &str = "python_status_delta";
+pub const PYTHON_INPUT: &str = "python_input";
+pub const TURBO_STARTED: &str = "turbo_started";
+pub const TURBO_ENDED: &str = "turbo_ended";
+pub const COMMAND_SLOW: &str = "command_slow";
+pub const ENGINE_DRAIN_PROGRESS: &str = "engine_drain_progress";
+pub const HOST_REQUEST: &str = "host_request";
+pub const HOST_REQUEST_EXPIRED: &str = "host_request_expired";
+pub const MODEL_DOWNGRADED: &str = "model_downgraded";
+pub const ARTIFACT_SAVE_PROGRESS: &str = "artifact_save_progress";
+pub const TEMPLATES_RELOADED: &str = "templates_reloaded";
+pub const STATUS_SUMMARY_CHANGED: &str = "status_summary_changed";
+pub const ENGINE_CRASHED: &str = "engine_crashed";
+pub const ENGINE_QUEUE_CHANGED: &str = "engine_queue_changed";
+pub const ENGINE_STARTUP_PROGRESS: &str = "engine_startup_progress";
+pub const ENGINE_LOG: &str = "engine_log";
+pub const PROVIDER_LOGIN_FINISHED: &str = "provider_login_finished";
+pub const SESSION_MODEL_SWITCHED: &str = "session_model_switched";
+pub const REPLAY_FINISHED: &str = "replay_finished";
+pub const ENGINE_NETWORK_ACTIVITY: &str = "engine_network_activity";
+pub const JOB_UPDATED: &str = "job_updated";
+pub const INSTALLATION_PROBLEMS: &str = "installation_problems";
+pub const ENGINE_STATE_CHANGED: &str = "engine_state_changed";
+pub const DOWNLOAD_PROGRESS: &str = "download_progress";
+pub const STREAM_TRUNCATED: &str = "stream_truncated";
+pub const BACKUP_CREATED: &str = "backup_created";
+pub const BACKUP_FAILED: &str = "backup_failed";
+pub const ENGINE_RESOURCES: &str = "engine_resources";
+pub const QUEUE_DEPTH: &str = "queue_depth";
+pub const SELECTION_RECEIVED: &str = "selection_received";
+pub const SELECTION_ANSWERED: &str = "selection_answered";
+pub const SETTINGS_RECOVERED: &str = "settings_recovered";
+pub const STREAM_RECORD_SKIPPED: &str = "stream_record_skipped";
+pub const ENGINE_EVENT: &str = "engine_event";
+pub const ENGINE_METRICS: &str = "engine_metrics";
+pub const PREFETCH_PROGRESS: &str = "prefetch_progress";
+pub const MODEL_READY: &str = "model_ready";
+pub const MODEL_CHANGED: &str = "model_changed";
+pub const PIPELINE_PROGRESS: &str = "pipeline_progress";
+pub const MODEL_DOWNLOAD_PROGRESS: &str = "model_download_progress";
+pub const INPUT_QUEUED: &str = "input_queued";
+pub const DEPRECATION_NOTICE: &str = "deprecation_notice";
+
+// ==================== Emission ====================
+
+/// A payload type bound to its event name.
+///
+/// Every implementor must also be listed in `typed_events!` below, which
+/// registers it for the TypeScript bindings (enforced by the supertrait).
+pub trait Event: Serialize + Clone + tauri_specta::Event {
+    const NAME: &'static str;
+    /// Kept in the recent-events buffer replayed by `resync`
+    const BUFFERED: bool = true;
+}
+
+/// An emitted event as replayed by `resync`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct BufferedEvent {
+    /// Increasing emission number
+    pub seq: u64,
+    pub name: &'static str,
+    pub payload: serde_json::Value,
+    pub at_ms: u64,
+}
+
+/// Most recent buffered events, oldest first
+static RECENT_EVENTS: Mutex<VecDeque<BufferedEvent>> = Mutex::new(VecDeque::new());
+
+static NEXT_EVENT_SEQ: AtomicU64 = AtomicU64::new(1);
+
+/// Buffered events emitted after `since_seq` (all of them if None).
+pub(crate) fn recent_since(since_seq: Option<u64>) -> Vec<BufferedEvent> {
+    let recent = RECENT_EVENTS.lock().unwrap_or_else(|e| e.into_inner());
+    recent.iter()
+        .filter(|event| since_seq.is_none_or(|since| event.seq > since))
+        .cloned()
+        .collect()
+}
+
+/// Emit a typed event to the frontend. Failures are logged, never fatal.
+pub fn emit<E: Event>(app: &AppHandle, event: E) {
+    recorder::record_with(|| Frame::Event {
+        name: <E as Event>::NAME.to_string(),
+        payload: serde_json::to_value(&event).unwrap_or_default(),
+    });
+    if E::BUFFERED {
+        let buffered = BufferedEvent {
+            seq: NEXT_EVENT_SEQ.fetch_add(1, Ordering::SeqCst),
+            name: <E as Event>::NAME,
+            payload: serde_json::to_value(&event).unwrap_or_default(),
+            at_ms: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0),
+        };
+        let mut recent = RECENT_EVENTS.lock().unwrap_or_else(|e| e.into_inner());
+        if recent.len() == RECENT_EVENTS_CAPACITY {
+            recent.pop_front();
+        }
+        recent.push_back(buffered);
+    }
+    if let Err(e) = app.emit(<E as Event>::NAME, event) {
+        println!("Failed to emit {}: {}", <E as Event>::NAME, e);
+    }
+}
+
+// ==================== Payload Types ====================
+
+/// Full status snapshot polled from the engine's /status endpoint (see status_delta).
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct PythonStatus {
+    pub version: u64,
+    pub status: serde_json::Value,
+}
+
+impl Event for PythonStatus {
+    const NAME: &'static str = PYTHON_STATUS;
+    // resync returns the full status instead
+    const BUFFERED: bool = false;
+}
+
+/// RFC 6902 patch turning status `base_version` into status `version`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct PythonStatusDelta {
+    pub version: u64,
+    pub base_version: u64,
+    /// RFC 6902 operations
+    #[specta(type = Vec<serde_json::Value>)]
+    pub patch: json_patch::Patch,
+}
+
+impl Event for PythonStatusDelta {
+    const NAME: &'static str = PYTHON_STATUS_DELTA;
+    // resync returns the full status instead
+    const BUFFERED: bool = false;
+}
+
+/// Engine response to a user input.
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(transparent)]
+pub struct PythonInput(pub serde_json::Value);
+
+impl Event for PythonInput {
+    const NAME: &'static str = PYTHON_INPUT;
+}
+
+/// Turbo mode window opened.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct TurboStarted {
+    pub duration_secs: u64,
+    pub threads: usize,
+}
+
+impl Event for TurboStarted {
+    const NAME: &'static str = TURBO_STARTED;
+}
+
+/// Turbo mode window closed and defaults were restored.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct TurboEnded {}
+
+impl Event for TurboEnded {
+    const NAME: &'static str = TURBO_ENDED;
+}
+
+/// A command exceeded its execution budget and is still running.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct CommandSlow {
+    pub command: &'static str,
+    pub phase: &'static str,
+    pub elapsed_ms: u64,
+    pub budget_ms: u64,
+}
+
+impl Event for CommandSlow {
+    const NAME: &'static str = COMMAND_SLOW;
+}
+
+/// Progress while `stop_engine` waits for in-flight requests.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct EngineDrainProgress {
+    pub in_flight: usize,
+    pub elapsed_ms: u64,
+    pub deadline_ms: u64,
+}
+
+impl Event for EngineDrainProgress {
+    const NAME: &'static str = ENGINE_DRAIN_PROGRESS;
+}
+
+/// The engine asked the host for something (see host_requests).
+impl Event for HostRequest {
+    const NAME: &'static str = HOST_REQUEST;
+}
+
+/// A host request was not answered before its deadline.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct HostRequestExpired {
+    pub id: String,
+}
+
+impl Event for HostRequestExpired {
+    const NAME: &'static str = HOST_REQUEST_EXPIRED;
+}
+
+/// A smaller model tier was selected because the preferred one doesn't fit in memory.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ModelDowngraded {
+    pub requested: String,
+    pub selected: String,
+    pub available_mb: u64,
+    pub required_mb: u64,
+}
+
+impl Event for ModelDowngraded {
+    const NAME: &'static str = MODEL_DOWNGRADED;
+}
+
+/// Download progress while `save_artifact` runs.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ArtifactSaveProgress {
+    pub id: String,
+    pub bytes_written: u64,
+    pub total_bytes: u64,
+}
+
+impl Event for ArtifactSaveProgress {
+    const NAME: &'static str = ARTIFACT_SAVE_PROGRESS;
+}
+
+/// Templates or filter rules changed on disk and were reloaded.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct TemplatesReloaded {
+    pub templates: usize,
+    pub rules: usize,
+    /// Files that failed validation (their last good version stays active)
+    pub errors: Vec<ValidationError>,
+}
+
+impl Event for TemplatesReloaded {
+    const NAME: &'static str = TEMPLATES_RELOADED;
+}
+
+/// The accessible status sentence changed.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct StatusSummaryChanged {
+    pub summary: String,
+}
+
+impl Event for StatusSummaryChanged {
+    const NAME: &'static str = STATUS_SUMMARY_CHANGED;
+}
+
+/// The engine process exited or lost its socket unexpectedly.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct EngineCrashed {
+    pub cause: CrashCause,
+    pub code: Option<i32>,
+    pub signal: Option<i32>,
+    pub will_restart: bool,
+    pub restart_in_ms: Option<u64>,
+}
+
+impl Event for EngineCrashed {
+    const NAME: &'static str = ENGINE_CRASHED;
+}
+
+/// The engine's task queue changed (tasks ordered by position).
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct EngineQueueChanged {
+    pub tasks: Vec<EngineTask>,
+}
+
+impl Event for EngineQueueChanged {
+    const NAME: &'static str = ENGINE_QUEUE_CHANGED;
+}
+
+/// Engine startup phase while waiting for its socket.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct EngineStartupProgress {
+    /// "unpacking_runtime" or "loading_model"
+    pub phase: &'static str,
+    /// Size of the unpacked runtime so far, for onefile builds
+    pub unpacked_mb: Option<u64>,
+}
+
+impl Event for EngineStartupProgress {
+    const NAME: &'static str = ENGINE_STARTUP_PROGRESS;
+}
+
+/// A line of engine stdout/stderr.
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(transparent)]
+pub struct EngineLog(pub EngineLogLine);
+
+impl Event for EngineLog {
+    const NAME: &'static str = ENGINE_LOG;
+    // Too chatty; the log has its own buffer (get_engine_logs)
+    const BUFFERED: bool = false;
+}
+
+/// An OAuth device login started with `start_provider_login` ended.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ProviderLoginFinished {
+    pub provider: String,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+impl Event for ProviderLoginFinished {
+    const NAME: &'static str = PROVIDER_LOGIN_FINISHED;
+}
+
+/// A session switched models; engine-side context of earlier messages isn't carried over.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct SessionModelSwitched {
+    pub session_id: String,
+    /// Model that answered the session's last message
+    pub previous_model: String,
+    pub model: String,
+}
+
+impl Event for SessionModelSwitched {
+    const NAME: &'static str = SESSION_MODEL_SWITCHED;
+}
+
+/// A replay started with `replay_recording` has finished.
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(transparent)]
+pub struct ReplayFinished(pub ReplaySummary);
+
+impl Event for ReplayFinished {
+    const NAME: &'static str = REPLAY_FINISHED;
+}
+
+/// The engine was seen connected to an internet or undeclared address.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct EngineNetworkActivity {
+    /// Remote address and port
+    pub remote: String,
+    pub external: bool,
+    /// Not covered by the engine's declared network mode
+    pub undeclared: bool,
+    /// The engine's declared mode (None if it made no declaration)
+    pub declared_mode: Option<NetworkMode>,
+}
+
+impl Event for EngineNetworkActivity {
+    const NAME: &'static str = ENGINE_NETWORK_ACTIVITY;
+}
+
+/// A job changed state or queue position.
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(transparent)]
+pub struct JobUpdated(pub JobStatus);
+
+impl Event for JobUpdated {
+    const NAME: &'static str = JOB_UPDATED;
+}
+
+/// The startup preflight found parts of the installation broken.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct InstallationProblems {
+    /// The checks that failed (see `repair_installation`)
+    pub steps: Vec<RepairStep>,
+}
+
+impl Event for InstallationProblems {
+    const NAME: &'static str = INSTALLATION_PROBLEMS;
+}
+
+/// The engine moved to a new lifecycle state.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct EngineStateChanged {
+    pub state: EngineState,
+    pub previous: EngineState,
+    pub reason: String,
+    pub at_ms: u64,
+}
+
+impl Event for EngineStateChanged {
+    const NAME: &'static str = ENGINE_STATE_CHANGED;
+}
+
+/// Progress of a `download_from_python` transfer.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct DownloadProgress {
+    pub request_id: String,
+    pub endpoint: String,
+    pub bytes_written: u64,
+    /// From Content-Length; None if the engine didn't send one
+    pub total_bytes: Option<u64>,
+}
+
+impl Event for DownloadProgress {
+    const NAME: &'static str = DOWNLOAD_PROGRESS;
+}
+
+/// A streamed answer was cut off before the engine finished it.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct StreamTruncated {
+    pub stream_id: u64,
+    pub request_id: String,
+    pub received_bytes: u64,
+    /// Length announced by the engine's trailer, if one arrived
+    pub expected_bytes: Option<u64>,
+    pub reason: String,
+}
+
+impl Event for StreamTruncated {
+    const NAME: &'static str = STREAM_TRUNCATED;
+}
+
+/// A backup was written.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct BackupCreated {
+    pub backup: BackupInfo,
+    pub trigger: BackupTrigger,
+}
+
+impl Event for BackupCreated {
+    const NAME: &'static str = BACKUP_CREATED;
+}
+
+/// A backup attempt failed.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct BackupFailed {
+    pub error: String,
+    pub trigger: BackupTrigger,
+}
+
+impl Event for BackupFailed {
+    const NAME: &'static str = BACKUP_FAILED;
+}
+
+/// Periodic CPU and memory sample of the engine.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct EngineResourcesSampled {
+    pub resources: EngineResources,
+}
+
+impl Event for EngineResourcesSampled {
+    const NAME: &'static str = ENGINE_RESOURCES;
+}
+
+/// Inputs waiting for and holding an /input slot changed.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct QueueDepth {
+    /// Inputs waiting for a slot
+    pub pending: usize,
+    pub running: usize,
+    /// settings.engine.max_concurrent_inputs in effect
+    pub limit: usize,
+}
+
+impl Event for QueueDepth {
+    const NAME: &'static str = QUEUE_DEPTH;
+}
+
+/// A waiting input's position in the /input queue changed.
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(transparent)]
+pub struct InputQueued(pub QueuePosition);
+
+impl Event for InputQueued {
+    const NAME: &'static str = INPUT_QUEUED;
+    const BUFFERED: bool = false;
+}
+
+/// Text sent from the OS context menu is being asked about.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct SelectionReceived {
+    /// Correlation id of the input (also accepted by cancel_request)
+    pub request_id: String,
+    pub selection: String,
+}
+
+impl Event for SelectionReceived {
+    const NAME: &'static str = SELECTION_RECEIVED;
+}
+
+/// The engine answered (or failed to answer) a context-menu selection.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct SelectionAnswered {
+    pub request_id: String,
+    /// The /input response, as in `python_input`
+    pub response: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+impl Event for SelectionAnswered {
+    const NAME: &'static str = SELECTION_ANSWERED;
+}
+
+/// A corrupt settings (or other state) file was restored on load.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct SettingsRecovered {
+    pub recovery: Recovery,
+}
+
+impl Event for SettingsRecovered {
+    const NAME: &'static str = SETTINGS_RECOVERED;
+}
+
+/// A malformed or oversized record in an engine stream was skipped.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct StreamRecordSkipped {
+    /// Stream the record came from, e.g. "/input" or "/events"
+    pub endpoint: String,
+    /// Correlation id of the streamed input, if any
+    pub request_id: Option<String>,
+    pub reason: String,
+}
+
+impl Event for StreamRecordSkipped {
+    const NAME: &'static str = STREAM_RECORD_SKIPPED;
+}
+
+/// An event pushed by the engine over its /events stream.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct EngineEvent {
+    pub name: String,
+    pub data: serde_json::Value,
+}
+
+impl Event for EngineEvent {
+    const NAME: &'static str = ENGINE_EVENT;
+}
+
+/// Periodic rolling latency statistics of engine requests.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct EngineMetricsSampled {
+    pub metrics: EngineMetrics,
+}
+
+impl Event for EngineMetricsSampled {
+    const NAME: &'static str = ENGINE_METRICS;
+    // Superseded every interval; get_engine_metrics returns the current one
+    const BUFFERED: bool = false;
+}
+
+/// The default model prefetch changed state or made progress.
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(transparent)]
+pub struct PrefetchProgress(pub PrefetchStatus);
+
+impl Event for PrefetchProgress {
+    const NAME: &'static str = PREFETCH_PROGRESS;
+}
+
+/// The engine reports a model resident in memory (after `preload_model` or on its own).
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ModelReady {
+    pub model: String,
+    /// Time from `preload_model` until the model was resident, if it was preloaded
+    pub load_ms: Option<u64>,
+}
+
+impl Event for ModelReady {
+    const NAME: &'static str = MODEL_READY;
+}
+
+/// The engine's loaded model changed (see models).
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ModelChanged {
+    pub previous: Option<String>,
+    /// None once no model is loaded
+    pub current: Option<String>,
+}
+
+impl Event for ModelChanged {
+    const NAME: &'static str = MODEL_CHANGED;
+}
+
+/// A pipeline run or one of its steps changed state.
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(transparent)]
+pub struct PipelineProgress(pub PipelineRun);
+
+impl Event for PipelineProgress {
+    const NAME: &'static str = PIPELINE_PROGRESS;
+}
+
+/// Bytes of a `download_model` transfer so far.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ModelDownloadProgress {
+    pub request_id: String,
+    pub url: String,
+    /// Where the model ends up once complete
+    pub path: PathBuf,
+    /// Including what an earlier, interrupted download already fetched
+    pub bytes_done: u64,
+    /// None until the server reports a size
+    pub total_bytes: Option<u64>,
+}
+
+impl Event for ModelDownloadProgress {
+    const NAME: &'static str = MODEL_DOWNLOAD_PROGRESS;
+    const BUFFERED: bool = false;
+}
+
+/// The engine announced a deprecation not seen before on this install.
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(transparent)]
+pub struct DeprecationNoticed(pub DeprecationNotice);
+
+impl Event for DeprecationNoticed {
+    const NAME: &'static str = DEPRECATION_NOTICE;
+}
+
+// ==================== TypeScript Bindings ====================
+
+/// Register payload types for the TypeScript bindings under the names they
+/// are emitted with, and collect them for the bindings builder.
+macro_rules! typed_events {
+    ($($payload:ident),* $(,)?) => {
+        $(impl tauri_specta::Event for $payload {
+            const NAME: &'static str = <$payload as Event>::NAME;
+        })*
+
+        /// Every event, for the TypeScript bindings.
+        pub(crate) fn collect() -> tauri_specta::Events {
+            tauri_specta::collect_events![$($payload),*]
+        }
+    };
+}
+
+typed_events![
+    PythonStatus,
+    PythonStatusDelta,
+    PythonInput,
+    TurboStarted,
+    TurboEnded,
+    CommandSlow,
+    EngineDrainProgress,
+    HostRequest,
+    HostRequestExpired,
+    ModelDowngraded,
+    ArtifactSaveProgress,
+    TemplatesReloaded,
+    StatusSummaryChanged,
+    EngineCrashed,
+    EngineQueueChanged,
+    EngineStartupProgress,
+    EngineLog,
+    ProviderLoginFinished,
+    SessionModelSwitched,
+    ReplayFinished,
+    EngineNetworkActivity,
+    JobUpdated,
+    InstallationProblems,
+    EngineStateChanged,
+    DownloadProgress,
+    StreamTruncated,
+    BackupCreated,
+    BackupFailed,
+    EngineResourcesSampled,
+    QueueDepth,
+    SelectionReceived,
+    SelectionAnswered,
+    SettingsRecovered,
+    StreamRecordSkipped,
+    EngineEvent,
+    EngineMetricsSampled,
+    PrefetchProgress,
+    ModelReady,
+    ModelChanged,
+    PipelineProgress,
+    ModelDownloadProgress,
+    InputQueued,
+    DeprecationNoticed,
+];
@@ -0,0 +1,138 @@
+//! =============================================================================
+//! PyInstaller Runtime Extraction
+//! =============================================================================
+//!
+//! A PyInstaller onefile engine unpacks its Python runtime into a fresh
+//! `_MEIxxxxxx` directory in the temp dir before any engine code runs. On a
+//! cold start that takes long enough to look like a hang, so while waiting
+//! for the socket the backend watches for that directory and reports:
+//!
+//!   engine_startup_progress { "phase": "unpacking_runtime", "unpacked_mb": 84 }
+//!   engine_startup_progress { "phase": "loading_model",     "unpacked_mb": 212 }
+//!
+//! Extraction counts as finished once the directory stops growing for
+//! EXTRACTION_SETTLE_MS; if no directory shows up within EXTRACTION_DETECT_MS
+//! (onedir layout, see `get_onedir_engine`) the engine goes straight to
+//! "loading_model". The socket wait doesn't count against its retry budget
+//! while unpacking is in progress.
+
+use tauri::AppHandle;
+use tauri::async_runtime::JoinHandle;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::events::{self, EngineStartupProgress};
+
+// ==================== Configuration Constants ====================
+
+/// Prefix of PyInstaller's extraction directories
+const EXTRACTION_DIR_PREFIX: &str = "_MEI";
+
+/// How often the extraction directory is measured
+const EXTRACTION_POLL_MS: u64 = 250;
+
+/// No extraction directory within this time: nothing to unpack
+const EXTRACTION_DETECT_MS: u64 = 1_000;
+
+/// No growth for this long: extraction finished
+const EXTRACTION_SETTLE_MS: u64 = 1_000;
+
+// ==================== Watcher ====================
+
+/// Watches one engine start for runtime extraction; stops when dropped.
+pub struct ExtractionWatch {
+    unpacking: Arc<AtomicBool>,
+    task: JoinHandle<()>,
+}
+
+impl ExtractionWatch {
+    /// Start watching for an extraction directory created after `spawned_at`.
+    pub fn start(app: &AppHandle, spawned_at: SystemTime) -> ExtractionWatch {
+        let unpacking = Arc::new(AtomicBool::new(false));
+        let app = app.clone();
+        let task_unpacking = unpacking.clone();
+        let task = tauri::async_runtime::spawn(async move {
+            let started = Instant::now();
+            let mut dir: Option<PathBuf> = None;
+            let mut size: u64 = 0;
+            let mut last_growth = Instant::now();
+
+            loop {
+                tokio::time::sleep(Duration::from_millis(EXTRACTION_POLL_MS)).await;
+                if dir.is_none() {
+                    dir = find_extraction_dir(&std::env::temp_dir(), spawned_at);
+                }
+                let Some(dir) = &dir else {
+                    if started.elapsed() >= Duration::from_millis(EXTRACTION_DETECT_MS) {
+                        break;
+                    }
+                    continue;
+                };
+
+                let current = dir_size(dir);
+                if current > size {
+                    size = current;
+                    last_growth = Instant::now();
+                    task_unpacking.store(true, Ordering::SeqCst);
+                    events::emit(&app, EngineStartupProgress {
+                        phase: "unpacking_runtime",
+                        unpacked_mb: Some(size / (1024 * 1024)),
+                    });
+                } else if last_growth.elapsed() >= Duration::from_millis(EXTRACTION_SETTLE_MS) {
+                    println!("Engine runtime unpacked ({} MB) in {:?}", size / (1024 * 1024), dir);
+                    break;
+                }
+            }
+
+            task_unpacking.store(false, Ordering::SeqCst);
+            events::emit(&app, EngineStartupProgress {
+                phase: "loading_model",
+                unpacked_mb: (size > 0).then_some(size / (1024 * 1024)),
+            });
+        });
+        ExtractionWatch { unpacking, task }
+    }
+
+    /// True while the engine is still unpacking its runtime.
+    pub fn is_unpacking(&self) -> bool {
+        self.unpacking.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for ExtractionWatch {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Newest `_MEI*` directory in `temp_dir` created after `since`.
+fn find_extraction_dir(temp_dir: &Path, since: SystemTime) -> Option<PathBuf> {
+    std::fs::read_dir(temp_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(EXTRACTION_DIR_PREFIX))
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok()?;
+            let created = meta.created().or_else(|_| meta.modified()).ok()?;
+            (meta.is_dir() && created >= since).then_some((created, entry.path()))
+        })
+        .max_by_key(|(created, _)| *created)
+        .map(|(_, path)| path)
+}
+
+/// Total size of the files below `dir`.
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
@@ -0,0 +1,207 @@
+//! =============================================================================
+//! Feature Kill Switches
+//! =============================================================================
+//!
+//! A subsystem that misbehaves in the field can be switched off without a
+//! new build, by naming it in `settings.features.disabled`:
+//!
+//!   { "features": { "disabled": ["pipelines", "broker"] } }
+//!
+//! Every command goes through `check_command` before it runs (see the
+//! invoke handler in lib.rs); the commands of a disabled feature fail with
+//! `feature_disabled` instead of running. Work that starts without a
+//! command checks its feature itself: the backup schedule, prefetch resuming
+//! on launch, queued jobs, streamed or templated inputs from jobs and the
+//! broker, engine requests forwarded for the broker, and every pipeline
+//! step, which fails while `pipelines` or `proxy` is disabled (a run in
+//! progress stops at its next step).
+//! Commands that only read state (list_pipelines, get_job_status, ...)
+//! stay available so the UI can still show what exists. Changes apply immediately, like other settings.
+//!
+//! FEATURES lists the features and their commands. `get_feature_flags`
+//! reports the state of each, and the engine capabilities carry the
+//! disabled ones in `disabled_features`.
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use specta::Type;
+use std::collections::BTreeSet;
+use std::sync::RwLock;
+
+use crate::error::EngineError;
+
+/// Names of the features currently switched off
+static DISABLED: RwLock<BTreeSet<&'static str>> = RwLock::new(BTreeSet::new());
+
+// ==================== Registry ====================
+
+/// A subsystem that can be switched off, and the commands that use it.
+struct Feature {
+    name: &'static str,
+    description: &'static str,
+    commands: &'static [&'static str],
+}
+
+/// Every feature with a kill switch
+const FEATURES: &[Feature] = &[
+    Feature {
+        name: "streaming",
+        description: "Token streaming of inputs",
+        commands: &["stream_input_to_python", "resume_stream"],
+    },
+    Feature {
+        name: "uploads",
+        description: "File uploads to the engine",
+        commands: &["send_file_to_python"],
+    },
+    Feature {
+        name: "downloads",
+        description: "Downloads of engine responses",
+        commands: &["download_from_python", "fetch_from_python"],
+    },
+    Feature {
+        name: "proxy",
+        description: "Generic requests to allowlisted engine endpoints",
+        commands: &["call_engine"],
+    },
+    Feature {
+        name: "broker",
+        description: "Automation clients on the broker socket",
+        commands: &[],
+    },
+    Feature {
+        name: "pipelines",
+        description: "Multi-step pipelines",
+        commands: &["save_pipeline", "delete_pipeline", "run_pipeline", "resume_pipeline_run"],
+    },
+    Feature {
+        name: "jobs",
+        description: "Background job queue",
+        commands: &["submit_input"],
+    },
+    Feature {
+        name: "sessions",
+        description: "Conversation sessions",
+        commands: &["create_session", "send_input_to_session", "set_session_model"],
+    },
+    Feature {
+        name: "templates",
+        description: "Prompt templates",
+        commands: &["set_prompt_template", "apply_prompt_template"],
+    },
+    Feature {
+        name: "model_downloads",
+        description: "Model downloads from inside the app",
+        commands: &["download_model"],
+    },
+    Feature {
+        name: "prefetch",
+        description: "Background prefetch of the default model",
+        commands: &["start_prefetch", "resume_prefetch"],
+    },
+    Feature {
+        name: "artifacts",
+        description: "Saving engine artifacts",
+        commands: &["save_artifact"],
+    },
+    Feature {
+        name: "remote_providers",
+        description: "Sign-in to remote providers",
+        commands: &["start_provider_login", "test_provider_credentials"],
+    },
+    Feature {
+        name: "recording",
+        description: "Session recording and replay",
+        commands: &["start_recording", "replay_recording"],
+    },
+    Feature {
+        name: "backup",
+        description: "Backup and restore",
+        commands: &["create_backup_now", "restore_backup"],
+    },
+    Feature {
+        name: "context_menu",
+        description: "OS context menu entry",
+        commands: &["install_context_menu"],
+    },
+    Feature {
+        name: "turbo",
+        description: "Turbo mode",
+        commands: &["enable_turbo"],
+    },
+];
+
+// ==================== Settings ====================
+
+/// Features switched off, persisted under `settings.features`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(default)]
+pub struct FeatureSettings {
+    /// Names of the disabled features (see `get_feature_flags`)
+    pub disabled: Vec<String>,
+}
+
+/// State of one feature, as returned by `get_feature_flags`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct FeatureFlag {
+    pub name: String,
+    pub description: String,
+    pub enabled: bool,
+}
+
+/// Apply the kill switches (on load and whenever settings change).
+pub(crate) fn configure(settings: &FeatureSettings) {
+    let mut disabled = BTreeSet::new();
+    for name in &settings.disabled {
+        match FEATURES.iter().find(|feature| feature.name == name.trim()) {
+            Some(feature) => {
+                disabled.insert(feature.name);
+            }
+            None => println!("Ignoring unknown feature in settings.features.disabled: {}", name),
+        }
+    }
+    let mut current = DISABLED.write().unwrap_or_else(|e| e.into_inner());
+    if *current != disabled {
+        println!("Disabled features: {:?}", disabled);
+        *current = disabled;
+    }
+}
+
+// ==================== Checks ====================
+
+/// Fail with `FeatureDisabled` if `feature` is switched off.
+pub(crate) fn check(feature: &str) -> Result<(), EngineError> {
+    if DISABLED.read().unwrap_or_else(|e| e.into_inner()).contains(feature) {
+        return Err(EngineError::FeatureDisabled(feature.to_string()));
+    }
+    Ok(())
+}
+
+/// Fail with `FeatureDisabled` if `command` belongs to a feature that is switched off.
+pub(crate) fn check_command(command: &str) -> Result<(), EngineError> {
+    match FEATURES.iter().find(|feature| feature.commands.contains(&command)) {
+        Some(feature) => check(feature.name),
+        None => Ok(()),
+    }
+}
+
+/// Names of the features currently switched off.
+pub(crate) fn disabled_features() -> Vec<String> {
+    DISABLED.read().unwrap_or_else(|e| e.into_inner()).iter().map(|name| name.to_string()).collect()
+}
+
+// ==================== Tauri Command: get_feature_flags ====================
+
+/// Return every feature with a kill switch and whether it is enabled.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_feature_flags() -> Result<Vec<FeatureFlag>, EngineError> {
+    let disabled = DISABLED.read().unwrap_or_else(|e| e.into_inner());
+    Ok(FEATURES.iter()
+        .map(|feature| FeatureFlag {
+            name: feature.name.to_string(),
+            description: feature.description.to_string(),
+            enabled: !disabled.contains(feature.name),
+        })
+        .collect())
+}
@@ -0,0 +1,129 @@
+//! =============================================================================
+//! Generation Parameters
+//! =============================================================================
+//!
+//! Sampling settings for /input, typed instead of hand-written into the
+//! payload. Each set field goes into the request body next to the input:
+//!
+//!   { "input": "…", "request_id": "req-4", "temperature": 0.7, "top_p": 0.9,
+//!     "max_tokens": 512, "stop": ["\n\nUser:"], "seed": 42 }
+//!
+//! Fields left unset are left to the engine's own defaults. An input takes
+//! its parameters from `route.generation`, field by field over the app-wide
+//! defaults set with `set_default_generation_params` (kept in memory for the
+//! app's lifetime).
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager, State};
+use tauri::async_runtime::Mutex;
+
+use crate::error::EngineError;
+
+// ==================== Configuration Constants ====================
+
+/// Highest accepted temperature
+const MAX_TEMPERATURE: f32 = 2.0;
+
+/// Most stop sequences per request
+const MAX_STOP_SEQUENCES: usize = 8;
+
+// ==================== Types ====================
+
+/// Sampling settings of a generation; None leaves a field to the engine.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+pub struct GenerationParams {
+    /// 0 is greedy; at most MAX_TEMPERATURE
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    /// Nucleus sampling mass, in (0, 1]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    /// Generation ends before any of these
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+    /// Fixed seed for reproducible sampling
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+impl GenerationParams {
+    /// These parameters, with unset fields taken from `defaults`.
+    fn or(self, defaults: &GenerationParams) -> GenerationParams {
+        GenerationParams {
+            temperature: self.temperature.or(defaults.temperature),
+            top_p: self.top_p.or(defaults.top_p),
+            max_tokens: self.max_tokens.or(defaults.max_tokens),
+            stop: self.stop.or_else(|| defaults.stop.clone()),
+            seed: self.seed.or(defaults.seed),
+        }
+    }
+
+    fn validate(&self) -> Result<(), EngineError> {
+        let invalid = |message: String| Err(EngineError::InvalidRequest(message));
+        if let Some(temperature) = self.temperature.filter(|t| !(0.0..=MAX_TEMPERATURE).contains(t)) {
+            return invalid(format!("temperature must be between 0 and {}, got {}", MAX_TEMPERATURE, temperature));
+        }
+        if let Some(top_p) = self.top_p.filter(|p| !(*p > 0.0 && *p <= 1.0)) {
+            return invalid(format!("top_p must be in (0, 1], got {}", top_p));
+        }
+        if self.max_tokens == Some(0) {
+            return invalid("max_tokens must be greater than zero".to_string());
+        }
+        if let Some(stop) = &self.stop {
+            if stop.len() > MAX_STOP_SEQUENCES {
+                return invalid(format!("At most {} stop sequences are allowed, got {}", MAX_STOP_SEQUENCES, stop.len()));
+            }
+            if stop.iter().any(String::is_empty) {
+                return invalid("Stop sequences must not be empty".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// App-wide defaults under the parameters of each input.
+#[derive(Default)]
+pub struct GenerationDefaults {
+    params: GenerationParams,
+}
+
+// ==================== Request Body ====================
+
+/// Write `params` (over the defaults) into the /input `body`.
+///
+/// Fails on out-of-range values, before anything is sent.
+pub(crate) async fn apply(app: &AppHandle, params: Option<GenerationParams>, body: &mut serde_json::Value) -> Result<(), EngineError> {
+    let state = app.state::<Mutex<GenerationDefaults>>();
+    let params = params.unwrap_or_default().or(&state.lock().await.params);
+    params.validate()?;
+    let fields = serde_json::to_value(&params)
+        .map_err(|e| EngineError::Internal(format!("Failed to serialize generation parameters: {}", e)))?;
+    let serde_json::Value::Object(fields) = fields else {
+        return Ok(());
+    };
+    for (name, value) in fields {
+        body[name] = value;
+    }
+    Ok(())
+}
+
+// ==================== Tauri Commands ====================
+
+/// Set the generation parameters of inputs that don't set their own.
+///
+/// Replaces all defaults (unset fields go back to the engine's); returns
+/// the stored parameters.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_default_generation_params(
+    params: GenerationParams,
+    state: State<'_, Mutex<GenerationDefaults>>,
+) -> Result<GenerationParams, EngineError> {
+    params.validate()?;
+    println!("Default generation parameters: {:?}", params);
+    state.lock().await.params = params.clone();
+    Ok(params)
+}
@@ -0,0 +1,198 @@
+//! =============================================================================
+//! Zero-Downtime Restart
+//! =============================================================================
+//!
+//! `restart_python_script()` replaces a running engine without a gap in
+//! service (e.g. to pick up a new model or engine variant):
+//!
+//!   1. Spawn a new engine on an alternate endpoint while the current one
+//!      keeps serving
+//!   2. Wait for the new engine's socket and /health
+//!   3. Switch the active endpoint, process handle and generation in one
+//!      step; new requests go to the new engine from here on
+//!   4. Wait up to HANDOFF_DRAIN_MS for requests still in flight on the old
+//!      engine, then /stop and kill it
+//!
+//! Engines alternate between the configured endpoint and the same endpoint
+//! with HANDOFF_SUFFIX; if the configured endpoint changed (`set_socket_path`)
+//! the new engine takes it directly. A new engine that never becomes healthy
+//! is killed and the old one keeps running. The lifecycle state stays
+//! Ready/Busy throughout. A stopped engine is simply started, and in replay
+//! mode (single mock endpoint) or with an attached external engine it is
+//! stopped and started again.
+//!
+//! Scheduled recycling (see recycling) replaces the engine the same way.
+
+use tauri::{AppHandle, Manager};
+use tauri::async_runtime::Mutex;
+use tauri_plugin_shell::process::CommandChild;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, SystemTime};
+
+use crate::budget::{CommandClass, CommandTimer, Timed};
+use crate::error::EngineError;
+use crate::extraction::ExtractionWatch;
+use crate::mux::MuxClient;
+use crate::{capabilities, clock_sync, compression, crash_supervisor, dev_engine, drain, engine_auth, engine_events, engine_queue, ipc, network_activity, recycling, replay, resources, socket_watch, stale_engine};
+use crate::{
+    get_socket_path, is_socket_ready, socket_http_post, spawn_engine, spawn_status_loop, start_engine, teardown_engine,
+    wait_for_socket_ready, PythonProcess, ENGINE_START, SHUTDOWN_GRACE_MS,
+};
+
+/// Appended to the configured endpoint for every other engine instance
+const HANDOFF_SUFFIX: &str = ".next";
+
+/// How long the old engine gets to finish requests already sent to it
+const HANDOFF_DRAIN_MS: u64 = 30_000;
+
+/// Endpoint for the engine replacing the one at `current`.
+fn handoff_endpoint(current: &str, configured: &str) -> String {
+    if current == configured {
+        format!("{}{}", configured, HANDOFF_SUFFIX)
+    } else {
+        configured.to_string()
+    }
+}
+
+/// Kill a process that never took over.
+fn discard(child: CommandChild) {
+    if let Err(e) = child.kill() {
+        println!("Failed to kill replacement engine: {}", e);
+    }
+}
+
+/// Start a replacement engine and switch to it; returns the old endpoint and process.
+///
+/// Called with ENGINE_START held.
+async fn hand_over(app: &AppHandle, timer: &CommandTimer) -> Result<(String, Option<Box<dyn std::any::Any + Send>>), EngineError> {
+    let state = app.state::<Mutex<PythonProcess>>();
+    let old_generation = state.lock().await.engine_generation.load(Ordering::SeqCst);
+    let old_endpoint = get_socket_path();
+    let configured = ipc::configured_endpoint(app).await.map_err(EngineError::SpawnFailed)?;
+    let endpoint = handoff_endpoint(&old_endpoint, &configured);
+
+    // A socket file left by an earlier instance would make the new engine fail to bind
+    if cfg!(unix) && std::path::Path::new(&endpoint).exists() && !is_socket_ready(&endpoint).await {
+        let _ = std::fs::remove_file(&endpoint);
+    }
+
+    println!("Restarting AI Engine: {} → {}", old_endpoint, endpoint);
+    timer.phase("spawning").await;
+    let spawned_at = SystemTime::now() - Duration::from_secs(1);
+    let (events_rx, child) = spawn_engine(app, &endpoint).await?;
+
+    let extraction = ExtractionWatch::start(app, spawned_at);
+    let healthy = wait_for_socket_ready(timer, &extraction, &endpoint).await;
+    drop(extraction);
+    if let Err(e) = healthy {
+        discard(child);
+        return Err(EngineError::SpawnFailed(format!("replacement engine: {}", e)));
+    }
+    #[cfg(unix)]
+    crate::restrict_socket_permissions(&endpoint);
+    // Never switch to an engine speaking an unsupported protocol
+    let engine_capabilities = match capabilities::fetch(&endpoint).await {
+        Ok(engine_capabilities) => engine_capabilities,
+        Err(e) => {
+            discard(child);
+            return Err(e);
+        }
+    };
+
+    // Switch over, unless the old engine was stopped or crashed meanwhile
+    timer.phase("switching").await;
+    let pid = child.pid();
+    let mut proc_state = state.lock().await;
+    let still_running = *proc_state.is_running.lock().await;
+    if !still_running || proc_state.engine_generation.load(Ordering::SeqCst) != old_generation {
+        drop(proc_state);
+        discard(child);
+        return Err(EngineError::NotRunning);
+    }
+    let old_child = proc_state.child.replace(Box::new(child));
+    // Retire the old engine's status loop and crash watcher
+    let generation = proc_state.engine_generation.fetch_add(1, Ordering::SeqCst) + 1;
+    ipc::set_active_endpoint(&endpoint);
+    *proc_state.mux.lock().await = None;
+    let mux = proc_state.mux.clone();
+    drop(proc_state);
+
+    crash_supervisor::watch(app.clone(), events_rx, generation);
+    stale_engine::record_pid(app, pid);
+    network_activity::track_process(app, pid).await;
+    resources::track_process(app, pid).await;
+    engine_queue::clear(app).await;
+    *mux.lock().await = MuxClient::negotiate(&endpoint).await;
+    compression::negotiate(&endpoint).await;
+    network_activity::negotiate(app, &endpoint).await;
+    capabilities::store(app, engine_capabilities).await;
+    clock_sync::measure(&endpoint).await;
+    spawn_status_loop(app, generation).await;
+    socket_watch::watch(app, &endpoint, generation);
+    engine_events::subscribe(app, endpoint.clone(), generation);
+    recycling::watch(app, generation);
+    Ok((old_endpoint, old_child))
+}
+
+// ==================== Tauri Command: restart_python_script ====================
+
+/// Restart the AI Engine without interrupting requests.
+///
+/// This command:
+///   1. Starts a replacement engine on an alternate socket
+///   2. Switches to it once it is healthy
+///   3. Lets the old engine finish its requests, then stops it
+///
+/// Returns the restart duration; emits `command_slow` past the lifecycle budget.
+#[tauri::command]
+#[specta::specta]
+pub async fn restart_python_script(app: AppHandle) -> Result<Timed<()>, EngineError> {
+    let timer = CommandTimer::start(&app, "restart_python_script", CommandClass::Lifecycle);
+    let state = app.state::<Mutex<PythonProcess>>();
+    let is_running = *state.lock().await.is_running.lock().await;
+    if !is_running {
+        start_engine(&app, &timer).await?;
+        return Ok(timer.finish(()));
+    }
+    if replay::mock_endpoint().is_some() {
+        teardown_engine(&mut *state.lock().await, true).await;
+        start_engine(&app, &timer).await?;
+        return Ok(timer.finish(()));
+    }
+    if dev_engine::is_attached() {
+        // Detaching forgets the socket; attach to the same one again
+        let socket_path = get_socket_path();
+        teardown_engine(&mut *state.lock().await, true).await;
+        dev_engine::request_attach(Some(socket_path));
+        start_engine(&app, &timer).await?;
+        return Ok(timer.finish(()));
+    }
+
+    swap(&app, &timer).await?;
+    Ok(timer.finish(()))
+}
+
+/// Replace the running engine with a fresh one, then drain and stop the old one.
+///
+/// The old engine keeps serving if the replacement doesn't become healthy.
+pub(crate) async fn swap(app: &AppHandle, timer: &CommandTimer) -> Result<(), EngineError> {
+    let starting = ENGINE_START.lock().await;
+    let (old_endpoint, old_child) = hand_over(app, timer).await?;
+    drop(starting);
+
+    // Requests sent before the switch still go to the old engine
+    timer.phase("draining_old_engine").await;
+    let in_flight = app.state::<Mutex<PythonProcess>>().lock().await.in_flight.clone();
+    let remaining = drain::wait_for_in_flight(app, &in_flight, Duration::from_millis(HANDOFF_DRAIN_MS)).await;
+    if remaining > 0 {
+        println!("{} request(s) still in flight, stopping the old engine anyway", remaining);
+    }
+    let _ = socket_http_post(&old_endpoint, "/stop", &serde_json::json!({})).await;
+    tokio::time::sleep(Duration::from_millis(SHUTDOWN_GRACE_MS)).await;
+    if let Some(Ok(child)) = old_child.map(|child| child.downcast::<CommandChild>()) {
+        let _ = child.kill();
+    }
+    engine_auth::forget(&old_endpoint);
+    println!("AI Engine restarted on {}", get_socket_path());
+    Ok(())
+}
@@ -0,0 +1,186 @@
+//! =============================================================================
+//! Heartbeat for External Watchdogs
+//! =============================================================================
+//!
+//! Lets systemd/launchd (or any external monitor) tell a hung app from one that
+//! has exited. While the app is healthy, a background task periodically:
+//!
+//!   • Rewrites the heartbeat file with the current Unix time and PID
+//!   • Sends WATCHDOG=1 to systemd when NOTIFY_SOCKET is set
+//!
+//! systemd also gets READY=1 the first time the engine is healthy, so a
+//! `Type=notify` unit finishes starting then.
+//!
+//! Healthy means the status polling (supervisor) loop has ticked recently
+//! whenever the engine is running. A stuck poll stops the heartbeat.
+//!
+//! On clean shutdown the task is stopped, the file is removed and systemd
+//! receives STOPPING=1, so a missing file means "exited", a stale one "hung".
+//!
+//! Configuration (environment variables, heartbeat is disabled if neither
+//! is set; a NOTIFY_SOCKET starting with '@' is in the abstract namespace):
+//!   AI_ENGINE_HEARTBEAT_FILE           Path of the heartbeat file
+//!   AI_ENGINE_HEARTBEAT_INTERVAL_SECS  Interval between beats (default 10)
+
+use tauri::{AppHandle, Manager};
+use tauri::async_runtime::{JoinHandle, Mutex};
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::STATUS_POLL_INTERVAL_SECS;
+
+// ==================== Configuration Constants ====================
+
+/// Environment variable holding the heartbeat file path
+const HEARTBEAT_FILE_ENV: &str = "AI_ENGINE_HEARTBEAT_FILE";
+
+/// Environment variable holding the heartbeat interval in seconds
+const HEARTBEAT_INTERVAL_ENV: &str = "AI_ENGINE_HEARTBEAT_INTERVAL_SECS";
+
+/// Socket systemd sets for Type=notify services
+const NOTIFY_SOCKET_ENV: &str = "NOTIFY_SOCKET";
+
+/// Default interval between heartbeats
+const DEFAULT_HEARTBEAT_INTERVAL_SECS: u64 = 10;
+
+/// Supervisor loop is considered hung after this many missed poll intervals
+const SUPERVISOR_STALL_FACTOR: u64 = 5;
+
+// ==================== Heartbeat Writer ====================
+
+/// A running heartbeat task and where it reports to.
+pub struct Heartbeat {
+    file: Option<PathBuf>,
+    notify_socket: Option<PathBuf>,
+    task: JoinHandle<()>,
+    /// READY=1 was sent to systemd
+    ready: AtomicBool,
+}
+
+impl Heartbeat {
+    /// Start the heartbeat task if configured via environment variables.
+    ///
+    /// `supervisor_tick` is refreshed by the status polling loop on every
+    /// iteration; `is_running` tells whether that loop should be ticking.
+    pub fn start_from_env(supervisor_tick: Arc<Mutex<Instant>>, is_running: Arc<Mutex<bool>>) -> Option<Heartbeat> {
+        let file = std::env::var_os(HEARTBEAT_FILE_ENV).map(PathBuf::from);
+        let notify_socket = std::env::var_os(NOTIFY_SOCKET_ENV).map(PathBuf::from);
+        if file.is_none() && notify_socket.is_none() {
+            return None;
+        }
+
+        let interval = std::env::var(HEARTBEAT_INTERVAL_ENV)
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(DEFAULT_HEARTBEAT_INTERVAL_SECS);
+        let interval = Duration::from_secs(interval);
+        let stall_limit = Duration::from_secs(STATUS_POLL_INTERVAL_SECS * SUPERVISOR_STALL_FACTOR).max(interval);
+
+        println!("Heartbeat enabled every {} secs (file: {:?}, systemd: {})", interval.as_secs(), file, notify_socket.is_some());
+
+        let task_file = file.clone();
+        let task_socket = notify_socket.clone();
+        let task = tauri::async_runtime::spawn(async move {
+            loop {
+                let supervisor_healthy = !*is_running.lock().await
+                    || supervisor_tick.lock().await.elapsed() <= stall_limit;
+
+                if supervisor_healthy {
+                    if let Some(path) = &task_file {
+                        write_heartbeat_file(path);
+                    }
+                    if let Some(socket) = &task_socket {
+                        sd_notify(socket, "WATCHDOG=1");
+                    }
+                } else {
+                    println!("Supervisor loop stalled, withholding heartbeat");
+                }
+
+                tokio::time::sleep(interval).await;
+            }
+        });
+
+        Some(Heartbeat { file, notify_socket, task, ready: AtomicBool::new(false) })
+    }
+
+    /// Tell systemd the app is up (once).
+    fn ready(&self) {
+        if let Some(socket) = &self.notify_socket {
+            if !self.ready.swap(true, Ordering::SeqCst) {
+                sd_notify(socket, "READY=1");
+            }
+        }
+    }
+
+    /// Stop beating and signal a clean exit to external monitors.
+    pub fn stop(self) {
+        self.task.abort();
+        if let Some(path) = &self.file {
+            let _ = std::fs::remove_file(path);
+        }
+        if let Some(socket) = &self.notify_socket {
+            sd_notify(socket, "STOPPING=1");
+        }
+        println!("Heartbeat stopped");
+    }
+}
+
+/// Write "<unix_time> <pid>" to the heartbeat file.
+fn write_heartbeat_file(path: &Path) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    if let Err(e) = std::fs::write(path, format!("{} {}\n", now, std::process::id())) {
+        println!("Failed to write heartbeat file {:?}: {}", path, e);
+    }
+}
+
+/// Report a healthy engine to systemd (READY=1 the first time).
+pub(crate) fn engine_ready(app: &AppHandle) {
+    let Some(heartbeat) = app.try_state::<std::sync::Mutex<Option<Heartbeat>>>() else {
+        return;
+    };
+    let heartbeat = heartbeat.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(heartbeat) = heartbeat.as_ref() {
+        heartbeat.ready();
+    }
+}
+
+/// Send a state string to systemd's notification socket.
+#[cfg(unix)]
+fn sd_notify(socket: &Path, message: &str) {
+    use std::os::unix::ffi::OsStrExt;
+
+    let result = UnixDatagram::unbound().and_then(|datagram| match socket.as_os_str().as_bytes().strip_prefix(b"@") {
+        Some(name) => send_abstract(&datagram, name, message),
+        None => datagram.send_to(message.as_bytes(), socket),
+    });
+    if let Err(e) = result {
+        println!("Failed to notify systemd ({}): {}", message, e);
+    }
+}
+
+/// Send `message` to the abstract socket `name` (NOTIFY_SOCKET "@name").
+#[cfg(target_os = "linux")]
+fn send_abstract(datagram: &UnixDatagram, name: &[u8], message: &str) -> std::io::Result<usize> {
+    use std::os::linux::net::SocketAddrExt;
+
+    let address = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+    datagram.send_to_addr(message.as_bytes(), &address)
+}
+
+/// Abstract sockets exist only on Linux.
+#[cfg(all(unix, not(target_os = "linux")))]
+fn send_abstract(_datagram: &UnixDatagram, _name: &[u8], _message: &str) -> std::io::Result<usize> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "abstract sockets are Linux-only"))
+}
+
+/// NOTIFY_SOCKET is a systemd (Unix-only) protocol.
+#[cfg(not(unix))]
+fn sd_notify(_socket: &Path, _message: &str) {}
@@ -0,0 +1,80 @@
+//! =============================================================================
+//! Conversation History
+//! =============================================================================
+//!
+//! Every input sent to the engine (`send_input_to_python`,
+//! `stream_input_to_python`, sessions, queued jobs) is stored with its
+//! outcome in the history store (SQLite under the app data dir by default,
+//! see history_store), so conversations survive app restarts without relying
+//! on the engine:
+//!
+//!   { "request_id": "req-4", "session_id": "s1", "input": "Hi",
+//!     "response": { "output": "Hello!" }, "error": null,
+//!     "sent_at": 1718000000, "latency_ms": 812 }
+//!
+//! Failed inputs are stored with their error instead of a response.
+//! `get_history(session_id, offset, limit)` pages through the exchanges,
+//! newest first; `clear_history()` deletes all of them. Exchanges are pruned
+//! with the rest of the history by `settings.retention.history_days`.
+
+use tauri::{AppHandle, Manager, State};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::EngineError;
+use crate::history_store::{SharedHistoryStore, StoredExchange};
+
+/// Page size of `get_history` when no limit is given
+const DEFAULT_PAGE_SIZE: u64 = 50;
+
+/// Largest page `get_history` returns
+const MAX_PAGE_SIZE: u64 = 500;
+
+/// Store the outcome of input `body` (the /input payload), sent `latency` ago.
+pub(crate) async fn record(app: &AppHandle, request_id: &str, body: &serde_json::Value, result: &Result<serde_json::Value, EngineError>, latency: Duration) {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let exchange = StoredExchange {
+        request_id: request_id.to_string(),
+        session_id: body.get("session_id").and_then(|v| v.as_str()).map(str::to_string),
+        input: body.get("input").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+        response: result.as_ref().ok().cloned(),
+        error: result.as_ref().err().map(|e| e.to_string()),
+        sent_at: now.saturating_sub(latency).as_secs(),
+        latency_ms: latency.as_millis() as u64,
+    };
+    let stored = app.state::<SharedHistoryStore>().lock().await.record_exchange(exchange);
+    if let Err(e) = stored {
+        println!("Failed to store history of {}: {}", request_id, e);
+    }
+}
+
+// ==================== Tauri Commands ====================
+
+/// Return stored inputs and responses, newest first (of one session, or all).
+///
+/// `limit` defaults to DEFAULT_PAGE_SIZE and is capped at MAX_PAGE_SIZE.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_history(
+    session_id: Option<String>,
+    offset: Option<u64>,
+    limit: Option<u64>,
+    store: State<'_, SharedHistoryStore>,
+) -> Result<Vec<StoredExchange>, EngineError> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
+    store.lock().await
+        .exchanges(session_id.as_deref(), offset.unwrap_or(0), limit)
+        .map_err(|e| EngineError::Internal(format!("Failed to read history: {}", e)))
+}
+
+/// Delete every stored input and response; returns how many were deleted.
+///
+/// Chat sessions and their model overrides are kept.
+#[tauri::command]
+#[specta::specta]
+pub async fn clear_history(store: State<'_, SharedHistoryStore>) -> Result<u64, EngineError> {
+    let cleared = store.lock().await
+        .clear_exchanges()
+        .map_err(|e| EngineError::Internal(format!("Failed to clear history: {}", e)))?;
+    println!("Cleared {} history entries", cleared);
+    Ok(cleared)
+}
@@ -0,0 +1,808 @@
+//! =============================================================================
+//! History Storage Backends
+//! =============================================================================
+//!
+//! Conversation history (every input with its response, see history; chat
+//! sessions, see sessions; session model overrides and the answered
+//! messages of each session, see session_models) is kept behind the
+//! `HistoryStore` trait. Two backends implement it,
+//! selected by `settings.storage.backend`:
+//!
+//!   sqlite  session_models.sqlite in the app data dir (the default)
+//!   jsonl   history.jsonl in the app data dir, one record per line, for
+//!           deployments that sync history with their own tools:
+//!
+//!     {"type":"session","id":"s-1f…","title":"Trip plan","created_at":1718000000,"updated_at":1718000000,"message_count":0}
+//!     {"type":"session_model","session_id":"s1","model":"llama-3b-q4"}
+//!     {"type":"message","request_id":"req-4","session_id":"s1","model":"llama-3b-q4","answered_at":1718000000,"complete":true}
+//!     {"type":"session_deleted","session_id":"s-1f…"}
+//!     {"type":"exchange","request_id":"req-4","session_id":"s1","input":"Hi","response":{…},"error":null,"sent_at":1718000000,"latency_ms":812}
+//!     {"type":"exchanges_cleared"}
+//!
+//!           Records are appended as they happen; later records replace
+//!           earlier ones (same session, same request id), a
+//!           `session_model` record with `"model": null` clears the
+//!           override, `session_deleted` drops the session with its
+//!           override, messages and exchanges, and `exchanges_cleared` drops
+//!           all exchanges. Pruning rewrites the file compacted.
+//!
+//! `migrate_storage(target)` copies everything into the other backend,
+//! switches to it and saves the setting. The old backend's file is left in
+//! place as a backup. The backend can't be changed via `update_settings`,
+//! since that would switch to a store without the data.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use specta::Type;
+use tauri::{AppHandle, Manager, State};
+use tauri::async_runtime::Mutex;
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::EngineError;
+use crate::settings::SettingsStore;
+
+/// SQLite database file name inside the app data directory
+pub(crate) const HISTORY_DB_FILE: &str = "session_models.sqlite";
+
+/// JSONL file name inside the app data directory
+pub(crate) const HISTORY_JSONL_FILE: &str = "history.jsonl";
+
+// ==================== Settings ====================
+
+/// Where history is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    #[default]
+    Sqlite,
+    Jsonl,
+}
+
+/// History storage settings, persisted under `settings.storage`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(default)]
+pub struct StorageSettings {
+    /// Changed by `migrate_storage` only
+    pub backend: StorageBackend,
+}
+
+// ==================== Types ====================
+
+/// One answered message of a session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredMessage {
+    pub request_id: String,
+    pub session_id: String,
+    pub model: Option<String>,
+    /// Unix seconds
+    pub answered_at: u64,
+    /// False if the answer's stream was truncated
+    #[serde(default = "complete_default")]
+    pub complete: bool,
+}
+
+fn complete_default() -> bool {
+    true
+}
+
+/// A chat session's metadata.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct StoredSession {
+    pub id: String,
+    /// Empty until set, or taken from the first input
+    pub title: String,
+    /// Unix seconds
+    pub created_at: u64,
+    /// Unix seconds of the last answered message (or creation)
+    pub updated_at: u64,
+    /// Messages answered in the session
+    pub message_count: u64,
+}
+
+/// One input sent to the engine and its outcome.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct StoredExchange {
+    pub request_id: String,
+    pub session_id: Option<String>,
+    pub input: String,
+    /// The engine's response, if it answered
+    pub response: Option<serde_json::Value>,
+    /// Why the input failed, if it did
+    pub error: Option<String>,
+    /// Unix seconds
+    pub sent_at: u64,
+    pub latency_ms: u64,
+}
+
+/// Everything a store holds, for moving it to another backend or a backup.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HistorySnapshot {
+    /// Missing in snapshots from before chat sessions existed
+    #[serde(default)]
+    pub sessions: Vec<StoredSession>,
+    /// (session id, pinned model)
+    pub session_models: Vec<(String, String)>,
+    pub messages: Vec<StoredMessage>,
+    /// Missing in snapshots from before exchanges were stored
+    #[serde(default)]
+    pub exchanges: Vec<StoredExchange>,
+}
+
+/// Response of `migrate_storage`.
+#[derive(Debug, Serialize, Type)]
+pub struct StorageMigration {
+    pub from: StorageBackend,
+    pub to: StorageBackend,
+    /// Session model overrides copied
+    pub sessions: u64,
+    pub messages: u64,
+}
+
+// ==================== Store Trait ====================
+
+/// Persistent conversation history, whatever the backend.
+pub trait HistoryStore: Send {
+    fn backend(&self) -> StorageBackend;
+
+    /// Create or update a chat session.
+    fn save_session(&mut self, session: StoredSession) -> Result<(), String>;
+
+    fn session(&self, session_id: &str) -> Result<Option<StoredSession>, String>;
+
+    /// All chat sessions, most recently updated first.
+    fn sessions(&self) -> Result<Vec<StoredSession>, String>;
+
+    /// Remove a chat session with its model override and messages; false if it didn't exist.
+    fn delete_session(&mut self, session_id: &str) -> Result<bool, String>;
+
+    /// Model pinned for a session, if any.
+    fn session_model(&self, session_id: &str) -> Result<Option<String>, String>;
+
+    /// Pin a model for a session (`None` removes the override).
+    fn set_session_model(&mut self, session_id: &str, model: Option<&str>) -> Result<(), String>;
+
+    /// Store a message, replacing one with the same request id.
+    fn record_message(&mut self, message: StoredMessage) -> Result<(), String>;
+
+    /// A session's messages, oldest first.
+    fn messages(&self, session_id: &str) -> Result<Vec<StoredMessage>, String>;
+
+    /// A session's most recent message.
+    fn last_message(&self, session_id: &str) -> Result<Option<StoredMessage>, String>;
+
+    /// Remove (or with `dry_run`, count) messages answered before `before`.
+    fn prune_messages(&mut self, before: u64, dry_run: bool) -> Result<u64, String>;
+
+    /// Store an exchange, replacing one with the same request id.
+    fn record_exchange(&mut self, exchange: StoredExchange) -> Result<(), String>;
+
+    /// Exchanges (of one session, or all), newest first, skipping `offset` and returning at most `limit`.
+    fn exchanges(&self, session_id: Option<&str>, offset: u64, limit: u64) -> Result<Vec<StoredExchange>, String>;
+
+    /// Remove every exchange; returns how many there were.
+    fn clear_exchanges(&mut self) -> Result<u64, String>;
+
+    /// Remove (or with `dry_run`, count) exchanges sent before `before`.
+    fn prune_exchanges(&mut self, before: u64, dry_run: bool) -> Result<u64, String>;
+
+    /// Copy out everything the store holds.
+    fn export(&self) -> Result<HistorySnapshot, String>;
+
+    /// Replace everything the store holds with `snapshot`.
+    fn import(&mut self, snapshot: HistorySnapshot) -> Result<(), String>;
+}
+
+/// Managed state holding the active history store.
+pub type SharedHistoryStore = Mutex<Box<dyn HistoryStore>>;
+
+// ==================== SQLite Backend ====================
+
+/// History in a SQLite database.
+pub struct SqliteHistoryStore {
+    db: Connection,
+}
+
+impl SqliteHistoryStore {
+    /// Open (or create) the database in `data_dir`.
+    pub fn open(data_dir: &Path) -> Result<SqliteHistoryStore, String> {
+        std::fs::create_dir_all(data_dir)
+            .map_err(|e| format!("Failed to create {:?}: {}", data_dir, e))?;
+        let db = Connection::open(data_dir.join(HISTORY_DB_FILE)).map_err(|e| e.to_string())?;
+        create_schema(&db).map_err(|e| format!("Failed to create history schema: {}", e))?;
+        Ok(SqliteHistoryStore { db })
+    }
+
+    /// A store that lives in memory only (used when the database can't be opened).
+    pub fn in_memory() -> SqliteHistoryStore {
+        let db = Connection::open_in_memory().expect("in-memory SQLite must be available");
+        if let Err(e) = create_schema(&db) {
+            println!("Failed to create history schema: {}", e);
+        }
+        SqliteHistoryStore { db }
+    }
+}
+
+fn create_schema(db: &Connection) -> rusqlite::Result<()> {
+    db.execute_batch(
+        "CREATE TABLE IF NOT EXISTS session (
+             id             TEXT PRIMARY KEY,
+             title          TEXT NOT NULL,
+             created_at     INTEGER NOT NULL,
+             updated_at     INTEGER NOT NULL,
+             message_count  INTEGER NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS session_model (
+             session_id  TEXT PRIMARY KEY,
+             model       TEXT NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS message_model (
+             request_id   TEXT PRIMARY KEY,
+             session_id   TEXT NOT NULL,
+             model        TEXT,
+             answered_at  INTEGER NOT NULL,
+             complete     INTEGER NOT NULL DEFAULT 1
+         );
+         CREATE INDEX IF NOT EXISTS message_model_session ON message_model (session_id, answered_at);
+         CREATE TABLE IF NOT EXISTS exchange (
+             request_id  TEXT PRIMARY KEY,
+             session_id  TEXT,
+             input       TEXT NOT NULL,
+             response    TEXT,
+             error       TEXT,
+             sent_at     INTEGER NOT NULL,
+             latency_ms  INTEGER NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS exchange_session ON exchange (session_id, sent_at);
+         CREATE INDEX IF NOT EXISTS exchange_sent ON exchange (sent_at);",
+    )?;
+    // Databases from before truncation tracking lack the column
+    let has_complete = db
+        .prepare("SELECT 1 FROM pragma_table_info('message_model') WHERE name = 'complete'")?
+        .exists([])?;
+    if !has_complete {
+        db.execute_batch("ALTER TABLE message_model ADD COLUMN complete INTEGER NOT NULL DEFAULT 1;")?;
+    }
+    Ok(())
+}
+
+fn message_from_row(row: &rusqlite::Row) -> rusqlite::Result<StoredMessage> {
+    Ok(StoredMessage {
+        request_id: row.get(0)?,
+        session_id: row.get(1)?,
+        model: row.get(2)?,
+        answered_at: row.get::<_, i64>(3)? as u64,
+        complete: row.get(4)?,
+    })
+}
+
+const MESSAGE_COLUMNS: &str = "request_id, session_id, model, answered_at, complete";
+
+fn session_from_row(row: &rusqlite::Row) -> rusqlite::Result<StoredSession> {
+    Ok(StoredSession {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        created_at: row.get::<_, i64>(2)? as u64,
+        updated_at: row.get::<_, i64>(3)? as u64,
+        message_count: row.get::<_, i64>(4)? as u64,
+    })
+}
+
+fn exchange_from_row(row: &rusqlite::Row) -> rusqlite::Result<StoredExchange> {
+    let response: Option<String> = row.get(3)?;
+    Ok(StoredExchange {
+        request_id: row.get(0)?,
+        session_id: row.get(1)?,
+        input: row.get(2)?,
+        response: response.and_then(|r| serde_json::from_str(&r).ok()),
+        error: row.get(4)?,
+        sent_at: row.get::<_, i64>(5)? as u64,
+        latency_ms: row.get::<_, i64>(6)? as u64,
+    })
+}
+
+const EXCHANGE_COLUMNS: &str = "request_id, session_id, input, response, error, sent_at, latency_ms";
+
+fn insert_exchange(db: &Connection, exchange: &StoredExchange) -> rusqlite::Result<usize> {
+    let response = exchange.response.as_ref().map(|r| r.to_string());
+    db.execute(
+        "INSERT OR REPLACE INTO exchange (request_id, session_id, input, response, error, sent_at, latency_ms) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![exchange.request_id, exchange.session_id, exchange.input, response, exchange.error, exchange.sent_at as i64, exchange.latency_ms as i64],
+    )
+}
+
+const SESSION_COLUMNS: &str = "id, title, created_at, updated_at, message_count";
+
+fn insert_session(db: &Connection, session: &StoredSession) -> rusqlite::Result<usize> {
+    db.execute(
+        "INSERT OR REPLACE INTO session (id, title, created_at, updated_at, message_count) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![session.id, session.title, session.created_at as i64, session.updated_at as i64, session.message_count as i64],
+    )
+}
+
+fn insert_message(db: &Connection, message: &StoredMessage) -> rusqlite::Result<usize> {
+    db.execute(
+        "INSERT OR REPLACE INTO message_model (request_id, session_id, model, answered_at, complete) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![message.request_id, message.session_id, message.model, message.answered_at as i64, message.complete],
+    )
+}
+
+impl HistoryStore for SqliteHistoryStore {
+    fn backend(&self) -> StorageBackend {
+        StorageBackend::Sqlite
+    }
+
+    fn save_session(&mut self, session: StoredSession) -> Result<(), String> {
+        insert_session(&self.db, &session).map(|_| ()).map_err(|e| e.to_string())
+    }
+
+    fn session(&self, session_id: &str) -> Result<Option<StoredSession>, String> {
+        let query = format!("SELECT {} FROM session WHERE id = ?1", SESSION_COLUMNS);
+        self.db
+            .query_row(&query, params![session_id], session_from_row)
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    fn sessions(&self) -> Result<Vec<StoredSession>, String> {
+        let query = format!("SELECT {} FROM session ORDER BY updated_at DESC, rowid DESC", SESSION_COLUMNS);
+        let mut stmt = self.db.prepare(&query).map_err(|e| e.to_string())?;
+        let rows = stmt.query_map([], session_from_row).map_err(|e| e.to_string())?;
+        rows.collect::<rusqlite::Result<_>>().map_err(|e| e.to_string())
+    }
+
+    fn delete_session(&mut self, session_id: &str) -> Result<bool, String> {
+        let mut delete = || -> rusqlite::Result<bool> {
+            let tx = self.db.transaction()?;
+            let deleted = tx.execute("DELETE FROM session WHERE id = ?1", params![session_id])?;
+            tx.execute("DELETE FROM session_model WHERE session_id = ?1", params![session_id])?;
+            tx.execute("DELETE FROM message_model WHERE session_id = ?1", params![session_id])?;
+            tx.execute("DELETE FROM exchange WHERE session_id = ?1", params![session_id])?;
+            tx.commit()?;
+            Ok(deleted > 0)
+        };
+        delete().map_err(|e| e.to_string())
+    }
+
+    fn session_model(&self, session_id: &str) -> Result<Option<String>, String> {
+        self.db
+            .query_row("SELECT model FROM session_model WHERE session_id = ?1", params![session_id], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    fn set_session_model(&mut self, session_id: &str, model: Option<&str>) -> Result<(), String> {
+        match model {
+            Some(model) => self.db.execute(
+                "INSERT INTO session_model (session_id, model) VALUES (?1, ?2)
+                 ON CONFLICT(session_id) DO UPDATE SET model = excluded.model",
+                params![session_id, model],
+            ),
+            None => self.db.execute("DELETE FROM session_model WHERE session_id = ?1", params![session_id]),
+        }
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+    }
+
+    fn record_message(&mut self, message: StoredMessage) -> Result<(), String> {
+        insert_message(&self.db, &message).map(|_| ()).map_err(|e| e.to_string())
+    }
+
+    fn messages(&self, session_id: &str) -> Result<Vec<StoredMessage>, String> {
+        let query = format!("SELECT {} FROM message_model WHERE session_id = ?1 ORDER BY answered_at, rowid", MESSAGE_COLUMNS);
+        let mut stmt = self.db.prepare(&query).map_err(|e| e.to_string())?;
+        let rows = stmt.query_map(params![session_id], message_from_row).map_err(|e| e.to_string())?;
+        rows.collect::<rusqlite::Result<_>>().map_err(|e| e.to_string())
+    }
+
+    fn last_message(&self, session_id: &str) -> Result<Option<StoredMessage>, String> {
+        let query = format!("SELECT {} FROM message_model WHERE session_id = ?1 ORDER BY answered_at DESC, rowid DESC LIMIT 1", MESSAGE_COLUMNS);
+        self.db
+            .query_row(&query, params![session_id], message_from_row)
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    fn prune_messages(&mut self, before: u64, dry_run: bool) -> Result<u64, String> {
+        let count = if dry_run {
+            self.db.query_row("SELECT COUNT(*) FROM message_model WHERE answered_at < ?1", params![before as i64], |row| row.get::<_, i64>(0))
+                .map(|count| count as usize)
+        } else {
+            self.db.execute("DELETE FROM message_model WHERE answered_at < ?1", params![before as i64])
+        };
+        count.map(|count| count as u64).map_err(|e| e.to_string())
+    }
+
+    fn record_exchange(&mut self, exchange: StoredExchange) -> Result<(), String> {
+        insert_exchange(&self.db, &exchange).map(|_| ()).map_err(|e| e.to_string())
+    }
+
+    fn exchanges(&self, session_id: Option<&str>, offset: u64, limit: u64) -> Result<Vec<StoredExchange>, String> {
+        let query = format!(
+            "SELECT {} FROM exchange WHERE ?1 IS NULL OR session_id = ?1 ORDER BY sent_at DESC, rowid DESC LIMIT ?2 OFFSET ?3",
+            EXCHANGE_COLUMNS
+        );
+        let mut stmt = self.db.prepare(&query).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![session_id, limit.min(i64::MAX as u64) as i64, offset.min(i64::MAX as u64) as i64], exchange_from_row)
+            .map_err(|e| e.to_string())?;
+        rows.collect::<rusqlite::Result<_>>().map_err(|e| e.to_string())
+    }
+
+    fn clear_exchanges(&mut self) -> Result<u64, String> {
+        self.db.execute("DELETE FROM exchange", []).map(|count| count as u64).map_err(|e| e.to_string())
+    }
+
+    fn prune_exchanges(&mut self, before: u64, dry_run: bool) -> Result<u64, String> {
+        let count = if dry_run {
+            self.db.query_row("SELECT COUNT(*) FROM exchange WHERE sent_at < ?1", params![before as i64], |row| row.get::<_, i64>(0))
+                .map(|count| count as usize)
+        } else {
+            self.db.execute("DELETE FROM exchange WHERE sent_at < ?1", params![before as i64])
+        };
+        count.map(|count| count as u64).map_err(|e| e.to_string())
+    }
+
+    fn export(&self) -> Result<HistorySnapshot, String> {
+        let read = || -> rusqlite::Result<HistorySnapshot> {
+            let query = format!("SELECT {} FROM session ORDER BY created_at, rowid", SESSION_COLUMNS);
+            let mut stmt = self.db.prepare(&query)?;
+            let sessions = stmt.query_map([], session_from_row)?.collect::<rusqlite::Result<_>>()?;
+            let mut stmt = self.db.prepare("SELECT session_id, model FROM session_model ORDER BY session_id")?;
+            let session_models = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?.collect::<rusqlite::Result<_>>()?;
+            let query = format!("SELECT {} FROM message_model ORDER BY answered_at, rowid", MESSAGE_COLUMNS);
+            let mut stmt = self.db.prepare(&query)?;
+            let messages = stmt.query_map([], message_from_row)?.collect::<rusqlite::Result<_>>()?;
+            let query = format!("SELECT {} FROM exchange ORDER BY sent_at, rowid", EXCHANGE_COLUMNS);
+            let mut stmt = self.db.prepare(&query)?;
+            let exchanges = stmt.query_map([], exchange_from_row)?.collect::<rusqlite::Result<_>>()?;
+            Ok(HistorySnapshot { sessions, session_models, messages, exchanges })
+        };
+        read().map_err(|e| e.to_string())
+    }
+
+    fn import(&mut self, snapshot: HistorySnapshot) -> Result<(), String> {
+        let mut write = || -> rusqlite::Result<()> {
+            let tx = self.db.transaction()?;
+            tx.execute_batch("DELETE FROM session; DELETE FROM session_model; DELETE FROM message_model; DELETE FROM exchange;")?;
+            for session in &snapshot.sessions {
+                insert_session(&tx, session)?;
+            }
+            for (session_id, model) in &snapshot.session_models {
+                tx.execute("INSERT INTO session_model (session_id, model) VALUES (?1, ?2)", params![session_id, model])?;
+            }
+            for message in &snapshot.messages {
+                insert_message(&tx, message)?;
+            }
+            for exchange in &snapshot.exchanges {
+                insert_exchange(&tx, exchange)?;
+            }
+            tx.commit()
+        };
+        write().map_err(|e| e.to_string())
+    }
+}
+
+// ==================== JSONL Backend ====================
+
+/// One line of the JSONL history file.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum JsonlRecord {
+    Session(StoredSession),
+    SessionDeleted {
+        session_id: String,
+    },
+    SessionModel {
+        session_id: String,
+        model: Option<String>,
+    },
+    Message(StoredMessage),
+    Exchange(StoredExchange),
+    ExchangesCleared,
+}
+
+/// History in an append-only JSONL file, mirrored in memory.
+pub struct JsonlHistoryStore {
+    path: PathBuf,
+    sessions: BTreeMap<String, StoredSession>,
+    session_models: BTreeMap<String, String>,
+    /// Oldest first
+    messages: Vec<StoredMessage>,
+    /// Oldest first
+    exchanges: Vec<StoredExchange>,
+}
+
+impl JsonlHistoryStore {
+    /// Load the file in `data_dir` (missing means empty); unparseable lines are skipped.
+    pub fn open(data_dir: &Path) -> Result<JsonlHistoryStore, String> {
+        std::fs::create_dir_all(data_dir)
+            .map_err(|e| format!("Failed to create {:?}: {}", data_dir, e))?;
+        let path = data_dir.join(HISTORY_JSONL_FILE);
+        let mut store = JsonlHistoryStore { path, sessions: BTreeMap::new(), session_models: BTreeMap::new(), messages: Vec::new(), exchanges: Vec::new() };
+        let file = match std::fs::File::open(&store.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(store),
+            Err(e) => return Err(format!("Failed to open {:?}: {}", store.path, e)),
+        };
+        for (number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| format!("Failed to read {:?}: {}", store.path, e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(record) => store.apply(record),
+                Err(e) => println!("Skipping invalid line {} of {:?}: {}", number + 1, store.path, e),
+            }
+        }
+        Ok(store)
+    }
+
+    fn apply(&mut self, record: JsonlRecord) {
+        match record {
+            JsonlRecord::Session(session) => {
+                self.sessions.insert(session.id.clone(), session);
+            }
+            JsonlRecord::SessionDeleted { session_id } => {
+                self.sessions.remove(&session_id);
+                self.session_models.remove(&session_id);
+                self.messages.retain(|m| m.session_id != session_id);
+                self.exchanges.retain(|e| e.session_id.as_deref() != Some(session_id.as_str()));
+            }
+            JsonlRecord::SessionModel { session_id, model: Some(model) } => {
+                self.session_models.insert(session_id, model);
+            }
+            JsonlRecord::SessionModel { session_id, model: None } => {
+                self.session_models.remove(&session_id);
+            }
+            JsonlRecord::Message(message) => {
+                self.messages.retain(|m| m.request_id != message.request_id);
+                self.messages.push(message);
+            }
+            JsonlRecord::Exchange(exchange) => {
+                self.exchanges.retain(|e| e.request_id != exchange.request_id);
+                self.exchanges.push(exchange);
+            }
+            JsonlRecord::ExchangesCleared => self.exchanges.clear(),
+        }
+    }
+
+    fn append(&self, record: &JsonlRecord) -> Result<(), String> {
+        let line = serde_json::to_string(record).map_err(|e| format!("Failed to serialize history record: {}", e))?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open {:?}: {}", self.path, e))?;
+        writeln!(file, "{}", line).map_err(|e| format!("Failed to write {:?}: {}", self.path, e))
+    }
+
+    /// Rewrite the file from memory, via a temporary file.
+    fn rewrite(&self) -> Result<(), String> {
+        let temp = self.path.with_extension("jsonl.tmp");
+        let write = || -> std::io::Result<()> {
+            let mut writer = std::io::BufWriter::new(std::fs::File::create(&temp)?);
+            for session in self.sessions.values() {
+                writeln!(writer, "{}", serde_json::to_string(&JsonlRecord::Session(session.clone()))?)?;
+            }
+            for (session_id, model) in &self.session_models {
+                let record = JsonlRecord::SessionModel { session_id: session_id.clone(), model: Some(model.clone()) };
+                writeln!(writer, "{}", serde_json::to_string(&record)?)?;
+            }
+            for message in &self.messages {
+                writeln!(writer, "{}", serde_json::to_string(&JsonlRecord::Message(message.clone()))?)?;
+            }
+            for exchange in &self.exchanges {
+                writeln!(writer, "{}", serde_json::to_string(&JsonlRecord::Exchange(exchange.clone()))?)?;
+            }
+            writer.flush()?;
+            drop(writer);
+            std::fs::rename(&temp, &self.path)
+        };
+        write().map_err(|e| format!("Failed to rewrite {:?}: {}", self.path, e))
+    }
+}
+
+impl HistoryStore for JsonlHistoryStore {
+    fn backend(&self) -> StorageBackend {
+        StorageBackend::Jsonl
+    }
+
+    fn save_session(&mut self, session: StoredSession) -> Result<(), String> {
+        let record = JsonlRecord::Session(session);
+        self.append(&record)?;
+        self.apply(record);
+        Ok(())
+    }
+
+    fn session(&self, session_id: &str) -> Result<Option<StoredSession>, String> {
+        Ok(self.sessions.get(session_id).cloned())
+    }
+
+    fn sessions(&self) -> Result<Vec<StoredSession>, String> {
+        let mut sessions: Vec<StoredSession> = self.sessions.values().cloned().collect();
+        sessions.sort_by_key(|s| std::cmp::Reverse((s.updated_at, s.created_at)));
+        Ok(sessions)
+    }
+
+    fn delete_session(&mut self, session_id: &str) -> Result<bool, String> {
+        let existed = self.sessions.contains_key(session_id);
+        let record = JsonlRecord::SessionDeleted { session_id: session_id.to_string() };
+        self.append(&record)?;
+        self.apply(record);
+        Ok(existed)
+    }
+
+    fn session_model(&self, session_id: &str) -> Result<Option<String>, String> {
+        Ok(self.session_models.get(session_id).cloned())
+    }
+
+    fn set_session_model(&mut self, session_id: &str, model: Option<&str>) -> Result<(), String> {
+        let record = JsonlRecord::SessionModel { session_id: session_id.to_string(), model: model.map(str::to_string) };
+        self.append(&record)?;
+        self.apply(record);
+        Ok(())
+    }
+
+    fn record_message(&mut self, message: StoredMessage) -> Result<(), String> {
+        let record = JsonlRecord::Message(message);
+        self.append(&record)?;
+        self.apply(record);
+        Ok(())
+    }
+
+    fn messages(&self, session_id: &str) -> Result<Vec<StoredMessage>, String> {
+        let mut messages: Vec<StoredMessage> = self.messages.iter().filter(|m| m.session_id == session_id).cloned().collect();
+        // Stable: messages answered in the same second keep their order
+        messages.sort_by_key(|m| m.answered_at);
+        Ok(messages)
+    }
+
+    fn last_message(&self, session_id: &str) -> Result<Option<StoredMessage>, String> {
+        Ok(self.messages(session_id)?.pop())
+    }
+
+    fn prune_messages(&mut self, before: u64, dry_run: bool) -> Result<u64, String> {
+        let count = self.messages.iter().filter(|m| m.answered_at < before).count() as u64;
+        if dry_run || count == 0 {
+            return Ok(count);
+        }
+        self.messages.retain(|m| m.answered_at >= before);
+        self.rewrite()?;
+        Ok(count)
+    }
+
+    fn record_exchange(&mut self, exchange: StoredExchange) -> Result<(), String> {
+        let record = JsonlRecord::Exchange(exchange);
+        self.append(&record)?;
+        self.apply(record);
+        Ok(())
+    }
+
+    fn exchanges(&self, session_id: Option<&str>, offset: u64, limit: u64) -> Result<Vec<StoredExchange>, String> {
+        let mut exchanges: Vec<&StoredExchange> = self.exchanges.iter()
+            .filter(|e| session_id.is_none() || e.session_id.as_deref() == session_id)
+            .collect();
+        // Stable: exchanges sent in the same second keep their order
+        exchanges.sort_by_key(|e| e.sent_at);
+        Ok(exchanges.into_iter()
+            .rev()
+            .skip(offset.min(usize::MAX as u64) as usize)
+            .take(limit.min(usize::MAX as u64) as usize)
+            .cloned()
+            .collect())
+    }
+
+    fn clear_exchanges(&mut self) -> Result<u64, String> {
+        let count = self.exchanges.len() as u64;
+        self.append(&JsonlRecord::ExchangesCleared)?;
+        self.apply(JsonlRecord::ExchangesCleared);
+        Ok(count)
+    }
+
+    fn prune_exchanges(&mut self, before: u64, dry_run: bool) -> Result<u64, String> {
+        let count = self.exchanges.iter().filter(|e| e.sent_at < before).count() as u64;
+        if dry_run || count == 0 {
+            return Ok(count);
+        }
+        self.exchanges.retain(|e| e.sent_at >= before);
+        self.rewrite()?;
+        Ok(count)
+    }
+
+    fn export(&self) -> Result<HistorySnapshot, String> {
+        Ok(HistorySnapshot {
+            sessions: self.sessions.values().cloned().collect(),
+            session_models: self.session_models.iter().map(|(s, m)| (s.clone(), m.clone())).collect(),
+            messages: self.messages.clone(),
+            exchanges: self.exchanges.clone(),
+        })
+    }
+
+    fn import(&mut self, snapshot: HistorySnapshot) -> Result<(), String> {
+        self.sessions = snapshot.sessions.into_iter().map(|s| (s.id.clone(), s)).collect();
+        self.session_models = snapshot.session_models.into_iter().collect();
+        self.messages = snapshot.messages;
+        self.exchanges = snapshot.exchanges;
+        self.rewrite()
+    }
+}
+
+// ==================== Opening ====================
+
+fn data_dir(app: &AppHandle) -> PathBuf {
+    app.path().app_data_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join("ai-engine"))
+}
+
+fn try_open(backend: StorageBackend, data_dir: &Path) -> Result<Box<dyn HistoryStore>, String> {
+    Ok(match backend {
+        StorageBackend::Sqlite => Box::new(SqliteHistoryStore::open(data_dir)?),
+        StorageBackend::Jsonl => Box::new(JsonlHistoryStore::open(data_dir)?),
+    })
+}
+
+/// Open the `backend` store in `data_dir`, falling back to memory.
+pub(crate) fn open(backend: StorageBackend, data_dir: &Path) -> Box<dyn HistoryStore> {
+    try_open(backend, data_dir).unwrap_or_else(|e| {
+        println!("Failed to open {:?} history store, using in-memory store: {}", backend, e);
+        Box::new(SqliteHistoryStore::in_memory())
+    })
+}
+
+/// Open the configured history store and register it as managed state.
+///
+/// Runs after settings::init.
+pub fn init(app: &AppHandle) {
+    let backend = app.state::<Mutex<SettingsStore>>().blocking_lock().settings.storage.backend;
+    let store: SharedHistoryStore = Mutex::new(open(backend, &data_dir(app)));
+    app.manage(store);
+}
+
+// ==================== Tauri Command: migrate_storage ====================
+
+/// Move the history to the `target` backend and switch to it.
+///
+/// Everything is copied and the new backend saved in the settings before
+/// switching, so a failed migration leaves the current store in use. The
+/// previous backend's file is kept.
+#[tauri::command]
+#[specta::specta]
+pub async fn migrate_storage(
+    app: AppHandle,
+    target: StorageBackend,
+    history: State<'_, SharedHistoryStore>,
+    settings: State<'_, Mutex<SettingsStore>>,
+) -> Result<StorageMigration, EngineError> {
+    let mut history = history.lock().await;
+    let from = history.backend();
+    let snapshot = history.export()
+        .map_err(|e| EngineError::Internal(format!("Failed to read {:?} history: {}", from, e)))?;
+    let migration = StorageMigration {
+        from,
+        to: target,
+        sessions: snapshot.session_models.len() as u64,
+        messages: snapshot.messages.len() as u64,
+    };
+    if from == target {
+        return Ok(migration);
+    }
+
+    let mut store = try_open(target, &data_dir(&app))
+        .map_err(|e| EngineError::Internal(format!("Failed to open {:?} history store: {}", target, e)))?;
+    store.import(snapshot)
+        .map_err(|e| EngineError::Internal(format!("Failed to write {:?} history: {}", target, e)))?;
+
+    // Persist the switch before making it, so the next launch opens the same store
+    let mut settings = settings.lock().await;
+    settings.settings.storage.backend = target;
+    if let Err(e) = settings.save() {
+        settings.settings.storage.backend = from;
+        return Err(e.into());
+    }
+    *history = store;
+    println!("Migrated history from {:?} to {:?} ({} session(s), {} message(s))", from, target, migration.sessions, migration.messages);
+    Ok(migration)
+}
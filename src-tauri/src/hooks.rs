@@ -0,0 +1,152 @@
+//! =============================================================================
+//! Request Hooks
+//! =============================================================================
+//!
+//! Apps embedding this crate can run their own code around every engine
+//! request made through the transport layer (inputs, status polls, jobs, ...),
+//! e.g. to add tenant headers or log to their own system. Hooks are
+//! registered on the `Builder` before the app starts:
+//!
+//!   backend_trial_lib::Builder::default()
+//!       .pre_dispatch(|mut call| async move {
+//!           call.headers.push(("X-Tenant".into(), "acme".into()));
+//!           Ok(call)
+//!       })
+//!       .on_error(|call, error| async move { println!("{} failed: {}", call.endpoint, error) })
+//!       .run();
+//!
+//! Ordering guarantees, for each request:
+//!
+//!   1. pre_dispatch hooks run in registration order, each receiving the
+//!      call as returned by the previous one; the first Err rejects the
+//!      request (`invalid_request`) and no later hook runs
+//!   2. the request is sent (the timeout covers the send only, not hooks)
+//!   3. on success, post_response hooks run in registration order, each
+//!      receiving the response as returned by the previous one, before the
+//!      caller sees it
+//!   4. on failure, including a rejection in 1, on_error hooks run in
+//!      registration order, then the error is returned unchanged
+//!
+//! Hooks of one request run one after another; hooks of concurrent requests
+//! run concurrently. Uploads run all hooks too. Requests whose response
+//! isn't one JSON value (streamed inputs, raw bytes from fetch_from_python,
+//! downloads, artifacts) run pre_dispatch and on_error hooks, including for
+//! errors while the body is read, but no post_response hooks.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::error::EngineError;
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+type PreDispatchHook = Arc<dyn Fn(EngineCall) -> BoxFuture<Result<EngineCall, String>> + Send + Sync>;
+type PostResponseHook = Arc<dyn Fn(EngineCall, serde_json::Value) -> BoxFuture<serde_json::Value> + Send + Sync>;
+type ErrorHook = Arc<dyn Fn(EngineCall, EngineError) -> BoxFuture<()> + Send + Sync>;
+
+/// An engine request as seen (and changed) by hooks.
+#[derive(Debug, Clone)]
+pub struct EngineCall {
+    pub method: String,
+    /// Path on the engine, e.g. "/input"
+    pub endpoint: String,
+    /// Extra HTTP headers (mux frames carry them as a `headers` object)
+    pub headers: Vec<(String, String)>,
+    pub body: Option<serde_json::Value>,
+}
+
+/// Hooks registered on the `Builder`, held as managed state.
+#[derive(Clone, Default)]
+pub struct RequestHooks {
+    pre_dispatch: Vec<PreDispatchHook>,
+    post_response: Vec<PostResponseHook>,
+    on_error: Vec<ErrorHook>,
+}
+
+impl RequestHooks {
+    pub(crate) fn add_pre_dispatch<F, Fut>(&mut self, hook: F)
+    where
+        F: Fn(EngineCall) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<EngineCall, String>> + Send + 'static,
+    {
+        self.pre_dispatch.push(Arc::new(move |call| Box::pin(hook(call))));
+    }
+
+    pub(crate) fn add_post_response<F, Fut>(&mut self, hook: F)
+    where
+        F: Fn(EngineCall, serde_json::Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = serde_json::Value> + Send + 'static,
+    {
+        self.post_response.push(Arc::new(move |call, response| Box::pin(hook(call, response))));
+    }
+
+    pub(crate) fn add_on_error<F, Fut>(&mut self, hook: F)
+    where
+        F: Fn(EngineCall, EngineError) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.on_error.push(Arc::new(move |call, error| Box::pin(hook(call, error))));
+    }
+
+    /// Run `send` for `call` with all hooks around it (see the ordering above).
+    pub(crate) async fn run<F, Fut>(&self, mut call: EngineCall, send: F) -> Result<serde_json::Value, EngineError>
+    where
+        F: FnOnce(EngineCall) -> Fut,
+        Fut: Future<Output = Result<serde_json::Value, EngineError>>,
+    {
+        let result = match self.dispatch(&mut call).await {
+            Ok(()) => send(call.clone()).await,
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(mut response) => {
+                for hook in &self.post_response {
+                    response = hook(call.clone(), response).await;
+                }
+                Ok(response)
+            }
+            Err(e) => {
+                self.report(&call, &e).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Run `send` for a `call` whose response isn't one JSON value (a
+    /// stream, raw bytes, a file): like `run`, without post_response hooks.
+    pub(crate) async fn run_raw<T, F, Fut>(&self, mut call: EngineCall, send: F) -> Result<T, EngineError>
+    where
+        F: FnOnce(EngineCall) -> Fut,
+        Fut: Future<Output = Result<T, EngineError>>,
+    {
+        let result = match self.dispatch(&mut call).await {
+            Ok(()) => send(call.clone()).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = &result {
+            self.report(&call, e).await;
+        }
+        result
+    }
+
+    /// Run the pre_dispatch hooks on `call`; Err if one rejects it.
+    async fn dispatch(&self, call: &mut EngineCall) -> Result<(), EngineError> {
+        for hook in &self.pre_dispatch {
+            match hook(call.clone()).await {
+                Ok(changed) => *call = changed,
+                Err(reason) => {
+                    return Err(EngineError::InvalidRequest(format!("Request to {} rejected: {}", call.endpoint, reason)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the on_error hooks for a failed `call`.
+    async fn report(&self, call: &EngineCall, error: &EngineError) {
+        for hook in &self.on_error {
+            hook(call.clone(), error.clone()).await;
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! =============================================================================
+//! Engine-initiated Host Requests
+//! =============================================================================
+//!
+//! Sometimes the engine needs something from the host mid-task (ask the user a
+//! question, pick a file). It lists such requests in its /status payload:
+//!
+//!   { ..., "host_requests": [ { "id": "q-17", "kind": "ask_user",
+//!                               "payload": { "question": "…" }, "timeout_ms": 60000 } ] }
+//!
+//! Flow:
+//!   engine /status  →  backend emits `host_request` (once per id)
+//!   frontend        →  respond_to_host_request(id, payload)
+//!   backend         →  POST /host_response { "id", "payload" }
+//!
+//! Requests not answered before their deadline are answered with
+//! `{ "id", "error": "timeout" }` and reported via `host_request_expired`.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager, State};
+use tauri::async_runtime::Mutex;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::error::EngineError;
+use crate::events::{self, HostRequestExpired};
+use crate::transport;
+
+/// Deadline used when the engine doesn't specify one
+const DEFAULT_HOST_REQUEST_TIMEOUT_MS: u64 = 60_000;
+
+// ==================== Types ====================
+
+/// A request from the engine to the host, as listed in /status.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct HostRequest {
+    pub id: String,
+    pub kind: String,
+    #[serde(default)]
+    pub payload: serde_json::Value,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+/// Host requests that were forwarded to the frontend and await an answer.
+#[derive(Default)]
+pub struct HostRequestState {
+    pending: HashMap<String, Instant>,
+    /// Ids already answered or expired, so repeated /status listings are ignored
+    completed: HashMap<String, Instant>,
+}
+
+// ==================== Ingestion ====================
+
+/// Forward new host requests from a /status payload and expire overdue ones.
+///
+/// Called by the status polling loop after every successful poll.
+pub(crate) async fn ingest(app: &AppHandle, status: &serde_json::Value) {
+    let requests: Vec<HostRequest> = status
+        .get("host_requests")
+        .cloned()
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+
+    let state = app.state::<Mutex<HostRequestState>>();
+    let mut state = state.lock().await;
+    let now = Instant::now();
+
+    for request in requests {
+        if state.pending.contains_key(&request.id) || state.completed.contains_key(&request.id) {
+            continue;
+        }
+        let timeout = Duration::from_millis(request.timeout_ms.unwrap_or(DEFAULT_HOST_REQUEST_TIMEOUT_MS));
+        state.pending.insert(request.id.clone(), now + timeout);
+        println!("Engine host request {} ({})", request.id, request.kind);
+        events::emit(app, request);
+    }
+
+    let expired: Vec<String> = state.pending
+        .iter()
+        .filter(|(_, deadline)| **deadline <= now)
+        .map(|(id, _)| id.clone())
+        .collect();
+    for id in &expired {
+        state.pending.remove(id);
+        state.completed.insert(id.clone(), now);
+    }
+
+    // Forget completed ids once the engine has had ample time to drop them
+    state.completed.retain(|_, at| now.duration_since(*at) < Duration::from_millis(DEFAULT_HOST_REQUEST_TIMEOUT_MS * 2));
+    drop(state);
+
+    for id in expired {
+        println!("Host request {} timed out", id);
+        let body = serde_json::json!({ "id": id, "error": "timeout" });
+        let _ = transport::engine_request(app, "POST", "/host_response", Some(&body), None).await;
+        events::emit(app, HostRequestExpired { id });
+    }
+}
+
+// ==================== Tauri Command: respond_to_host_request ====================
+
+/// Answer a pending engine host request.
+///
+/// Fails if the id is unknown or the request has already timed out.
+#[tauri::command]
+#[specta::specta]
+pub async fn respond_to_host_request(app: AppHandle, id: String, payload: serde_json::Value, state: State<'_, Mutex<HostRequestState>>) -> Result<(), EngineError> {
+    {
+        let mut state = state.lock().await;
+        if state.pending.remove(&id).is_none() {
+            return Err(EngineError::InvalidRequest(format!("No pending host request with id {}", id)));
+        }
+        state.completed.insert(id.clone(), Instant::now());
+    }
+
+    let body = serde_json::json!({ "id": id, "payload": payload });
+    transport::engine_request(&app, "POST", "/host_response", Some(&body), None).await?;
+    Ok(())
+}
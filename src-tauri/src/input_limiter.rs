@@ -0,0 +1,270 @@
+//! =============================================================================
+//! Input Concurrency Limit
+//! =============================================================================
+//!
+//! Rapid inputs would otherwise all hit the engine at once and slow each
+//! other down. At most `settings.engine.max_concurrent_inputs` /input
+//! requests (direct or from the job queue) run at a time; the rest wait in
+//! arrival order:
+//!
+//!   process_input  →  acquire slot (waits, cancellable)  →  POST /input
+//!
+//! Whenever the number of waiting or running inputs changes, `queue_depth`
+//! is emitted so the frontend can show "N requests pending". Streamed inputs
+//! (see streaming) take the same slots. A raised limit lets waiting inputs
+//! start at once; after a lowered one, running inputs keep their slots and
+//! new ones start only once fewer than the new limit run. While turbo mode
+//! is active the limit is raised to at least the CPU thread count (see turbo).
+//!
+//! An input belonging to a capacity reservation also may take one of the
+//! reservation's extra slots, whichever frees up first (see reservations).
+//!
+//! Each waiting input is told where it stands: `input_queued` is emitted for
+//! every waiting request whenever the queue moves, with its position and
+//! when it should start. The estimate assumes the slots free up at the
+//! rolling average service time of the last SERVICE_WINDOW inputs:
+//!
+//!   estimated wait = average service time × position / limit
+//!
+//! and is None until an input has completed. Position 0 means the request
+//! got its slot. `get_queue_position(request_id)` returns the same.
+//! Reserved inputs may overtake the order the positions assume.
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Manager, State};
+use tauri::async_runtime::Mutex;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::error::EngineError;
+use crate::events::{self, InputQueued, QueueDepth};
+use crate::reservations;
+use crate::settings::SettingsStore;
+use crate::{turbo, PythonProcess};
+
+/// Completed inputs the average service time is taken over
+const SERVICE_WINDOW: usize = 20;
+
+/// Where a waiting input stands, emitted as `input_queued`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct QueuePosition {
+    pub request_id: String,
+    /// 1 for the next input to start; 0 once the input has its slot
+    pub position: usize,
+    /// Unix time in milliseconds; None until an input has completed
+    pub estimated_start: Option<u64>,
+    pub estimated_wait_ms: Option<u64>,
+}
+
+/// Managed state: the slots for /input requests and how they are used.
+#[derive(Default)]
+pub struct InputLimiter {
+    /// (limit, semaphore with that many permits)
+    slots: std::sync::Mutex<Option<(usize, Arc<Semaphore>)>>,
+    pending: AtomicUsize,
+    running: AtomicUsize,
+    /// Request ids of the waiting inputs, in arrival order
+    waiting: std::sync::Mutex<Vec<String>>,
+    /// Request ids of the inputs holding a slot
+    holding: std::sync::Mutex<Vec<String>>,
+    /// Durations of the last SERVICE_WINDOW inputs, in milliseconds
+    service_ms: std::sync::Mutex<VecDeque<u64>>,
+}
+
+fn unix_now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl InputLimiter {
+    /// Semaphore for the configured limit, resized in place when the limit changed.
+    ///
+    /// Shrinking takes the surplus permits as they are returned and forgets
+    /// them, so old and new holders together never exceed the new limit.
+    fn semaphore(&self, limit: usize) -> Arc<Semaphore> {
+        let mut slots = self.slots.lock().unwrap_or_else(|e| e.into_inner());
+        let Some((size, semaphore)) = slots.as_mut() else {
+            let semaphore = Arc::new(Semaphore::new(limit));
+            *slots = Some((limit, semaphore.clone()));
+            return semaphore;
+        };
+        if limit > *size {
+            semaphore.add_permits(limit - *size);
+        } else if limit < *size {
+            let surplus = (*size - limit) as u32;
+            let shrinking = semaphore.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Ok(permits) = shrinking.acquire_many_owned(surplus).await {
+                    permits.forget();
+                }
+            });
+        }
+        *size = limit;
+        semaphore.clone()
+    }
+
+    fn limit(&self) -> usize {
+        self.slots.lock().unwrap_or_else(|e| e.into_inner()).as_ref().map(|(size, _)| *size).unwrap_or(0)
+    }
+
+    fn publish(&self, app: &AppHandle) {
+        events::emit(app, QueueDepth {
+            pending: self.pending.load(Ordering::SeqCst),
+            running: self.running.load(Ordering::SeqCst),
+            limit: self.limit(),
+        });
+    }
+
+    /// Record how long an input held its slot.
+    fn record_service(&self, elapsed_ms: u64) {
+        let mut service_ms = self.service_ms.lock().unwrap_or_else(|e| e.into_inner());
+        if service_ms.len() == SERVICE_WINDOW {
+            service_ms.pop_front();
+        }
+        service_ms.push_back(elapsed_ms);
+    }
+
+    /// Position and estimated start of `request_id` at (1-based) `position`.
+    fn estimate(&self, request_id: &str, position: usize) -> QueuePosition {
+        let average_ms = {
+            let service_ms = self.service_ms.lock().unwrap_or_else(|e| e.into_inner());
+            (!service_ms.is_empty()).then(|| service_ms.iter().sum::<u64>() / service_ms.len() as u64)
+        };
+        let wait_ms = average_ms.map(|average| average * position as u64 / self.limit().max(1) as u64);
+        QueuePosition {
+            request_id: request_id.to_string(),
+            position,
+            estimated_start: wait_ms.map(|wait| unix_now_ms() + wait),
+            estimated_wait_ms: wait_ms,
+        }
+    }
+
+    /// Positions of all waiting inputs.
+    fn positions(&self) -> Vec<QueuePosition> {
+        let waiting = self.waiting.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        waiting.iter().enumerate().map(|(i, id)| self.estimate(id, i + 1)).collect()
+    }
+
+    /// Emit `input_queued` for every waiting input.
+    fn publish_positions(&self, app: &AppHandle) {
+        for position in self.positions() {
+            events::emit(app, InputQueued(position));
+        }
+    }
+}
+
+/// A slot for one /input request; frees it (and reports the new depth) on drop.
+pub(crate) struct InputSlot {
+    app: AppHandle,
+    request_id: String,
+    _permit: OwnedSemaphorePermit,
+    started: Instant,
+}
+
+impl Drop for InputSlot {
+    fn drop(&mut self) {
+        let limiter = self.app.state::<InputLimiter>();
+        limiter.running.fetch_sub(1, Ordering::SeqCst);
+        let mut holding = limiter.holding.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(index) = holding.iter().position(|id| *id == self.request_id) {
+            holding.swap_remove(index);
+        }
+        drop(holding);
+        limiter.record_service(self.started.elapsed().as_millis() as u64);
+        limiter.publish(&self.app);
+    }
+}
+
+/// Counts an input as pending until it gets a slot or gives up waiting.
+struct Pending<'a> {
+    app: &'a AppHandle,
+    request_id: &'a str,
+}
+
+impl Drop for Pending<'_> {
+    fn drop(&mut self) {
+        let limiter = self.app.state::<InputLimiter>();
+        limiter.pending.fetch_sub(1, Ordering::SeqCst);
+        limiter.waiting.lock().unwrap_or_else(|e| e.into_inner()).retain(|id| id != self.request_id);
+        limiter.publish(self.app);
+        limiter.publish_positions(self.app);
+    }
+}
+
+/// Wait for a free /input slot, or one of reservation `reservation`'s slots.
+///
+/// While waiting, `input_queued` reports `request_id`'s position. Dropping
+/// the future (e.g. on `cancel_request`) leaves the queue.
+pub(crate) async fn acquire(app: &AppHandle, request_id: &str, reservation: Option<&str>) -> Result<InputSlot, EngineError> {
+    let configured = app.state::<Mutex<SettingsStore>>().lock().await.settings.engine.max_concurrent_inputs.max(1);
+    let turbo = app.state::<Mutex<PythonProcess>>().lock().await.turbo.clone();
+    let limit = turbo::effective_input_limit(&turbo, configured).await;
+    let limiter = app.state::<InputLimiter>();
+    let semaphore = limiter.semaphore(limit);
+    let reserved = reservation.and_then(|id| {
+        let slots = reservations::slots(id);
+        if slots.is_none() {
+            println!("Reservation {} is not active, input admitted normally", id);
+        }
+        slots
+    });
+
+    let available = reserved.clone()
+        .and_then(|slots| slots.try_acquire_owned().ok())
+        .or_else(|| semaphore.clone().try_acquire_owned().ok());
+    let permit = match available {
+        Some(permit) => {
+            limiter.running.fetch_add(1, Ordering::SeqCst);
+            limiter.publish(app);
+            permit
+        }
+        None => {
+            limiter.pending.fetch_add(1, Ordering::SeqCst);
+            limiter.waiting.lock().unwrap_or_else(|e| e.into_inner()).push(request_id.to_string());
+            let pending = Pending { app, request_id };
+            limiter.publish(app);
+            limiter.publish_positions(app);
+            println!("Input waiting for a slot ({} running, limit {})", limiter.running.load(Ordering::SeqCst), limit);
+            let permit = match reserved {
+                // A released reservation closes its slots; keep waiting for a regular one
+                Some(slots) => tokio::select! {
+                    permit = slots.acquire_owned() => match permit {
+                        Ok(permit) => Ok(permit),
+                        Err(_) => semaphore.acquire_owned().await,
+                    },
+                    permit = semaphore.clone().acquire_owned() => permit,
+                },
+                None => semaphore.acquire_owned().await,
+            }
+            .map_err(|e| EngineError::Internal(format!("Input slots closed: {}", e)))?;
+            limiter.running.fetch_add(1, Ordering::SeqCst);
+            // Reports the input moving from pending to running
+            drop(pending);
+            events::emit(app, InputQueued(limiter.estimate(request_id, 0)));
+            permit
+        }
+    };
+    limiter.holding.lock().unwrap_or_else(|e| e.into_inner()).push(request_id.to_string());
+    Ok(InputSlot { app: app.clone(), request_id: request_id.to_string(), _permit: permit, started: Instant::now() })
+}
+
+// ==================== Tauri Commands ====================
+
+/// Return where input `request_id` stands in the queue.
+///
+/// Position 0 means it has its slot; None if it is neither waiting nor running.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_queue_position(request_id: String, limiter: State<'_, InputLimiter>) -> Result<Option<QueuePosition>, EngineError> {
+    if let Some(position) = limiter.positions().into_iter().find(|p| p.request_id == request_id) {
+        return Ok(Some(position));
+    }
+    let running = limiter.holding.lock().unwrap_or_else(|e| e.into_inner()).contains(&request_id);
+    Ok(running.then(|| limiter.estimate(&request_id, 0)))
+}
@@ -0,0 +1,212 @@
+//! =============================================================================
+//! Platform IPC Endpoint
+//! =============================================================================
+//!
+//! The engine listens on a local IPC endpoint whose kind depends on the OS:
+//!
+//!   • Unix (macOS, Linux) - Unix domain socket, by default
+//!                           $XDG_RUNTIME_DIR/<namespace>.sock if set, otherwise
+//!                           <app data dir>/<namespace>.sock
+//!   • Windows             - named pipe, by default \\.\pipe\<namespace>-<user>
+//!
+//! Both defaults are per user and per build (see runtime_identity for the
+//! namespace), so two users on one machine, a dev build next to an installed
+//! release, or a sandboxed macOS install without access to /tmp don't collide. `settings.socket.path`
+//! overrides the default; `set_socket_path` changes it from the UI.
+//!
+//! The endpoint is resolved each time the engine starts (`activate`) and
+//! passed to it in AI_ENGINE_SOCKET; a restart hands over to an engine on an
+//! alternate endpoint (see handoff). Everything that talks to the engine
+//! connects through `connect()` and works on the returned `EngineStream`, so
+//! the HTTP helpers, the mux client and streaming are platform independent.
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use specta::Type;
+use tauri::{AppHandle, Manager, State};
+use tauri::async_runtime::Mutex;
+use std::io;
+use std::sync::RwLock;
+
+use crate::error::EngineError;
+use crate::runtime_identity;
+use crate::settings::SettingsStore;
+use crate::PythonProcess;
+
+/// Environment variable telling the engine where to listen
+pub(crate) const SOCKET_ENV_VAR: &str = "AI_ENGINE_SOCKET";
+
+/// Longest Unix socket path accepted (sun_path is 104 bytes on macOS)
+#[cfg(unix)]
+const MAX_SOCKET_PATH_LEN: usize = 100;
+
+// ==================== Socket Configuration ====================
+
+/// Endpoint settings, persisted under `settings.socket`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(default)]
+pub struct SocketConfig {
+    /// Explicit socket path (or pipe name on Windows); None uses the per-user default
+    pub path: Option<String>,
+}
+
+/// Endpoint of the current (or last started) engine
+static ACTIVE_ENDPOINT: RwLock<String> = RwLock::new(String::new());
+
+/// Directory of the per-user default socket.
+#[cfg(unix)]
+pub(crate) fn default_socket_dir(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    match std::env::var_os("XDG_RUNTIME_DIR").map(std::path::PathBuf::from) {
+        Some(dir) if dir.is_dir() => Ok(dir),
+        _ => app.path().app_data_dir()
+            .map_err(|e| format!("Failed to resolve app data dir: {}", e)),
+    }
+}
+
+/// Directory the configured socket lives in (without creating it).
+#[cfg(unix)]
+pub(crate) fn socket_dir(app: &AppHandle, config: &SocketConfig) -> Result<std::path::PathBuf, String> {
+    match &config.path {
+        Some(path) => std::path::Path::new(path)
+            .parent()
+            .map(|dir| dir.to_path_buf())
+            .ok_or_else(|| format!("Socket path has no parent directory: {}", path)),
+        None => default_socket_dir(app),
+    }
+}
+
+/// Per-user default endpoint for this platform.
+#[cfg(unix)]
+fn default_endpoint(app: &AppHandle) -> Result<String, String> {
+    let dir = default_socket_dir(app)?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    Ok(dir.join(format!("{}.sock", runtime_identity::namespace(app))).to_string_lossy().into_owned())
+}
+
+/// Per-user default endpoint for this platform.
+#[cfg(windows)]
+fn default_endpoint(app: &AppHandle) -> Result<String, String> {
+    let user = std::env::var("USERNAME").unwrap_or_else(|_| "default".to_string());
+    Ok(format!(r"\\.\pipe\{}-{}", runtime_identity::namespace(app), user))
+}
+
+/// Check that an explicitly configured endpoint is usable.
+fn validate_endpoint(path: &str) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        if !std::path::Path::new(path).is_absolute() {
+            return Err(format!("Socket path must be absolute: {}", path));
+        }
+        if path.len() > MAX_SOCKET_PATH_LEN {
+            return Err(format!("Socket path is longer than {} bytes: {}", MAX_SOCKET_PATH_LEN, path));
+        }
+    }
+    #[cfg(windows)]
+    {
+        if !path.starts_with(r"\\.\pipe\") {
+            return Err(format!(r"Pipe name must start with \\.\pipe\: {}", path));
+        }
+    }
+    Ok(())
+}
+
+/// The endpoint `config` resolves to.
+fn resolve(app: &AppHandle, config: &SocketConfig) -> Result<String, String> {
+    match &config.path {
+        Some(path) => validate_endpoint(path).map(|_| path.clone()),
+        None => default_endpoint(app),
+    }
+}
+
+/// The endpoint the settings currently resolve to.
+pub(crate) async fn configured_endpoint(app: &AppHandle) -> Result<String, String> {
+    let config = app.state::<Mutex<SettingsStore>>().lock().await.settings.socket.clone();
+    resolve(app, &config)
+}
+
+/// Resolve the configured endpoint for an engine about to start and make it current.
+pub(crate) async fn activate(app: &AppHandle) -> Result<String, String> {
+    let endpoint = configured_endpoint(app).await?;
+    set_active_endpoint(&endpoint);
+    Ok(endpoint)
+}
+
+/// Make `endpoint` current without consulting the settings (e.g. a mock engine).
+pub(crate) fn set_active_endpoint(endpoint: &str) {
+    *ACTIVE_ENDPOINT.write().unwrap_or_else(|e| e.into_inner()) = endpoint.to_string();
+}
+
+/// Endpoint of the current engine.
+pub(crate) fn active_endpoint() -> String {
+    ACTIVE_ENDPOINT.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Stream connected to the engine's IPC endpoint.
+#[cfg(unix)]
+pub type EngineStream = tokio::net::UnixStream;
+
+/// Stream connected to the engine's IPC endpoint.
+#[cfg(windows)]
+pub type EngineStream = tokio::net::windows::named_pipe::NamedPipeClient;
+
+/// Connect to the engine endpoint at `path`.
+#[cfg(unix)]
+pub async fn connect(path: &str) -> io::Result<EngineStream> {
+    tokio::net::UnixStream::connect(path).await
+}
+
+/// Connect to the engine endpoint at `path`.
+///
+/// A named pipe refuses clients while all its instances are busy, so this
+/// retries briefly on ERROR_PIPE_BUSY.
+#[cfg(windows)]
+pub async fn connect(path: &str) -> io::Result<EngineStream> {
+    use std::time::Duration;
+    use tokio::net::windows::named_pipe::ClientOptions;
+
+    /// Win32 ERROR_PIPE_BUSY
+    const ERROR_PIPE_BUSY: i32 = 231;
+    const PIPE_BUSY_RETRIES: u32 = 20;
+
+    let mut attempt = 0;
+    loop {
+        match ClientOptions::new().open(path) {
+            Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY) && attempt < PIPE_BUSY_RETRIES => {
+                attempt += 1;
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+// ==================== Tauri Command: set_socket_path ====================
+
+/// Override the engine socket path (None restores the per-user default).
+///
+/// Takes effect the next time the engine starts. Returns the endpoint that
+/// will be used.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_socket_path(
+    app: AppHandle,
+    path: Option<String>,
+    store: State<'_, Mutex<SettingsStore>>,
+    process: State<'_, Mutex<PythonProcess>>,
+) -> Result<String, EngineError> {
+    let config = SocketConfig { path: path.filter(|p| !p.trim().is_empty()) };
+    let endpoint = resolve(&app, &config).map_err(EngineError::InvalidRequest)?;
+
+    let mut store = store.lock().await;
+    store.settings.socket = config;
+    store.save()?;
+    drop(store);
+
+    if *process.lock().await.is_running.lock().await {
+        println!("Socket path set to {} (applies after the engine restarts)", endpoint);
+    } else {
+        println!("Socket path set to {}", endpoint);
+    }
+    Ok(endpoint)
+}
@@ -0,0 +1,572 @@
+//! =============================================================================
+//! Job Queue
+//! =============================================================================
+//!
+//! `submit_input` queues an input and returns its job id right away, so the
+//! UI can present a work queue instead of waiting on each call:
+//!
+//!   queued  →  running  →  completed | failed
+//!      └──────────┴──────→  cancelled          (cancel_job)
+//!
+//! Queued jobs start by priority (higher first), then in submission order,
+//! with at most `settings.jobs.concurrency` running at a time (plus the
+//! workers a capacity reservation adds for its own jobs, which start first;
+//! see reservations). A running job is an ordinary input request (see
+//! `process_input`) whose correlation id is the job id, so `cancel_job`
+//! aborts it like `cancel_request` does.
+//!
+//! Every state change is emitted as `job_updated`. Finished jobs are kept
+//! for `get_job_status` / `list_jobs` until MAX_FINISHED_JOBS newer ones
+//! have finished.
+//!
+//! Jobs run on `concurrency` batch workers. Next to them is the interactive
+//! worker, which serves `send_input_to_python`. With
+//! `settings.jobs.work_stealing`, a queued job may borrow the interactive
+//! worker while no interactive input is running. An interactive input
+//! reclaims it immediately: the borrowed job is aborted and goes back to the
+//! head of the queue (state `queued` again) to be rerun.
+//! `get_worker_utilization` reports how busy each worker has been.
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use specta::Type;
+use tauri::{AppHandle, Manager, State};
+use tauri::async_runtime::Mutex;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::future::Future;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::error::EngineError;
+use crate::events::{self, JobUpdated};
+use crate::feature_flags;
+use crate::requests::{self, ActiveRequests};
+use crate::reservations;
+use crate::session_models::InputRoute;
+use crate::settings::SettingsStore;
+use crate::process_input;
+
+/// Finished jobs kept for status queries
+const MAX_FINISHED_JOBS: usize = 100;
+
+/// Source of job ids
+static NEXT_JOB_ID: AtomicU64 = AtomicU64::new(1);
+
+// ==================== Settings ====================
+
+/// Job queue settings, persisted under `settings.jobs`.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(default)]
+pub struct JobSettings {
+    /// Jobs running against the engine at the same time
+    #[schemars(range(min = 1))]
+    pub concurrency: usize,
+    /// Let a queued job use the interactive worker while it is idle
+    pub work_stealing: bool,
+}
+
+impl Default for JobSettings {
+    fn default() -> Self {
+        JobSettings { concurrency: 1, work_stealing: false }
+    }
+}
+
+// ==================== Types ====================
+
+/// Lifecycle state of a job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobState {
+    fn is_finished(self) -> bool {
+        matches!(self, JobState::Completed | JobState::Failed | JobState::Cancelled)
+    }
+}
+
+/// A job as reported to the frontend.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct JobStatus {
+    pub id: String,
+    pub state: JobState,
+    pub priority: i32,
+    /// Place in the queue (0 = next to start) while queued
+    pub position: Option<usize>,
+    pub submitted_at: u64,
+    pub started_at: Option<u64>,
+    pub finished_at: Option<u64>,
+    /// Engine response of a completed job
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+/// A worker jobs and interactive inputs run on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Worker {
+    Interactive,
+    Batch(usize),
+}
+
+impl std::fmt::Display for Worker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Worker::Interactive => write!(f, "interactive"),
+            Worker::Batch(index) => write!(f, "batch-{}", index),
+        }
+    }
+}
+
+/// Busy time of one worker.
+#[derive(Default)]
+struct WorkerUsage {
+    busy_since: Option<Instant>,
+    busy: Duration,
+    /// Jobs and interactive inputs started on the worker
+    tasks: u64,
+}
+
+/// Utilization of one worker as reported to the frontend.
+#[derive(Debug, Serialize, Type)]
+pub struct WorkerUtilization {
+    /// `interactive` or `batch-<n>`
+    pub worker: String,
+    pub busy: bool,
+    /// Job running on the worker, if any
+    pub job_id: Option<String>,
+    /// Jobs and interactive inputs started on the worker
+    pub tasks: u64,
+    pub busy_ms: u64,
+    /// Share of the time since tracking started that the worker was busy (0-1)
+    pub utilization: f64,
+}
+
+/// A submitted job and what it needs to run.
+struct Job {
+    status: JobStatus,
+    input: String,
+    timeout_ms: Option<u64>,
+    route: Option<InputRoute>,
+    /// Submission order, breaking priority ties
+    seq: u64,
+    /// Worker the job is running on
+    worker: Option<Worker>,
+    /// Aborted to free the interactive worker; requeued once its request returns
+    preempted: bool,
+}
+
+/// All known jobs, by id, and the workers they run on.
+pub struct JobQueueState {
+    jobs: BTreeMap<String, Job>,
+    /// Interactive inputs in flight
+    interactive_inputs: usize,
+    usage: BTreeMap<Worker, WorkerUsage>,
+    tracked_since: Instant,
+}
+
+impl Job {
+    /// Reservation the job was submitted under, if any.
+    fn reservation(&self) -> Option<&str> {
+        self.route.as_ref().and_then(|r| r.reservation_id.as_deref())
+    }
+}
+
+impl Default for JobQueueState {
+    fn default() -> Self {
+        JobQueueState {
+            jobs: BTreeMap::new(),
+            interactive_inputs: 0,
+            usage: BTreeMap::new(),
+            tracked_since: Instant::now(),
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl JobQueueState {
+    /// Queued job ids in start order (jobs of an active reservation first).
+    fn queue_order(&self) -> Vec<String> {
+        let mut queued: Vec<&Job> = self.jobs.values().filter(|j| j.status.state == JobState::Queued).collect();
+        queued.sort_by_key(|j| {
+            let reserved = j.reservation().is_some_and(reservations::is_active);
+            (std::cmp::Reverse(reserved), std::cmp::Reverse(j.status.priority), j.seq)
+        });
+        queued.into_iter().map(|j| j.status.id.clone()).collect()
+    }
+
+    /// Status of job `id` with its current queue position.
+    fn status(&self, id: &str) -> Option<JobStatus> {
+        let mut status = self.jobs.get(id)?.status.clone();
+        if status.state == JobState::Queued {
+            status.position = self.queue_order().iter().position(|queued| queued == id);
+        }
+        Some(status)
+    }
+
+    /// Number of jobs waiting to start.
+    pub(crate) fn queued(&self) -> usize {
+        self.jobs.values().filter(|j| j.status.state == JobState::Queued).count()
+    }
+
+    /// Job running on `worker`.
+    fn job_on(&self, worker: Worker) -> Option<&Job> {
+        self.jobs.values().find(|j| j.status.state == JobState::Running && j.worker == Some(worker))
+    }
+
+    fn is_busy(&self, worker: Worker) -> bool {
+        self.job_on(worker).is_some() || (worker == Worker::Interactive && self.interactive_inputs > 0)
+    }
+
+    /// Count a task started on `worker` and start its busy time.
+    fn occupy(&mut self, worker: Worker) {
+        let usage = self.usage.entry(worker).or_default();
+        usage.tasks += 1;
+        usage.busy_since.get_or_insert_with(Instant::now);
+    }
+
+    /// Stop `worker`'s busy time once nothing runs on it anymore.
+    fn release(&mut self, worker: Worker) {
+        if self.is_busy(worker) {
+            return;
+        }
+        if let Some(usage) = self.usage.get_mut(&worker) {
+            if let Some(since) = usage.busy_since.take() {
+                usage.busy += since.elapsed();
+            }
+        }
+    }
+
+    /// Forget the oldest finished jobs beyond MAX_FINISHED_JOBS.
+    fn prune(&mut self) {
+        let mut finished: Vec<(u64, String)> = self.jobs.values()
+            .filter(|j| j.status.state.is_finished())
+            .map(|j| (j.seq, j.status.id.clone()))
+            .collect();
+        let excess = finished.len().saturating_sub(MAX_FINISHED_JOBS);
+        finished.sort();
+        for (_, id) in finished.into_iter().take(excess) {
+            self.jobs.remove(&id);
+        }
+    }
+}
+
+// ==================== Dispatch ====================
+
+/// Start queued jobs on the free workers.
+///
+/// These are the idle batch workers, plus the interactive worker when work
+/// stealing is on and no interactive input is running. The workers added by
+/// a reservation (numbered after the regular ones) only take its jobs.
+/// Nothing starts while the jobs feature is disabled.
+async fn dispatch(app: &AppHandle) {
+    // Queued jobs wait while the feature is disabled (settings changes dispatch again)
+    if feature_flags::check("jobs").is_err() {
+        return;
+    }
+    let settings = app.state::<Mutex<SettingsStore>>().lock().await.settings.jobs.clone();
+    let queue = app.state::<Mutex<JobQueueState>>();
+    let mut queue = queue.lock().await;
+
+    let regular = settings.concurrency.max(1);
+    let mut free: Vec<Worker> = (0..regular)
+        .map(Worker::Batch)
+        .filter(|worker| !queue.is_busy(*worker))
+        .collect();
+    if settings.work_stealing && !queue.is_busy(Worker::Interactive) {
+        free.push(Worker::Interactive);
+    }
+    let mut free = free.into_iter();
+
+    // Idle workers of each reservation: its size minus its jobs on added workers
+    let mut spare = reservations::workers();
+    let on_added = |job: &&Job| job.status.state == JobState::Running && matches!(job.worker, Some(Worker::Batch(n)) if n >= regular);
+    for job in queue.jobs.values().filter(on_added) {
+        if let Some(count) = job.reservation().and_then(|id| spare.get_mut(id)) {
+            *count = count.saturating_sub(1);
+        }
+    }
+    let mut added = (regular..).map(Worker::Batch).filter(|worker| !queue.is_busy(*worker));
+
+    let mut assigned: Vec<(String, Worker)> = Vec::new();
+    for id in queue.queue_order() {
+        let reserved = queue.jobs.get(&id)
+            .and_then(Job::reservation)
+            .and_then(|reservation| spare.get_mut(reservation))
+            .filter(|count| **count > 0);
+        let worker = match reserved {
+            Some(count) => {
+                *count -= 1;
+                added.next()
+            }
+            None => free.next(),
+        };
+        if let Some(worker) = worker {
+            assigned.push((id, worker));
+        }
+    }
+    let started: Vec<String> = assigned.iter().map(|(id, _)| id.clone()).collect();
+    for (id, worker) in &assigned {
+        let Some(job) = queue.jobs.get_mut(id) else {
+            continue;
+        };
+        job.status.state = JobState::Running;
+        job.status.started_at = Some(unix_now());
+        job.worker = Some(*worker);
+        let (input, timeout_ms, route) = (job.input.clone(), job.timeout_ms, job.route.clone());
+        queue.occupy(*worker);
+        println!("Starting job {} on the {} worker", id, worker);
+
+        let (app, id) = (app.clone(), id.clone());
+        tauri::async_runtime::spawn(async move {
+            let result = process_input(&app, "job", input, timeout_ms, None, Some(id.clone()), route).await;
+            finish(&app, &id, result).await;
+            schedule(app);
+        });
+    }
+    // Started jobs changed state, the remaining queued ones moved up
+    let updated: Vec<JobStatus> = started.into_iter()
+        .chain(queue.queue_order())
+        .filter_map(|id| queue.status(&id))
+        .collect();
+    drop(queue);
+    for status in updated {
+        events::emit(app, JobUpdated(status));
+    }
+}
+
+/// Run `dispatch` in the background (once a job has finished or workers were added).
+pub(crate) fn schedule(app: AppHandle) {
+    tauri::async_runtime::spawn(async move { dispatch(&app).await });
+}
+
+/// Record the outcome of a job that ran.
+async fn finish(app: &AppHandle, id: &str, result: Result<serde_json::Value, EngineError>) {
+    let queue = app.state::<Mutex<JobQueueState>>();
+    let mut queue = queue.lock().await;
+    let Some(job) = queue.jobs.get_mut(id) else {
+        return;
+    };
+    let worker = job.worker.take();
+    if job.preempted && matches!(result, Err(EngineError::Cancelled(_))) {
+        // Back to the head of its priority, to rerun on the next free worker
+        job.preempted = false;
+        job.status.state = JobState::Queued;
+        job.status.started_at = None;
+        println!("Job {} preempted, requeued", id);
+        if let Some(worker) = worker {
+            queue.release(worker);
+        }
+        let status = queue.status(id);
+        drop(queue);
+        if let Some(status) = status {
+            events::emit(app, JobUpdated(status));
+        }
+        return;
+    }
+    job.preempted = false;
+    job.status.finished_at = Some(unix_now());
+    match result {
+        Ok(response) => {
+            job.status.state = JobState::Completed;
+            job.status.result = Some(response);
+        }
+        Err(EngineError::Cancelled(_)) => job.status.state = JobState::Cancelled,
+        Err(e) => {
+            job.status.state = JobState::Failed;
+            job.status.error = Some(e.to_string());
+        }
+    }
+    println!("Job {} {:?}", id, job.status.state);
+    let status = job.status.clone();
+    if let Some(worker) = worker {
+        queue.release(worker);
+    }
+    queue.prune();
+    drop(queue);
+    events::emit(app, JobUpdated(status));
+}
+
+/// Run an interactive input on the interactive worker.
+///
+/// A job borrowing the worker (work stealing) is aborted and requeued first.
+pub(crate) async fn interactive<T>(app: &AppHandle, input: impl Future<Output = T>) -> T {
+    let preempted = {
+        let queue = app.state::<Mutex<JobQueueState>>();
+        let mut queue = queue.lock().await;
+        queue.interactive_inputs += 1;
+        queue.occupy(Worker::Interactive);
+        let stolen = queue.job_on(Worker::Interactive).filter(|job| !job.preempted).map(|job| job.status.id.clone());
+        if let Some(job) = stolen.as_ref().and_then(|id| queue.jobs.get_mut(id)) {
+            job.preempted = true;
+        }
+        stolen
+    };
+    if let Some(id) = preempted {
+        println!("Interactive input reclaims the interactive worker from job {}", id);
+        if !requests::cancel(app, &app.state::<ActiveRequests>(), &id).await {
+            // Not cancellable right now; it finishes on the interactive worker
+            if let Some(job) = app.state::<Mutex<JobQueueState>>().lock().await.jobs.get_mut(&id) {
+                job.preempted = false;
+            }
+        }
+    }
+
+    let output = input.await;
+
+    {
+        let queue = app.state::<Mutex<JobQueueState>>();
+        let mut queue = queue.lock().await;
+        queue.interactive_inputs -= 1;
+        queue.release(Worker::Interactive);
+    }
+    // The interactive worker may be free for a queued job again
+    schedule(app.clone());
+    output
+}
+
+// ==================== Tauri Commands ====================
+
+/// Queue an input for the engine; returns the job id immediately.
+///
+/// Takes the same `timeout_ms` and `route` as `send_input_to_python`;
+/// `priority` (default 0) lets urgent jobs start first.
+#[tauri::command]
+#[specta::specta]
+pub async fn submit_input(
+    app: AppHandle,
+    input: String,
+    priority: Option<i32>,
+    timeout_ms: Option<u64>,
+    route: Option<InputRoute>,
+    queue: State<'_, Mutex<JobQueueState>>,
+) -> Result<String, EngineError> {
+    let seq = NEXT_JOB_ID.fetch_add(1, Ordering::SeqCst);
+    let id = format!("job-{}", seq);
+    let status = JobStatus {
+        id: id.clone(),
+        state: JobState::Queued,
+        priority: priority.unwrap_or(0),
+        position: None,
+        submitted_at: unix_now(),
+        started_at: None,
+        finished_at: None,
+        result: None,
+        error: None,
+    };
+    queue.lock().await.jobs.insert(id.clone(), Job { status, input, timeout_ms, route, seq, worker: None, preempted: false });
+    println!("Queued job {}", id);
+
+    dispatch(&app).await;
+    Ok(id)
+}
+
+/// Return the status of job `id`.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_job_status(id: String, queue: State<'_, Mutex<JobQueueState>>) -> Result<JobStatus, EngineError> {
+    queue.lock().await
+        .status(&id)
+        .ok_or_else(|| EngineError::InvalidRequest(format!("Unknown job {}", id)))
+}
+
+/// List all known jobs: running, then queued in start order, then finished (newest first).
+#[tauri::command]
+#[specta::specta]
+pub async fn list_jobs(queue: State<'_, Mutex<JobQueueState>>) -> Result<Vec<JobStatus>, EngineError> {
+    let queue = queue.lock().await;
+    let mut jobs: Vec<(u8, usize, std::cmp::Reverse<u64>, JobStatus)> = queue.jobs.values()
+        .filter_map(|job| {
+            let status = queue.status(&job.status.id)?;
+            let group = match status.state {
+                JobState::Running => 0,
+                JobState::Queued => 1,
+                _ => 2,
+            };
+            Some((group, status.position.unwrap_or(0), std::cmp::Reverse(job.seq), status))
+        })
+        .collect();
+    jobs.sort_by_key(|(group, position, seq, _)| (*group, *position, *seq));
+    Ok(jobs.into_iter().map(|(_, _, _, status)| status).collect())
+}
+
+/// Cancel job `id`: a queued job is dropped from the queue, a running one is aborted.
+#[tauri::command]
+#[specta::specta]
+pub async fn cancel_job(
+    app: AppHandle,
+    id: String,
+    queue: State<'_, Mutex<JobQueueState>>,
+    active: State<'_, ActiveRequests>,
+) -> Result<(), EngineError> {
+    let mut jobs = queue.lock().await;
+    let Some(job) = jobs.jobs.get_mut(&id) else {
+        return Err(EngineError::InvalidRequest(format!("Unknown job {}", id)));
+    };
+    match job.status.state {
+        JobState::Queued => {
+            job.status.state = JobState::Cancelled;
+            job.status.finished_at = Some(unix_now());
+            let status = job.status.clone();
+            jobs.prune();
+            drop(jobs);
+            println!("Cancelled queued job {}", id);
+            events::emit(&app, JobUpdated(status));
+            // Later jobs move up
+            dispatch(&app).await;
+            Ok(())
+        }
+        JobState::Running => {
+            // A job cancelled by the user is not requeued
+            job.preempted = false;
+            drop(jobs);
+            // The job's task records the cancellation once its request returns
+            if !requests::cancel(&app, &active, &id).await {
+                return Err(EngineError::InvalidRequest(format!("Job {} is starting or already finishing", id)));
+            }
+            Ok(())
+        }
+        _ => Err(EngineError::InvalidRequest(format!("Job {} has already finished", id))),
+    }
+}
+
+/// Report how busy each worker has been since the app started.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_worker_utilization(
+    queue: State<'_, Mutex<JobQueueState>>,
+    settings: State<'_, Mutex<SettingsStore>>,
+) -> Result<Vec<WorkerUtilization>, EngineError> {
+    let concurrency = settings.lock().await.settings.jobs.concurrency.max(1);
+    let queue = queue.lock().await;
+    let tracked = queue.tracked_since.elapsed();
+
+    // Configured workers, plus batch workers left over from a higher concurrency
+    let mut workers: Vec<Worker> = std::iter::once(Worker::Interactive)
+        .chain((0..concurrency).map(Worker::Batch))
+        .collect();
+    workers.extend(queue.usage.keys().filter(|worker| !workers.contains(worker)).copied().collect::<Vec<_>>());
+
+    Ok(workers.into_iter().map(|worker| {
+        let usage = queue.usage.get(&worker);
+        let busy = usage.map(|u| u.busy + u.busy_since.map(|since| since.elapsed()).unwrap_or_default()).unwrap_or_default();
+        WorkerUtilization {
+            worker: worker.to_string(),
+            busy: queue.is_busy(worker),
+            job_id: queue.job_on(worker).map(|job| job.status.id.clone()),
+            tasks: usage.map(|u| u.tasks).unwrap_or(0),
+            busy_ms: busy.as_millis() as u64,
+            utilization: (busy.as_secs_f64() / tracked.as_secs_f64().max(f64::EPSILON)).min(1.0),
+        }
+    }).collect())
+}
@@ -15,7 +15,7 @@
 //!   └────────────────┬────────────────────────────┘
 //!                    │
 //!         Unix Domain Socket (UDS)
-//!         /tmp/ai-engine.sock
+//!         <app data dir>/<namespace>.sock
 //!                    │
 //!   ┌────────────────▼────────────────────────────┐
 //! Python AI Engine (Hypercorn/Starlette)       │
@@ -26,41 +26,183 @@
 //!   └─────────────────────────────────────────────┘
 //!
 //! Communication:
-//!   • Unix Domain Socket (/tmp/ai-engine.sock)
+//!   • Unix Domain Socket (per user, configurable - see ipc.rs)
+//!   • Named pipe on Windows (\\.\pipe\<namespace>-<user>)
 //!   • HTTP/1.1 over Unix socket (via Hypercorn)
 //!   • No TCP overhead, direct kernel IPC
 //!
 //! Key Features:
 //!   • Unix Socket Communication - Enterprise-grade IPC with file permissions
 //!   • Memory Optimization - TensorFlow/PyTorch stay resident
-//!   • Idle Timeout (5 min) - Automatically stops server when inactive
+//!   • Idle Timeout (5 min, adjustable at runtime) - Automatically stops server when inactive
 //!   • Binary Support - Works with PyInstaller compiled executables
 //!   • Graceful Shutdown - Clean termination with signal handling
 
 use tauri_plugin_shell::ShellExt;
-use tauri::{AppHandle, State, Emitter};
-use tauri::async_runtime::Mutex;
-use std::time::{Duration, Instant};
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use tauri::{AppHandle, Manager, State, Webview};
+use tauri::async_runtime::{Mutex, Receiver};
+use std::time::{Duration, Instant, SystemTime};
 use std::sync::Arc;
-use std::path::Path;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::path::PathBuf;
+
+mod analytics;
+mod artifacts;
+mod atomic_file;
+mod audit;
+mod auth;
+mod backup;
+mod broker;
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod bench_support;
+mod budget;
+mod capabilities;
+mod clock_sync;
+mod compression;
+mod context_menu;
+mod crash_supervisor;
+mod deprecations;
+mod dev_engine;
+mod dev_python;
+mod downloads;
+mod drain;
+mod engine_auth;
+mod engine_events;
+mod engine_logs;
+mod engine_metrics;
+mod engine_queue;
+mod engine_state;
+mod engine_variants;
+mod error;
+mod events;
+mod extraction;
+mod feature_flags;
+mod generation;
+mod handoff;
+mod heartbeat;
+mod history;
+mod history_store;
+mod hooks;
+mod host_requests;
+mod input_limiter;
+mod ipc;
+mod jobs;
+mod licenses;
+mod metrics_history;
+mod model_downloads;
+mod model_fallback;
+mod models;
+mod moderation;
+mod mux;
+mod ndjson;
+mod network_activity;
+mod otel;
+mod performance;
+mod pipelines;
+mod prefetch;
+mod proxy;
+mod recorder;
+mod recycling;
+mod repair;
+mod replay;
+mod requests;
+mod reservations;
+mod resources;
+mod resync;
+mod retention;
+mod runtime_identity;
+mod session_models;
+mod sessions;
+mod settings;
+mod settings_schema;
+mod shutdown;
+mod socket_watch;
+mod stale_engine;
+mod startup_gate;
+mod status_delta;
+mod status_summary;
+mod streaming;
+mod temp_files;
+mod templates;
+mod trace_context;
+mod transport;
+mod turbo;
+mod updates;
+mod uploads;
+mod warmup;
+
+use budget::{CommandClass, CommandTimer, Timed};
+use capabilities::EngineCapabilitiesState;
+use crash_supervisor::SupervisorState;
+use engine_logs::EngineLogBuffer;
+use engine_queue::EngineQueueState;
+use engine_state::{EngineLifecycle, EngineState};
+pub use error::{EngineError, SocketFailure};
+use events::PythonInput;
+use extraction::ExtractionWatch;
+use generation::GenerationDefaults;
+use heartbeat::Heartbeat;
+pub use hooks::EngineCall;
+use hooks::RequestHooks;
+use host_requests::HostRequestState;
+use input_limiter::InputLimiter;
+use jobs::JobQueueState;
+use metrics_history::MetricsHistory;
+use model_fallback::ModelSelectionState;
+use mux::{MuxClient, MuxSlot};
+use network_activity::NetworkActivityState;
+use otel::RequestTrace;
+use requests::ActiveRequests;
+use resources::EngineResourcesState;
+use session_models::InputRoute;
+use settings::SettingsStore;
+use startup_gate::StartupGate;
+use status_delta::StatusDeltaState;
+use status_summary::StatusSummaryState;
+use streaming::{StreamChannel, StreamWriter};
+use temp_files::TempFiles;
+use turbo::TurboState;
+use updates::UpdateState;
 
 // Store the running Python process and idle timer
 pub struct PythonProcess {
     child: Option<Box<dyn std::any::Any + Send>>,
     last_activity: Arc<Mutex<Instant>>,
     is_running: Arc<Mutex<bool>>,
+    supervisor_tick: Arc<Mutex<Instant>>,
+    turbo: Arc<Mutex<TurboState>>,
+    mux: MuxSlot,
+    /// Requests awaiting an engine response; the idle timeout waits for them
+    in_flight: Arc<AtomicUsize>,
+    /// Idle timeout in seconds; 0 keeps the engine resident indefinitely
+    idle_timeout_secs: Arc<AtomicU64>,
+    draining: Arc<AtomicBool>,
+    startup_gate: Arc<StartupGate>,
+    /// Incremented on every spawn; identifies the current engine process
+    engine_generation: Arc<AtomicU64>,
+    /// Lifecycle state; transitions are emitted as `engine_state_changed`
+    lifecycle: EngineLifecycle,
+    /// Model the engine has loaded, as last reported (see models)
+    loaded_model: Option<String>,
 }
 
 // Wrapper to handle state cloning for async tasks
 pub struct PythonProcessState {
     last_activity: Arc<Mutex<Instant>>,
     is_running: Arc<Mutex<bool>>,
+    supervisor_tick: Arc<Mutex<Instant>>,
+    turbo: Arc<Mutex<TurboState>>,
+    in_flight: Arc<AtomicUsize>,
+    idle_timeout_secs: Arc<AtomicU64>,
+    engine_generation: Arc<AtomicU64>,
 }
 
 // ==================== Configuration Constants ====================
 
 /// Idle timeout: If no activity for this duration, server stops automatically
+/// (default; see set_idle_timeout / disable_idle_timeout)
 const IDLE_TIMEOUT_SECS: u64 = 300; // 5 minutes
 
 /// Health check: Maximum retries when waiting for server to start
@@ -69,95 +211,153 @@ const HEALTH_CHECK_RETRIES: u32 = 20;
 /// Health check: Delay between consecutive startup attempts
 const HEALTH_CHECK_INTERVAL_MS: u64 = 500;
 
+/// Health check: Time allowed for a single GET /health during startup
+const HEALTH_PROBE_TIMEOUT_MS: u64 = 2_000;
+
 /// Status polling: How often we check server health
-const STATUS_POLL_INTERVAL_SECS: u64 = 1;
+pub(crate) const STATUS_POLL_INTERVAL_SECS: u64 = 1;
+
+/// Shutdown: Grace period between the /stop request and killing the process
+pub(crate) const SHUTDOWN_GRACE_MS: u64 = 500;
+
+/// TypeScript bindings for the frontend, regenerated on debug runs
+#[cfg(debug_assertions)]
+const BINDINGS_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../src/bindings.ts");
+
+/// Sidecar name of the AI Engine binary (bundle.externalBin in tauri.conf.json)
+const ENGINE_SIDECAR: &str = "ai-engine";
+
+/// Resource directory holding an optional onedir build of the AI Engine
+const ENGINE_ONEDIR_RESOURCE: &str = "binaries/ai-engine-onedir";
 
 /// Socket file permissions: Owner can read/write only (0o600)
+#[cfg(unix)]
 const SOCKET_PERMISSIONS: u32 = 0o600;
 
 // ==================== Socket Path Management ====================
 
-/// Get the Unix socket path used for IPC communication.
-/// Default: /tmp/ai-engine.sock
+/// Get the socket path used for IPC communication.
+/// Resolved from settings.socket when the engine starts (see ipc.rs).
 /// The socket file will be created by the Python server.
 fn get_socket_path() -> String {
-    "/tmp/ai-engine.sock".to_string()
+    ipc::active_endpoint()
+}
+
+/// Restrict the socket to its owner (SOCKET_PERMISSIONS).
+#[cfg(unix)]
+fn restrict_socket_permissions(socket_path: &str) {
+    use std::os::unix::fs::PermissionsExt;
+
+    let permissions = std::fs::Permissions::from_mode(SOCKET_PERMISSIONS);
+    if let Err(e) = std::fs::set_permissions(socket_path, permissions) {
+        println!("Failed to restrict permissions on {}: {}", socket_path, e);
+    }
 }
 
-/// Check if Unix socket file exists and is ready for connections.
-/// Returns true if socket exists and is accessible.
-fn is_socket_ready(socket_path: &str) -> bool {
-    Path::new(socket_path).exists()
+/// Check if the engine socket is accepting connections.
+/// Returns true only if a connection succeeds - a socket file left behind
+/// by a crashed engine exists but refuses connections.
+async fn is_socket_ready(socket_path: &str) -> bool {
+    ipc::connect(socket_path).await.is_ok()
 }
 
 // ==================== Utility Functions ====================
 
-/// Get the compiled AI Engine binary path based on platform and architecture.
+/// Get the path the AI Engine sidecar resolves to.
 /// 
-/// Returns the path to prebuilt executable in src-tauri/binaries/
-/// Format: ai-engine-{arch}-{os}[-exe]
-fn get_ai_engine_binary() -> String {
-    #[cfg(target_os = "macos")]
-    {
-        #[cfg(target_arch = "aarch64")]
-        {
-            "../src-tauri/binaries/ai-engine-aarch64-apple-darwin".to_string()
-        }
-        #[cfg(target_arch = "x86_64")]
-        {
-            "../src-tauri/binaries/ai-engine-x86_64-apple-darwin".to_string()
-        }
-    }
-    #[cfg(target_os = "linux")]
-    {
-        #[cfg(target_arch = "x86_64")]
-        {
-            "../src-tauri/binaries/ai-engine-x86_64-unknown-linux-gnu".to_string()
-        }
-        #[cfg(target_arch = "aarch64")]
-        {
-            "../src-tauri/binaries/ai-engine-aarch64-unknown-linux-gnu".to_string()
+/// The binary is bundled as a Tauri sidecar (bundle.externalBin
+/// "binaries/ai-engine"): built as binaries/ai-engine-{target-triple}[.exe]
+/// and installed next to the app executable without the triple, both by
+/// `tauri dev` and in production bundles on all platforms.
+fn get_ai_engine_binary() -> Result<PathBuf, String> {
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("Failed to locate app executable: {}", e))?;
+    let dir = exe.parent()
+        .ok_or_else(|| format!("App executable {:?} has no parent directory", exe))?;
+    Ok(dir.join(format!("{}{}", ENGINE_SIDECAR, std::env::consts::EXE_SUFFIX)))
+}
+
+/// Get the onedir (unpacked) build of the AI Engine, if one is bundled.
+///
+/// A PyInstaller onedir build shipped as the resource directory
+/// ENGINE_ONEDIR_RESOURCE starts without the onefile self-extraction step,
+/// so it is preferred over the sidecar when present.
+fn get_onedir_engine(app: &AppHandle) -> Option<PathBuf> {
+    let path = app.path().resource_dir().ok()?
+        .join(ENGINE_ONEDIR_RESOURCE)
+        .join(format!("{}{}", ENGINE_SIDECAR, std::env::consts::EXE_SUFFIX));
+    path.is_file().then_some(path)
+}
+
+/// Check that the engine answers GET /health with 200 and a JSON body.
+///
+/// The socket can accept connections before Hypercorn serves requests, so
+/// a successful connection alone doesn't mean the engine is up.
+async fn check_health(socket_path: &str) -> Result<(), String> {
+    let probe = async {
+        let response = socket_http_send(socket_path, "GET", "/health", None, "application/json")
+            .await
+            .map_err(|e| e.to_string())?;
+        let status = response.status();
+        let bytes = hyper::body::to_bytes(response.into_body())
+            .await
+            .map_err(|e| format!("Failed to read /health response: {}", e))?;
+        if status != hyper::StatusCode::OK {
+            return Err(format!("/health returned status {}", status.as_u16()));
         }
-    }
-    #[cfg(target_os = "windows")]
-    {
-        "../src-tauri/binaries/ai-engine-x86_64-pc-windows-msvc.exe".to_string()
-    }
+        serde_json::from_slice::<serde_json::Value>(&bytes)
+            .map(|_| ())
+            .map_err(|e| format!("/health returned invalid JSON: {}", e))
+    };
+    tokio::time::timeout(Duration::from_millis(HEALTH_PROBE_TIMEOUT_MS), probe)
+        .await
+        .unwrap_or_else(|_| Err(format!("/health did not answer within {} ms", HEALTH_PROBE_TIMEOUT_MS)))
 }
 
-/// Wait for Unix socket to be ready and accepting connections.
+/// Wait for the engine to accept connections and pass its health check.
 /// 
-/// Attempts to connect to the socket file at the specified path.
-/// Returns Ok if socket is ready within HEALTH_CHECK_RETRIES attempts.
+/// Attempts to connect to the socket at the specified path, then issues
+/// GET /health (see check_health).
+/// Returns Ok if the engine is healthy within HEALTH_CHECK_RETRIES attempts.
+/// Attempts made while a onefile engine is still unpacking its runtime
+/// (see extraction) don't count.
 /// 
-/// This is the startup verification - we check for socket file existence
-/// rather than making HTTP requests.
-async fn wait_for_socket_ready() -> Result<(), String> {
-    let socket_path = get_socket_path();
-    
-    for attempt in 1..=HEALTH_CHECK_RETRIES {
-        if is_socket_ready(&socket_path) {
-            println!("Socket ready at {} (attempt {}/{})", socket_path, attempt, HEALTH_CHECK_RETRIES);
-            return Ok(());
+/// This is the startup verification - the engine is only marked running
+/// once this succeeds.
+async fn wait_for_socket_ready(timer: &CommandTimer, extraction: &ExtractionWatch, socket_path: &str) -> Result<(), String> {
+    let mut attempt = 0;
+    let mut last_error = format!("Socket failed to appear at {}", socket_path);
+    loop {
+        if is_socket_ready(socket_path).await {
+            match check_health(socket_path).await {
+                Ok(()) => {
+                    println!("Engine healthy at {} (attempt {}/{})", socket_path, attempt + 1, HEALTH_CHECK_RETRIES);
+                    return Ok(());
+                }
+                Err(e) => last_error = e,
+            }
+        }
+
+        if extraction.is_unpacking() {
+            timer.phase("unpacking_runtime").await;
+        } else {
+            timer.phase("waiting_for_socket").await;
+            attempt += 1;
         }
         
         if attempt >= HEALTH_CHECK_RETRIES {
-            return Err(format!(
-                "Socket failed to appear at {} after {} attempts",
-                socket_path, HEALTH_CHECK_RETRIES
-            ));
+            return Err(format!("{} after {} attempts", last_error, HEALTH_CHECK_RETRIES));
         }
         
         tokio::time::sleep(Duration::from_millis(HEALTH_CHECK_INTERVAL_MS)).await;
     }
-    
-    Err("Socket startup timeout".to_string())
 }
 
 /// Update activity timestamp (called when user interacts with app).
 /// 
-/// Resets the idle timer. If server hasn't been accessed for IDLE_TIMEOUT_SECS,
-/// it will be automatically stopped to save memory.
+/// Resets the idle timer. If server hasn't been accessed for the idle timeout
+/// (IDLE_TIMEOUT_SECS unless changed with set_idle_timeout), it will be
+/// automatically stopped to save memory.
 async fn update_activity_impl(last_activity_arc: &Arc<Mutex<Instant>>) {
     let mut last_activity = last_activity_arc.lock().await;
     *last_activity = Instant::now();
@@ -165,99 +365,191 @@ async fn update_activity_impl(last_activity_arc: &Arc<Mutex<Instant>>) {
 
 // ==================== Unix Socket HTTP Communication ====================
 
+/// Send an HTTP request over the engine socket and return the response.
+///
+/// Uses a one-shot hyper HTTP/1.1 connection on top of `ipc::connect`, so
+/// headers, status codes, Content-Length and chunked bodies are handled by
+/// hyper rather than by string matching. The caller consumes the body, which
+/// lets streaming readers process it incrementally.
+async fn socket_http_send(
+    socket_path: &str,
+    method: &str,
+    endpoint: &str,
+    body: Option<&serde_json::Value>,
+    accept: &str,
+) -> Result<hyper::Response<hyper::Body>, EngineError> {
+    socket_http_send_with_headers(socket_path, method, endpoint, body, accept, &[]).await
+}
+
+/// `socket_http_send` with extra request headers (e.g. added by request hooks).
+///
+/// Correlation headers (see trace_context) are added and the engine's echo
+/// of the trace id is verified, as is its proof of the secret if the app spawned it
+/// (see engine_auth).
+async fn socket_http_send_with_headers(
+    socket_path: &str,
+    method: &str,
+    endpoint: &str,
+    body: Option<&serde_json::Value>,
+    accept: &str,
+    headers: &[(String, String)],
+) -> Result<hyper::Response<hyper::Body>, EngineError> {
+    let mut request = hyper::Request::builder()
+        .method(method)
+        .uri(endpoint)
+        .header(hyper::header::HOST, "localhost")
+        .header(hyper::header::ACCEPT, accept);
+    let correlation = trace_context::headers_for(body);
+    for (name, value) in correlation.iter().chain(headers) {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    let keys = engine_auth::keys(socket_path);
+    if let Some(keys) = &keys {
+        request = request.header(hyper::header::AUTHORIZATION, engine_auth::authorization(keys));
+    }
+    let request = match body {
+        Some(body) => {
+            let body_bytes = serde_json::to_vec(body)
+                .map_err(|e| format!("Failed to serialize JSON: {}", e))?;
+            // Large bodies are gzipped when the engine accepts it
+            let (body_bytes, encoding) = compression::encode(body_bytes);
+            let request = request.header(hyper::header::CONTENT_TYPE, "application/json");
+            let request = match encoding {
+                Some(encoding) => request.header(hyper::header::CONTENT_ENCODING, encoding),
+                None => request,
+            };
+            request.body(hyper::Body::from(body_bytes))
+        }
+        None => request.body(hyper::Body::empty()),
+    }
+    .map_err(|e| EngineError::Internal(format!("Invalid request for {}: {}", endpoint, e)))?;
+
+    let response = socket_http_request(socket_path, request).await?;
+    let echoed = response.headers().get(trace_context::TRACE_ID_HEADER).and_then(|v| v.to_str().ok());
+    trace_context::verify_echo(endpoint, &correlation[0].1, echoed);
+    if let Some(keys) = &keys {
+        let proof = response.headers().get(engine_auth::PROOF_HEADER).and_then(|v| v.to_str().ok());
+        engine_auth::verify(keys, endpoint, &correlation[0].1, proof)?;
+    }
+    deprecations::inspect_headers(endpoint, response.headers());
+    Ok(response)
+}
+
+/// Send a prepared request over a fresh connection to the engine socket.
+async fn socket_http_request(socket_path: &str, request: hyper::Request<hyper::Body>) -> Result<hyper::Response<hyper::Body>, EngineError> {
+    let stream = ipc::connect(socket_path)
+        .await
+        .map_err(|e| EngineError::socket(&format!("Failed to connect to {}", socket_path), &e))?;
+    let (mut sender, connection) = hyper::client::conn::handshake(stream)
+        .await
+        .map_err(|e| EngineError::socket("HTTP handshake failed", &e))?;
+    // Drive the connection until the response body has been read
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = connection.await {
+            println!("Engine connection error: {}", e);
+        }
+    });
+
+    sender.send_request(request)
+        .await
+        .map_err(|e| EngineError::socket("Failed to send request", &e))
+}
+
+/// An engine response body as raw bytes, for binary payloads (audio, raw embeddings).
+pub(crate) struct RawResponse {
+    pub content_type: Option<String>,
+    pub body: hyper::body::Bytes,
+}
+
+/// Send a request and parse the JSON response (an empty body parses as `{}`).
+async fn socket_http_json(
+    socket_path: &str,
+    method: &str,
+    endpoint: &str,
+    body: Option<&serde_json::Value>,
+    headers: &[(String, String)],
+) -> Result<serde_json::Value, EngineError> {
+    let response = socket_http_send_with_headers(socket_path, method, endpoint, body, "application/json", headers).await?;
+    read_json_response(response).await
+}
+
+/// Send a request and return the response body as is, whatever its encoding.
+async fn socket_http_bytes(
+    socket_path: &str,
+    method: &str,
+    endpoint: &str,
+    body: Option<&serde_json::Value>,
+    headers: &[(String, String)],
+) -> Result<RawResponse, EngineError> {
+    let response = socket_http_send_with_headers(socket_path, method, endpoint, body, "*/*", headers).await?;
+    read_raw_response(response).await
+}
+
+/// Read a whole response body; error statuses become `EngineError::Http`.
+///
+/// hyper has already undone the transfer framing (Content-Length, or
+/// `Transfer-Encoding: chunked` as Hypercorn sends for larger or streamed
+/// replies), so the bytes are exactly what the engine wrote, whatever their
+/// size. A chunked body cut off before its final chunk fails here as a
+/// closed connection rather than yielding truncated JSON.
+///
+/// The body is kept as bytes, so non-UTF-8 payloads survive; only the error
+/// message is decoded (lossily).
+async fn read_raw_response(response: hyper::Response<hyper::Body>) -> Result<RawResponse, EngineError> {
+    let status = response.status();
+    let content_type = response.headers()
+        .get(hyper::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let body = hyper::body::to_bytes(response.into_body())
+        .await
+        .map_err(|e| EngineError::socket("Failed to read from socket", &e))?;
+
+    if !status.is_success() {
+        return Err(EngineError::http(status.as_u16(), &body));
+    }
+    Ok(RawResponse { content_type, body })
+}
+
+/// `EngineError::Http` for a response with an error status, read from its body.
+///
+/// For callers that consume successful bodies incrementally (streams, downloads).
+pub(crate) async fn error_response(response: hyper::Response<hyper::Body>) -> EngineError {
+    let status = response.status().as_u16();
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap_or_default();
+    EngineError::http(status, &body)
+}
+
+/// Read a JSON response body; error statuses become `EngineError::Http`.
+async fn read_json_response(response: hyper::Response<hyper::Body>) -> Result<serde_json::Value, EngineError> {
+    let RawResponse { body, .. } = read_raw_response(response).await?;
+    if body.iter().all(|b| b.is_ascii_whitespace()) {
+        return Ok(serde_json::json!({}));
+    }
+    serde_json::from_slice(&body)
+        .map_err(|e| EngineError::BadResponse(format!("invalid JSON: {}", e)))
+}
+
 /// Send an HTTP GET request over Unix domain socket.
 /// 
 /// This function creates an HTTP request to the Hypercorn server listening
 /// on a Unix socket. It's used for health checks and status polling.
-async fn socket_http_get(socket_path: &str, endpoint: &str) -> Result<serde_json::Value, String> {
-    use tokio::net::UnixStream;
-    
-    let mut stream = UnixStream::connect(socket_path)
-        .await
-        .map_err(|e| format!("Failed to connect to socket: {}", e))?;
-    
-    // Construct HTTP GET request
-    let request = format!(
-        "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
-        endpoint
-    );
-    
-    // Send request
-    stream.write_all(request.as_bytes())
-        .await
-        .map_err(|e| format!("Failed to write to socket: {}", e))?;
-    
-    // Read response
-    let mut response = String::new();
-    stream.read_to_string(&mut response)
-        .await
-        .map_err(|e| format!("Failed to read from socket: {}", e))?;
-    
-    // Parse HTTP response body (skip headers)
-    // Try Windows line endings first (\r\n\r\n), then Unix line endings (\n\n)
-    let body = if let Some(pos) = response.find("\r\n\r\n") {
-        &response[pos + 4..]
-    } else if let Some(pos) = response.find("\n\n") {
-        &response[pos + 2..]
-    } else {
-        return Err("Invalid HTTP response format - no body separator found".to_string());
-    };
-    
-    // Parse JSON from body
-    serde_json::from_str(body.trim())
-        .map_err(|e| format!("Failed to parse response JSON: {}", e))
+/// Fails with `EngineError::Timeout` after the endpoint's configured timeout.
+async fn socket_http_get(socket_path: &str, endpoint: &str) -> Result<serde_json::Value, EngineError> {
+    let timeout = transport::timeout_for(endpoint);
+    transport::with_timeout(endpoint, timeout, socket_http_json(socket_path, "GET", endpoint, None, &[])).await
 }
 
 /// Send an HTTP POST request with JSON body over Unix domain socket.
 /// 
 /// This function creates an HTTP POST request to the Hypercorn server.
 /// Used for sending user input and stop signals.
-async fn socket_http_post(socket_path: &str, endpoint: &str, body: &serde_json::Value) -> Result<serde_json::Value, String> {
-    use tokio::net::UnixStream;
-    
-    let mut stream = UnixStream::connect(socket_path)
-        .await
-        .map_err(|e| format!("Failed to connect to socket: {}", e))?;
-    
-    let body_str = serde_json::to_string(body)
-        .map_err(|e| format!("Failed to serialize JSON: {}", e))?;
-    
-    // Construct HTTP POST request
-    let request = format!(
-        "POST {} HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
-        endpoint,
-        body_str.len(),
-        body_str
-    );
-    
-    // Send request
-    stream.write_all(request.as_bytes())
-        .await
-        .map_err(|e| format!("Failed to write to socket: {}", e))?;
-    
-    // Read response
-    let mut response = String::new();
-    stream.read_to_string(&mut response)
-        .await
-        .map_err(|e| format!("Failed to read from socket: {}", e))?;
-    
-    // Parse HTTP response body (skip headers)
-    // Try Windows line endings first (\r\n\r\n), then Unix line endings (\n\n)
-    let body = if let Some(pos) = response.find("\r\n\r\n") {
-        &response[pos + 4..]
-    } else if let Some(pos) = response.find("\n\n") {
-        &response[pos + 2..]
-    } else {
-        return Err("Invalid HTTP response format - no body separator found".to_string());
-    };
-    
-    // Parse JSON from body (skip empty bodies)
-    if body.is_empty() {
-        return Ok(serde_json::json!({}));
-    }
-    
-    serde_json::from_str(body.trim())
-        .map_err(|e| format!("Failed to parse response JSON: {}", e))
+/// Fails with `EngineError::Timeout` after the endpoint's configured timeout.
+/// Timed for the rolling latency metrics (see engine_metrics).
+async fn socket_http_post(socket_path: &str, endpoint: &str, body: &serde_json::Value) -> Result<serde_json::Value, EngineError> {
+    let timeout = transport::timeout_for(endpoint);
+    let request = transport::with_timeout(endpoint, timeout, socket_http_json(socket_path, "POST", endpoint, Some(body), &[]));
+    engine_metrics::measure(endpoint, request).await
 }
 
 // ==================== Tauri Command: start_python_script ====================
@@ -267,15 +559,33 @@ async fn socket_http_post(socket_path: &str, endpoint: &str, body: &serde_json::
 /// This command:
 ///   1. Checks if server is already running
 ///   2. Spawns the ai-engine binary (PyInstaller executable)
-///   3. Waits for Unix socket to become ready
+///   3. Waits for the socket to accept connections and GET /health to succeed
 ///   4. Starts the status polling loop that monitors health and idle timeout
 ///
 /// The binary path is selected based on the current platform/architecture.
-/// Returns Ok if startup succeeds, Err with details if it fails.
+/// Returns the startup duration if it succeeds, Err with details if it fails.
+/// Emits `command_slow` if startup exceeds the lifecycle budget.
 #[tauri::command]
-async fn start_python_script(app: AppHandle, state: State<'_, Mutex<PythonProcess>>) -> Result<(), String> {
+#[specta::specta]
+async fn start_python_script(app: AppHandle) -> Result<Timed<()>, EngineError> {
     println!("Starting AI Engine backend (Unix socket mode)...");
-    
+    let timer = CommandTimer::start(&app, "start_python_script", CommandClass::Lifecycle);
+    start_engine(&app, &timer).await?;
+    Ok(timer.finish(()))
+}
+
+/// Serializes engine starts, so concurrent callers share one engine
+static ENGINE_START: Mutex<()> = Mutex::const_new(());
+
+/// Spawn the engine, wait for its socket and start the status polling loop.
+///
+/// Shared by `start_python_script`, auto-start on input and the crash
+/// supervisor's restarts. Does nothing if the engine is already running;
+/// a caller arriving during a start waits for it to finish.
+pub(crate) async fn start_engine(app: &AppHandle, timer: &CommandTimer) -> Result<(), EngineError> {
+    let _starting = ENGINE_START.lock().await;
+    let state = app.state::<Mutex<PythonProcess>>();
+
     // Check if already running to prevent multiple instances
     let proc_state = state.lock().await;
     let is_running = *proc_state.is_running.lock().await;
@@ -283,45 +593,200 @@ async fn start_python_script(app: AppHandle, state: State<'_, Mutex<PythonProces
         println!("AI Engine is already running");
         return Ok(());
     }
+    let lifecycle = proc_state.lifecycle.clone();
     drop(proc_state);
+
+    // A failed start returns to the state it started from
+    let previous = lifecycle.current();
+    lifecycle.transition(EngineState::Starting, "start requested");
+    let result = launch_engine(app, timer).await;
+    if let Err(e) = &result {
+        lifecycle.transition(previous, &format!("start failed: {}", e));
+    }
+    result
+}
+
+/// Spawn (or, when replaying, connect to) the engine and attach to it once healthy.
+async fn launch_engine(app: &AppHandle, timer: &CommandTimer) -> Result<(), EngineError> {
+    let state = app.state::<Mutex<PythonProcess>>();
+
+    // Replaying a recording: use the mock engine instead of spawning one
+    if let Some(endpoint) = replay::mock_endpoint() {
+        println!("Replay mode: attaching to mock engine at {}", endpoint);
+        engine_auth::forget(&endpoint);
+        ipc::set_active_endpoint(&endpoint);
+        let generation = state.lock().await.engine_generation.fetch_add(1, Ordering::SeqCst) + 1;
+        *state.lock().await.last_activity.lock().await = Instant::now();
+        return attach_engine(app, timer, endpoint, generation).await;
+    }
+
+    // Development: use an engine that was started by hand
+    if let Some(socket_path) = dev_engine::attach_endpoint() {
+        return dev_engine::attach(app, timer, socket_path).await;
+    }
     
-    // Get the compiled binary path for this platform
-    let binary_path = get_ai_engine_binary();
-    let socket_path = get_socket_path();
-    
-    println!("Binary path: {}", binary_path);
+    let socket_path = ipc::activate(app).await?;
     println!("Socket path: {}", socket_path);
-    
+
+    // A crashed previous run may have left its socket and engine behind
+    stale_engine::cleanup(app, &socket_path).await;
+
     // Spawn the AI Engine binary
     // The binary is self-contained and will listen on the Unix socket
-    let (_rx, child) = app.shell()
-        .command(&binary_path)
-        .spawn()
-        .map_err(|e| {
-            println!("Error spawning AI Engine binary: {}", e);
-            format!("Failed to spawn binary at {}: {}", binary_path, e)
-        })?;
+    timer.phase("spawning").await;
+    // Slack for coarse file system timestamps on the extraction directory
+    let spawned_at = SystemTime::now() - Duration::from_secs(1);
+    let (events_rx, child) = spawn_engine(app, &socket_path).await?;
 
     println!("AI Engine process spawned successfully");
+    stale_engine::record_pid(app, child.pid());
+    network_activity::track_process(app, child.pid()).await;
+    resources::track_process(app, child.pid()).await;
 
     // Store the child process handle and initialize activity tracking
     let mut proc_state = state.lock().await;
     proc_state.child = Some(Box::new(child));
+    // A new generation: loops and watchers of a previous process retire
+    let generation = proc_state.engine_generation.fetch_add(1, Ordering::SeqCst) + 1;
+    crash_supervisor::watch(app.clone(), events_rx, generation);
     let mut last_activity = proc_state.last_activity.lock().await;
     *last_activity = Instant::now();
     drop(last_activity);
     drop(proc_state);
 
-    // Wait for Unix socket to be ready (server has started and created socket)
-    println!("Waiting for socket to be ready...");
-    wait_for_socket_ready().await?;
+    // Wait for the socket to accept connections and /health to answer
+    println!("Waiting for engine to become healthy...");
+    let extraction = ExtractionWatch::start(app, spawned_at);
+    wait_for_socket_ready(timer, &extraction, &socket_path).await.map_err(EngineError::SpawnFailed)?;
+    drop(extraction);
+    #[cfg(unix)]
+    restrict_socket_permissions(&socket_path);
+
+    attach_engine(app, timer, socket_path, generation).await
+}
+
+/// Spawn the engine binary listening on `socket_path`.
+///
+/// Picks the binary (a selected engine variant, else an unpacked onedir
+/// build, else the onefile sidecar) and a model tier that fits in memory.
+async fn spawn_engine(app: &AppHandle, socket_path: &str) -> Result<(Receiver<CommandEvent>, CommandChild), EngineError> {
+    let (command, description) = engine_command(app)?;
+    let mut command = command
+        .env(ipc::SOCKET_ENV_VAR, socket_path)
+        .env(runtime_identity::NAMESPACE_ENV_VAR, runtime_identity::namespace(app))
+        .env(engine_auth::TOKEN_ENV_VAR, engine_auth::issue(socket_path)?);
+    // Pick a model tier that fits in memory (fails early if none does)
+    if let Some(decision) = model_fallback::select_model(app).await.map_err(EngineError::SpawnFailed)? {
+        println!("Model: {}", decision.selected);
+        command = command.env(model_fallback::MODEL_ENV_VAR, decision.selected);
+    }
+
+    command.spawn().map_err(|e| {
+        println!("Error spawning AI Engine ({}): {}", description, e);
+        EngineError::SpawnFailed(format!("{}: {}", description, e))
+    })
+}
+
+/// Command starting the engine, and a description of it for logs and errors.
+///
+/// Prefers the selected variant, then the onedir build, then the sidecar;
+/// dev mode (or, in debug builds, a missing binary) runs the Python source.
+fn engine_command(app: &AppHandle) -> Result<(tauri_plugin_shell::process::Command, String), EngineError> {
+    if let Some(dev) = dev_python::enabled(app) {
+        println!("Dev mode: running AI Engine from source ({})", dev.describe());
+        return Ok((dev.command(app), dev.describe()));
+    }
+
+    let onedir = match engine_variants::selected_binary(app).map_err(EngineError::SpawnFailed)? {
+        Some(variant) => Some(variant),
+        None => get_onedir_engine(app),
+    };
+    let binary_path = match &onedir {
+        Some(path) => path.clone(),
+        None => get_ai_engine_binary().map_err(EngineError::SpawnFailed)?,
+    };
+    if !binary_path.exists() {
+        return match dev_python::fallback(app) {
+            Some(dev) => {
+                println!("AI Engine binary not found at {:?}, running from source ({})", binary_path, dev.describe());
+                Ok((dev.command(app), dev.describe()))
+            }
+            None => Err(EngineError::SpawnFailed(format!(
+                "AI Engine binary not found at {:?} (build it with python/build_binary.sh, or set {}=1 to run the engine from source)",
+                binary_path,
+                dev_python::DEV_MODE_ENV_VAR
+            ))),
+        };
+    }
+    println!("Binary path: {:?}", binary_path);
+
+    let command = match &onedir {
+        Some(path) => app.shell().command(path),
+        None => app.shell().sidecar(ENGINE_SIDECAR)
+            .map_err(|e| EngineError::SpawnFailed(format!("sidecar {}: {}", ENGINE_SIDECAR, e)))?,
+    };
+    Ok((command, format!("binary at {:?}", binary_path)))
+}
+
+/// Bring up the connection to a healthy engine at `socket_path` and start the status polling loop.
+///
+/// `generation` identifies the engine; the loop stops once it changes.
+async fn attach_engine(app: &AppHandle, timer: &CommandTimer, socket_path: String, generation: u64) -> Result<(), EngineError> {
+    let state = app.state::<Mutex<PythonProcess>>();
+
+    // A fresh engine starts with an empty task queue and no model loaded
+    engine_queue::clear(app).await;
+    warmup::clear();
+    models::set_loaded(app, None).await;
+
+    // Only health checks reach the fresh engine during its warm-up window
+    state.lock().await.startup_gate.begin();
+
+    // Negotiate a multiplexed connection (falls back to per-request connections)
+    timer.phase("negotiating_transport").await;
+    let mux_client = MuxClient::negotiate(&socket_path).await;
+    *state.lock().await.mux.lock().await = mux_client;
+    compression::negotiate(&socket_path).await;
+    network_activity::negotiate(app, &socket_path).await;
+
+    // An engine speaking an unsupported protocol is stopped before it is marked Ready
+    timer.phase("checking_capabilities").await;
+    match capabilities::fetch(&socket_path).await {
+        Ok(engine_capabilities) => capabilities::store(app, engine_capabilities).await,
+        Err(e) => {
+            println!("Refusing AI Engine: {}", e);
+            teardown_engine(&mut *state.lock().await, true).await;
+            return Err(e);
+        }
+    }
+    clock_sync::measure(&socket_path).await;
 
     // Update running state to mark server as operational
     {
         let proc_state = state.lock().await;
         let mut is_running = proc_state.is_running.lock().await;
         *is_running = true;
+        proc_state.lifecycle.transition(EngineState::Ready, "engine healthy");
+        // Requests that waited for the start are already in flight
+        if proc_state.in_flight.load(Ordering::SeqCst) > 0 {
+            proc_state.lifecycle.try_transition(EngineState::Busy, "request in flight");
+        }
     }
+    heartbeat::engine_ready(app);
+
+    spawn_status_loop(app, generation).await;
+    socket_watch::watch(app, &socket_path, generation);
+    engine_events::subscribe(app, socket_path, generation);
+    recycling::watch(app, generation);
+    Ok(())
+}
+
+/// Start the status polling loop for engine `generation`.
+///
+/// Also enforces the idle timeout; the loop ends once the engine is stopped
+/// or replaced.
+async fn spawn_status_loop(app: &AppHandle, generation: u64) {
+    let state = app.state::<Mutex<PythonProcess>>();
 
     // Clone app handle and state for the background polling task
     let app_clone = app.clone();
@@ -329,6 +794,11 @@ async fn start_python_script(app: AppHandle, state: State<'_, Mutex<PythonProces
     let state_clone = PythonProcessState {
         last_activity: proc_state.last_activity.clone(),
         is_running: proc_state.is_running.clone(),
+        supervisor_tick: proc_state.supervisor_tick.clone(),
+        turbo: proc_state.turbo.clone(),
+        in_flight: proc_state.in_flight.clone(),
+        idle_timeout_secs: proc_state.idle_timeout_secs.clone(),
+        engine_generation: proc_state.engine_generation.clone(),
     };
     drop(proc_state);
 
@@ -341,44 +811,64 @@ async fn start_python_script(app: AppHandle, state: State<'_, Mutex<PythonProces
     // Communication: Direct Unix Domain Socket (no TCP overhead)
     tauri::async_runtime::spawn(async move {
         println!("Starting status polling loop (via Unix socket)...");
-        let socket_path_clone = socket_path.clone();
+        let mut poll_failures: u32 = 0;
         
         loop {
-            // Check idle timeout
+            // Stop polling once this engine process is stopped or replaced
+            if state_clone.engine_generation.load(Ordering::SeqCst) != generation || !*state_clone.is_running.lock().await {
+                break;
+            }
+
+            // Record that the supervisor loop is alive (read by the heartbeat)
+            *state_clone.supervisor_tick.lock().await = Instant::now();
+            
+            // Outstanding requests count as activity, so a long request never has
+            // the engine stopped underneath it; the idle timer restarts once it finishes
+            if state_clone.in_flight.load(Ordering::SeqCst) > 0 {
+                update_activity_impl(&state_clone.last_activity).await;
+            }
+
+            // Check idle timeout (extended while turbo mode is active, skipped while disabled)
             let last_activity_lock = state_clone.last_activity.lock().await;
             let last_activity = *last_activity_lock;
             drop(last_activity_lock);
             
-            if last_activity.elapsed() > Duration::from_secs(IDLE_TIMEOUT_SECS) {
-                println!("Idle timeout reached ({} secs), stopping AI Engine...", IDLE_TIMEOUT_SECS);
-                
-                // Send graceful shutdown request via Unix socket
-                if let Ok(_response) = socket_http_post(&socket_path_clone, "/stop", &serde_json::json!({}))
-                    .await
-                {
-                    println!("Sent stop signal to AI Engine via Unix socket");
-                }
+            let idle_timeout_secs = state_clone.idle_timeout_secs.load(Ordering::SeqCst);
+            let idle_timeout = turbo::effective_idle_timeout(&state_clone.turbo, idle_timeout_secs).await;
+            if idle_timeout_secs > 0 && last_activity.elapsed() > idle_timeout {
+                println!("Idle timeout reached ({} secs), stopping AI Engine...", idle_timeout.as_secs());
                 
-                let mut is_running = state_clone.is_running.lock().await;
-                *is_running = false;
+                // Send graceful shutdown request via Unix socket, then make sure the process exits
+                let proc_state = app_clone.state::<Mutex<PythonProcess>>();
+                teardown_engine(&mut *proc_state.lock().await, true).await;
                 break;
             }
             
-            // Wait before next poll
-            tokio::time::sleep(Duration::from_secs(STATUS_POLL_INTERVAL_SECS)).await;
+            // Wait before next poll (backing off with jitter while the engine is unreachable)
+            tokio::time::sleep(Duration::from_secs(STATUS_POLL_INTERVAL_SECS) + startup_gate::reconnect_backoff(poll_failures)).await;
+            app_clone.state::<Mutex<MetricsHistory>>().lock().await.record_uptime(STATUS_POLL_INTERVAL_SECS);
             
             // Poll /status endpoint for updates via Unix socket
             // The response contains application state that we emit to the frontend
-            if let Ok(json_data) = socket_http_get(&socket_path_clone, "/status")
-                .await
-            {
-                println!("Status: {:?}", json_data);
-                let _ = app_clone.emit("python_status", json_data.to_string());
+            let polled = Instant::now();
+            match transport::engine_request(&app_clone, "GET", "/status", None, None).await {
+                Ok(mut json_data) => {
+                    poll_failures = 0;
+                    // Put engine timestamps on the host clock
+                    clock_sync::observe(&json_data, polled.elapsed());
+                    clock_sync::normalize(&mut json_data);
+                    println!("Status: {:?}", json_data);
+                    host_requests::ingest(&app_clone, &json_data).await;
+                    engine_queue::ingest(&app_clone, &json_data).await;
+                    status_summary::update(&app_clone, &json_data).await;
+                    status_delta::publish(&app_clone, json_data).await;
+                }
+                Err(_) => poll_failures = poll_failures.saturating_add(1),
             }
+            network_activity::sample(&app_clone).await;
+            resources::sample(&app_clone).await;
         }
     });
-
-    Ok(())
 }
 
 // ==================== Tauri Command: stop_python_script ====================
@@ -394,35 +884,61 @@ async fn start_python_script(app: AppHandle, state: State<'_, Mutex<PythonProces
 ///
 /// The Unix socket communication is direct kernel IPC with no TCP overhead.
 #[tauri::command]
-async fn stop_python_script(state: State<'_, Mutex<PythonProcess>>) -> Result<(), String> {
+#[specta::specta]
+async fn stop_python_script(app: AppHandle, state: State<'_, Mutex<PythonProcess>>) -> Result<Timed<()>, EngineError> {
     println!("Stopping AI Engine backend...");
+    let timer = CommandTimer::start(&app, "stop_python_script", CommandClass::Lifecycle);
     
     let mut proc_state = state.lock().await;
     let is_running = *proc_state.is_running.lock().await;
     
     if !is_running {
         println!("AI Engine is not running");
-        return Ok(());
+        return Ok(timer.finish(()));
     }
 
-    // Send graceful stop request via Unix socket
-    let socket_path = get_socket_path();
-    let _ = socket_http_post(&socket_path, "/stop", &serde_json::json!({}))
-        .await;
+    // Send /stop, wait for graceful shutdown, then terminate if still alive
+    timer.phase("stopping").await;
+    teardown_engine(&mut proc_state, true).await;
+
+    Ok(timer.finish(()))
+}
+
+/// Shut the engine down and mark it as stopped.
+///
+/// When `graceful`, sends /stop via Unix socket and waits SHUTDOWN_GRACE_MS
+/// before killing the process; otherwise the process is killed immediately.
+/// An attached external engine is only detached from (see dev_engine).
+/// The exit is expected, so the crash supervisor won't restart the engine.
+/// The engine's keys are dropped (see engine_auth).
+async fn teardown_engine(proc_state: &mut PythonProcess, graceful: bool) {
+    proc_state.lifecycle.try_transition(EngineState::Stopping, if graceful { "graceful shutdown" } else { "forced shutdown" });
+    // Retire the status loop and crash watcher of the current process
+    proc_state.engine_generation.fetch_add(1, Ordering::SeqCst);
 
-    // Wait for graceful shutdown
-    tokio::time::sleep(Duration::from_millis(500)).await;
+    // An engine we attached to keeps running
+    let attached = dev_engine::detach();
+    if graceful && !attached {
+        let socket_path = get_socket_path();
+        let _ = socket_http_post(&socket_path, "/stop", &serde_json::json!({}))
+            .await;
+        tokio::time::sleep(Duration::from_millis(SHUTDOWN_GRACE_MS)).await;
+    }
 
     // Terminate process if still alive
-    if let Some(_child) = proc_state.child.take() {
+    if let Some(child) = proc_state.child.take() {
+        if let Ok(child) = child.downcast::<CommandChild>() {
+            let _ = child.kill();
+        }
         println!("AI Engine process terminated");
     }
+    engine_auth::forget(&get_socket_path());
+    *proc_state.mux.lock().await = None;
     
     // Mark as stopped
     let mut is_running_flag = proc_state.is_running.lock().await;
     *is_running_flag = false;
-
-    Ok(())
+    proc_state.lifecycle.transition(EngineState::Stopped, "engine stopped");
 }
 
 // ==================== Tauri Command: send_input_to_python ====================
@@ -430,39 +946,183 @@ async fn stop_python_script(state: State<'_, Mutex<PythonProcess>>) -> Result<()
 /// Send user input to the AI Engine backend via Unix socket.
 ///
 /// This command:
-///   1. Updates the idle activity timestamp (resets idle counter)
-///   2. Sends user input as JSON POST to /input endpoint
-///   3. Emits the response back to the frontend
+///   1. Updates the idle activity timestamp (resets idle counter), starting
+///      the engine first if it is stopped and `settings.engine.auto_start` is on
+///   2. Sends user input as JSON POST to /input endpoint, tagged with the
+///      `request_id` correlation id (generated if not given)
+///   3. Returns the parsed response (also emitted as `python_input`)
+///
+/// `cancel_request(request_id)` aborts the call (see requests). While it
+/// waits behind other inputs, `input_queued` reports its position and
+/// estimated start (see input_limiter).
+///
+/// `route` optionally assigns the input to a session and/or picks the model
+/// that answers it (see session_models), and sets its temperature, top_p,
+/// max_tokens, stop sequences and seed (see generation) and the prompt
+/// template wrapped around the input (see templates).
+///
+/// With an `on_token` channel the response is streamed: tokens are forwarded
+/// as ordered frames (see streaming) and the command returns
+/// `{ "output": <full text>, "stream_id": <id> }` once generation finishes.
 ///
 /// Used when user interacts with the application.
 /// Communication: Direct Unix Domain Socket with HTTP request format.
+/// Returns the response and request duration; emits `command_slow` past the interactive budget.
 #[tauri::command]
-async fn send_input_to_python(app: AppHandle, input: String, state: State<'_, Mutex<PythonProcess>>) -> Result<(), String> {
+#[specta::specta]
+async fn send_input_to_python(
+    app: AppHandle,
+    webview: Webview,
+    input: String,
+    timeout_ms: Option<u64>,
+    on_token: Option<StreamChannel>,
+    request_id: Option<String>,
+    route: Option<InputRoute>,
+) -> Result<Timed<serde_json::Value>, EngineError> {
+    let timer = CommandTimer::start(&app, "send_input_to_python", CommandClass::Interactive);
+    timer.phase("awaiting_response").await;
+    let writer = on_token.map(|channel| channel.writer(webview));
+    let input = process_input(&app, "send_input_to_python", input, timeout_ms, writer, request_id, route);
+    let response = jobs::interactive(&app, input).await?;
+    Ok(timer.finish(response))
+}
+
+/// Run one input against the engine: the work behind `send_input_to_python` and queued jobs.
+///
+/// Handles activity tracking, draining, cancellation (`request_id`),
+/// session routing, streaming to `writer`, moderation, metrics and tracing;
+/// the response is also emitted as `python_input`.
+pub(crate) async fn process_input(
+    app: &AppHandle,
+    operation: &'static str,
+    input: String,
+    timeout_ms: Option<u64>,
+    writer: Option<StreamWriter>,
+    request_id: Option<String>,
+    route: Option<InputRoute>,
+) -> Result<serde_json::Value, EngineError> {
+    // Jobs and the broker reach here without a command, so check the features the input uses
+    if writer.is_some() {
+        feature_flags::check("streaming")?;
+    }
+    if route.as_ref().is_some_and(|r| r.template.is_some()) {
+        feature_flags::check("templates")?;
+    }
     println!("Sending input to AI Engine: {}", input);
+    analytics::count(operation);
+    let mut trace = RequestTrace::start(app, operation).await;
+    let queued = Instant::now();
     
     // Update activity timestamp (prevent idle timeout)
+    let state = app.state::<Mutex<PythonProcess>>();
     let proc_state = state.lock().await;
     update_activity_impl(&proc_state.last_activity).await;
+    // Count this request as in flight (refused while the engine drains)
+    let _request = drain::begin_request(&proc_state)?;
     drop(proc_state);
+    // Register the correlation id so cancel_request can abort this call
+    let mut handle = app.state::<ActiveRequests>().register(request_id)?;
+
+    // Unsampled inputs still get a trace id for the engine's logs (see trace_context)
+    let trace_id = trace.trace_id().map(str::to_string).unwrap_or_else(trace_context::new_trace_id);
+    let mut body = serde_json::json!({ "input": input, "request_id": handle.id(), "trace_id": trace_id });
+    // Invalid parameters or templates fail before starting the engine or waiting for a slot
+    generation::apply(app, route.as_ref().and_then(|r| r.generation.clone()), &mut body).await?;
+    templates::apply(app, route.as_ref().and_then(|r| r.template.clone()), &mut body).await?;
+
+    auto_start_engine(app).await?;
+    // Wait for one of the limited /input slots (or one reserved for the input)
+    let reservation = route.as_ref().and_then(|r| r.reservation_id.clone());
+    let request_id = handle.id().to_string();
+    let _slot = handle.run(input_limiter::acquire(app, &request_id, reservation.as_deref())).await?;
     
-    // Send request via Unix socket
-    let socket_path = get_socket_path();
-    
-    match socket_http_post(&socket_path, "/input", &serde_json::json!({ "input": input }))
-        .await
-    {
+    // Send request via Unix socket (timeout_ms overrides the configured chat timeout)
+    let request_started = Instant::now();
+    trace.span("queue", queued, request_started);
+
+    let turn = session_models::route_input(app, route, &mut body).await;
+    let result = match writer {
+        Some(mut writer) => {
+            body["stream"] = true.into();
+            writer.bind_request(handle.id());
+            let socket_path = get_socket_path();
+            let stream = streaming::read_token_stream(app, &socket_path, "/input", &body, &mut writer);
+            let result = handle.run(engine_metrics::measure("/input", stream)).await;
+            // A blocked stream ends with the policy message as its error frame,
+            // a truncated one with the truncation reason
+            let (result, frame_error) = match result {
+                Ok(streamed) => {
+                    let (response, frame_error) = streaming::finish_moderation(app, handle.id(), &streamed, &mut writer).await?;
+                    (Ok(response), frame_error)
+                }
+                Err(e) => {
+                    let message = e.to_string();
+                    (Err(e), Some(message))
+                }
+            };
+            writer.finish(frame_error)?;
+            result
+        }
+        None => match handle.run(transport::engine_request(app, "POST", "/input", Some(&body), timeout_ms.map(Duration::from_millis))).await {
+            Ok(mut response) => {
+                moderation::moderate_response(app, handle.id(), &mut response).await;
+                Ok(response)
+            }
+            Err(e) => Err(e),
+        },
+    };
+    let response_received = Instant::now();
+    trace.span("transport", request_started, response_received);
+    if let Some(engine_ms) = result.as_ref().ok().and_then(|r| r.get("engine_ms")).and_then(|v| v.as_u64()) {
+        let engine_started = response_received.checked_sub(Duration::from_millis(engine_ms)).unwrap_or(request_started);
+        trace.span("engine", engine_started.max(request_started), response_received);
+    }
+
+    // Record the request in the persistent metrics history
+    let tokens = result.as_ref().map(metrics_history::response_token_count).unwrap_or(0);
+    app.state::<Mutex<MetricsHistory>>().lock().await
+        .record_request(request_started.elapsed(), result.is_ok(), tokens);
+    history::record(app, handle.id(), &body, &result, request_started.elapsed()).await;
+    if let (Some(turn), Ok(response)) = (&turn, &result) {
+        let complete = response.get("complete").and_then(|v| v.as_bool()).unwrap_or(true);
+        session_models::record_answer(app, turn, handle.id(), Some(response), complete).await;
+    }
+
+    match result {
         Ok(json_data) => {
             println!("Received response: {:?}", json_data);
-            // Emit response to frontend
-            let _ = app.emit("python_input", json_data.to_string());
-            Ok(())
+            // Emit response for other listeners, and return it to the caller
+            events::emit(app, PythonInput(json_data.clone()));
+            trace.span("parse", response_received, Instant::now());
+            trace.finish(None);
+            Ok(json_data)
         }
         Err(e) => {
-            Err(format!("Error sending input via Unix socket: {}", e))
+            println!("Error sending input via Unix socket: {}", e);
+            trace.finish(Some(&e.to_string()));
+            Err(e)
         }
     }
 }
 
+/// Start a stopped engine for an input, unless `settings.engine.auto_start` is off.
+///
+/// Fails with `NotRunning` when the engine is stopped and auto-start is off.
+async fn auto_start_engine(app: &AppHandle) -> Result<(), EngineError> {
+    let is_running = *app.state::<Mutex<PythonProcess>>().lock().await.is_running.lock().await;
+    if is_running {
+        return Ok(());
+    }
+    if !app.state::<Mutex<SettingsStore>>().lock().await.settings.engine.auto_start {
+        return Err(EngineError::NotRunning);
+    }
+    println!("AI Engine not running, starting it for incoming input...");
+    let timer = CommandTimer::start(app, "auto_start", CommandClass::Lifecycle);
+    start_engine(app, &timer).await?;
+    timer.finish(());
+    Ok(())
+}
+
 // ==================== Tauri Command: on_app_interaction ====================
 
 /// Called when user interacts with the frontend to reset idle timer.
@@ -471,7 +1131,8 @@ async fn send_input_to_python(app: AppHandle, input: String, state: State<'_, Mu
 /// the server from being stopped due to inactivity.
 /// Call this on any user action (clicks, input, etc).
 #[tauri::command]
-async fn on_app_interaction(state: State<'_, Mutex<PythonProcess>>) -> Result<(), String> {
+#[specta::specta]
+async fn on_app_interaction(state: State<'_, Mutex<PythonProcess>>) -> Result<(), EngineError> {
     // Update activity timestamp to prevent idle timeout
     let proc_state = state.lock().await;
     let mut last_activity = proc_state.last_activity.lock().await;
@@ -482,27 +1143,314 @@ async fn on_app_interaction(state: State<'_, Mutex<PythonProcess>>) -> Result<()
     Ok(())
 }
 
+// ==================== Tauri Commands: Idle Timeout ====================
+
+/// Change the idle timeout of the engine (takes effect on the next poll).
+///
+/// Shorter timeouts free memory sooner on laptops; turbo mode still extends
+/// the timeout while active.
+#[tauri::command]
+#[specta::specta]
+async fn set_idle_timeout(secs: u64, state: State<'_, Mutex<PythonProcess>>) -> Result<(), EngineError> {
+    if secs == 0 {
+        return Err(EngineError::InvalidRequest(
+            "Idle timeout must be at least 1 second; use disable_idle_timeout to keep the engine resident".to_string(),
+        ));
+    }
+    state.lock().await.idle_timeout_secs.store(secs, Ordering::SeqCst);
+    println!("Idle timeout set to {} secs", secs);
+    Ok(())
+}
+
+/// Keep the engine resident until it is stopped explicitly.
+///
+/// `set_idle_timeout` re-enables the timeout.
+#[tauri::command]
+#[specta::specta]
+async fn disable_idle_timeout(state: State<'_, Mutex<PythonProcess>>) -> Result<(), EngineError> {
+    state.lock().await.idle_timeout_secs.store(0, Ordering::SeqCst);
+    println!("Idle timeout disabled");
+    Ok(())
+}
+
 // ==================== Tauri App Entry Point ====================
 
-/// Initialize and run the Tauri application.
-/// Sets up the AI Engine process manager and exposes IPC commands to frontend.
+/// Builds the app for embedding apps that hook into engine requests.
+///
+/// Hooks run in registration order around every request made through the
+/// transport layer; see hooks for the exact ordering guarantees.
+#[derive(Default)]
+pub struct Builder {
+    hooks: RequestHooks,
+}
+
+impl Builder {
+    /// Run `hook` before each request is sent; it may change the request
+    /// (e.g. add headers) or reject it with `Err(reason)`.
+    pub fn pre_dispatch<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn(EngineCall) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<EngineCall, String>> + Send + 'static,
+    {
+        self.hooks.add_pre_dispatch(hook);
+        self
+    }
+
+    /// Run `hook` on each successful response; it returns the response the caller gets.
+    pub fn post_response<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn(EngineCall, serde_json::Value) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = serde_json::Value> + Send + 'static,
+    {
+        self.hooks.add_post_response(hook);
+        self
+    }
+
+    /// Run `hook` on each failed request, before the error is returned.
+    pub fn on_error<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn(EngineCall, EngineError) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        self.hooks.add_on_error(hook);
+        self
+    }
+
+    /// Initialize and run the Tauri application.
+    pub fn run(self) {
+        launch(self.hooks)
+    }
+}
+
+/// Initialize and run the Tauri application without request hooks.
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
-        .plugin(tauri_plugin_shell::init())
-        // Initialize the Python process state (not started yet)
-        .manage(Mutex::new(PythonProcess {
-            child: None,
-            last_activity: Arc::new(Mutex::new(Instant::now())),
-            is_running: Arc::new(Mutex::new(false)),
-        }))
-        // Expose these commands to the frontend via Tauri IPC
-        .invoke_handler(tauri::generate_handler![
+    Builder::default().run()
+}
+
+/// Write the frontend's TypeScript bindings to BINDINGS_PATH.
+#[cfg(debug_assertions)]
+fn export_bindings(bindings: &tauri_specta::Builder<tauri::Wry>) -> Result<(), String> {
+    bindings
+        .export(
+            specta_typescript::Typescript::default().bigint(specta_typescript::BigIntExportBehavior::Number),
+            BINDINGS_PATH,
+        )
+        .map_err(|e| format!("Failed to export TypeScript bindings to {}: {}", BINDINGS_PATH, e))
+}
+
+/// Initialize and run the Tauri application.
+/// Sets up the AI Engine process manager and exposes IPC commands to frontend.
+fn launch(hooks: RequestHooks) {
+    let context = tauri::generate_context!();
+    // Text from the OS context menu goes to the running instance if there is one
+    let selection = context_menu::selection_from_args();
+    if selection.as_deref().is_some_and(|text| context_menu::forward(&context.config().identifier, text)) {
+        return;
+    }
+
+    let process = PythonProcess {
+        child: None,
+        last_activity: Arc::new(Mutex::new(Instant::now())),
+        is_running: Arc::new(Mutex::new(false)),
+        supervisor_tick: Arc::new(Mutex::new(Instant::now())),
+        turbo: Arc::new(Mutex::new(TurboState::default())),
+        mux: Arc::new(Mutex::new(None)),
+        in_flight: Arc::new(AtomicUsize::new(0)),
+        idle_timeout_secs: Arc::new(AtomicU64::new(IDLE_TIMEOUT_SECS)),
+        draining: Arc::new(AtomicBool::new(false)),
+        startup_gate: Arc::new(StartupGate::default()),
+        engine_generation: Arc::new(AtomicU64::new(0)),
+        lifecycle: EngineLifecycle::default(),
+        loaded_model: None,
+    };
+    let supervisor_tick = process.supervisor_tick.clone();
+    let is_running = process.is_running.clone();
+    let lifecycle = process.lifecycle.clone();
+
+    // Commands and events exposed to the frontend via Tauri IPC, typed for TypeScript
+    let bindings = tauri_specta::Builder::<tauri::Wry>::new()
+        .commands(tauri_specta::collect_commands![
             start_python_script,    // Start AI Engine backend
             stop_python_script,     // Stop AI Engine backend
+            handoff::restart_python_script,  // Restart with zero-downtime handoff
+            warmup::preload_model,           // Load a model before the first input
+            warmup::get_model_readiness,     // Loading/ready state of preloaded models
+            models::list_models,             // Models the engine can serve
+            models::load_model,              // Switch to another model
+            models::unload_model,            // Free a model's memory
+            model_downloads::download_model, // Fetch model weights and register them
+            dev_engine::attach_to_engine,    // Use an engine started by hand (development)
+            engine_state::get_engine_state,  // Current lifecycle state
+            engine_state::get_engine_lifecycle_history,  // Recent transitions and recycles
             send_input_to_python,   // Send user request
-            on_app_interaction      // Reset idle timer
+            streaming::stream_input_to_python,  // Send user request, stream tokens
+            generation::set_default_generation_params,  // Sampling settings of inputs without their own
+            input_limiter::get_queue_position,  // Position and ETA of a waiting input
+            analytics::preview_analytics_payload,  // Exactly what the next usage upload sends
+            deprecations::get_deprecation_notices,  // Deprecations announced by the engine
+            uploads::send_file_to_python,      // Upload an image/PDF/... as multipart
+            downloads::download_from_python,   // Stream a large response to disk
+            downloads::fetch_from_python,      // Binary response body, unparsed
+            proxy::call_engine,                // JSON request to an allowlisted endpoint
+            broker::get_broker_endpoint,       // Socket automation clients connect to
+            pipelines::save_pipeline,          // Create or replace a named pipeline
+            pipelines::delete_pipeline,        // Remove a pipeline
+            pipelines::list_pipelines,         // All pipeline definitions
+            pipelines::run_pipeline,           // Run a pipeline's steps against the engine
+            pipelines::resume_pipeline_run,    // Continue a failed run from its failed step
+            pipelines::list_pipeline_runs,     // Recent runs with step outputs
+            on_app_interaction,     // Reset idle timer
+            drain::stop_engine,     // Stop with drain/force semantics
+            turbo::enable_turbo,    // Temporarily raise limits
+            reservations::reserve_capacity,  // Prepare for a burst of requests
+            reservations::release_capacity,  // End a reservation early
+            updates::set_update_channel,   // Select stable/beta/nightly
+            updates::get_engine_version,   // Engine version + channel
+            updates::check_engine_update,  // Query channel manifest
+            metrics_history::get_metrics_history,  // Hourly/daily usage rollups
+            engine_metrics::get_engine_metrics,    // Rolling request latency and error rate
+            licenses::accept_model_license,  // Record EULA acceptance
+            licenses::get_pending_licenses,  // Licenses awaiting acceptance
+            settings::get_settings,     // Read persisted settings
+            settings::update_settings,  // Replace persisted settings
+            settings_schema::get_settings_schema,   // Settings JSON Schema for rendering a form
+            host_requests::respond_to_host_request,  // Answer engine → host request
+            model_fallback::get_model_selection,     // Model chosen at last start
+            artifacts::list_engine_artifacts,  // Files the engine has ready
+            artifacts::save_artifact,          // Download + verify + clean up
+            templates::get_templates,          // Live-reloaded templates and rules
+            templates::set_prompt_template,    // Save a prompt template as JSON
+            templates::list_prompt_templates,  // Prompt templates with their system prompts
+            templates::apply_prompt_template,  // Render a template as /input would get it
+            status_summary::get_status_summary,  // One-sentence status for screen readers
+            ipc::set_socket_path,              // Override the engine socket path
+            engine_queue::get_engine_queue,     // Engine-side task queue
+            engine_queue::reorder_engine_task,  // Move an engine task
+            engine_queue::cancel_engine_task,   // Cancel an engine task
+            requests::cancel_request,           // Abort an in-flight send_input_to_python
+            runtime_identity::get_runtime_identity,  // Namespace of socket/PID/lock files
+            engine_logs::get_engine_logs,       // Recent engine stdout/stderr
+            status_delta::get_full_status,      // Resync the delta-encoded status
+            status_delta::ack_status,           // Acknowledge an applied status version
+            resync::resync,                     // Rebuild the frontend's view after a reload
+            streaming::resume_stream,           // Reattach an open stream to a new channel
+            session_models::set_session_model,  // Pin a model for a session
+            session_models::get_session_models, // Which model answered each message
+            sessions::create_session,           // Start a persistent chat session
+            sessions::list_sessions,            // Chat sessions, most recently used first
+            sessions::send_input_to_session,    // Send user request within a chat session
+            sessions::delete_session,           // Remove a chat session and its history
+            history::get_history,               // Stored inputs and responses, newest first
+            history::clear_history,             // Delete the stored inputs and responses
+            history_store::migrate_storage,     // Move history between SQLite and JSONL
+            set_idle_timeout,                   // Change the idle timeout
+            disable_idle_timeout,               // Keep the engine resident
+            auth::set_provider_credentials,     // Store a remote provider key in the keychain
+            auth::clear_provider_credentials,   // Forget a remote provider's credentials
+            auth::start_provider_login,         // OAuth device login for a remote provider
+            auth::test_provider_credentials,    // Check remote provider credentials
+            network_activity::get_engine_network_activity,  // Declared and observed engine network access
+            jobs::submit_input,                 // Queue an input, returns a job id
+            jobs::get_job_status,               // Status of one job
+            jobs::list_jobs,                    // The work queue
+            jobs::cancel_job,                   // Drop a queued job or abort a running one
+            jobs::get_worker_utilization,       // Busy share of the interactive and batch workers
+            retention::run_retention_now,       // Apply retention limits (or dry-run them)
+            repair::check_installation,         // Preflight checks of the installation
+            repair::repair_installation,        // Fix what the preflight finds broken
+            engine_variants::list_engine_variants,   // Bundled engine builds and their requirements
+            engine_variants::select_engine_variant,  // Switch engine build (restarts the engine)
+            performance::explain_performance,   // Ranked reasons responses may be slow
+            recorder::start_recording,          // Record commands, engine traffic and events
+            recorder::stop_recording,           // Finish the session recording
+            replay::replay_recording,           // Re-drive the backend from a recording
+            backup::create_backup_now,          // Encrypted backup of settings, history and templates
+            backup::restore_backup,             // Restore from an encrypted backup
+            resources::get_engine_resources,    // Engine CPU, memory and uptime
+            capabilities::get_engine_capabilities,  // Models, context size, streaming, protocol
+            feature_flags::get_feature_flags,   // Kill switch state of each feature
+            clock_sync::get_clock_sync,         // Offset between the engine and host clocks
+            context_menu::install_context_menu,     // Add "Ask AI about selection" to the OS context menu
+            context_menu::uninstall_context_menu,   // Remove the context-menu entry
+            context_menu::get_context_menu_status,  // Whether the context-menu entry is registered
+            temp_files::get_temp_usage,         // Live temporary files and their disk usage
+            trace_context::get_protocol_warnings,   // Missing or mismatched trace id echoes
+            prefetch::start_prefetch,           // Fetch the default model in the background
+            prefetch::pause_prefetch,           // Pause it, keeping the partial file
+            prefetch::resume_prefetch,          // Continue where it stopped
+            prefetch::get_prefetch_status,      // Progress of the prefetch
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri app");
-}
\ No newline at end of file
+        .events(events::collect());
+
+    // Debug runs regenerate the frontend's bindings, keeping them in sync with the backend;
+    // if that fails the app still starts with the committed bindings
+    #[cfg(debug_assertions)]
+    if let Err(e) = export_bindings(&bindings) {
+        println!("{}", e);
+    }
+    let handler = bindings.invoke_handler();
+
+    tauri::Builder::default()
+        .plugin(tauri_plugin_shell::init())
+        // Initialize the Python process state (not started yet)
+        .manage(Mutex::new(process))
+        .manage(Mutex::new(UpdateState::default()))
+        .manage(Mutex::new(HostRequestState::default()))
+        .manage(Mutex::new(ModelSelectionState::default()))
+        .manage(Mutex::new(StatusSummaryState::default()))
+        .manage(Mutex::new(SupervisorState::default()))
+        .manage(Mutex::new(EngineQueueState::default()))
+        .manage(ActiveRequests::default())
+        .manage(TempFiles::default())
+        .manage(Mutex::new(EngineLogBuffer::default()))
+        .manage(Mutex::new(StatusDeltaState::default()))
+        .manage(Mutex::new(NetworkActivityState::default()))
+        .manage(Mutex::new(EngineResourcesState::default()))
+        .manage(Mutex::new(EngineCapabilitiesState::default()))
+        .manage(InputLimiter::default())
+        .manage(Mutex::new(GenerationDefaults::default()))
+        .manage(hooks)
+        .manage(Mutex::new(JobQueueState::default()))
+        // Start the optional watchdog heartbeat and load persisted stores once the runtime is up
+        .setup(move |app| {
+            // std Mutex: the exit handler below runs outside the async runtime
+            app.manage(std::sync::Mutex::new(Heartbeat::start_from_env(supervisor_tick, is_running)));
+            lifecycle.attach(app.handle());
+            bindings.mount_events(app);
+            settings::init(app.handle());
+            analytics::init(app.handle());
+            deprecations::init(app.handle());
+            broker::init(app.handle());
+            metrics_history::init(app.handle());
+            engine_metrics::init(app.handle());
+            licenses::init(app.handle());
+            templates::init(app.handle());
+            history_store::init(app.handle());
+            retention::init(app.handle());
+            backup::init(app.handle());
+            temp_files::init(app.handle());
+            prefetch::init(app.handle());
+            pipelines::init(app.handle());
+            context_menu::init(app.handle(), selection);
+            repair::preflight(app.handle());
+            shutdown::watch_signals(app.handle());
+            Ok(())
+        })
+        // Expose the commands (recorded while a session recording runs; refused if their feature is switched off)
+        .invoke_handler(move |invoke| {
+            recorder::record_command(&invoke.message);
+            if let Err(e) = feature_flags::check_command(invoke.message.command()) {
+                println!("Refusing {}: {}", invoke.message.command(), e);
+                invoke.resolver.reject(e);
+                return true;
+            }
+            handler(invoke)
+        })
+        .build(context)
+        .expect("error while running tauri app")
+        .run(|app, event| {
+            // Ordered shutdown (streams, requests, engine, metrics, files) before exit
+            shutdown::handle_run_event(app, &event);
+        });
+}
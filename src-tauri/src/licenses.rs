@@ -0,0 +1,151 @@
+//! =============================================================================
+//! Model License Registry
+//! =============================================================================
+//!
+//! Some models require accepting a license (EULA) before they may be
+//! downloaded or used. The engine declares gated models via GET /licenses:
+//!
+//!   { "licenses": [ { "model": "llama-3-8b", "version_hash": "ab12…",
+//!                     "name": "Llama 3 Community License", "url": "https://…" } ] }
+//!
+//! Acceptances are persisted with a timestamp in licenses.json in the app data
+//! dir. An acceptance only covers the exact `version_hash` it was given for, so
+//! a changed license text has to be accepted again.
+//!
+//! `download_model`, the default model prefetch, `load_model` and
+//! `preload_model` refuse a gated model without a current acceptance with
+//! `license_required`. A model is matched by its engine id, and a download
+//! by URL also by the file name. An engine without /licenses gates nothing.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager, State};
+use tauri::async_runtime::Mutex;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::atomic_file;
+use crate::error::EngineError;
+use crate::{auto_start_engine, transport};
+
+/// File holding accepted licenses inside the app data directory
+const LICENSES_FILE: &str = "licenses.json";
+
+// ==================== Types ====================
+
+/// A license the engine requires for a model.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ModelLicense {
+    pub model: String,
+    pub version_hash: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+/// A recorded acceptance of a license version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LicenseAcceptance {
+    pub version_hash: String,
+    /// Unix seconds when the user accepted
+    pub accepted_at: u64,
+}
+
+// ==================== License Registry ====================
+
+/// Accepted licenses keyed by model name, persisted to disk.
+pub struct LicenseRegistry {
+    path: PathBuf,
+    accepted: HashMap<String, LicenseAcceptance>,
+}
+
+impl LicenseRegistry {
+    /// Load accepted licenses from `path` (missing or unreadable file = none).
+    pub fn load(app: &AppHandle, path: PathBuf) -> LicenseRegistry {
+        let accepted = atomic_file::load_json(&path).report(app).unwrap_or_default();
+        LicenseRegistry { path, accepted }
+    }
+
+    /// True if `model`'s license has been accepted for exactly this version.
+    pub fn is_accepted(&self, model: &str, version_hash: &str) -> bool {
+        self.accepted
+            .get(model)
+            .is_some_and(|a| a.version_hash == version_hash)
+    }
+
+    /// Record an acceptance and persist the registry.
+    fn accept(&mut self, model: String, version_hash: String) -> Result<(), String> {
+        let accepted_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.accepted.insert(model, LicenseAcceptance { version_hash, accepted_at });
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        atomic_file::write_json(&self.path, &self.accepted)
+    }
+}
+
+/// Load the registry from the app data dir and register it as managed state.
+pub fn init(app: &AppHandle) {
+    let data_dir = app.path().app_data_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join("ai-engine"));
+    app.manage(Mutex::new(LicenseRegistry::load(app, data_dir.join(LICENSES_FILE))));
+}
+
+/// Fetch the licenses the engine declares for its gated models.
+///
+/// An engine without /licenses (404) has none.
+async fn fetch_engine_licenses(app: &AppHandle) -> Result<Vec<ModelLicense>, EngineError> {
+    let response = match transport::engine_request(app, "GET", "/licenses", None, None).await {
+        Ok(response) => response,
+        Err(EngineError::Http { status: 404, .. }) => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    serde_json::from_value(response.get("licenses").cloned().unwrap_or_default())
+        .map_err(|e| EngineError::BadResponse(format!("Invalid /licenses response: {}", e)))
+}
+
+/// Fail with `LicenseRequired` if one of `models` is gated and its current
+/// license version hasn't been accepted.
+///
+/// Starts the engine if needed to ask which models are gated.
+pub(crate) async fn require_accepted(app: &AppHandle, models: &[&str]) -> Result<(), EngineError> {
+    auto_start_engine(app).await?;
+    let licenses = fetch_engine_licenses(app).await?;
+    let registry = app.state::<Mutex<LicenseRegistry>>();
+    let registry = registry.lock().await;
+    match licenses.into_iter().find(|l| models.contains(&l.model.as_str()) && !registry.is_accepted(&l.model, &l.version_hash)) {
+        Some(license) => {
+            println!("Refusing model {}: license {} not accepted", license.model, license.version_hash);
+            Err(EngineError::LicenseRequired { model: license.model, version_hash: license.version_hash })
+        }
+        None => Ok(()),
+    }
+}
+
+// ==================== Tauri Commands ====================
+
+/// Record that the user accepted `model`'s license at `version_hash`.
+#[tauri::command]
+#[specta::specta]
+pub async fn accept_model_license(model: String, version_hash: String, registry: State<'_, Mutex<LicenseRegistry>>) -> Result<(), EngineError> {
+    println!("License accepted for {} ({})", model, version_hash);
+    Ok(registry.lock().await.accept(model, version_hash)?)
+}
+
+/// List gated models whose current license version hasn't been accepted.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_pending_licenses(app: AppHandle, registry: State<'_, Mutex<LicenseRegistry>>) -> Result<Vec<ModelLicense>, EngineError> {
+    let licenses = fetch_engine_licenses(&app).await?;
+    let registry = registry.lock().await;
+    Ok(licenses
+        .into_iter()
+        .filter(|l| !registry.is_accepted(&l.model, &l.version_hash))
+        .collect())
+}
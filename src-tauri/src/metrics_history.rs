@@ -0,0 +1,449 @@
+//! =============================================================================
+//! Persistent Metrics History
+//! =============================================================================
+//!
+//! Keeps long-term usage metrics in SQLite so a dashboard can show weeks of
+//! history, not just what happened since the app started.
+//!
+//! Data flow:
+//!   send_input_to_python / status loop
+//!       ↓ record_request() / record_uptime()
+//!   In-memory accumulator (current flush window)
+//!       ↓ flushed every METRICS_FLUSH_INTERVAL_SECS and on exit
+//!   metrics_hourly   (one row per UTC hour)
+//!       ↓ re-aggregated on every flush
+//!   metrics_daily    (one row per UTC day)
+//!
+//! Latency percentiles of a bucket are merged across flushes as a
+//! request-weighted average (p99 keeps the maximum), so they are approximate
+//! once a bucket spans several flush windows.
+//!
+//! Retention: hourly rows are kept HOURLY_RETENTION_DAYS, daily rows
+//! DAILY_RETENTION_DAYS; older rows are pruned on every flush. The retention
+//! janitor can prune further (settings.retention.metrics_days, see retention).
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager, State};
+use tauri::async_runtime::Mutex;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::compression;
+use crate::error::EngineError;
+
+// ==================== Configuration Constants ====================
+
+/// How often the in-memory accumulator is written to SQLite
+const METRICS_FLUSH_INTERVAL_SECS: u64 = 60;
+
+/// How long hourly rollups are kept
+const HOURLY_RETENTION_DAYS: u64 = 30;
+
+/// How long daily rollups are kept
+const DAILY_RETENTION_DAYS: u64 = 365;
+
+/// Database file name inside the app data directory
+pub(crate) const METRICS_DB_FILE: &str = "metrics.sqlite";
+
+const SECS_PER_HOUR: u64 = 3600;
+const SECS_PER_DAY: u64 = 86_400;
+
+// ==================== Types ====================
+
+/// Bucket size for history queries.
+#[derive(Debug, Clone, Copy, Deserialize, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    Hour,
+    Day,
+}
+
+/// Time range (Unix seconds, inclusive) for history queries.
+#[derive(Debug, Deserialize, Type)]
+pub struct MetricsRange {
+    pub from: u64,
+    pub to: u64,
+}
+
+/// One hourly or daily rollup row.
+#[derive(Debug, Serialize, Type)]
+pub struct MetricsRollup {
+    /// Start of the bucket (Unix seconds, UTC)
+    pub bucket: u64,
+    pub requests: u64,
+    pub errors: u64,
+    pub tokens: u64,
+    pub latency_p50_ms: u64,
+    pub latency_p95_ms: u64,
+    pub latency_p99_ms: u64,
+    pub uptime_secs: u64,
+    pub crashes: u64,
+    /// Size of gzip-compressed request bodies before compression
+    pub bytes_uncompressed: u64,
+    /// Size of the same bodies after compression
+    pub bytes_compressed: u64,
+    /// bytes_compressed / bytes_uncompressed, if anything was compressed
+    pub compression_ratio: Option<f64>,
+    /// Responses replaced by the moderation policy message
+    pub moderation_blocked: u64,
+}
+
+/// Metrics gathered since the last flush.
+#[derive(Default)]
+struct Accumulator {
+    latencies_ms: Vec<u64>,
+    errors: u64,
+    tokens: u64,
+    uptime_secs: u64,
+    crashes: u64,
+    bytes_uncompressed: u64,
+    bytes_compressed: u64,
+    moderation_blocked: u64,
+}
+
+impl Accumulator {
+    fn is_empty(&self) -> bool {
+        self.latencies_ms.is_empty() && self.uptime_secs == 0 && self.crashes == 0 && self.bytes_uncompressed == 0
+            && self.moderation_blocked == 0
+    }
+
+    fn rollup(&mut self, bucket: u64) -> MetricsRollup {
+        self.latencies_ms.sort_unstable();
+        MetricsRollup {
+            bucket,
+            requests: self.latencies_ms.len() as u64,
+            errors: self.errors,
+            tokens: self.tokens,
+            latency_p50_ms: percentile(&self.latencies_ms, 50),
+            latency_p95_ms: percentile(&self.latencies_ms, 95),
+            latency_p99_ms: percentile(&self.latencies_ms, 99),
+            uptime_secs: self.uptime_secs,
+            crashes: self.crashes,
+            bytes_uncompressed: self.bytes_uncompressed,
+            bytes_compressed: self.bytes_compressed,
+            compression_ratio: compression_ratio(self.bytes_uncompressed, self.bytes_compressed),
+            moderation_blocked: self.moderation_blocked,
+        }
+    }
+}
+
+fn compression_ratio(uncompressed: u64, compressed: u64) -> Option<f64> {
+    (uncompressed > 0).then(|| compressed as f64 / uncompressed as f64)
+}
+
+/// Nearest-rank percentile of an already sorted slice.
+pub(crate) fn percentile(sorted: &[u64], pct: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (pct * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// ==================== Metrics History Store ====================
+
+/// SQLite-backed metrics history plus the pending accumulator.
+pub struct MetricsHistory {
+    db: Connection,
+    pending: Accumulator,
+    /// Hour bucket the pending accumulator belongs to
+    pending_bucket: u64,
+}
+
+impl MetricsHistory {
+    /// Open (or create) the metrics database in `data_dir`.
+    ///
+    /// Falls back to an in-memory database if the file can't be opened,
+    /// so metrics keep working for the session.
+    pub fn open(data_dir: &Path) -> MetricsHistory {
+        let db = std::fs::create_dir_all(data_dir)
+            .map_err(|e| e.to_string())
+            .and_then(|_| Connection::open(data_dir.join(METRICS_DB_FILE)).map_err(|e| e.to_string()))
+            .unwrap_or_else(|e| {
+                println!("Failed to open metrics database, using in-memory store: {}", e);
+                Connection::open_in_memory().expect("in-memory SQLite must be available")
+            });
+
+        if let Err(e) = create_schema(&db) {
+            println!("Failed to create metrics schema: {}", e);
+        }
+
+        MetricsHistory {
+            db,
+            pending: Accumulator::default(),
+            pending_bucket: hour_bucket(unix_now()),
+        }
+    }
+
+    /// Record one completed engine request.
+    pub fn record_request(&mut self, latency: Duration, ok: bool, tokens: u64) {
+        self.roll_bucket();
+        self.pending.latencies_ms.push(latency.as_millis() as u64);
+        if !ok {
+            self.pending.errors += 1;
+        }
+        self.pending.tokens += tokens;
+    }
+
+    /// Record an unexpected engine exit.
+    pub fn record_crash(&mut self) {
+        self.roll_bucket();
+        self.pending.crashes += 1;
+    }
+
+    /// Record a response blocked by moderation.
+    pub fn record_moderation_block(&mut self) {
+        self.roll_bucket();
+        self.pending.moderation_blocked += 1;
+    }
+
+    /// Record engine uptime accumulated by the status loop.
+    pub fn record_uptime(&mut self, secs: u64) {
+        self.roll_bucket();
+        self.pending.uptime_secs += secs;
+    }
+
+    /// Flush first if the current hour has ended, so samples land in the right bucket.
+    fn roll_bucket(&mut self) {
+        if hour_bucket(unix_now()) != self.pending_bucket {
+            self.flush();
+        }
+    }
+
+    /// Write pending metrics to SQLite, refresh the daily rollup and prune.
+    pub fn flush(&mut self) {
+        let now_bucket = hour_bucket(unix_now());
+        let (uncompressed, compressed) = compression::take_totals();
+        self.pending.bytes_uncompressed += uncompressed;
+        self.pending.bytes_compressed += compressed;
+        if !self.pending.is_empty() {
+            let rollup = self.pending.rollup(self.pending_bucket);
+            if let Err(e) = upsert_hourly(&self.db, &rollup).and_then(|_| refresh_daily(&self.db, day_bucket(rollup.bucket))) {
+                println!("Failed to persist metrics: {}", e);
+            }
+        }
+        self.pending = Accumulator::default();
+        self.pending_bucket = now_bucket;
+
+        if let Err(e) = prune(&self.db) {
+            println!("Failed to prune metrics history: {}", e);
+        }
+    }
+
+    /// Remove (or with `dry_run`, count) hourly and daily rows of buckets before `before`.
+    pub(crate) fn prune_before(&self, before: u64, dry_run: bool) -> rusqlite::Result<u64> {
+        let mut rows = 0;
+        for table in ["metrics_hourly", "metrics_daily"] {
+            rows += if dry_run {
+                self.db.query_row(&format!("SELECT COUNT(*) FROM {} WHERE bucket < ?1", table), params![before as i64], |row| row.get::<_, i64>(0))? as u64
+            } else {
+                self.db.execute(&format!("DELETE FROM {} WHERE bucket < ?1", table), params![before as i64])? as u64
+            };
+        }
+        Ok(rows)
+    }
+
+    /// Load rollups within `range` at the requested granularity.
+    pub fn history(&self, range: &MetricsRange, granularity: Granularity) -> Result<Vec<MetricsRollup>, String> {
+        let table = match granularity {
+            Granularity::Hour => "metrics_hourly",
+            Granularity::Day => "metrics_daily",
+        };
+        let mut stmt = self.db
+            .prepare(&format!(
+                "SELECT bucket, requests, errors, tokens, latency_p50_ms, latency_p95_ms, latency_p99_ms, uptime_secs, crashes,
+                        bytes_uncompressed, bytes_compressed, moderation_blocked
+                 FROM {} WHERE bucket BETWEEN ?1 AND ?2 ORDER BY bucket",
+                table
+            ))
+            .map_err(|e| format!("Failed to query metrics history: {}", e))?;
+
+        let rows = stmt
+            .query_map(params![range.from as i64, range.to as i64], |row| {
+                let bytes_uncompressed = row.get::<_, i64>(9)? as u64;
+                let bytes_compressed = row.get::<_, i64>(10)? as u64;
+                Ok(MetricsRollup {
+                    bucket: row.get::<_, i64>(0)? as u64,
+                    requests: row.get::<_, i64>(1)? as u64,
+                    errors: row.get::<_, i64>(2)? as u64,
+                    tokens: row.get::<_, i64>(3)? as u64,
+                    latency_p50_ms: row.get::<_, i64>(4)? as u64,
+                    latency_p95_ms: row.get::<_, i64>(5)? as u64,
+                    latency_p99_ms: row.get::<_, i64>(6)? as u64,
+                    uptime_secs: row.get::<_, i64>(7)? as u64,
+                    crashes: row.get::<_, i64>(8)? as u64,
+                    bytes_uncompressed,
+                    bytes_compressed,
+                    compression_ratio: compression_ratio(bytes_uncompressed, bytes_compressed),
+                    moderation_blocked: row.get::<_, i64>(11)? as u64,
+                })
+            })
+            .map_err(|e| format!("Failed to query metrics history: {}", e))?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read metrics history: {}", e))
+    }
+}
+
+fn hour_bucket(ts: u64) -> u64 {
+    ts - ts % SECS_PER_HOUR
+}
+
+fn day_bucket(ts: u64) -> u64 {
+    ts - ts % SECS_PER_DAY
+}
+
+// ==================== SQL ====================
+
+fn create_schema(db: &Connection) -> rusqlite::Result<()> {
+    for table in ["metrics_hourly", "metrics_daily"] {
+        db.execute(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS {} (
+                    bucket          INTEGER PRIMARY KEY,
+                    requests        INTEGER NOT NULL,
+                    errors          INTEGER NOT NULL,
+                    tokens          INTEGER NOT NULL,
+                    latency_p50_ms  INTEGER NOT NULL,
+                    latency_p95_ms  INTEGER NOT NULL,
+                    latency_p99_ms  INTEGER NOT NULL,
+                    uptime_secs     INTEGER NOT NULL,
+                    crashes         INTEGER NOT NULL
+                )",
+                table
+            ),
+            [],
+        )?;
+        // Columns added after the first release
+        for column in ["bytes_uncompressed", "bytes_compressed", "moderation_blocked"] {
+            add_column_if_missing(db, table, column)?;
+        }
+    }
+    Ok(())
+}
+
+/// Add an `INTEGER NOT NULL DEFAULT 0` column to an existing table.
+fn add_column_if_missing(db: &Connection, table: &str, column: &str) -> rusqlite::Result<()> {
+    let exists = db
+        .prepare(&format!("PRAGMA table_info({})", table))?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<rusqlite::Result<Vec<_>>>()?
+        .iter()
+        .any(|name| name == column);
+    if !exists {
+        db.execute(&format!("ALTER TABLE {} ADD COLUMN {} INTEGER NOT NULL DEFAULT 0", table, column), [])?;
+    }
+    Ok(())
+}
+
+/// Merge a flush window into its hourly row (SET expressions see the old row).
+fn upsert_hourly(db: &Connection, r: &MetricsRollup) -> rusqlite::Result<()> {
+    db.execute(
+        "INSERT INTO metrics_hourly
+            (bucket, requests, errors, tokens, latency_p50_ms, latency_p95_ms, latency_p99_ms, uptime_secs, crashes,
+             bytes_uncompressed, bytes_compressed, moderation_blocked)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+         ON CONFLICT(bucket) DO UPDATE SET
+            latency_p50_ms = CASE WHEN requests + excluded.requests = 0 THEN 0
+                ELSE (latency_p50_ms * requests + excluded.latency_p50_ms * excluded.requests) / (requests + excluded.requests) END,
+            latency_p95_ms = CASE WHEN requests + excluded.requests = 0 THEN 0
+                ELSE (latency_p95_ms * requests + excluded.latency_p95_ms * excluded.requests) / (requests + excluded.requests) END,
+            latency_p99_ms = MAX(latency_p99_ms, excluded.latency_p99_ms),
+            requests = requests + excluded.requests,
+            errors = errors + excluded.errors,
+            tokens = tokens + excluded.tokens,
+            uptime_secs = uptime_secs + excluded.uptime_secs,
+            crashes = crashes + excluded.crashes,
+            bytes_uncompressed = bytes_uncompressed + excluded.bytes_uncompressed,
+            bytes_compressed = bytes_compressed + excluded.bytes_compressed,
+            moderation_blocked = moderation_blocked + excluded.moderation_blocked",
+        params![
+            r.bucket as i64, r.requests as i64, r.errors as i64, r.tokens as i64,
+            r.latency_p50_ms as i64, r.latency_p95_ms as i64, r.latency_p99_ms as i64,
+            r.uptime_secs as i64, r.crashes as i64,
+            r.bytes_uncompressed as i64, r.bytes_compressed as i64, r.moderation_blocked as i64,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Recompute the daily row for `day` from its hourly rows.
+fn refresh_daily(db: &Connection, day: u64) -> rusqlite::Result<()> {
+    db.execute(
+        "INSERT OR REPLACE INTO metrics_daily
+            (bucket, requests, errors, tokens, latency_p50_ms, latency_p95_ms, latency_p99_ms, uptime_secs, crashes,
+             bytes_uncompressed, bytes_compressed, moderation_blocked)
+         SELECT ?1,
+                SUM(requests), SUM(errors), SUM(tokens),
+                COALESCE(SUM(latency_p50_ms * requests) / NULLIF(SUM(requests), 0), 0),
+                COALESCE(SUM(latency_p95_ms * requests) / NULLIF(SUM(requests), 0), 0),
+                MAX(latency_p99_ms),
+                SUM(uptime_secs), SUM(crashes),
+                SUM(bytes_uncompressed), SUM(bytes_compressed), SUM(moderation_blocked)
+         FROM metrics_hourly WHERE bucket >= ?1 AND bucket < ?2",
+        params![day as i64, (day + SECS_PER_DAY) as i64],
+    )?;
+    Ok(())
+}
+
+fn prune(db: &Connection) -> rusqlite::Result<()> {
+    let now = unix_now();
+    db.execute(
+        "DELETE FROM metrics_hourly WHERE bucket < ?1",
+        params![now.saturating_sub(HOURLY_RETENTION_DAYS * SECS_PER_DAY) as i64],
+    )?;
+    db.execute(
+        "DELETE FROM metrics_daily WHERE bucket < ?1",
+        params![now.saturating_sub(DAILY_RETENTION_DAYS * SECS_PER_DAY) as i64],
+    )?;
+    Ok(())
+}
+
+// ==================== Setup & Helpers ====================
+
+/// Open the metrics store in the app data dir and start the flush task.
+pub fn init(app: &AppHandle) {
+    let data_dir = app.path().app_data_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join("ai-engine"));
+    app.manage(Mutex::new(MetricsHistory::open(&data_dir)));
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(METRICS_FLUSH_INTERVAL_SECS)).await;
+            app.state::<Mutex<MetricsHistory>>().lock().await.flush();
+        }
+    });
+}
+
+/// Token count reported by the engine in a response, if any.
+///
+/// Accepts either `{"usage": {"total_tokens": N}}` or `{"tokens": N}`.
+pub(crate) fn response_token_count(response: &serde_json::Value) -> u64 {
+    response.pointer("/usage/total_tokens")
+        .or_else(|| response.get("tokens"))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0)
+}
+
+// ==================== Tauri Command: get_metrics_history ====================
+
+/// Return hourly or daily metric rollups for a time range.
+///
+/// Pending metrics are flushed first so the current hour is included.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_metrics_history(range: MetricsRange, granularity: Granularity, history: State<'_, Mutex<MetricsHistory>>) -> Result<Vec<MetricsRollup>, EngineError> {
+    let mut history = history.lock().await;
+    history.flush();
+    Ok(history.history(&range, granularity)?)
+}
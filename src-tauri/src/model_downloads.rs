@@ -0,0 +1,238 @@
+//! =============================================================================
+//! Model Downloads
+//! =============================================================================
+//!
+//! `download_model(url_or_id, dest)` fetches model weights from inside the
+//! app and hands them to the engine:
+//!
+//!   url_or_id is a model id  →  POST /models/resolve { "model": id }
+//!                            →  { "url": "https://...", "sha256": "..." }
+//!   GET url (Range: bytes=<partial size>-)  →  <dest>.partial
+//!   SHA-256 check  →  rename to <dest>
+//!   POST /models/register { "path": dest, "sha256": "...", "model": id }
+//!
+//! The transfer is the one of the default model prefetch (see prefetch), so
+//! a download that failed or was cancelled keeps its partial file and the
+//! next `download_model` to the same destination continues where it stopped.
+//! `model_download_progress` is emitted at most every 500 ms.
+//!
+//! The checksum is `sha256` if given, else the one from /models/resolve; a
+//! mismatch discards the file. Without either the file's checksum is only
+//! reported. A gated model (see licenses) is only downloaded once its
+//! license is accepted. `dest` defaults to `<app data dir>/models/<file name>`; it is
+//! refused while the default model prefetch (see prefetch) has an unfinished
+//! download of the same file.
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri::async_runtime::Mutex;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use crate::analytics;
+use crate::downloads::partial_path;
+use crate::error::EngineError;
+use crate::events::{self, ModelDownloadProgress};
+use crate::requests::ActiveRequests;
+use crate::{auto_start_engine, drain, licenses, prefetch, transport, update_activity_impl, PythonProcess};
+
+/// Destinations with a download in progress (two writers would mix their bytes)
+static IN_PROGRESS: std::sync::Mutex<BTreeSet<PathBuf>> = std::sync::Mutex::new(BTreeSet::new());
+
+// ==================== Types ====================
+
+/// Result of a successful `download_model`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct DownloadedModel {
+    pub request_id: String,
+    pub url: String,
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    /// Lowercase hex SHA-256 of the file
+    pub sha256: String,
+    /// Whether the checksum was checked against an expected one
+    pub verified: bool,
+    /// Name the engine registered the model under, if it reported one
+    pub model: Option<String>,
+}
+
+/// Where a model comes from: its URL and, if known, its checksum.
+struct Source {
+    url: String,
+    /// Model id the URL was resolved from
+    id: Option<String>,
+    sha256: Option<String>,
+}
+
+/// Whether `download_model` is downloading to `dest`.
+pub(crate) fn is_downloading(dest: &Path) -> bool {
+    IN_PROGRESS.lock().unwrap_or_else(|e| e.into_inner()).contains(dest)
+}
+
+/// Marks `dest` as being downloaded until dropped.
+struct DestinationGuard(PathBuf);
+
+impl DestinationGuard {
+    fn claim(dest: &Path) -> Result<Self, EngineError> {
+        if !IN_PROGRESS.lock().unwrap_or_else(|e| e.into_inner()).insert(dest.to_path_buf()) {
+            return Err(EngineError::InvalidRequest(format!("{:?} is already being downloaded", dest)));
+        }
+        Ok(DestinationGuard(dest.to_path_buf()))
+    }
+}
+
+impl Drop for DestinationGuard {
+    fn drop(&mut self) {
+        IN_PROGRESS.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.0);
+    }
+}
+
+// ==================== Engine Calls ====================
+
+/// Count a request to the engine as activity and in flight (refused while it drains).
+async fn begin_engine_request(app: &AppHandle) -> Result<drain::InFlightGuard, EngineError> {
+    let state = app.state::<Mutex<PythonProcess>>();
+    let proc_state = state.lock().await;
+    update_activity_impl(&proc_state.last_activity).await;
+    drain::begin_request(&proc_state)
+}
+
+/// Take `url_or_id` as an http(s) URL, or ask the engine where model id `url_or_id` lives.
+async fn resolve(app: &AppHandle, url_or_id: &str) -> Result<Source, EngineError> {
+    if let Ok(url) = reqwest::Url::parse(url_or_id) {
+        if matches!(url.scheme(), "http" | "https") {
+            return Ok(Source { url: url_or_id.to_string(), id: None, sha256: None });
+        }
+        return Err(EngineError::InvalidRequest(format!("Unsupported model URL scheme: {}", url.scheme())));
+    }
+
+    let _request = begin_engine_request(app).await?;
+    auto_start_engine(app).await?;
+    let body = serde_json::json!({ "model": url_or_id });
+    let response = transport::engine_request(app, "POST", "/models/resolve", Some(&body), None).await?;
+    let url = response.get("url")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| EngineError::BadResponse("/models/resolve response has no \"url\"".to_string()))?;
+    Ok(Source {
+        url: url.to_string(),
+        id: Some(url_or_id.to_string()),
+        sha256: response.get("sha256").and_then(|v| v.as_str()).map(str::to_string),
+    })
+}
+
+/// Register the downloaded file with the engine; returns the model name it reports.
+async fn register(app: &AppHandle, source: &Source, path: &Path, sha256: &str) -> Result<Option<String>, EngineError> {
+    let _request = begin_engine_request(app).await?;
+    auto_start_engine(app).await?;
+    let body = serde_json::json!({
+        "path": path,
+        "sha256": sha256,
+        "model": source.id,
+    });
+    let response = transport::engine_request(app, "POST", "/models/register", Some(&body), None).await?;
+    Ok(response.get("model").and_then(|v| v.as_str()).map(str::to_string))
+}
+
+// ==================== Tauri Commands ====================
+
+/// Download a model by URL or engine model id and register it with the engine.
+///
+/// This command:
+///   1. Resolves a model id to its URL (and checksum) through /models/resolve
+///   2. Fails with `license_required` if the model is gated and its license
+///      wasn't accepted (see licenses)
+///   3. Downloads to `<dest>.partial`, continuing a previous partial download,
+///      cancellable with `cancel_request(request_id)` (generated if not given)
+///   4. Verifies the SHA-256 checksum and moves the file to `dest`
+///   5. POSTs the path to /models/register
+///
+/// Emits `model_download_progress` while downloading.
+#[tauri::command]
+#[specta::specta]
+pub async fn download_model(
+    app: AppHandle,
+    url_or_id: String,
+    dest: Option<PathBuf>,
+    sha256: Option<String>,
+    request_id: Option<String>,
+) -> Result<DownloadedModel, EngineError> {
+    let url_or_id = url_or_id.trim().to_string();
+    if url_or_id.is_empty() {
+        return Err(EngineError::InvalidRequest("Model URL or id must not be empty".to_string()));
+    }
+    analytics::count("download_model");
+    let mut handle = app.state::<ActiveRequests>().register(request_id)?;
+    let request_id = handle.id().to_string();
+
+    let source = handle.run(resolve(&app, &url_or_id)).await?;
+    let file_name = prefetch::file_name(&source.url)?;
+    let gated_as: Vec<&str> = source.id.iter().map(String::as_str).chain([file_name.as_str()]).collect();
+    handle.run(licenses::require_accepted(&app, &gated_as)).await?;
+    let expected = sha256.or_else(|| source.sha256.clone());
+    let dest = match dest {
+        Some(dest) if dest.is_dir() => dest.join(&file_name),
+        Some(dest) => dest,
+        None => {
+            let data_dir = app.path().app_data_dir()
+                .unwrap_or_else(|_| std::env::temp_dir().join("ai-engine"));
+            data_dir.join(prefetch::MODELS_DIR).join(&file_name)
+        }
+    };
+    let _claim = DestinationGuard::claim(&dest)?;
+    // Checked after claiming, so a prefetch starting now sees the claim
+    if app.state::<Mutex<prefetch::Prefetch>>().lock().await.owns(&dest) {
+        return Err(EngineError::InvalidRequest(format!("{:?} is being prefetched (see get_prefetch_status)", dest)));
+    }
+    if let Some(dir) = dest.parent() {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    }
+
+    println!("Downloading model {} to {:?}", source.url, dest);
+    let partial = partial_path(&dest);
+    let progress = |bytes_done, total_bytes, _persist| {
+        events::emit(&app, ModelDownloadProgress {
+            request_id: request_id.clone(),
+            url: source.url.clone(),
+            path: dest.clone(),
+            bytes_done,
+            total_bytes,
+        });
+        std::future::ready(())
+    };
+    let download = async {
+        prefetch::download_resumable(&source.url, &partial, expected.as_deref(), 0, progress).await.map_err(EngineError::from)
+    };
+    handle.run(download)
+        .await
+        .inspect_err(|e| println!("Download of model {} failed: {}", source.url, e))?;
+
+    let actual = prefetch::file_sha256(partial.clone()).await?;
+    if let Some(expected) = &expected {
+        if !actual.eq_ignore_ascii_case(expected) {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(EngineError::BadResponse(format!(
+                "Checksum mismatch for {}: expected {}, got {}",
+                source.url, expected, actual
+            )));
+        }
+    }
+    let size_bytes = tokio::fs::metadata(&partial).await.map(|m| m.len()).unwrap_or(0);
+    tokio::fs::rename(&partial, &dest)
+        .await
+        .map_err(|e| format!("Failed to move {:?} into place: {}", partial, e))?;
+    println!("Downloaded model {} ({} bytes) to {:?}", source.url, size_bytes, dest);
+
+    let model = register(&app, &source, &dest, &actual).await?;
+    Ok(DownloadedModel {
+        request_id,
+        url: source.url,
+        path: dest,
+        size_bytes,
+        sha256: actual,
+        verified: expected.is_some(),
+        model,
+    })
+}
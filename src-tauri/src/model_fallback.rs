@@ -0,0 +1,187 @@
+//! =============================================================================
+//! Model Selection Under Memory Pressure
+//! =============================================================================
+//!
+//! `settings.model.fallback_chain` lists model tiers from most to least
+//! preferred, each with the memory it needs to load:
+//!
+//!   [ { "name": "llama-8b-q8", "min_memory_mb": 10240 },
+//!     { "name": "llama-8b-q4", "min_memory_mb": 6144 },
+//!     { "name": "llama-3b-q4", "min_memory_mb": 3072 } ]
+//!
+//! Before the engine is spawned, the first tier that fits in the available
+//! memory is selected and passed to the engine as AI_ENGINE_MODEL. Picking
+//! anything but the first tier emits `model_downgraded`; if no tier fits the
+//! start fails up front instead of the engine crashing while loading.
+//!
+//! The last decision is kept for diagnostics (`get_model_selection`).
+//! With an empty chain the engine picks its own model.
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use specta::Type;
+use tauri::{AppHandle, Manager, State};
+use tauri::async_runtime::Mutex;
+
+use crate::error::EngineError;
+use crate::events::{self, ModelDowngraded};
+use crate::settings::SettingsStore;
+
+/// Environment variable telling the engine which model to load
+pub(crate) const MODEL_ENV_VAR: &str = "AI_ENGINE_MODEL";
+
+// ==================== Types ====================
+
+/// One entry of the fallback chain.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, JsonSchema)]
+pub struct ModelTier {
+    pub name: String,
+    pub min_memory_mb: u64,
+}
+
+/// Model chosen for an engine start and why.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ModelDecision {
+    /// First tier of the chain
+    pub requested: String,
+    pub selected: String,
+    /// Available memory at selection time, if it could be detected
+    pub available_mb: Option<u64>,
+    pub required_mb: u64,
+    pub downgraded: bool,
+}
+
+/// Most recent model decision, kept for diagnostics.
+#[derive(Default)]
+pub struct ModelSelectionState {
+    last: Option<ModelDecision>,
+}
+
+// ==================== Hardware Detection ====================
+
+/// Memory available for new allocations, in MiB.
+///
+/// Returns None where detection isn't supported; no downgrade happens then.
+fn available_memory_mb() -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+        meminfo
+            .lines()
+            .find(|line| line.starts_with("MemAvailable:"))
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|kb| kb.parse::<u64>().ok())
+            .map(|kb| kb / 1024)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+// ==================== Selection ====================
+
+/// The first tier of `chain` that fits in `available_mb` (the first tier
+/// when memory is unknown); None for an empty chain.
+fn choose(chain: &[ModelTier], available_mb: Option<u64>) -> Result<Option<ModelDecision>, String> {
+    let Some(preferred) = chain.first() else {
+        return Ok(None);
+    };
+    let tier = match available_mb {
+        Some(available) => chain
+            .iter()
+            .find(|tier| tier.min_memory_mb <= available)
+            .ok_or_else(|| format!(
+                "Not enough memory for any configured model: {} MiB available, smallest tier {} needs {} MiB",
+                available,
+                chain.last().map(|t| t.name.as_str()).unwrap_or(""),
+                chain.last().map(|t| t.min_memory_mb).unwrap_or(0),
+            ))?,
+        None => preferred,
+    };
+
+    Ok(Some(ModelDecision {
+        requested: preferred.name.clone(),
+        selected: tier.name.clone(),
+        available_mb,
+        required_mb: tier.min_memory_mb,
+        downgraded: tier.name != preferred.name,
+    }))
+}
+
+/// Pick the model for the next engine start from the configured chain.
+///
+/// Returns Ok(None) when no chain is configured, and an error when not even
+/// the last tier fits in memory.
+pub(crate) async fn select_model(app: &AppHandle) -> Result<Option<ModelDecision>, String> {
+    let chain = app.state::<Mutex<SettingsStore>>().lock().await.settings.model.fallback_chain.clone();
+    if chain.is_empty() {
+        return Ok(None);
+    }
+    let available_mb = available_memory_mb();
+    let Some(decision) = choose(&chain, available_mb)? else {
+        return Ok(None);
+    };
+
+    if decision.downgraded {
+        println!(
+            "Insufficient memory for {} ({:?} MiB available), falling back to {}",
+            decision.requested, available_mb, decision.selected
+        );
+        events::emit(app, ModelDowngraded {
+            requested: decision.requested.clone(),
+            selected: decision.selected.clone(),
+            available_mb: available_mb.unwrap_or(0),
+            required_mb: decision.required_mb,
+        });
+    }
+
+    app.state::<Mutex<ModelSelectionState>>().lock().await.last = Some(decision.clone());
+    Ok(Some(decision))
+}
+
+// ==================== Tauri Command: get_model_selection ====================
+
+/// Return the model decision made for the most recent engine start.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_model_selection(state: State<'_, Mutex<ModelSelectionState>>) -> Result<Option<ModelDecision>, EngineError> {
+    Ok(state.lock().await.last.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain() -> Vec<ModelTier> {
+        [("llama-8b-q8", 10240), ("llama-8b-q4", 6144), ("llama-3b-q4", 3072)]
+            .into_iter()
+            .map(|(name, min_memory_mb)| ModelTier { name: name.to_string(), min_memory_mb })
+            .collect()
+    }
+
+    fn selected(available_mb: Option<u64>) -> (String, bool) {
+        let decision = choose(&chain(), available_mb).unwrap().unwrap();
+        (decision.selected, decision.downgraded)
+    }
+
+    #[test]
+    fn first_tier_that_fits_is_selected() {
+        assert_eq!(selected(Some(16384)), ("llama-8b-q8".to_string(), false));
+        assert_eq!(selected(Some(10240)), ("llama-8b-q8".to_string(), false));
+        assert_eq!(selected(Some(8000)), ("llama-8b-q4".to_string(), true));
+        assert_eq!(selected(Some(3072)), ("llama-3b-q4".to_string(), true));
+    }
+
+    #[test]
+    fn unknown_memory_keeps_the_preferred_tier() {
+        assert_eq!(selected(None), ("llama-8b-q8".to_string(), false));
+    }
+
+    #[test]
+    fn nothing_fitting_or_configured() {
+        let error = choose(&chain(), Some(2048)).unwrap_err();
+        assert!(error.contains("llama-3b-q4 needs 3072 MiB"), "{}", error);
+        assert!(choose(&[], Some(2048)).unwrap().is_none());
+    }
+}
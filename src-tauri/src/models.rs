@@ -0,0 +1,162 @@
+//! =============================================================================
+//! Model Management
+//! =============================================================================
+//!
+//! Lets the frontend offer a model picker. The engine lists the models it
+//! can serve and loads or unloads them on request:
+//!
+//!   GET  /models         →  { "models": [ { "name": "llama-3b-q4", "loaded": true,
+//!                                           "size_mb": 2048 }, ... ] }
+//!   POST /models/load    { "model": "llama-8b-q4" }
+//!   POST /models/unload  { "model": "llama-3b-q4" }
+//!
+//! Loading a model replaces the current one. The loaded model is tracked in
+//! `PythonProcess` from these calls, from what /models reports and from
+//! `model_loaded` engine events; every change is emitted as `model_changed`.
+//! A fresh engine starts without a tracked model.
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri::async_runtime::Mutex;
+use std::time::Duration;
+
+use crate::error::EngineError;
+use crate::events::{self, ModelChanged};
+use crate::{analytics, auto_start_engine, drain, licenses, transport, update_activity_impl, warmup, PythonProcess};
+
+/// Timeout of a load request; loading a model can take half a minute or more
+const MODEL_LOAD_TIMEOUT_SECS: u64 = 120;
+
+// ==================== Types ====================
+
+/// A model the engine can serve.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ModelInfo {
+    pub name: String,
+    pub loaded: bool,
+    pub size_mb: Option<u64>,
+}
+
+/// Models of the engine, returned by `list_models`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ModelList {
+    pub models: Vec<ModelInfo>,
+    /// Currently loaded model, if any
+    pub loaded: Option<String>,
+}
+
+/// Parse one /models entry (an object with a `name`, or just the name).
+fn parse_model(entry: &serde_json::Value) -> Option<ModelInfo> {
+    if let Some(name) = entry.as_str() {
+        return Some(ModelInfo { name: name.to_string(), loaded: false, size_mb: None });
+    }
+    Some(ModelInfo {
+        name: entry.get("name")?.as_str()?.to_string(),
+        loaded: entry.get("loaded").and_then(|v| v.as_bool()).unwrap_or(false),
+        size_mb: entry.get("size_mb").and_then(|v| v.as_u64()),
+    })
+}
+
+// ==================== Loaded Model Tracking ====================
+
+/// Record `model` as the loaded one, emitting `model_changed` if it changed.
+pub(crate) async fn set_loaded(app: &AppHandle, model: Option<String>) {
+    let state = app.state::<Mutex<PythonProcess>>();
+    let mut proc_state = state.lock().await;
+    if proc_state.loaded_model == model {
+        return;
+    }
+    let previous = std::mem::replace(&mut proc_state.loaded_model, model.clone());
+    drop(proc_state);
+
+    println!("Loaded model changed: {:?} -> {:?}", previous, model);
+    events::emit(app, ModelChanged { previous, current: model });
+}
+
+/// Count a model request as activity and in flight (refused while the engine drains).
+async fn begin_model_request(app: &AppHandle) -> Result<drain::InFlightGuard, EngineError> {
+    let state = app.state::<Mutex<PythonProcess>>();
+    let proc_state = state.lock().await;
+    update_activity_impl(&proc_state.last_activity).await;
+    drain::begin_request(&proc_state)
+}
+
+// ==================== Tauri Commands ====================
+
+/// List the models the engine can serve and which one is loaded.
+///
+/// Starts the engine if it is stopped and `settings.engine.auto_start` is on.
+/// The loaded model reported by the engine becomes the tracked one.
+#[tauri::command]
+#[specta::specta]
+pub async fn list_models(app: AppHandle) -> Result<ModelList, EngineError> {
+    let _request = begin_model_request(&app).await?;
+    auto_start_engine(&app).await?;
+    let response = transport::engine_request(&app, "GET", "/models", None, None).await?;
+    let models: Vec<ModelInfo> = response.get("models")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| EngineError::BadResponse("/models response has no \"models\" array".to_string()))?
+        .iter()
+        .filter_map(parse_model)
+        .collect();
+
+    let loaded = models.iter().find(|m| m.loaded).map(|m| m.name.clone());
+    set_loaded(&app, loaded.clone()).await;
+    Ok(ModelList { models, loaded })
+}
+
+/// Load model `name`, replacing the current one.
+///
+/// This command:
+///   1. Starts the engine if it is stopped and `settings.engine.auto_start` is on
+///   2. Fails with `license_required` if the model is gated and its license
+///      wasn't accepted (see licenses)
+///   3. POSTs the model to /models/load (waiting up to MODEL_LOAD_TIMEOUT_SECS)
+///   4. Tracks it as the loaded model, emitting `model_changed` and `model_ready`
+#[tauri::command]
+#[specta::specta]
+pub async fn load_model(app: AppHandle, name: String) -> Result<(), EngineError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(EngineError::InvalidRequest("Model name must not be empty".to_string()));
+    }
+    let _request = begin_model_request(&app).await?;
+    auto_start_engine(&app).await?;
+    licenses::require_accepted(&app, &[&name]).await?;
+
+    println!("Loading model {}", name);
+    analytics::count("load_model");
+    let body = serde_json::json!({ "model": name });
+    let timeout = Some(Duration::from_secs(MODEL_LOAD_TIMEOUT_SECS));
+    transport::engine_request(&app, "POST", "/models/load", Some(&body), timeout).await?;
+
+    // The new model replaced the previous one in memory
+    let previous = app.state::<Mutex<PythonProcess>>().lock().await.loaded_model.clone();
+    if let Some(previous) = previous.filter(|previous| *previous != name) {
+        warmup::forget(&previous);
+    }
+    warmup::mark_ready(&app, &name);
+    set_loaded(&app, Some(name)).await;
+    Ok(())
+}
+
+/// Unload model `name` to free its memory.
+///
+/// If it was the loaded model, no model is tracked afterwards (`model_changed`).
+#[tauri::command]
+#[specta::specta]
+pub async fn unload_model(app: AppHandle, name: String) -> Result<(), EngineError> {
+    let _request = begin_model_request(&app).await?;
+
+    println!("Unloading model {}", name);
+    let body = serde_json::json!({ "model": name });
+    transport::engine_request(&app, "POST", "/models/unload", Some(&body), None).await?;
+
+    warmup::forget(&name);
+    let was_loaded = app.state::<Mutex<PythonProcess>>().lock().await.loaded_model.as_deref() == Some(name.as_str());
+    if was_loaded {
+        set_loaded(&app, None).await;
+    }
+    Ok(())
+}
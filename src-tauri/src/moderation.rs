@@ -0,0 +1,186 @@
+//! =============================================================================
+//! Response Moderation
+//! =============================================================================
+//!
+//! Kiosk deployments check every engine response before it reaches the UI.
+//! `settings.moderation.strictness` selects how:
+//!
+//!   off       no checks (default)
+//!   relaxed   filter rules only (rules/*.json, see templates): "block" rules
+//!             block the response, "redact" rules rewrite it
+//!   standard  rules, then the engine's classifier (POST /moderate) with a
+//!             flag threshold of STANDARD_THRESHOLD; if the classifier is
+//!             unavailable the response passes
+//!   strict    rules, then the classifier with STRICT_THRESHOLD; if the
+//!             classifier is unavailable the response is blocked
+//!
+//! Classifier contract:
+//!
+//!   POST /moderate { "text": "…" }  →  { "flagged": true, "score": 0.93, "categories": ["violence"] }
+//!
+//! A blocked response is replaced with `settings.moderation.policy_message`,
+//! written to the audit trail (without its text) and counted in the metrics
+//! history. With moderation on, streamed tokens are held back until the full
+//! text has been checked (see streaming): an allowed stream then arrives as
+//! one chunk, a blocked one sends no chunks and ends with the policy message
+//! as its error frame.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri::async_runtime::Mutex;
+
+use crate::audit;
+use crate::metrics_history::MetricsHistory;
+use crate::settings::SettingsStore;
+use crate::templates::{FilterAction, TemplateStore};
+use crate::transport;
+
+/// Classifier score at or above which `standard` blocks a response
+const STANDARD_THRESHOLD: f64 = 0.8;
+
+/// Classifier score at or above which `strict` blocks a response
+const STRICT_THRESHOLD: f64 = 0.5;
+
+// ==================== Settings ====================
+
+/// How thoroughly responses are moderated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Strictness {
+    #[default]
+    Off,
+    Relaxed,
+    Standard,
+    Strict,
+}
+
+/// Response moderation settings.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(default)]
+pub struct ModerationSettings {
+    pub strictness: Strictness,
+    /// Shown instead of a blocked response
+    pub policy_message: String,
+}
+
+impl Default for ModerationSettings {
+    fn default() -> Self {
+        ModerationSettings {
+            strictness: Strictness::Off,
+            policy_message: "This response was withheld by the content policy.".to_string(),
+        }
+    }
+}
+
+// ==================== Checks ====================
+
+/// Classifier verdict from POST /moderate.
+#[derive(Debug, Deserialize)]
+struct ClassifierVerdict {
+    #[serde(default)]
+    flagged: bool,
+    score: Option<f64>,
+    #[serde(default)]
+    categories: Vec<String>,
+}
+
+/// Apply the filter rules; returns the (redacted) text or the name of the blocking rule.
+async fn apply_rules(app: &AppHandle, text: &str) -> Result<String, String> {
+    let rules = app.state::<Mutex<TemplateStore>>().lock().await.rules();
+    let mut text = text.to_string();
+    for rule in rules {
+        // Patterns were validated when the rules file was loaded
+        let Ok(pattern) = Regex::new(&rule.pattern) else {
+            continue;
+        };
+        match rule.action {
+            FilterAction::Block if pattern.is_match(&text) => return Err(format!("rule '{}'", rule.name)),
+            FilterAction::Block => {}
+            FilterAction::Redact => {
+                let replacement = rule.replacement.as_deref().unwrap_or_default();
+                text = pattern.replace_all(&text, regex::NoExpand(replacement)).into_owned();
+            }
+        }
+    }
+    Ok(text)
+}
+
+/// Ask the engine's classifier about `text`; Err if it can't be reached.
+async fn classify(app: &AppHandle, text: &str, threshold: f64) -> Result<Option<String>, String> {
+    let response = transport::engine_request(app, "POST", "/moderate", Some(&serde_json::json!({ "text": text })), None)
+        .await
+        .map_err(|e| e.to_string())?;
+    let verdict: ClassifierVerdict = serde_json::from_value(response)
+        .map_err(|e| format!("invalid /moderate response: {}", e))?;
+    let score = verdict.score.unwrap_or(if verdict.flagged { 1.0 } else { 0.0 });
+    Ok((score >= threshold).then(|| {
+        format!("classifier score {:.2} ({})", score, verdict.categories.join(", "))
+    }))
+}
+
+/// Whether responses are moderated, so streams must hold their tokens back.
+pub(crate) async fn is_enabled(app: &AppHandle) -> bool {
+    app.state::<Mutex<SettingsStore>>().lock().await.settings.moderation.strictness != Strictness::Off
+}
+
+/// Moderate one response text.
+///
+/// Returns the text to show (redacted where rules say so), or Err with the
+/// policy message if the response is blocked.
+pub(crate) async fn moderate(app: &AppHandle, request_id: &str, text: &str) -> Result<String, String> {
+    let settings = app.state::<Mutex<SettingsStore>>().lock().await.settings.moderation.clone();
+    let threshold = match settings.strictness {
+        Strictness::Off => return Ok(text.to_string()),
+        Strictness::Relaxed => None,
+        Strictness::Standard => Some(STANDARD_THRESHOLD),
+        Strictness::Strict => Some(STRICT_THRESHOLD),
+    };
+
+    let verdict = match apply_rules(app, text).await {
+        Err(reason) => Err(reason),
+        Ok(text) => match threshold {
+            None => Ok(text),
+            Some(threshold) => match classify(app, &text, threshold).await {
+                Ok(None) => Ok(text),
+                Ok(Some(reason)) => Err(reason),
+                Err(e) if settings.strictness == Strictness::Strict => Err(format!("classifier unavailable: {}", e)),
+                Err(e) => {
+                    println!("Moderation classifier unavailable, allowing response {}: {}", request_id, e);
+                    Ok(text)
+                }
+            },
+        },
+    };
+
+    let Err(reason) = verdict else {
+        return verdict;
+    };
+    println!("Response {} blocked by moderation: {}", request_id, reason);
+    audit::record(app, "moderation_blocked", serde_json::json!({
+        "request_id": request_id,
+        "strictness": settings.strictness,
+        "reason": reason,
+        "length": text.len(),
+    }));
+    app.state::<Mutex<MetricsHistory>>().lock().await.record_moderation_block();
+    Err(settings.policy_message)
+}
+
+/// Moderate the `output` of an engine response in place.
+///
+/// A blocked response gets the policy message as `output` and `"moderated": true`.
+pub(crate) async fn moderate_response(app: &AppHandle, request_id: &str, response: &mut serde_json::Value) {
+    let Some(output) = response.get("output").and_then(|o| o.as_str()).map(str::to_string) else {
+        return;
+    };
+    match moderate(app, request_id, &output).await {
+        Ok(text) => response["output"] = text.into(),
+        Err(policy_message) => {
+            response["output"] = policy_message.into();
+            response["moderated"] = true.into();
+        }
+    }
+}
@@ -0,0 +1,371 @@
+//! =============================================================================
+//! Multiplexed Engine Connection
+//! =============================================================================
+//!
+//! Opening one socket connection per request gets expensive under high
+//! concurrency. When the engine supports it, all requests share a single
+//! persistent full-duplex connection instead:
+//!
+//!   Negotiation:  GET /mux  →  { "version": 1, "socket": "/tmp/ai-engine.mux.sock" }
+//!
+//!   Framing (both directions): 4-byte big-endian length + JSON payload
+//!     request   { "id": 7, "method": "POST", "path": "/input", "body": {...},
+//!                 "headers": { "x-trace-id": "4bf9…", ... } }
+//!     response  { "id": 7, "status": 200, "body": {...},
+//!                 "headers": { "x-trace-id": "4bf9…" } }
+//!
+//! Request headers are the correlation headers (see trace_context) plus any
+//! added by request hooks; the engine echoes the trace id like over HTTP.
+//! An engine the app spawned also gets its request key in `authorization` and
+//! answers with its proof in `x-engine-auth` (see engine_auth).
+//!
+//! Frames are written by a single writer task, so a request that times out
+//! or is cancelled mid-write never leaves half a frame on the shared
+//! connection; its response, if one still arrives, is dropped.
+//!
+//! Responses may arrive in any order; the request ID routes each one back to
+//! its caller. If the engine doesn't support /mux, or the connection breaks,
+//! requests fall back to one HTTP connection per request. A request whose
+//! frame was already written is not sent again: the engine may have acted on
+//! it, so it fails with the connection error instead.
+
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::Mutex;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, WriteHalf};
+use tokio::sync::{mpsc, oneshot};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crate::engine_auth::EngineKeys;
+use crate::error::EngineError;
+use crate::ipc::{self, EngineStream};
+use crate::{engine_auth, socket_http_get, trace_context};
+
+// ==================== Configuration Constants ====================
+
+/// Multiplexing protocol version this backend speaks
+const MUX_PROTOCOL_VERSION: u64 = 1;
+
+/// Largest frame accepted from the engine (16 MiB)
+const MAX_FRAME_BYTES: usize = 16 * 1024 * 1024;
+
+// ==================== Frame Types ====================
+
+#[derive(Serialize)]
+struct RequestFrame<'a> {
+    id: u64,
+    method: &'a str,
+    path: &'a str,
+    body: Option<&'a serde_json::Value>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    headers: HashMap<&'a str, &'a str>,
+}
+
+#[derive(Deserialize)]
+struct ResponseFrame {
+    id: u64,
+    status: u16,
+    #[serde(default)]
+    body: serde_json::Value,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+}
+
+/// A request waiting for its response frame.
+struct Pending {
+    path: String,
+    trace_id: String,
+    sender: oneshot::Sender<Result<serde_json::Value, EngineError>>,
+}
+
+type PendingMap = std::sync::Mutex<HashMap<u64, Pending>>;
+
+fn lock_pending(pending: &PendingMap) -> std::sync::MutexGuard<'_, HashMap<u64, Pending>> {
+    pending.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Removes a request from the pending map when it completes or is dropped.
+struct PendingGuard<'a> {
+    pending: &'a PendingMap,
+    id: u64,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        lock_pending(self.pending).remove(&self.id);
+    }
+}
+
+/// A complete frame for the writer task, and where to report the write.
+struct Outgoing {
+    payload: Vec<u8>,
+    written: oneshot::Sender<std::io::Result<()>>,
+}
+
+/// Why a multiplexed request failed.
+#[derive(Debug)]
+pub enum MuxError {
+    /// The frame was never written; the request may be sent another way
+    NotSent(EngineError),
+    /// The frame was written, so the engine may have acted on it
+    Failed(EngineError),
+}
+
+/// Shared slot holding the negotiated connection (None = per-request mode).
+pub type MuxSlot = Arc<Mutex<Option<Arc<MuxClient>>>>;
+
+// ==================== Mux Client ====================
+
+/// A single persistent connection carrying many concurrent requests.
+pub struct MuxClient {
+    /// Frames for the writer task, the only one writing to the connection
+    outgoing: mpsc::UnboundedSender<Outgoing>,
+    pending: Arc<PendingMap>,
+    next_id: AtomicU64,
+    alive: Arc<AtomicBool>,
+    /// Keys of the engine that offered the connection, if the app spawned it
+    keys: Option<EngineKeys>,
+}
+
+impl MuxClient {
+    /// Ask the engine whether it supports multiplexing and connect if so.
+    ///
+    /// Returns None when the engine doesn't offer a compatible /mux socket.
+    pub async fn negotiate(socket_path: &str) -> Option<Arc<MuxClient>> {
+        let offer = socket_http_get(socket_path, "/mux").await.ok()?;
+        let version = offer.get("version").and_then(|v| v.as_u64())?;
+        let mux_path = offer.get("socket").and_then(|v| v.as_str())?;
+
+        if version != MUX_PROTOCOL_VERSION {
+            println!("Engine offers mux protocol v{}, expected v{}; using per-request connections", version, MUX_PROTOCOL_VERSION);
+            return None;
+        }
+
+        match ipc::connect(mux_path).await {
+            Ok(stream) => {
+                println!("Multiplexed connection established at {}", mux_path);
+                Some(MuxClient::start(stream, engine_auth::keys(socket_path)))
+            }
+            Err(e) => {
+                println!("Failed to connect to mux socket {}: {}", mux_path, e);
+                None
+            }
+        }
+    }
+
+    /// Split the stream and spawn the reader task that routes responses and
+    /// the writer task that sends frames.
+    fn start(stream: EngineStream, keys: Option<EngineKeys>) -> Arc<MuxClient> {
+        let (mut reader, writer) = tokio::io::split(stream);
+        let pending: Arc<PendingMap> = Arc::new(std::sync::Mutex::new(HashMap::new()));
+        let alive = Arc::new(AtomicBool::new(true));
+
+        let (outgoing, frames) = mpsc::unbounded_channel();
+        tauri::async_runtime::spawn(write_frames(writer, frames, alive.clone()));
+
+        let reader_pending = pending.clone();
+        let reader_alive = alive.clone();
+        let reader_keys = keys.clone();
+        tauri::async_runtime::spawn(async move {
+            let error = loop {
+                let frame = match read_frame(&mut reader).await {
+                    Ok(frame) => frame,
+                    Err(e) => break e,
+                };
+                let response: ResponseFrame = match serde_json::from_slice(&frame) {
+                    Ok(response) => response,
+                    Err(e) => break format!("Invalid mux frame: {}", e),
+                };
+
+                let pending = lock_pending(&reader_pending).remove(&response.id);
+                if let Some(pending) = pending {
+                    let echoed = response.headers.iter()
+                        .find(|(name, _)| name.eq_ignore_ascii_case(trace_context::TRACE_ID_HEADER))
+                        .map(|(_, value)| value.as_str());
+                    trace_context::verify_echo(&pending.path, &pending.trace_id, echoed);
+                    let proof = response.headers.iter()
+                        .find(|(name, _)| name.eq_ignore_ascii_case(engine_auth::PROOF_HEADER))
+                        .map(|(_, value)| value.as_str());
+                    let authenticated = match &reader_keys {
+                        Some(keys) => engine_auth::verify(keys, &pending.path, &pending.trace_id, proof),
+                        None => Ok(()),
+                    };
+                    let result = if let Err(e) = authenticated {
+                        Err(e)
+                    } else if (200..300).contains(&response.status) {
+                        Ok(response.body)
+                    } else {
+                        Err(EngineError::http(response.status, &serde_json::to_vec(&response.body).unwrap_or_default()))
+                    };
+                    let _ = pending.sender.send(result);
+                }
+            };
+
+            // Connection is gone: fail everything still waiting
+            println!("Multiplexed connection closed: {}", error);
+            reader_alive.store(false, Ordering::SeqCst);
+            for (_, pending) in lock_pending(&reader_pending).drain() {
+                let _ = pending.sender.send(Err(EngineError::connection_closed(format!("Multiplexed connection closed: {}", error))));
+            }
+        });
+
+        Arc::new(MuxClient {
+            outgoing,
+            pending,
+            next_id: AtomicU64::new(1),
+            alive,
+            keys,
+        })
+    }
+
+    /// Returns false once the underlying connection has closed.
+    pub fn is_alive(&self) -> bool {
+        self.alive.load(Ordering::SeqCst)
+    }
+
+    /// Send one request and wait for its response frame.
+    pub async fn request(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&serde_json::Value>,
+        headers: &[(String, String)],
+    ) -> Result<serde_json::Value, MuxError> {
+        if !self.is_alive() {
+            return Err(MuxError::NotSent(EngineError::connection_closed("Multiplexed connection is closed".to_string())));
+        }
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let correlation = trace_context::headers_for(body);
+        let authorization = self.keys.as_ref().map(engine_auth::authorization);
+        let mut headers: HashMap<&str, &str> = correlation.iter().chain(headers).map(|(name, value)| (name.as_str(), value.as_str())).collect();
+        if let Some(authorization) = &authorization {
+            headers.insert("authorization", authorization);
+        }
+        let payload = serde_json::to_vec(&RequestFrame { id, method, path, body, headers })
+            .map_err(|e| MuxError::NotSent(EngineError::Internal(format!("Failed to serialize mux frame: {}", e))))?;
+
+        let (sender, receiver) = oneshot::channel();
+        let trace_id = correlation[0].1.clone();
+        lock_pending(&self.pending).insert(id, Pending { path: path.to_string(), trace_id, sender });
+        let _pending = PendingGuard { pending: &self.pending, id };
+
+        let (written, write_result) = oneshot::channel();
+        if self.outgoing.send(Outgoing { payload, written }).is_err() {
+            return Err(MuxError::NotSent(EngineError::connection_closed("Multiplexed connection is closed".to_string())));
+        }
+        match write_result.await {
+            Ok(Ok(())) => {}
+            // An incomplete frame can't be parsed by the engine
+            Ok(Err(e)) => return Err(MuxError::NotSent(EngineError::socket("Failed to write multiplexed frame", &e))),
+            Err(_) => {
+                return Err(MuxError::NotSent(EngineError::connection_closed(
+                    "Multiplexed connection closed before the request was sent".to_string(),
+                )));
+            }
+        }
+
+        receiver
+            .await
+            .map_err(|_| EngineError::connection_closed("Multiplexed connection dropped the request".to_string()))
+            .and_then(|result| result)
+            .map_err(MuxError::Failed)
+    }
+}
+
+// ==================== Framing ====================
+
+async fn read_frame(reader: &mut (impl AsyncReadExt + Unpin)) -> Result<Vec<u8>, String> {
+    let len = reader.read_u32().await
+        .map_err(|e| format!("Failed to read frame header: {}", e))? as usize;
+    if len > MAX_FRAME_BYTES {
+        return Err(format!("Frame of {} bytes exceeds limit of {} bytes", len, MAX_FRAME_BYTES));
+    }
+    let mut frame = vec![0u8; len];
+    reader.read_exact(&mut frame).await
+        .map_err(|e| format!("Failed to read frame body: {}", e))?;
+    Ok(frame)
+}
+
+async fn write_frame(writer: &mut (impl AsyncWriteExt + Unpin), payload: &[u8]) -> std::io::Result<()> {
+    writer.write_u32(payload.len() as u32).await?;
+    writer.write_all(payload).await
+}
+
+/// Write queued frames whole, one after another, until the client is gone
+/// or a write fails (the connection is unusable after a partial frame).
+async fn write_frames(
+    mut writer: WriteHalf<EngineStream>,
+    mut frames: mpsc::UnboundedReceiver<Outgoing>,
+    alive: Arc<AtomicBool>,
+) {
+    while let Some(frame) = frames.recv().await {
+        let result = write_frame(&mut writer, &frame.payload).await;
+        let failed = result.is_err();
+        let _ = frame.written.send(result);
+        if failed {
+            println!("Multiplexed connection closed: a frame couldn't be written");
+            alive.store(false, Ordering::SeqCst);
+            let _ = writer.shutdown().await;
+            return;
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::time::Duration;
+    use tokio::net::UnixStream;
+
+    #[test]
+    fn frames_round_trip_and_oversized_frames_are_refused() {
+        tauri::async_runtime::block_on(async {
+            let (mut a, mut b) = tokio::io::duplex(1024);
+            write_frame(&mut a, b"{\"id\":1}").await.unwrap();
+            write_frame(&mut a, b"").await.unwrap();
+            assert_eq!(read_frame(&mut b).await.unwrap(), b"{\"id\":1}");
+            assert_eq!(read_frame(&mut b).await.unwrap(), b"");
+
+            a.write_u32(MAX_FRAME_BYTES as u32 + 1).await.unwrap();
+            let error = read_frame(&mut b).await.unwrap_err();
+            assert!(error.contains("exceeds limit"), "{}", error);
+        });
+    }
+
+    #[test]
+    fn cancelled_request_is_sent_whole_and_forgotten() {
+        tauri::async_runtime::block_on(async {
+            let (client_end, mut engine) = UnixStream::pair().unwrap();
+            let client = MuxClient::start(client_end, None);
+
+            // Abandon a request after its frame went out
+            let abandoned = tokio::time::timeout(Duration::from_millis(100), client.request("POST", "/slow", None, &[])).await;
+            assert!(abandoned.is_err());
+            assert!(lock_pending(&client.pending).is_empty());
+
+            let frame: serde_json::Value = serde_json::from_slice(&read_frame(&mut engine).await.unwrap()).unwrap();
+            assert_eq!(frame["id"], 1);
+            assert_eq!(frame["path"], "/slow");
+
+            // A late response to it is dropped; the next request still gets its own
+            let reply = |id: u64| serde_json::to_vec(&json!({ "id": id, "status": 200, "body": { "id": id } })).unwrap();
+            let next = tauri::async_runtime::spawn({
+                let client = client.clone();
+                async move { client.request("GET", "/fast", None, &[]).await }
+            });
+            let frame: serde_json::Value = serde_json::from_slice(&read_frame(&mut engine).await.unwrap()).unwrap();
+            assert_eq!(frame["id"], 2);
+            write_frame(&mut engine, &reply(1)).await.unwrap();
+            write_frame(&mut engine, &reply(2)).await.unwrap();
+
+            match next.await.unwrap() {
+                Ok(body) => assert_eq!(body, json!({ "id": 2 })),
+                Err(e) => panic!("expected a response, got {:?}", e),
+            }
+            assert!(lock_pending(&client.pending).is_empty());
+            assert!(client.is_alive());
+        });
+    }
+}
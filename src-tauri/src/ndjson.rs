@@ -0,0 +1,131 @@
+//! =============================================================================
+//! Incremental NDJSON Decoding
+//! =============================================================================
+//!
+//! Engine streams (token streams from /input, the /events subscription) are
+//! newline-delimited JSON read off a socket, so a read can end anywhere: in
+//! the middle of a record, or with several records at once. `NdjsonDecoder`
+//! takes the body chunk by chunk and yields every complete line:
+//!
+//!   push(b"{\"token\":\"He")     → []
+//!   push(b"l\"}\n{\"tok")        → [Record({"token": "Hel"})]
+//!   push(b"en\":\"lo\"}\n")      → [Record({"token": "lo"})]
+//!
+//! Lines may also be SSE framed (`data: {…}`; comments and `event:`/`id:`
+//! fields carry no record, `data: [DONE]` becomes `{ "done": true }`).
+//!
+//! Bad input never fails the stream: a line that isn't a JSON record comes
+//! out as `Malformed`, and a line longer than the decoder's limit as
+//! `Oversized` once its end arrives, without ever being buffered in full.
+//! Readers skip both and report them with `report_skipped`.
+
+use tauri::AppHandle;
+
+use crate::events::{self, StreamRecordSkipped};
+
+/// Default longest accepted line
+pub(crate) const MAX_LINE_BYTES: usize = 1024 * 1024;
+
+/// One decoded line.
+#[derive(Debug)]
+pub(crate) enum Decoded {
+    Record(serde_json::Value),
+    /// Not a JSON record; the reason
+    Malformed(String),
+    /// Longer than the limit; its length in bytes
+    Oversized(usize),
+}
+
+/// Splits a byte stream into NDJSON (or SSE) records.
+pub(crate) struct NdjsonDecoder {
+    /// Start of the current line
+    pending: Vec<u8>,
+    max_line_bytes: usize,
+    /// Bytes dropped so far of an oversized line whose end hasn't arrived
+    discarding: Option<usize>,
+}
+
+impl Default for NdjsonDecoder {
+    fn default() -> Self {
+        NdjsonDecoder::with_max_line_bytes(MAX_LINE_BYTES)
+    }
+}
+
+impl NdjsonDecoder {
+    pub(crate) fn with_max_line_bytes(max_line_bytes: usize) -> NdjsonDecoder {
+        NdjsonDecoder { pending: Vec::new(), max_line_bytes, discarding: None }
+    }
+
+    /// Feed the next chunk; returns the lines it completed.
+    pub(crate) fn push(&mut self, mut chunk: &[u8]) -> Vec<Decoded> {
+        let mut decoded = Vec::new();
+        while let Some(newline) = chunk.iter().position(|b| *b == b'\n') {
+            let line = &chunk[..newline];
+            chunk = &chunk[newline + 1..];
+            if let Some(dropped) = self.discarding.take() {
+                decoded.push(Decoded::Oversized(dropped + line.len()));
+            } else if self.pending.len() + line.len() > self.max_line_bytes {
+                decoded.push(Decoded::Oversized(self.pending.len() + line.len()));
+                self.pending.clear();
+            } else {
+                self.pending.extend_from_slice(line);
+                let line = std::mem::take(&mut self.pending);
+                decoded.extend(decode_line(&line));
+            }
+        }
+        // Keep the partial trailing line for the next chunk (or drop it if too long)
+        match &mut self.discarding {
+            Some(dropped) => *dropped += chunk.len(),
+            None if self.pending.len() + chunk.len() > self.max_line_bytes => {
+                self.discarding = Some(self.pending.len() + chunk.len());
+                self.pending = Vec::new();
+            }
+            None => self.pending.extend_from_slice(chunk),
+        }
+        decoded
+    }
+
+    /// End of stream: the last line, if it wasn't newline-terminated.
+    pub(crate) fn finish(&mut self) -> Option<Decoded> {
+        if let Some(dropped) = self.discarding.take() {
+            return Some(Decoded::Oversized(dropped));
+        }
+        let line = std::mem::take(&mut self.pending);
+        decode_line(&line)
+    }
+}
+
+/// Decode one line (without its newline); None for lines that carry no record.
+fn decode_line(line: &[u8]) -> Option<Decoded> {
+    let Ok(line) = std::str::from_utf8(line) else {
+        return Some(Decoded::Malformed("record is not valid UTF-8".to_string()));
+    };
+    let line = line.trim();
+    let payload = match line.strip_prefix("data:") {
+        Some(data) => data.trim_start(),
+        None if line.is_empty() || line.starts_with(':') || line.starts_with("event:") || line.starts_with("id:") || line.starts_with("retry:") => return None,
+        None => line,
+    };
+    if payload == "[DONE]" {
+        return Some(Decoded::Record(serde_json::json!({ "done": true })));
+    }
+    Some(match serde_json::from_str(payload) {
+        Ok(record) => Decoded::Record(record),
+        Err(e) => Decoded::Malformed(format!("invalid JSON: {}", e)),
+    })
+}
+
+/// Log a skipped line and emit `stream_record_skipped`.
+pub(crate) fn report_skipped(app: &AppHandle, endpoint: &str, request_id: Option<&str>, skipped: &Decoded) {
+    let reason = match skipped {
+        Decoded::Record(_) => return,
+        Decoded::Malformed(reason) => reason.clone(),
+        Decoded::Oversized(bytes) => format!("{}-byte record exceeds the line limit", bytes),
+    };
+    println!("Skipped a record from {}: {}", endpoint, reason);
+    events::emit(app, StreamRecordSkipped {
+        endpoint: endpoint.to_string(),
+        request_id: request_id.map(str::to_string),
+        reason,
+    });
+}
@@ -0,0 +1,299 @@
+//! =============================================================================
+//! Engine Network Disclosure
+//! =============================================================================
+//!
+//! Tells the user whether the local AI talks to the network, from two sources:
+//!
+//!   declared  at handshake the engine is asked for its network behavior:
+//!
+//!               GET /network  →  { "mode": "offline" | "local" | "online",
+//!                                  "hosts": ["huggingface.co"], "purpose": "model downloads" }
+//!
+//!             an engine without the endpoint counts as undeclared
+//!
+//!   observed  on Linux, the TCP/UDP sockets of the engine process and its
+//!             children (the onefile bootloader forks the real interpreter)
+//!             are read from /proc every NETWORK_SAMPLE_INTERVAL_SECS; remote
+//!             endpoints other than loopback are kept. Connections opened and
+//!             closed between two samples are not seen. Other platforms only
+//!             report the declaration (`monitoring: false`).
+//!
+//! The first time the engine is seen talking to an internet address, or to
+//! any address its declaration doesn't allow, `engine_network_activity` is
+//! emitted for the UI to show an indicator. `get_engine_network_activity`
+//! returns the declaration and everything observed for the current engine.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager, State};
+use tauri::async_runtime::Mutex;
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::error::EngineError;
+use crate::events::{self, EngineNetworkActivity};
+use crate::socket_http_get;
+
+/// Minimum time between two scans of the engine's sockets
+const NETWORK_SAMPLE_INTERVAL_SECS: u64 = 5;
+
+// ==================== Types ====================
+
+/// Network behavior an engine declares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkMode {
+    /// No network access at all
+    Offline,
+    /// Loopback and local network only
+    Local,
+    /// May reach the internet
+    Online,
+}
+
+/// Response of GET /network.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct DeclaredNetwork {
+    pub mode: NetworkMode,
+    /// Hosts the engine may contact
+    #[serde(default)]
+    pub hosts: Vec<String>,
+    /// Why the engine needs the network
+    #[serde(default)]
+    pub purpose: Option<String>,
+}
+
+/// A remote endpoint the engine was seen connected to.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ObservedConnection {
+    pub remote: String,
+    /// Outside loopback, private and link-local ranges
+    pub external: bool,
+    /// Not covered by the engine's declared mode
+    pub undeclared: bool,
+    pub first_seen: u64,
+    pub last_seen: u64,
+}
+
+/// Response of `get_engine_network_activity`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct NetworkActivity {
+    /// The engine's declaration (None if it made none)
+    pub declared: Option<DeclaredNetwork>,
+    /// Whether connections are observed on this platform
+    pub monitoring: bool,
+    /// Any observed connection reached the internet
+    pub external: bool,
+    /// Observed connections, ordered by remote address
+    pub connections: Vec<ObservedConnection>,
+}
+
+/// Declaration and observations for the current engine.
+#[derive(Default)]
+pub struct NetworkActivityState {
+    declared: Option<DeclaredNetwork>,
+    pid: Option<u32>,
+    last_sample: Option<Instant>,
+    connections: BTreeMap<String, ObservedConnection>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Whether `ip` is outside loopback, private, link-local and unspecified ranges.
+fn is_external(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => !(ip.is_loopback() || ip.is_private() || ip.is_link_local() || ip.is_unspecified()),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_external(IpAddr::V4(ip)),
+            // fc00::/7 is unique-local, fe80::/10 link-local
+            None => !(ip.is_loopback()
+                || ip.is_unspecified()
+                || (ip.segments()[0] & 0xfe00) == 0xfc00
+                || (ip.segments()[0] & 0xffc0) == 0xfe80),
+        },
+    }
+}
+
+/// Whether a connection to a (non-loopback) address is outside the declaration.
+fn is_undeclared(declared: Option<&DeclaredNetwork>, external: bool) -> bool {
+    match declared.map(|d| d.mode) {
+        Some(NetworkMode::Online) => false,
+        Some(NetworkMode::Local) | None => external,
+        Some(NetworkMode::Offline) => true,
+    }
+}
+
+// ==================== Handshake ====================
+
+/// Start tracking a freshly spawned engine process.
+pub(crate) async fn track_process(app: &AppHandle, pid: u32) {
+    let state = app.state::<Mutex<NetworkActivityState>>();
+    *state.lock().await = NetworkActivityState { pid: Some(pid), ..Default::default() };
+}
+
+/// Ask the engine for its declared network behavior.
+pub(crate) async fn negotiate(app: &AppHandle, socket_path: &str) {
+    let declared = socket_http_get(socket_path, "/network")
+        .await
+        .ok()
+        .and_then(|response| serde_json::from_value::<DeclaredNetwork>(response).ok());
+    match &declared {
+        Some(declared) => println!("Engine network access: {:?} {:?}", declared.mode, declared.hosts),
+        None => println!("Engine network access: undeclared"),
+    }
+    app.state::<Mutex<NetworkActivityState>>().lock().await.declared = declared;
+}
+
+// ==================== Monitoring ====================
+
+/// Remote endpoints of the sockets of `root` and its descendants.
+#[cfg(target_os = "linux")]
+fn engine_connections(root: u32) -> Option<Vec<SocketAddr>> {
+    use std::collections::HashSet;
+    use sysinfo::{Pid, ProcessesToUpdate, System};
+
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::All, true);
+    if system.process(Pid::from_u32(root)).is_none() {
+        return Some(Vec::new());
+    }
+    let mut pids = vec![Pid::from_u32(root)];
+    let mut next = 0;
+    while next < pids.len() {
+        let parent = pids[next];
+        pids.extend(system.processes().iter().filter(|(_, p)| p.parent() == Some(parent)).map(|(pid, _)| *pid));
+        next += 1;
+    }
+
+    // Socket inodes held by the engine's processes
+    let mut inodes = HashSet::new();
+    for pid in &pids {
+        let Ok(entries) = std::fs::read_dir(format!("/proc/{}/fd", pid)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(target) = std::fs::read_link(entry.path()) else {
+                continue;
+            };
+            if let Some(inode) = target.to_str().and_then(|t| t.strip_prefix("socket:[")).and_then(|t| t.strip_suffix(']')) {
+                inodes.insert(inode.to_string());
+            }
+        }
+    }
+
+    // Match them against the socket tables of the engine's network namespace
+    let mut remotes = Vec::new();
+    for table in ["tcp", "tcp6", "udp", "udp6"] {
+        let Ok(contents) = std::fs::read_to_string(format!("/proc/{}/net/{}", root, table)) else {
+            continue;
+        };
+        for line in contents.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || !inodes.contains(fields[9]) {
+                continue;
+            }
+            if let Some(remote) = parse_proc_addr(fields[2]).filter(|addr| addr.port() != 0) {
+                remotes.push(remote);
+            }
+        }
+    }
+    Some(remotes)
+}
+
+/// Connections can't be observed on this platform.
+#[cfg(not(target_os = "linux"))]
+fn engine_connections(_root: u32) -> Option<Vec<SocketAddr>> {
+    None
+}
+
+/// Parse an `ADDR:PORT` column of /proc/net/{tcp,udp}[6].
+///
+/// The address is printed as native-endian 32-bit words of the
+/// network-order bytes; the port is already in host order.
+#[cfg(target_os = "linux")]
+fn parse_proc_addr(field: &str) -> Option<SocketAddr> {
+    let (addr, port) = field.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let mut bytes = Vec::with_capacity(16);
+    for word in addr.as_bytes().chunks(8) {
+        let word = u32::from_str_radix(std::str::from_utf8(word).ok()?, 16).ok()?;
+        bytes.extend_from_slice(&word.to_ne_bytes());
+    }
+    let ip = match bytes.len() {
+        4 => IpAddr::from(<[u8; 4]>::try_from(bytes).ok()?),
+        16 => IpAddr::from(<[u8; 16]>::try_from(bytes).ok()?),
+        _ => return None,
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+/// Scan the engine's connections if the sample interval has passed (called from the status loop).
+pub(crate) async fn sample(app: &AppHandle) {
+    let state = app.state::<Mutex<NetworkActivityState>>();
+    let pid = {
+        let mut state = state.lock().await;
+        let due = state.last_sample.is_none_or(|at| at.elapsed() >= Duration::from_secs(NETWORK_SAMPLE_INTERVAL_SECS));
+        let Some(pid) = state.pid.filter(|_| due) else {
+            return;
+        };
+        state.last_sample = Some(Instant::now());
+        pid
+    };
+
+    let Ok(Some(remotes)) = tokio::task::spawn_blocking(move || engine_connections(pid)).await else {
+        return;
+    };
+
+    let now = unix_now();
+    let mut state = state.lock().await;
+    let declared = state.declared.clone();
+    for remote in remotes.into_iter().filter(|r| !r.ip().is_loopback()) {
+        let key = remote.to_string();
+        if let Some(known) = state.connections.get_mut(&key) {
+            known.last_seen = now;
+            continue;
+        }
+        let external = is_external(remote.ip());
+        let undeclared = is_undeclared(declared.as_ref(), external);
+        println!("Engine connected to {} (external: {}, undeclared: {})", key, external, undeclared);
+        if external || undeclared {
+            events::emit(app, EngineNetworkActivity {
+                remote: key.clone(),
+                external,
+                undeclared,
+                declared_mode: declared.as_ref().map(|d| d.mode),
+            });
+        }
+        state.connections.insert(key.clone(), ObservedConnection {
+            remote: key,
+            external,
+            undeclared,
+            first_seen: now,
+            last_seen: now,
+        });
+    }
+}
+
+// ==================== Tauri Command: get_engine_network_activity ====================
+
+/// Return the engine's declared network behavior and the connections observed so far.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_engine_network_activity(
+    state: State<'_, Mutex<NetworkActivityState>>,
+) -> Result<NetworkActivity, EngineError> {
+    let state = state.lock().await;
+    let connections: Vec<ObservedConnection> = state.connections.values().cloned().collect();
+    Ok(NetworkActivity {
+        declared: state.declared.clone(),
+        monitoring: cfg!(target_os = "linux") && state.pid.is_some(),
+        external: connections.iter().any(|c| c.external),
+        connections,
+    })
+}
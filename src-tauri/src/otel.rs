@@ -0,0 +1,182 @@
+//! =============================================================================
+//! OpenTelemetry Request Tracing
+//! =============================================================================
+//!
+//! With the `otel` cargo feature enabled, sampled user requests are exported
+//! as OTLP/HTTP JSON traces to `settings.telemetry.otlp_endpoint`:
+//!
+//!   send_input_to_python          (root span, request.id = trace id)
+//!   ├─ queue       waiting for the process lock and an in-flight slot
+//!   ├─ transport   engine round trip over the socket
+//!   ├─ engine      engine-side processing, when /input reports `engine_ms`
+//!   └─ parse       response handling (metrics, emitting to the frontend)
+//!
+//! The trace id doubles as the request id: it is sent to the engine as
+//! `trace_id` (and as the X-Trace-Id header, see trace_context) so engine-side
+//! logs can be correlated with the exported trace.
+//!
+//! Without the feature `RequestTrace` is a no-op and nothing is exported.
+
+use tauri::AppHandle;
+use std::time::Instant;
+
+#[cfg(feature = "otel")]
+use tauri::Manager;
+#[cfg(feature = "otel")]
+use tauri::async_runtime::Mutex;
+#[cfg(feature = "otel")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "otel")]
+use crate::settings::SettingsStore;
+
+/// Service name reported in exported resources
+#[cfg(feature = "otel")]
+const SERVICE_NAME: &str = "backend-trial";
+
+// ==================== Request Trace ====================
+
+/// Spans recorded for one request; exported by `finish()` if sampled.
+pub struct RequestTrace {
+    #[cfg(feature = "otel")]
+    inner: Option<TraceData>,
+}
+
+#[cfg(feature = "otel")]
+struct TraceData {
+    endpoint: String,
+    trace_id: String,
+    root_span_id: String,
+    name: &'static str,
+    started: Instant,
+    started_unix_nanos: u128,
+    spans: Vec<(&'static str, Instant, Instant)>,
+}
+
+#[cfg(feature = "otel")]
+impl RequestTrace {
+    /// Start a trace for request `name`, applying the configured sampling rate.
+    pub async fn start(app: &AppHandle, name: &'static str) -> RequestTrace {
+        let store = app.state::<Mutex<SettingsStore>>();
+        let telemetry = store.lock().await.settings.telemetry.clone();
+        let inner = telemetry.otlp_endpoint
+            .filter(|_| fastrand::f64() < telemetry.sample_rate)
+            .map(|endpoint| TraceData {
+                endpoint,
+                trace_id: format!("{:032x}", fastrand::u128(1..)),
+                root_span_id: new_span_id(),
+                name,
+                started: Instant::now(),
+                started_unix_nanos: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_nanos())
+                    .unwrap_or(0),
+                spans: Vec::new(),
+            });
+        RequestTrace { inner }
+    }
+
+    /// Trace id of a sampled request, used as its request id.
+    pub fn trace_id(&self) -> Option<&str> {
+        self.inner.as_ref().map(|t| t.trace_id.as_str())
+    }
+
+    /// Record a finished child span.
+    pub fn span(&mut self, name: &'static str, start: Instant, end: Instant) {
+        if let Some(trace) = self.inner.as_mut() {
+            trace.spans.push((name, start, end));
+        }
+    }
+
+    /// Close the root span and export the trace in the background.
+    pub fn finish(self, error: Option<&str>) {
+        if let Some(trace) = self.inner {
+            let endpoint = trace.endpoint.clone();
+            let body = trace.into_otlp(Instant::now(), error);
+            tauri::async_runtime::spawn(export(endpoint, body));
+        }
+    }
+}
+
+/// No-op tracing when the `otel` feature is disabled.
+#[cfg(not(feature = "otel"))]
+impl RequestTrace {
+    pub async fn start(_app: &AppHandle, _name: &'static str) -> RequestTrace {
+        RequestTrace {}
+    }
+
+    pub fn trace_id(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn span(&mut self, _name: &'static str, _start: Instant, _end: Instant) {}
+
+    pub fn finish(self, _error: Option<&str>) {}
+}
+
+// ==================== OTLP Export ====================
+
+#[cfg(feature = "otel")]
+fn new_span_id() -> String {
+    format!("{:016x}", fastrand::u64(1..))
+}
+
+#[cfg(feature = "otel")]
+impl TraceData {
+    /// Wall-clock nanoseconds for `at`, as the string OTLP/JSON expects.
+    fn unix_nanos(&self, at: Instant) -> String {
+        let offset = at.saturating_duration_since(self.started).as_nanos();
+        (self.started_unix_nanos + offset).to_string()
+    }
+
+    /// Build an OTLP/JSON ExportTraceServiceRequest for this trace.
+    fn into_otlp(self, ended: Instant, error: Option<&str>) -> serde_json::Value {
+        let status = match error {
+            Some(message) => serde_json::json!({ "code": 2, "message": message }),
+            None => serde_json::json!({ "code": 1 }),
+        };
+        let mut spans = vec![serde_json::json!({
+            "traceId": self.trace_id,
+            "spanId": self.root_span_id,
+            "name": self.name,
+            "kind": 1,
+            "startTimeUnixNano": self.unix_nanos(self.started),
+            "endTimeUnixNano": self.unix_nanos(ended),
+            "attributes": [{ "key": "request.id", "value": { "stringValue": self.trace_id } }],
+            "status": status,
+        })];
+        for (name, start, end) in &self.spans {
+            spans.push(serde_json::json!({
+                "traceId": self.trace_id,
+                "spanId": new_span_id(),
+                "parentSpanId": self.root_span_id,
+                "name": name,
+                "kind": 1,
+                "startTimeUnixNano": self.unix_nanos(*start),
+                "endTimeUnixNano": self.unix_nanos(*end),
+            }));
+        }
+
+        serde_json::json!({
+            "resourceSpans": [{
+                "resource": {
+                    "attributes": [{ "key": "service.name", "value": { "stringValue": SERVICE_NAME } }]
+                },
+                "scopeSpans": [{ "scope": { "name": SERVICE_NAME }, "spans": spans }]
+            }]
+        })
+    }
+}
+
+/// POST a trace to the collector's OTLP/HTTP endpoint. Failures are logged only.
+#[cfg(feature = "otel")]
+async fn export(endpoint: String, body: serde_json::Value) {
+    let url = format!("{}/v1/traces", endpoint.trim_end_matches('/'));
+    match reqwest::Client::new().post(&url).json(&body).send().await {
+        Ok(response) if !response.status().is_success() => {
+            println!("OTLP export to {} failed: HTTP {}", url, response.status());
+        }
+        Err(e) => println!("OTLP export to {} failed: {}", url, e),
+        Ok(_) => {}
+    }
+}
@@ -0,0 +1,489 @@
+//! =============================================================================
+//! Performance Explanations
+//! =============================================================================
+//!
+//! `explain_performance()` answers "why is this slow on my machine?" for
+//! support. It compares recent latency (last RECENT_HOURS of metrics
+//! history) with the BASELINE_DAYS baseline and checks the usual suspects:
+//!
+//!   cpu_only        no GPU runtime, or a GPU build installed but not selected
+//!   thermal         CPU throttle events (Linux) or speed limit (macOS),
+//!                   or running hot
+//!   swap            the system is swapping with little memory available
+//!   large_context   the engine reports a large (or nearly full) context
+//!   queue           requests waiting behind each other (engine queue,
+//!                   in-flight requests, queued jobs, open streams)
+//!   power           running on battery or in a low-power mode
+//!
+//! Each factor that applies becomes a finding with a severity, one
+//! human-readable sentence and the data points behind it; findings are
+//! ranked most likely cause first. Checks that aren't supported on the
+//! platform are skipped. The engine-reported fields (`device`,
+//! `context_tokens`, `context_limit`, `queued`) come from the last /status.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri::async_runtime::Mutex;
+use std::sync::atomic::Ordering;
+use std::time::{SystemTime, UNIX_EPOCH};
+use sysinfo::{MemoryRefreshKind, RefreshKind, System};
+
+use crate::engine_variants;
+use crate::error::EngineError;
+use crate::jobs::JobQueueState;
+use crate::metrics_history::{Granularity, MetricsHistory, MetricsRange, MetricsRollup};
+use crate::status_delta::{self, StatusDeltaState};
+use crate::streaming;
+use crate::PythonProcess;
+
+/// Window of "recent" latency
+const RECENT_HOURS: u64 = 24;
+
+/// Window latency is compared against
+const BASELINE_DAYS: u64 = 7;
+
+/// Recent p50 this many times the baseline counts as slower than usual
+const SLOWDOWN_RATIO: f64 = 1.5;
+
+/// CPU speed limited below this fraction of its maximum counts as throttled (macOS)
+const THROTTLE_SPEED_LIMIT: f64 = 0.6;
+
+/// Window the thermal throttle counters are compared over (Linux)
+const THROTTLE_SAMPLE_MS: u64 = 1_000;
+
+/// Temperature (°C) considered hot
+const HOT_CPU_CELSIUS: f64 = 90.0;
+
+/// Context size (tokens) considered large
+const LARGE_CONTEXT_TOKENS: u64 = 8_192;
+
+const SECS_PER_HOUR: u64 = 3600;
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+// ==================== Types ====================
+
+/// A known cause of slow responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum PerformanceFactor {
+    /// Recent latency compared with the baseline
+    Latency,
+    CpuOnly,
+    Thermal,
+    Swap,
+    LargeContext,
+    Queue,
+    Power,
+}
+
+/// How much a finding likely contributes, most first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    High,
+    Medium,
+    Low,
+    /// Context only, not a cause
+    Info,
+}
+
+/// One measurement behind a finding.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct DataPoint {
+    pub name: String,
+    pub value: String,
+}
+
+/// One explanation of slowness.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct Finding {
+    pub factor: PerformanceFactor,
+    pub severity: Severity,
+    pub summary: String,
+    pub data: Vec<DataPoint>,
+}
+
+/// Response of `explain_performance`.
+#[derive(Debug, Serialize, Type)]
+pub struct PerformanceReport {
+    /// Unix seconds
+    pub generated_at: u64,
+    /// Ranked, most likely cause first
+    pub findings: Vec<Finding>,
+}
+
+/// The /status fields used here; all optional.
+#[derive(Debug, Default, Deserialize)]
+struct ReportedStatus {
+    /// Inference device, e.g. "cpu", "cuda:0", "mps"
+    #[serde(default)]
+    device: Option<String>,
+    #[serde(default)]
+    context_tokens: Option<u64>,
+    #[serde(default)]
+    context_limit: Option<u64>,
+    #[serde(default)]
+    queued: Option<u64>,
+}
+
+fn point(name: &str, value: impl ToString) -> DataPoint {
+    DataPoint { name: name.to_string(), value: value.to_string() }
+}
+
+fn finding(factor: PerformanceFactor, severity: Severity, summary: String, data: Vec<DataPoint>) -> Finding {
+    Finding { factor, severity, summary, data }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// ==================== Latency ====================
+
+/// Request-weighted average p50 and p95 of `rollups`, with the request count.
+fn weighted_latency(rollups: &[&MetricsRollup]) -> Option<(u64, u64, u64)> {
+    let requests: u64 = rollups.iter().map(|r| r.requests).sum();
+    if requests == 0 {
+        return None;
+    }
+    let p50 = rollups.iter().map(|r| r.latency_p50_ms * r.requests).sum::<u64>() / requests;
+    let p95 = rollups.iter().map(|r| r.latency_p95_ms * r.requests).sum::<u64>() / requests;
+    Some((requests, p50, p95))
+}
+
+fn check_latency(history: &MetricsHistory) -> Option<Finding> {
+    let now = unix_now();
+    let range = MetricsRange { from: now.saturating_sub(BASELINE_DAYS * 24 * SECS_PER_HOUR), to: now };
+    let rollups = history.history(&range, Granularity::Hour).ok()?;
+    let recent_from = now.saturating_sub(RECENT_HOURS * SECS_PER_HOUR);
+    let (recent, baseline): (Vec<&MetricsRollup>, Vec<&MetricsRollup>) = rollups.iter().partition(|r| r.bucket >= recent_from);
+    let (requests, p50, p95) = weighted_latency(&recent)?;
+
+    let mut data = vec![
+        point("requests_last_24h", requests),
+        point("p50_ms_last_24h", p50),
+        point("p95_ms_last_24h", p95),
+    ];
+    let Some((_, baseline_p50, _)) = weighted_latency(&baseline) else {
+        return Some(finding(
+            PerformanceFactor::Latency,
+            Severity::Info,
+            format!("Typical response time over the last {} hours is {} ms (no older history to compare with)", RECENT_HOURS, p50),
+            data,
+        ));
+    };
+    data.push(point("p50_ms_baseline", baseline_p50));
+    let ratio = p50 as f64 / baseline_p50.max(1) as f64;
+    let summary = if ratio >= SLOWDOWN_RATIO {
+        format!("Responses over the last {} hours are {:.1}× slower than the {}-day baseline ({} ms vs {} ms)", RECENT_HOURS, ratio, BASELINE_DAYS, p50, baseline_p50)
+    } else {
+        format!("Response times over the last {} hours are in line with the {}-day baseline ({} ms vs {} ms)", RECENT_HOURS, BASELINE_DAYS, p50, baseline_p50)
+    };
+    Some(finding(PerformanceFactor::Latency, Severity::Info, summary, data))
+}
+
+// ==================== Hardware ====================
+
+fn check_cpu_only(app: &AppHandle, reported: &ReportedStatus) -> Option<Finding> {
+    let gpu = engine_variants::detected_gpu();
+    let mut data = vec![point("gpu_runtime", gpu.unwrap_or("none"))];
+    if let Some(device) = &reported.device {
+        data.push(point("engine_device", device));
+        if !device.eq_ignore_ascii_case("cpu") {
+            return None;
+        }
+    }
+
+    let variants = engine_variants::describe(app);
+    let selected_uses_gpu = variants.iter().any(|v| v.selected && v.variant.requires.gpu.is_some());
+    let usable_gpu_variant = variants.iter().find(|v| v.installed && v.supported && v.variant.requires.gpu.is_some());
+    if let Some(selected) = variants.iter().find(|v| v.selected) {
+        data.push(point("engine_variant", &selected.variant.name));
+    }
+    match (gpu, usable_gpu_variant) {
+        (Some(gpu), Some(variant)) if !selected_uses_gpu => Some(finding(
+            PerformanceFactor::CpuOnly,
+            Severity::High,
+            format!("Inference runs on the CPU although this machine supports the {} engine build '{}'; select it with select_engine_variant", gpu, variant.variant.name),
+            data,
+        )),
+        (Some(gpu), _) if reported.device.is_some() => Some(finding(
+            PerformanceFactor::CpuOnly,
+            Severity::High,
+            format!("The engine runs on the CPU although a {} GPU is available", gpu),
+            data,
+        )),
+        (None, _) => Some(finding(
+            PerformanceFactor::CpuOnly,
+            Severity::Medium,
+            "No supported GPU was found, so inference runs on the CPU".to_string(),
+            data,
+        )),
+        _ => None,
+    }
+}
+
+/// How the platform shows that the CPU is being throttled.
+// Each platform reports one of the variants
+#[allow(dead_code)]
+enum Throttling {
+    /// Thermal throttle events counted over THROTTLE_SAMPLE_MS (Linux)
+    Events(u64),
+    /// CPU speed limit as a fraction of the maximum (macOS)
+    SpeedLimit(f64),
+}
+
+impl Throttling {
+    fn is_throttled(&self) -> bool {
+        match self {
+            Throttling::Events(events) => *events > 0,
+            Throttling::SpeedLimit(limit) => *limit < THROTTLE_SPEED_LIMIT,
+        }
+    }
+}
+
+/// Sum of the per-CPU thermal throttle counters (None without the thermal_throttle interface).
+#[cfg(target_os = "linux")]
+fn throttle_count() -> Option<u64> {
+    let mut total = None;
+    for cpu in std::fs::read_dir("/sys/devices/system/cpu").into_iter().flatten().flatten() {
+        let Ok(counters) = std::fs::read_dir(cpu.path().join("thermal_throttle")) else {
+            continue;
+        };
+        for counter in counters.flatten() {
+            if !counter.file_name().to_string_lossy().ends_with("_throttle_count") {
+                continue;
+            }
+            if let Some(count) = std::fs::read_to_string(counter.path()).ok().and_then(|s| s.trim().parse::<u64>().ok()) {
+                *total.get_or_insert(0) += count;
+            }
+        }
+    }
+    total
+}
+
+/// Throttle events over THROTTLE_SAMPLE_MS and the hottest thermal zone (°C).
+///
+/// The counters only grow while the CPU is actually held back for heat, so
+/// unlike the current clock they don't mistake power saving for throttling.
+#[cfg(target_os = "linux")]
+fn thermal_readings() -> (Option<Throttling>, Option<f64>) {
+    let throttling = throttle_count().and_then(|before| {
+        std::thread::sleep(std::time::Duration::from_millis(THROTTLE_SAMPLE_MS));
+        Some(Throttling::Events(throttle_count()?.saturating_sub(before)))
+    });
+    let hottest = std::fs::read_dir("/sys/class/thermal")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|zone| zone.file_name().to_string_lossy().starts_with("thermal_zone"))
+        .filter_map(|zone| std::fs::read_to_string(zone.path().join("temp")).ok()?.trim().parse::<f64>().ok())
+        .map(|millidegrees| millidegrees / 1000.0)
+        .fold(None, |hottest: Option<f64>, t| Some(hottest.map_or(t, |h| h.max(t))));
+    (throttling, hottest)
+}
+
+/// CPU speed limit reported by `pmset -g therm` (100 = unthrottled).
+#[cfg(target_os = "macos")]
+fn thermal_readings() -> (Option<Throttling>, Option<f64>) {
+    let output = std::process::Command::new("pmset").args(["-g", "therm"]).output().ok();
+    let limit = output.and_then(|output| {
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("CPU_Speed_Limit")?.trim_start_matches([' ', '=']).trim().parse::<f64>().ok())
+    });
+    (limit.map(|limit| Throttling::SpeedLimit(limit / 100.0)), None)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn thermal_readings() -> (Option<Throttling>, Option<f64>) {
+    (None, None)
+}
+
+fn check_thermal() -> Option<Finding> {
+    let (throttling, hottest) = thermal_readings();
+    let throttled = throttling.as_ref().is_some_and(Throttling::is_throttled);
+    let hot = hottest.is_some_and(|t| t >= HOT_CPU_CELSIUS);
+    if !throttled && !hot {
+        return None;
+    }
+    let mut data = Vec::new();
+    match throttling {
+        Some(Throttling::Events(events)) => data.push(point("throttle_events", format!("{} in {} ms", events, THROTTLE_SAMPLE_MS))),
+        Some(Throttling::SpeedLimit(limit)) => data.push(point("cpu_speed_limit_percent", format!("{:.0}", limit * 100.0))),
+        None => {}
+    }
+    if let Some(t) = hottest {
+        data.push(point("hottest_zone_celsius", format!("{:.0}", t)));
+    }
+    let summary = match throttling {
+        Some(Throttling::Events(events)) if throttled => {
+            format!("The CPU was thermally throttled ({} throttle events in {} ms)", events, THROTTLE_SAMPLE_MS)
+        }
+        Some(Throttling::SpeedLimit(limit)) if throttled => {
+            format!("The CPU is limited to {:.0}% of its maximum speed by thermal throttling", limit * 100.0)
+        }
+        _ => format!("The CPU is running hot ({:.0} °C) and may throttle", hottest.unwrap_or_default()),
+    };
+    Some(finding(PerformanceFactor::Thermal, if throttled { Severity::High } else { Severity::Medium }, summary, data))
+}
+
+fn check_swap() -> Option<Finding> {
+    let system = System::new_with_specifics(
+        RefreshKind::nothing().with_memory(MemoryRefreshKind::nothing().with_ram().with_swap()),
+    );
+    let (used_swap, total_swap) = (system.used_swap() / BYTES_PER_MB, system.total_swap() / BYTES_PER_MB);
+    let (available, total) = (system.available_memory() / BYTES_PER_MB, system.total_memory() / BYTES_PER_MB);
+    if total_swap == 0 || used_swap < 256 {
+        return None;
+    }
+    let data = vec![
+        point("swap_used_mb", used_swap),
+        point("swap_total_mb", total_swap),
+        point("memory_available_mb", available),
+        point("memory_total_mb", total),
+    ];
+    // Swap in use with plenty of free memory is old pages, not pressure
+    let pressure = available * 10 < total;
+    let severity = if pressure { Severity::High } else { Severity::Low };
+    let summary = if pressure {
+        format!("The system is swapping ({} MB of swap used, {} MB of memory available); model weights paged to disk slow inference down sharply", used_swap, available)
+    } else {
+        format!("{} MB of swap is in use, but memory isn't currently under pressure", used_swap)
+    };
+    Some(finding(PerformanceFactor::Swap, severity, summary, data))
+}
+
+// ==================== Power ====================
+
+/// Whether the machine runs on battery, and whether a low-power mode is on.
+#[cfg(target_os = "linux")]
+fn power_state() -> (Option<bool>, Option<bool>) {
+    let read = |path: std::path::PathBuf| std::fs::read_to_string(path).ok().map(|s| s.trim().to_string());
+    let supplies: Vec<std::path::PathBuf> = std::fs::read_dir("/sys/class/power_supply")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|supply| supply.path())
+        .collect();
+    let batteries: Vec<&std::path::PathBuf> = supplies.iter().filter(|p| read(p.join("type")).as_deref() == Some("Battery")).collect();
+    let on_battery = (!batteries.is_empty()).then(|| {
+        let mains_online = supplies.iter()
+            .filter(|p| read(p.join("type")).as_deref() == Some("Mains"))
+            .any(|p| read(p.join("online")).as_deref() == Some("1"));
+        !mains_online && batteries.iter().any(|p| read(p.join("status")).as_deref() == Some("Discharging"))
+    });
+    let low_power = read("/sys/firmware/acpi/platform_profile".into()).map(|profile| profile == "low-power" || profile == "quiet");
+    (on_battery, low_power)
+}
+
+#[cfg(target_os = "macos")]
+fn power_state() -> (Option<bool>, Option<bool>) {
+    let pmset = |args: &[&str]| {
+        std::process::Command::new("pmset").args(args).output().ok()
+            .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+    let on_battery = pmset(&["-g", "batt"]).map(|out| out.contains("'Battery Power'"));
+    let low_power = pmset(&["-g"]).map(|out| {
+        out.lines().any(|line| {
+            let mut fields = line.split_whitespace();
+            fields.next() == Some("lowpowermode") && fields.next() == Some("1")
+        })
+    });
+    (on_battery, low_power)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn power_state() -> (Option<bool>, Option<bool>) {
+    (None, None)
+}
+
+fn check_power() -> Option<Finding> {
+    let (on_battery, low_power) = power_state();
+    let on_battery = on_battery.unwrap_or(false);
+    let low_power = low_power.unwrap_or(false);
+    if !on_battery && !low_power {
+        return None;
+    }
+    let data = vec![point("on_battery", on_battery), point("low_power_mode", low_power)];
+    let summary = match (on_battery, low_power) {
+        (true, true) => "Running on battery in low-power mode, which limits CPU and GPU performance".to_string(),
+        (true, false) => "Running on battery; the system may limit CPU and GPU performance to save power".to_string(),
+        _ => "A low-power mode is active, which limits CPU and GPU performance".to_string(),
+    };
+    Some(finding(PerformanceFactor::Power, if low_power { Severity::High } else { Severity::Medium }, summary, data))
+}
+
+// ==================== Engine Load ====================
+
+fn check_context(reported: &ReportedStatus) -> Option<Finding> {
+    let tokens = reported.context_tokens?;
+    let mut data = vec![point("context_tokens", tokens)];
+    let fill = reported.context_limit.filter(|limit| *limit > 0).map(|limit| {
+        data.push(point("context_limit", limit));
+        tokens as f64 / limit as f64
+    });
+    let nearly_full = fill.is_some_and(|fill| fill >= 0.75);
+    if tokens < LARGE_CONTEXT_TOKENS && !nearly_full {
+        return None;
+    }
+    let summary = match fill {
+        Some(fill) => format!("The conversation context holds {} tokens ({:.0}% of the limit); every response has to process all of it", tokens, fill * 100.0),
+        None => format!("The conversation context holds {} tokens; every response has to process all of it", tokens),
+    };
+    Some(finding(PerformanceFactor::LargeContext, if nearly_full { Severity::High } else { Severity::Medium }, summary, data))
+}
+
+async fn check_queue(app: &AppHandle, reported: &ReportedStatus) -> Option<Finding> {
+    let in_flight = app.state::<Mutex<PythonProcess>>().lock().await.in_flight.load(Ordering::SeqCst);
+    let queued_jobs = app.state::<Mutex<JobQueueState>>().lock().await.queued();
+    let open_streams = streaming::open_streams().len();
+    let engine_queued = reported.queued.unwrap_or(0);
+    // The request being explained is one of the in-flight ones
+    let waiting = engine_queued as usize + queued_jobs + in_flight.saturating_sub(1);
+    if waiting == 0 {
+        return None;
+    }
+    let data = vec![
+        point("engine_queued", engine_queued),
+        point("in_flight_requests", in_flight),
+        point("queued_jobs", queued_jobs),
+        point("open_streams", open_streams),
+    ];
+    let severity = if waiting >= 3 { Severity::High } else { Severity::Medium };
+    Some(finding(
+        PerformanceFactor::Queue,
+        severity,
+        format!("{} other request(s) are competing for the engine, so responses wait their turn", waiting),
+        data,
+    ))
+}
+
+// ==================== Tauri Command: explain_performance ====================
+
+/// Explain why responses may be slow on this machine, most likely cause first.
+#[tauri::command]
+#[specta::specta]
+pub async fn explain_performance(app: AppHandle) -> Result<PerformanceReport, EngineError> {
+    let status = status_delta::latest(&*app.state::<Mutex<StatusDeltaState>>().lock().await).status;
+    let reported: ReportedStatus = serde_json::from_value(status).unwrap_or_default();
+
+    let mut findings = Vec::new();
+    findings.extend(check_latency(&*app.state::<Mutex<MetricsHistory>>().lock().await));
+    findings.extend(check_queue(&app, &reported).await);
+    findings.extend(check_context(&reported));
+    let app_clone = app.clone();
+    let hardware = tauri::async_runtime::spawn_blocking(move || {
+        [check_cpu_only(&app_clone, &reported), check_thermal(), check_swap(), check_power()]
+    });
+    findings.extend(hardware.await.map_err(|e| format!("Performance checks failed: {}", e))?.into_iter().flatten());
+
+    // Stable sort: within a severity, the order above (cheapest fix first) is kept
+    findings.sort_by_key(|f| f.severity);
+    println!("Performance explanation: {} finding(s)", findings.len());
+    Ok(PerformanceReport { generated_at: unix_now(), findings })
+}
@@ -0,0 +1,593 @@
+//! =============================================================================
+//! Named Pipelines
+//! =============================================================================
+//!
+//! Users repeat multi-step flows (transcribe → summarize → translate). A
+//! pipeline names such a flow once; `run_pipeline(name, input)` runs its
+//! steps in order against the engine:
+//!
+//!   { "name": "meeting-notes",
+//!     "steps": [
+//!       { "name": "transcribe", "endpoint": "/transcribe",
+//!         "body": { "audio": "{{input}}" }, "output": "text" },
+//!       { "name": "summarize", "endpoint": "/summarize", "retries": 3 },
+//!       { "name": "translate", "endpoint": "/translate",
+//!         "body": { "text": "{{summarize.summary}}", "lang": "de" } } ] }
+//!
+//! Each step POSTs its `body` to `endpoint`. Placeholders in the body's
+//! strings map earlier results in: `{{input}}` (the pipeline input),
+//! `{{prev}}` (the previous step's output) and `{{<step>}}`, each optionally
+//! followed by a dotted path (`{{transcribe.segments.0.text}}`). A string
+//! that is a single placeholder becomes the referenced JSON value; anywhere
+//! else it is spliced in as text. Without a body a step sends
+//! `{ "input": {{prev}} }`. A step's output is its response, or the field at
+//! its `output` path; the last step's output is the run's.
+//!
+//! Steps go through the transport middleware and must be allowed by
+//! `settings.proxy.allowed_endpoints` like `call_engine` requests. Each
+//! attempt first checks the `pipelines` and `proxy` kill switches, so
+//! disabling either fails a run in progress at its next step. An
+//! attempt the engine never received (nothing listening yet, pipe busy) is
+//! retried up to the step's `retries` times with growing delays. Steps are
+//! POSTs the engine may already have acted on, so timeouts, dropped
+//! connections and HTTP errors fail the step instead; resume the run to try
+//! it again.
+//!
+//! Pipelines are stored in pipelines.json. Runs are kept in
+//! pipeline_runs.json (the last MAX_RUNS) and saved after every step, so
+//! the outputs of finished steps survive a failure, a cancellation or an
+//! app restart: `resume_pipeline_run` continues a failed, cancelled or
+//! interrupted run from its first unfinished step. Every change of a run is
+//! emitted as `pipeline_progress`.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager, State};
+use tauri::async_runtime::Mutex;
+use std::collections::{BTreeMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::analytics;
+use crate::atomic_file;
+use crate::error::{EngineError, SocketFailure};
+use crate::events::{self, PipelineProgress};
+use crate::feature_flags;
+use crate::proxy;
+use crate::requests::ActiveRequests;
+use crate::settings::SettingsStore;
+use crate::{auto_start_engine, drain, transport, update_activity_impl, PythonProcess};
+
+// ==================== Configuration Constants ====================
+
+/// Pipeline definitions, in the app data dir
+const PIPELINES_FILE: &str = "pipelines.json";
+
+/// Recent runs, in the app data dir
+const RUNS_FILE: &str = "pipeline_runs.json";
+
+/// Runs kept for status queries and resuming
+const MAX_RUNS: usize = 50;
+
+/// Most steps in a pipeline
+const MAX_STEPS: usize = 20;
+
+/// Retries of a step without `retries`, and the most allowed
+const DEFAULT_RETRIES: u32 = 2;
+const MAX_RETRIES: u32 = 5;
+
+/// Delay before the first retry of a step; doubles with each further one
+const RETRY_DELAY_MS: u64 = 500;
+
+/// `{{reference}}` placeholders in step bodies, compiled once
+static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+
+// ==================== Types ====================
+
+/// One engine call of a pipeline.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct PipelineStep {
+    /// Unique within the pipeline; later steps reference the output by it
+    pub name: String,
+    /// Engine endpoint the body is POSTed to
+    pub endpoint: String,
+    /// Request body with placeholders; None sends `{ "input": {{prev}} }`
+    #[serde(default)]
+    pub body: Option<serde_json::Value>,
+    /// Dotted path of the response field that is the step's output; None keeps the whole response
+    #[serde(default)]
+    pub output: Option<String>,
+    /// Retries after a failed attempt (DEFAULT_RETRIES if not given)
+    #[serde(default)]
+    pub retries: Option<u32>,
+}
+
+/// A named, ordered sequence of engine calls.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct Pipeline {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub steps: Vec<PipelineStep>,
+}
+
+/// State of a run or of one of its steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum RunState {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Progress and result of one step in a run.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct StepRun {
+    pub name: String,
+    pub state: RunState,
+    /// Attempts made in the latest try of the step
+    pub attempts: u32,
+    pub output: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+/// A run of a pipeline, as persisted and emitted as `pipeline_progress`.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct PipelineRun {
+    pub id: String,
+    pub pipeline: String,
+    pub input: serde_json::Value,
+    pub state: RunState,
+    pub steps: Vec<StepRun>,
+    /// Output of the last step once the run completed
+    pub output: Option<serde_json::Value>,
+    pub error: Option<String>,
+    /// Unix time in seconds
+    pub started_at: u64,
+    pub finished_at: Option<u64>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// ==================== Pipeline Store ====================
+
+/// Managed state: the pipeline definitions and recent runs, persisted to disk.
+pub struct PipelineStore {
+    pipelines_file: PathBuf,
+    runs_file: PathBuf,
+    pipelines: BTreeMap<String, Pipeline>,
+    /// Oldest first
+    runs: VecDeque<PipelineRun>,
+}
+
+impl PipelineStore {
+    fn save_pipelines(&self) -> Result<(), String> {
+        atomic_file::write_json(&self.pipelines_file, &self.pipelines)
+    }
+
+    /// Store `run` (replacing its previous version) and persist the runs.
+    fn save_run(&mut self, run: &PipelineRun) {
+        match self.runs.iter_mut().find(|r| r.id == run.id) {
+            Some(stored) => *stored = run.clone(),
+            None => {
+                if self.runs.len() == MAX_RUNS {
+                    self.runs.pop_front();
+                }
+                self.runs.push_back(run.clone());
+            }
+        }
+        if let Err(e) = atomic_file::write_json(&self.runs_file, &self.runs) {
+            println!("Failed to save pipeline runs: {}", e);
+        }
+    }
+
+    fn run(&self, id: &str) -> Option<&PipelineRun> {
+        self.runs.iter().find(|r| r.id == id)
+    }
+}
+
+/// Load pipelines and runs from the app data dir and register the store.
+///
+/// Runs that were still running when the app quit are marked failed, so
+/// they can be resumed.
+pub fn init(app: &AppHandle) {
+    let data_dir = app.path().app_data_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join("ai-engine"));
+    let pipelines_file = data_dir.join(PIPELINES_FILE);
+    let runs_file = data_dir.join(RUNS_FILE);
+    let pipelines = atomic_file::load_json(&pipelines_file).report(app).unwrap_or_default();
+    let mut runs: VecDeque<PipelineRun> = atomic_file::load_json(&runs_file).report(app).unwrap_or_default();
+
+    for run in runs.iter_mut().filter(|r| r.state == RunState::Running) {
+        println!("Pipeline run {} was interrupted", run.id);
+        interrupt(run, "interrupted by an app restart", RunState::Failed);
+    }
+    app.manage(Mutex::new(PipelineStore { pipelines_file, runs_file, pipelines, runs }));
+}
+
+/// End `run` early with `state` (failed or cancelled); a running step fails with `reason`.
+fn interrupt(run: &mut PipelineRun, reason: &str, state: RunState) {
+    for step in run.steps.iter_mut().filter(|s| s.state == RunState::Running) {
+        step.state = RunState::Failed;
+        step.error = Some(reason.to_string());
+    }
+    run.state = state;
+    run.error = Some(reason.to_string());
+    run.finished_at = Some(unix_now());
+}
+
+// ==================== Validation ====================
+
+fn placeholder() -> &'static Regex {
+    PLACEHOLDER.get_or_init(|| Regex::new(r"\{\{\s*([A-Za-z0-9_.-]+)\s*\}\}").expect("valid placeholder pattern"))
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Placeholder references in the strings of `body`.
+fn references(body: &serde_json::Value, found: &mut Vec<String>) {
+    match body {
+        serde_json::Value::String(text) => {
+            found.extend(placeholder().captures_iter(text).map(|c| c[1].to_string()));
+        }
+        serde_json::Value::Array(items) => items.iter().for_each(|item| references(item, found)),
+        serde_json::Value::Object(fields) => fields.values().for_each(|value| references(value, found)),
+        _ => {}
+    }
+}
+
+/// Check names, endpoints and that placeholders only reference earlier steps.
+fn validate(pipeline: &Pipeline) -> Result<(), EngineError> {
+    let invalid = |message: String| Err(EngineError::InvalidRequest(format!("Pipeline {}: {}", pipeline.name, message)));
+    if !valid_name(&pipeline.name) {
+        return invalid("name must be non-empty letters, digits, '-' or '_'".to_string());
+    }
+    if pipeline.steps.is_empty() || pipeline.steps.len() > MAX_STEPS {
+        return invalid(format!("must have 1 to {} steps", MAX_STEPS));
+    }
+
+    let mut earlier: Vec<&str> = Vec::new();
+    for step in &pipeline.steps {
+        if !valid_name(&step.name) || step.name == "input" || step.name == "prev" {
+            return invalid(format!("step name {:?} must be letters, digits, '-' or '_', and not input or prev", step.name));
+        }
+        if earlier.contains(&step.name.as_str()) {
+            return invalid(format!("duplicate step name {}", step.name));
+        }
+        if !step.endpoint.starts_with('/') {
+            return invalid(format!("step {}: endpoint must start with '/'", step.name));
+        }
+        if step.retries.is_some_and(|retries| retries > MAX_RETRIES) {
+            return invalid(format!("step {}: at most {} retries", step.name, MAX_RETRIES));
+        }
+        let mut found = Vec::new();
+        if let Some(body) = &step.body {
+            references(body, &mut found);
+        }
+        for reference in found {
+            let root = reference.split('.').next().unwrap_or_default();
+            if root != "input" && root != "prev" && !earlier.contains(&root) {
+                return invalid(format!("step {} references {{{{{}}}}}, which is not an earlier step", step.name, reference));
+            }
+        }
+        earlier.push(&step.name);
+    }
+    Ok(())
+}
+
+// ==================== Input/Output Mapping ====================
+
+/// The value at dotted `path` in `value` (object keys and array indices).
+fn lookup<'a>(value: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    path.split('.').filter(|segment| !segment.is_empty()).try_fold(value, |value, segment| match value {
+        serde_json::Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+        _ => value.get(segment),
+    })
+}
+
+/// Resolve `reference` against the run so far; `index` is the step being rendered.
+fn resolve(run: &PipelineRun, index: usize, reference: &str) -> Result<serde_json::Value, String> {
+    let (root, path) = reference.split_once('.').unwrap_or((reference, ""));
+    let source = match root {
+        "input" => &run.input,
+        "prev" if index == 0 => &run.input,
+        "prev" => run.steps[index - 1].output.as_ref().ok_or("previous step has no output")?,
+        step => run.steps[..index].iter()
+            .find(|s| s.name == step)
+            .and_then(|s| s.output.as_ref())
+            .ok_or_else(|| format!("step {} has no output", step))?,
+    };
+    lookup(source, path).cloned().ok_or_else(|| format!("{{{{{}}}}} is not in the output", reference))
+}
+
+/// Replace the placeholders in `template` with the referenced values.
+fn render(template: &serde_json::Value, run: &PipelineRun, index: usize) -> Result<serde_json::Value, String> {
+    Ok(match template {
+        serde_json::Value::String(text) => {
+            // A lone placeholder keeps the referenced value's type
+            if let Some(captures) = placeholder().captures(text).filter(|c| c[0].len() == text.len()) {
+                return resolve(run, index, &captures[1]);
+            }
+            let mut rendered = String::new();
+            let mut last = 0;
+            for captures in placeholder().captures_iter(text) {
+                let whole = captures.get(0).expect("match");
+                rendered.push_str(&text[last..whole.start()]);
+                match resolve(run, index, &captures[1])? {
+                    serde_json::Value::String(value) => rendered.push_str(&value),
+                    value => rendered.push_str(&value.to_string()),
+                }
+                last = whole.end();
+            }
+            rendered.push_str(&text[last..]);
+            serde_json::Value::String(rendered)
+        }
+        serde_json::Value::Array(items) => serde_json::Value::Array(
+            items.iter().map(|item| render(item, run, index)).collect::<Result<_, _>>()?,
+        ),
+        serde_json::Value::Object(fields) => serde_json::Value::Object(
+            fields.iter().map(|(key, value)| Ok((key.clone(), render(value, run, index)?))).collect::<Result<_, String>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+// ==================== Execution ====================
+
+/// Whether a failed attempt never reached the engine, so retrying can't run it twice.
+fn is_retryable(error: &EngineError) -> bool {
+    matches!(
+        error,
+        EngineError::SocketUnavailable { reason: SocketFailure::Refused | SocketFailure::NotFound | SocketFailure::PipeBusy, .. }
+    )
+}
+
+/// Store `run` and emit `pipeline_progress`.
+async fn publish(app: &AppHandle, run: &PipelineRun) {
+    app.state::<Mutex<PipelineStore>>().lock().await.save_run(run);
+    events::emit(app, PipelineProgress(run.clone()));
+}
+
+/// Run step `index` of `pipeline`, retrying attempts the engine never received.
+async fn run_step(app: &AppHandle, pipeline: &Pipeline, run: &mut PipelineRun, index: usize) -> Result<serde_json::Value, String> {
+    let step = &pipeline.steps[index];
+    let template = step.body.clone().unwrap_or_else(|| serde_json::json!({ "input": "{{prev}}" }));
+    let body = render(&template, run, index)?;
+    let retries = step.retries.unwrap_or(DEFAULT_RETRIES);
+
+    let mut delay = Duration::from_millis(RETRY_DELAY_MS);
+    loop {
+        // Checked before every attempt, so switching either off stops a run in progress
+        feature_flags::check("pipelines").and_then(|_| feature_flags::check("proxy")).map_err(|e| e.to_string())?;
+        run.steps[index].attempts += 1;
+        let attempt = run.steps[index].attempts;
+        let error = match transport::engine_request(app, "POST", &step.endpoint, Some(&body), None).await {
+            Ok(response) => {
+                return match &step.output {
+                    Some(path) => lookup(&response, path)
+                        .cloned()
+                        .ok_or_else(|| format!("response has no {} field", path)),
+                    None => Ok(response),
+                };
+            }
+            Err(e) => e,
+        };
+        if attempt > retries || !is_retryable(&error) {
+            return Err(error.to_string());
+        }
+        println!("Pipeline {} step {} failed (attempt {}), retrying: {}", pipeline.name, step.name, attempt, error);
+        run.steps[index].error = Some(error.to_string());
+        publish(app, run).await;
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+    }
+}
+
+/// Run the unfinished steps of `run` in order; finished ones keep their outputs.
+async fn execute(app: &AppHandle, pipeline: &Pipeline, mut run: PipelineRun) -> PipelineRun {
+    run.state = RunState::Running;
+    run.error = None;
+    run.finished_at = None;
+    publish(app, &run).await;
+
+    for index in 0..pipeline.steps.len() {
+        if run.steps[index].state == RunState::Completed {
+            continue;
+        }
+        let step = &mut run.steps[index];
+        step.state = RunState::Running;
+        step.attempts = 0;
+        step.error = None;
+        publish(app, &run).await;
+
+        match run_step(app, pipeline, &mut run, index).await {
+            Ok(output) => {
+                let step = &mut run.steps[index];
+                step.state = RunState::Completed;
+                step.output = Some(output);
+                step.error = None;
+                publish(app, &run).await;
+            }
+            Err(e) => {
+                let name = run.steps[index].name.clone();
+                println!("Pipeline run {} failed at step {}: {}", run.id, name, e);
+                run.steps[index].state = RunState::Failed;
+                run.steps[index].error = Some(e.clone());
+                run.state = RunState::Failed;
+                run.error = Some(format!("step {} failed: {}", name, e));
+                run.finished_at = Some(unix_now());
+                publish(app, &run).await;
+                return run;
+            }
+        }
+    }
+
+    run.output = run.steps.last().and_then(|s| s.output.clone());
+    run.state = RunState::Completed;
+    run.finished_at = Some(unix_now());
+    println!("Pipeline run {} completed", run.id);
+    publish(app, &run).await;
+    run
+}
+
+/// Run `run` as a cancellable request (`request_id`), marking it cancelled if aborted.
+///
+/// A stored run left running by a failure before its steps start (e.g. a
+/// resumed one) is marked failed again.
+async fn start(app: &AppHandle, pipeline: Pipeline, run: PipelineRun, request_id: Option<String>) -> Result<PipelineRun, EngineError> {
+    let id = run.id.clone();
+    let result = admit_and_execute(app, pipeline, run, request_id).await;
+    if let Err(e) = &result {
+        let stored = app.state::<Mutex<PipelineStore>>().lock().await.run(&id).cloned();
+        if let Some(mut run) = stored.filter(|r| r.state == RunState::Running) {
+            println!("Pipeline run {} stopped: {}", id, e);
+            let state = if matches!(e, EngineError::Cancelled(_)) { RunState::Cancelled } else { RunState::Failed };
+            interrupt(&mut run, &e.to_string(), state);
+            publish(app, &run).await;
+        }
+    }
+    result
+}
+
+/// The admission and execution behind `start`.
+async fn admit_and_execute(app: &AppHandle, pipeline: Pipeline, run: PipelineRun, request_id: Option<String>) -> Result<PipelineRun, EngineError> {
+    let settings = app.state::<Mutex<SettingsStore>>().lock().await.settings.proxy.clone();
+    for step in &pipeline.steps {
+        proxy::check_endpoint("POST", &step.endpoint, &settings)?;
+    }
+
+    let state = app.state::<Mutex<PythonProcess>>();
+    let proc_state = state.lock().await;
+    update_activity_impl(&proc_state.last_activity).await;
+    let _request = drain::begin_request(&proc_state)?;
+    drop(proc_state);
+    let mut handle = app.state::<ActiveRequests>().register(request_id)?;
+    auto_start_engine(app).await?;
+
+    handle.run(async { Ok(execute(app, &pipeline, run).await) }).await
+}
+
+// ==================== Tauri Commands ====================
+
+/// Create or replace pipeline `pipeline.name`.
+#[tauri::command]
+#[specta::specta]
+pub async fn save_pipeline(pipeline: Pipeline, store: State<'_, Mutex<PipelineStore>>) -> Result<(), EngineError> {
+    validate(&pipeline)?;
+    let mut store = store.lock().await;
+    println!("Saving pipeline {} ({} steps)", pipeline.name, pipeline.steps.len());
+    store.pipelines.insert(pipeline.name.clone(), pipeline);
+    Ok(store.save_pipelines()?)
+}
+
+/// Delete pipeline `name`; returns false if it didn't exist.
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_pipeline(name: String, store: State<'_, Mutex<PipelineStore>>) -> Result<bool, EngineError> {
+    let mut store = store.lock().await;
+    if store.pipelines.remove(&name).is_none() {
+        return Ok(false);
+    }
+    store.save_pipelines()?;
+    Ok(true)
+}
+
+/// Return all pipelines, by name.
+#[tauri::command]
+#[specta::specta]
+pub async fn list_pipelines(store: State<'_, Mutex<PipelineStore>>) -> Result<Vec<Pipeline>, EngineError> {
+    Ok(store.lock().await.pipelines.values().cloned().collect())
+}
+
+/// Run pipeline `name` on `input` and return the finished run.
+///
+/// This command:
+///   1. Checks every step's endpoint against `settings.proxy.allowed_endpoints`
+///   2. Runs the steps in order, emitting `pipeline_progress` on every change
+///   3. Returns the run: completed with its output, or failed with the
+///      outputs of the steps that finished (see `resume_pipeline_run`)
+///
+/// The run can be cancelled with `cancel_request(request_id)`.
+#[tauri::command]
+#[specta::specta]
+pub async fn run_pipeline(
+    app: AppHandle,
+    name: String,
+    input: serde_json::Value,
+    request_id: Option<String>,
+) -> Result<PipelineRun, EngineError> {
+    analytics::count("run_pipeline");
+    let pipeline = app.state::<Mutex<PipelineStore>>().lock().await.pipelines.get(&name).cloned()
+        .ok_or_else(|| EngineError::InvalidRequest(format!("Unknown pipeline {}", name)))?;
+    let run = PipelineRun {
+        id: format!("run-{:016x}", fastrand::u64(..)),
+        pipeline: name,
+        input,
+        state: RunState::Pending,
+        steps: pipeline.steps.iter().map(|step| StepRun {
+            name: step.name.clone(),
+            state: RunState::Pending,
+            attempts: 0,
+            output: None,
+            error: None,
+        }).collect(),
+        output: None,
+        error: None,
+        started_at: unix_now(),
+        finished_at: None,
+    };
+    println!("Running pipeline {} as {}", run.pipeline, run.id);
+    start(&app, pipeline, run, request_id).await
+}
+
+/// Continue failed, cancelled or interrupted run `run_id` from its first unfinished step.
+///
+/// Finished steps keep their outputs. Fails if the pipeline's steps were
+/// renamed, added or removed since the run started. The run is marked
+/// running under the store lock, so concurrent resumes of it can't both
+/// start.
+#[tauri::command]
+#[specta::specta]
+pub async fn resume_pipeline_run(app: AppHandle, run_id: String, request_id: Option<String>) -> Result<PipelineRun, EngineError> {
+    let (pipeline, run) = {
+        let store = app.state::<Mutex<PipelineStore>>();
+        let mut store = store.lock().await;
+        let mut run = store.run(&run_id).cloned()
+            .ok_or_else(|| EngineError::InvalidRequest(format!("Unknown pipeline run {}", run_id)))?;
+        if !matches!(run.state, RunState::Failed | RunState::Cancelled) {
+            return Err(EngineError::InvalidRequest(format!("Pipeline run {} is {:?}, not failed or cancelled", run_id, run.state)));
+        }
+        let pipeline = store.pipelines.get(&run.pipeline).cloned()
+            .ok_or_else(|| EngineError::InvalidRequest(format!("Pipeline {} no longer exists", run.pipeline)))?;
+        let unchanged = pipeline.steps.len() == run.steps.len()
+            && pipeline.steps.iter().zip(&run.steps).all(|(step, run_step)| step.name == run_step.name);
+        if !unchanged {
+            return Err(EngineError::InvalidRequest(format!("Pipeline {} changed since run {} started", run.pipeline, run_id)));
+        }
+        run.state = RunState::Running;
+        run.error = None;
+        run.finished_at = None;
+        store.save_run(&run);
+        (pipeline, run)
+    };
+    println!("Resuming pipeline run {}", run_id);
+    start(&app, pipeline, run, request_id).await
+}
+
+/// Return the recent pipeline runs, newest first.
+#[tauri::command]
+#[specta::specta]
+pub async fn list_pipeline_runs(store: State<'_, Mutex<PipelineStore>>) -> Result<Vec<PipelineRun>, EngineError> {
+    Ok(store.lock().await.runs.iter().rev().cloned().collect())
+}
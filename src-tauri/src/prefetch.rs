@@ -0,0 +1,587 @@
+//! =============================================================================
+//! Default Model Prefetch
+//! =============================================================================
+//!
+//! Downloading the default model on the first prompt makes the user wait for
+//! gigabytes. Instead the UI calls `start_prefetch()` once onboarding is done
+//! (the UI does so once the engine is up, if a model is configured) and
+//! the model configured in `settings.prefetch.model_url` is fetched in the
+//! background into `<app data dir>/models/<file name>`:
+//!
+//!   GET model_url (Range: bytes=<partial size>-)  →  <file>.partial  →  <file>
+//!
+//! The transfer is limited to `max_bytes_per_sec` so it doesn't starve the
+//! user's connection. `pause_prefetch()` stops it and keeps the partial file;
+//! `resume_prefetch()` continues where it stopped (also after a failure).
+//! A partial file the server has nothing to add to (416) counts as complete
+//! only if it matches the size the server reports or `sha256`.
+//! The job is persisted in prefetch.json, so a prefetch that was running when
+//! the app quit resumes on the next launch. Network errors are retried
+//! MAX_ATTEMPTS times with a growing delay before the job is marked failed.
+//! With `sha256` set, the finished file is verified before it is moved into
+//! place; a mismatch discards it. A gated model (see licenses) is only
+//! fetched once its license is accepted. A prefetch and a `download_model` never
+//! write the same file: whichever claims it first makes the other fail.
+//!
+//! Every state change, and the byte count at most every
+//! PROGRESS_INTERVAL_MS, is emitted as `prefetch_progress`.
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use specta::Type;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager, State};
+use tauri::async_runtime::{JoinHandle, Mutex};
+use tokio::io::AsyncWriteExt;
+use std::future::Future;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::atomic_file;
+use crate::error::EngineError;
+use crate::events::{self, PrefetchProgress};
+use crate::feature_flags;
+use crate::licenses;
+use crate::model_downloads;
+use crate::settings::SettingsStore;
+
+// ==================== Configuration Constants ====================
+
+/// File holding the prefetch job inside the app data directory
+const PREFETCH_FILE: &str = "prefetch.json";
+
+/// Directory of downloaded models inside the app data directory
+pub(crate) const MODELS_DIR: &str = "models";
+
+/// Minimum time between progress events
+const PROGRESS_INTERVAL_MS: u64 = 500;
+
+/// How often the byte count of a running prefetch is persisted
+const SAVE_INTERVAL_SECS: u64 = 10;
+
+/// Download attempts before a prefetch is marked failed
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Delay before the first retry; doubles with each further one
+const RETRY_BASE_SECS: u64 = 15;
+
+/// Gives up on a connection that delivers nothing for this long
+const READ_TIMEOUT_SECS: u64 = 60;
+
+// ==================== Settings ====================
+
+/// Default model prefetch, persisted under `settings.prefetch`.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(default)]
+pub struct PrefetchSettings {
+    /// Download URL of the default model; None disables prefetching
+    pub model_url: Option<String>,
+    /// Lowercase hex SHA-256 of the model file, checked after the download
+    pub sha256: Option<String>,
+    /// Bandwidth limit in bytes per second (0 = unlimited)
+    pub max_bytes_per_sec: u64,
+}
+
+impl Default for PrefetchSettings {
+    fn default() -> Self {
+        PrefetchSettings {
+            model_url: None,
+            sha256: None,
+            max_bytes_per_sec: 4 * 1024 * 1024,
+        }
+    }
+}
+
+// ==================== Types ====================
+
+/// Lifecycle state of the prefetch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum PrefetchState {
+    Running,
+    Paused,
+    Completed,
+    Failed,
+}
+
+/// The prefetch job, as persisted in prefetch.json and reported to the UI.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct PrefetchStatus {
+    pub state: PrefetchState,
+    pub url: String,
+    /// Where the model ends up once complete
+    pub path: PathBuf,
+    /// Expected SHA-256, from settings at start
+    pub sha256: Option<String>,
+    pub bytes_done: u64,
+    /// None until the server reports a size
+    pub total_bytes: Option<u64>,
+    pub error: Option<String>,
+    /// Unix seconds
+    pub updated_at: u64,
+}
+
+impl PrefetchStatus {
+    fn partial_path(&self) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(".partial");
+        PathBuf::from(name)
+    }
+}
+
+/// The persisted job plus the task running it.
+pub struct Prefetch {
+    file: PathBuf,
+    models_dir: PathBuf,
+    status: Option<PrefetchStatus>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl Prefetch {
+    /// Persist the job and report it as `prefetch_progress`.
+    fn publish(&mut self, app: &AppHandle) {
+        let Some(status) = self.status.as_mut() else {
+            return;
+        };
+        status.updated_at = unix_now();
+        if let Err(e) = atomic_file::write_json(&self.file, status) {
+            println!("Failed to save prefetch state: {}", e);
+        }
+        events::emit(app, PrefetchProgress(status.clone()));
+    }
+
+    /// Whether the job keeps an unfinished download of `path` (running, paused or failed).
+    pub(crate) fn owns(&self, path: &Path) -> bool {
+        self.status.as_ref().is_some_and(|s| s.path == path && s.state != PrefetchState::Completed)
+    }
+
+    /// Start (or restart) the download task for the current job.
+    fn spawn(&mut self, app: &AppHandle) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+        let app = app.clone();
+        self.task = Some(tauri::async_runtime::spawn(async move { run(&app).await }));
+    }
+}
+
+/// `download_model` is writing `path` (both would write its `.partial` file).
+fn downloaded_elsewhere(path: &Path) -> EngineError {
+    EngineError::InvalidRequest(format!("{:?} is being downloaded by download_model", path))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// File name of the model at `url` (its last path segment).
+pub(crate) fn file_name(url: &str) -> Result<String, EngineError> {
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|e| EngineError::InvalidRequest(format!("Invalid model URL {}: {}", url, e)))?;
+    parsed.path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|name| !name.is_empty() && *name != "." && *name != "..")
+        .map(str::to_string)
+        .ok_or_else(|| EngineError::InvalidRequest(format!("Model URL has no file name: {}", url)))
+}
+
+// ==================== Download ====================
+
+/// Update the running job's byte counts (and persist them now and then).
+async fn report_progress(app: &AppHandle, bytes_done: u64, total_bytes: Option<u64>, persist: bool) {
+    let state = app.state::<Mutex<Prefetch>>();
+    let mut prefetch = state.lock().await;
+    let Some(status) = prefetch.status.as_mut() else {
+        return;
+    };
+    status.bytes_done = bytes_done;
+    status.total_bytes = total_bytes;
+    if persist {
+        prefetch.publish(app);
+    } else {
+        events::emit(app, PrefetchProgress(status.clone()));
+    }
+}
+
+/// Size of the whole file from a 416 response's `Content-Range: bytes */<size>`.
+fn unsatisfied_range_length(response: &reqwest::Response) -> Option<u64> {
+    response.headers()
+        .get(reqwest::header::CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .strip_prefix("bytes */")?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Whether the `offset` bytes of `partial` are the whole file, judged by
+/// the server's `length` or else the expected SHA-256.
+///
+/// Without either the file can't be trusted.
+async fn partial_is_complete(partial: &Path, offset: u64, length: Option<u64>, sha256: Option<&str>) -> Result<bool, String> {
+    match (length, sha256) {
+        (Some(length), _) => Ok(length == offset),
+        (None, Some(expected)) => Ok(file_sha256(partial.to_path_buf()).await?.eq_ignore_ascii_case(expected)),
+        (None, None) => Ok(false),
+    }
+}
+
+/// Download `url` into `partial`, continuing after what is already there.
+///
+/// If the server has nothing past the partial file (416), the file is kept
+/// as complete only if it matches the size the server reports or `sha256`;
+/// otherwise it is discarded and the download starts over.
+///
+/// `on_progress(bytes_done, total_bytes, persist)` is awaited at most every
+/// PROGRESS_INTERVAL_MS and once at the end; `persist` is set every
+/// SAVE_INTERVAL_SECS and on completion.
+pub(crate) async fn download_resumable<F, Fut>(
+    url: &str,
+    partial: &Path,
+    sha256: Option<&str>,
+    max_bytes_per_sec: u64,
+    mut on_progress: F,
+) -> Result<(), String>
+where
+    F: FnMut(u64, Option<u64>, bool) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let mut offset = tokio::fs::metadata(partial).await.map(|m| m.len()).unwrap_or(0);
+    let client = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(READ_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let mut response = loop {
+        let mut request = client.get(url);
+        if offset > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+        }
+        let response = request.send().await.map_err(|e| format!("Request failed: {}", e))?;
+        if offset == 0 || response.status() != reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+            break response;
+        }
+        // Nothing past the partial file: it is complete, or stale (the file changed)
+        let length = unsatisfied_range_length(&response);
+        if partial_is_complete(partial, offset, length, sha256).await? {
+            on_progress(offset, Some(offset), true).await;
+            return Ok(());
+        }
+        println!("Partial download {:?} doesn't match {}, restarting from the beginning", partial, url);
+        tokio::fs::remove_file(partial)
+            .await
+            .map_err(|e| format!("Failed to remove {:?}: {}", partial, e))?;
+        offset = 0;
+    };
+
+    let (mut written, append) = match response.status() {
+        reqwest::StatusCode::PARTIAL_CONTENT => (offset, true),
+        status if status.is_success() => (0, false),
+        status => return Err(format!("Server answered {}", status)),
+    };
+    let total_bytes = response.content_length().map(|len| len + written);
+    if offset > 0 && !append {
+        println!("Server ignored the range request, restarting {} from the beginning", url);
+    }
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(partial)
+        .await
+        .map_err(|e| format!("Failed to open {:?}: {}", partial, e))?;
+
+    let started = Instant::now();
+    let mut session_bytes: u64 = 0;
+    let mut last_progress = Instant::now();
+    let mut last_save = Instant::now();
+    loop {
+        let chunk = tokio::time::timeout(Duration::from_secs(READ_TIMEOUT_SECS), response.chunk())
+            .await
+            .map_err(|_| format!("No data for {} s", READ_TIMEOUT_SECS))?
+            .map_err(|e| format!("Download interrupted: {}", e))?;
+        let Some(chunk) = chunk else {
+            break;
+        };
+        file.write_all(&chunk).await.map_err(|e| format!("Failed to write {:?}: {}", partial, e))?;
+        written += chunk.len() as u64;
+        session_bytes += chunk.len() as u64;
+
+        if last_progress.elapsed() >= Duration::from_millis(PROGRESS_INTERVAL_MS) {
+            last_progress = Instant::now();
+            let persist = last_save.elapsed() >= Duration::from_secs(SAVE_INTERVAL_SECS);
+            if persist {
+                last_save = Instant::now();
+            }
+            on_progress(written, total_bytes, persist).await;
+        }
+
+        // Stay under the bandwidth limit on average
+        if max_bytes_per_sec > 0 {
+            let due = Duration::from_secs_f64(session_bytes as f64 / max_bytes_per_sec as f64);
+            if let Some(ahead) = due.checked_sub(started.elapsed()) {
+                tokio::time::sleep(ahead).await;
+            }
+        }
+    }
+    file.flush().await.map_err(|e| format!("Failed to write {:?}: {}", partial, e))?;
+
+    if let Some(total) = total_bytes.filter(|total| written < *total) {
+        return Err(format!("Connection closed after {} of {} bytes", written, total));
+    }
+    on_progress(written, total_bytes.or(Some(written)), true).await;
+    Ok(())
+}
+
+/// Lowercase hex SHA-256 of the file at `path`.
+pub(crate) async fn file_sha256(path: PathBuf) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut file = std::fs::File::open(&path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0u8; 1024 * 1024];
+        loop {
+            let read = file.read(&mut buffer).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+        }
+        Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+    })
+    .await
+    .map_err(|e| format!("Checksum task failed: {}", e))?
+}
+
+/// Verify the downloaded file and move it into place.
+async fn finish(status: &PrefetchStatus) -> Result<(), String> {
+    let partial = status.partial_path();
+    if let Some(expected) = &status.sha256 {
+        let actual = file_sha256(partial.clone()).await?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(format!("Checksum mismatch: expected {}, got {}", expected, actual));
+        }
+    }
+    tokio::fs::rename(&partial, &status.path)
+        .await
+        .map_err(|e| format!("Failed to move {:?} into place: {}", partial, e))
+}
+
+/// Run the current job until it completes or fails (the task behind `spawn`).
+async fn run(app: &AppHandle) {
+    let state = app.state::<Mutex<Prefetch>>();
+    let Some(status) = state.lock().await.status.clone() else {
+        return;
+    };
+    let max_bytes_per_sec = app.state::<Mutex<SettingsStore>>().lock().await.settings.prefetch.max_bytes_per_sec;
+    println!("Prefetching {} to {:?}", status.url, status.path);
+
+    let mut attempt = 1;
+    let outcome = loop {
+        let progress = |bytes_done, total_bytes, persist| report_progress(app, bytes_done, total_bytes, persist);
+        match download_resumable(&status.url, &status.partial_path(), status.sha256.as_deref(), max_bytes_per_sec, progress).await {
+            Ok(()) => break finish(&status).await,
+            Err(e) if attempt < MAX_ATTEMPTS => {
+                let delay = RETRY_BASE_SECS << (attempt - 1);
+                println!("Prefetch attempt {} failed ({}), retrying in {} s", attempt, e, delay);
+                attempt += 1;
+                tokio::time::sleep(Duration::from_secs(delay)).await;
+            }
+            Err(e) => break Err(e),
+        }
+    };
+
+    let mut prefetch = state.lock().await;
+    let Some(current) = prefetch.status.as_mut() else {
+        return;
+    };
+    match outcome {
+        Ok(()) => {
+            println!("Prefetch of {} complete", current.url);
+            current.state = PrefetchState::Completed;
+            current.total_bytes = Some(current.bytes_done);
+        }
+        Err(e) => {
+            println!("Prefetch of {} failed: {}", current.url, e);
+            current.state = PrefetchState::Failed;
+            current.error = Some(e);
+        }
+    }
+    prefetch.task = None;
+    prefetch.publish(app);
+}
+
+/// Load the persisted job and resume it if it was running when the app quit.
+pub fn init(app: &AppHandle) {
+    let data_dir = app.path().app_data_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join("ai-engine"));
+    let status = atomic_file::load_json::<PrefetchStatus>(&data_dir.join(PREFETCH_FILE)).report(app);
+    // With prefetch disabled the job stays Running, to resume on a later launch
+    let resume = status.as_ref().is_some_and(|s| s.state == PrefetchState::Running)
+        && feature_flags::check("prefetch").is_ok();
+    app.manage(Mutex::new(Prefetch {
+        file: data_dir.join(PREFETCH_FILE),
+        models_dir: data_dir.join(MODELS_DIR),
+        status,
+        task: None,
+    }));
+    if resume {
+        println!("Resuming model prefetch");
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let state = app.state::<Mutex<Prefetch>>();
+            let Some(url) = state.lock().await.status.as_ref().map(|s| s.url.clone()) else {
+                return;
+            };
+            let accepted = match file_name(&url) {
+                Ok(name) => licenses::require_accepted(&app, &[&name]).await,
+                Err(e) => Err(e),
+            };
+            let mut prefetch = state.lock().await;
+            match accepted {
+                Ok(()) => prefetch.spawn(&app),
+                Err(e) => {
+                    println!("Not resuming prefetch of {}: {}", url, e);
+                    if let Some(status) = prefetch.status.as_mut() {
+                        status.state = PrefetchState::Failed;
+                        status.error = Some(e.to_string());
+                    }
+                    prefetch.publish(&app);
+                }
+            }
+        });
+    }
+}
+
+// ==================== Tauri Commands ====================
+
+/// Start fetching the default model in the background (call after onboarding).
+///
+/// This command:
+///   1. Reads the model URL from `settings.prefetch`
+///   2. Fails with `license_required` if the model is gated and its license
+///      wasn't accepted (see licenses)
+///   3. Returns the existing job if it is for the same URL (resuming a paused or failed one)
+///   4. Otherwise discards any other job and starts downloading
+#[tauri::command]
+#[specta::specta]
+pub async fn start_prefetch(app: AppHandle, state: State<'_, Mutex<Prefetch>>) -> Result<PrefetchStatus, EngineError> {
+    let settings = app.state::<Mutex<SettingsStore>>().lock().await.settings.prefetch.clone();
+    let url = settings.model_url
+        .ok_or_else(|| EngineError::InvalidRequest("No default model configured (settings.prefetch.model_url)".to_string()))?;
+    let name = file_name(&url)?;
+    licenses::require_accepted(&app, &[&name]).await?;
+
+    let mut prefetch = state.lock().await;
+    if let Some(existing) = prefetch.status.clone().filter(|s| s.url == url) {
+        match existing.state {
+            PrefetchState::Running => return Ok(existing),
+            PrefetchState::Completed if existing.path.exists() => return Ok(existing),
+            PrefetchState::Paused | PrefetchState::Failed => {
+                drop(prefetch);
+                return resume(&app, &state).await;
+            }
+            // Completed, but the file was deleted since: fetch it again
+            PrefetchState::Completed => {}
+        }
+    }
+
+    if let Some(task) = prefetch.task.take() {
+        task.abort();
+    }
+    if let Some(old) = prefetch.status.take() {
+        let _ = tokio::fs::remove_file(old.partial_path()).await;
+    }
+    let path = prefetch.models_dir.join(name);
+    if model_downloads::is_downloading(&path) {
+        return Err(downloaded_elsewhere(&path));
+    }
+    tokio::fs::create_dir_all(&prefetch.models_dir)
+        .await
+        .map_err(|e| format!("Failed to create {:?}: {}", prefetch.models_dir, e))?;
+    let status = PrefetchStatus {
+        state: PrefetchState::Running,
+        url,
+        path,
+        sha256: settings.sha256,
+        bytes_done: 0,
+        total_bytes: None,
+        error: None,
+        updated_at: unix_now(),
+    };
+    prefetch.status = Some(status.clone());
+    prefetch.publish(&app);
+    prefetch.spawn(&app);
+    Ok(status)
+}
+
+/// Pause the running prefetch, keeping what was downloaded.
+#[tauri::command]
+#[specta::specta]
+pub async fn pause_prefetch(app: AppHandle, state: State<'_, Mutex<Prefetch>>) -> Result<PrefetchStatus, EngineError> {
+    let mut prefetch = state.lock().await;
+    let Some(status) = prefetch.status.clone().filter(|s| s.state == PrefetchState::Running) else {
+        return Err(EngineError::InvalidRequest("No prefetch is running".to_string()));
+    };
+    if let Some(task) = prefetch.task.take() {
+        task.abort();
+    }
+    let bytes_done = tokio::fs::metadata(status.partial_path()).await.map(|m| m.len()).unwrap_or(0);
+    if let Some(status) = prefetch.status.as_mut() {
+        status.state = PrefetchState::Paused;
+        status.bytes_done = bytes_done;
+    }
+    prefetch.publish(&app);
+    println!("Prefetch paused at {} bytes", bytes_done);
+    Ok(prefetch.status.clone().unwrap_or(status))
+}
+
+/// Continue a paused or failed prefetch where it stopped.
+///
+/// Fails with `license_required` if the model is gated and its license
+/// wasn't accepted (see licenses).
+#[tauri::command]
+#[specta::specta]
+pub async fn resume_prefetch(app: AppHandle, state: State<'_, Mutex<Prefetch>>) -> Result<PrefetchStatus, EngineError> {
+    let url = state.lock().await.status.as_ref().map(|s| s.url.clone());
+    if let Some(url) = url {
+        licenses::require_accepted(&app, &[&file_name(&url)?]).await?;
+    }
+    resume(&app, &state).await
+}
+
+/// Restart the job if it is paused or failed; return it.
+async fn resume(app: &AppHandle, state: &Mutex<Prefetch>) -> Result<PrefetchStatus, EngineError> {
+    let mut prefetch = state.lock().await;
+    let Some(status) = prefetch.status.as_mut() else {
+        return Err(EngineError::InvalidRequest("No prefetch was started".to_string()));
+    };
+    let resume = matches!(status.state, PrefetchState::Paused | PrefetchState::Failed);
+    if resume && model_downloads::is_downloading(&status.path) {
+        return Err(downloaded_elsewhere(&status.path));
+    }
+    if resume {
+        status.state = PrefetchState::Running;
+        status.error = None;
+        println!("Resuming prefetch of {}", status.url);
+    }
+    let current = status.clone();
+    if resume {
+        prefetch.publish(app);
+        prefetch.spawn(app);
+    }
+    Ok(current)
+}
+
+/// Return the prefetch job, if one was ever started.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_prefetch_status(state: State<'_, Mutex<Prefetch>>) -> Result<Option<PrefetchStatus>, EngineError> {
+    Ok(state.lock().await.status.clone())
+}
@@ -0,0 +1,207 @@
+//! =============================================================================
+//! Generic Engine Proxy
+//! =============================================================================
+//!
+//! The engine grows endpoints faster than the backend grows commands.
+//! `call_engine(method, endpoint, body)` forwards an arbitrary JSON request
+//! through the transport middleware (timeouts, hooks, mux, recording) and
+//! returns the engine's JSON response, so a new Python endpoint only needs an
+//! allowlist entry instead of a new Rust command.
+//!
+//! Only requests matching `settings.proxy.allowed_endpoints` are forwarded.
+//! Each entry is `"<METHOD> <path>"`; `*` as the method matches any method
+//! and a path ending in `*` matches by prefix:
+//!
+//!   [ "POST /summarize", "GET /models/*", "* /experimental/*" ]
+//!
+//! The list is empty by default. Endpoints the backend manages itself
+//! (RESERVED_ENDPOINTS) and everything below them are never forwarded,
+//! whatever the list says; that includes /input, which only goes through
+//! process_input (input limit, moderation, templates, generation parameters,
+//! history). Paths with percent-escapes or empty segments are refused and
+//! trailing slashes ignored, so `/input/`, `//input` or `/%69nput` can't
+//! slip past that check.
+//!
+//! The same check applies to pipeline steps, downloads (see downloads) and
+//! the broker. Pipeline steps don't go through `forward` and check the
+//! `proxy` kill switch themselves.
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri::async_runtime::Mutex;
+use std::time::Duration;
+
+use crate::error::EngineError;
+use crate::requests::ActiveRequests;
+use crate::settings::SettingsStore;
+use crate::{drain, feature_flags, transport, update_activity_impl, PythonProcess};
+
+/// Methods `call_engine` forwards
+const METHODS: &[&str] = &["GET", "POST", "PUT", "PATCH", "DELETE"];
+
+/// Endpoints only the backend may call (lifecycle, connection control and inputs)
+const RESERVED_ENDPOINTS: &[&str] = &["/stop", "/mux", "/health", "/input"];
+
+// ==================== Settings ====================
+
+/// Endpoints `call_engine` may forward, persisted under `settings.proxy`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(default)]
+pub struct ProxySettings {
+    /// "<METHOD> <path>" entries; "*" matches any method, a trailing "*" any path suffix
+    pub allowed_endpoints: Vec<String>,
+}
+
+impl ProxySettings {
+    /// Whether a `method` request to `path` matches an allowlist entry.
+    fn allows(&self, method: &str, path: &str) -> bool {
+        self.allowed_endpoints.iter().any(|entry| {
+            let Some((allowed_method, pattern)) = entry.trim().split_once(char::is_whitespace) else {
+                return false;
+            };
+            let method_matches = allowed_method == "*" || allowed_method.eq_ignore_ascii_case(method);
+            let pattern = pattern.trim();
+            let path_matches = match pattern.strip_suffix('*') {
+                Some(prefix) => path.starts_with(prefix),
+                None => path == pattern,
+            };
+            method_matches && path_matches
+        })
+    }
+}
+
+/// Whether `path` is a reserved endpoint or below one, compared by segment.
+fn is_reserved(path: &str) -> bool {
+    RESERVED_ENDPOINTS.iter().any(|reserved| {
+        let mut segments = path.split('/');
+        reserved.split('/').all(|expected| segments.next().is_some_and(|segment| segment.eq_ignore_ascii_case(expected)))
+    })
+}
+
+/// Reject endpoints that could escape the allowlist or hit reserved routes.
+pub(crate) fn check_endpoint(method: &str, endpoint: &str, settings: &ProxySettings) -> Result<(), EngineError> {
+    if !METHODS.contains(&method) {
+        return Err(EngineError::InvalidRequest(format!("Unsupported method {} (expected one of {})", method, METHODS.join(", "))));
+    }
+    if !endpoint.starts_with('/') {
+        return Err(EngineError::InvalidRequest(format!("Endpoint must start with '/': {}", endpoint)));
+    }
+    let path = endpoint.split(['?', '#']).next().unwrap_or(endpoint);
+    if path.split('/').any(|segment| segment == "." || segment == "..") || path.contains('\\') {
+        return Err(EngineError::InvalidRequest(format!("Endpoint must not contain '.' or '..' segments or '\\': {}", endpoint)));
+    }
+    // The engine would decode escapes and merge slashes, reaching routes the checks below don't see
+    if path.contains('%') {
+        return Err(EngineError::InvalidRequest(format!("Endpoint must not contain percent-escapes: {}", endpoint)));
+    }
+    let path = match path.trim_end_matches('/') {
+        "" => "/",
+        trimmed => trimmed,
+    };
+    if path.len() > 1 && path[1..].split('/').any(str::is_empty) {
+        return Err(EngineError::InvalidRequest(format!("Endpoint must not contain empty segments: {}", endpoint)));
+    }
+    if is_reserved(path) {
+        return Err(EngineError::InvalidRequest(format!("{} is reserved for the backend", path)));
+    }
+    if !settings.allows(method, path) {
+        return Err(EngineError::InvalidRequest(format!(
+            "{} {} is not in settings.proxy.allowed_endpoints",
+            method, path
+        )));
+    }
+    Ok(())
+}
+
+// ==================== Tauri Command: call_engine ====================
+
+/// Forward a JSON request to an allowlisted engine endpoint and return its JSON response.
+///
+/// This command:
+///   1. Checks `method` and `endpoint` against `settings.proxy.allowed_endpoints`
+///   2. Counts the request as in flight, cancellable with
+///      `cancel_request(request_id)` (generated if not given)
+///   3. Sends it through the transport middleware like the built-in commands
+///
+/// `timeout_ms` overrides the configured timeout for the endpoint's class.
+#[tauri::command]
+#[specta::specta]
+pub async fn call_engine(
+    app: AppHandle,
+    method: String,
+    endpoint: String,
+    body: Option<serde_json::Value>,
+    timeout_ms: Option<u64>,
+    request_id: Option<String>,
+) -> Result<serde_json::Value, EngineError> {
+    forward(&app, &method, &endpoint, body.as_ref(), timeout_ms, request_id).await
+}
+
+/// Check and forward one proxied request, as `call_engine` does (also used by broker).
+pub(crate) async fn forward(
+    app: &AppHandle,
+    method: &str,
+    endpoint: &str,
+    body: Option<&serde_json::Value>,
+    timeout_ms: Option<u64>,
+    request_id: Option<String>,
+) -> Result<serde_json::Value, EngineError> {
+    // Also reached from the broker, which call_engine's command check doesn't cover
+    feature_flags::check("proxy")?;
+    let method = method.to_ascii_uppercase();
+    let settings = app.state::<Mutex<SettingsStore>>().lock().await.settings.proxy.clone();
+    check_endpoint(&method, endpoint, &settings)?;
+
+    let state = app.state::<Mutex<PythonProcess>>();
+    let proc_state = state.lock().await;
+    update_activity_impl(&proc_state.last_activity).await;
+    let _request = drain::begin_request(&proc_state)?;
+    drop(proc_state);
+    let mut handle = app.state::<ActiveRequests>().register(request_id)?;
+
+    let timeout = timeout_ms.map(Duration::from_millis);
+    handle.run(transport::engine_request(app, &method, endpoint, body, timeout))
+        .await
+        .inspect_err(|e| println!("Proxied {} {} failed: {}", method, endpoint, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allow_all() -> ProxySettings {
+        ProxySettings { allowed_endpoints: vec!["* /*".to_string()] }
+    }
+
+    #[test]
+    fn allowlisted_endpoint_is_forwarded() {
+        let settings = ProxySettings { allowed_endpoints: vec!["POST /summarize".to_string(), "GET /models/*".to_string()] };
+        assert!(check_endpoint("POST", "/summarize", &settings).is_ok());
+        assert!(check_endpoint("GET", "/models/llama?full=1", &settings).is_ok());
+        assert!(check_endpoint("GET", "/summarize", &settings).is_err());
+        assert!(check_endpoint("POST", "/other", &settings).is_err());
+    }
+
+    #[test]
+    fn reserved_endpoints_are_refused_even_when_allowed() {
+        for endpoint in ["/input", "/stop", "/mux", "/health", "/input/stream", "/INPUT", "/input?x=1"] {
+            assert!(check_endpoint("POST", endpoint, &allow_all()).is_err(), "{} was forwarded", endpoint);
+        }
+        assert!(check_endpoint("POST", "/inputs", &allow_all()).is_ok());
+    }
+
+    #[test]
+    fn reserved_endpoint_bypass_forms_are_refused() {
+        for endpoint in ["/input/", "//input", "/%69nput", "/stop/", "/stop//", "/./input", "/a/../input", "/a//b", "/input\\"] {
+            assert!(check_endpoint("POST", endpoint, &allow_all()).is_err(), "{} was forwarded", endpoint);
+        }
+    }
+
+    #[test]
+    fn malformed_requests_are_refused() {
+        assert!(check_endpoint("TRACE", "/summarize", &allow_all()).is_err());
+        assert!(check_endpoint("GET", "summarize", &allow_all()).is_err());
+    }
+}
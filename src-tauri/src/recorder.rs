@@ -0,0 +1,187 @@
+//! =============================================================================
+//! Session Recorder
+//! =============================================================================
+//!
+//! Debug aid for sequencing bugs that only show up on a customer's machine.
+//! Between `start_recording` and `stop_recording` everything crossing the
+//! backend's boundaries is appended to a JSONL session file in
+//! <app data dir>/recordings/, one timestamped frame per line:
+//!
+//!   { "at_ms": 0,   "kind": "command", "command": "start_python_script", "args": {} }
+//!   { "at_ms": 812, "kind": "engine_request", "method": "GET", "endpoint": "/status", "body": null }
+//!   { "at_ms": 815, "kind": "engine_response", "method": "GET", "endpoint": "/status",
+//!     "response": { … }, "error": null }
+//!   { "at_ms": 815, "kind": "event", "name": "python_status", "payload": { … } }
+//!   { "at_ms": 990, "kind": "stream_record", "endpoint": "/input", "request_id": "req-2",
+//!     "record": { "token": "Hel" } }
+//!
+//! Arguments of commands carrying secrets (REDACTED_COMMANDS) are not
+//! written. The file can be fed to `replay_recording` (see replay).
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri::ipc::{InvokeBody, InvokeMessage};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::error::EngineError;
+
+/// Subdirectory of the app data dir holding recordings
+const RECORDINGS_DIR: &str = "recordings";
+
+/// Commands whose arguments are never written to a recording
+const REDACTED_COMMANDS: &[&str] = &["set_provider_credentials"];
+
+/// Fast check so frames aren't built while nothing is recording
+static RECORDING_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// The recording in progress, if any
+static RECORDING: Mutex<Option<Recording>> = Mutex::new(None);
+
+// ==================== Frames ====================
+
+/// What happened at one point of a recorded session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Frame {
+    /// A command invoked by the frontend
+    Command { command: String, args: serde_json::Value },
+    /// A request sent to the engine
+    EngineRequest { method: String, endpoint: String, body: Option<serde_json::Value> },
+    /// The engine's answer to a request (or why there was none)
+    EngineResponse {
+        method: String,
+        endpoint: String,
+        response: Option<serde_json::Value>,
+        error: Option<String>,
+    },
+    /// One NDJSON record of a token stream
+    StreamRecord { endpoint: String, request_id: Option<String>, record: serde_json::Value },
+    /// An event emitted to the frontend
+    Event { name: String, payload: serde_json::Value },
+}
+
+/// A frame with its offset from the start of the recording.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    pub at_ms: u64,
+    #[serde(flatten)]
+    pub frame: Frame,
+}
+
+/// Recording in progress.
+struct Recording {
+    path: PathBuf,
+    started: Instant,
+    writer: BufWriter<File>,
+    frames: u64,
+}
+
+/// Returned by `stop_recording`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct RecordingSummary {
+    pub path: String,
+    pub frames: u64,
+    pub duration_ms: u64,
+}
+
+// ==================== Recording ====================
+
+/// Append a frame if a recording is in progress; `frame` is only built then.
+pub(crate) fn record_with(frame: impl FnOnce() -> Frame) {
+    if !RECORDING_ACTIVE.load(Ordering::Relaxed) {
+        return;
+    }
+    let mut recording = RECORDING.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(recording) = recording.as_mut() else {
+        return;
+    };
+    let entry = RecordedFrame {
+        at_ms: recording.started.elapsed().as_millis() as u64,
+        frame: frame(),
+    };
+    let written = serde_json::to_string(&entry)
+        .map_err(|e| e.to_string())
+        .and_then(|line| writeln!(recording.writer, "{}", line).map_err(|e| e.to_string()));
+    match written {
+        Ok(()) => recording.frames += 1,
+        Err(e) => println!("Failed to write recording frame: {}", e),
+    }
+}
+
+/// Record a command invocation (called for every IPC call, see `run()`).
+pub(crate) fn record_command<R: Runtime>(message: &InvokeMessage<R>) {
+    record_with(|| {
+        let command = message.command().to_string();
+        let args = if REDACTED_COMMANDS.contains(&command.as_str()) {
+            serde_json::json!({ "redacted": true })
+        } else {
+            match message.payload() {
+                InvokeBody::Json(args) => args.clone(),
+                InvokeBody::Raw(bytes) => serde_json::json!({ "raw_bytes": bytes.len() }),
+            }
+        };
+        Frame::Command { command, args }
+    });
+}
+
+/// Directory holding the session recordings.
+pub(crate) fn recordings_dir(app: &AppHandle) -> PathBuf {
+    app.path().app_data_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join("ai-engine"))
+        .join(RECORDINGS_DIR)
+}
+
+/// File of the recording in progress, if any.
+pub(crate) fn active_recording() -> Option<PathBuf> {
+    RECORDING.lock().unwrap_or_else(|e| e.into_inner()).as_ref().map(|r| r.path.clone())
+}
+
+// ==================== Tauri Commands ====================
+
+/// Start recording to a new session file; returns its path.
+#[tauri::command]
+#[specta::specta]
+pub async fn start_recording(app: AppHandle) -> Result<String, EngineError> {
+    let mut recording = RECORDING.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(current) = recording.as_ref() {
+        return Err(EngineError::InvalidRequest(format!("Already recording to {:?}", current.path)));
+    }
+
+    let dir = recordings_dir(&app);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| EngineError::Internal(format!("Failed to create {:?}: {}", dir, e)))?;
+    let stamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0);
+    let path = dir.join(format!("session-{}.jsonl", stamp));
+    let file = File::create(&path)
+        .map_err(|e| EngineError::Internal(format!("Failed to create {:?}: {}", path, e)))?;
+
+    println!("Recording session to {:?}", path);
+    let display = path.to_string_lossy().into_owned();
+    *recording = Some(Recording { path, started: Instant::now(), writer: BufWriter::new(file), frames: 0 });
+    RECORDING_ACTIVE.store(true, Ordering::SeqCst);
+    Ok(display)
+}
+
+/// Stop the recording in progress and flush its file.
+#[tauri::command]
+#[specta::specta]
+pub async fn stop_recording() -> Result<RecordingSummary, EngineError> {
+    RECORDING_ACTIVE.store(false, Ordering::SeqCst);
+    let Some(mut recording) = RECORDING.lock().unwrap_or_else(|e| e.into_inner()).take() else {
+        return Err(EngineError::InvalidRequest("No recording in progress".to_string()));
+    };
+    recording.writer.flush()
+        .map_err(|e| EngineError::Internal(format!("Failed to write {:?}: {}", recording.path, e)))?;
+    println!("Recorded {} frame(s) to {:?}", recording.frames, recording.path);
+    Ok(RecordingSummary {
+        path: recording.path.to_string_lossy().into_owned(),
+        frames: recording.frames,
+        duration_ms: recording.started.elapsed().as_millis() as u64,
+    })
+}
@@ -0,0 +1,149 @@
+//! =============================================================================
+//! Scheduled Engine Recycling
+//! =============================================================================
+//!
+//! A long-running Python process slowly leaks memory. With a lifetime policy
+//! (`settings.recycle`) the engine is replaced once it reaches either limit:
+//!
+//!   max_lifetime_hours  time since the engine became ready
+//!   max_requests        requests sent to it
+//!
+//! Recycling waits for an idle window (no request in flight and none started
+//! for `idle_window_secs`), then swaps in a fresh engine exactly like
+//! `restart_python_script` (see handoff): the replacement takes over once it
+//! is healthy and the old engine is drained and stopped, so no request fails
+//! and the state never leaves Ready/Busy. Both the decision and its outcome
+//! are noted in the lifecycle history (see engine_state). A failed recycle
+//! keeps the old engine and is retried after RECYCLE_RETRY_SECS.
+//!
+//! Attached external engines and replay mode can't be swapped without a gap
+//! and are never recycled.
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri::async_runtime::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::budget::{CommandClass, CommandTimer};
+use crate::settings::SettingsStore;
+use crate::{dev_engine, handoff, replay, PythonProcess};
+
+/// How often a running engine is checked against the policy
+const RECYCLE_CHECK_INTERVAL_SECS: u64 = 30;
+
+/// Wait after a failed recycle before trying again
+const RECYCLE_RETRY_SECS: u64 = 600;
+
+const SECS_PER_HOUR: u64 = 3600;
+
+/// Requests sent to any engine since startup
+static REQUESTS: AtomicU64 = AtomicU64::new(0);
+
+// ==================== Settings ====================
+
+/// Engine lifetime policy, persisted under `settings.recycle` (0 = no limit).
+#[derive(Debug, Clone, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(default)]
+pub struct RecycleSettings {
+    pub max_lifetime_hours: u64,
+    pub max_requests: u64,
+    /// Quiet time required before a due engine is recycled
+    pub idle_window_secs: u64,
+}
+
+impl Default for RecycleSettings {
+    fn default() -> Self {
+        RecycleSettings {
+            max_lifetime_hours: 0,
+            max_requests: 0,
+            idle_window_secs: 30,
+        }
+    }
+}
+
+impl RecycleSettings {
+    /// Why an engine that ran `uptime` and served `requests` is due, if it is.
+    fn due(&self, uptime: Duration, requests: u64) -> Option<String> {
+        if self.max_lifetime_hours > 0 && uptime >= Duration::from_secs(self.max_lifetime_hours * SECS_PER_HOUR) {
+            return Some(format!("max lifetime of {} h reached", self.max_lifetime_hours));
+        }
+        if self.max_requests > 0 && requests >= self.max_requests {
+            return Some(format!("max of {} requests reached", self.max_requests));
+        }
+        None
+    }
+}
+
+// ==================== Recycling ====================
+
+/// Count a request sent to the engine.
+pub(crate) fn count_request() {
+    REQUESTS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Whether engine `generation` is current and no request was active within `window`.
+async fn idle_for(app: &AppHandle, generation: u64, window: Duration) -> Option<bool> {
+    let state = app.state::<Mutex<PythonProcess>>();
+    let proc_state = state.lock().await;
+    if proc_state.engine_generation.load(Ordering::SeqCst) != generation
+        || !*proc_state.is_running.lock().await
+        || proc_state.draining.load(Ordering::SeqCst)
+    {
+        return None;
+    }
+    let quiet = proc_state.last_activity.lock().await.elapsed() >= window;
+    Some(quiet && proc_state.in_flight.load(Ordering::SeqCst) == 0)
+}
+
+/// Replace the engine, noting the outcome in the lifecycle history.
+async fn recycle(app: &AppHandle, reason: &str) -> bool {
+    let lifecycle = app.state::<Mutex<PythonProcess>>().lock().await.lifecycle.clone();
+    lifecycle.note(&format!("recycling engine: {}", reason));
+    let timer = CommandTimer::start(app, "engine_recycle", CommandClass::Lifecycle);
+    match handoff::swap(app, &timer).await {
+        Ok(()) => {
+            lifecycle.note("recycled engine: replacement took over");
+            true
+        }
+        Err(e) => {
+            lifecycle.note(&format!("recycle failed, keeping the engine: {}", e));
+            false
+        }
+    }
+}
+
+/// Check engine `generation` against the lifetime policy until it is replaced (runs in the background).
+pub(crate) fn watch(app: &AppHandle, generation: u64) {
+    if dev_engine::is_attached() || replay::mock_endpoint().is_some() {
+        return;
+    }
+    let app = app.clone();
+    let started = Instant::now();
+    let requests_at_start = REQUESTS.load(Ordering::Relaxed);
+    tauri::async_runtime::spawn(async move {
+        let mut retry_at: Option<Instant> = None;
+        loop {
+            tokio::time::sleep(Duration::from_secs(RECYCLE_CHECK_INTERVAL_SECS)).await;
+            let policy = app.state::<Mutex<SettingsStore>>().lock().await.settings.recycle.clone();
+            let requests = REQUESTS.load(Ordering::Relaxed) - requests_at_start;
+            let due = policy.due(started.elapsed(), requests);
+            let Some(idle) = idle_for(&app, generation, Duration::from_secs(policy.idle_window_secs)).await else {
+                // Stopped or already replaced
+                return;
+            };
+            let (Some(reason), true) = (due, idle) else {
+                continue;
+            };
+            if retry_at.is_some_and(|at| Instant::now() < at) {
+                continue;
+            }
+            if recycle(&app, &format!("{} ({} requests in {} min)", reason, requests, started.elapsed().as_secs() / 60)).await {
+                return;
+            }
+            retry_at = Some(Instant::now() + Duration::from_secs(RECYCLE_RETRY_SECS));
+        }
+    });
+}
@@ -0,0 +1,310 @@
+//! =============================================================================
+//! Installation Self-Repair
+//! =============================================================================
+//!
+//! A preflight runs at startup and checks the pieces a broken install or an
+//! unclean shutdown can damage:
+//!
+//!   engine_binary   the engine binary (of the selected variant, see
+//!                   engine_variants) exists, isn't empty and is executable
+//!   socket_dir      the socket directory exists and is usable by its owner
+//!                   only (Unix; named pipes have no directory)
+//!   settings        settings.json parses
+//!   <database>      each SQLite store passes PRAGMA quick_check
+//!
+//! Problems are logged and emitted as `installation_problems`.
+//! `check_installation()` runs the same checks on demand and
+//! `repair_installation()` fixes what it can:
+//!
+//!   engine_binary   restores the executable bit only: the binary is the
+//!                   only bundled copy, so there is nothing to re-extract a
+//!                   missing or empty one from (the step fails: reinstall)
+//!   socket_dir      recreated / reset to 0700
+//!   settings        restored from the last good backup (see settings)
+//!   <database>      moved aside as <file>.corrupt and recreated empty
+//!
+//! Each step of the report says whether that piece was healthy, broken,
+//! repaired, or could not be repaired, and why.
+
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri::async_runtime::Mutex;
+use std::path::{Path, PathBuf};
+
+use crate::atomic_file;
+use crate::dev_python;
+use crate::engine_variants;
+use crate::error::EngineError;
+use crate::events::{self, InstallationProblems};
+use crate::metrics_history::{self, MetricsHistory};
+use crate::history_store::{self, SharedHistoryStore, StorageBackend};
+use crate::settings::{Settings, SettingsStore};
+use crate::{get_ai_engine_binary, get_onedir_engine};
+
+/// Files moved aside with this suffix when they are replaced
+const CORRUPT_SUFFIX: &str = "corrupt";
+
+// ==================== Types ====================
+
+/// Outcome of one check or repair step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum StepOutcome {
+    Healthy,
+    /// Found broken (check only)
+    Broken,
+    Repaired,
+    /// Broken and could not be repaired
+    Failed,
+}
+
+/// One check of the installation.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct RepairStep {
+    pub check: String,
+    pub outcome: StepOutcome,
+    pub detail: String,
+}
+
+/// Response of `check_installation` and `repair_installation`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct InstallationReport {
+    /// No step is broken or failed
+    pub healthy: bool,
+    pub steps: Vec<RepairStep>,
+}
+
+fn step(check: &str, outcome: StepOutcome, detail: impl Into<String>) -> RepairStep {
+    RepairStep { check: check.to_string(), outcome, detail: detail.into() }
+}
+
+/// Outcome for a problem, fixed with `fix` when repairing.
+fn resolve(check: &str, problem: String, repair: bool, fix: impl FnOnce() -> Result<String, String>) -> RepairStep {
+    if !repair {
+        return step(check, StepOutcome::Broken, problem);
+    }
+    match fix() {
+        Ok(done) => step(check, StepOutcome::Repaired, format!("{}; {}", problem, done)),
+        Err(e) => step(check, StepOutcome::Failed, format!("{}; {}", problem, e)),
+    }
+}
+
+// ==================== Checks ====================
+
+/// The engine binary the next start would use.
+fn check_engine_binary(app: &AppHandle, repair: bool) -> RepairStep {
+    const CHECK: &str = "engine_binary";
+    if let Some(dev) = dev_python::enabled(app) {
+        return step(CHECK, StepOutcome::Healthy, format!("dev mode, runs {}", dev.describe()));
+    }
+    let direct = engine_variants::selected_binary(app).map(|variant| variant.or_else(|| get_onedir_engine(app)));
+    let path = match direct.and_then(|direct| direct.map(Ok).unwrap_or_else(get_ai_engine_binary)) {
+        Ok(path) => path,
+        Err(e) => return step(CHECK, if repair { StepOutcome::Failed } else { StepOutcome::Broken }, e),
+    };
+    let unrepairable = |problem: String| {
+        step(CHECK, if repair { StepOutcome::Failed } else { StepOutcome::Broken }, format!("{}; no bundled copy to restore it from, reinstall the app", problem))
+    };
+    let metadata = match std::fs::metadata(&path) {
+        Ok(metadata) if metadata.is_file() => metadata,
+        _ => return unrepairable(format!("{:?} is missing", path)),
+    };
+    if metadata.len() == 0 {
+        return unrepairable(format!("{:?} is empty", path));
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = metadata.permissions().mode();
+        if mode & 0o100 == 0 {
+            return resolve(CHECK, format!("{:?} is not executable", path), repair, || {
+                std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode | 0o755))
+                    .map(|_| "made executable".to_string())
+                    .map_err(|e| format!("failed to make it executable: {}", e))
+            });
+        }
+    }
+    step(CHECK, StepOutcome::Healthy, format!("{:?}", path))
+}
+
+/// The directory the engine socket is created in.
+#[cfg(unix)]
+async fn check_socket_dir(app: &AppHandle, repair: bool) -> RepairStep {
+    use std::os::unix::fs::PermissionsExt;
+
+    const CHECK: &str = "socket_dir";
+    let config = app.state::<Mutex<SettingsStore>>().lock().await.settings.socket.clone();
+    let dir = match crate::ipc::socket_dir(app, &config) {
+        Ok(dir) => dir,
+        Err(e) => return step(CHECK, if repair { StepOutcome::Failed } else { StepOutcome::Broken }, e),
+    };
+
+    let mode = match std::fs::metadata(&dir) {
+        Ok(metadata) if metadata.is_dir() => metadata.permissions().mode(),
+        Ok(_) => {
+            return step(CHECK, if repair { StepOutcome::Failed } else { StepOutcome::Broken }, format!("{:?} is not a directory", dir));
+        }
+        Err(_) => {
+            return resolve(CHECK, format!("{:?} is missing", dir), repair, || {
+                std::fs::create_dir_all(&dir)
+                    .and_then(|_| std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700)))
+                    .map(|_| "recreated with mode 0700".to_string())
+                    .map_err(|e| format!("failed to recreate it: {}", e))
+            });
+        }
+    };
+    // Shared sticky directories like /tmp are fine as a parent
+    let owner_lacks_access = mode & 0o700 != 0o700;
+    let writable_by_others = mode & 0o022 != 0 && mode & 0o1000 == 0;
+    if owner_lacks_access || writable_by_others {
+        return resolve(CHECK, format!("{:?} has mode {:o}", dir, mode & 0o7777), repair, || {
+            let fixed = (mode | 0o700) & !0o022 & 0o7777;
+            std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(fixed))
+                .map(|_| format!("mode set to {:o}", fixed))
+                .map_err(|e| format!("failed to change its mode: {}", e))
+        });
+    }
+    step(CHECK, StepOutcome::Healthy, format!("{:?}", dir))
+}
+
+/// Named pipes have no directory to check.
+#[cfg(windows)]
+async fn check_socket_dir(_app: &AppHandle, _repair: bool) -> RepairStep {
+    step("socket_dir", StepOutcome::Healthy, "named pipe, no directory")
+}
+
+/// The settings file.
+async fn check_settings(app: &AppHandle, repair: bool) -> RepairStep {
+    const CHECK: &str = "settings";
+    let store = app.state::<Mutex<SettingsStore>>();
+    let mut store = store.lock().await;
+    let path = store.path().to_path_buf();
+    let problem = match atomic_file::read_checked::<Settings>(&path) {
+        Ok(None) => return step(CHECK, StepOutcome::Healthy, format!("{:?} not created yet, using defaults", path)),
+        Ok(Some(_)) => return step(CHECK, StepOutcome::Healthy, format!("{:?}", path)),
+        Err(reason) => format!("{:?} is corrupted: {}", path, reason),
+    };
+    resolve(CHECK, problem, repair, || store.restore_last_good())
+}
+
+/// Why the SQLite file at `path` is unusable, if it is.
+fn database_problem(path: &Path) -> Option<String> {
+    if !path.exists() {
+        return None;
+    }
+    let verdict = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .and_then(|db| db.query_row("PRAGMA quick_check", [], |row| row.get::<_, String>(0)));
+    match verdict {
+        Ok(verdict) if verdict == "ok" => None,
+        Ok(verdict) => Some(format!("{:?} failed its integrity check: {}", path, verdict)),
+        Err(e) => Some(format!("{:?} can't be read: {}", path, e)),
+    }
+}
+
+/// Move a database and its journal files aside with CORRUPT_SUFFIX.
+fn move_aside(path: &Path) -> Result<PathBuf, String> {
+    let file_name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    for suffix in ["-journal", "-wal", "-shm"] {
+        let companion = path.with_file_name(format!("{}{}", file_name, suffix));
+        if companion.exists() {
+            let _ = std::fs::rename(&companion, path.with_file_name(format!("{}{}.{}", file_name, suffix, CORRUPT_SUFFIX)));
+        }
+    }
+    let target = path.with_file_name(format!("{}.{}", file_name, CORRUPT_SUFFIX));
+    std::fs::rename(path, &target)
+        .map(|_| target)
+        .map_err(|e| format!("failed to move it aside: {}", e))
+}
+
+/// The SQLite stores in the app data directory; broken ones are recreated and reopened.
+async fn check_databases(app: &AppHandle, repair: bool) -> Vec<RepairStep> {
+    let data_dir = app.path().app_data_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join("ai-engine"));
+    let mut steps = Vec::new();
+    for file in [metrics_history::METRICS_DB_FILE, history_store::HISTORY_DB_FILE] {
+        let path = data_dir.join(file);
+        let Some(problem) = database_problem(&path) else {
+            steps.push(step(file, StepOutcome::Healthy, format!("{:?}", path)));
+            continue;
+        };
+        let moved = if repair { Some(move_aside(&path)) } else { None };
+        let result = match moved {
+            None => step(file, StepOutcome::Broken, problem),
+            Some(Err(e)) => step(file, StepOutcome::Failed, format!("{}; {}", problem, e)),
+            Some(Ok(target)) => {
+                // Reopen the store on a fresh file
+                if file == metrics_history::METRICS_DB_FILE {
+                    *app.state::<Mutex<MetricsHistory>>().lock().await = MetricsHistory::open(&data_dir);
+                } else {
+                    // Only reopen the SQLite history if it is the backend in use
+                    let history = app.state::<SharedHistoryStore>();
+                    let mut history = history.lock().await;
+                    if history.backend() == StorageBackend::Sqlite {
+                        *history = history_store::open(StorageBackend::Sqlite, &data_dir);
+                    }
+                }
+                step(file, StepOutcome::Repaired, format!("{}; recreated empty, old file kept as {:?}", problem, target))
+            }
+        };
+        steps.push(result);
+    }
+    steps
+}
+
+// ==================== Runner ====================
+
+/// Run every check, repairing what can be repaired when `repair` is set.
+async fn run(app: &AppHandle, repair: bool) -> InstallationReport {
+    let mut steps = vec![
+        check_engine_binary(app, repair),
+        check_socket_dir(app, repair).await,
+        check_settings(app, repair).await,
+    ];
+    steps.extend(check_databases(app, repair).await);
+
+    for s in steps.iter().filter(|s| s.outcome != StepOutcome::Healthy) {
+        println!("Installation check {}: {:?} - {}", s.check, s.outcome, s.detail);
+    }
+    let healthy = steps.iter().all(|s| matches!(s.outcome, StepOutcome::Healthy | StepOutcome::Repaired));
+    InstallationReport { healthy, steps }
+}
+
+/// Check the installation in the background at startup; emits `installation_problems` if broken.
+pub fn preflight(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let report = run(&app, false).await;
+        if !report.healthy {
+            let steps = report.steps.into_iter().filter(|s| s.outcome != StepOutcome::Healthy).collect();
+            events::emit(&app, InstallationProblems { steps });
+        }
+    });
+}
+
+// ==================== Tauri Commands ====================
+
+/// Check the installation without changing anything.
+#[tauri::command]
+#[specta::specta]
+pub async fn check_installation(app: AppHandle) -> Result<InstallationReport, EngineError> {
+    Ok(run(&app, false).await)
+}
+
+/// Repair what the installation checks find broken and report each step.
+///
+/// This command:
+///   1. Makes a non-executable engine binary executable again (a missing or
+///      empty one fails the step; only a reinstall restores it)
+///   2. Recreates the socket directory with owner-only access (Unix)
+///   3. Restores settings.json from the last good backup
+///   4. Moves corrupt databases aside and recreates them empty
+#[tauri::command]
+#[specta::specta]
+pub async fn repair_installation(app: AppHandle) -> Result<InstallationReport, EngineError> {
+    println!("Repairing installation...");
+    Ok(run(&app, true).await)
+}
@@ -0,0 +1,352 @@
+//! =============================================================================
+//! Session Replay
+//! =============================================================================
+//!
+//! Re-drives the backend from a recording made with `start_recording` (see
+//! recorder), so a customer's sequencing bug can be reproduced locally:
+//!
+//!   1. A mock engine is started on its own endpoint. It answers each
+//!      method + endpoint with the recorded responses in recorded order
+//!      (repeating the last one once they run out), and token streams with
+//!      their recorded records.
+//!   2. `start_engine` attaches to the mock instead of spawning a binary for
+//!      as long as the replay runs.
+//!   3. The recorded commands are invoked again through the webview's IPC at
+//!      their recorded offsets (divided by `speed`), so they take exactly
+//!      the path the frontend's calls took.
+//!
+//! Channels can't be recreated from a recording; channel arguments are
+//! dropped, so a streamed send_input_to_python replays as a plain request.
+//! `replay_finished` is emitted when the replay is over and the mock engine
+//! has been shut down.
+
+use hyper::service::service_fn;
+use hyper::{Body, Request, Response, StatusCode};
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Manager, State};
+use tauri::async_runtime::Mutex;
+use tokio::io::{AsyncRead, AsyncWrite};
+use std::collections::{HashMap, VecDeque};
+use std::convert::Infallible;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::error::EngineError;
+use crate::events::{self, ReplayFinished};
+use crate::recorder::{Frame, RecordedFrame};
+use crate::{runtime_identity, teardown_engine, PythonProcess};
+
+/// Commands that control recording/replay themselves and aren't replayed
+const NOT_REPLAYED: &[&str] = &["start_recording", "stop_recording", "replay_recording"];
+
+/// Prefix of serialized channel arguments in IPC payloads
+const CHANNEL_ARG_PREFIX: &str = "__CHANNEL__:";
+
+/// Time given to requests still in flight after the last recorded frame
+const REPLAY_TAIL_MS: u64 = 2_000;
+
+/// Endpoint of the mock engine while a replay runs
+static REPLAY_ENDPOINT: RwLock<Option<String>> = RwLock::new(None);
+
+/// Endpoint `start_engine` should attach to instead of spawning, during a replay.
+pub(crate) fn mock_endpoint() -> Option<String> {
+    REPLAY_ENDPOINT.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn set_mock_endpoint(endpoint: Option<String>) {
+    *REPLAY_ENDPOINT.write().unwrap_or_else(|e| e.into_inner()) = endpoint;
+}
+
+// ==================== Types ====================
+
+/// Returned by `replay_recording` and emitted as `replay_finished`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ReplaySummary {
+    pub path: String,
+    /// Commands that will be / were invoked again
+    pub commands: usize,
+    /// Recorded engine responses served by the mock engine
+    pub engine_responses: usize,
+    /// Length of the recording in milliseconds
+    pub recorded_ms: u64,
+}
+
+/// A recorded engine answer.
+#[derive(Debug, Clone)]
+enum MockResponse {
+    Json(serde_json::Value),
+    Error(String),
+    /// NDJSON records of a token stream
+    Stream(Vec<serde_json::Value>),
+}
+
+/// Recorded answers per `(method, endpoint)`, served in order.
+struct MockEngine {
+    routes: std::sync::Mutex<HashMap<(String, String), VecDeque<MockResponse>>>,
+}
+
+// ==================== Mock Engine ====================
+
+impl MockEngine {
+    /// Collect the engine answers of a recording.
+    fn from_frames(frames: &[RecordedFrame]) -> MockEngine {
+        let mut routes: HashMap<(String, String), VecDeque<MockResponse>> = HashMap::new();
+        // Stream records are appended to the stream their request started
+        let mut streams: HashMap<Option<String>, (String, usize)> = HashMap::new();
+
+        for entry in frames {
+            match &entry.frame {
+                Frame::EngineRequest { method, endpoint, body: Some(body) }
+                    if body.get("stream").and_then(|s| s.as_bool()).unwrap_or(false) =>
+                {
+                    let queue = routes.entry((method.clone(), endpoint.clone())).or_default();
+                    queue.push_back(MockResponse::Stream(Vec::new()));
+                    let request_id = body.get("request_id").and_then(|v| v.as_str()).map(str::to_string);
+                    streams.insert(request_id, (method.clone(), queue.len() - 1));
+                }
+                Frame::EngineResponse { method, endpoint, response, error } => {
+                    let answer = match (response, error) {
+                        (Some(response), _) => MockResponse::Json(response.clone()),
+                        (None, error) => MockResponse::Error(error.clone().unwrap_or_default()),
+                    };
+                    routes.entry((method.clone(), endpoint.clone())).or_default().push_back(answer);
+                }
+                Frame::StreamRecord { endpoint, request_id, record } => {
+                    let Some((method, index)) = streams.get(request_id) else {
+                        continue;
+                    };
+                    if let Some(MockResponse::Stream(records)) = routes
+                        .get_mut(&(method.clone(), endpoint.clone()))
+                        .and_then(|queue| queue.get_mut(*index))
+                    {
+                        records.push(record.clone());
+                    }
+                }
+                _ => {}
+            }
+        }
+        MockEngine { routes: std::sync::Mutex::new(routes) }
+    }
+
+    fn response_count(&self) -> usize {
+        self.routes.lock().unwrap_or_else(|e| e.into_inner()).values().map(|q| q.len()).sum()
+    }
+
+    /// Next recorded answer for a request; the last one repeats once the queue runs out.
+    fn next(&self, method: &str, endpoint: &str) -> Option<MockResponse> {
+        let mut routes = self.routes.lock().unwrap_or_else(|e| e.into_inner());
+        let queue = routes.get_mut(&(method.to_string(), endpoint.to_string()))?;
+        if queue.len() > 1 {
+            queue.pop_front()
+        } else {
+            queue.front().cloned()
+        }
+    }
+
+    async fn respond(&self, request: Request<Body>) -> Response<Body> {
+        let method = request.method().to_string();
+        let endpoint = request.uri().path_and_query().map(|p| p.to_string()).unwrap_or_default();
+        let body = hyper::body::to_bytes(request.into_body()).await.unwrap_or_default();
+        let streaming = serde_json::from_slice::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|b| b.get("stream").and_then(|s| s.as_bool()))
+            .unwrap_or(false);
+
+        match (self.next(&method, &endpoint), streaming) {
+            (Some(MockResponse::Stream(records)), true) => ndjson(&records),
+            (Some(MockResponse::Stream(records)), false) => {
+                let output: String = records.iter().filter_map(|r| r.get("token").and_then(|t| t.as_str())).collect();
+                json(StatusCode::OK, &serde_json::json!({ "output": output }))
+            }
+            (Some(MockResponse::Json(response)), true) => {
+                let output = response.get("output").and_then(|o| o.as_str()).unwrap_or_default();
+                ndjson(&[serde_json::json!({ "token": output, "done": true })])
+            }
+            (Some(MockResponse::Json(response)), false) => json(StatusCode::OK, &response),
+            (Some(MockResponse::Error(error)), _) => {
+                json(StatusCode::INTERNAL_SERVER_ERROR, &serde_json::json!({ "error": error }))
+            }
+            // Startup and shutdown requests that don't go through the transport layer
+            (None, _) if matches!(endpoint.as_str(), "/health" | "/status" | "/stop") => {
+                json(StatusCode::OK, &serde_json::json!({}))
+            }
+            (None, _) => json(StatusCode::NOT_FOUND, &serde_json::json!({ "error": "not in recording" })),
+        }
+    }
+}
+
+fn json(status: StatusCode, body: &serde_json::Value) -> Response<Body> {
+    let mut response = Response::new(Body::from(body.to_string()));
+    *response.status_mut() = status;
+    response.headers_mut().insert(hyper::header::CONTENT_TYPE, hyper::header::HeaderValue::from_static("application/json"));
+    response
+}
+
+fn ndjson(records: &[serde_json::Value]) -> Response<Body> {
+    let body: String = records.iter().map(|r| format!("{}\n", r)).collect();
+    let mut response = Response::new(Body::from(body));
+    response.headers_mut().insert(hyper::header::CONTENT_TYPE, hyper::header::HeaderValue::from_static("application/x-ndjson"));
+    response
+}
+
+/// Serve HTTP/1.1 on one accepted connection.
+fn serve_connection<S>(stream: S, mock: Arc<MockEngine>)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    tauri::async_runtime::spawn(async move {
+        let service = service_fn(move |request| {
+            let mock = mock.clone();
+            async move { Ok::<_, Infallible>(mock.respond(request).await) }
+        });
+        if let Err(e) = hyper::server::conn::Http::new().serve_connection(stream, service).await {
+            println!("Mock engine connection error: {}", e);
+        }
+    });
+}
+
+/// Mock engine endpoint for this build.
+#[cfg(unix)]
+fn replay_endpoint(app: &AppHandle) -> String {
+    std::env::temp_dir()
+        .join(format!("{}.replay.sock", runtime_identity::namespace(app)))
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Mock engine endpoint for this build.
+#[cfg(windows)]
+fn replay_endpoint(app: &AppHandle) -> String {
+    format!(r"\\.\pipe\{}-replay", runtime_identity::namespace(app))
+}
+
+/// Accept connections on `endpoint` until aborted.
+#[cfg(unix)]
+async fn serve(endpoint: String, mock: Arc<MockEngine>) -> std::io::Result<()> {
+    let _ = std::fs::remove_file(&endpoint);
+    let listener = tokio::net::UnixListener::bind(&endpoint)?;
+    loop {
+        let (stream, _) = listener.accept().await?;
+        serve_connection(stream, mock.clone());
+    }
+}
+
+/// Accept connections on `endpoint` until aborted.
+#[cfg(windows)]
+async fn serve(endpoint: String, mock: Arc<MockEngine>) -> std::io::Result<()> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let mut server = ServerOptions::new().first_pipe_instance(true).create(&endpoint)?;
+    loop {
+        server.connect().await?;
+        let connected = std::mem::replace(&mut server, ServerOptions::new().create(&endpoint)?);
+        serve_connection(connected, mock.clone());
+    }
+}
+
+// ==================== Replay ====================
+
+/// Recorded commands to invoke again: (offset, command, arguments without channels).
+fn replayable_commands(frames: &[RecordedFrame]) -> Vec<(u64, String, serde_json::Value)> {
+    frames
+        .iter()
+        .filter_map(|entry| match &entry.frame {
+            Frame::Command { command, args } if !NOT_REPLAYED.contains(&command.as_str()) => {
+                let mut args = args.clone();
+                if let Some(args) = args.as_object_mut() {
+                    args.retain(|_, value| !value.as_str().is_some_and(|v| v.starts_with(CHANNEL_ARG_PREFIX)));
+                }
+                Some((entry.at_ms, command.clone(), args))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn load_recording(path: &str) -> Result<Vec<RecordedFrame>, EngineError> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| EngineError::InvalidRequest(format!("Failed to read recording {}: {}", path, e)))?;
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| EngineError::InvalidRequest(format!("{}:{}: invalid frame: {}", path, number + 1, e)))
+        })
+        .collect()
+}
+
+// ==================== Tauri Command: replay_recording ====================
+
+/// Replay a recorded session against a mock engine (see module docs).
+///
+/// Returns once the replay has started; `replay_finished` follows at the end.
+/// `speed` scales the recorded timing (2.0 = twice as fast).
+#[tauri::command]
+#[specta::specta]
+pub async fn replay_recording(
+    app: AppHandle,
+    path: String,
+    speed: Option<f64>,
+    state: State<'_, Mutex<PythonProcess>>,
+) -> Result<ReplaySummary, EngineError> {
+    if mock_endpoint().is_some() {
+        return Err(EngineError::InvalidRequest("A replay is already running".to_string()));
+    }
+    let speed = speed.unwrap_or(1.0);
+    if speed.is_nan() || speed <= 0.0 {
+        return Err(EngineError::InvalidRequest("Replay speed must be positive".to_string()));
+    }
+    let Some(webview) = app.webview_windows().into_values().next() else {
+        return Err(EngineError::InvalidRequest("No window to replay commands into".to_string()));
+    };
+
+    let frames = load_recording(&path)?;
+    let commands = replayable_commands(&frames);
+    let mock = Arc::new(MockEngine::from_frames(&frames));
+    let summary = ReplaySummary {
+        path: path.clone(),
+        commands: commands.len(),
+        engine_responses: mock.response_count(),
+        recorded_ms: frames.last().map(|f| f.at_ms).unwrap_or(0),
+    };
+    println!("Replaying {} command(s) from {}", summary.commands, path);
+
+    // The replay starts from a stopped engine, like the recording did
+    teardown_engine(&mut *state.lock().await, true).await;
+    let endpoint = replay_endpoint(&app);
+    let server = tauri::async_runtime::spawn(serve(endpoint.clone(), mock));
+    set_mock_endpoint(Some(endpoint.clone()));
+
+    let finished = summary.clone();
+    tauri::async_runtime::spawn(async move {
+        let started = Instant::now();
+        let scaled = |at_ms: u64| Duration::from_millis((at_ms as f64 / speed) as u64);
+        for (at_ms, command, args) in commands {
+            tokio::time::sleep_until((started + scaled(at_ms)).into()).await;
+            let script = format!(
+                "window.__TAURI_INTERNALS__.invoke({}, {}).catch(() => {{}})",
+                serde_json::Value::from(command.as_str()),
+                args
+            );
+            if let Err(e) = webview.eval(&script) {
+                println!("Failed to replay {}: {}", command, e);
+            }
+        }
+        tokio::time::sleep_until((started + scaled(finished.recorded_ms) + Duration::from_millis(REPLAY_TAIL_MS)).into()).await;
+
+        let state = app.state::<Mutex<PythonProcess>>();
+        teardown_engine(&mut *state.lock().await, false).await;
+        set_mock_endpoint(None);
+        server.abort();
+        if cfg!(unix) {
+            let _ = std::fs::remove_file(&endpoint);
+        }
+        println!("Replay of {} finished", finished.path);
+        events::emit(&app, ReplayFinished(finished));
+    });
+
+    Ok(summary)
+}
@@ -0,0 +1,140 @@
+//! =============================================================================
+//! In-flight Request Cancellation
+//! =============================================================================
+//!
+//! Every `send_input_to_python` call runs under a correlation id: the one the
+//! frontend passed as `request_id`, or a generated `req-<n>`. The id is sent
+//! to the engine in the request body and registered here until the call
+//! returns.
+//!
+//! `cancel_request(request_id)`:
+//!   1. Wakes the waiting command, which drops the request future and with it
+//!      the socket connection; the command fails with `EngineError::Cancelled`
+//!   2. Tells the engine to stop working on it: POST /cancel { "request_id" }
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, State};
+use tokio::sync::oneshot;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+use crate::error::EngineError;
+use crate::transport;
+
+/// Source of generated correlation ids
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+// ==================== Registry ====================
+
+/// A registered request: its cancel signal and when it started.
+struct ActiveEntry {
+    cancel: oneshot::Sender<()>,
+    started: Instant,
+}
+
+/// An in-flight request as reported by `resync`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ActiveRequest {
+    pub request_id: String,
+    pub elapsed_ms: u64,
+}
+
+/// Requests currently waiting on the engine, by correlation id.
+#[derive(Default, Clone)]
+pub struct ActiveRequests(Arc<Mutex<HashMap<String, ActiveEntry>>>);
+
+impl ActiveRequests {
+    /// Register request `id` (a fresh id if None); fails if the id is already in flight.
+    pub(crate) fn register(&self, id: Option<String>) -> Result<RequestHandle, EngineError> {
+        let id = id.unwrap_or_else(|| format!("req-{}", NEXT_REQUEST_ID.fetch_add(1, Ordering::SeqCst)));
+        let (sender, cancelled) = oneshot::channel();
+        let mut active = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if active.contains_key(&id) {
+            return Err(EngineError::InvalidRequest(format!("Request {} is already in flight", id)));
+        }
+        active.insert(id.clone(), ActiveEntry { cancel: sender, started: Instant::now() });
+        Ok(RequestHandle { id, registry: self.clone(), cancelled })
+    }
+
+    /// Signal request `id`; returns false if it isn't in flight.
+    fn cancel(&self, id: &str) -> bool {
+        let entry = self.0.lock().unwrap_or_else(|e| e.into_inner()).remove(id);
+        entry.map(|entry| entry.cancel.send(()).is_ok()).unwrap_or(false)
+    }
+
+    /// The requests in flight, longest-running first.
+    pub(crate) fn snapshot(&self) -> Vec<ActiveRequest> {
+        let active = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let mut requests: Vec<(Instant, ActiveRequest)> = active.iter()
+            .map(|(id, entry)| (entry.started, ActiveRequest {
+                request_id: id.clone(),
+                elapsed_ms: entry.started.elapsed().as_millis() as u64,
+            }))
+            .collect();
+        requests.sort_by_key(|(started, _)| *started);
+        requests.into_iter().map(|(_, request)| request).collect()
+    }
+}
+
+/// Registration of one in-flight request; unregisters on drop.
+pub(crate) struct RequestHandle {
+    id: String,
+    registry: ActiveRequests,
+    cancelled: oneshot::Receiver<()>,
+}
+
+impl RequestHandle {
+    /// The request's correlation id.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Drive `work` until it completes or the request is cancelled.
+    ///
+    /// On cancellation `work` is dropped, closing its engine connection.
+    pub async fn run<T>(&mut self, work: impl Future<Output = Result<T, EngineError>>) -> Result<T, EngineError> {
+        tokio::select! {
+            result = work => result,
+            Ok(()) = &mut self.cancelled => Err(EngineError::Cancelled(self.id.clone())),
+        }
+    }
+}
+
+impl Drop for RequestHandle {
+    fn drop(&mut self) {
+        self.registry.0.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.id);
+    }
+}
+
+/// Abort in-flight request `id` and ask the engine to stop working on it.
+///
+/// Returns false if the request isn't in flight.
+pub(crate) async fn cancel(app: &AppHandle, active: &ActiveRequests, id: &str) -> bool {
+    if !active.cancel(id) {
+        return false;
+    }
+    println!("Cancelled request {}", id);
+
+    // The connection is already gone; the engine may still be computing
+    let body = serde_json::json!({ "request_id": id });
+    if let Err(e) = transport::engine_request(app, "POST", "/cancel", Some(&body), None).await {
+        println!("Failed to notify engine of cancelled request {}: {}", id, e);
+    }
+    true
+}
+
+// ==================== Tauri Command: cancel_request ====================
+
+/// Abort in-flight request `request_id` and ask the engine to stop working on it.
+#[tauri::command]
+#[specta::specta]
+pub async fn cancel_request(app: AppHandle, request_id: String, active: State<'_, ActiveRequests>) -> Result<(), EngineError> {
+    if !cancel(&app, &active, &request_id).await {
+        return Err(EngineError::InvalidRequest(format!("No request {} in flight", request_id)));
+    }
+    Ok(())
+}
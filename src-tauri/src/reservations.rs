@@ -0,0 +1,183 @@
+//! =============================================================================
+//! Capacity Reservations
+//! =============================================================================
+//!
+//! The frontend sometimes knows a burst is coming (re-embedding a workspace,
+//! a bulk import). `reserve_capacity(estimate)` prepares for it before the
+//! first request arrives and returns a reservation id:
+//!
+//!   • Workers     the job queue runs `slots` extra batch workers for the
+//!                 reservation's jobs
+//!   • Queue       `slots` extra /input slots, on top of
+//!                 `settings.engine.max_concurrent_inputs`
+//!   • Connections the engine is started (if `settings.engine.auto_start`)
+//!                 and a dropped multiplexed connection is re-established
+//!
+//! `slots` is the estimate capped at MAX_SLOTS_PER_RESERVATION, and all
+//! reservations together hold at most MAX_RESERVED_SLOTS.
+//!
+//! Requests that reference the reservation (`route.reservation_id`) are
+//! admitted first: inputs take a reserved slot or a regular one, whichever
+//! frees up first, and queued jobs start ahead of unreserved ones, on the
+//! reservation's workers or a regular one. Other requests never use the
+//! reserved slots or workers. An unknown or expired id is
+//! ignored and the request is admitted normally.
+//!
+//! A reservation lasts `ttl_secs` (DEFAULT_TTL_SECS, at most MAX_TTL_SECS)
+//! or until `release_capacity`; running requests keep their slots.
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri::async_runtime::Mutex;
+use tokio::sync::Semaphore;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::EngineError;
+use crate::mux::MuxClient;
+use crate::{auto_start_engine, get_socket_path, jobs, PythonProcess};
+
+// ==================== Configuration Constants ====================
+
+/// Lifetime of a reservation without `ttl_secs`
+const DEFAULT_TTL_SECS: u64 = 300;
+
+/// Longest reservation
+const MAX_TTL_SECS: u64 = 3600;
+
+/// Most extra slots (and workers) one reservation gets
+const MAX_SLOTS_PER_RESERVATION: usize = 4;
+
+/// Most extra slots held by all reservations together
+const MAX_RESERVED_SLOTS: usize = 8;
+
+/// Source of reservation ids
+static NEXT_RESERVATION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Active reservations, by id
+static RESERVATIONS: std::sync::Mutex<BTreeMap<String, Reservation>> = std::sync::Mutex::new(BTreeMap::new());
+
+/// An active reservation.
+struct Reservation {
+    /// The reserved /input slots; closed on release
+    slots: Arc<Semaphore>,
+    size: usize,
+}
+
+// ==================== Types ====================
+
+/// A granted reservation, returned by `reserve_capacity`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct CapacityReservation {
+    /// Pass as `route.reservation_id` with the burst's requests
+    pub id: String,
+    pub estimate: u32,
+    /// Extra /input slots and batch workers reserved
+    pub slots: usize,
+    /// Unix time in seconds
+    pub expires_at: u64,
+}
+
+// ==================== Lookups ====================
+
+/// Reserved /input slots of reservation `id`, if it is still active.
+pub(crate) fn slots(id: &str) -> Option<Arc<Semaphore>> {
+    RESERVATIONS.lock().unwrap_or_else(|e| e.into_inner()).get(id).map(|r| r.slots.clone())
+}
+
+/// Whether reservation `id` is still active.
+pub(crate) fn is_active(id: &str) -> bool {
+    RESERVATIONS.lock().unwrap_or_else(|e| e.into_inner()).contains_key(id)
+}
+
+/// Batch workers of each active reservation, by id (they only run its jobs).
+pub(crate) fn workers() -> BTreeMap<String, usize> {
+    RESERVATIONS.lock().unwrap_or_else(|e| e.into_inner()).iter().map(|(id, r)| (id.clone(), r.size)).collect()
+}
+
+/// End reservation `id`; requests waiting on its slots fall back to the regular ones.
+fn release(id: &str) -> bool {
+    let Some(reservation) = RESERVATIONS.lock().unwrap_or_else(|e| e.into_inner()).remove(id) else {
+        return false;
+    };
+    reservation.slots.close();
+    println!("Capacity reservation {} released ({} slots)", id, reservation.size);
+    true
+}
+
+/// Start the engine if needed and re-establish a dropped multiplexed connection.
+///
+/// Failures are logged only; the burst's requests report their own errors.
+async fn prewarm(app: &AppHandle) {
+    if let Err(e) = auto_start_engine(app).await {
+        println!("Could not pre-warm the engine for a reservation: {}", e);
+        return;
+    }
+    let mux = app.state::<Mutex<PythonProcess>>().lock().await.mux.clone();
+    let dropped = mux.lock().await.as_ref().is_some_and(|client| !client.is_alive());
+    if dropped {
+        println!("Re-establishing the multiplexed connection for a reservation");
+        let client = MuxClient::negotiate(&get_socket_path()).await;
+        *mux.lock().await = client;
+    }
+}
+
+// ==================== Tauri Commands ====================
+
+/// Reserve capacity for a burst of about `estimate` requests.
+///
+/// This command:
+///   1. Reserves up to MAX_SLOTS_PER_RESERVATION extra /input slots and
+///      batch workers for `ttl_secs` (default DEFAULT_TTL_SECS)
+///   2. Starts queued jobs on the added workers
+///   3. Pre-warms the engine connection in the background
+///   4. Schedules the release once the reservation expires
+///
+/// Fails when all MAX_RESERVED_SLOTS are already reserved.
+#[tauri::command]
+#[specta::specta]
+pub async fn reserve_capacity(app: AppHandle, estimate: u32, ttl_secs: Option<u64>) -> Result<CapacityReservation, EngineError> {
+    if estimate == 0 {
+        return Err(EngineError::InvalidRequest("Reservation estimate must be greater than zero".to_string()));
+    }
+    let ttl = Duration::from_secs(ttl_secs.unwrap_or(DEFAULT_TTL_SECS).clamp(1, MAX_TTL_SECS));
+
+    let id = format!("res-{}", NEXT_RESERVATION_ID.fetch_add(1, Ordering::SeqCst));
+    let size = {
+        let mut reservations = RESERVATIONS.lock().unwrap_or_else(|e| e.into_inner());
+        let available = MAX_RESERVED_SLOTS.saturating_sub(reservations.values().map(|r| r.size).sum());
+        let size = (estimate as usize).min(MAX_SLOTS_PER_RESERVATION).min(available);
+        if size == 0 {
+            return Err(EngineError::InvalidRequest(format!(
+                "All {} reservable slots are reserved; release a reservation first",
+                MAX_RESERVED_SLOTS
+            )));
+        }
+        reservations.insert(id.clone(), Reservation { slots: Arc::new(Semaphore::new(size)), size });
+        size
+    };
+    println!("Capacity reserved for ~{} requests: {} ({} slots, {} secs)", estimate, id, size, ttl.as_secs());
+
+    jobs::schedule(app.clone());
+    let prewarm_app = app.clone();
+    tauri::async_runtime::spawn(async move { prewarm(&prewarm_app).await });
+
+    let expiring = id.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(ttl).await;
+        release(&expiring);
+    });
+
+    let expires_at = (SystemTime::now() + ttl).duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    Ok(CapacityReservation { id, estimate, slots: size, expires_at })
+}
+
+/// End reservation `reservation_id` before it expires; returns false if it wasn't active.
+#[tauri::command]
+#[specta::specta]
+pub async fn release_capacity(reservation_id: String) -> Result<bool, EngineError> {
+    Ok(release(&reservation_id))
+}
@@ -0,0 +1,149 @@
+//! =============================================================================
+//! Engine Resource Usage
+//! =============================================================================
+//!
+//! Reports how much CPU and memory the engine uses, mostly the resident
+//! model, so the UI can show it and warn before the system runs out of
+//! memory. The engine process and its children (the onefile bootloader forks
+//! the real interpreter) are measured together:
+//!
+//!   cpu_percent  since the previous sample; 100 = one core fully busy
+//!   rss_bytes    resident memory
+//!   uptime_secs  since the engine process started
+//!
+//! The status loop samples every RESOURCE_SAMPLE_INTERVAL_SECS and emits
+//! `engine_resources`; `low_memory` is set once the system's available memory
+//! drops below LOW_MEMORY_PERCENT of the total. `get_engine_resources` takes
+//! a sample on demand.
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri::async_runtime::Mutex;
+use sysinfo::{Pid, ProcessesToUpdate, System};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::error::EngineError;
+use crate::events::{self, EngineResourcesSampled};
+use crate::PythonProcess;
+
+/// Minimum time between two samples from the status loop
+const RESOURCE_SAMPLE_INTERVAL_SECS: u64 = 5;
+
+/// Available system memory below this share of the total sets `low_memory`
+const LOW_MEMORY_PERCENT: u64 = 10;
+
+// ==================== Types ====================
+
+/// CPU and memory use of the engine.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct EngineResources {
+    pub pid: u32,
+    /// Summed over the engine's processes; 100 = one core fully busy
+    pub cpu_percent: f32,
+    /// Resident memory of the engine's processes
+    pub rss_bytes: u64,
+    pub uptime_secs: u64,
+    pub system_total_bytes: u64,
+    pub system_available_bytes: u64,
+    /// Available system memory is below LOW_MEMORY_PERCENT of the total
+    pub low_memory: bool,
+}
+
+/// Process being measured, and the sysinfo state CPU usage is computed against.
+#[derive(Default)]
+pub struct EngineResourcesState {
+    pid: Option<u32>,
+    last_sample: Option<Instant>,
+    system: Arc<std::sync::Mutex<System>>,
+}
+
+// ==================== Sampling ====================
+
+/// Start measuring a freshly spawned engine process.
+pub(crate) async fn track_process(app: &AppHandle, pid: u32) {
+    let state = app.state::<Mutex<EngineResourcesState>>();
+    *state.lock().await = EngineResourcesState { pid: Some(pid), ..Default::default() };
+}
+
+/// Measure `root` and its descendants; None once the process is gone.
+fn measure(system: &mut System, root: u32) -> Option<EngineResources> {
+    system.refresh_processes(ProcessesToUpdate::All, true);
+    system.refresh_memory();
+    let uptime_secs = system.process(Pid::from_u32(root))?.run_time();
+
+    let mut pids = vec![Pid::from_u32(root)];
+    let mut next = 0;
+    while next < pids.len() {
+        let parent = pids[next];
+        pids.extend(system.processes().iter().filter(|(_, p)| p.parent() == Some(parent)).map(|(pid, _)| *pid));
+        next += 1;
+    }
+    let processes = pids.iter().filter_map(|pid| system.process(*pid));
+    let (cpu_percent, rss_bytes) = processes.fold((0.0, 0), |(cpu, rss), p| (cpu + p.cpu_usage(), rss + p.memory()));
+
+    let system_total_bytes = system.total_memory();
+    let system_available_bytes = system.available_memory();
+    Some(EngineResources {
+        pid: root,
+        cpu_percent,
+        rss_bytes,
+        uptime_secs,
+        system_total_bytes,
+        system_available_bytes,
+        low_memory: system_available_bytes < system_total_bytes / 100 * LOW_MEMORY_PERCENT,
+    })
+}
+
+/// Sample the tracked engine, off the async runtime.
+async fn take_sample(app: &AppHandle, only_if_due: bool) -> Option<EngineResources> {
+    let (pid, system) = {
+        let state = app.state::<Mutex<EngineResourcesState>>();
+        let mut state = state.lock().await;
+        let due = state.last_sample.is_none_or(|at| at.elapsed() >= Duration::from_secs(RESOURCE_SAMPLE_INTERVAL_SECS));
+        if only_if_due && !due {
+            return None;
+        }
+        state.last_sample = Some(Instant::now());
+        (state.pid?, state.system.clone())
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut system = system.lock().unwrap_or_else(|e| e.into_inner());
+        measure(&mut system, pid)
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+/// Emit `engine_resources` if the sample interval has passed (called from the status loop).
+pub(crate) async fn sample(app: &AppHandle) {
+    let Some(resources) = take_sample(app, true).await else {
+        return;
+    };
+    if resources.low_memory {
+        println!(
+            "Low memory: {} MiB available, engine uses {} MiB",
+            resources.system_available_bytes >> 20,
+            resources.rss_bytes >> 20
+        );
+    }
+    events::emit(app, EngineResourcesSampled { resources });
+}
+
+// ==================== Tauri Command: get_engine_resources ====================
+
+/// Return the engine's current CPU, memory and uptime.
+///
+/// CPU usage is averaged since the previous sample (at most
+/// RESOURCE_SAMPLE_INTERVAL_SECS ago while the engine runs).
+#[tauri::command]
+#[specta::specta]
+pub async fn get_engine_resources(app: AppHandle) -> Result<EngineResources, EngineError> {
+    let is_running = *app.state::<Mutex<PythonProcess>>().lock().await.is_running.lock().await;
+    if !is_running {
+        return Err(EngineError::NotRunning);
+    }
+    take_sample(&app, false).await.ok_or(EngineError::NotRunning)
+}
@@ -0,0 +1,87 @@
+//! =============================================================================
+//! Frontend Resync After Reloads
+//! =============================================================================
+//!
+//! A webview reload (dev hot-reload or a renderer crash) loses the
+//! frontend's event listeners and the Channels of its open streams, while
+//! the engine and the requests it is working on keep going. After
+//! re-registering its listeners, the reloaded frontend calls
+//! `resync(since_seq)` once and rebuilds its view from the result:
+//!
+//!   engine      lifecycle state (see engine_state) and whether it runs
+//!   status      the latest /status snapshot and its version (see status_delta)
+//!   requests    correlation ids of the requests still in flight
+//!   streams     open streams with the seq of their next chunk; reattach
+//!               with `resume_stream` (see streaming)
+//!   events      buffered events emitted after `since_seq` (all of them
+//!               without it), oldest first (see events)
+//!
+//! Responses of requests whose invoke was lost still arrive as
+//! `python_input` events. Queued jobs are listed by `list_jobs`.
+
+use serde::Serialize;
+use specta::Type;
+use tauri::State;
+use tauri::async_runtime::Mutex;
+
+use crate::engine_state::EngineState;
+use crate::error::EngineError;
+use crate::events::{self, BufferedEvent};
+use crate::requests::{ActiveRequest, ActiveRequests};
+use crate::status_delta::{self, FullStatus, StatusDeltaState};
+use crate::streaming::{self, OpenStream};
+use crate::PythonProcess;
+
+// ==================== Types ====================
+
+/// Engine part of the resync snapshot.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct EngineSnapshot {
+    pub state: EngineState,
+    pub running: bool,
+}
+
+/// Response of `resync`.
+#[derive(Debug, Serialize, Type)]
+pub struct ResyncSnapshot {
+    pub engine: EngineSnapshot,
+    pub status: FullStatus,
+    pub requests: Vec<ActiveRequest>,
+    pub streams: Vec<OpenStream>,
+    pub events: Vec<BufferedEvent>,
+}
+
+// ==================== Tauri Command: resync ====================
+
+/// Everything a reloaded frontend needs to rebuild its view.
+///
+/// `since_seq` is the seq of the last buffered event the frontend saw, if it
+/// kept one across the reload.
+#[tauri::command]
+#[specta::specta]
+pub async fn resync(
+    since_seq: Option<u64>,
+    state: State<'_, Mutex<PythonProcess>>,
+    status: State<'_, Mutex<StatusDeltaState>>,
+    active: State<'_, ActiveRequests>,
+) -> Result<ResyncSnapshot, EngineError> {
+    let engine = {
+        let proc_state = state.lock().await;
+        let running = *proc_state.is_running.lock().await;
+        EngineSnapshot { state: proc_state.lifecycle.current(), running }
+    };
+    let snapshot = ResyncSnapshot {
+        engine,
+        status: status_delta::latest(&*status.lock().await),
+        requests: active.snapshot(),
+        streams: streaming::open_streams(),
+        events: events::recent_since(since_seq),
+    };
+    println!(
+        "Frontend resync: {} request(s), {} stream(s), {} event(s)",
+        snapshot.requests.len(),
+        snapshot.streams.len(),
+        snapshot.events.len()
+    );
+    Ok(snapshot)
+}
@@ -0,0 +1,304 @@
+//! =============================================================================
+//! Data Retention
+//! =============================================================================
+//!
+//! Keeps the app's disk footprint bounded on long-lived installs. A janitor
+//! task applies `settings.retention` RETENTION_FIRST_RUN_SECS after startup
+//! and every RETENTION_INTERVAL_SECS after that:
+//!
+//!   history_days    stored inputs and responses (see history), per-message
+//!                   model records of sessions (see history_store) and
+//!                   session recordings (see recorder)
+//!   history_max_mb  after that, the oldest recordings are deleted until the
+//!                   recordings fit (the one in progress is never touched)
+//!   logs_days       entries of the audit log (see audit)
+//!   metrics_days    hourly and daily metrics rollups (see metrics_history)
+//!
+//! 0 keeps data of that kind forever. `run_retention_now(dry_run)` runs the
+//! janitor on demand; with `dry_run` it only reports what would be deleted.
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri::async_runtime::Mutex;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::audit;
+use crate::error::EngineError;
+use crate::history_store::SharedHistoryStore;
+use crate::metrics_history::MetricsHistory;
+use crate::recorder;
+use crate::settings::SettingsStore;
+
+/// Delay before the first janitor run, keeping it out of startup
+const RETENTION_FIRST_RUN_SECS: u64 = 60;
+
+/// Interval between janitor runs
+const RETENTION_INTERVAL_SECS: u64 = 6 * 60 * 60;
+
+const SECS_PER_DAY: u64 = 86_400;
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+// ==================== Settings ====================
+
+/// Retention limits, persisted under `settings.retention` (0 = keep forever).
+#[derive(Debug, Clone, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(default)]
+pub struct RetentionSettings {
+    pub history_days: u64,
+    pub history_max_mb: u64,
+    pub logs_days: u64,
+    pub metrics_days: u64,
+}
+
+impl Default for RetentionSettings {
+    fn default() -> Self {
+        RetentionSettings {
+            history_days: 90,
+            history_max_mb: 500,
+            logs_days: 30,
+            metrics_days: 365,
+        }
+    }
+}
+
+// ==================== Types ====================
+
+/// Kind of data a retention limit applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum RetentionCategory {
+    History,
+    Logs,
+    Metrics,
+}
+
+/// Data deleted (or, in a dry run, to be deleted) from one place.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct RetentionItem {
+    pub category: RetentionCategory,
+    /// What was pruned: a file path or a record kind
+    pub target: String,
+    /// Files, rows or log entries
+    pub count: u64,
+    /// Bytes freed, where known
+    pub bytes: u64,
+}
+
+/// Response of `run_retention_now`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct RetentionReport {
+    pub dry_run: bool,
+    pub items: Vec<RetentionItem>,
+    pub total_bytes: u64,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Cutoff (unix secs) for data kept `days` days; None when kept forever.
+fn cutoff(days: u64) -> Option<u64> {
+    (days > 0).then(|| unix_now().saturating_sub(days * SECS_PER_DAY))
+}
+
+// ==================== History ====================
+
+/// Finished recordings with their modification time (unix secs) and size, oldest first.
+fn recordings(dir: &Path) -> Vec<(PathBuf, u64, u64)> {
+    let active = recorder::active_recording();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<(PathBuf, u64, u64)> = entries
+        .flatten()
+        .filter(|entry| active.as_deref() != Some(entry.path().as_path()))
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+            let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_secs();
+            Some((entry.path(), modified, metadata.len()))
+        })
+        .collect();
+    files.sort_by_key(|(path, modified, _)| (*modified, path.clone()));
+    files
+}
+
+/// Apply `history_days` and `history_max_mb` to the session recordings.
+fn prune_recordings(app: &AppHandle, settings: &RetentionSettings, dry_run: bool, items: &mut Vec<RetentionItem>) {
+    let mut files = recordings(&recorder::recordings_dir(app));
+    let mut doomed = Vec::new();
+    if let Some(before) = cutoff(settings.history_days) {
+        let keep_from = files.iter().position(|(_, modified, _)| *modified >= before).unwrap_or(files.len());
+        doomed.extend(files.drain(..keep_from));
+    }
+    if settings.history_max_mb > 0 {
+        let mut total: u64 = files.iter().map(|(_, _, size)| size).sum();
+        let mut oldest = files.into_iter();
+        while total > settings.history_max_mb * BYTES_PER_MB {
+            let Some(file) = oldest.next() else {
+                break;
+            };
+            total -= file.2;
+            doomed.push(file);
+        }
+    }
+
+    for (path, _, size) in doomed {
+        if !dry_run {
+            if let Err(e) = std::fs::remove_file(&path) {
+                println!("Failed to delete recording {:?}: {}", path, e);
+                continue;
+            }
+        }
+        items.push(RetentionItem {
+            category: RetentionCategory::History,
+            target: path.to_string_lossy().into_owned(),
+            count: 1,
+            bytes: size,
+        });
+    }
+}
+
+// ==================== Logs ====================
+
+/// Drop audit log entries older than `before`; returns (entries, bytes) removed.
+///
+/// The kept entries are written to a temporary file that replaces the log.
+fn prune_audit_log(path: &Path, before: u64, dry_run: bool) -> std::io::Result<(u64, u64)> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((0, 0)),
+        Err(e) => return Err(e),
+    };
+    let before_ms = before * 1000;
+    let mut kept = Vec::new();
+    let (mut entries, mut bytes) = (0, 0);
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        let timestamp_ms = serde_json::from_str::<serde_json::Value>(&line)
+            .ok()
+            .and_then(|entry| entry.get("timestamp_ms").and_then(|t| t.as_u64()));
+        // Unparseable lines are kept; they may be someone's evidence
+        if timestamp_ms.is_some_and(|t| t < before_ms) {
+            entries += 1;
+            bytes += line.len() as u64 + 1;
+        } else {
+            kept.push(line);
+        }
+    }
+    if dry_run || entries == 0 {
+        return Ok((entries, bytes));
+    }
+
+    let temp = path.with_extension("jsonl.tmp");
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(&temp)?);
+    for line in &kept {
+        writeln!(writer, "{}", line)?;
+    }
+    writer.flush()?;
+    drop(writer);
+    std::fs::rename(&temp, path)?;
+    Ok((entries, bytes))
+}
+
+// ==================== Janitor ====================
+
+/// Apply the retention settings once; with `dry_run` nothing is deleted.
+pub(crate) async fn run(app: &AppHandle, dry_run: bool) -> RetentionReport {
+    let settings = app.state::<Mutex<SettingsStore>>().lock().await.settings.retention.clone();
+    let mut items = Vec::new();
+
+    if let Some(before) = cutoff(settings.history_days) {
+        let result = app.state::<SharedHistoryStore>().lock().await.prune_messages(before, dry_run);
+        match result {
+            Ok(0) => {}
+            Ok(count) => items.push(RetentionItem {
+                category: RetentionCategory::History,
+                target: "session messages".to_string(),
+                count,
+                bytes: 0,
+            }),
+            Err(e) => println!("Failed to prune session messages: {}", e),
+        }
+        let result = app.state::<SharedHistoryStore>().lock().await.prune_exchanges(before, dry_run);
+        match result {
+            Ok(0) => {}
+            Ok(count) => items.push(RetentionItem {
+                category: RetentionCategory::History,
+                target: "conversation history".to_string(),
+                count,
+                bytes: 0,
+            }),
+            Err(e) => println!("Failed to prune conversation history: {}", e),
+        }
+    }
+    let app_clone = app.clone();
+    let settings_clone = settings.clone();
+    let recordings = tokio::task::spawn_blocking(move || {
+        let mut items = Vec::new();
+        prune_recordings(&app_clone, &settings_clone, dry_run, &mut items);
+        items
+    });
+    items.extend(recordings.await.unwrap_or_default());
+
+    if let Some(before) = cutoff(settings.logs_days) {
+        let path = audit::log_path(app);
+        let target = path.to_string_lossy().into_owned();
+        match tokio::task::spawn_blocking(move || prune_audit_log(&path, before, dry_run)).await {
+            Ok(Ok((0, _))) => {}
+            Ok(Ok((count, bytes))) => items.push(RetentionItem { category: RetentionCategory::Logs, target, count, bytes }),
+            Ok(Err(e)) => println!("Failed to prune audit log: {}", e),
+            Err(e) => println!("Failed to prune audit log: {}", e),
+        }
+    }
+
+    if let Some(before) = cutoff(settings.metrics_days) {
+        let result = app.state::<Mutex<MetricsHistory>>().lock().await.prune_before(before, dry_run);
+        match result {
+            Ok(0) => {}
+            Ok(count) => items.push(RetentionItem {
+                category: RetentionCategory::Metrics,
+                target: "metrics rollups".to_string(),
+                count,
+                bytes: 0,
+            }),
+            Err(e) => println!("Failed to prune metrics history: {}", e),
+        }
+    }
+
+    let total_bytes = items.iter().map(|item| item.bytes).sum();
+    println!(
+        "Retention {}: {} item(s), {} bytes",
+        if dry_run { "dry run" } else { "run" },
+        items.len(),
+        total_bytes
+    );
+    RetentionReport { dry_run, items, total_bytes }
+}
+
+/// Start the periodic janitor.
+pub fn init(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(RETENTION_FIRST_RUN_SECS)).await;
+        loop {
+            run(&app, false).await;
+            tokio::time::sleep(Duration::from_secs(RETENTION_INTERVAL_SECS)).await;
+        }
+    });
+}
+
+// ==================== Tauri Command: run_retention_now ====================
+
+/// Apply the retention settings now; with `dry_run` only report what would be deleted.
+#[tauri::command]
+#[specta::specta]
+pub async fn run_retention_now(app: AppHandle, dry_run: Option<bool>) -> Result<RetentionReport, EngineError> {
+    Ok(run(&app, dry_run.unwrap_or(false)).await)
+}
@@ -0,0 +1,68 @@
+//! =============================================================================
+//! Runtime Identity
+//! =============================================================================
+//!
+//! A dev build and an installed release can run side by side for the same
+//! user. Runtime artifacts are therefore named after a namespace made of the
+//! app identifier plus the build channel:
+//!
+//!   com.kelvin.backend-trial.dev       (debug builds)
+//!   com.kelvin.backend-trial.release   (release builds)
+//!
+//! The channel can be overridden at build time with AI_ENGINE_BUILD_CHANNEL
+//! (e.g. "beta"). The default socket / pipe name is derived from the
+//! namespace (see ipc), and the engine receives it in AI_ENGINE_NAMESPACE to
+//! name its own PID, lock and shared-memory files.
+
+use serde::Serialize;
+use specta::Type;
+use tauri::AppHandle;
+
+use crate::error::EngineError;
+use crate::ipc;
+
+/// Environment variable passing the namespace to the engine
+pub(crate) const NAMESPACE_ENV_VAR: &str = "AI_ENGINE_NAMESPACE";
+
+/// Build channel of this binary
+const BUILD_CHANNEL: &str = match option_env!("AI_ENGINE_BUILD_CHANNEL") {
+    Some(channel) => channel,
+    None if cfg!(debug_assertions) => "dev",
+    None => "release",
+};
+
+/// Identity of this app instance, as returned by `get_runtime_identity`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct RuntimeIdentity {
+    pub identifier: String,
+    pub channel: String,
+    /// `<identifier>.<channel>`, prefix of all runtime artifact names
+    pub namespace: String,
+    /// Endpoint of the current (or last started) engine, if any
+    pub socket_path: Option<String>,
+}
+
+/// Namespace for runtime artifacts of this build.
+pub(crate) fn namespace(app: &AppHandle) -> String {
+    namespace_for(&app.config().identifier)
+}
+
+/// Namespace for the app `identifier`, before an AppHandle exists.
+pub(crate) fn namespace_for(identifier: &str) -> String {
+    format!("{}.{}", identifier, BUILD_CHANNEL)
+}
+
+// ==================== Tauri Command: get_runtime_identity ====================
+
+/// Return the app identifier, build channel and the namespace derived from them.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_runtime_identity(app: AppHandle) -> Result<RuntimeIdentity, EngineError> {
+    let socket_path = ipc::active_endpoint();
+    Ok(RuntimeIdentity {
+        identifier: app.config().identifier.clone(),
+        channel: BUILD_CHANNEL.to_string(),
+        namespace: namespace(&app),
+        socket_path: (!socket_path.is_empty()).then_some(socket_path),
+    })
+}
@@ -0,0 +1,190 @@
+//! =============================================================================
+//! Per-Session Model Routing
+//! =============================================================================
+//!
+//! A conversation can pin a model with `set_session_model(session_id, model)`,
+//! and a single message can override it again. The model for a message is
+//! resolved as:
+//!
+//!   message `route.model`  →  session override  →  engine default
+//!
+//! and sent in the /input payload next to the session id, where the engine
+//! routes it to the worker serving that model:
+//!
+//!   { "input": "…", "request_id": "req-4", "session_id": "s1", "model": "llama-3b-q4" }
+//!
+//! Engine-side conversation context lives in the worker of the model that
+//! produced it, so switching models mid-session starts the new model without
+//! it. When that happens `session_model_switched` is emitted before the
+//! request is sent.
+//!
+//! Which model answered each message (the engine's reported `model`, else
+//! the requested one) is kept in the history store (SQLite or JSONL, see
+//! history_store), together with the session overrides, so mixed-model
+//! conversations stay attributable after a restart.
+//! Messages whose stream was cut off are stored with `complete = false`.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager, State};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::EngineError;
+use crate::events::{self, SessionModelSwitched};
+use crate::generation::GenerationParams;
+use crate::history_store::{SharedHistoryStore, StoredMessage};
+use crate::templates::TemplateRef;
+
+// ==================== Types ====================
+
+/// Session and model routing of one input, plus its generation parameters and template (all optional).
+#[derive(Debug, Clone, Default, Deserialize, Type)]
+pub struct InputRoute {
+    pub session_id: Option<String>,
+    /// Model for this message only, overriding the session's model
+    pub model: Option<String>,
+    /// Capacity reservation the input belongs to, for admission priority (see reservations)
+    pub reservation_id: Option<String>,
+    /// Sampling settings for this input, over the defaults (see generation)
+    pub generation: Option<GenerationParams>,
+    /// Prompt template wrapped around the input (see templates)
+    pub template: Option<TemplateRef>,
+}
+
+/// Routing resolved for a request, needed again to record the answer.
+#[derive(Debug, Clone)]
+pub(crate) struct SessionTurn {
+    session_id: String,
+    model: Option<String>,
+}
+
+/// A message of a session and the model that answered it.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct AnsweredMessage {
+    pub request_id: String,
+    pub model: Option<String>,
+    pub answered_at: u64,
+    /// False if the answer's stream was truncated
+    pub complete: bool,
+}
+
+/// Response of `get_session_models`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct SessionModels {
+    pub session_id: String,
+    /// Model pinned with `set_session_model`, if any
+    pub model: Option<String>,
+    /// Answered messages, oldest first
+    pub messages: Vec<AnsweredMessage>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// ==================== Routing ====================
+
+/// Resolve the model for an input and add `session_id` / `model` to its /input body.
+///
+/// Emits `session_model_switched` when the session was last answered by a
+/// different model. Returns the turn to pass to `record_answer`, if the
+/// input belongs to a session.
+pub(crate) async fn route_input(app: &AppHandle, route: Option<InputRoute>, body: &mut serde_json::Value) -> Option<SessionTurn> {
+    let route = route.unwrap_or_default();
+    let Some(session_id) = route.session_id else {
+        if let Some(model) = route.model {
+            body["model"] = model.into();
+        }
+        return None;
+    };
+
+    let store = app.state::<SharedHistoryStore>();
+    let store = store.lock().await;
+    let model = match route.model {
+        Some(model) => Some(model),
+        None => store.session_model(&session_id).unwrap_or_else(|e| {
+            println!("Failed to read model of session {}: {}", session_id, e);
+            None
+        }),
+    };
+    let previous_model = store.last_message(&session_id).ok().flatten().and_then(|m| m.model);
+    drop(store);
+
+    if let (Some(previous), Some(model)) = (&previous_model, &model) {
+        if previous != model {
+            println!("Session {} switches from {} to {}; engine-side context is not carried over", session_id, previous, model);
+            events::emit(app, SessionModelSwitched {
+                session_id: session_id.clone(),
+                previous_model: previous.clone(),
+                model: model.clone(),
+            });
+        }
+    }
+
+    body["session_id"] = session_id.clone().into();
+    if let Some(model) = &model {
+        body["model"] = model.clone().into();
+    }
+    Some(SessionTurn { session_id, model })
+}
+
+/// Persist which model answered a session's message, and whether the answer is complete.
+///
+/// Prefers the `model` the engine reports in `response` over the requested one.
+pub(crate) async fn record_answer(app: &AppHandle, turn: &SessionTurn, request_id: &str, response: Option<&serde_json::Value>, complete: bool) {
+    let model = response
+        .and_then(|r| r.get("model"))
+        .and_then(|m| m.as_str())
+        .or(turn.model.as_deref());
+    let message = StoredMessage {
+        request_id: request_id.to_string(),
+        session_id: turn.session_id.clone(),
+        model: model.map(str::to_string),
+        answered_at: unix_now(),
+        complete,
+    };
+    let result = app.state::<SharedHistoryStore>().lock().await.record_message(message);
+    if let Err(e) = result {
+        println!("Failed to record model of {}: {}", request_id, e);
+    }
+}
+
+// ==================== Tauri Commands ====================
+
+/// Pin a model for all later messages of a session (`None` returns to the engine default).
+#[tauri::command]
+#[specta::specta]
+pub async fn set_session_model(
+    session_id: String,
+    model: Option<String>,
+    store: State<'_, SharedHistoryStore>,
+) -> Result<(), EngineError> {
+    if model.as_deref().is_some_and(|m| m.trim().is_empty()) {
+        return Err(EngineError::InvalidRequest("Model name must not be empty".to_string()));
+    }
+    store.lock().await
+        .set_session_model(&session_id, model.as_deref())
+        .map_err(|e| EngineError::Internal(format!("Failed to save model of session {}: {}", session_id, e)))
+}
+
+/// Return a session's pinned model and the model that answered each message.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_session_models(session_id: String, store: State<'_, SharedHistoryStore>) -> Result<SessionModels, EngineError> {
+    let store = store.lock().await;
+    let read_error = |e: String| EngineError::Internal(format!("Failed to read session {}: {}", session_id, e));
+    let model = store.session_model(&session_id).map_err(read_error)?;
+    let messages = store.messages(&session_id).map_err(read_error)?
+        .into_iter()
+        .map(|m| AnsweredMessage {
+            request_id: m.request_id,
+            model: m.model,
+            answered_at: m.answered_at,
+            complete: m.complete,
+        })
+        .collect();
+    Ok(SessionModels { session_id, model, messages })
+}
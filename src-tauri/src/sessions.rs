@@ -0,0 +1,144 @@
+//! =============================================================================
+//! Chat Sessions
+//! =============================================================================
+//!
+//! A chat session groups the inputs of one conversation. Sessions are
+//! created by the backend and their metadata is kept in the history store
+//! (SQLite by default, see history_store), so they survive restarts:
+//!
+//!   { "id": "s-3f9c0a6e1b7d2c45", "title": "Plan a trip to Lisbon",
+//!     "created_at": 1718000000, "updated_at": 1718000420, "message_count": 6 }
+//!
+//! `send_input_to_session(session_id, input)` is `send_input_to_python` with
+//! the input routed to the session: the session id goes into the /input
+//! payload (see session_models) and every answered input bumps the message
+//! count. A session created without a title takes the first line of its
+//! first input. `delete_session` removes the session with its model
+//! override and answered messages.
+
+use tauri::{AppHandle, Manager, State, Webview};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::budget::{CommandClass, CommandTimer, Timed};
+use crate::error::EngineError;
+use crate::history_store::{SharedHistoryStore, StoredSession};
+use crate::session_models::InputRoute;
+use crate::streaming::StreamChannel;
+use crate::{jobs, process_input};
+
+/// Longest title taken from a first input, in characters
+const MAX_DERIVED_TITLE_CHARS: usize = 60;
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Title for an untitled session from its first input.
+fn derive_title(input: &str) -> String {
+    let line = input.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or_default();
+    match line.char_indices().nth(MAX_DERIVED_TITLE_CHARS) {
+        Some((end, _)) => format!("{}…", line[..end].trim_end()),
+        None => line.to_string(),
+    }
+}
+
+fn store_error(e: String) -> EngineError {
+    EngineError::Internal(format!("Failed to access sessions: {}", e))
+}
+
+/// The session `session_id`, or `invalid_request` if there is none.
+async fn existing(app: &AppHandle, session_id: &str) -> Result<StoredSession, EngineError> {
+    app.state::<SharedHistoryStore>().lock().await
+        .session(session_id)
+        .map_err(store_error)?
+        .ok_or_else(|| EngineError::InvalidRequest(format!("No session {}", session_id)))
+}
+
+/// Count an answered input of the session (naming it after the first input if untitled).
+async fn record_message(app: &AppHandle, session_id: &str, input: &str) {
+    let store = app.state::<SharedHistoryStore>();
+    let mut store = store.lock().await;
+    // Deleted while the input was answered
+    let Ok(Some(mut session)) = store.session(session_id) else {
+        return;
+    };
+    if session.title.is_empty() {
+        session.title = derive_title(input);
+    }
+    session.message_count += 1;
+    session.updated_at = unix_now();
+    if let Err(e) = store.save_session(session) {
+        println!("Failed to update session {}: {}", session_id, e);
+    }
+}
+
+// ==================== Tauri Commands ====================
+
+/// Start a new chat session (`title` is optional).
+#[tauri::command]
+#[specta::specta]
+pub async fn create_session(title: Option<String>, store: State<'_, SharedHistoryStore>) -> Result<StoredSession, EngineError> {
+    let now = unix_now();
+    let session = StoredSession {
+        id: format!("s-{:016x}", fastrand::u64(..)),
+        title: title.map(|t| t.trim().to_string()).unwrap_or_default(),
+        created_at: now,
+        updated_at: now,
+        message_count: 0,
+    };
+    store.lock().await.save_session(session.clone()).map_err(store_error)?;
+    println!("Created session {}", session.id);
+    Ok(session)
+}
+
+/// List the chat sessions, most recently used first.
+#[tauri::command]
+#[specta::specta]
+pub async fn list_sessions(store: State<'_, SharedHistoryStore>) -> Result<Vec<StoredSession>, EngineError> {
+    store.lock().await.sessions().map_err(store_error)
+}
+
+/// Send user input to the AI Engine as part of a chat session.
+///
+/// This command:
+///   1. Checks that the session exists
+///   2. Sends the input like `send_input_to_python`, with `session_id` (and
+///      the session's pinned model, see `set_session_model`) in the /input payload
+///   3. Counts the answered input in the session's metadata
+///
+/// `timeout_ms`, `on_token` and `request_id` work as for `send_input_to_python`.
+#[tauri::command]
+#[specta::specta]
+pub async fn send_input_to_session(
+    app: AppHandle,
+    webview: Webview,
+    session_id: String,
+    input: String,
+    timeout_ms: Option<u64>,
+    on_token: Option<StreamChannel>,
+    request_id: Option<String>,
+) -> Result<Timed<serde_json::Value>, EngineError> {
+    existing(&app, &session_id).await?;
+    let timer = CommandTimer::start(&app, "send_input_to_session", CommandClass::Interactive);
+    timer.phase("awaiting_response").await;
+    let writer = on_token.map(|channel| channel.writer(webview));
+    let route = InputRoute { session_id: Some(session_id.clone()), ..InputRoute::default() };
+    let work = process_input(&app, "send_input_to_session", input.clone(), timeout_ms, writer, request_id, Some(route));
+    let response = jobs::interactive(&app, work).await?;
+    record_message(&app, &session_id, &input).await;
+    Ok(timer.finish(response))
+}
+
+/// Delete a chat session with its model override and answered messages.
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_session(session_id: String, store: State<'_, SharedHistoryStore>) -> Result<(), EngineError> {
+    if !store.lock().await.delete_session(&session_id).map_err(store_error)? {
+        return Err(EngineError::InvalidRequest(format!("No session {}", session_id)));
+    }
+    println!("Deleted session {}", session_id);
+    Ok(())
+}
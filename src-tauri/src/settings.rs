@@ -0,0 +1,376 @@
+//! =============================================================================
+//! User Settings
+//! =============================================================================
+//!
+//! Persisted, user-editable backend settings stored as settings.json in the
+//! app config directory. Missing keys fall back to their defaults, so older
+//! settings files keep loading as new settings are added. The file is
+//! versioned, migrated and validated against the settings' JSON Schema on
+//! load (see settings_schema).
+//!
+//! Saves are atomic and checksummed, and every save and every load of a
+//! valid file also writes settings.last-good.json (see atomic_file); a file
+//! repaired on load doesn't. A settings.json found corrupt or unrepairable
+//! at startup is restored from it and reported as `settings_recovered`;
+//! `repair_installation` does the same later on.
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use specta::Type;
+use tauri::{AppHandle, Manager, State};
+use tauri::async_runtime::Mutex;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::analytics::{self, AnalyticsSettings};
+use crate::atomic_file::{self, Recovery, RecoverySource};
+use crate::auth::RemoteSettings;
+use crate::backup::BackupSettings;
+use crate::broker::BrokerSettings;
+use crate::compression::{self, CompressionSettings};
+use crate::error::EngineError;
+use crate::events::{self, SettingsRecovered};
+use crate::feature_flags::{self, FeatureSettings};
+use crate::history_store::StorageSettings;
+use crate::crash_supervisor::SupervisorSettings;
+use crate::ipc::SocketConfig;
+use crate::jobs::{self, JobSettings};
+use crate::model_fallback::ModelTier;
+use crate::moderation::ModerationSettings;
+use crate::prefetch::PrefetchSettings;
+use crate::proxy::ProxySettings;
+use crate::recycling::RecycleSettings;
+use crate::retention::RetentionSettings;
+use crate::settings_schema::{self, Rewrite};
+use crate::transport;
+
+/// File holding the settings inside the app config directory
+pub(crate) const SETTINGS_FILE: &str = "settings.json";
+
+// ==================== Endpoint Timeouts ====================
+
+/// Endpoint classes sharing a response timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndpointClass {
+    /// Health checks and status polling (/status, /health, ...)
+    Status,
+    /// Interactive chat requests (/input)
+    Chat,
+    /// Long-running batch jobs (/batch/...)
+    Batch,
+    /// Everything else (control endpoints like /stop, /config)
+    Control,
+}
+
+impl EndpointClass {
+    /// Classify an engine endpoint path.
+    pub fn for_endpoint(endpoint: &str) -> EndpointClass {
+        let path = endpoint.split('?').next().unwrap_or(endpoint);
+        match path {
+            "/status" | "/health" | "/version" | "/mux" => EndpointClass::Status,
+            "/input" | "/upload" => EndpointClass::Chat,
+            _ if path.starts_with("/batch") => EndpointClass::Batch,
+            _ => EndpointClass::Control,
+        }
+    }
+}
+
+/// Response timeouts per endpoint class, in milliseconds.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(default)]
+pub struct TimeoutSettings {
+    #[schemars(range(min = 1))]
+    pub status_ms: u64,
+    #[schemars(range(min = 1))]
+    pub chat_ms: u64,
+    #[schemars(range(min = 1))]
+    pub batch_ms: u64,
+    #[schemars(range(min = 1))]
+    pub control_ms: u64,
+}
+
+impl Default for TimeoutSettings {
+    fn default() -> Self {
+        TimeoutSettings {
+            status_ms: 2_000,
+            chat_ms: 120_000,
+            batch_ms: 4 * 60 * 60 * 1000,
+            control_ms: 10_000,
+        }
+    }
+}
+
+impl TimeoutSettings {
+    /// Timeout for `class`.
+    pub fn for_class(&self, class: EndpointClass) -> Duration {
+        Duration::from_millis(match class {
+            EndpointClass::Status => self.status_ms,
+            EndpointClass::Chat => self.chat_ms,
+            EndpointClass::Batch => self.batch_ms,
+            EndpointClass::Control => self.control_ms,
+        })
+    }
+}
+
+// ==================== Telemetry ====================
+
+/// Request trace export (only used when built with the `otel` feature).
+#[derive(Debug, Clone, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(default)]
+pub struct TelemetrySettings {
+    /// OTLP/HTTP collector base URL, e.g. http://localhost:4318; None disables export
+    pub otlp_endpoint: Option<String>,
+    /// Fraction of requests traced, 0.0 - 1.0
+    #[schemars(range(min = 0.0, max = 1.0))]
+    pub sample_rate: f64,
+}
+
+impl Default for TelemetrySettings {
+    fn default() -> Self {
+        TelemetrySettings {
+            otlp_endpoint: None,
+            sample_rate: 1.0,
+        }
+    }
+}
+
+// ==================== Engine ====================
+
+/// Engine lifecycle settings.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(default)]
+pub struct EngineSettings {
+    /// Start the engine on the first input instead of requiring start_python_script
+    pub auto_start: bool,
+    /// /input requests sent to the engine at the same time; more wait (see input_limiter)
+    #[schemars(range(min = 1))]
+    pub max_concurrent_inputs: usize,
+}
+
+impl Default for EngineSettings {
+    fn default() -> Self {
+        EngineSettings { auto_start: true, max_concurrent_inputs: 2 }
+    }
+}
+
+// ==================== Model ====================
+
+/// Model selection settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(default)]
+pub struct ModelSettings {
+    /// Model tiers from most to least preferred (see model_fallback)
+    pub fallback_chain: Vec<ModelTier>,
+}
+
+// ==================== Settings ====================
+
+/// All persisted backend settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(default)]
+pub struct Settings {
+    pub engine: EngineSettings,
+    pub timeouts: TimeoutSettings,
+    pub telemetry: TelemetrySettings,
+    pub model: ModelSettings,
+    pub supervisor: SupervisorSettings,
+    pub compression: CompressionSettings,
+    pub socket: SocketConfig,
+    pub remote: RemoteSettings,
+    pub moderation: ModerationSettings,
+    pub jobs: JobSettings,
+    pub retention: RetentionSettings,
+    pub storage: StorageSettings,
+    pub backup: BackupSettings,
+    pub recycle: RecycleSettings,
+    pub prefetch: PrefetchSettings,
+    pub proxy: ProxySettings,
+    pub analytics: AnalyticsSettings,
+    pub broker: BrokerSettings,
+    pub features: FeatureSettings,
+}
+
+/// Managed settings plus the file they are persisted to.
+pub struct SettingsStore {
+    path: PathBuf,
+    pub settings: Settings,
+}
+
+impl SettingsStore {
+    /// Load settings from `path`, restoring the last good ones if it is corrupt
+    /// or can't be repaired.
+    ///
+    /// Missing settings (or no usable backup) fall back to defaults.
+    pub fn load(path: PathBuf) -> (SettingsStore, Option<Recovery>) {
+        let (raw, mut recovery) = match atomic_file::read_checked::<serde_json::Value>(&path) {
+            Ok(raw) => (raw, None),
+            Err(reason) => {
+                let (raw, recovery) = atomic_file::recover(&path, reason);
+                (raw, Some(recovery))
+            }
+        };
+        let mut loaded = raw.map(settings_schema::load);
+        // Settings that can't be repaired are as corrupt as a file that doesn't parse
+        if let (Some(Err(reason)), None) = (&loaded, &recovery) {
+            let (raw, restored) = atomic_file::recover(&path, reason.clone());
+            loaded = raw.map(settings_schema::load);
+            recovery = Some(restored);
+        }
+        let (settings, rewrite) = match loaded {
+            Some(Ok(loaded)) => loaded,
+            Some(Err(e)) => {
+                println!("Unusable settings backup, using defaults: {}", e);
+                (Settings::default(), Rewrite::None)
+            }
+            None => (Settings::default(), Rewrite::None),
+        };
+        let store = SettingsStore { path, settings };
+        match rewrite {
+            Rewrite::None => atomic_file::refresh_backup(&store.path),
+            // The migrated layout is as good as the file it came from
+            Rewrite::Migrated => {
+                if let Err(e) = store.save() {
+                    println!("Failed to save migrated settings: {}", e);
+                }
+            }
+            // Repaired settings lost keys, so the last good ones stay the backup
+            Rewrite::Repaired => {
+                if let Err(e) = atomic_file::write_json_unbacked(&store.path, &settings_schema::versioned(&store.settings)) {
+                    println!("Failed to save repaired settings: {}", e);
+                }
+            }
+        }
+        (store, recovery)
+    }
+
+    /// Path of the settings file.
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Replace a corrupted settings file with the last good backup (defaults if there is none).
+    ///
+    /// The corrupted file is kept next to it with a `.corrupt` suffix.
+    /// Returns a description of what was restored.
+    pub(crate) fn restore_last_good(&mut self) -> Result<String, String> {
+        let (settings, recovery) = atomic_file::recover::<serde_json::Value>(&self.path, "repair requested".to_string());
+        let restored = match recovery.source {
+            RecoverySource::Backup => format!("restored from {:?}", atomic_file::backup_path(&self.path)),
+            RecoverySource::Defaults => "no usable backup, reset to defaults".to_string(),
+        };
+        let settings = settings.and_then(|raw| {
+            settings_schema::load(raw)
+                .inspect_err(|e| println!("Unusable settings backup, using defaults: {}", e))
+                .ok()
+        });
+        self.replace(settings.map(|(settings, _)| settings).unwrap_or_default())?;
+        match recovery.corrupt_copy {
+            Some(corrupt) => Ok(format!("{} (corrupted file kept as {:?})", restored, corrupt)),
+            None => Ok(restored),
+        }
+    }
+
+    /// Apply and persist `settings` in place of the current ones.
+    pub(crate) fn replace(&mut self, settings: Settings) -> Result<(), String> {
+        apply(&settings);
+        self.settings = settings;
+        self.save()
+    }
+
+    /// Persist the settings atomically (and as the last good ones), stamped with their layout version.
+    pub(crate) fn save(&self) -> Result<(), String> {
+        atomic_file::write_json(&self.path, &settings_schema::versioned(&self.settings))
+    }
+}
+
+/// Apply settings that are cached outside the store (on load and whenever they change).
+fn apply(settings: &Settings) {
+    analytics::configure(&settings.analytics);
+    compression::configure(&settings.compression);
+    feature_flags::configure(&settings.features);
+    transport::configure(&settings.timeouts);
+}
+
+/// Load settings from the app config dir and register them as managed state.
+pub fn init(app: &AppHandle) {
+    let config_dir = app.path().app_config_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join("ai-engine"));
+    let (store, recovery) = SettingsStore::load(config_dir.join(SETTINGS_FILE));
+    apply(&store.settings);
+    app.manage(Mutex::new(store));
+    if let Some(recovery) = recovery {
+        events::emit(app, SettingsRecovered { recovery });
+    }
+}
+
+// ==================== Tauri Commands ====================
+
+/// Return the current settings.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_settings(store: State<'_, Mutex<SettingsStore>>) -> Result<Settings, EngineError> {
+    Ok(store.lock().await.settings.clone())
+}
+
+/// Replace the settings and persist them.
+///
+/// Fails with `invalid_request` naming every invalid key (see `get_settings_schema`).
+#[tauri::command]
+#[specta::specta]
+pub async fn update_settings(app: AppHandle, mut settings: Settings, store: State<'_, Mutex<SettingsStore>>) -> Result<(), EngineError> {
+    settings_schema::validate(&settings)?;
+    let mut store = store.lock().await;
+    // The history backend changes only via migrate_storage, which moves the data
+    settings.storage = store.settings.storage.clone();
+    store.replace(settings)?;
+    // Start jobs that queued up while the jobs feature was disabled
+    jobs::schedule(app);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A settings path in a fresh directory.
+    fn settings_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("settings-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join(SETTINGS_FILE)
+    }
+
+    /// The last-good backup, parsed.
+    fn last_good(path: &Path) -> serde_json::Value {
+        atomic_file::read_checked(&atomic_file::backup_path(path)).unwrap().unwrap()
+    }
+
+    #[test]
+    fn unrepairable_file_is_restored_from_last_good() {
+        let path = settings_path("unrepairable");
+        let (mut store, _) = SettingsStore::load(path.clone());
+        store.settings.engine.auto_start = false;
+        store.save().unwrap();
+
+        atomic_file::write_json_unbacked(&path, &serde_json::json!({ "schema_version": "two" })).unwrap();
+        let (store, recovery) = SettingsStore::load(path);
+        assert_eq!(recovery.map(|r| r.source), Some(RecoverySource::Backup));
+        assert!(!store.settings.engine.auto_start);
+    }
+
+    #[test]
+    fn repaired_file_keeps_last_good() {
+        let path = settings_path("repaired");
+        let (mut store, _) = SettingsStore::load(path.clone());
+        store.settings.telemetry.sample_rate = 0.5;
+        store.save().unwrap();
+        let good = last_good(&path);
+
+        let mut damaged = good.clone();
+        damaged["telemetry"]["sample_rate"] = 7.into();
+        atomic_file::write_json_unbacked(&path, &damaged).unwrap();
+        let (store, recovery) = SettingsStore::load(path.clone());
+        assert!(recovery.is_none());
+        assert_eq!(store.settings.telemetry.sample_rate, 1.0);
+        assert_eq!(last_good(&path), good);
+    }
+}
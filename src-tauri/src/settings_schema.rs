@@ -0,0 +1,536 @@
+//! =============================================================================
+//! Settings Schema & Migrations
+//! =============================================================================
+//!
+//! settings.json carries the version of its layout:
+//!
+//!   { "schema_version": 3, "engine": { "auto_start": true, ... }, ... }
+//!
+//! Files without `schema_version` predate versioning and are v1. On load
+//! (and when a backup is restored) the file is upgraded through the
+//! migration chain, v1 → v2 → … → SETTINGS_VERSION, one step per layout
+//! change, and then validated against the JSON Schema derived from
+//! `Settings` (types, enum values, ranges such as `sample_rate` 0 - 1).
+//!
+//! Validation errors name the offending key, e.g.
+//! `telemetry.sample_rate: must be at most 1 (got 1.5)` or
+//! `remote.providers[0].base_url: is required`. An invalid key in the
+//! settings file falls back to its default (an invalid list entry is
+//! dropped) and is logged, so one bad value no longer resets everything;
+//! `update_settings` and backup restores reject invalid settings instead.
+//! A repaired file is rewritten but doesn't replace the last-good backup,
+//! and a file that can't be repaired is recovered from that backup like a
+//! corrupt one (see atomic_file).
+//!
+//! A file written by a newer app version is read as far as this version
+//! understands it but never rewritten on load. `get_settings_schema`
+//! returns the schema (with each setting's description) so the UI can
+//! render the settings form from it.
+
+use schemars::schema_for;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use specta::Type;
+use std::sync::OnceLock;
+
+use crate::error::EngineError;
+use crate::settings::{EngineSettings, Settings};
+
+/// Layout version written to settings.json
+pub(crate) const SETTINGS_VERSION: u32 = 3;
+
+/// Key holding the layout version in settings.json
+const VERSION_KEY: &str = "schema_version";
+
+/// Validation rounds when repairing a loaded file before giving up on it
+const MAX_REPAIR_PASSES: usize = 8;
+
+/// One layout change, applied to the settings object in place.
+type Migration = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+/// `MIGRATIONS[n]` upgrades version n + 1 to n + 2.
+const MIGRATIONS: &[Migration] = &[v1_to_v2, v2_to_v3];
+
+const _: () = assert!(MIGRATIONS.len() as u32 == SETTINGS_VERSION - 1);
+
+/// JSON Schema of `Settings`, generated once
+static SCHEMA: OnceLock<Value> = OnceLock::new();
+
+// ==================== Types ====================
+
+/// A setting that failed validation.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct SettingsError {
+    /// Path of the setting, e.g. "telemetry.sample_rate" or "remote.providers[0].name"
+    pub key: String,
+    pub message: String,
+}
+
+/// Settings layout version and its JSON Schema, returned by `get_settings_schema`.
+#[derive(Debug, Serialize, Type)]
+pub struct SettingsSchema {
+    pub version: u32,
+    /// JSON Schema (draft 7) of the settings object
+    pub schema: Value,
+}
+
+/// What to write back after loading a settings file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Rewrite {
+    /// Valid and current, or written by a newer version: leave it as is
+    None,
+    /// Valid in an older layout: save it, as the last-good backup too
+    Migrated,
+    /// Invalid keys were dropped: save it, keeping the last-good backup
+    Repaired,
+}
+
+/// Settings as written to disk: the layout version followed by the settings.
+#[derive(Serialize)]
+pub(crate) struct Versioned<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    settings: &'a Settings,
+}
+
+/// `settings` stamped with the current layout version.
+pub(crate) fn versioned(settings: &Settings) -> Versioned<'_> {
+    Versioned { schema_version: SETTINGS_VERSION, settings }
+}
+
+// ==================== Migrations ====================
+
+/// v1 is every file written before settings were versioned; its layout is
+/// the same as v2's, so only the version changes.
+fn v1_to_v2(_settings: &mut Map<String, Value>) -> Result<(), String> {
+    Ok(())
+}
+
+/// v3 drops `streaming.max_concurrent_streams`: streamed inputs now count
+/// against `engine.max_concurrent_inputs`. A stream cap below the input
+/// limit becomes the input limit, so the engine never runs more at once
+/// than the user allowed before.
+fn v2_to_v3(settings: &mut Map<String, Value>) -> Result<(), String> {
+    let Some(streaming) = settings.remove("streaming") else {
+        return Ok(());
+    };
+    let Some(streams) = streaming.get("max_concurrent_streams").and_then(Value::as_u64).filter(|n| *n >= 1) else {
+        return Ok(());
+    };
+    let engine = settings.entry("engine").or_insert_with(|| Value::Object(Map::new()));
+    let Value::Object(engine) = engine else {
+        return Ok(());
+    };
+    let inputs = engine.get("max_concurrent_inputs")
+        .and_then(Value::as_u64)
+        .unwrap_or(EngineSettings::default().max_concurrent_inputs as u64);
+    if streams < inputs {
+        println!("Moved streaming.max_concurrent_streams ({}) into engine.max_concurrent_inputs", streams);
+        engine.insert("max_concurrent_inputs".to_string(), Value::from(streams));
+    }
+    Ok(())
+}
+
+/// Upgrade raw settings to SETTINGS_VERSION, returning them and the version they had.
+fn migrate(raw: Value) -> Result<(Value, u32), String> {
+    let Value::Object(mut settings) = raw else {
+        return Err("settings must be a JSON object".to_string());
+    };
+    let version = match settings.remove(VERSION_KEY) {
+        None => 1,
+        Some(value) => value.as_u64()
+            .filter(|v| *v >= 1)
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| format!("{}: invalid version {}", VERSION_KEY, value))?,
+    };
+    if version > SETTINGS_VERSION {
+        println!(
+            "Settings were written by a newer version (v{}, this app knows v{}); newer settings are ignored",
+            version, SETTINGS_VERSION
+        );
+    }
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version as usize - 1) {
+        let from = index + 1;
+        migration(&mut settings).map_err(|e| format!("migration from v{} failed: {}", from, e))?;
+        println!("Migrated settings from v{} to v{}", from, from + 1);
+    }
+    Ok((Value::Object(settings), version))
+}
+
+// ==================== Validation ====================
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// A validation failure at `path`.
+struct Violation {
+    path: Vec<Segment>,
+    message: String,
+    /// A required key is absent (repairing drops its parent)
+    missing: bool,
+}
+
+impl Violation {
+    fn key(&self) -> String {
+        let mut key = String::new();
+        for segment in &self.path {
+            match segment {
+                Segment::Key(name) if key.is_empty() => key.push_str(name),
+                Segment::Key(name) => {
+                    key.push('.');
+                    key.push_str(name);
+                }
+                Segment::Index(index) => key.push_str(&format!("[{}]", index)),
+            }
+        }
+        if key.is_empty() {
+            key.push_str("settings");
+        }
+        key
+    }
+
+    fn to_error(&self) -> SettingsError {
+        SettingsError { key: self.key(), message: self.message.clone() }
+    }
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn type_matches(expected: &str, value: &Value) -> bool {
+    match expected {
+        // Integers must deserialize as such, so 1.0 doesn't count
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        other => kind(value) == other,
+    }
+}
+
+/// Checks values against a JSON Schema as generated by schemars.
+struct Validator<'a> {
+    definitions: Option<&'a Map<String, Value>>,
+}
+
+impl<'a> Validator<'a> {
+    /// Follow a `#/definitions/...` reference.
+    fn resolve(&self, schema: &'a Value) -> &'a Value {
+        let target = schema.get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|r| r.strip_prefix("#/definitions/"))
+            .and_then(|name| self.definitions?.get(name));
+        target.unwrap_or(schema)
+    }
+
+    fn violation(path: &[Segment], message: String) -> Violation {
+        Violation { path: path.to_vec(), message, missing: false }
+    }
+
+    fn check(&self, schema: &'a Value, value: &Value, path: &mut Vec<Segment>, out: &mut Vec<Violation>) {
+        let Some(schema) = self.resolve(schema).as_object() else {
+            return;
+        };
+        if let Some(branches) = schema.get("anyOf").or_else(|| schema.get("oneOf")).and_then(Value::as_array) {
+            self.check_branches(branches, value, path, out);
+        }
+        for part in schema.get("allOf").and_then(Value::as_array).into_iter().flatten() {
+            self.check(part, value, path, out);
+        }
+
+        let allowed: Vec<&str> = match schema.get("type") {
+            Some(Value::String(ty)) => vec![ty.as_str()],
+            Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|ty| type_matches(ty, value)) {
+            out.push(Self::violation(path, format!("expected {}, got {}", allowed.join(" or "), kind(value))));
+            return;
+        }
+        if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+            if !options.contains(value) {
+                let options: Vec<String> = options.iter().map(Value::to_string).collect();
+                out.push(Self::violation(path, format!("must be one of {} (got {})", options.join(", "), value)));
+                return;
+            }
+        }
+        if let Some(number) = value.as_f64() {
+            if let Some(min) = schema.get("minimum").and_then(Value::as_f64).filter(|min| number < *min) {
+                out.push(Self::violation(path, format!("must be at least {} (got {})", min, value)));
+            }
+            if let Some(max) = schema.get("maximum").and_then(Value::as_f64).filter(|max| number > *max) {
+                out.push(Self::violation(path, format!("must be at most {} (got {})", max, value)));
+            }
+        }
+
+        match value {
+            Value::Object(fields) => {
+                for name in schema.get("required").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str) {
+                    if !fields.contains_key(name) {
+                        path.push(Segment::Key(name.to_string()));
+                        out.push(Violation { path: path.clone(), message: "is required".to_string(), missing: true });
+                        path.pop();
+                    }
+                }
+                let properties = schema.get("properties").and_then(Value::as_object);
+                let additional = schema.get("additionalProperties").filter(|s| s.is_object());
+                for (name, field) in fields {
+                    let Some(field_schema) = properties.and_then(|p| p.get(name)).or(additional) else {
+                        continue;
+                    };
+                    path.push(Segment::Key(name.clone()));
+                    self.check(field_schema, field, path, out);
+                    path.pop();
+                }
+            }
+            Value::Array(items) => {
+                if let Some(item_schema) = schema.get("items") {
+                    for (index, item) in items.iter().enumerate() {
+                        path.push(Segment::Index(index));
+                        self.check(item_schema, item, path, out);
+                        path.pop();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// `anyOf` / `oneOf`: valid if a branch accepts the value; otherwise
+    /// report the errors of the branch it was meant for, if that is clear.
+    fn check_branches(&self, branches: &'a [Value], value: &Value, path: &mut Vec<Segment>, out: &mut Vec<Violation>) {
+        let accepts = |branch: &'a Value| {
+            let mut errors = Vec::new();
+            self.check(branch, value, &mut path.clone(), &mut errors);
+            errors.is_empty()
+        };
+        if branches.iter().any(accepts) {
+            return;
+        }
+        let intended: Vec<&Value> = branches.iter()
+            .filter(|branch| self.resolve(branch).get("type") != Some(&Value::from("null")))
+            .filter(|branch| self.tag_matches(branch, value))
+            .collect();
+        match intended.as_slice() {
+            [branch] => self.check(branch, value, path, out),
+            _ => out.push(Self::violation(path, format!("{} is not one of the allowed forms", kind(value)))),
+        }
+    }
+
+    /// Whether `value` carries the tag (a single-value enum property) of an enum variant schema.
+    fn tag_matches(&self, branch: &'a Value, value: &Value) -> bool {
+        let properties = self.resolve(branch).get("properties").and_then(Value::as_object);
+        properties.into_iter().flatten().all(|(name, property)| {
+            match property.get("enum").and_then(Value::as_array).map(Vec::as_slice) {
+                Some([tag]) => value.get(name) == Some(tag),
+                _ => true,
+            }
+        })
+    }
+}
+
+/// The JSON Schema of `Settings`.
+fn schema() -> &'static Value {
+    SCHEMA.get_or_init(|| serde_json::to_value(schema_for!(Settings)).unwrap_or(Value::Bool(true)))
+}
+
+fn violations(value: &Value) -> Vec<Violation> {
+    let schema = schema();
+    let validator = Validator { definitions: schema.get("definitions").and_then(Value::as_object) };
+    let mut out = Vec::new();
+    validator.check(schema, value, &mut Vec::new(), &mut out);
+    out
+}
+
+/// Remove the value at `path` (an array entry is dropped, the root emptied).
+fn remove(value: &mut Value, path: &[Segment]) {
+    let Some((last, parents)) = path.split_last() else {
+        *value = Value::Object(Map::new());
+        return;
+    };
+    let mut target = value;
+    for segment in parents {
+        let next = match segment {
+            Segment::Key(name) => target.get_mut(name.as_str()),
+            Segment::Index(index) => target.get_mut(*index),
+        };
+        let Some(next) = next else {
+            return;
+        };
+        target = next;
+    }
+    match (last, target) {
+        (Segment::Key(name), Value::Object(fields)) => {
+            fields.remove(name);
+        }
+        (Segment::Index(index), Value::Array(items)) if *index < items.len() => {
+            items.remove(*index);
+        }
+        _ => {}
+    }
+}
+
+fn describe(errors: &[SettingsError]) -> String {
+    errors.iter().map(|e| format!("{}: {}", e.key, e.message)).collect::<Vec<_>>().join("; ")
+}
+
+// ==================== Loading & Checking ====================
+
+/// Settings from a loaded settings file, and how to write the file back.
+///
+/// Invalid keys fall back to their defaults and are logged. Err if the
+/// file can't be migrated or is still invalid after repairing it.
+pub(crate) fn load(raw: Value) -> Result<(Settings, Rewrite), String> {
+    let (mut value, version) = migrate(raw)?;
+
+    let mut repaired = false;
+    let mut passes = 0;
+    loop {
+        let found = violations(&value);
+        if found.is_empty() {
+            break;
+        }
+        if passes == MAX_REPAIR_PASSES {
+            let errors: Vec<SettingsError> = found.iter().map(Violation::to_error).collect();
+            return Err(format!("still invalid after repair: {}", describe(&errors)));
+        }
+        passes += 1;
+        repaired = true;
+        // Later entries first, so earlier array indices stay valid
+        for violation in found.iter().rev() {
+            println!("Invalid setting {} ({}), using its default", violation.key(), violation.message);
+            let path = if violation.missing { &violation.path[..violation.path.len() - 1] } else { &violation.path[..] };
+            remove(&mut value, path);
+        }
+    }
+
+    let settings = serde_json::from_value(value).map_err(|e| format!("unusable after repair: {}", e))?;
+    let rewrite = if version > SETTINGS_VERSION {
+        Rewrite::None
+    } else if repaired {
+        Rewrite::Repaired
+    } else if version < SETTINGS_VERSION {
+        Rewrite::Migrated
+    } else {
+        Rewrite::None
+    };
+    Ok((settings, rewrite))
+}
+
+/// Settings from a settings file that must be valid as a whole (e.g. in a backup).
+pub(crate) fn parse(raw: Value) -> Result<Settings, String> {
+    let (value, _) = migrate(raw)?;
+    let errors: Vec<SettingsError> = violations(&value).iter().map(Violation::to_error).collect();
+    if !errors.is_empty() {
+        return Err(format!("invalid settings: {}", describe(&errors)));
+    }
+    serde_json::from_value(value).map_err(|e| format!("invalid settings: {}", e))
+}
+
+/// Check settings against the schema, naming every offending key.
+pub(crate) fn validate(settings: &Settings) -> Result<(), EngineError> {
+    let value = serde_json::to_value(settings)
+        .map_err(|e| EngineError::Internal(format!("Failed to serialize settings: {}", e)))?;
+    let errors: Vec<SettingsError> = violations(&value).iter().map(Violation::to_error).collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(EngineError::InvalidRequest(format!("Invalid settings: {}", describe(&errors))))
+    }
+}
+
+// ==================== Tauri Command: get_settings_schema ====================
+
+/// Return the settings layout version and its JSON Schema, for rendering a settings form.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_settings_schema() -> Result<SettingsSchema, EngineError> {
+    Ok(SettingsSchema {
+        version: SETTINGS_VERSION,
+        schema: schema().clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn out_of_range_value_is_rejected_with_its_key() {
+        let mut settings = Settings::default();
+        settings.telemetry.sample_rate = 1.5;
+        match validate(&settings) {
+            Err(EngineError::InvalidRequest(message)) => {
+                assert!(message.contains("telemetry.sample_rate: must be at most 1 (got 1.5)"), "{}", message);
+            }
+            other => panic!("expected invalid_request, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn repair_drops_invalid_list_entries_and_values() {
+        let raw = json!({
+            "schema_version": SETTINGS_VERSION,
+            "telemetry": { "sample_rate": 2.0 },
+            "remote": { "providers": [
+                { "name": "a", "base_url": "https://a.example", "auth": { "type": "api_key" } },
+                { "name": "b", "auth": { "type": "api_key" } },
+            ] },
+        });
+        let (settings, rewrite) = load(raw).expect("repairable");
+        assert_eq!(rewrite, Rewrite::Repaired);
+        assert_eq!(settings.telemetry.sample_rate, 1.0);
+        let names: Vec<&str> = settings.remote.providers.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["a"]);
+    }
+
+    #[test]
+    fn newer_file_is_read_but_not_rewritten() {
+        let raw = json!({
+            "schema_version": SETTINGS_VERSION + 1,
+            "engine": { "auto_start": false },
+            "from_the_future": { "enabled": true },
+        });
+        let (settings, rewrite) = load(raw).expect("readable");
+        assert_eq!(rewrite, Rewrite::None);
+        assert!(!settings.engine.auto_start);
+    }
+
+    #[test]
+    fn unversioned_file_is_migrated() {
+        let (_, rewrite) = load(json!({ "engine": { "auto_start": true } })).expect("migratable");
+        assert_eq!(rewrite, Rewrite::Migrated);
+    }
+
+    #[test]
+    fn stream_cap_is_migrated_into_the_input_limit() {
+        let raw = json!({
+            "schema_version": 2,
+            "engine": { "max_concurrent_inputs": 3 },
+            "streaming": { "max_concurrent_streams": 1 },
+        });
+        let (settings, rewrite) = load(raw).expect("migratable");
+        assert_eq!(rewrite, Rewrite::Migrated);
+        assert_eq!(settings.engine.max_concurrent_inputs, 1);
+
+        let raw = json!({ "schema_version": 2, "streaming": { "max_concurrent_streams": 4 } });
+        let (settings, _) = load(raw).expect("migratable");
+        assert_eq!(settings.engine.max_concurrent_inputs, EngineSettings::default().max_concurrent_inputs);
+    }
+
+    #[test]
+    fn invalid_version_is_unrepairable() {
+        assert!(load(json!({ "schema_version": "two" })).is_err());
+        assert!(load(json!([1, 2])).is_err());
+    }
+}
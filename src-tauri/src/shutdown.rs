@@ -0,0 +1,226 @@
+//! =============================================================================
+//! Ordered Application Shutdown
+//! =============================================================================
+//!
+//! When the app is asked to exit (last window closed, `app.exit()`), exit is
+//! held back while everything is torn down in order within
+//! SHUTDOWN_BUDGET_MS:
+//!
+//!   1. cancel_streams  - end open token streams with an error frame
+//!   2. drain_requests  - refuse new requests, wait briefly for in-flight ones
+//!   3. stop_engine     - graceful /stop if time allows, otherwise kill
+//!   4. flush           - persist metrics, stop the heartbeat
+//!   5. remove_files    - delete the engine socket if it was left behind and
+//!      the temporary files of abandoned requests (see temp_files)
+//!
+//! Steps 1-3 share the budget; a step that runs out of time is cut short
+//! (the engine is then killed). Steps 4-5 are local and run even once the
+//! budget is spent. The outcome is logged as a single `shutdown_report`
+//! JSON line.
+//!
+//! Termination signals (Ctrl-C, and SIGTERM / SIGHUP on Unix, e.g. at
+//! logout or from `kill`) bypass the run loop, so they are turned into an
+//! exit request and go through the same sequence instead of orphaning the
+//! engine and its socket.
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, RunEvent};
+use tauri::async_runtime::Mutex;
+use std::future::Future;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::heartbeat::Heartbeat;
+use crate::metrics_history::MetricsHistory;
+use crate::temp_files::TempFiles;
+use crate::{drain, get_socket_path, streaming, teardown_engine, PythonProcess, SHUTDOWN_GRACE_MS};
+
+// ==================== Configuration Constants ====================
+
+/// Total time the shutdown sequence may take before exit proceeds anyway
+const SHUTDOWN_BUDGET_MS: u64 = 5_000;
+
+/// Limit for the local steps (flush, remove_files), which run even past the budget
+const LOCAL_STEP_TIMEOUT_MS: u64 = 2_000;
+
+/// Set once the shutdown sequence has started / finished
+static SHUTDOWN_STARTED: AtomicBool = AtomicBool::new(false);
+static SHUTDOWN_DONE: AtomicBool = AtomicBool::new(false);
+
+// ==================== Report ====================
+
+/// Outcome of one shutdown step.
+#[derive(Debug, Serialize)]
+struct StepReport {
+    step: &'static str,
+    duration_ms: u64,
+    completed: bool,
+    detail: String,
+}
+
+/// Logged once at the end of the shutdown sequence.
+#[derive(Debug, Serialize)]
+struct ShutdownReport {
+    total_ms: u64,
+    budget_ms: u64,
+    within_budget: bool,
+    steps: Vec<StepReport>,
+}
+
+impl ShutdownReport {
+    /// Run `step` with at most `limit`, recording its outcome.
+    async fn run_step<F>(&mut self, step: &'static str, limit: Duration, work: F) -> bool
+    where
+        F: Future<Output = String>,
+    {
+        let started = Instant::now();
+        let (completed, detail) = match tokio::time::timeout(limit, work).await {
+            Ok(detail) => (true, detail),
+            Err(_) => (false, format!("cut short after {} ms", limit.as_millis())),
+        };
+        self.steps.push(StepReport {
+            step,
+            duration_ms: started.elapsed().as_millis() as u64,
+            completed,
+            detail,
+        });
+        completed
+    }
+}
+
+// ==================== Shutdown Sequence ====================
+
+/// Run the ordered shutdown sequence and log its report.
+async fn run(app: &AppHandle) {
+    let started = Instant::now();
+    let deadline = started + Duration::from_millis(SHUTDOWN_BUDGET_MS);
+    let remaining = || deadline.saturating_duration_since(Instant::now());
+    let mut report = ShutdownReport { total_ms: 0, budget_ms: SHUTDOWN_BUDGET_MS, within_budget: true, steps: Vec::new() };
+    println!("Shutting down...");
+
+    report.run_step("cancel_streams", remaining(), async {
+        format!("{} stream(s) cancelled", streaming::cancel_all())
+    }).await;
+
+    let state = app.state::<Mutex<PythonProcess>>();
+    let (in_flight, draining) = {
+        let proc_state = state.lock().await;
+        (proc_state.in_flight.clone(), proc_state.draining.clone())
+    };
+    draining.store(true, Ordering::SeqCst);
+    // Leave at least half of what remains for stopping the engine
+    report.run_step("drain_requests", remaining(), async {
+        let abandoned = drain::wait_for_in_flight(app, &in_flight, remaining() / 2).await;
+        format!("{} request(s) abandoned", abandoned)
+    }).await;
+
+    let graceful = remaining() > Duration::from_millis(SHUTDOWN_GRACE_MS * 2);
+    let stopped = report.run_step("stop_engine", remaining(), async {
+        let mut proc_state = state.lock().await;
+        if !*proc_state.is_running.lock().await && proc_state.child.is_none() {
+            return "engine not running".to_string();
+        }
+        teardown_engine(&mut proc_state, graceful).await;
+        if graceful { "stopped gracefully" } else { "killed" }.to_string()
+    }).await;
+    if !stopped {
+        // Out of budget mid-/stop: make sure the process doesn't outlive us
+        teardown_engine(&mut *state.lock().await, false).await;
+    }
+
+    report.run_step("flush", Duration::from_millis(LOCAL_STEP_TIMEOUT_MS), async {
+        app.state::<Mutex<MetricsHistory>>().lock().await.flush();
+        let heartbeat = app.state::<std::sync::Mutex<Option<Heartbeat>>>();
+        if let Some(heartbeat) = heartbeat.lock().ok().and_then(|mut h| h.take()) {
+            heartbeat.stop();
+        }
+        "metrics flushed, heartbeat stopped".to_string()
+    }).await;
+
+    report.run_step("remove_files", Duration::from_millis(LOCAL_STEP_TIMEOUT_MS), async {
+        let socket_path = get_socket_path();
+        let socket = if cfg!(unix) && Path::new(&socket_path).exists() {
+            match std::fs::remove_file(&socket_path) {
+                Ok(()) => format!("removed {}", socket_path),
+                Err(e) => format!("failed to remove {}: {}", socket_path, e),
+            }
+        } else {
+            "no socket to remove".to_string()
+        };
+        format!("{}, {} temporary file(s) removed", socket, app.state::<TempFiles>().remove_all())
+    }).await;
+
+    report.total_ms = started.elapsed().as_millis() as u64;
+    report.within_budget = report.total_ms <= SHUTDOWN_BUDGET_MS;
+    println!(
+        "shutdown_report: {}",
+        serde_json::to_string(&report).unwrap_or_else(|e| format!("<unserializable: {}>", e))
+    );
+}
+
+// ==================== Termination Signals ====================
+
+/// Wait for the next termination signal and return its name.
+#[cfg(unix)]
+async fn termination_signal() -> std::io::Result<&'static str> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut hangup = signal(SignalKind::hangup())?;
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => result.map(|_| "SIGINT"),
+        _ = terminate.recv() => Ok("SIGTERM"),
+        _ = hangup.recv() => Ok("SIGHUP"),
+    }
+}
+
+/// Wait for the next termination signal and return its name.
+#[cfg(not(unix))]
+async fn termination_signal() -> std::io::Result<&'static str> {
+    tokio::signal::ctrl_c().await.map(|_| "Ctrl-C")
+}
+
+/// Turn termination signals into an exit request, so they run the shutdown sequence.
+pub(crate) fn watch_signals(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match termination_signal().await {
+            Ok(signal) => {
+                println!("Received {}, exiting...", signal);
+                app.exit(0);
+            }
+            Err(e) => println!("Failed to listen for termination signals: {}", e),
+        }
+    });
+}
+
+// ==================== Run Event Hook ====================
+
+/// Hook for the app's run loop: holds exit back until shutdown has finished.
+pub(crate) fn handle_run_event(app: &AppHandle, event: &RunEvent) {
+    match event {
+        RunEvent::ExitRequested { code, api, .. } => {
+            if SHUTDOWN_DONE.load(Ordering::SeqCst) {
+                return;
+            }
+            api.prevent_exit();
+            if SHUTDOWN_STARTED.swap(true, Ordering::SeqCst) {
+                return;
+            }
+            let code = code.unwrap_or(0);
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                run(&app).await;
+                SHUTDOWN_DONE.store(true, Ordering::SeqCst);
+                app.exit(code);
+            });
+        }
+        // Exit without a prior ExitRequested: run the sequence synchronously
+        RunEvent::Exit if !SHUTDOWN_STARTED.swap(true, Ordering::SeqCst) => {
+            tauri::async_runtime::block_on(run(app));
+            SHUTDOWN_DONE.store(true, Ordering::SeqCst);
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,102 @@
+//! =============================================================================
+//! Socket File Watch
+//! =============================================================================
+//!
+//! When the engine crashes hard its socket file is sometimes gone before the
+//! process's Terminated event arrives, and an engine we attached to (see
+//! dev_engine) has no child process to report an exit at all. The socket's
+//! directory is therefore watched (inotify, FSEvents/kqueue via notify) for
+//! as long as the engine is the current one:
+//!
+//!   socket file removed while the engine is live  →  crash (see crash_supervisor)
+//!
+//! A deletion only counts if the file is really gone afterwards, so a socket
+//! re-created in place is fine. Deletions after a deliberate stop, restart
+//! or handoff don't count: those retire the engine's generation first.
+//! Windows named pipes aren't files and aren't watched.
+
+use tauri::AppHandle;
+#[cfg(unix)]
+use tauri::{Manager, async_runtime::Mutex};
+#[cfg(unix)]
+use std::sync::atomic::Ordering;
+
+#[cfg(unix)]
+use crate::PythonProcess;
+
+/// How often the watch checks whether its engine is still the current one
+#[cfg(unix)]
+const WATCH_CHECK_INTERVAL_SECS: u64 = 5;
+
+/// Whether engine `generation` is still the current one.
+#[cfg(unix)]
+async fn is_current(app: &AppHandle, generation: u64) -> bool {
+    let state = app.state::<Mutex<PythonProcess>>();
+    let current = state.lock().await.engine_generation.load(Ordering::SeqCst);
+    current == generation
+}
+
+/// Watch the socket of engine `generation` for deletion (runs in the background).
+#[cfg(unix)]
+pub(crate) fn watch(app: &AppHandle, socket_path: &str, generation: u64) {
+    use notify::{RecursiveMode, Watcher};
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    let socket = PathBuf::from(socket_path);
+    let Some(dir) = socket.parent().map(PathBuf::from) else {
+        return;
+    };
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = match notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let _ = tx.send(event);
+    }) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            println!("Cannot watch the engine socket: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+        println!("Cannot watch {:?} for socket deletion: {}", dir, e);
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        // Owned by the task: dropping it ends the watch
+        let _watcher = watcher;
+        loop {
+            let event = tokio::select! {
+                event = rx.recv() => event,
+                _ = tokio::time::sleep(Duration::from_secs(WATCH_CHECK_INTERVAL_SECS)) => {
+                    if !is_current(&app, generation).await {
+                        return;
+                    }
+                    continue;
+                }
+            };
+            let Some(event) = event else {
+                return;
+            };
+            let touches_socket = match event {
+                Ok(event) => event.paths.contains(&socket),
+                // Events may have been dropped; look at the file itself
+                Err(_) => true,
+            };
+            if !touches_socket || std::fs::symlink_metadata(&socket).is_ok() {
+                continue;
+            }
+            if !is_current(&app, generation).await {
+                return;
+            }
+            println!("AI Engine socket {:?} was deleted", socket);
+            crate::crash_supervisor::on_socket_deleted(&app, generation).await;
+            return;
+        }
+    });
+}
+
+/// Named pipes can't be watched as files.
+#[cfg(not(unix))]
+pub(crate) fn watch(_app: &AppHandle, _socket_path: &str, _generation: u64) {}
@@ -0,0 +1,91 @@
+//! =============================================================================
+//! Stale Socket and Orphan Cleanup
+//! =============================================================================
+//!
+//! If the app crashed or was killed, its engine may have outlived it and the
+//! socket file may still exist. Left alone, the next start would see the old
+//! socket as "ready" and talk to a dead endpoint (or to the orphan).
+//!
+//! Every spawn records the engine's PID in `<namespace>.engine.pid` in the
+//! app data dir. Before the next spawn, `cleanup`:
+//!
+//!   1. probes an existing endpoint with a real connection and GET /health;
+//!      an engine that still answers is asked to /stop
+//!   2. kills the process from the PID file if it is still alive and is an
+//!      ai-engine binary (a reused PID belonging to something else is left
+//!      alone)
+//!   3. unlinks a socket file that is still left behind (Unix)
+
+use tauri::{AppHandle, Manager};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use sysinfo::{Pid, ProcessesToUpdate, System};
+
+use crate::{ipc, runtime_identity, socket_http_get, socket_http_post, ENGINE_SIDECAR, SHUTDOWN_GRACE_MS};
+
+/// Time allowed for the health probe of a leftover endpoint
+const STALE_PROBE_TIMEOUT_MS: u64 = 1_000;
+
+/// File recording the PID of the last spawned engine.
+fn pid_file(app: &AppHandle) -> PathBuf {
+    let data_dir = app.path().app_data_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join("ai-engine"));
+    data_dir.join(format!("{}.engine.pid", runtime_identity::namespace(app)))
+}
+
+/// Remember the PID of a freshly spawned engine.
+pub(crate) fn record_pid(app: &AppHandle, pid: u32) {
+    let path = pid_file(app);
+    let result = path.parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, pid.to_string()));
+    if let Err(e) = result {
+        println!("Failed to write engine PID file {:?}: {}", path, e);
+    }
+}
+
+/// Kill the engine recorded in the PID file if it is still running.
+fn kill_orphan(app: &AppHandle) {
+    let path = pid_file(app);
+    let Some(pid) = std::fs::read_to_string(&path).ok().and_then(|s| s.trim().parse::<u32>().ok()) else {
+        return;
+    };
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+
+    match system.process(pid) {
+        Some(process) if process.name().to_string_lossy().starts_with(ENGINE_SIDECAR) => {
+            if process.kill() {
+                println!("Killed orphaned AI Engine process {}", pid);
+            } else {
+                println!("Failed to kill orphaned AI Engine process {}", pid);
+            }
+        }
+        Some(process) => println!("PID {} now belongs to {:?}, leaving it alone", pid, process.name()),
+        None => {}
+    }
+    let _ = std::fs::remove_file(&path);
+}
+
+/// Remove leftovers of a previous engine at `socket_path` before spawning a new one.
+pub(crate) async fn cleanup(app: &AppHandle, socket_path: &str) {
+    let probe = tokio::time::timeout(Duration::from_millis(STALE_PROBE_TIMEOUT_MS), async {
+        ipc::connect(socket_path).await.ok()?;
+        socket_http_get(socket_path, "/health").await.ok()
+    });
+    if let Ok(Some(_)) = probe.await {
+        println!("An orphaned AI Engine is still serving {}, stopping it", socket_path);
+        let _ = socket_http_post(socket_path, "/stop", &serde_json::json!({})).await;
+        tokio::time::sleep(Duration::from_millis(SHUTDOWN_GRACE_MS)).await;
+    }
+
+    kill_orphan(app);
+
+    if cfg!(unix) && Path::new(socket_path).exists() {
+        match std::fs::remove_file(socket_path) {
+            Ok(()) => println!("Removed stale socket {}", socket_path),
+            Err(e) => println!("Failed to remove stale socket {}: {}", socket_path, e),
+        }
+    }
+}
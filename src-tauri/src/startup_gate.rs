@@ -0,0 +1,105 @@
+//! =============================================================================
+//! Post-start Warm-up and Reconnect Storm Protection
+//! =============================================================================
+//!
+//! Right after the engine (re)starts, every client of the socket tends to hit
+//! it at once: status polling, retried requests, user input. To keep a freshly
+//! started engine from being knocked over again:
+//!
+//!   • Warm-up window - for WARMUP_MS after the socket comes up only status
+//!     class requests (health checks, /status) go through
+//!   • Gradual release - requests held back during warm-up are let through
+//!     one per RELEASE_INTERVAL_MS, each with a random jitter
+//!   • Jittered reconnects - failed status polls back off exponentially with
+//!     jitter instead of retrying in lockstep
+//!
+//! Once the held-back requests have been released the gate stays open until
+//! the next start.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::settings::EndpointClass;
+
+// ==================== Configuration Constants ====================
+
+/// Time after startup during which only status requests are admitted
+const WARMUP_MS: u64 = 3_000;
+
+/// Spacing between requests released after the warm-up window
+const RELEASE_INTERVAL_MS: u64 = 100;
+
+/// Maximum random delay added to each released request
+const RELEASE_JITTER_MS: u64 = 250;
+
+/// Base and cap for the status poll reconnect backoff
+const RECONNECT_BASE_MS: u64 = 250;
+const RECONNECT_MAX_MS: u64 = 8_000;
+
+// ==================== Startup Gate ====================
+
+/// Admission control for requests right after an engine start.
+#[derive(Default)]
+pub struct StartupGate {
+    window: Mutex<Option<ReleaseWindow>>,
+}
+
+struct ReleaseWindow {
+    warm_until: Instant,
+    /// Requests released (or scheduled for release) since warm-up began
+    released: u32,
+}
+
+impl StartupGate {
+    /// Open a new warm-up window; called once the engine socket is ready.
+    pub fn begin(&self) {
+        let mut window = self.window.lock().unwrap_or_else(|e| e.into_inner());
+        *window = Some(ReleaseWindow {
+            warm_until: Instant::now() + Duration::from_millis(WARMUP_MS),
+            released: 0,
+        });
+    }
+
+    /// Wait until a request of `class` may be sent to the engine.
+    pub async fn admit(&self, class: EndpointClass) {
+        if class == EndpointClass::Status {
+            return;
+        }
+
+        let wait = {
+            let mut window = self.window.lock().unwrap_or_else(|e| e.into_inner());
+            let Some(current) = window.as_mut() else {
+                return;
+            };
+            let now = Instant::now();
+            let slot = current.warm_until + Duration::from_millis(RELEASE_INTERVAL_MS) * current.released;
+            if now >= slot {
+                // Everything held back has been released; stop gating
+                *window = None;
+                return;
+            }
+            current.released += 1;
+            slot - now + jitter(RELEASE_JITTER_MS)
+        };
+
+        tokio::time::sleep(wait).await;
+    }
+}
+
+// ==================== Jitter ====================
+
+/// Random delay in [0, max_ms).
+pub(crate) fn jitter(max_ms: u64) -> Duration {
+    Duration::from_millis(fastrand::u64(0..max_ms.max(1)))
+}
+
+/// Delay before the next status poll after `failures` consecutive failures.
+pub(crate) fn reconnect_backoff(failures: u32) -> Duration {
+    if failures == 0 {
+        return Duration::ZERO;
+    }
+    let exponential = RECONNECT_BASE_MS.saturating_mul(1 << failures.min(16));
+    let capped = exponential.min(RECONNECT_MAX_MS);
+    // Half fixed, half random: retries spread out but never come back too soon
+    Duration::from_millis(capped / 2) + jitter(capped / 2)
+}
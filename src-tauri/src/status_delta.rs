@@ -0,0 +1,124 @@
+//! =============================================================================
+//! Delta-encoded Status Events
+//! =============================================================================
+//!
+//! The /status payload (models, tasks, memory, queues) is several KB and is
+//! polled every second. Instead of emitting it in full each time, every
+//! changed status gets a version and is emitted as:
+//!
+//!   python_status        { "version": 40, "status": { …full… } }
+//!   python_status_delta  { "version": 41, "base_version": 40,
+//!                          "patch": [ { "op": "replace", "path": "/count", "value": 7 } ] }
+//!
+//! A delta is an RFC 6902 JSON patch against the last status the frontend
+//! acknowledged with `ack_status(version)`. A full snapshot is sent instead
+//! when nothing has been acknowledged yet, every FULL_SNAPSHOT_EVERY versions,
+//! and when the patch wouldn't be smaller than the status itself. A frontend
+//! that lost track (missed event, base it doesn't have) calls
+//! `get_full_status` to resync. Unchanged statuses aren't emitted at all.
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Manager, State};
+use tauri::async_runtime::Mutex;
+use std::collections::VecDeque;
+
+use crate::error::EngineError;
+use crate::events::{self, PythonStatus, PythonStatusDelta};
+
+/// Send a full snapshot at least every this many versions
+const FULL_SNAPSHOT_EVERY: u64 = 30;
+
+/// Recent versions kept so a late acknowledgement can still become the base
+const RECENT_VERSIONS: usize = 16;
+
+// ==================== State ====================
+
+/// Status versions and the frontend's acknowledged base.
+#[derive(Default)]
+pub struct StatusDeltaState {
+    /// Most recent statuses, newest last
+    recent: VecDeque<(u64, serde_json::Value)>,
+    /// Last version acknowledged by the frontend
+    acked: Option<(u64, serde_json::Value)>,
+    /// Version of the last full snapshot sent
+    last_snapshot: u64,
+}
+
+/// Response of `get_full_status`.
+#[derive(Debug, Serialize, Type)]
+pub struct FullStatus {
+    pub version: u64,
+    pub status: serde_json::Value,
+}
+
+// ==================== Publishing ====================
+
+/// Version and emit a polled /status payload as a delta or a full snapshot.
+///
+/// Called by the status polling loop after every successful poll.
+pub(crate) async fn publish(app: &AppHandle, status: serde_json::Value) {
+    let state = app.state::<Mutex<StatusDeltaState>>();
+    let mut state = state.lock().await;
+    let latest = state.recent.back().map(|(version, _)| *version).unwrap_or(0);
+    if state.recent.back().is_some_and(|(_, last)| *last == status) {
+        return;
+    }
+    let version = latest + 1;
+
+    let patch = match &state.acked {
+        Some((base_version, base)) if version - state.last_snapshot < FULL_SNAPSHOT_EVERY => {
+            let patch = json_patch::diff(base, &status);
+            let smaller = serde_json::to_string(&patch).map(|p| p.len()).unwrap_or(usize::MAX)
+                < serde_json::to_string(&status).map(|s| s.len()).unwrap_or(0);
+            smaller.then_some((*base_version, patch))
+        }
+        _ => None,
+    };
+
+    if state.recent.len() == RECENT_VERSIONS {
+        state.recent.pop_front();
+    }
+    state.recent.push_back((version, status.clone()));
+    if patch.is_none() {
+        state.last_snapshot = version;
+    }
+    drop(state);
+
+    match patch {
+        Some((base_version, patch)) => events::emit(app, PythonStatusDelta { version, base_version, patch }),
+        None => events::emit(app, PythonStatus { version, status }),
+    }
+}
+
+/// The latest status in full (version 0 and `{}` before the first poll).
+pub(crate) fn latest(state: &StatusDeltaState) -> FullStatus {
+    let (version, status) = state.recent.back().cloned().unwrap_or((0, serde_json::json!({})));
+    FullStatus { version, status }
+}
+
+// ==================== Tauri Commands ====================
+
+/// Return the latest status in full (to resync after a missed delta).
+#[tauri::command]
+#[specta::specta]
+pub async fn get_full_status(state: State<'_, Mutex<StatusDeltaState>>) -> Result<FullStatus, EngineError> {
+    Ok(latest(&*state.lock().await))
+}
+
+/// Record that the frontend has applied status `version`; later deltas are based on it.
+#[tauri::command]
+#[specta::specta]
+pub async fn ack_status(version: u64, state: State<'_, Mutex<StatusDeltaState>>) -> Result<(), EngineError> {
+    let mut state = state.lock().await;
+    if state.acked.as_ref().is_some_and(|(acked, _)| *acked >= version) {
+        return Ok(());
+    }
+    match state.recent.iter().find(|(v, _)| *v == version).cloned() {
+        Some(entry) => {
+            state.acked = Some(entry);
+            Ok(())
+        }
+        None => Err(EngineError::InvalidRequest(format!("Unknown or expired status version {}", version))),
+    }
+}
@@ -0,0 +1,101 @@
+//! =============================================================================
+//! Accessible Status Summaries
+//! =============================================================================
+//!
+//! Screen-reader users need one concise sentence instead of a stream of raw
+//! status events. Each /status poll is parsed into `EngineStatus` and turned
+//! into a sentence like:
+//!
+//!   "Engine ready, llama-3 8B loaded, 2 requests queued, 450 MB memory"
+//!
+//! `get_status_summary()` returns the current sentence; `status_summary_changed`
+//! is emitted only when the sentence differs from the previous one.
+
+use serde::Deserialize;
+use tauri::{AppHandle, Manager, State};
+use tauri::async_runtime::Mutex;
+
+use crate::error::EngineError;
+use crate::events::{self, StatusSummaryChanged};
+use crate::PythonProcess;
+
+// ==================== Typed Status ====================
+
+/// The /status fields relevant to the summary; all optional.
+#[derive(Debug, Default, Deserialize)]
+pub struct EngineStatus {
+    /// Engine state, e.g. "ready", "loading", "busy"
+    #[serde(default)]
+    pub state: Option<String>,
+    /// Display name of the loaded model
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Requests waiting in the engine's queue
+    #[serde(default)]
+    pub queued: Option<u64>,
+    /// Resident memory of the engine process
+    #[serde(default)]
+    pub memory_mb: Option<u64>,
+}
+
+impl EngineStatus {
+    /// Parse a /status payload; unknown fields are ignored, a malformed payload yields an empty status.
+    pub fn from_value(value: &serde_json::Value) -> EngineStatus {
+        serde_json::from_value(value.clone()).unwrap_or_default()
+    }
+
+    /// One short, human-readable sentence describing this status.
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!("Engine {}", self.state.as_deref().unwrap_or("running"))];
+        if let Some(model) = &self.model {
+            parts.push(format!("{} loaded", model));
+        }
+        match self.queued {
+            Some(1) => parts.push("1 request queued".to_string()),
+            Some(n) if n > 1 => parts.push(format!("{} requests queued", n)),
+            _ => {}
+        }
+        if let Some(memory_mb) = self.memory_mb {
+            parts.push(format!("{} MB memory", memory_mb));
+        }
+        parts.join(", ")
+    }
+}
+
+// ==================== Summary Tracking ====================
+
+/// Last summary sentence produced by the status poller.
+#[derive(Default)]
+pub struct StatusSummaryState {
+    last: Option<String>,
+}
+
+/// Regenerate the summary from a /status payload; emits when it changed.
+///
+/// Called by the status polling loop after every successful poll.
+pub(crate) async fn update(app: &AppHandle, status: &serde_json::Value) {
+    let summary = EngineStatus::from_value(status).summary();
+    let state = app.state::<Mutex<StatusSummaryState>>();
+    let mut state = state.lock().await;
+    if state.last.as_deref() != Some(summary.as_str()) {
+        state.last = Some(summary.clone());
+        drop(state);
+        events::emit(app, StatusSummaryChanged { summary });
+    }
+}
+
+// ==================== Tauri Command: get_status_summary ====================
+
+/// Return a short sentence describing the engine's current state.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_status_summary(
+    summary: State<'_, Mutex<StatusSummaryState>>,
+    process: State<'_, Mutex<PythonProcess>>,
+) -> Result<String, EngineError> {
+    let is_running = *process.lock().await.is_running.lock().await;
+    if !is_running {
+        return Ok("Engine stopped".to_string());
+    }
+    Ok(summary.lock().await.last.clone().unwrap_or_else(|| "Engine starting".to_string()))
+}
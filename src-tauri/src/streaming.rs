@@ -0,0 +1,727 @@
+//! =============================================================================
+//! Ordered Streaming Responses
+//! =============================================================================
+//!
+//! Streamed engine output reaches the frontend over a `tauri::ipc::Channel`
+//! as a sequence of frames:
+//!
+//!   { "type": "chunk", "stream_id": 3, "seq": 0, "data": "Hel" }
+//!   { "type": "chunk", "stream_id": 3, "seq": 1, "data": "lo" }
+//!   { "type": "end",   "stream_id": 3, "total_chunks": 2, "checksum": "f7d18982", "error": null }
+//!
+//! Ordering guarantees:
+//!   • Each Channel has exactly one writer (`StreamWriter`, not Clone), which
+//!     sends frames in order, so `seq` is gapless and increasing
+//!   • The terminal `end` frame carries the chunk count and a CRC32 (hex) of
+//!     all chunk `data` concatenated as UTF-8, so the frontend can detect
+//!     missing, duplicated or reordered chunks
+//!
+//! Engine protocol: POST /input with `"stream": true` answers with one
+//! `{ "token": "…" }` object per line, finished by `{ "done": true }`,
+//! either as NDJSON or as SSE (`data: {…}` lines, `data: [DONE]` also ends
+//! the stream). The body is read chunk by chunk as it arrives and split into
+//! records by the NDJSON decoder (see ndjson), so chunked transfer encoding
+//! works; malformed or oversized records are skipped and reported as
+//! `stream_record_skipped`.
+//!
+//! Integrity: the `done` record carries a trailer over all tokens
+//! concatenated as UTF-8,
+//!
+//!   { "done": true, "length": 5, "checksum": "f7d18982" }
+//!
+//! with the byte length and CRC32 (hex). A stream whose trailer doesn't
+//! match, or that ends without a `done` record (the engine died mid-write),
+//! is truncated: the text received so far is kept, the session message is
+//! stored as partial (see session_models), `stream_truncated` is emitted and
+//! the terminal frame carries the reason as its error. A `done` record
+//! without a trailer ends the stream normally but leaves it unverified: the
+//! session message is stored as partial and the response reports
+//! `"complete": false, "verified": false`.
+//!
+//! Streams are started by `stream_input_to_python`, or by
+//! `send_input_to_python` when it is given an `on_token` channel.
+//!
+//! Moderation: with `settings.moderation.strictness` other than off, tokens
+//! are held back until the stream has finished and its full text has passed
+//! moderation (see moderation), then sent as a single chunk. A blocked
+//! stream sends no chunks; its terminal frame carries the policy message.
+//!
+//! Concurrency: any number of streams can be open at once, each with its own
+//! Channel, engine connection and correlation id (cancel one with
+//! `cancel_request`, see requests). A streamed /input takes one of the
+//! /input slots like any other input (see input_limiter), so streams count
+//! against `settings.engine.max_concurrent_inputs` and wait in the same
+//! queue. Readers yield after every chunk so one fast stream can't starve
+//! the others.
+//! `cancel_all()` ends every open stream with an error frame (used on exit).
+//!
+//! Resuming: a webview reload loses its Channels, but the streams keep
+//! running. Open streams are listed by `resync` (see resync) with the seq of
+//! their next chunk; `resume_stream(stream_id, from_seq, on_frame)` replays
+//! the chunks from `from_seq` on to a new Channel and continues there.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::ipc::{Channel, JavaScriptChannelId};
+use tauri::{AppHandle, Manager, State, Webview};
+use tauri::async_runtime::Mutex;
+use hyper::body::HttpBody;
+use tokio::sync::watch;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex as StdMutex, OnceLock};
+use std::time::Instant;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use crate::analytics;
+use crate::error::EngineError;
+use crate::events::{self, StreamTruncated};
+use crate::generation;
+use crate::history;
+use crate::hooks::{EngineCall, RequestHooks};
+use crate::input_limiter;
+use crate::moderation;
+use crate::ndjson::{self, Decoded, NdjsonDecoder};
+use crate::recorder::{self, Frame};
+use crate::requests::ActiveRequests;
+use crate::session_models::{self, InputRoute};
+use crate::templates;
+use crate::{drain, error_response, get_socket_path, socket_http_send_with_headers, update_activity_impl, PythonProcess};
+
+/// Source of process-unique stream ids
+static NEXT_STREAM_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Number of StreamWriters currently alive
+static ACTIVE_STREAMS: AtomicUsize = AtomicUsize::new(0);
+
+/// Set to true to cancel all open streams
+static CANCEL_STREAMS: OnceLock<watch::Sender<bool>> = OnceLock::new();
+
+fn cancel_signal() -> &'static watch::Sender<bool> {
+    CANCEL_STREAMS.get_or_init(|| watch::channel(false).0)
+}
+
+// ==================== Cancellation ====================
+
+/// Cancel every open stream and refuse new ones; returns how many were open.
+pub(crate) fn cancel_all() -> usize {
+    cancel_signal().send_replace(true);
+    ACTIVE_STREAMS.load(Ordering::SeqCst)
+}
+
+// ==================== Frame Types ====================
+
+/// A frame sent over a streaming Channel.
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamFrame {
+    Chunk {
+        stream_id: u64,
+        seq: u64,
+        data: String,
+    },
+    End {
+        stream_id: u64,
+        total_chunks: u64,
+        checksum: String,
+        error: Option<String>,
+    },
+}
+
+// ==================== Stream Writer ====================
+
+/// Where a stream's frames go, plus the chunks sent so far for resuming.
+struct StreamTarget {
+    channel: Channel<StreamFrame>,
+    chunks: Vec<String>,
+    request_id: Option<String>,
+    started: Instant,
+}
+
+/// Targets of the streams currently open, by stream id
+static OPEN_STREAMS: StdMutex<BTreeMap<u64, Arc<StdMutex<StreamTarget>>>> = StdMutex::new(BTreeMap::new());
+
+fn lock_target(target: &StdMutex<StreamTarget>) -> std::sync::MutexGuard<'_, StreamTarget> {
+    target.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The single writer for one streaming Channel.
+///
+/// Owns the Channel, numbers chunks in send order and folds them into the
+/// checksum reported by the terminal frame. The chunks are kept until the
+/// stream ends so `resume_stream` can move it to a new Channel.
+pub struct StreamWriter {
+    stream_id: u64,
+    target: Arc<StdMutex<StreamTarget>>,
+    next_seq: u64,
+    hasher: crc32fast::Hasher,
+    /// Chunks held back until moderation passed the text (None = sent as they come)
+    held: Option<String>,
+}
+
+impl StreamWriter {
+    pub fn new(channel: Channel<StreamFrame>) -> StreamWriter {
+        ACTIVE_STREAMS.fetch_add(1, Ordering::SeqCst);
+        let stream_id = NEXT_STREAM_ID.fetch_add(1, Ordering::SeqCst);
+        let target = Arc::new(StdMutex::new(StreamTarget {
+            channel,
+            chunks: Vec::new(),
+            request_id: None,
+            started: Instant::now(),
+        }));
+        OPEN_STREAMS.lock().unwrap_or_else(|e| e.into_inner()).insert(stream_id, target.clone());
+        StreamWriter {
+            stream_id,
+            target,
+            next_seq: 0,
+            hasher: crc32fast::Hasher::new(),
+            held: None,
+        }
+    }
+
+    pub fn stream_id(&self) -> u64 {
+        self.stream_id
+    }
+
+    /// Associate the stream with the correlation id of its request.
+    pub fn bind_request(&self, request_id: &str) {
+        lock_target(&self.target).request_id = Some(request_id.to_string());
+    }
+
+    /// Keep chunks back instead of sending them, until `release`.
+    pub fn hold(&mut self) {
+        self.held.get_or_insert_with(String::new);
+    }
+
+    /// Stop holding chunks back and send `text` in place of the held ones.
+    ///
+    /// Does nothing unless the writer was holding.
+    pub fn release(&mut self, text: String) -> Result<(), String> {
+        if self.held.take().is_none() || text.is_empty() {
+            return Ok(());
+        }
+        self.send_chunk(text)
+    }
+
+    /// Send the next chunk in sequence (or hold it back, see `hold`).
+    pub fn send_chunk(&mut self, data: String) -> Result<(), String> {
+        if let Some(held) = self.held.as_mut() {
+            held.push_str(&data);
+            return Ok(());
+        }
+        self.hasher.update(data.as_bytes());
+        let frame = StreamFrame::Chunk { stream_id: self.stream_id, seq: self.next_seq, data: data.clone() };
+        self.next_seq += 1;
+        let mut target = lock_target(&self.target);
+        target.chunks.push(data);
+        target.channel
+            .send(frame)
+            .map_err(|e| format!("Failed to send stream chunk: {}", e))
+    }
+
+    /// Send the terminal frame; `error` marks a stream that ended abnormally.
+    pub fn finish(self, error: Option<String>) -> Result<(), String> {
+        let frame = StreamFrame::End {
+            stream_id: self.stream_id,
+            total_chunks: self.next_seq,
+            checksum: format!("{:08x}", self.hasher.clone().finalize()),
+            error,
+        };
+        lock_target(&self.target).channel
+            .send(frame)
+            .map_err(|e| format!("Failed to send stream end frame: {}", e))
+    }
+}
+
+impl Drop for StreamWriter {
+    fn drop(&mut self) {
+        OPEN_STREAMS.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.stream_id);
+        ACTIVE_STREAMS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// An optional streaming Channel command argument (e.g. `on_token`).
+///
+/// Tauri can't take `Option<Channel<_>>` directly, so the argument arrives
+/// as a channel id; the bindings still type it as `Channel<StreamFrame>`.
+#[derive(Deserialize)]
+#[serde(transparent)]
+pub struct StreamChannel(JavaScriptChannelId);
+
+impl StreamChannel {
+    /// Open a writer on the channel in `webview`.
+    pub fn writer(self, webview: Webview) -> StreamWriter {
+        StreamWriter::new(self.0.channel_on(webview))
+    }
+}
+
+impl specta::Type for StreamChannel {
+    fn inline(type_map: &mut specta::TypeCollection, generics: specta::Generics) -> specta::datatype::DataType {
+        <Channel<StreamFrame> as specta::Type>::inline(type_map, generics)
+    }
+
+    fn reference(type_map: &mut specta::TypeCollection, generics: &[specta::datatype::DataType]) -> specta::datatype::reference::Reference {
+        <Channel<StreamFrame> as specta::Type>::reference(type_map, generics)
+    }
+}
+
+// ==================== Resuming ====================
+
+/// An open stream as reported by `resync`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct OpenStream {
+    pub stream_id: u64,
+    pub request_id: Option<String>,
+    /// Seq of the next chunk; `resume_stream` can replay every chunk before it
+    pub next_seq: u64,
+    pub elapsed_ms: u64,
+}
+
+/// The streams currently open, oldest first.
+pub(crate) fn open_streams() -> Vec<OpenStream> {
+    let streams = OPEN_STREAMS.lock().unwrap_or_else(|e| e.into_inner());
+    streams.iter()
+        .map(|(stream_id, target)| {
+            let target = lock_target(target);
+            OpenStream {
+                stream_id: *stream_id,
+                request_id: target.request_id.clone(),
+                next_seq: target.chunks.len() as u64,
+                elapsed_ms: target.started.elapsed().as_millis() as u64,
+            }
+        })
+        .collect()
+}
+
+// ==================== Engine Stream Reader ====================
+
+/// Why a stream is incomplete.
+#[derive(Debug, Clone)]
+pub(crate) struct Truncation {
+    pub reason: String,
+    /// Byte length announced by the trailer, if one arrived
+    pub expected_bytes: Option<u64>,
+}
+
+/// Text read from an engine stream.
+#[derive(Debug)]
+pub(crate) struct StreamedText {
+    pub text: String,
+    /// None unless the stream was cut off or its trailer didn't match
+    pub truncated: Option<Truncation>,
+    /// True if the `done` record carried a trailer and it matched
+    pub verified: bool,
+}
+
+impl StreamedText {
+    fn truncated(text: String, reason: String, expected_bytes: Option<u64>) -> StreamedText {
+        StreamedText { text, truncated: Some(Truncation { reason, expected_bytes }), verified: false }
+    }
+
+    /// Whether the stream is known to be whole: finished with a matching trailer.
+    pub(crate) fn is_complete(&self) -> bool {
+        self.truncated.is_none() && self.verified
+    }
+
+    /// Emit `stream_truncated` if the stream was cut off; returns the reason.
+    pub(crate) fn report_truncation(&self, app: &AppHandle, stream_id: u64, request_id: &str) -> Option<String> {
+        let truncation = self.truncated.as_ref()?;
+        println!("Stream {} ({}) truncated after {} bytes: {}", stream_id, request_id, self.text.len(), truncation.reason);
+        events::emit(app, StreamTruncated {
+            stream_id,
+            request_id: request_id.to_string(),
+            received_bytes: self.text.len() as u64,
+            expected_bytes: truncation.expected_bytes,
+            reason: truncation.reason.clone(),
+        });
+        Some(truncation.reason.clone())
+    }
+}
+
+/// Check the `done` record's length/checksum trailer against the received text.
+///
+/// A `done` record without a trailer is unverified: not truncated, but not
+/// known to be complete either.
+fn verify_trailer(record: &serde_json::Value, text: String) -> StreamedText {
+    let length = record.get("length").and_then(|v| v.as_u64());
+    let checksum = record.get("checksum").and_then(|v| v.as_str());
+    if length.is_none() && checksum.is_none() {
+        println!("Stream finished without a length/checksum trailer; its completeness is unverified");
+        return StreamedText { text, truncated: None, verified: false };
+    }
+    if let Some(length) = length.filter(|length| *length != text.len() as u64) {
+        let reason = format!("received {} of {} bytes", text.len(), length);
+        return StreamedText::truncated(text, reason, Some(length));
+    }
+    if let Some(checksum) = checksum {
+        let actual = format!("{:08x}", crc32fast::hash(text.as_bytes()));
+        if !actual.eq_ignore_ascii_case(checksum) {
+            let reason = format!("checksum mismatch: expected {}, got {}", checksum, actual);
+            return StreamedText::truncated(text, reason, length);
+        }
+    }
+    StreamedText { text, truncated: None, verified: true }
+}
+
+/// POST `body` to `endpoint` and forward each streamed token to `writer`.
+///
+/// Returns the generated text once the engine signals completion, or the
+/// text received so far if the stream was truncated. Request hooks run
+/// around it, except post_response ones (see hooks). While moderation is on
+/// the tokens are held back; `finish_moderation` sends what passes.
+pub(crate) async fn read_token_stream(app: &AppHandle, socket_path: &str, endpoint: &str, body: &serde_json::Value, writer: &mut StreamWriter) -> Result<StreamedText, EngineError> {
+    if moderation::is_enabled(app).await {
+        writer.hold();
+    }
+    let call = EngineCall {
+        method: "POST".to_string(),
+        endpoint: endpoint.to_string(),
+        headers: Vec::new(),
+        body: Some(body.clone()),
+    };
+    let send = |call: EngineCall| async move {
+        let body = call.body.unwrap_or_default();
+        let request_id = body.get("request_id").and_then(|v| v.as_str()).map(str::to_string);
+        let on_skipped = |skipped: &Decoded| ndjson::report_skipped(app, &call.endpoint, request_id.as_deref(), skipped);
+        forward_token_stream(socket_path, &call.endpoint, &body, &call.headers, writer, on_skipped).await
+    };
+    app.state::<RequestHooks>().run_raw(call, send).await
+}
+
+/// `read_token_stream` with extra request `headers`, passing skipped records to `on_skipped`.
+async fn forward_token_stream(
+    socket_path: &str,
+    endpoint: &str,
+    body: &serde_json::Value,
+    headers: &[(String, String)],
+    writer: &mut StreamWriter,
+    on_skipped: impl Fn(&Decoded),
+) -> Result<StreamedText, EngineError> {
+    let mut cancelled = cancel_signal().subscribe();
+    if *cancelled.borrow() {
+        return Err(EngineError::ShuttingDown);
+    }
+
+    recorder::record_with(|| Frame::EngineRequest {
+        method: "POST".to_string(),
+        endpoint: endpoint.to_string(),
+        body: Some(body.clone()),
+    });
+    let request_id = body.get("request_id").and_then(|v| v.as_str()).map(str::to_string);
+    let response = socket_http_send_with_headers(socket_path, "POST", endpoint, Some(body), "application/x-ndjson, text/event-stream", headers).await?;
+    if !response.status().is_success() {
+        return Err(error_response(response).await);
+    }
+    let mut body = response.into_body();
+
+    let mut text = String::new();
+    let mut decoder = NdjsonDecoder::default();
+    loop {
+        let chunk = tokio::select! {
+            chunk = body.data() => chunk,
+            _ = cancelled.wait_for(|cancel| *cancel) => {
+                return Err(EngineError::ShuttingDown);
+            }
+        };
+        let Some(chunk) = chunk else {
+            break;
+        };
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => return Ok(StreamedText::truncated(text, format!("connection lost: {}", e), None)),
+        };
+        // Handle every complete line; the decoder keeps a partial trailing line for the next chunk
+        for decoded in decoder.push(&chunk) {
+            let Decoded::Record(record) = decoded else {
+                // A skipped token shows up as a trailer mismatch
+                on_skipped(&decoded);
+                continue;
+            };
+            if apply_record(endpoint, request_id.as_deref(), &record, &mut text, writer)? {
+                return Ok(verify_trailer(&record, text));
+            }
+        }
+        // Let other streams read before taking the next chunk
+        tokio::task::yield_now().await;
+    }
+
+    // The last record may lack its newline
+    match decoder.finish() {
+        Some(Decoded::Record(record)) if apply_record(endpoint, request_id.as_deref(), &record, &mut text, writer)? => {
+            return Ok(verify_trailer(&record, text));
+        }
+        Some(Decoded::Record(_)) | None => {}
+        Some(skipped) => on_skipped(&skipped),
+    }
+    Ok(StreamedText::truncated(text, "stream ended before the engine signalled completion".to_string(), None))
+}
+
+/// Record one stream record and forward its token; true if it ends the stream.
+fn apply_record(
+    endpoint: &str,
+    request_id: Option<&str>,
+    record: &serde_json::Value,
+    text: &mut String,
+    writer: &mut StreamWriter,
+) -> Result<bool, EngineError> {
+    recorder::record_with(|| Frame::StreamRecord {
+        endpoint: endpoint.to_string(),
+        request_id: request_id.map(str::to_string),
+        record: record.clone(),
+    });
+    if let Some(error) = record.get("error").and_then(|v| v.as_str()) {
+        return Err(EngineError::BadResponse(error.to_string()));
+    }
+    if let Some(token) = record.get("token").and_then(|v| v.as_str()) {
+        text.push_str(token);
+        writer.send_chunk(token.to_string())?;
+    }
+    Ok(record.get("done").and_then(|v| v.as_bool()).unwrap_or(false))
+}
+
+/// Moderate a finished stream's text and send it if `writer` held it back.
+///
+/// Returns the response and the error for the terminal frame: the policy
+/// message if the text was blocked, else the truncation reason.
+pub(crate) async fn finish_moderation(
+    app: &AppHandle,
+    request_id: &str,
+    streamed: &StreamedText,
+    writer: &mut StreamWriter,
+) -> Result<(serde_json::Value, Option<String>), String> {
+    let stream_id = writer.stream_id();
+    let truncation = streamed.report_truncation(app, stream_id, request_id);
+    let (complete, verified) = (streamed.is_complete(), streamed.verified);
+    match moderation::moderate(app, request_id, &streamed.text).await {
+        Ok(output) => {
+            writer.release(output.clone())?;
+            Ok((serde_json::json!({ "output": output, "stream_id": stream_id, "complete": complete, "verified": verified }), truncation))
+        }
+        Err(policy) => Ok((
+            serde_json::json!({ "output": policy.clone(), "stream_id": stream_id, "complete": complete, "verified": verified, "moderated": true }),
+            Some(policy),
+        )),
+    }
+}
+
+// ==================== Tauri Command: stream_input_to_python ====================
+
+/// Send user input and stream the generated tokens over `on_frame`.
+///
+/// Returns the stream id once the terminal frame has been sent. Engine
+/// errors are reported in the terminal frame rather than as a command error.
+/// The stream can be cancelled with `cancel_request(request_id)`; `route`
+/// selects the session and model (see session_models).
+#[tauri::command]
+#[specta::specta]
+pub async fn stream_input_to_python(
+    app: AppHandle,
+    input: String,
+    on_frame: Channel<StreamFrame>,
+    request_id: Option<String>,
+    route: Option<InputRoute>,
+    state: State<'_, Mutex<PythonProcess>>,
+    active: State<'_, ActiveRequests>,
+) -> Result<u64, EngineError> {
+    println!("Streaming input to AI Engine: {}", input);
+    analytics::count("stream_input_to_python");
+
+    let proc_state = state.lock().await;
+    update_activity_impl(&proc_state.last_activity).await;
+    let _request = drain::begin_request(&proc_state)?;
+    drop(proc_state);
+    let mut handle = active.register(request_id)?;
+
+    // Invalid parameters or templates fail before the stream opens or waits for a slot
+    let mut body = serde_json::json!({ "input": input, "stream": true, "request_id": handle.id() });
+    generation::apply(&app, route.as_ref().and_then(|r| r.generation.clone()), &mut body).await?;
+    templates::apply(&app, route.as_ref().and_then(|r| r.template.clone()), &mut body).await?;
+    let mut writer = StreamWriter::new(on_frame);
+    writer.bind_request(handle.id());
+    let stream_id = writer.stream_id();
+    let reservation = route.as_ref().and_then(|r| r.reservation_id.clone());
+    let turn = session_models::route_input(&app, route, &mut body).await;
+    // Wait for one of the limited /input slots, like unstreamed inputs
+    let request_id = handle.id().to_string();
+    let _slot = handle.run(input_limiter::acquire(&app, &request_id, reservation.as_deref())).await?;
+
+    let started = Instant::now();
+    let result = handle.run(read_token_stream(&app, &get_socket_path(), "/input", &body, &mut writer)).await;
+    if let (Some(turn), Ok(streamed)) = (&turn, &result) {
+        session_models::record_answer(&app, turn, handle.id(), None, streamed.is_complete()).await;
+    }
+    // A blocked response ends with the policy message as its error frame,
+    // a truncated one with the truncation reason
+    let (result, frame_error) = match result {
+        Ok(streamed) => {
+            let (response, frame_error) = finish_moderation(&app, handle.id(), &streamed, &mut writer).await?;
+            (Ok(response), frame_error)
+        }
+        Err(e) => {
+            let message = e.to_string();
+            (Err(e), Some(message))
+        }
+    };
+    history::record(&app, handle.id(), &body, &result, started.elapsed()).await;
+    writer.finish(frame_error)?;
+
+    Ok(stream_id)
+}
+
+// ==================== Tauri Command: resume_stream ====================
+
+/// Move open stream `stream_id` to `on_frame` (e.g. after a webview reload).
+///
+/// Chunks from `from_seq` (default 0) on are replayed to `on_frame` first;
+/// later chunks and the terminal frame follow on it in order. Returns the
+/// seq of the next live chunk.
+#[tauri::command]
+#[specta::specta]
+pub async fn resume_stream(stream_id: u64, from_seq: Option<u64>, on_frame: Channel<StreamFrame>) -> Result<u64, EngineError> {
+    let target = OPEN_STREAMS.lock().unwrap_or_else(|e| e.into_inner()).get(&stream_id).cloned();
+    let Some(target) = target else {
+        return Err(EngineError::InvalidRequest(format!("No stream {} is open", stream_id)));
+    };
+    let mut target = lock_target(&target);
+    let next_seq = target.chunks.len() as u64;
+    let from_seq = from_seq.unwrap_or(0).min(next_seq);
+    for (seq, data) in target.chunks.iter().enumerate().skip(from_seq as usize) {
+        on_frame
+            .send(StreamFrame::Chunk { stream_id, seq: seq as u64, data: data.clone() })
+            .map_err(|e| format!("Failed to replay stream chunk: {}", e))?;
+    }
+    target.channel = on_frame;
+    println!("Resumed stream {} from seq {} ({} chunk(s) replayed)", stream_id, from_seq, next_seq - from_seq);
+    Ok(next_seq)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use hyper::{Body, Request, Response};
+    use serde_json::json;
+    use std::convert::Infallible;
+    use tauri::ipc::InvokeResponseBody;
+
+    /// Streams read at once
+    const STREAMS: usize = 8;
+
+    /// Tokens per stream
+    const TOKENS: usize = 200;
+
+    /// Token `i` of the stream for `input`.
+    fn token(input: &str, i: usize) -> String {
+        format!("{}-{} ", input, i)
+    }
+
+    /// Stream TOKENS tokens for the request's `input`, then a `done` record with its trailer.
+    ///
+    /// The body is sent in small chunks that split records, with a yield
+    /// after each, so concurrent streams interleave.
+    async fn handle(request: Request<Body>) -> Result<Response<Body>, Infallible> {
+        let trace_id = request.headers().get("x-trace-id").cloned();
+        let body = hyper::body::to_bytes(request.into_body()).await.unwrap_or_default();
+        let input = serde_json::from_slice::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|b| b.get("input").and_then(|v| v.as_str()).map(str::to_string))
+            .unwrap_or_default();
+
+        let tokens: Vec<String> = (0..TOKENS).map(|i| token(&input, i)).collect();
+        let text = tokens.concat();
+        let mut payload: String = tokens.iter().map(|t| format!("{}\n", json!({ "token": t }))).collect();
+        payload.push_str(&json!({ "done": true, "length": text.len(), "checksum": format!("{:08x}", crc32fast::hash(text.as_bytes())) }).to_string());
+        payload.push('\n');
+
+        let chunk_size = 5 + input.len() * 3;
+        let (mut sender, body) = Body::channel();
+        tokio::spawn(async move {
+            for chunk in payload.as_bytes().chunks(chunk_size) {
+                if sender.send_data(hyper::body::Bytes::copy_from_slice(chunk)).await.is_err() {
+                    return;
+                }
+                tokio::task::yield_now().await;
+            }
+        });
+        let mut response = Response::builder()
+            .header("content-type", "application/x-ndjson")
+            .body(body)
+            .expect("valid mock response");
+        if let Some(trace_id) = trace_id {
+            response.headers_mut().insert("x-trace-id", trace_id);
+        }
+        Ok(response)
+    }
+
+    /// Serve the mock engine on a fresh socket in the temp directory; returns its path.
+    fn start_mock_engine() -> String {
+        let path = std::env::temp_dir().join(format!("streaming-test-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = tokio::net::UnixListener::bind(&path).expect("bind mock engine socket");
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let service = hyper::service::service_fn(handle);
+                    let _ = hyper::server::conn::Http::new().http1_only(true).serve_connection(stream, service).await;
+                });
+            }
+        });
+        path.to_string_lossy().into_owned()
+    }
+
+    /// A Channel that keeps every frame sent to it, as JSON.
+    fn collecting_channel() -> (Channel<StreamFrame>, Arc<StdMutex<Vec<serde_json::Value>>>) {
+        let frames = Arc::new(StdMutex::new(Vec::new()));
+        let sink = frames.clone();
+        let channel = Channel::new(move |body| {
+            if let InvokeResponseBody::Json(frame) = body {
+                sink.lock().unwrap().push(serde_json::from_str(&frame).expect("frame is JSON"));
+            }
+            Ok(())
+        });
+        (channel, frames)
+    }
+
+    #[test]
+    fn concurrent_streams_keep_their_frame_order() {
+        tauri::async_runtime::block_on(async {
+            let socket = start_mock_engine();
+            let streams: Vec<_> = (0..STREAMS)
+                .map(|n| {
+                    let socket = socket.clone();
+                    tokio::spawn(async move {
+                        let input = format!("s{}", n);
+                        let (channel, frames) = collecting_channel();
+                        let mut writer = StreamWriter::new(channel);
+                        let stream_id = writer.stream_id();
+                        let body = json!({ "input": input, "stream": true, "request_id": format!("req-{}", n) });
+                        let streamed = forward_token_stream(&socket, "/input", &body, &[], &mut writer, |_| {}).await.expect("stream");
+                        writer.finish(None).expect("end frame");
+                        (input, stream_id, streamed, frames)
+                    })
+                })
+                .collect();
+
+            for stream in streams {
+                let (input, stream_id, streamed, frames) = stream.await.expect("stream task");
+                let expected: Vec<String> = (0..TOKENS).map(|i| token(&input, i)).collect();
+                assert!(streamed.is_complete(), "{} not complete: {:?}", input, streamed.truncated);
+                assert_eq!(streamed.text, expected.concat());
+
+                let frames = frames.lock().unwrap();
+                assert_eq!(frames.len(), TOKENS + 1, "{} sent {} frames", input, frames.len());
+                for (seq, (frame, data)) in frames.iter().zip(&expected).enumerate() {
+                    assert_eq!(frame["type"], "chunk");
+                    assert_eq!(frame["stream_id"], stream_id);
+                    assert_eq!(frame["seq"], seq as u64);
+                    assert_eq!(frame["data"], data.as_str());
+                }
+                let end = &frames[TOKENS];
+                assert_eq!(end["type"], "end");
+                assert_eq!(end["stream_id"], stream_id);
+                assert_eq!(end["total_chunks"], TOKENS as u64);
+                assert_eq!(end["checksum"], format!("{:08x}", crc32fast::hash(streamed.text.as_bytes())));
+                assert!(end["error"].is_null());
+            }
+            let _ = std::fs::remove_file(&socket);
+        });
+    }
+}
@@ -0,0 +1,233 @@
+//! =============================================================================
+//! Temporary File Lifecycle
+//! =============================================================================
+//!
+//! Features that stage data on disk (downloads and saved artifacts written to
+//! `<dest>.partial`) create their temporary files through `TempFiles`, which
+//! ties each file to the request (or session) that owns it.
+//! The returned `TempFileGuard` deletes the file when dropped, so a failed or
+//! cancelled request (whose future is dropped) leaves nothing behind;
+//! `persist(dest)` moves a finished file into place instead.
+//!
+//! Every registration is appended to the temp journal before the file is
+//! created:
+//!
+//!   {"path": "/Users/me/model.gguf.partial", "owner": "req-7"}
+//!
+//! so files of a crashed run are deleted by `init` at the next startup. The
+//! journal is truncated whenever no temporary file is live. Shutdown deletes
+//! whatever abandoned requests left, and `get_temp_usage` reports the live
+//! files per owner.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager, State};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::error::EngineError;
+
+/// Journal of registered temporary files, in the app's local data dir
+const TEMP_JOURNAL_FILE: &str = "temp_files.jsonl";
+
+// ==================== Types ====================
+
+/// One journal line.
+#[derive(Debug, Serialize, Deserialize)]
+struct JournalEntry {
+    path: PathBuf,
+    owner: String,
+}
+
+/// Temporary files of one owner, as reported by `get_temp_usage`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct TempOwnerUsage {
+    /// Request id, session id, or `artifact-<id>` for a saved artifact
+    pub owner: String,
+    pub files: u32,
+    pub bytes: u64,
+}
+
+/// Response of `get_temp_usage`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct TempUsage {
+    pub files: u32,
+    pub bytes: u64,
+    /// Largest owners first
+    pub owners: Vec<TempOwnerUsage>,
+    /// Files of a crashed run deleted at startup
+    pub recovered_at_startup: u32,
+}
+
+#[derive(Default)]
+struct Registry {
+    /// Live files and their owners
+    files: HashMap<PathBuf, String>,
+    /// None until init (registrations are then only kept in memory)
+    journal: Option<PathBuf>,
+    recovered_at_startup: u32,
+}
+
+impl Registry {
+    fn append(&self, entry: &JournalEntry) -> Result<(), String> {
+        let Some(journal) = &self.journal else {
+            return Ok(());
+        };
+        let line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(journal)
+            .and_then(|mut file| writeln!(file, "{}", line).and_then(|_| file.sync_data()))
+            .map_err(|e| format!("Failed to write {:?}: {}", journal, e))
+    }
+
+    /// Forget `path`; the journal starts over once nothing is live.
+    fn unregister(&mut self, path: &Path) {
+        self.files.remove(path);
+        if let (true, Some(journal)) = (self.files.is_empty(), &self.journal) {
+            if let Err(e) = std::fs::write(journal, b"") {
+                println!("Failed to truncate {:?}: {}", journal, e);
+            }
+        }
+    }
+}
+
+/// Registry of live temporary files (managed state).
+#[derive(Default, Clone)]
+pub struct TempFiles(Arc<Mutex<Registry>>);
+
+impl TempFiles {
+    fn registry(&self) -> std::sync::MutexGuard<'_, Registry> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Create (truncate) the temporary file `path` on behalf of `owner`.
+    pub(crate) async fn create(&self, owner: &str, path: PathBuf) -> Result<(TempFileGuard, tokio::fs::File), String> {
+        {
+            let mut registry = self.registry();
+            if let Some(current) = registry.files.get(&path) {
+                return Err(format!("{:?} is already in use by {}", path, current));
+            }
+            registry.append(&JournalEntry { path: path.clone(), owner: owner.to_string() })?;
+            registry.files.insert(path.clone(), owner.to_string());
+        }
+        // From here on the guard removes the registration, also if creating fails
+        let guard = TempFileGuard { path, registry: self.clone() };
+        let file = tokio::fs::File::create(&guard.path)
+            .await
+            .map_err(|e| format!("Failed to create {:?}: {}", guard.path, e))?;
+        Ok((guard, file))
+    }
+
+    /// Delete every live temporary file; returns how many there were.
+    pub(crate) fn remove_all(&self) -> usize {
+        let mut registry = self.registry();
+        let paths: Vec<PathBuf> = registry.files.keys().cloned().collect();
+        for path in &paths {
+            let _ = std::fs::remove_file(path);
+            registry.unregister(path);
+        }
+        paths.len()
+    }
+
+    /// Live files and their sizes, per owner.
+    pub(crate) fn usage(&self) -> TempUsage {
+        let registry = self.registry();
+        let mut owners: BTreeMap<&str, TempOwnerUsage> = BTreeMap::new();
+        for (path, owner) in &registry.files {
+            let usage = owners.entry(owner).or_insert_with(|| TempOwnerUsage { owner: owner.clone(), files: 0, bytes: 0 });
+            usage.files += 1;
+            usage.bytes += std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        }
+        let mut owners: Vec<TempOwnerUsage> = owners.into_values().collect();
+        owners.sort_by_key(|o| std::cmp::Reverse(o.bytes));
+        TempUsage {
+            files: owners.iter().map(|o| o.files).sum(),
+            bytes: owners.iter().map(|o| o.bytes).sum(),
+            owners,
+            recovered_at_startup: registry.recovered_at_startup,
+        }
+    }
+}
+
+/// A live temporary file; deleted on drop unless persisted.
+pub(crate) struct TempFileGuard {
+    path: PathBuf,
+    registry: TempFiles,
+}
+
+impl TempFileGuard {
+    /// Move the file to `dest`, after which it is no longer temporary.
+    pub(crate) async fn persist(self, dest: &Path) -> Result<(), String> {
+        tokio::fs::rename(&self.path, dest)
+            .await
+            .map_err(|e| format!("Failed to move {:?} to {:?}: {}", self.path, dest, e))
+        // Dropping self now only unregisters: the path no longer exists
+    }
+}
+
+impl Drop for TempFileGuard {
+    fn drop(&mut self) {
+        match std::fs::remove_file(&self.path) {
+            Ok(()) => println!("Removed temporary file {:?}", self.path),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => println!("Failed to remove temporary file {:?}: {}", self.path, e),
+        }
+        self.registry.registry().unregister(&self.path);
+    }
+}
+
+// ==================== Startup Recovery ====================
+
+/// Delete the temporary files a crashed run left behind; returns how many.
+fn recover(journal: &Path) -> u32 {
+    let Ok(file) = std::fs::File::open(journal) else {
+        return 0;
+    };
+    let mut recovered = 0;
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        // A torn last line is skipped: its file was never created
+        let Ok(entry) = serde_json::from_str::<JournalEntry>(&line) else {
+            continue;
+        };
+        if std::fs::remove_file(&entry.path).is_ok() {
+            println!("Removed temporary file {:?} left by {}", entry.path, entry.owner);
+            recovered += 1;
+        }
+    }
+    if let Err(e) = std::fs::write(journal, b"") {
+        println!("Failed to truncate {:?}: {}", journal, e);
+    }
+    recovered
+}
+
+/// Clean up after a crashed run and start journaling temporary files.
+pub fn init(app: &AppHandle) {
+    let dir = app.path().app_local_data_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join("ai-engine"));
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        println!("Failed to create {:?}, temporary files won't survive a crash: {}", dir, e);
+        return;
+    }
+    let journal = dir.join(TEMP_JOURNAL_FILE);
+    let recovered = recover(&journal);
+    if recovered > 0 {
+        println!("Removed {} temporary file(s) of a previous run", recovered);
+    }
+    let temp_files = app.state::<TempFiles>();
+    let mut registry = temp_files.registry();
+    registry.journal = Some(journal);
+    registry.recovered_at_startup = recovered;
+}
+
+// ==================== Tauri Command: get_temp_usage ====================
+
+/// Return the live temporary files and their disk usage, per owner.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_temp_usage(temp_files: State<'_, TempFiles>) -> Result<TempUsage, EngineError> {
+    Ok(temp_files.usage())
+}
@@ -0,0 +1,477 @@
+//! =============================================================================
+//! Prompt Templates and Filter Rules (live reload)
+//! =============================================================================
+//!
+//! Power users edit these files with external editors, in the app config dir:
+//!
+//!   templates/<name>.txt   prompt template, `{{placeholder}}` substitutions
+//!   templates/<name>.json  prompt template with a system prompt (also written
+//!                          by `set_prompt_template`; wins over <name>.txt):
+//!                          { "system": "You are terse.",
+//!                            "prompt": "Answer in {{language}}: {{input}}" }
+//!   rules/<set>.json       filter rules:
+//!                          [ { "name": "no-keys", "pattern": "sk-[A-Za-z0-9]+",
+//!                              "action": "redact", "replacement": "[key]" } ]
+//!
+//! Both directories are polled for changes every RELOAD_POLL_INTERVAL_SECS;
+//! the backups and checksums kept next to saved templates are ignored.
+//! A changed file is validated before it replaces the loaded version; a broken
+//! file keeps its last good version. Every reload emits `templates_reloaded`
+//! with the loaded counts and any validation errors.
+//!
+//! An input with `route.template = { "name": ..., "variables": {...} }` is
+//! sent with the template applied: the /input payload's `input` becomes the
+//! rendered prompt, with `{{input}}` replaced by the user's input (appended
+//! after a blank line if the prompt has no `{{input}}`), plus the rendered
+//! `system` prompt and the template's name. Every other placeholder needs a
+//! variable. `apply_prompt_template` renders the same without sending.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager, State};
+use tauri::async_runtime::Mutex;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::atomic_file;
+use crate::error::EngineError;
+use crate::events::{self, TemplatesReloaded};
+
+// ==================== Configuration Constants ====================
+
+/// Subdirectories of the app config dir holding templates and rules
+pub(crate) const TEMPLATES_DIR: &str = "templates";
+pub(crate) const RULES_DIR: &str = "rules";
+
+/// How often the directories are checked for changes
+const RELOAD_POLL_INTERVAL_SECS: u64 = 2;
+
+// ==================== Types ====================
+
+/// What a filter rule does with matching text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterAction {
+    /// Reject the text outright
+    Block,
+    /// Replace each match with `replacement`
+    Redact,
+}
+
+/// A filter rule as written in a rules file.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct FilterRule {
+    pub name: String,
+    /// Regular expression matched against the text
+    pub pattern: String,
+    pub action: FilterAction,
+    #[serde(default)]
+    pub replacement: Option<String>,
+}
+
+/// A file that failed validation; its last good version stays loaded.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ValidationError {
+    pub file: String,
+    pub error: String,
+}
+
+/// A prompt template: text around the user's input and an optional system prompt.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct PromptTemplate {
+    #[serde(default)]
+    pub system: Option<String>,
+    /// `{{input}}` marks where the user's input goes
+    pub prompt: String,
+}
+
+/// A loaded prompt template, returned by `list_prompt_templates`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct PromptTemplateInfo {
+    pub name: String,
+    pub system: Option<String>,
+    pub prompt: String,
+}
+
+/// Template to apply to one input (see `InputRoute`).
+#[derive(Debug, Clone, Deserialize, Type)]
+pub struct TemplateRef {
+    pub name: String,
+    /// Values of the template's placeholders other than `{{input}}`
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+}
+
+/// A template applied to an input, returned by `apply_prompt_template`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct RenderedPrompt {
+    pub system: Option<String>,
+    /// Sent as the /input payload's `input`
+    pub input: String,
+}
+
+/// Snapshot returned by `get_templates`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct TemplatesSnapshot {
+    /// Prompt of each template, by name
+    pub templates: HashMap<String, String>,
+    pub rules: Vec<FilterRule>,
+    pub errors: Vec<ValidationError>,
+}
+
+/// Change signature of a file on disk.
+type FileStamp = (SystemTime, u64);
+
+/// Loaded templates and rules, keyed by source file.
+#[derive(Default)]
+pub struct TemplateStore {
+    templates: HashMap<PathBuf, (FileStamp, PromptTemplate)>,
+    rules: HashMap<PathBuf, (FileStamp, Vec<FilterRule>)>,
+    /// Latest validation error per file, cleared when the file loads again
+    errors: HashMap<PathBuf, (FileStamp, String)>,
+}
+
+// ==================== Validation ====================
+
+/// Check that `{{` and `}}` pair up around identifier placeholders.
+fn validate_template(text: &str) -> Result<(), String> {
+    if text.trim().is_empty() {
+        return Err("template is empty".to_string());
+    }
+    let mut rest = text;
+    while let Some(open) = rest.find("{{") {
+        let after = &rest[open + 2..];
+        let close = after.find("}}").ok_or("unclosed '{{'")?;
+        let name = after[..close].trim();
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("invalid placeholder '{{{{{}}}}}'", name));
+        }
+        rest = &after[close + 2..];
+    }
+    if rest.contains("}}") {
+        return Err("'}}' without matching '{{'".to_string());
+    }
+    Ok(())
+}
+
+/// Parse a JSON prompt template and check its texts.
+fn parse_template(text: &str) -> Result<PromptTemplate, String> {
+    let template: PromptTemplate = serde_json::from_str(text)
+        .map_err(|e| format!("invalid template JSON: {}", e))?;
+    check_template(&template)?;
+    Ok(template)
+}
+
+/// Check the prompt and system prompt of `template`.
+fn check_template(template: &PromptTemplate) -> Result<(), String> {
+    validate_template(&template.prompt)?;
+    if let Some(system) = &template.system {
+        validate_template(system).map_err(|e| format!("system: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Parse a rules file and check every pattern compiles.
+fn parse_rules(text: &str) -> Result<Vec<FilterRule>, String> {
+    let rules: Vec<FilterRule> = serde_json::from_str(text)
+        .map_err(|e| format!("invalid rules JSON: {}", e))?;
+    for rule in &rules {
+        Regex::new(&rule.pattern)
+            .map_err(|e| format!("rule '{}': invalid pattern: {}", rule.name, e))?;
+        if rule.action == FilterAction::Redact && rule.replacement.is_none() {
+            return Err(format!("rule '{}': redact rules need a replacement", rule.name));
+        }
+    }
+    Ok(rules)
+}
+
+// ==================== Reloading ====================
+
+/// Files in `dir` with extension `ext`, with their change stamps.
+///
+/// The backups and checksums `set_prompt_template` writes next to a
+/// template are skipped (see atomic_file).
+fn scan_dir(dir: &Path, ext: &str) -> HashMap<PathBuf, FileStamp> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return HashMap::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some(ext) && !atomic_file::is_companion(path))
+        .filter_map(|path| {
+            let meta = std::fs::metadata(&path).ok()?;
+            let modified = meta.modified().ok()?;
+            meta.is_file().then_some((path, (modified, meta.len())))
+        })
+        .collect()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+impl TemplateStore {
+    /// Reload changed files under `config_dir`; returns true if anything changed.
+    fn reload(&mut self, config_dir: &Path) -> bool {
+        let mut changed = false;
+
+        let mut templates = scan_dir(&config_dir.join(TEMPLATES_DIR), "txt");
+        templates.extend(scan_dir(&config_dir.join(TEMPLATES_DIR), "json"));
+        let rules = scan_dir(&config_dir.join(RULES_DIR), "json");
+
+        // Deleted files drop out entirely
+        let before = self.templates.len() + self.rules.len() + self.errors.len();
+        self.templates.retain(|path, _| templates.contains_key(path));
+        self.rules.retain(|path, _| rules.contains_key(path));
+        self.errors.retain(|path, _| templates.contains_key(path) || rules.contains_key(path));
+        changed |= before != self.templates.len() + self.rules.len() + self.errors.len();
+
+        for (path, stamp) in templates {
+            if self.is_current(&path, stamp, self.templates.get(&path).map(|(s, _)| *s)) {
+                continue;
+            }
+            changed = true;
+            let is_json = path.extension().and_then(|e| e.to_str()) == Some("json");
+            let result = std::fs::read_to_string(&path)
+                .map_err(|e| format!("unreadable: {}", e))
+                .and_then(|text| match is_json {
+                    true => parse_template(&text),
+                    false => validate_template(&text).map(|_| PromptTemplate { system: None, prompt: text }),
+                });
+            match result {
+                Ok(template) => {
+                    self.errors.remove(&path);
+                    self.templates.insert(path, (stamp, template));
+                }
+                Err(e) => {
+                    self.errors.insert(path, (stamp, e));
+                }
+            }
+        }
+
+        for (path, stamp) in rules {
+            if self.is_current(&path, stamp, self.rules.get(&path).map(|(s, _)| *s)) {
+                continue;
+            }
+            changed = true;
+            let result = std::fs::read_to_string(&path)
+                .map_err(|e| format!("unreadable: {}", e))
+                .and_then(|text| parse_rules(&text));
+            match result {
+                Ok(parsed) => {
+                    self.errors.remove(&path);
+                    self.rules.insert(path, (stamp, parsed));
+                }
+                Err(e) => {
+                    self.errors.insert(path, (stamp, e));
+                }
+            }
+        }
+
+        changed
+    }
+
+    /// True if `path` at `stamp` is already loaded or already known broken.
+    fn is_current(&self, path: &Path, stamp: FileStamp, loaded: Option<FileStamp>) -> bool {
+        loaded == Some(stamp) || self.errors.get(path).map(|(s, _)| *s) == Some(stamp)
+    }
+
+    /// All loaded filter rules.
+    pub(crate) fn rules(&self) -> Vec<FilterRule> {
+        self.rules.values().flat_map(|(_, rules)| rules.iter().cloned()).collect()
+    }
+
+    /// Loaded prompt templates by name (a .json file wins over a .txt one).
+    fn prompt_templates(&self) -> BTreeMap<String, PromptTemplate> {
+        let mut by_name = BTreeMap::new();
+        let mut paths: Vec<&PathBuf> = self.templates.keys().collect();
+        // .txt sorts before .json, so the .json version is inserted last
+        paths.sort_by_key(|path| path.extension().and_then(|e| e.to_str()) == Some("json"));
+        for path in paths {
+            let name = path.file_stem().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            by_name.insert(name, self.templates[path].1.clone());
+        }
+        by_name
+    }
+
+    fn snapshot(&self) -> TemplatesSnapshot {
+        TemplatesSnapshot {
+            templates: self.prompt_templates()
+                .into_iter()
+                .map(|(name, template)| (name, template.prompt))
+                .collect(),
+            rules: self.rules(),
+            errors: self.errors
+                .iter()
+                .map(|(path, (_, error))| ValidationError { file: file_name(path), error: error.clone() })
+                .collect(),
+        }
+    }
+}
+
+fn config_dir(app: &AppHandle) -> PathBuf {
+    app.path().app_config_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join("ai-engine"))
+}
+
+/// Reload changed files and emit `templates_reloaded` if anything changed.
+async fn reload(app: &AppHandle, config_dir: &Path) {
+    let snapshot = {
+        let store = app.state::<Mutex<TemplateStore>>();
+        let mut store = store.lock().await;
+        store.reload(config_dir).then(|| store.snapshot())
+    };
+    if let Some(snapshot) = snapshot {
+        println!(
+            "Templates reloaded: {} template(s), {} rule(s), {} error(s)",
+            snapshot.templates.len(), snapshot.rules.len(), snapshot.errors.len()
+        );
+        events::emit(app, TemplatesReloaded {
+            templates: snapshot.templates.len(),
+            rules: snapshot.rules.len(),
+            errors: snapshot.errors,
+        });
+    }
+}
+
+/// Load templates and rules, register the store, and start watching for changes.
+pub fn init(app: &AppHandle) {
+    let config_dir = config_dir(app);
+    app.manage(Mutex::new(TemplateStore::default()));
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            reload(&app, &config_dir).await;
+            tokio::time::sleep(Duration::from_secs(RELOAD_POLL_INTERVAL_SECS)).await;
+        }
+    });
+}
+
+// ==================== Applying Templates ====================
+
+/// Replace the `{{name}}` placeholders of `text` with `variables`.
+fn render(text: &str, variables: &HashMap<String, String>) -> Result<String, EngineError> {
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find("{{") {
+        let after = &rest[open + 2..];
+        let Some(close) = after.find("}}") else {
+            break;
+        };
+        let name = after[..close].trim();
+        let value = variables.get(name)
+            .ok_or_else(|| EngineError::InvalidRequest(format!("Template variable '{}' has no value", name)))?;
+        rendered.push_str(&rest[..open]);
+        rendered.push_str(value);
+        rest = &after[close + 2..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+/// Whether `text` has an `{{input}}` placeholder.
+fn has_input_placeholder(text: &str) -> bool {
+    text.split("{{").skip(1).any(|part| part.split("}}").next().map(str::trim) == Some("input"))
+}
+
+/// Apply `template` to `input`.
+fn render_template(template: &PromptTemplate, input: &str, mut variables: HashMap<String, String>) -> Result<RenderedPrompt, EngineError> {
+    variables.insert("input".to_string(), input.to_string());
+    let mut prompt = render(&template.prompt, &variables)?;
+    if !has_input_placeholder(&template.prompt) {
+        prompt = format!("{}\n\n{}", prompt.trim_end(), input);
+    }
+    Ok(RenderedPrompt {
+        system: template.system.as_deref().map(|system| render(system, &variables)).transpose()?,
+        input: prompt,
+    })
+}
+
+/// Loaded template `name`.
+async fn find(app: &AppHandle, name: &str) -> Result<PromptTemplate, EngineError> {
+    let store = app.state::<Mutex<TemplateStore>>();
+    let template = store.lock().await.prompt_templates().remove(name);
+    template.ok_or_else(|| EngineError::InvalidRequest(format!("Unknown prompt template {}", name)))
+}
+
+/// Merge `template` into the /input `body` (its `input`, `system` and `template`).
+pub(crate) async fn apply(app: &AppHandle, template: Option<TemplateRef>, body: &mut serde_json::Value) -> Result<(), EngineError> {
+    let Some(template) = template else {
+        return Ok(());
+    };
+    let input = body.get("input").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    let rendered = render_template(&find(app, &template.name).await?, &input, template.variables)?;
+    body["input"] = rendered.input.into();
+    if let Some(system) = rendered.system {
+        body["system"] = system.into();
+    }
+    body["template"] = template.name.into();
+    Ok(())
+}
+
+// ==================== Tauri Command: get_templates ====================
+
+/// Return the loaded templates and rules plus any current validation errors.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_templates(store: State<'_, Mutex<TemplateStore>>) -> Result<TemplatesSnapshot, EngineError> {
+    Ok(store.lock().await.snapshot())
+}
+
+// ==================== Tauri Commands: Prompt Templates ====================
+
+/// Create or replace prompt template `name` (templates/<name>.json).
+///
+/// This command:
+///   1. Checks the name and the template's placeholders
+///   2. Writes the file atomically, with a checksum and a last-good backup
+///      (see atomic_file)
+///   3. Reloads the templates, emitting `templates_reloaded`
+#[tauri::command]
+#[specta::specta]
+pub async fn set_prompt_template(app: AppHandle, name: String, template: PromptTemplate) -> Result<(), EngineError> {
+    let valid_name = !name.is_empty()
+        && name.len() <= 64
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid_name {
+        return Err(EngineError::InvalidRequest(format!(
+            "Invalid template name '{}': use up to 64 letters, digits, '-' and '_'",
+            name
+        )));
+    }
+    check_template(&template).map_err(|e| EngineError::InvalidRequest(format!("Template {}: {}", name, e)))?;
+
+    let config_dir = config_dir(&app);
+    let dir = config_dir.join(TEMPLATES_DIR);
+    atomic_file::write_json(&dir.join(format!("{}.json", name)), &template)?;
+    println!("Saved prompt template {}", name);
+
+    reload(&app, &config_dir).await;
+    Ok(())
+}
+
+/// Return the loaded prompt templates, sorted by name.
+#[tauri::command]
+#[specta::specta]
+pub async fn list_prompt_templates(store: State<'_, Mutex<TemplateStore>>) -> Result<Vec<PromptTemplateInfo>, EngineError> {
+    Ok(store.lock().await.prompt_templates()
+        .into_iter()
+        .map(|(name, template)| PromptTemplateInfo { name, system: template.system, prompt: template.prompt })
+        .collect())
+}
+
+/// Render prompt template `name` for `input`, as it would be sent to /input.
+#[tauri::command]
+#[specta::specta]
+pub async fn apply_prompt_template(
+    app: AppHandle,
+    name: String,
+    input: String,
+    variables: Option<HashMap<String, String>>,
+) -> Result<RenderedPrompt, EngineError> {
+    render_template(&find(&app, &name).await?, &input, variables.unwrap_or_default())
+}
@@ -0,0 +1,135 @@
+//! =============================================================================
+//! Trace Context Propagation
+//! =============================================================================
+//!
+//! Every request to the engine, per-request HTTP and multiplexed alike,
+//! carries correlation headers so engine logs can be matched to backend
+//! requests:
+//!
+//!   X-Trace-Id    always; the `trace_id` of the JSON body (see otel), or a
+//!                 fresh id for requests outside a user input (status polls,
+//!                 health checks, artifact downloads)
+//!   X-Request-Id  the body's `request_id`, when it has one
+//!   X-Session-Id  the body's `session_id`, when it has one
+//!
+//! The engine must log the trace id and echo `X-Trace-Id` in its response
+//! (a `headers` map in mux response frames). Each echo is verified: a
+//! response without it, or with a different id, is counted as a protocol
+//! warning. Mismatches are logged and kept (the last MAX_RECENT_WARNINGS)
+//! for `get_protocol_warnings`; a missing echo, typical of an older engine,
+//! is only logged the first time.
+
+use serde::Serialize;
+use specta::Type;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::EngineError;
+
+/// Trace id header, sent with every request and echoed by the engine
+pub(crate) const TRACE_ID_HEADER: &str = "x-trace-id";
+
+/// Request id header, sent when the body has a `request_id`
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Session id header, sent when the body has a `session_id`
+const SESSION_ID_HEADER: &str = "x-session-id";
+
+/// Mismatches kept for `get_protocol_warnings`
+const MAX_RECENT_WARNINGS: usize = 50;
+
+/// Responses whose trace id echo was checked
+static CHECKED: AtomicU64 = AtomicU64::new(0);
+
+/// Responses without an `X-Trace-Id` echo
+static MISSING: AtomicU64 = AtomicU64::new(0);
+
+/// Responses echoing a different trace id
+static MISMATCHED: AtomicU64 = AtomicU64::new(0);
+
+static RECENT: Mutex<VecDeque<ProtocolWarning>> = Mutex::new(VecDeque::new());
+
+// ==================== Types ====================
+
+/// A response that echoed the wrong trace id.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ProtocolWarning {
+    pub endpoint: String,
+    pub sent_trace_id: String,
+    pub echoed_trace_id: String,
+    /// Unix time in milliseconds
+    pub at_ms: u64,
+}
+
+/// Trace id echo statistics since startup, returned by `get_protocol_warnings`.
+#[derive(Debug, Serialize, Type)]
+pub struct ProtocolWarnings {
+    pub checked: u64,
+    pub missing_echo: u64,
+    pub mismatched: u64,
+    /// Latest mismatches, oldest first
+    pub recent: Vec<ProtocolWarning>,
+}
+
+// ==================== Propagation ====================
+
+/// A new random trace id (32 hex digits, as in W3C trace context).
+pub(crate) fn new_trace_id() -> String {
+    format!("{:032x}", fastrand::u128(1..))
+}
+
+/// Correlation headers for a request with JSON `body`; the trace id comes first.
+pub(crate) fn headers_for(body: Option<&serde_json::Value>) -> Vec<(String, String)> {
+    let field = |name: &str| body.and_then(|b| b.get(name)).and_then(|v| v.as_str()).map(str::to_string);
+    let mut headers = vec![(TRACE_ID_HEADER.to_string(), field("trace_id").unwrap_or_else(new_trace_id))];
+    if let Some(request_id) = field("request_id") {
+        headers.push((REQUEST_ID_HEADER.to_string(), request_id));
+    }
+    if let Some(session_id) = field("session_id") {
+        headers.push((SESSION_ID_HEADER.to_string(), session_id));
+    }
+    headers
+}
+
+/// Check the trace id `echoed` by the engine for a request to `endpoint` sent with `sent`.
+pub(crate) fn verify_echo(endpoint: &str, sent: &str, echoed: Option<&str>) {
+    CHECKED.fetch_add(1, Ordering::Relaxed);
+    match echoed {
+        Some(echoed) if echoed.eq_ignore_ascii_case(sent) => {}
+        Some(echoed) => {
+            MISMATCHED.fetch_add(1, Ordering::Relaxed);
+            println!("Protocol warning: {} echoed trace id {} for {}", endpoint, echoed, sent);
+            let mut recent = RECENT.lock().unwrap_or_else(|e| e.into_inner());
+            if recent.len() == MAX_RECENT_WARNINGS {
+                recent.pop_front();
+            }
+            recent.push_back(ProtocolWarning {
+                endpoint: endpoint.to_string(),
+                sent_trace_id: sent.to_string(),
+                echoed_trace_id: echoed.to_string(),
+                at_ms: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0),
+            });
+        }
+        None => {
+            if MISSING.fetch_add(1, Ordering::Relaxed) == 0 {
+                println!("Protocol warning: {} did not echo X-Trace-Id (further misses are only counted)", endpoint);
+            }
+        }
+    }
+}
+
+// ==================== Tauri Command: get_protocol_warnings ====================
+
+/// Report how often the engine failed to echo the trace id, for diagnostics.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_protocol_warnings() -> Result<ProtocolWarnings, EngineError> {
+    Ok(ProtocolWarnings {
+        checked: CHECKED.load(Ordering::Relaxed),
+        missing_echo: MISSING.load(Ordering::Relaxed),
+        mismatched: MISMATCHED.load(Ordering::Relaxed),
+        recent: RECENT.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned().collect(),
+    })
+}
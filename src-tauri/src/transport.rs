@@ -0,0 +1,216 @@
+//! =============================================================================
+//! Engine Transport Middleware
+//! =============================================================================
+//!
+//! Single entry point for requests to the AI Engine. Every request:
+//!
+//!   1. Resolves its response timeout:
+//!      per-request override, then settings.timeouts[endpoint class]
+//!   2. Waits for admission while the engine is warming up after a start
+//!      (see startup_gate)
+//!   3. Goes over the multiplexed connection when one is negotiated,
+//!      otherwise over a fresh per-request Unix socket connection
+//!   4. Fails with a timeout error if the engine doesn't answer in time
+//!
+//! Requests also run the embedding app's request hooks around the send
+//! (see hooks). POSTs are timed for the rolling latency metrics (see
+//! engine_metrics).
+//!
+//! `engine_request` parses the response as JSON; `engine_request_bytes`
+//! returns the body untouched for binary payloads (audio, raw embeddings).
+//! Bytes always go over a per-request connection, since multiplexed frames
+//! carry JSON only.
+//!
+//! Direct socket requests outside this path (`socket_http_get` /
+//! `socket_http_post`, used during startup and shutdown) get the same
+//! per-class timeout through `with_timeout`, so a hung engine can't block
+//! them forever either.
+
+use tauri::{AppHandle, Manager};
+use tauri::async_runtime::Mutex;
+use std::future::Future;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use crate::deprecations;
+use crate::engine_metrics;
+use crate::error::EngineError;
+use crate::hooks::{EngineCall, RequestHooks};
+use crate::mux::{MuxError, MuxSlot};
+use crate::recorder::{self, Frame};
+use crate::settings::{EndpointClass, TimeoutSettings};
+use crate::{get_socket_path, socket_http_bytes, socket_http_json, PythonProcess, RawResponse};
+
+/// Configured timeouts (defaults until settings are loaded)
+static TIMEOUTS: RwLock<Option<TimeoutSettings>> = RwLock::new(None);
+
+/// Apply timeout settings (on load and whenever settings change).
+pub(crate) fn configure(settings: &TimeoutSettings) {
+    *TIMEOUTS.write().unwrap_or_else(|e| e.into_inner()) = Some(settings.clone());
+}
+
+/// Configured response timeout for `endpoint`'s class.
+pub(crate) fn timeout_for(endpoint: &str) -> Duration {
+    let class = EndpointClass::for_endpoint(endpoint);
+    match TIMEOUTS.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        Some(timeouts) => timeouts.for_class(class),
+        None => TimeoutSettings::default().for_class(class),
+    }
+}
+
+/// Run a request to `endpoint`, failing with `EngineError::Timeout` after `timeout`.
+pub(crate) async fn with_timeout<T>(
+    endpoint: &str,
+    timeout: Duration,
+    request: impl Future<Output = Result<T, EngineError>>,
+) -> Result<T, EngineError> {
+    tokio::time::timeout(timeout, request)
+        .await
+        .unwrap_or_else(|_| Err(EngineError::Timeout { endpoint: endpoint.to_string(), timeout_ms: timeout.as_millis() as u64 }))
+}
+
+/// Send a JSON request to the engine and return the parsed response body.
+///
+/// `timeout_override` takes precedence over the configured timeout for the
+/// endpoint's class. Request hooks run around the send; the recording shows
+/// the request as the hooks left it.
+pub(crate) async fn engine_request(
+    app: &AppHandle,
+    method: &str,
+    endpoint: &str,
+    body: Option<&serde_json::Value>,
+    timeout_override: Option<Duration>,
+) -> Result<serde_json::Value, EngineError> {
+    let class = EndpointClass::for_endpoint(endpoint);
+    let timeout = timeout_override.unwrap_or_else(|| timeout_for(endpoint));
+    let (mux, startup_gate) = {
+        let proc_state = app.state::<Mutex<PythonProcess>>();
+        let proc_state = proc_state.lock().await;
+        (proc_state.mux.clone(), proc_state.startup_gate.clone())
+    };
+
+    startup_gate.admit(class).await;
+
+    let call = EngineCall {
+        method: method.to_string(),
+        endpoint: endpoint.to_string(),
+        headers: Vec::new(),
+        body: body.cloned(),
+    };
+    let send = |call: EngineCall| async move {
+        recorder::record_with(|| Frame::EngineRequest {
+            method: call.method.clone(),
+            endpoint: call.endpoint.clone(),
+            body: call.body.clone(),
+        });
+        let socket_path = get_socket_path();
+        let request = with_timeout(&call.endpoint, timeout, route(&mux, &socket_path, &call));
+        // POSTs feed the rolling latency metrics
+        let result = if call.method == "POST" {
+            engine_metrics::measure(&call.endpoint, request).await
+        } else {
+            request.await
+        };
+        if let Ok(response) = &result {
+            deprecations::inspect_body(&call.endpoint, response);
+        }
+        recorder::record_with(|| Frame::EngineResponse {
+            method: call.method.clone(),
+            endpoint: call.endpoint.clone(),
+            response: result.as_ref().ok().cloned(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        });
+        result
+    };
+    let hooks = app.state::<RequestHooks>();
+    hooks.run(call, send).await
+}
+
+/// Send a request to the engine and return the raw response body.
+///
+/// Like `engine_request`, but the body is returned as bytes, whatever its
+/// encoding. Request hooks run around the send, except post_response ones
+/// (see hooks). The recording keeps the content type and size, not the bytes.
+pub(crate) async fn engine_request_bytes(
+    app: &AppHandle,
+    method: &str,
+    endpoint: &str,
+    body: Option<&serde_json::Value>,
+    timeout_override: Option<Duration>,
+) -> Result<RawResponse, EngineError> {
+    let class = EndpointClass::for_endpoint(endpoint);
+    let timeout = timeout_override.unwrap_or_else(|| timeout_for(endpoint));
+    let proc_state = app.state::<Mutex<PythonProcess>>();
+    let startup_gate = proc_state.lock().await.startup_gate.clone();
+
+    startup_gate.admit(class).await;
+
+    let call = EngineCall {
+        method: method.to_string(),
+        endpoint: endpoint.to_string(),
+        headers: Vec::new(),
+        body: body.cloned(),
+    };
+    let send = |call: EngineCall| async move {
+        recorder::record_with(|| Frame::EngineRequest {
+            method: call.method.clone(),
+            endpoint: call.endpoint.clone(),
+            body: call.body.clone(),
+        });
+        let socket_path = get_socket_path();
+        let request = socket_http_bytes(&socket_path, &call.method, &call.endpoint, call.body.as_ref(), &call.headers);
+        let request = with_timeout(&call.endpoint, timeout, request);
+        let result = if call.method == "POST" {
+            engine_metrics::measure(&call.endpoint, request).await
+        } else {
+            request.await
+        };
+        recorder::record_with(|| Frame::EngineResponse {
+            method: call.method.clone(),
+            endpoint: call.endpoint.clone(),
+            response: result.as_ref().ok().map(|raw| serde_json::json!({
+                "content_type": raw.content_type,
+                "size_bytes": raw.body.len(),
+            })),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        });
+        result
+    };
+    let hooks = app.state::<RequestHooks>();
+    hooks.run_raw(call, send).await
+}
+
+/// Send over the multiplexed connection if negotiated, else per-request.
+///
+/// A broken mux connection is dropped from the slot. The request is retried
+/// in per-request mode only if its frame was never written, so a POST the
+/// engine may already have acted on doesn't run twice.
+async fn route(mux: &MuxSlot, socket_path: &str, call: &EngineCall) -> Result<serde_json::Value, EngineError> {
+    let EngineCall { method, endpoint, headers, body } = call;
+    let client = mux.lock().await.clone();
+    if let Some(client) = client {
+        if client.is_alive() {
+            match client.request(method, endpoint, body.as_ref(), headers).await {
+                Ok(response) => return Ok(response),
+                Err(MuxError::Failed(e)) => {
+                    if !client.is_alive() {
+                        println!("Multiplexed connection lost, falling back to per-request connections");
+                        *mux.lock().await = None;
+                    }
+                    return Err(e);
+                }
+                Err(MuxError::NotSent(e)) if client.is_alive() => return Err(e),
+                Err(MuxError::NotSent(_)) => {}
+            }
+        }
+        println!("Multiplexed connection lost, falling back to per-request connections");
+        *mux.lock().await = None;
+    }
+
+    // The caller's timeout applies, not the per-class one of socket_http_get/post
+    match (method.as_str(), body) {
+        ("GET", _) => socket_http_json(socket_path, "GET", endpoint, None, headers).await,
+        (_, Some(body)) => socket_http_json(socket_path, method, endpoint, Some(body), headers).await,
+        (_, None) => socket_http_json(socket_path, method, endpoint, Some(&serde_json::json!({})), headers).await,
+    }
+}
@@ -0,0 +1,144 @@
+//! =============================================================================
+//! Turbo Mode - Time-boxed Limit Raising
+//! =============================================================================
+//!
+//! Turbo mode temporarily lifts the backend's conservative limits for a bounded
+//! window (e.g. while the user runs a bulk import):
+//!
+//!   • Idle timeout is extended to TURBO_IDLE_TIMEOUT_SECS
+//!   • The engine is asked to use every available CPU thread
+//!   • At least one /input per CPU thread may run at a time (see input_limiter)
+//!
+//! When the window closes, everything reverts automatically and the frontend
+//! receives `turbo_started` / `turbo_ended` events.
+
+use tauri::{AppHandle, State};
+use tauri::async_runtime::Mutex;
+use std::time::{Duration, Instant};
+use std::sync::Arc;
+
+use crate::error::EngineError;
+use crate::events::{self, TurboEnded, TurboStarted};
+use crate::{transport, PythonProcess};
+
+// ==================== Configuration Constants ====================
+
+/// Idle timeout used while turbo mode is active
+pub(crate) const TURBO_IDLE_TIMEOUT_SECS: u64 = 1800; // 30 minutes
+
+/// Upper bound for a single turbo window
+const TURBO_MAX_DURATION_SECS: u64 = 3600; // 1 hour
+
+// ==================== Turbo State ====================
+
+/// Shared turbo window state.
+///
+/// `generation` increases every time turbo is (re-)enabled so that a revert
+/// task scheduled for an older window does not cut a newer one short.
+#[derive(Default)]
+pub struct TurboState {
+    active_until: Option<Instant>,
+    generation: u64,
+}
+
+impl TurboState {
+    /// Returns true while the turbo window is open.
+    pub fn is_active(&self) -> bool {
+        self.active_until.is_some_and(|until| Instant::now() < until)
+    }
+}
+
+/// Idle timeout to enforce right now, taking turbo mode into account.
+pub(crate) async fn effective_idle_timeout(turbo: &Arc<Mutex<TurboState>>, base_secs: u64) -> Duration {
+    if turbo.lock().await.is_active() {
+        Duration::from_secs(base_secs.max(TURBO_IDLE_TIMEOUT_SECS))
+    } else {
+        Duration::from_secs(base_secs)
+    }
+}
+
+/// /input slots to allow right now: while turbo is active, at least one per
+/// engine thread.
+pub(crate) async fn effective_input_limit(turbo: &Arc<Mutex<TurboState>>, base: usize) -> usize {
+    if turbo.lock().await.is_active() {
+        base.max(turbo_thread_count())
+    } else {
+        base
+    }
+}
+
+/// Number of threads the engine should use while turbo is active.
+fn turbo_thread_count() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+/// Ask the engine to change its worker thread count.
+///
+/// `threads: null` tells the engine to go back to its own default.
+/// Failures are logged only - older engines don't expose /config.
+async fn set_engine_threads(app: &AppHandle, threads: Option<usize>) {
+    let body = serde_json::json!({ "threads": threads });
+    if let Err(e) = transport::engine_request(app, "POST", "/config", Some(&body), None).await {
+        println!("Could not update engine thread count: {}", e);
+    }
+}
+
+// ==================== Tauri Command: enable_turbo ====================
+
+/// Enable turbo mode for `duration_secs` seconds.
+///
+/// This command:
+///   1. Opens (or extends) the turbo window, capped at TURBO_MAX_DURATION_SECS
+///   2. Raises the engine's thread count to all available cores
+///   3. Emits `turbo_started` to the frontend
+///   4. Schedules a revert task that restores defaults and emits `turbo_ended`
+///
+/// Calling it again while active restarts the window with the new duration.
+#[tauri::command]
+#[specta::specta]
+pub async fn enable_turbo(app: AppHandle, duration_secs: u64, state: State<'_, Mutex<PythonProcess>>) -> Result<(), EngineError> {
+    if duration_secs == 0 {
+        return Err(EngineError::InvalidRequest("Turbo duration must be greater than zero".to_string()));
+    }
+    let duration = Duration::from_secs(duration_secs.min(TURBO_MAX_DURATION_SECS));
+
+    let proc_state = state.lock().await;
+    let turbo = proc_state.turbo.clone();
+    drop(proc_state);
+
+    let generation = {
+        let mut turbo_state = turbo.lock().await;
+        turbo_state.active_until = Some(Instant::now() + duration);
+        turbo_state.generation += 1;
+        turbo_state.generation
+    };
+
+    let threads = turbo_thread_count();
+    println!("Turbo mode enabled for {} secs ({} engine threads)", duration.as_secs(), threads);
+    set_engine_threads(&app, Some(threads)).await;
+
+    events::emit(&app, TurboStarted {
+        duration_secs: duration.as_secs(),
+        threads,
+    });
+
+    // Revert once the window closes, unless a newer window replaced this one
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(duration).await;
+
+        let mut turbo_state = turbo.lock().await;
+        if turbo_state.generation != generation {
+            return;
+        }
+        turbo_state.active_until = None;
+        drop(turbo_state);
+
+        println!("Turbo mode ended, restoring defaults");
+        set_engine_threads(&app, None).await;
+        events::emit(&app, TurboEnded {});
+    });
+
+    Ok(())
+}
@@ -0,0 +1,191 @@
+//! =============================================================================
+//! Engine Update Channels
+//! =============================================================================
+//!
+//! Lets users opt into stable, beta or nightly AI Engine builds.
+//!
+//! Each channel has its own manifest, served from the update server configured
+//! via the AI_ENGINE_UPDATE_URL environment variable:
+//!
+//!   {AI_ENGINE_UPDATE_URL}/{channel}/manifest.json
+//!   { "version": "1.4.0-beta.2", "url": "...", "sha256": "...", "notes": "..." }
+//!
+//! Downgrade protection: a manifest whose version is older than the running
+//! engine is never offered (e.g. after switching nightly → stable) unless the
+//! caller explicitly allows it.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, State};
+use tauri::async_runtime::Mutex;
+use semver::Version;
+
+use crate::error::EngineError;
+use crate::transport;
+
+/// Environment variable holding the base URL of the update server
+const UPDATE_URL_ENV: &str = "AI_ENGINE_UPDATE_URL";
+
+// ==================== Channel Types ====================
+
+/// Release channel the engine updates are taken from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    Beta,
+    Nightly,
+}
+
+impl UpdateChannel {
+    fn as_str(&self) -> &'static str {
+        match self {
+            UpdateChannel::Stable => "stable",
+            UpdateChannel::Beta => "beta",
+            UpdateChannel::Nightly => "nightly",
+        }
+    }
+}
+
+/// Update settings shared between commands.
+#[derive(Default)]
+pub struct UpdateState {
+    channel: UpdateChannel,
+}
+
+/// Per-channel manifest published by the update server.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct UpdateManifest {
+    pub version: String,
+    pub url: String,
+    pub sha256: String,
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+/// Result of checking the selected channel for a newer engine.
+#[derive(Debug, Serialize, Type)]
+pub struct UpdateCheck {
+    pub channel: UpdateChannel,
+    pub current_version: Option<String>,
+    pub manifest: UpdateManifest,
+    pub update_available: bool,
+    pub downgrade_blocked: bool,
+}
+
+/// Engine version plus the channel and build it came from.
+#[derive(Debug, Serialize, Type)]
+pub struct EngineVersionInfo {
+    pub version: Option<String>,
+    pub channel: UpdateChannel,
+    pub build: serde_json::Value,
+}
+
+// ==================== Utility Functions ====================
+
+/// Query the running engine for its version via GET /version.
+/// Returns None if the engine is not running or doesn't report a version.
+async fn query_engine_version(app: &AppHandle) -> (Option<String>, serde_json::Value) {
+    match transport::engine_request(app, "GET", "/version", None, None).await {
+        Ok(json) => {
+            let version = json.get("version").and_then(|v| v.as_str()).map(str::to_string);
+            let build = json.get("build").cloned().unwrap_or(serde_json::Value::Null);
+            (version, build)
+        }
+        Err(_) => (None, serde_json::Value::Null),
+    }
+}
+
+/// Decide whether `candidate` should be offered over `current`.
+///
+/// Returns (update_available, downgrade_blocked).
+fn compare_versions(current: Option<&str>, candidate: &str, allow_downgrade: bool) -> Result<(bool, bool), String> {
+    let candidate = Version::parse(candidate)
+        .map_err(|e| format!("Invalid manifest version '{}': {}", candidate, e))?;
+
+    let Some(current) = current.and_then(|v| Version::parse(v).ok()) else {
+        // Unknown current version: any published build is an update
+        return Ok((true, false));
+    };
+
+    if candidate > current {
+        Ok((true, false))
+    } else if candidate < current && !allow_downgrade {
+        Ok((false, true))
+    } else {
+        Ok((candidate != current, false))
+    }
+}
+
+/// Fetch the manifest for a channel from the configured update server.
+async fn fetch_manifest(channel: UpdateChannel) -> Result<UpdateManifest, String> {
+    let base_url = std::env::var(UPDATE_URL_ENV)
+        .map_err(|_| format!("No update server configured ({} is not set)", UPDATE_URL_ENV))?;
+    let url = format!("{}/{}/manifest.json", base_url.trim_end_matches('/'), channel.as_str());
+
+    reqwest::get(&url)
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to fetch update manifest from {}: {}", url, e))?
+        .json::<UpdateManifest>()
+        .await
+        .map_err(|e| format!("Invalid update manifest at {}: {}", url, e))
+}
+
+// ==================== Tauri Commands ====================
+
+/// Select the release channel used for engine updates.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_update_channel(channel: UpdateChannel, state: State<'_, Mutex<UpdateState>>) -> Result<(), EngineError> {
+    println!("Engine update channel set to {}", channel.as_str());
+    state.lock().await.channel = channel;
+    Ok(())
+}
+
+/// Report the running engine version with its channel and build metadata.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_engine_version(app: AppHandle, state: State<'_, Mutex<UpdateState>>) -> Result<EngineVersionInfo, EngineError> {
+    let channel = state.lock().await.channel;
+    let (version, engine_build) = query_engine_version(&app).await;
+
+    Ok(EngineVersionInfo {
+        version,
+        channel,
+        build: serde_json::json!({
+            "engine": engine_build,
+            "host_version": env!("CARGO_PKG_VERSION"),
+            "target_os": std::env::consts::OS,
+            "target_arch": std::env::consts::ARCH,
+            "debug": cfg!(debug_assertions),
+        }),
+    })
+}
+
+/// Check the selected channel's manifest for a newer engine build.
+///
+/// Older builds are reported as `downgrade_blocked` unless `allow_downgrade`
+/// is set, so switching from nightly back to stable never silently rolls back.
+#[tauri::command]
+#[specta::specta]
+pub async fn check_engine_update(app: AppHandle, allow_downgrade: Option<bool>, state: State<'_, Mutex<UpdateState>>) -> Result<UpdateCheck, EngineError> {
+    let channel = state.lock().await.channel;
+    let (current_version, _) = query_engine_version(&app).await;
+    let manifest = fetch_manifest(channel).await?;
+
+    let (update_available, downgrade_blocked) = compare_versions(
+        current_version.as_deref(),
+        &manifest.version,
+        allow_downgrade.unwrap_or(false),
+    )?;
+
+    Ok(UpdateCheck {
+        channel,
+        current_version,
+        manifest,
+        update_available,
+        downgrade_blocked,
+    })
+}
@@ -0,0 +1,254 @@
+//! =============================================================================
+//! File Uploads
+//! =============================================================================
+//!
+//! `send_file_to_python(path, metadata)` sends an image, PDF or any other
+//! file to the engine as multipart/form-data:
+//!
+//!   POST /upload
+//!   Content-Type: multipart/form-data; boundary=<boundary>
+//!
+//!   part "metadata"  application/json
+//!     { "request_id": "req-7", "filename": "scan.pdf",
+//!       "content_type": "application/pdf", "size_bytes": 48213,
+//!       "metadata": { …caller's metadata… } }
+//!   part "file"      the file's bytes, with its content type
+//!
+//! The file is read and sent UPLOAD_CHUNK_BYTES at a time, so it is never
+//! held in memory as a whole; Content-Length is computed up front and a file
+//! that changes size mid-upload aborts the request.
+//!
+//! Otherwise an upload is handled like an input: it counts as in flight,
+//! starts a stopped engine (see `settings.engine.auto_start`), can be aborted
+//! with `cancel_request(request_id)`, and its response is moderated and
+//! emitted as `python_input`.
+
+use tauri::{AppHandle, Manager};
+use tauri::async_runtime::Mutex;
+use tokio::io::AsyncReadExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::analytics;
+use crate::budget::{CommandClass, CommandTimer, Timed};
+use crate::error::EngineError;
+use crate::events::{self, PythonInput};
+use crate::hooks::{EngineCall, RequestHooks};
+use crate::moderation;
+use crate::recorder::{self, Frame};
+use crate::requests::ActiveRequests;
+use crate::settings::EndpointClass;
+use crate::{engine_auth, trace_context, transport};
+use crate::{auto_start_engine, drain, get_socket_path, read_json_response, socket_http_request, update_activity_impl, PythonProcess};
+
+/// Engine endpoint receiving uploads
+const UPLOAD_ENDPOINT: &str = "/upload";
+
+/// Bytes read from the file and sent per body chunk
+const UPLOAD_CHUNK_BYTES: usize = 64 * 1024;
+
+// ==================== Multipart Body ====================
+
+/// Content type for a file, by extension.
+fn content_type_for(path: &Path) -> &'static str {
+    let extension = path.extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Escape a value for a quoted Content-Disposition parameter.
+fn quoted(value: &str) -> String {
+    value.replace(['\r', '\n'], " ").replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Everything before the file's bytes, and everything after them.
+fn multipart_frame(boundary: &str, description: &[u8], filename: &str, content_type: &str) -> (Vec<u8>, Vec<u8>) {
+    let mut head = Vec::with_capacity(description.len() + 512);
+    head.extend_from_slice(format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"metadata\"\r\nContent-Type: application/json\r\n\r\n",
+        boundary
+    ).as_bytes());
+    head.extend_from_slice(description);
+    head.extend_from_slice(format!(
+        "\r\n--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+        boundary,
+        quoted(filename),
+        content_type
+    ).as_bytes());
+    let tail = format!("\r\n--{}--\r\n", boundary).into_bytes();
+    (head, tail)
+}
+
+/// Feed the multipart body into `sender`, reading `file` chunk by chunk.
+async fn pump_file(sender: &mut hyper::body::Sender, mut file: tokio::fs::File, size: u64, head: Vec<u8>, tail: Vec<u8>) -> Result<(), String> {
+    let send_error = |e: hyper::Error| format!("Engine stopped reading the upload: {}", e);
+    sender.send_data(head.into()).await.map_err(send_error)?;
+    let mut remaining = size;
+    let mut buffer = vec![0u8; UPLOAD_CHUNK_BYTES];
+    loop {
+        let read = file.read(&mut buffer).await.map_err(|e| format!("Failed to read file: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        remaining = remaining.checked_sub(read as u64).ok_or("File grew during the upload")?;
+        sender.send_data(hyper::body::Bytes::copy_from_slice(&buffer[..read])).await.map_err(send_error)?;
+    }
+    if remaining > 0 {
+        return Err("File shrank during the upload".to_string());
+    }
+    sender.send_data(tail.into()).await.map_err(send_error)
+}
+
+/// POST the file as multipart/form-data and return the engine's JSON response.
+///
+/// `call` carries the endpoint, extra headers and the description part as
+/// the request hooks left them.
+async fn upload(socket_path: &str, file: tokio::fs::File, size: u64, filename: &str, content_type: &str, call: &EngineCall) -> Result<serde_json::Value, EngineError> {
+    let endpoint = call.endpoint.as_str();
+    let boundary = format!("ai-engine-upload-{:016x}{:016x}", fastrand::u64(..), fastrand::u64(..));
+    let correlation = trace_context::headers_for(call.body.as_ref());
+    let description = serde_json::to_vec(call.body.as_ref().unwrap_or(&serde_json::Value::Null))
+        .map_err(|e| format!("Failed to serialize upload metadata: {}", e))?;
+    let (head, tail) = multipart_frame(&boundary, &description, filename, content_type);
+    let content_length = head.len() as u64 + size + tail.len() as u64;
+
+    let (mut sender, body) = hyper::Body::channel();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = pump_file(&mut sender, file, size, head, tail).await {
+            println!("Upload aborted: {}", e);
+            sender.abort();
+        }
+    });
+
+    let mut request = hyper::Request::builder()
+        .method("POST")
+        .uri(endpoint)
+        .header(hyper::header::HOST, "localhost")
+        .header(hyper::header::ACCEPT, "application/json")
+        .header(hyper::header::CONTENT_TYPE, format!("multipart/form-data; boundary={}", boundary))
+        .header(hyper::header::CONTENT_LENGTH, content_length);
+    for (name, value) in correlation.iter().chain(&call.headers) {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    let keys = engine_auth::keys(socket_path);
+    if let Some(keys) = &keys {
+        request = request.header(hyper::header::AUTHORIZATION, engine_auth::authorization(keys));
+    }
+    let request = request
+        .body(body)
+        .map_err(|e| EngineError::Internal(format!("Invalid request for {}: {}", endpoint, e)))?;
+    let response = socket_http_request(socket_path, request).await?;
+    let echoed = response.headers().get(trace_context::TRACE_ID_HEADER).and_then(|v| v.to_str().ok());
+    trace_context::verify_echo(endpoint, &correlation[0].1, echoed);
+    if let Some(keys) = &keys {
+        let proof = response.headers().get(engine_auth::PROOF_HEADER).and_then(|v| v.to_str().ok());
+        engine_auth::verify(keys, endpoint, &correlation[0].1, proof)?;
+    }
+    read_json_response(response).await
+}
+
+// ==================== Tauri Command: send_file_to_python ====================
+
+/// Send a file (image, PDF, ...) with optional `metadata` to the AI Engine.
+///
+/// This command:
+///   1. Checks the file and picks its content type from the extension
+///   2. Counts the upload as in flight and starts a stopped engine
+///   3. Streams the file as multipart/form-data to POST /upload, tagged
+///      with the `request_id` correlation id (generated if not given)
+///   4. Returns the engine's response (also emitted as `python_input`)
+///
+/// `timeout_ms` overrides the configured chat timeout; it covers the
+/// transfer as well as the engine's answer.
+#[tauri::command]
+#[specta::specta]
+pub async fn send_file_to_python(
+    app: AppHandle,
+    path: PathBuf,
+    metadata: Option<serde_json::Value>,
+    request_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<Timed<serde_json::Value>, EngineError> {
+    let timer = CommandTimer::start(&app, "send_file_to_python", CommandClass::Interactive);
+    analytics::count("send_file_to_python");
+    let file = tokio::fs::File::open(&path)
+        .await
+        .map_err(|e| EngineError::InvalidRequest(format!("Cannot open {:?}: {}", path, e)))?;
+    let file_metadata = file.metadata()
+        .await
+        .map_err(|e| EngineError::InvalidRequest(format!("Cannot read {:?}: {}", path, e)))?;
+    if !file_metadata.is_file() {
+        return Err(EngineError::InvalidRequest(format!("{:?} is not a file", path)));
+    }
+    let size = file_metadata.len();
+    let filename = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let content_type = content_type_for(&path);
+    println!("Uploading {:?} to AI Engine ({} bytes, {})", path, size, content_type);
+
+    // Same bookkeeping as an input: activity, drain, cancellation, auto-start
+    let state = app.state::<Mutex<PythonProcess>>();
+    let proc_state = state.lock().await;
+    update_activity_impl(&proc_state.last_activity).await;
+    let _request = drain::begin_request(&proc_state)?;
+    let startup_gate = proc_state.startup_gate.clone();
+    drop(proc_state);
+    let mut handle = app.state::<ActiveRequests>().register(request_id)?;
+    auto_start_engine(&app).await?;
+    startup_gate.admit(EndpointClass::Chat).await;
+
+    let description = serde_json::json!({
+        "request_id": handle.id(),
+        "filename": filename,
+        "content_type": content_type,
+        "size_bytes": size,
+        "metadata": metadata.unwrap_or_else(|| serde_json::json!({})),
+    });
+    let call = EngineCall {
+        method: "POST".to_string(),
+        endpoint: UPLOAD_ENDPOINT.to_string(),
+        headers: Vec::new(),
+        body: Some(description),
+    };
+
+    timer.phase("uploading").await;
+    let timeout = timeout_ms.map(Duration::from_millis).unwrap_or_else(|| transport::timeout_for(UPLOAD_ENDPOINT));
+    let socket_path = get_socket_path();
+    // Hooks run around the upload like around other requests; the recording shows it as they left it
+    let send = |call: EngineCall| async move {
+        recorder::record_with(|| Frame::EngineRequest {
+            method: call.method.clone(),
+            endpoint: call.endpoint.clone(),
+            body: call.body.clone(),
+        });
+        let upload = upload(&socket_path, file, size, &filename, content_type, &call);
+        let result = transport::with_timeout(&call.endpoint, timeout, upload).await;
+        recorder::record_with(|| Frame::EngineResponse {
+            method: call.method.clone(),
+            endpoint: call.endpoint.clone(),
+            response: result.as_ref().ok().cloned(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        });
+        result
+    };
+    let hooks = app.state::<RequestHooks>();
+    let result = handle.run(hooks.run(call, send)).await;
+
+    let mut response = result.inspect_err(|e| println!("Error uploading {:?}: {}", path, e))?;
+    moderation::moderate_response(&app, handle.id(), &mut response).await;
+    events::emit(&app, PythonInput(response.clone()));
+    Ok(timer.finish(response))
+}
@@ -0,0 +1,251 @@
+//! =============================================================================
+//! Model Preloading
+//! =============================================================================
+//!
+//! Loading a model takes the engine around 30 seconds, and
+//! `start_python_script` returns as soon as the socket answers, so without
+//! preloading the first input pays for the load. `preload_model(name)` asks
+//! the engine to load the model ahead of time:
+//!
+//!   POST /warmup  { "model": "llama-3b-q4" }
+//!              →  { "model": "llama-3b-q4", "resident": true }
+//!
+//! An engine that loads in the background answers `"resident": false` and
+//! is asked again every WARMUP_POLL_INTERVAL_SECS until the model is
+//! resident or WARMUP_TIMEOUT_SECS have passed. A `model_loaded` engine
+//! event (see engine_events) also counts as the model being resident.
+//!
+//! Readiness per model (loading, ready, failed) is kept until the model is
+//! unloaded or replaced (see models) or the engine is; `get_model_readiness`
+//! returns it and `model_ready` is emitted once a model becomes resident.
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri::async_runtime::Mutex;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::error::EngineError;
+use crate::events::{self, ModelReady};
+use crate::{auto_start_engine, drain, licenses, transport, update_activity_impl, PythonProcess};
+
+// ==================== Configuration Constants ====================
+
+/// Endpoint that loads a model
+const WARMUP_ENDPOINT: &str = "/warmup";
+
+/// Timeout of one /warmup request (an engine may answer only once loaded)
+const WARMUP_REQUEST_TIMEOUT_SECS: u64 = 60;
+
+/// Delay between /warmup requests while the engine reports the model loading
+const WARMUP_POLL_INTERVAL_SECS: u64 = 2;
+
+/// Give up on a model that isn't resident after this long
+const WARMUP_TIMEOUT_SECS: u64 = 300;
+
+/// Readiness of the models of the current engine, by name
+static MODELS: std::sync::Mutex<BTreeMap<String, Tracked>> = std::sync::Mutex::new(BTreeMap::new());
+
+/// A model's readiness and when its preload started.
+struct Tracked {
+    status: ModelWarmup,
+    started: Option<Instant>,
+}
+
+// ==================== Types ====================
+
+/// Readiness of a model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelReadiness {
+    Loading,
+    Ready,
+    Failed,
+}
+
+/// A model's readiness, returned by `preload_model` and `get_model_readiness`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ModelWarmup {
+    pub model: String,
+    pub readiness: ModelReadiness,
+    /// Time until the model was resident, if it was preloaded
+    pub load_ms: Option<u64>,
+    pub error: Option<String>,
+    /// Unix time in seconds of the last change
+    pub updated_at: u64,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// ==================== Readiness Tracking ====================
+
+/// Readiness of `model`, if it is tracked.
+fn status(model: &str) -> Option<ModelWarmup> {
+    MODELS.lock().unwrap_or_else(|e| e.into_inner()).get(model).map(|t| t.status.clone())
+}
+
+/// Start tracking a preload of `model`.
+fn begin(model: &str) -> ModelWarmup {
+    let status = ModelWarmup {
+        model: model.to_string(),
+        readiness: ModelReadiness::Loading,
+        load_ms: None,
+        error: None,
+        updated_at: unix_now(),
+    };
+    MODELS.lock().unwrap_or_else(|e| e.into_inner())
+        .insert(model.to_string(), Tracked { status: status.clone(), started: Some(Instant::now()) });
+    status
+}
+
+/// Record `model` as resident and emit `model_ready` (once).
+pub(crate) fn mark_ready(app: &AppHandle, model: &str) -> ModelWarmup {
+    let mut models = MODELS.lock().unwrap_or_else(|e| e.into_inner());
+    let tracked = models.entry(model.to_string()).or_insert_with(|| Tracked {
+        status: ModelWarmup {
+            model: model.to_string(),
+            readiness: ModelReadiness::Loading,
+            load_ms: None,
+            error: None,
+            updated_at: 0,
+        },
+        started: None,
+    });
+    if tracked.status.readiness == ModelReadiness::Ready {
+        return tracked.status.clone();
+    }
+    tracked.status.readiness = ModelReadiness::Ready;
+    tracked.status.load_ms = tracked.started.map(|started| started.elapsed().as_millis() as u64);
+    tracked.status.error = None;
+    tracked.status.updated_at = unix_now();
+    let status = tracked.status.clone();
+    drop(models);
+
+    match status.load_ms {
+        Some(load_ms) => println!("Model {} is resident ({} ms)", model, load_ms),
+        None => println!("Model {} is resident", model),
+    }
+    events::emit(app, ModelReady { model: status.model.clone(), load_ms: status.load_ms });
+    status
+}
+
+/// Record the preload of `model` as failed.
+fn mark_failed(model: &str, error: String) {
+    println!("Preloading model {} failed: {}", model, error);
+    if let Some(tracked) = MODELS.lock().unwrap_or_else(|e| e.into_inner()).get_mut(model) {
+        tracked.status.readiness = ModelReadiness::Failed;
+        tracked.status.error = Some(error);
+        tracked.status.updated_at = unix_now();
+    }
+}
+
+/// Forget the readiness of `model` (it was unloaded).
+pub(crate) fn forget(model: &str) {
+    MODELS.lock().unwrap_or_else(|e| e.into_inner()).remove(model);
+}
+
+/// Forget all readiness (a fresh engine has no model loaded).
+pub(crate) fn clear() {
+    MODELS.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+// ==================== Warm-up Requests ====================
+
+/// POST /warmup for `model`; Ok(true) once the engine reports it resident.
+async fn request_warmup(app: &AppHandle, model: &str) -> Result<bool, EngineError> {
+    let body = serde_json::json!({ "model": model });
+    let timeout = Some(Duration::from_secs(WARMUP_REQUEST_TIMEOUT_SECS));
+    let response = transport::engine_request(app, "POST", WARMUP_ENDPOINT, Some(&body), timeout).await?;
+    Ok(response.get("resident").and_then(|v| v.as_bool()).unwrap_or(false))
+}
+
+/// Ask again until `model` is resident, fails, or WARMUP_TIMEOUT_SECS have passed (runs in the background).
+fn poll_until_resident(app: &AppHandle, model: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let deadline = Instant::now() + Duration::from_secs(WARMUP_TIMEOUT_SECS);
+        loop {
+            tokio::time::sleep(Duration::from_secs(WARMUP_POLL_INTERVAL_SECS)).await;
+            // Resident already (engine event), failed, or the engine was replaced
+            if status(&model).is_none_or(|s| s.readiness != ModelReadiness::Loading) {
+                return;
+            }
+            if Instant::now() >= deadline {
+                mark_failed(&model, format!("not resident after {} secs", WARMUP_TIMEOUT_SECS));
+                return;
+            }
+            match request_warmup(&app, &model).await {
+                Ok(true) => {
+                    mark_ready(&app, &model);
+                    return;
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    mark_failed(&model, e.to_string());
+                    return;
+                }
+            }
+        }
+    });
+}
+
+// ==================== Tauri Commands ====================
+
+/// Load `model_name` in the engine ahead of the first input.
+///
+/// This command:
+///   1. Starts the engine if it is stopped and `settings.engine.auto_start` is on
+///   2. Fails with `license_required` if the model is gated and its license
+///      wasn't accepted (see licenses)
+///   3. POSTs the model to /warmup
+///   4. Returns the model's readiness: `ready` if the engine reports it
+///      resident, otherwise `loading` while it keeps being polled
+///
+/// `model_ready` is emitted once the model is resident. A model that is
+/// already loading or ready is returned as is.
+#[tauri::command]
+#[specta::specta]
+pub async fn preload_model(app: AppHandle, model_name: String) -> Result<ModelWarmup, EngineError> {
+    let model = model_name.trim().to_string();
+    if model.is_empty() {
+        return Err(EngineError::InvalidRequest("Model name must not be empty".to_string()));
+    }
+    if let Some(status) = status(&model).filter(|s| s.readiness != ModelReadiness::Failed) {
+        return Ok(status);
+    }
+
+    let state = app.state::<Mutex<PythonProcess>>();
+    let proc_state = state.lock().await;
+    update_activity_impl(&proc_state.last_activity).await;
+    let _request = drain::begin_request(&proc_state)?;
+    drop(proc_state);
+    auto_start_engine(&app).await?;
+    licenses::require_accepted(&app, &[&model]).await?;
+
+    println!("Preloading model {}", model);
+    let status = begin(&model);
+    match request_warmup(&app, &model).await {
+        Ok(true) => Ok(mark_ready(&app, &model)),
+        Ok(false) => {
+            poll_until_resident(&app, model);
+            Ok(status)
+        }
+        Err(e) => {
+            mark_failed(&model, e.to_string());
+            Err(e)
+        }
+    }
+}
+
+/// Return the readiness of every model preloaded on (or reported by) the current engine.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_model_readiness() -> Result<Vec<ModelWarmup>, EngineError> {
+    Ok(MODELS.lock().unwrap_or_else(|e| e.into_inner()).values().map(|t| t.status.clone()).collect())
+}
@@ -1,6 +1,6 @@
 import { useState, useEffect, useRef } from "react";
-import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
+import { commands, type EngineError, type Result } from "./bindings";
 import "./App.css";
 
 interface PythonOutput {
@@ -12,6 +12,69 @@ interface PythonOutput {
   timestamp?: number;
 }
 
+/** Full status snapshot (`python_status`) or `get_full_status` response. */
+interface StatusSnapshot {
+  version: number;
+  status: PythonOutput;
+}
+
+/** RFC 6902 operation as produced by the backend's status diff. */
+interface PatchOperation {
+  op: "add" | "remove" | "replace";
+  path: string;
+  value?: unknown;
+}
+
+/** `python_status_delta`: patch from status `base_version` to `version`. */
+interface StatusDelta {
+  version: number;
+  base_version: number;
+  patch: PatchOperation[];
+}
+
+/** Apply a JSON patch (add/remove/replace) to a copy of `doc`. */
+function applyPatch<T>(doc: T, patch: PatchOperation[]): T {
+  let root: unknown = structuredClone(doc);
+  for (const { op, path, value } of patch) {
+    const keys = path.split("/").slice(1).map((k) => k.replace(/~1/g, "/").replace(/~0/g, "~"));
+    if (keys.length === 0) {
+      root = op === "remove" ? undefined : value;
+      continue;
+    }
+    const last = keys.pop()!;
+    const parent = keys.reduce((node: any, key) => node[key], root as any);
+    if (Array.isArray(parent)) {
+      const index = last === "-" ? parent.length : Number(last);
+      if (op === "add") parent.splice(index, 0, value);
+      else if (op === "remove") parent.splice(index, 1);
+      else parent[index] = value;
+    } else if (op === "remove") {
+      delete parent[last];
+    } else {
+      parent[last] = value;
+    }
+  }
+  return root as T;
+}
+
+/** Return a command's data, or throw its EngineError like a plain `invoke` would. */
+function unwrap<T>(result: Result<T, EngineError>): T {
+  if (result.status === "error") throw result.error;
+  return result.data;
+}
+
+function errorMessage(error: unknown): string {
+  const engineError = error as Partial<EngineError>;
+  return typeof engineError?.message === "string" ? engineError.message : String(error);
+}
+
+/** Start fetching the default model in the background, if one is configured. */
+async function prefetchDefaultModel() {
+  const settings = unwrap(await commands.getSettings());
+  if (!settings.prefetch.model_url) return;
+  unwrap(await commands.startPrefetch());
+}
+
 function StatusCard({ data }: { data: PythonOutput }) {
   const formatTime = (timestamp: number) => {
     return new Date(timestamp * 1000).toLocaleTimeString();
@@ -68,56 +131,64 @@ function App() {
   const [input, setInput] = useState<string>("");
   const [isRunning, setIsRunning] = useState<boolean>(false);
   const unlistenStatusRef = useRef<(() => void) | null>(null);
-  const unlistenInputRef = useRef<(() => void) | null>(null);
+  const statusRef = useRef<StatusSnapshot | null>(null);
+
+  function showStatus(snapshot: StatusSnapshot) {
+    statusRef.current = snapshot;
+    setStatusOutput(snapshot.status);
+    commands.ackStatus(snapshot.version).then(unwrap).catch(console.error);
+  }
 
   async function startPython() {
     try {
       setStatusOutput(null);
       setInputOutput(null);
       
-      // Set up listener for status updates BEFORE starting the script
-      const unlistenStatus = await listen("python_status", (event) => {
-        try {
-          const parsed = JSON.parse(event.payload as string);
-          setStatusOutput(parsed);
-        } catch {
-          setStatusOutput({ message: event.payload as string });
-        }
+      // Set up listeners for status updates BEFORE starting the script:
+      // full snapshots, and deltas against the last acknowledged version
+      statusRef.current = null;
+      const unlistenSnapshot = await listen<StatusSnapshot>("python_status", (event) => {
+        showStatus(event.payload);
       });
-      unlistenStatusRef.current = unlistenStatus;
-
-      // Set up listener for user input responses
-      const unlistenInput = await listen("python_input", (event) => {
-        try {
-          const parsed = JSON.parse(event.payload as string);
-          setInputOutput(parsed);
-        } catch {
-          setInputOutput({ message: event.payload as string });
+      const unlistenDelta = await listen<StatusDelta>("python_status_delta", (event) => {
+        const { version, base_version, patch } = event.payload;
+        const current = statusRef.current;
+        if (current && current.version === base_version) {
+          showStatus({ version, status: applyPatch(current.status, patch) });
+        } else if (!current || current.version < version) {
+          // Missing the base: resync from the full status
+          commands.getFullStatus()
+            .then(unwrap)
+            .then((snapshot) => showStatus(snapshot as StatusSnapshot))
+            .catch(console.error);
         }
       });
-      unlistenInputRef.current = unlistenInput;
+      unlistenStatusRef.current = () => {
+        unlistenSnapshot();
+        unlistenDelta();
+      };
 
       // Start the Python script
-      await invoke("start_python_script");
+      unwrap(await commands.startPythonScript());
       setIsRunning(true);
+
+      // Onboarding is done once the engine runs: fetch the default model now
+      // rather than on the first prompt
+      prefetchDefaultModel().catch(console.error);
     } catch (error) {
       console.error(error);
-      setStatusOutput({ message: "Error starting Python: " + String(error) });
+      setStatusOutput({ message: "Error starting Python: " + errorMessage(error) });
       setIsRunning(false);
       if (unlistenStatusRef.current) {
         unlistenStatusRef.current();
         unlistenStatusRef.current = null;
       }
-      if (unlistenInputRef.current) {
-        unlistenInputRef.current();
-        unlistenInputRef.current = null;
-      }
     }
   }
 
   async function stopPython() {
     try {
-      await invoke("stop_python_script");
+      unwrap(await commands.stopPythonScript());
       setIsRunning(false);
       setStatusOutput({ message: "Python script stopped" });
       
@@ -126,13 +197,9 @@ function App() {
         unlistenStatusRef.current();
         unlistenStatusRef.current = null;
       }
-      if (unlistenInputRef.current) {
-        unlistenInputRef.current();
-        unlistenInputRef.current = null;
-      }
     } catch (error) {
       console.error(error);
-      setStatusOutput({ message: "Error stopping Python: " + String(error) });
+      setStatusOutput({ message: "Error stopping Python: " + errorMessage(error) });
     }
   }
 
@@ -141,11 +208,12 @@ function App() {
     if (!input.trim()) return;
 
     try {
-      await invoke("send_input_to_python", { input: input });
+      const response = unwrap(await commands.sendInputToPython(input, null, null, null, null));
+      setInputOutput(response.data as PythonOutput);
       setInput("");
     } catch (error) {
       console.error(error);
-      setStatusOutput({ message: "Error sending input: " + String(error) });
+      setStatusOutput({ message: "Error sending input: " + errorMessage(error) });
     }
   }
 
@@ -155,9 +223,6 @@ function App() {
       if (unlistenStatusRef.current) {
         unlistenStatusRef.current();
       }
-      if (unlistenInputRef.current) {
-        unlistenInputRef.current();
-      }
     };
   }, []);
 
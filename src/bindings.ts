@@ -0,0 +1,3177 @@
+
+// This file was generated by [tauri-specta](https://github.com/oscartbeaumont/tauri-specta). Do not edit this file manually.
+
+/** user-defined commands **/
+
+
+export const commands = {
+/**
+ * Start the AI Engine backend process via precompiled binary.
+ * 
+ * This command:
+ * 1. Checks if server is already running
+ * 2. Spawns the ai-engine binary (PyInstaller executable)
+ * 3. Waits for the socket to accept connections and GET /health to succeed
+ * 4. Starts the status polling loop that monitors health and idle timeout
+ * 
+ * The binary path is selected based on the current platform/architecture.
+ * Returns the startup duration if it succeeds, Err with details if it fails.
+ * Emits `command_slow` if startup exceeds the lifecycle budget.
+ */
+async startPythonScript() : Promise<Result<Timed<null>, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("start_python_script") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Stop the AI Engine backend process gracefully.
+ * 
+ * This command:
+ * 1. Checks if server is running
+ * 2. Sends graceful /stop request via Unix socket
+ * 3. Waits briefly for shutdown
+ * 4. Terminates process if needed
+ * 5. Marks server as stopped
+ * 
+ * The Unix socket communication is direct kernel IPC with no TCP overhead.
+ */
+async stopPythonScript() : Promise<Result<Timed<null>, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("stop_python_script") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Restart the AI Engine without interrupting requests.
+ * 
+ * This command:
+ * 1. Starts a replacement engine on an alternate socket
+ * 2. Switches to it once it is healthy
+ * 3. Lets the old engine finish its requests, then stops it
+ * 
+ * Returns the restart duration; emits `command_slow` past the lifecycle budget.
+ */
+async restartPythonScript() : Promise<Result<Timed<null>, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("restart_python_script") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Load `model_name` in the engine ahead of the first input.
+ * 
+ * This command:
+ * 1. Starts the engine if it is stopped and `settings.engine.auto_start` is on
+ * 2. Fails with `license_required` if the model is gated and its license
+ * wasn't accepted (see licenses)
+ * 3. POSTs the model to /warmup
+ * 4. Returns the model's readiness: `ready` if the engine reports it
+ * resident, otherwise `loading` while it keeps being polled
+ * 
+ * `model_ready` is emitted once the model is resident. A model that is
+ * already loading or ready is returned as is.
+ */
+async preloadModel(modelName: string) : Promise<Result<ModelWarmup, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("preload_model", { modelName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return the readiness of every model preloaded on (or reported by) the current engine.
+ */
+async getModelReadiness() : Promise<Result<ModelWarmup[], EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_model_readiness") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * List the models the engine can serve and which one is loaded.
+ * 
+ * Starts the engine if it is stopped and `settings.engine.auto_start` is on.
+ * The loaded model reported by the engine becomes the tracked one.
+ */
+async listModels() : Promise<Result<ModelList, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_models") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Load model `name`, replacing the current one.
+ * 
+ * This command:
+ * 1. Starts the engine if it is stopped and `settings.engine.auto_start` is on
+ * 2. Fails with `license_required` if the model is gated and its license
+ * wasn't accepted (see licenses)
+ * 3. POSTs the model to /models/load (waiting up to MODEL_LOAD_TIMEOUT_SECS)
+ * 4. Tracks it as the loaded model, emitting `model_changed` and `model_ready`
+ */
+async loadModel(name: string) : Promise<Result<null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("load_model", { name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Unload model `name` to free its memory.
+ * 
+ * If it was the loaded model, no model is tracked afterwards (`model_changed`).
+ */
+async unloadModel(name: string) : Promise<Result<null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("unload_model", { name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Download a model by URL or engine model id and register it with the engine.
+ * 
+ * This command:
+ * 1. Resolves a model id to its URL (and checksum) through /models/resolve
+ * 2. Fails with `license_required` if the model is gated and its license
+ * wasn't accepted (see licenses)
+ * 3. Downloads to `<dest>.partial`, continuing a previous partial download,
+ * cancellable with `cancel_request(request_id)` (generated if not given)
+ * 4. Verifies the SHA-256 checksum and moves the file to `dest`
+ * 5. POSTs the path to /models/register
+ * 
+ * Emits `model_download_progress` while downloading.
+ */
+async downloadModel(urlOrId: string, dest: string | null, sha256: string | null, requestId: string | null) : Promise<Result<DownloadedModel, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("download_model", { urlOrId, dest, sha256, requestId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Use an engine that is already running at `socket_path` instead of spawning one.
+ * 
+ * This command:
+ * 1. Fails if an engine is already running
+ * 2. Connects to `socket_path` and checks GET /health
+ * 3. Starts the status polling loop against it
+ * 
+ * Returns the attach duration; emits `command_slow` past the lifecycle budget.
+ */
+async attachToEngine(socketPath: string) : Promise<Result<Timed<null>, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("attach_to_engine", { socketPath }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return the engine's current lifecycle state.
+ */
+async getEngineState() : Promise<Result<EngineState, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_engine_state") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return the recent lifecycle transitions and notes, oldest first.
+ */
+async getEngineLifecycleHistory() : Promise<Result<LifecycleRecord[], EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_engine_lifecycle_history") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Send user input to the AI Engine backend via Unix socket.
+ * 
+ * This command:
+ * 1. Updates the idle activity timestamp (resets idle counter), starting
+ * the engine first if it is stopped and `settings.engine.auto_start` is on
+ * 2. Sends user input as JSON POST to /input endpoint, tagged with the
+ * `request_id` correlation id (generated if not given)
+ * 3. Returns the parsed response (also emitted as `python_input`)
+ * 
+ * `cancel_request(request_id)` aborts the call (see requests). While it
+ * waits behind other inputs, `input_queued` reports its position and
+ * estimated start (see input_limiter).
+ * 
+ * `route` optionally assigns the input to a session and/or picks the model
+ * that answers it (see session_models), and sets its temperature, top_p,
+ * max_tokens, stop sequences and seed (see generation) and the prompt
+ * template wrapped around the input (see templates).
+ * 
+ * With an `on_token` channel the response is streamed: tokens are forwarded
+ * as ordered frames (see streaming) and the command returns
+ * `{ "output": <full text>, "stream_id": <id> }` once generation finishes.
+ * 
+ * Used when user interacts with the application.
+ * Communication: Direct Unix Domain Socket with HTTP request format.
+ * Returns the response and request duration; emits `command_slow` past the interactive budget.
+ */
+async sendInputToPython(input: string, timeoutMs: number | null, onToken: TAURI_CHANNEL<StreamFrame> | null, requestId: string | null, route: InputRoute | null) : Promise<Result<Timed<JsonValue>, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("send_input_to_python", { input, timeoutMs, onToken, requestId, route }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Send user input and stream the generated tokens over `on_frame`.
+ * 
+ * Returns the stream id once the terminal frame has been sent. Engine
+ * errors are reported in the terminal frame rather than as a command error.
+ * The stream can be cancelled with `cancel_request(request_id)`; `route`
+ * selects the session and model (see session_models).
+ */
+async streamInputToPython(input: string, onFrame: TAURI_CHANNEL<StreamFrame>, requestId: string | null, route: InputRoute | null) : Promise<Result<number, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("stream_input_to_python", { input, onFrame, requestId, route }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Set the generation parameters of inputs that don't set their own.
+ * 
+ * Replaces all defaults (unset fields go back to the engine's); returns
+ * the stored parameters.
+ */
+async setDefaultGenerationParams(params: GenerationParams) : Promise<Result<GenerationParams, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_default_generation_params", { params }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return where input `request_id` stands in the queue.
+ * 
+ * Position 0 means it has its slot; None if it is neither waiting nor running.
+ */
+async getQueuePosition(requestId: string) : Promise<Result<QueuePosition | null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_queue_position", { requestId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return exactly what the next usage statistics upload would send, and where.
+ * 
+ * The payload is already noised and thresholded; it stays the same until
+ * something new is counted or the analytics settings change.
+ */
+async previewAnalyticsPayload() : Promise<Result<AnalyticsPreview, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("preview_analytics_payload") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return every deprecation the engine has announced, most recently seen first.
+ */
+async getDeprecationNotices() : Promise<Result<DeprecationNotice[], EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_deprecation_notices") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Send a file (image, PDF, ...) with optional `metadata` to the AI Engine.
+ * 
+ * This command:
+ * 1. Checks the file and picks its content type from the extension
+ * 2. Counts the upload as in flight and starts a stopped engine
+ * 3. Streams the file as multipart/form-data to POST /upload, tagged
+ * with the `request_id` correlation id (generated if not given)
+ * 4. Returns the engine's response (also emitted as `python_input`)
+ * 
+ * `timeout_ms` overrides the configured chat timeout; it covers the
+ * transfer as well as the engine's answer.
+ */
+async sendFileToPython(path: string, metadata: JsonValue | null, requestId: string | null, timeoutMs: number | null) : Promise<Result<Timed<JsonValue>, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("send_file_to_python", { path, metadata, requestId, timeoutMs }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Download the response of GET `endpoint` to `dest_path`, emitting `download_progress`.
+ * 
+ * This command:
+ * 1. Checks `endpoint` against `settings.proxy.allowed_endpoints`
+ * 2. Counts the download as in flight, cancellable with
+ * `cancel_request(request_id)` (generated if not given)
+ * 3. Streams the body to `<dest_path>.partial`
+ * 4. Checks the size against Content-Length, then renames the file into place
+ */
+async downloadFromPython(endpoint: string, destPath: string, requestId: string | null) : Promise<Result<DownloadedFile, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("download_from_python", { endpoint, destPath, requestId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Send a request to `endpoint` and return the response body as raw bytes.
+ * 
+ * This command:
+ * 1. Checks `endpoint` against `settings.proxy.allowed_endpoints`
+ * 2. Counts the request as in flight, cancellable with
+ * `cancel_request(request_id)` (generated if not given)
+ * 3. POSTs `body` to `endpoint` (GET without a body)
+ * 4. Returns the body unparsed as an ArrayBuffer, so non-UTF-8 payloads
+ * come through intact
+ * 
+ * `timeout_ms` overrides the configured timeout for the endpoint's class.
+ */
+async fetchFromPython(endpoint: string, body: JsonValue | null, timeoutMs: number | null, requestId: string | null) : Promise<Result<unknown, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("fetch_from_python", { endpoint, body, timeoutMs, requestId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Forward a JSON request to an allowlisted engine endpoint and return its JSON response.
+ * 
+ * This command:
+ * 1. Checks `method` and `endpoint` against `settings.proxy.allowed_endpoints`
+ * 2. Counts the request as in flight, cancellable with
+ * `cancel_request(request_id)` (generated if not given)
+ * 3. Sends it through the transport middleware like the built-in commands
+ * 
+ * `timeout_ms` overrides the configured timeout for the endpoint's class.
+ */
+async callEngine(method: string, endpoint: string, body: JsonValue | null, timeoutMs: number | null, requestId: string | null) : Promise<Result<JsonValue, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("call_engine", { method, endpoint, body, timeoutMs, requestId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return the socket (pipe) automation clients connect to, or None if the broker is off.
+ */
+async getBrokerEndpoint() : Promise<Result<string | null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_broker_endpoint") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Create or replace pipeline `pipeline.name`.
+ */
+async savePipeline(pipeline: Pipeline) : Promise<Result<null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("save_pipeline", { pipeline }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Delete pipeline `name`; returns false if it didn't exist.
+ */
+async deletePipeline(name: string) : Promise<Result<boolean, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_pipeline", { name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return all pipelines, by name.
+ */
+async listPipelines() : Promise<Result<Pipeline[], EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_pipelines") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Run pipeline `name` on `input` and return the finished run.
+ * 
+ * This command:
+ * 1. Checks every step's endpoint against `settings.proxy.allowed_endpoints`
+ * 2. Runs the steps in order, emitting `pipeline_progress` on every change
+ * 3. Returns the run: completed with its output, or failed with the
+ * outputs of the steps that finished (see `resume_pipeline_run`)
+ * 
+ * The run can be cancelled with `cancel_request(request_id)`.
+ */
+async runPipeline(name: string, input: JsonValue, requestId: string | null) : Promise<Result<PipelineRun, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("run_pipeline", { name, input, requestId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Continue failed, cancelled or interrupted run `run_id` from its first unfinished step.
+ * 
+ * Finished steps keep their outputs. Fails if the pipeline's steps were
+ * renamed, added or removed since the run started. The run is marked
+ * running under the store lock, so concurrent resumes of it can't both
+ * start.
+ */
+async resumePipelineRun(runId: string, requestId: string | null) : Promise<Result<PipelineRun, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("resume_pipeline_run", { runId, requestId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return the recent pipeline runs, newest first.
+ */
+async listPipelineRuns() : Promise<Result<PipelineRun[], EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_pipeline_runs") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Called when user interacts with the frontend to reset idle timer.
+ * 
+ * This command updates the last activity timestamp, preventing
+ * the server from being stopped due to inactivity.
+ * Call this on any user action (clicks, input, etc).
+ */
+async onAppInteraction() : Promise<Result<null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("on_app_interaction") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Stop the AI Engine, optionally draining in-flight requests first.
+ * 
+ * This command:
+ * 1. Marks the engine as draining so new requests are refused
+ * 2. (drain) Waits for in-flight requests up to the deadline,
+ * emitting `engine_drain_progress` while it waits
+ * 3. Tears the engine down (graceful /stop for drain, kill for force)
+ * 4. Reports how many requests were abandoned
+ */
+async stopEngine(options: StopOptions | null) : Promise<Result<StopReport, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("stop_engine", { options }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Enable turbo mode for `duration_secs` seconds.
+ * 
+ * This command:
+ * 1. Opens (or extends) the turbo window, capped at TURBO_MAX_DURATION_SECS
+ * 2. Raises the engine's thread count to all available cores
+ * 3. Emits `turbo_started` to the frontend
+ * 4. Schedules a revert task that restores defaults and emits `turbo_ended`
+ * 
+ * Calling it again while active restarts the window with the new duration.
+ */
+async enableTurbo(durationSecs: number) : Promise<Result<null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("enable_turbo", { durationSecs }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Reserve capacity for a burst of about `estimate` requests.
+ * 
+ * This command:
+ * 1. Reserves up to MAX_SLOTS_PER_RESERVATION extra /input slots and
+ * batch workers for `ttl_secs` (default DEFAULT_TTL_SECS)
+ * 2. Starts queued jobs on the added workers
+ * 3. Pre-warms the engine connection in the background
+ * 4. Schedules the release once the reservation expires
+ * 
+ * Fails when all MAX_RESERVED_SLOTS are already reserved.
+ */
+async reserveCapacity(estimate: number, ttlSecs: number | null) : Promise<Result<CapacityReservation, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reserve_capacity", { estimate, ttlSecs }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * End reservation `reservation_id` before it expires; returns false if it wasn't active.
+ */
+async releaseCapacity(reservationId: string) : Promise<Result<boolean, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("release_capacity", { reservationId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Select the release channel used for engine updates.
+ */
+async setUpdateChannel(channel: UpdateChannel) : Promise<Result<null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_update_channel", { channel }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Report the running engine version with its channel and build metadata.
+ */
+async getEngineVersion() : Promise<Result<EngineVersionInfo, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_engine_version") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Check the selected channel's manifest for a newer engine build.
+ * 
+ * Older builds are reported as `downgrade_blocked` unless `allow_downgrade`
+ * is set, so switching from nightly back to stable never silently rolls back.
+ */
+async checkEngineUpdate(allowDowngrade: boolean | null) : Promise<Result<UpdateCheck, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("check_engine_update", { allowDowngrade }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return hourly or daily metric rollups for a time range.
+ * 
+ * Pending metrics are flushed first so the current hour is included.
+ */
+async getMetricsHistory(range: MetricsRange, granularity: Granularity) : Promise<Result<MetricsRollup[], EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_metrics_history", { range, granularity }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return request count, latency percentiles and error rate of the last METRICS_WINDOW_SECS.
+ */
+async getEngineMetrics() : Promise<Result<EngineMetrics, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_engine_metrics") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Record that the user accepted `model`'s license at `version_hash`.
+ */
+async acceptModelLicense(model: string, versionHash: string) : Promise<Result<null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("accept_model_license", { model, versionHash }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * List gated models whose current license version hasn't been accepted.
+ */
+async getPendingLicenses() : Promise<Result<ModelLicense[], EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_pending_licenses") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return the current settings.
+ */
+async getSettings() : Promise<Result<Settings, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_settings") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Replace the settings and persist them.
+ * 
+ * Fails with `invalid_request` naming every invalid key (see `get_settings_schema`).
+ */
+async updateSettings(settings: Settings) : Promise<Result<null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_settings", { settings }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return the settings layout version and its JSON Schema, for rendering a settings form.
+ */
+async getSettingsSchema() : Promise<Result<SettingsSchema, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_settings_schema") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Answer a pending engine host request.
+ * 
+ * Fails if the id is unknown or the request has already timed out.
+ */
+async respondToHostRequest(id: string, payload: JsonValue) : Promise<Result<null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("respond_to_host_request", { id, payload }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return the model decision made for the most recent engine start.
+ */
+async getModelSelection() : Promise<Result<ModelDecision | null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_model_selection") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * List the artifacts the engine has ready for saving.
+ */
+async listEngineArtifacts() : Promise<Result<EngineArtifact[], EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_engine_artifacts") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Download an artifact to `dest_path`, verify it, and remove the engine's copy.
+ * 
+ * This command:
+ * 1. Looks up the artifact's size and checksum
+ * 2. Streams the content to `<dest_path>.partial`, emitting `artifact_save_progress`
+ * 3. Verifies size and SHA-256, then renames the file into place
+ * 4. Asks the engine to delete its copy (failure is reported, not fatal)
+ */
+async saveArtifact(artifactId: string, destPath: string) : Promise<Result<SavedArtifact, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("save_artifact", { artifactId, destPath }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return the loaded templates and rules plus any current validation errors.
+ */
+async getTemplates() : Promise<Result<TemplatesSnapshot, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_templates") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Create or replace prompt template `name` (templates/<name>.json).
+ * 
+ * This command:
+ * 1. Checks the name and the template's placeholders
+ * 2. Writes the file atomically, with a checksum and a last-good backup
+ * (see atomic_file)
+ * 3. Reloads the templates, emitting `templates_reloaded`
+ */
+async setPromptTemplate(name: string, template: PromptTemplate) : Promise<Result<null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_prompt_template", { name, template }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return the loaded prompt templates, sorted by name.
+ */
+async listPromptTemplates() : Promise<Result<PromptTemplateInfo[], EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_prompt_templates") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Render prompt template `name` for `input`, as it would be sent to /input.
+ */
+async applyPromptTemplate(name: string, input: string, variables: Partial<{ [key in string]: string }> | null) : Promise<Result<RenderedPrompt, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("apply_prompt_template", { name, input, variables }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return a short sentence describing the engine's current state.
+ */
+async getStatusSummary() : Promise<Result<string, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_status_summary") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Override the engine socket path (None restores the per-user default).
+ * 
+ * Takes effect the next time the engine starts. Returns the endpoint that
+ * will be used.
+ */
+async setSocketPath(path: string | null) : Promise<Result<string, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_socket_path", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return the engine's task queue, freshly fetched when the engine is running.
+ */
+async getEngineQueue() : Promise<Result<EngineTask[], EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_engine_queue") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Move engine task `id` to `position` (0 = next to run).
+ */
+async reorderEngineTask(id: string, position: number) : Promise<Result<EngineTask[], EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reorder_engine_task", { id, position }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Cancel engine task `id` (queued or running).
+ */
+async cancelEngineTask(id: string) : Promise<Result<EngineTask[], EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("cancel_engine_task", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Abort in-flight request `request_id` and ask the engine to stop working on it.
+ */
+async cancelRequest(requestId: string) : Promise<Result<null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("cancel_request", { requestId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return the app identifier, build channel and the namespace derived from them.
+ */
+async getRuntimeIdentity() : Promise<Result<RuntimeIdentity, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_runtime_identity") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return the most recent engine log lines, oldest first (at most `limit`).
+ */
+async getEngineLogs(limit: number | null) : Promise<Result<EngineLogLine[], EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_engine_logs", { limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return the latest status in full (to resync after a missed delta).
+ */
+async getFullStatus() : Promise<Result<FullStatus, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_full_status") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Record that the frontend has applied status `version`; later deltas are based on it.
+ */
+async ackStatus(version: number) : Promise<Result<null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("ack_status", { version }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Everything a reloaded frontend needs to rebuild its view.
+ * 
+ * `since_seq` is the seq of the last buffered event the frontend saw, if it
+ * kept one across the reload.
+ */
+async resync(sinceSeq: number | null) : Promise<Result<ResyncSnapshot, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("resync", { sinceSeq }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Move open stream `stream_id` to `on_frame` (e.g. after a webview reload).
+ * 
+ * Chunks from `from_seq` (default 0) on are replayed to `on_frame` first;
+ * later chunks and the terminal frame follow on it in order. Returns the
+ * seq of the next live chunk.
+ */
+async resumeStream(streamId: number, fromSeq: number | null, onFrame: TAURI_CHANNEL<StreamFrame>) : Promise<Result<number, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("resume_stream", { streamId, fromSeq, onFrame }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Pin a model for all later messages of a session (`None` returns to the engine default).
+ */
+async setSessionModel(sessionId: string, model: string | null) : Promise<Result<null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_session_model", { sessionId, model }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return a session's pinned model and the model that answered each message.
+ */
+async getSessionModels(sessionId: string) : Promise<Result<SessionModels, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_session_models", { sessionId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Start a new chat session (`title` is optional).
+ */
+async createSession(title: string | null) : Promise<Result<StoredSession, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_session", { title }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * List the chat sessions, most recently used first.
+ */
+async listSessions() : Promise<Result<StoredSession[], EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_sessions") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Send user input to the AI Engine as part of a chat session.
+ * 
+ * This command:
+ * 1. Checks that the session exists
+ * 2. Sends the input like `send_input_to_python`, with `session_id` (and
+ * the session's pinned model, see `set_session_model`) in the /input payload
+ * 3. Counts the answered input in the session's metadata
+ * 
+ * `timeout_ms`, `on_token` and `request_id` work as for `send_input_to_python`.
+ */
+async sendInputToSession(sessionId: string, input: string, timeoutMs: number | null, onToken: TAURI_CHANNEL<StreamFrame> | null, requestId: string | null) : Promise<Result<Timed<JsonValue>, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("send_input_to_session", { sessionId, input, timeoutMs, onToken, requestId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Delete a chat session with its model override and answered messages.
+ */
+async deleteSession(sessionId: string) : Promise<Result<null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_session", { sessionId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return stored inputs and responses, newest first (of one session, or all).
+ * 
+ * `limit` defaults to DEFAULT_PAGE_SIZE and is capped at MAX_PAGE_SIZE.
+ */
+async getHistory(sessionId: string | null, offset: number | null, limit: number | null) : Promise<Result<StoredExchange[], EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_history", { sessionId, offset, limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Delete every stored input and response; returns how many were deleted.
+ * 
+ * Chat sessions and their model overrides are kept.
+ */
+async clearHistory() : Promise<Result<number, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("clear_history") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Move the history to the `target` backend and switch to it.
+ * 
+ * Everything is copied and the new backend saved in the settings before
+ * switching, so a failed migration leaves the current store in use. The
+ * previous backend's file is kept.
+ */
+async migrateStorage(target: StorageBackend) : Promise<Result<StorageMigration, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("migrate_storage", { target }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Change the idle timeout of the engine (takes effect on the next poll).
+ * 
+ * Shorter timeouts free memory sooner on laptops; turbo mode still extends
+ * the timeout while active.
+ */
+async setIdleTimeout(secs: number) : Promise<Result<null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_idle_timeout", { secs }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Keep the engine resident until it is stopped explicitly.
+ * 
+ * `set_idle_timeout` re-enables the timeout.
+ */
+async disableIdleTimeout() : Promise<Result<null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("disable_idle_timeout") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Store the API key or custom header value for a provider in the keychain.
+ */
+async setProviderCredentials(provider: string, secret: string) : Promise<Result<null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_provider_credentials", { provider, secret }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Remove a provider's credentials from the keychain.
+ */
+async clearProviderCredentials(provider: string) : Promise<Result<null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("clear_provider_credentials", { provider }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Start an OAuth device login; completion is reported as `provider_login_finished`.
+ */
+async startProviderLogin(provider: string) : Promise<Result<DeviceLogin, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("start_provider_login", { provider }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Make one authenticated request to the provider to check its credentials.
+ */
+async testProviderCredentials(provider: string) : Promise<Result<CredentialCheck, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("test_provider_credentials", { provider }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return the engine's declared network behavior and the connections observed so far.
+ */
+async getEngineNetworkActivity() : Promise<Result<NetworkActivity, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_engine_network_activity") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Queue an input for the engine; returns the job id immediately.
+ * 
+ * Takes the same `timeout_ms` and `route` as `send_input_to_python`;
+ * `priority` (default 0) lets urgent jobs start first.
+ */
+async submitInput(input: string, priority: number | null, timeoutMs: number | null, route: InputRoute | null) : Promise<Result<string, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("submit_input", { input, priority, timeoutMs, route }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return the status of job `id`.
+ */
+async getJobStatus(id: string) : Promise<Result<JobStatus, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_job_status", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * List all known jobs: running, then queued in start order, then finished (newest first).
+ */
+async listJobs() : Promise<Result<JobStatus[], EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_jobs") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Cancel job `id`: a queued job is dropped from the queue, a running one is aborted.
+ */
+async cancelJob(id: string) : Promise<Result<null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("cancel_job", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Report how busy each worker has been since the app started.
+ */
+async getWorkerUtilization() : Promise<Result<WorkerUtilization[], EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_worker_utilization") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Apply the retention settings now; with `dry_run` only report what would be deleted.
+ */
+async runRetentionNow(dryRun: boolean | null) : Promise<Result<RetentionReport, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("run_retention_now", { dryRun }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Check the installation without changing anything.
+ */
+async checkInstallation() : Promise<Result<InstallationReport, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("check_installation") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Repair what the installation checks find broken and report each step.
+ * 
+ * This command:
+ * 1. Makes a non-executable engine binary executable again (a missing or
+ * empty one fails the step; only a reinstall restores it)
+ * 2. Recreates the socket directory with owner-only access (Unix)
+ * 3. Restores settings.json from the last good backup
+ * 4. Moves corrupt databases aside and recreates them empty
+ */
+async repairInstallation() : Promise<Result<InstallationReport, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("repair_installation") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * List the bundled engine variants (empty without a manifest).
+ */
+async listEngineVariants() : Promise<Result<EngineVariantInfo[], EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_engine_variants") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Select the engine variant to run on this machine; restarts a running engine on it.
+ */
+async selectEngineVariant(name: string) : Promise<Result<Timed<EngineVariantInfo>, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("select_engine_variant", { name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Explain why responses may be slow on this machine, most likely cause first.
+ */
+async explainPerformance() : Promise<Result<PerformanceReport, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("explain_performance") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Start recording to a new session file; returns its path.
+ */
+async startRecording() : Promise<Result<string, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("start_recording") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Stop the recording in progress and flush its file.
+ */
+async stopRecording() : Promise<Result<RecordingSummary, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("stop_recording") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Replay a recorded session against a mock engine (see module docs).
+ * 
+ * Returns once the replay has started; `replay_finished` follows at the end.
+ * `speed` scales the recorded timing (2.0 = twice as fast).
+ */
+async replayRecording(path: string, speed: number | null) : Promise<Result<ReplaySummary, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("replay_recording", { path, speed }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Back up settings, history and templates now.
+ */
+async createBackupNow() : Promise<Result<BackupInfo, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_backup_now") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Restore settings, history and templates from the backup at `path`.
+ * 
+ * Nothing is changed unless the whole backup decrypts and parses.
+ */
+async restoreBackup(path: string) : Promise<Result<RestoredBackup, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("restore_backup", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return the engine's current CPU, memory and uptime.
+ * 
+ * CPU usage is averaged since the previous sample (at most
+ * RESOURCE_SAMPLE_INTERVAL_SECS ago while the engine runs).
+ */
+async getEngineResources() : Promise<Result<EngineResources, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_engine_resources") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return what the running engine supports (models, context size, streaming, protocol).
+ */
+async getEngineCapabilities() : Promise<Result<EngineCapabilities, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_engine_capabilities") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return every feature with a kill switch and whether it is enabled.
+ */
+async getFeatureFlags() : Promise<Result<FeatureFlag[], EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_feature_flags") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return the measured offset between the engine's clock and the host's.
+ * 
+ * Fails with `not_running` until an engine with timestamped status has been measured.
+ */
+async getClockSync() : Promise<Result<ClockSync, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_clock_sync") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Register "Ask AI about selection" in the OS context menu for this user.
+ * 
+ * This command:
+ * 1. Fails on platforms without an integration (anything but macOS and Windows)
+ * 2. Writes the Quick Action (macOS) or HKCU registry entry (Windows)
+ * pointing at the current executable; re-running updates it
+ */
+async installContextMenu() : Promise<Result<ContextMenuStatus, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("install_context_menu") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Remove the context-menu entry again (a no-op if it isn't registered).
+ */
+async uninstallContextMenu() : Promise<Result<ContextMenuStatus, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("uninstall_context_menu") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Whether the context-menu entry is supported here and registered.
+ */
+async getContextMenuStatus() : Promise<Result<ContextMenuStatus, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_context_menu_status") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return the live temporary files and their disk usage, per owner.
+ */
+async getTempUsage() : Promise<Result<TempUsage, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_temp_usage") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Report how often the engine failed to echo the trace id, for diagnostics.
+ */
+async getProtocolWarnings() : Promise<Result<ProtocolWarnings, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_protocol_warnings") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Start fetching the default model in the background (call after onboarding).
+ * 
+ * This command:
+ * 1. Reads the model URL from `settings.prefetch`
+ * 2. Fails with `license_required` if the model is gated and its license
+ * wasn't accepted (see licenses)
+ * 3. Returns the existing job if it is for the same URL (resuming a paused or failed one)
+ * 4. Otherwise discards any other job and starts downloading
+ */
+async startPrefetch() : Promise<Result<PrefetchStatus, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("start_prefetch") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Pause the running prefetch, keeping what was downloaded.
+ */
+async pausePrefetch() : Promise<Result<PrefetchStatus, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("pause_prefetch") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Continue a paused or failed prefetch where it stopped.
+ * 
+ * Fails with `license_required` if the model is gated and its license
+ * wasn't accepted (see licenses).
+ */
+async resumePrefetch() : Promise<Result<PrefetchStatus, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("resume_prefetch") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Return the prefetch job, if one was ever started.
+ */
+async getPrefetchStatus() : Promise<Result<PrefetchStatus | null, EngineError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_prefetch_status") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+}
+}
+
+/** user-defined events **/
+
+
+export const events = __makeEvents__<{
+artifactSaveProgress: ArtifactSaveProgress,
+backupCreated: BackupCreated,
+backupFailed: BackupFailed,
+commandSlow: CommandSlow,
+deprecationNotice: DeprecationNoticed,
+downloadProgress: DownloadProgress,
+engineCrashed: EngineCrashed,
+engineDrainProgress: EngineDrainProgress,
+engineEvent: EngineEvent,
+engineLog: EngineLog,
+engineMetrics: EngineMetricsSampled,
+engineNetworkActivity: EngineNetworkActivity,
+engineQueueChanged: EngineQueueChanged,
+engineResources: EngineResourcesSampled,
+engineStartupProgress: EngineStartupProgress,
+engineStateChanged: EngineStateChanged,
+hostRequest: HostRequest,
+hostRequestExpired: HostRequestExpired,
+inputQueued: InputQueued,
+installationProblems: InstallationProblems,
+jobUpdated: JobUpdated,
+modelChanged: ModelChanged,
+modelDowngraded: ModelDowngraded,
+modelDownloadProgress: ModelDownloadProgress,
+modelReady: ModelReady,
+pipelineProgress: PipelineProgress,
+prefetchProgress: PrefetchProgress,
+providerLoginFinished: ProviderLoginFinished,
+pythonInput: PythonInput,
+pythonStatus: PythonStatus,
+pythonStatusDelta: PythonStatusDelta,
+queueDepth: QueueDepth,
+replayFinished: ReplayFinished,
+selectionAnswered: SelectionAnswered,
+selectionReceived: SelectionReceived,
+sessionModelSwitched: SessionModelSwitched,
+settingsRecovered: SettingsRecovered,
+statusSummaryChanged: StatusSummaryChanged,
+streamRecordSkipped: StreamRecordSkipped,
+streamTruncated: StreamTruncated,
+templatesReloaded: TemplatesReloaded,
+turboEnded: TurboEnded,
+turboStarted: TurboStarted
+}>({
+artifactSaveProgress: "artifact_save_progress",
+backupCreated: "backup_created",
+backupFailed: "backup_failed",
+commandSlow: "command_slow",
+deprecationNotice: "deprecation_notice",
+downloadProgress: "download_progress",
+engineCrashed: "engine_crashed",
+engineDrainProgress: "engine_drain_progress",
+engineEvent: "engine_event",
+engineLog: "engine_log",
+engineMetrics: "engine_metrics",
+engineNetworkActivity: "engine_network_activity",
+engineQueueChanged: "engine_queue_changed",
+engineResources: "engine_resources",
+engineStartupProgress: "engine_startup_progress",
+engineStateChanged: "engine_state_changed",
+hostRequest: "host_request",
+hostRequestExpired: "host_request_expired",
+inputQueued: "input_queued",
+installationProblems: "installation_problems",
+jobUpdated: "job_updated",
+modelChanged: "model_changed",
+modelDowngraded: "model_downgraded",
+modelDownloadProgress: "model_download_progress",
+modelReady: "model_ready",
+pipelineProgress: "pipeline_progress",
+prefetchProgress: "prefetch_progress",
+providerLoginFinished: "provider_login_finished",
+pythonInput: "python_input",
+pythonStatus: "python_status",
+pythonStatusDelta: "python_status_delta",
+queueDepth: "queue_depth",
+replayFinished: "replay_finished",
+selectionAnswered: "selection_answered",
+selectionReceived: "selection_received",
+sessionModelSwitched: "session_model_switched",
+settingsRecovered: "settings_recovered",
+statusSummaryChanged: "status_summary_changed",
+streamRecordSkipped: "stream_record_skipped",
+streamTruncated: "stream_truncated",
+templatesReloaded: "templates_reloaded",
+turboEnded: "turbo_ended",
+turboStarted: "turbo_started"
+})
+
+/** user-defined constants **/
+
+
+
+/** user-defined types **/
+
+/**
+ * An in-flight request as reported by `resync`.
+ */
+export type ActiveRequest = { request_id: string; elapsed_ms: number }
+/**
+ * Exactly what one upload sends.
+ */
+export type AnalyticsPayload = { version: number; 
+/**
+ * First and last day (days since the Unix epoch, UTC) the batch covers
+ */
+period_start_day: number; period_end_day: number; active_days: number; features: Partial<{ [key in string]: number }> }
+/**
+ * Response of `preview_analytics_payload`.
+ */
+export type AnalyticsPreview = { enabled: boolean; endpoint: string | null; 
+/**
+ * Unix seconds from which the next upload is due
+ */
+next_upload_at: number | null; 
+/**
+ * None if nothing was counted since the last upload
+ */
+payload: AnalyticsPayload | null }
+/**
+ * Usage statistics, persisted under `settings.analytics`; off by default.
+ */
+export type AnalyticsSettings = { 
+/**
+ * Count usage and upload it; off unless the user opts in
+ */
+enabled: boolean; 
+/**
+ * URL the batches are POSTed to; None keeps the counts local
+ */
+endpoint: string | null; upload_interval_hours: number; 
+/**
+ * Privacy budget per count; smaller adds more noise
+ */
+noise_epsilon: number; 
+/**
+ * Features with a (noisy) count below this are not reported
+ */
+min_count: number }
+/**
+ * A message of a session and the model that answered it.
+ */
+export type AnsweredMessage = { request_id: string; model: string | null; answered_at: number; 
+/**
+ * False if the answer's stream was truncated
+ */
+complete: boolean }
+/**
+ * Download progress while `save_artifact` runs.
+ */
+export type ArtifactSaveProgress = { id: string; bytes_written: number; total_bytes: number }
+/**
+ * How requests to a provider are authenticated.
+ */
+export type AuthMethod = 
+/**
+ * `Authorization: Bearer <key>`
+ */
+{ type: "api_key" } | 
+/**
+ * `<header>: <value>`
+ */
+{ type: "custom_header"; header: string } | 
+/**
+ * OAuth 2.0 device authorization grant (RFC 8628)
+ */
+{ type: "oauth_device"; client_id: string; device_authorization_url: string; token_url: string; scope?: string | null }
+/**
+ * A backup was written.
+ */
+export type BackupCreated = { backup: BackupInfo; trigger: BackupTrigger }
+/**
+ * A backup attempt failed.
+ */
+export type BackupFailed = { error: string; trigger: BackupTrigger }
+/**
+ * A backup that was written.
+ */
+export type BackupInfo = { path: string; 
+/**
+ * Unix seconds
+ */
+created_at: number; size_bytes: number }
+/**
+ * Backup schedule, persisted under `settings.backup`.
+ */
+export type BackupSettings = { enabled: boolean; interval_hours: number; 
+/**
+ * Destination directory; None uses `backups` in the app data dir
+ */
+directory: string | null; 
+/**
+ * Backups kept; older ones are deleted (0 keeps all)
+ */
+keep: number }
+/**
+ * What started a backup.
+ */
+export type BackupTrigger = "scheduled" | "manual"
+/**
+ * Secondary socket for external automations, persisted under `settings.broker`.
+ */
+export type BrokerSettings = { 
+/**
+ * Accept external clients; off by default
+ */
+enabled: boolean; 
+/**
+ * Socket path (pipe name on Windows); None uses a per-build default
+ */
+endpoint: string | null }
+/**
+ * An emitted event as replayed by `resync`.
+ */
+export type BufferedEvent = { 
+/**
+ * Increasing emission number
+ */
+seq: number; name: string; payload: JsonValue; at_ms: number }
+/**
+ * A granted reservation, returned by `reserve_capacity`.
+ */
+export type CapacityReservation = { 
+/**
+ * Pass as `route.reservation_id` with the burst's requests
+ */
+id: string; estimate: number; 
+/**
+ * Extra /input slots and batch workers reserved
+ */
+slots: number; 
+/**
+ * Unix time in seconds
+ */
+expires_at: number }
+/**
+ * Measured offset between the engine's clock and the host's.
+ */
+export type ClockSync = { 
+/**
+ * Engine clock minus host clock; positive if the engine is ahead
+ */
+offset_ms: number; 
+/**
+ * Shortest round trip seen, which bounds the error of the offset (± half of it)
+ */
+best_rtt_ms: number; 
+/**
+ * Samples that went into the estimate
+ */
+samples: number; 
+/**
+ * Host time of the last accepted sample (epoch milliseconds)
+ */
+updated_at_ms: number }
+/**
+ * A command exceeded its execution budget and is still running.
+ */
+export type CommandSlow = { command: string; phase: string; elapsed_ms: number; budget_ms: number }
+/**
+ * Compression settings, persisted under `settings.compression`.
+ */
+export type CompressionSettings = { enabled: boolean; 
+/**
+ * Smallest body (in bytes) worth compressing
+ */
+threshold_bytes: number }
+/**
+ * Whether the context-menu entry is registered, as returned by the commands.
+ */
+export type ContextMenuStatus = { 
+/**
+ * This OS has a context-menu integration (macOS, Windows)
+ */
+supported: boolean; installed: boolean; 
+/**
+ * Quick Action bundle or registry key of the entry
+ */
+location: string | null }
+/**
+ * What revealed a crash.
+ */
+export type CrashCause = 
+/**
+ * The engine process exited
+ */
+"exited" | 
+/**
+ * The engine's socket file was deleted
+ */
+"socket_deleted"
+/**
+ * Result of `test_provider_credentials`.
+ */
+export type CredentialCheck = { provider: string; valid: boolean; 
+/**
+ * HTTP status returned by the provider
+ */
+status: number; message: string }
+/**
+ * One measurement behind a finding.
+ */
+export type DataPoint = { name: string; value: string }
+/**
+ * Response of GET /network.
+ */
+export type DeclaredNetwork = { mode: NetworkMode; 
+/**
+ * Hosts the engine may contact
+ */
+hosts?: string[]; 
+/**
+ * Why the engine needs the network
+ */
+purpose?: string | null }
+/**
+ * What a notice deprecates.
+ */
+export type DeprecationKind = "endpoint" | "model" | "field" | "other"
+/**
+ * A deprecation announced by the engine.
+ */
+export type DeprecationNotice = { 
+/**
+ * De-duplication key: the engine's `code`, else "<kind>:<subject>"
+ */
+id: string; kind: DeprecationKind; 
+/**
+ * The deprecated endpoint, model or field
+ */
+subject: string; message: string; 
+/**
+ * Unix seconds it was deprecated at, from a `Deprecation: @<epoch>` header
+ */
+deprecated_at: number | null; 
+/**
+ * When it stops working, as the engine wrote it
+ */
+sunset: string | null; replacement: string | null; link: string | null; 
+/**
+ * Endpoint whose response carried the notice
+ */
+endpoint: string; 
+/**
+ * Unix seconds
+ */
+first_seen: number; last_seen: number; occurrences: number }
+/**
+ * The engine announced a deprecation not seen before on this install.
+ */
+export type DeprecationNoticed = DeprecationNotice
+/**
+ * What the user needs to complete a device login, returned by `start_provider_login`.
+ */
+export type DeviceLogin = { provider: string; user_code: string; verification_uri: string; verification_uri_complete: string | null; expires_in: number }
+/**
+ * Progress of a `download_from_python` transfer.
+ */
+export type DownloadProgress = { request_id: string; endpoint: string; bytes_written: number; 
+/**
+ * From Content-Length; None if the engine didn't send one
+ */
+total_bytes: number | null }
+/**
+ * Result of a successful `download_from_python`.
+ */
+export type DownloadedFile = { request_id: string; path: string; size_bytes: number; 
+/**
+ * Content-Type reported by the engine
+ */
+content_type: string | null }
+/**
+ * Result of a successful `download_model`.
+ */
+export type DownloadedModel = { request_id: string; url: string; path: string; size_bytes: number; 
+/**
+ * Lowercase hex SHA-256 of the file
+ */
+sha256: string; 
+/**
+ * Whether the checksum was checked against an expected one
+ */
+verified: boolean; 
+/**
+ * Name the engine registered the model under, if it reported one
+ */
+model: string | null }
+/**
+ * Statistics of one endpoint.
+ */
+export type EndpointLatency = { endpoint: string; stats: LatencyStats }
+/**
+ * An artifact the engine has ready for the user.
+ */
+export type EngineArtifact = { id: string; name: string; size_bytes: number; 
+/**
+ * Lowercase hex SHA-256 of the content
+ */
+sha256: string }
+/**
+ * What the running engine supports, as returned by `get_engine_capabilities`.
+ */
+export type EngineCapabilities = { protocol_version: number; 
+/**
+ * Models the engine can load (empty if not declared)
+ */
+models: string[]; 
+/**
+ * Longest input context in tokens, if declared
+ */
+max_context_tokens: number | null; 
+/**
+ * Whether /input can stream tokens, if declared
+ */
+streaming: boolean | null; 
+/**
+ * False for engines without /capabilities (the protocol version is assumed)
+ */
+declared: boolean; 
+/**
+ * Features switched off in the settings (see feature_flags), as of the call
+ */
+disabled_features: string[] }
+/**
+ * The engine process exited or lost its socket unexpectedly.
+ */
+export type EngineCrashed = { cause: CrashCause; code: number | null; signal: number | null; will_restart: boolean; restart_in_ms: number | null }
+/**
+ * Progress while `stop_engine` waits for in-flight requests.
+ */
+export type EngineDrainProgress = { in_flight: number; elapsed_ms: number; deadline_ms: number }
+/**
+ * Wire shape of `EngineError` for the TypeScript bindings.
+ */
+export type EngineError = { kind: ErrorKind; message: string; 
+/**
+ * Set for `socket_unavailable`
+ */
+reason: SocketFailure | null; 
+/**
+ * Suggested next step, set for `socket_unavailable`
+ */
+action: string | null; 
+/**
+ * HTTP status, set for `http_error`
+ */
+status: number | null; 
+/**
+ * JSON error body from the engine, set for `http_error` when there is
+ * one; the license's model and version_hash for `license_required`
+ */
+details: JsonValue | null }
+/**
+ * An event pushed by the engine over its /events stream.
+ */
+export type EngineEvent = { name: string; data: JsonValue }
+/**
+ * A line of engine stdout/stderr.
+ */
+export type EngineLog = EngineLogLine
+/**
+ * One line of engine output.
+ */
+export type EngineLogLine = { 
+/**
+ * Increasing sequence number, to merge backfill with live events
+ */
+seq: number; stream: LogStream; line: string; timestamp_ms: number }
+/**
+ * Rolling engine latency, returned by `get_engine_metrics` and emitted as `engine_metrics`.
+ */
+export type EngineMetrics = { window_secs: number; overall: LatencyStats; 
+/**
+ * Busiest endpoint first
+ */
+endpoints: EndpointLatency[] }
+/**
+ * Periodic rolling latency statistics of engine requests.
+ */
+export type EngineMetricsSampled = { metrics: EngineMetrics }
+/**
+ * The engine was seen connected to an internet or undeclared address.
+ */
+export type EngineNetworkActivity = { 
+/**
+ * Remote address and port
+ */
+remote: string; external: boolean; 
+/**
+ * Not covered by the engine's declared network mode
+ */
+undeclared: boolean; 
+/**
+ * The engine's declared mode (None if it made no declaration)
+ */
+declared_mode: NetworkMode | null }
+/**
+ * The engine's task queue changed (tasks ordered by position).
+ */
+export type EngineQueueChanged = { tasks: EngineTask[] }
+/**
+ * CPU and memory use of the engine.
+ */
+export type EngineResources = { pid: number; 
+/**
+ * Summed over the engine's processes; 100 = one core fully busy
+ */
+cpu_percent: number; 
+/**
+ * Resident memory of the engine's processes
+ */
+rss_bytes: number; uptime_secs: number; system_total_bytes: number; system_available_bytes: number; 
+/**
+ * Available system memory is below LOW_MEMORY_PERCENT of the total
+ */
+low_memory: boolean }
+/**
+ * Periodic CPU and memory sample of the engine.
+ */
+export type EngineResourcesSampled = { resources: EngineResources }
+/**
+ * Engine lifecycle settings.
+ */
+export type EngineSettings = { 
+/**
+ * Start the engine on the first input instead of requiring start_python_script
+ */
+auto_start: boolean; 
+/**
+ * /input requests sent to the engine at the same time; more wait (see input_limiter)
+ */
+max_concurrent_inputs: number }
+/**
+ * Engine part of the resync snapshot.
+ */
+export type EngineSnapshot = { state: EngineState; running: boolean }
+/**
+ * Engine startup phase while waiting for its socket.
+ */
+export type EngineStartupProgress = { 
+/**
+ * "unpacking_runtime" or "loading_model"
+ */
+phase: string; 
+/**
+ * Size of the unpacked runtime so far, for onefile builds
+ */
+unpacked_mb: number | null }
+/**
+ * Lifecycle state of the engine.
+ */
+export type EngineState = "stopped" | "starting" | "ready" | "busy" | "stopping" | "crashed"
+/**
+ * The engine moved to a new lifecycle state.
+ */
+export type EngineStateChanged = { state: EngineState; previous: EngineState; reason: string; at_ms: number }
+/**
+ * A task in the engine's job queue.
+ */
+export type EngineTask = { id: string; name?: string; 
+/**
+ * "queued", "running", ...
+ */
+state?: string; 
+/**
+ * 0-based position in the queue
+ */
+position?: number; 
+/**
+ * Completion fraction 0.0 - 1.0, if the engine reports it
+ */
+progress?: number | null }
+/**
+ * A variant as reported by `list_engine_variants`.
+ */
+export type EngineVariantInfo = ({ name: string; 
+/**
+ * Executable next to the app executable, without suffix
+ */
+binary: string; description?: string | null; size_mb?: number | null; requires?: HardwareRequirements }) & { 
+/**
+ * Its binary is present
+ */
+installed: boolean; 
+/**
+ * This machine meets its hardware requirements
+ */
+supported: boolean; 
+/**
+ * Why it isn't supported, if it isn't
+ */
+unsupported_reason: string | null; default: boolean; selected: boolean }
+/**
+ * Engine version plus the channel and build it came from.
+ */
+export type EngineVersionInfo = { version: string | null; channel: UpdateChannel; build: JsonValue }
+/**
+ * The `kind` values, as returned by `EngineError::kind`.
+ */
+export type ErrorKind = "not_running" | "spawn_failed" | "socket_unavailable" | "timeout" | "incompatible_engine" | "http_error" | "bad_response" | "cancelled" | "shutting_down" | "invalid_request" | "feature_disabled" | "license_required" | "internal"
+/**
+ * State of one feature, as returned by `get_feature_flags`.
+ */
+export type FeatureFlag = { name: string; description: string; enabled: boolean }
+/**
+ * Features switched off, persisted under `settings.features`.
+ */
+export type FeatureSettings = { 
+/**
+ * Names of the disabled features (see `get_feature_flags`)
+ */
+disabled: string[] }
+/**
+ * What a filter rule does with matching text.
+ */
+export type FilterAction = 
+/**
+ * Reject the text outright
+ */
+"block" | 
+/**
+ * Replace each match with `replacement`
+ */
+"redact"
+/**
+ * A filter rule as written in a rules file.
+ */
+export type FilterRule = { name: string; 
+/**
+ * Regular expression matched against the text
+ */
+pattern: string; action: FilterAction; replacement?: string | null }
+/**
+ * One explanation of slowness.
+ */
+export type Finding = { factor: PerformanceFactor; severity: Severity; summary: string; data: DataPoint[] }
+/**
+ * Response of `get_full_status`.
+ */
+export type FullStatus = { version: number; status: JsonValue }
+/**
+ * Sampling settings of a generation; None leaves a field to the engine.
+ */
+export type GenerationParams = { 
+/**
+ * 0 is greedy; at most MAX_TEMPERATURE
+ */
+temperature?: number | null; 
+/**
+ * Nucleus sampling mass, in (0, 1]
+ */
+top_p?: number | null; max_tokens?: number | null; 
+/**
+ * Generation ends before any of these
+ */
+stop?: string[] | null; 
+/**
+ * Fixed seed for reproducible sampling
+ */
+seed?: number | null }
+/**
+ * Bucket size for history queries.
+ */
+export type Granularity = "hour" | "day"
+/**
+ * Hardware a variant needs.
+ */
+export type HardwareRequirements = { 
+/**
+ * GPU runtime: "cuda", "rocm" or "metal"
+ */
+gpu: string | null; 
+/**
+ * Total system memory needed
+ */
+min_memory_mb: number | null }
+/**
+ * A request from the engine to the host, as listed in /status.
+ */
+export type HostRequest = { id: string; kind: string; payload?: JsonValue; timeout_ms?: number | null }
+/**
+ * A host request was not answered before its deadline.
+ */
+export type HostRequestExpired = { id: string }
+/**
+ * A waiting input's position in the /input queue changed.
+ */
+export type InputQueued = QueuePosition
+/**
+ * Session and model routing of one input, plus its generation parameters and template (all optional).
+ */
+export type InputRoute = { session_id: string | null; 
+/**
+ * Model for this message only, overriding the session's model
+ */
+model: string | null; 
+/**
+ * Capacity reservation the input belongs to, for admission priority (see reservations)
+ */
+reservation_id: string | null; 
+/**
+ * Sampling settings for this input, over the defaults (see generation)
+ */
+generation: GenerationParams | null; 
+/**
+ * Prompt template wrapped around the input (see templates)
+ */
+template: TemplateRef | null }
+/**
+ * The startup preflight found parts of the installation broken.
+ */
+export type InstallationProblems = { 
+/**
+ * The checks that failed (see `repair_installation`)
+ */
+steps: RepairStep[] }
+/**
+ * Response of `check_installation` and `repair_installation`.
+ */
+export type InstallationReport = { 
+/**
+ * No step is broken or failed
+ */
+healthy: boolean; steps: RepairStep[] }
+/**
+ * Job queue settings, persisted under `settings.jobs`.
+ */
+export type JobSettings = { 
+/**
+ * Jobs running against the engine at the same time
+ */
+concurrency: number; 
+/**
+ * Let a queued job use the interactive worker while it is idle
+ */
+work_stealing: boolean }
+/**
+ * Lifecycle state of a job.
+ */
+export type JobState = "queued" | "running" | "completed" | "failed" | "cancelled"
+/**
+ * A job as reported to the frontend.
+ */
+export type JobStatus = { id: string; state: JobState; priority: number; 
+/**
+ * Place in the queue (0 = next to start) while queued
+ */
+position: number | null; submitted_at: number; started_at: number | null; finished_at: number | null; 
+/**
+ * Engine response of a completed job
+ */
+result: JsonValue | null; error: string | null }
+/**
+ * A job changed state or queue position.
+ */
+export type JobUpdated = JobStatus
+export type JsonValue = null | boolean | number | string | JsonValue[] | Partial<{ [key in string]: JsonValue }>
+/**
+ * Latency statistics of a set of requests.
+ */
+export type LatencyStats = { count: number; errors: number; 
+/**
+ * errors / count (0 without requests)
+ */
+error_rate: number; p50_ms: number; p95_ms: number; p99_ms: number }
+/**
+ * One entry of the lifecycle history.
+ */
+export type LifecycleRecord = { state: EngineState; 
+/**
+ * Equal to `state` for notes that didn't change the state
+ */
+previous: EngineState; reason: string; at_ms: number }
+/**
+ * Output stream a log line came from.
+ */
+export type LogStream = "stdout" | "stderr"
+/**
+ * Time range (Unix seconds, inclusive) for history queries.
+ */
+export type MetricsRange = { from: number; to: number }
+/**
+ * One hourly or daily rollup row.
+ */
+export type MetricsRollup = { 
+/**
+ * Start of the bucket (Unix seconds, UTC)
+ */
+bucket: number; requests: number; errors: number; tokens: number; latency_p50_ms: number; latency_p95_ms: number; latency_p99_ms: number; uptime_secs: number; crashes: number; 
+/**
+ * Size of gzip-compressed request bodies before compression
+ */
+bytes_uncompressed: number; 
+/**
+ * Size of the same bodies after compression
+ */
+bytes_compressed: number; 
+/**
+ * bytes_compressed / bytes_uncompressed, if anything was compressed
+ */
+compression_ratio: number | null; 
+/**
+ * Responses replaced by the moderation policy message
+ */
+moderation_blocked: number }
+/**
+ * The engine's loaded model changed (see models).
+ */
+export type ModelChanged = { previous: string | null; 
+/**
+ * None once no model is loaded
+ */
+current: string | null }
+/**
+ * Model chosen for an engine start and why.
+ */
+export type ModelDecision = { 
+/**
+ * First tier of the chain
+ */
+requested: string; selected: string; 
+/**
+ * Available memory at selection time, if it could be detected
+ */
+available_mb: number | null; required_mb: number; downgraded: boolean }
+/**
+ * A smaller model tier was selected because the preferred one doesn't fit in memory.
+ */
+export type ModelDowngraded = { requested: string; selected: string; available_mb: number; required_mb: number }
+/**
+ * Bytes of a `download_model` transfer so far.
+ */
+export type ModelDownloadProgress = { request_id: string; url: string; 
+/**
+ * Where the model ends up once complete
+ */
+path: string; 
+/**
+ * Including what an earlier, interrupted download already fetched
+ */
+bytes_done: number; 
+/**
+ * None until the server reports a size
+ */
+total_bytes: number | null }
+/**
+ * A model the engine can serve.
+ */
+export type ModelInfo = { name: string; loaded: boolean; size_mb: number | null }
+/**
+ * A license the engine requires for a model.
+ */
+export type ModelLicense = { model: string; version_hash: string; name?: string | null; url?: string | null }
+/**
+ * Models of the engine, returned by `list_models`.
+ */
+export type ModelList = { models: ModelInfo[]; 
+/**
+ * Currently loaded model, if any
+ */
+loaded: string | null }
+/**
+ * Readiness of a model.
+ */
+export type ModelReadiness = "loading" | "ready" | "failed"
+/**
+ * The engine reports a model resident in memory (after `preload_model` or on its own).
+ */
+export type ModelReady = { model: string; 
+/**
+ * Time from `preload_model` until the model was resident, if it was preloaded
+ */
+load_ms: number | null }
+/**
+ * Model selection settings.
+ */
+export type ModelSettings = { 
+/**
+ * Model tiers from most to least preferred (see model_fallback)
+ */
+fallback_chain: ModelTier[] }
+/**
+ * One entry of the fallback chain.
+ */
+export type ModelTier = { name: string; min_memory_mb: number }
+/**
+ * A model's readiness, returned by `preload_model` and `get_model_readiness`.
+ */
+export type ModelWarmup = { model: string; readiness: ModelReadiness; 
+/**
+ * Time until the model was resident, if it was preloaded
+ */
+load_ms: number | null; error: string | null; 
+/**
+ * Unix time in seconds of the last change
+ */
+updated_at: number }
+/**
+ * Response moderation settings.
+ */
+export type ModerationSettings = { strictness: Strictness; 
+/**
+ * Shown instead of a blocked response
+ */
+policy_message: string }
+/**
+ * Response of `get_engine_network_activity`.
+ */
+export type NetworkActivity = { 
+/**
+ * The engine's declaration (None if it made none)
+ */
+declared: DeclaredNetwork | null; 
+/**
+ * Whether connections are observed on this platform
+ */
+monitoring: boolean; 
+/**
+ * Any observed connection reached the internet
+ */
+external: boolean; 
+/**
+ * Observed connections, ordered by remote address
+ */
+connections: ObservedConnection[] }
+/**
+ * Network behavior an engine declares.
+ */
+export type NetworkMode = 
+/**
+ * No network access at all
+ */
+"offline" | 
+/**
+ * Loopback and local network only
+ */
+"local" | 
+/**
+ * May reach the internet
+ */
+"online"
+/**
+ * A remote endpoint the engine was seen connected to.
+ */
+export type ObservedConnection = { remote: string; 
+/**
+ * Outside loopback, private and link-local ranges
+ */
+external: boolean; 
+/**
+ * Not covered by the engine's declared mode
+ */
+undeclared: boolean; first_seen: number; last_seen: number }
+/**
+ * An open stream as reported by `resync`.
+ */
+export type OpenStream = { stream_id: number; request_id: string | null; 
+/**
+ * Seq of the next chunk; `resume_stream` can replay every chunk before it
+ */
+next_seq: number; elapsed_ms: number }
+/**
+ * A known cause of slow responses.
+ */
+export type PerformanceFactor = 
+/**
+ * Recent latency compared with the baseline
+ */
+"latency" | "cpu_only" | "thermal" | "swap" | "large_context" | "queue" | "power"
+/**
+ * Response of `explain_performance`.
+ */
+export type PerformanceReport = { 
+/**
+ * Unix seconds
+ */
+generated_at: number; 
+/**
+ * Ranked, most likely cause first
+ */
+findings: Finding[] }
+/**
+ * A named, ordered sequence of engine calls.
+ */
+export type Pipeline = { name: string; description?: string | null; steps: PipelineStep[] }
+/**
+ * A pipeline run or one of its steps changed state.
+ */
+export type PipelineProgress = PipelineRun
+/**
+ * A run of a pipeline, as persisted and emitted as `pipeline_progress`.
+ */
+export type PipelineRun = { id: string; pipeline: string; input: JsonValue; state: RunState; steps: StepRun[]; 
+/**
+ * Output of the last step once the run completed
+ */
+output: JsonValue | null; error: string | null; 
+/**
+ * Unix time in seconds
+ */
+started_at: number; finished_at: number | null }
+/**
+ * One engine call of a pipeline.
+ */
+export type PipelineStep = { 
+/**
+ * Unique within the pipeline; later steps reference the output by it
+ */
+name: string; 
+/**
+ * Engine endpoint the body is POSTed to
+ */
+endpoint: string; 
+/**
+ * Request body with placeholders; None sends `{ "input": {{prev}} }`
+ */
+body?: JsonValue | null; 
+/**
+ * Dotted path of the response field that is the step's output; None keeps the whole response
+ */
+output?: string | null; 
+/**
+ * Retries after a failed attempt (DEFAULT_RETRIES if not given)
+ */
+retries?: number | null }
+/**
+ * The default model prefetch changed state or made progress.
+ */
+export type PrefetchProgress = PrefetchStatus
+/**
+ * Default model prefetch, persisted under `settings.prefetch`.
+ */
+export type PrefetchSettings = { 
+/**
+ * Download URL of the default model; None disables prefetching
+ */
+model_url: string | null; 
+/**
+ * Lowercase hex SHA-256 of the model file, checked after the download
+ */
+sha256: string | null; 
+/**
+ * Bandwidth limit in bytes per second (0 = unlimited)
+ */
+max_bytes_per_sec: number }
+/**
+ * Lifecycle state of the prefetch.
+ */
+export type PrefetchState = "running" | "paused" | "completed" | "failed"
+/**
+ * The prefetch job, as persisted in prefetch.json and reported to the UI.
+ */
+export type PrefetchStatus = { state: PrefetchState; url: string; 
+/**
+ * Where the model ends up once complete
+ */
+path: string; 
+/**
+ * Expected SHA-256, from settings at start
+ */
+sha256: string | null; bytes_done: number; 
+/**
+ * None until the server reports a size
+ */
+total_bytes: number | null; error: string | null; 
+/**
+ * Unix seconds
+ */
+updated_at: number }
+/**
+ * A prompt template: text around the user's input and an optional system prompt.
+ */
+export type PromptTemplate = { system?: string | null; 
+/**
+ * `{{input}}` marks where the user's input goes
+ */
+prompt: string }
+/**
+ * A loaded prompt template, returned by `list_prompt_templates`.
+ */
+export type PromptTemplateInfo = { name: string; system: string | null; prompt: string }
+/**
+ * A response that echoed the wrong trace id.
+ */
+export type ProtocolWarning = { endpoint: string; sent_trace_id: string; echoed_trace_id: string; 
+/**
+ * Unix time in milliseconds
+ */
+at_ms: number }
+/**
+ * Trace id echo statistics since startup, returned by `get_protocol_warnings`.
+ */
+export type ProtocolWarnings = { checked: number; missing_echo: number; mismatched: number; 
+/**
+ * Latest mismatches, oldest first
+ */
+recent: ProtocolWarning[] }
+/**
+ * An OAuth device login started with `start_provider_login` ended.
+ */
+export type ProviderLoginFinished = { provider: string; ok: boolean; error: string | null }
+/**
+ * Endpoints `call_engine` may forward, persisted under `settings.proxy`.
+ */
+export type ProxySettings = { 
+/**
+ * "<METHOD> <path>" entries; "*" matches any method, a trailing "*" any path suffix
+ */
+allowed_endpoints: string[] }
+/**
+ * Engine response to a user input.
+ */
+export type PythonInput = JsonValue
+/**
+ * Full status snapshot polled from the engine's /status endpoint (see status_delta).
+ */
+export type PythonStatus = { version: number; status: JsonValue }
+/**
+ * RFC 6902 patch turning status `base_version` into status `version`.
+ */
+export type PythonStatusDelta = { version: number; base_version: number; 
+/**
+ * RFC 6902 operations
+ */
+patch: JsonValue[] }
+/**
+ * Inputs waiting for and holding an /input slot changed.
+ */
+export type QueueDepth = { 
+/**
+ * Inputs waiting for a slot
+ */
+pending: number; running: number; 
+/**
+ * settings.engine.max_concurrent_inputs in effect
+ */
+limit: number }
+/**
+ * Where a waiting input stands, emitted as `input_queued`.
+ */
+export type QueuePosition = { request_id: string; 
+/**
+ * 1 for the next input to start; 0 once the input has its slot
+ */
+position: number; 
+/**
+ * Unix time in milliseconds; None until an input has completed
+ */
+estimated_start: number | null; estimated_wait_ms: number | null }
+/**
+ * Returned by `stop_recording`.
+ */
+export type RecordingSummary = { path: string; frames: number; duration_ms: number }
+/**
+ * A corrupt state file and how it was recovered.
+ */
+export type Recovery = { file: string; 
+/**
+ * Why the file was considered corrupt
+ */
+reason: string; source: RecoverySource; 
+/**
+ * Where the corrupt file was moved to
+ */
+corrupt_copy: string | null }
+/**
+ * What a corrupt file was replaced with.
+ */
+export type RecoverySource = 
+/**
+ * The last-good backup
+ */
+"backup" | 
+/**
+ * No usable backup; defaults
+ */
+"defaults"
+/**
+ * Engine lifetime policy, persisted under `settings.recycle` (0 = no limit).
+ */
+export type RecycleSettings = { max_lifetime_hours: number; max_requests: number; 
+/**
+ * Quiet time required before a due engine is recycled
+ */
+idle_window_secs: number }
+/**
+ * One remote provider.
+ */
+export type RemoteProvider = { name: string; base_url: string; 
+/**
+ * Path requested by `test_provider_credentials`
+ */
+test_path?: string; auth: AuthMethod }
+/**
+ * Remote providers the backend can authenticate against.
+ */
+export type RemoteSettings = { providers: RemoteProvider[] }
+/**
+ * A template applied to an input, returned by `apply_prompt_template`.
+ */
+export type RenderedPrompt = { system: string | null; 
+/**
+ * Sent as the /input payload's `input`
+ */
+input: string }
+/**
+ * One check of the installation.
+ */
+export type RepairStep = { check: string; outcome: StepOutcome; detail: string }
+/**
+ * A replay started with `replay_recording` has finished.
+ */
+export type ReplayFinished = ReplaySummary
+/**
+ * Returned by `replay_recording` and emitted as `replay_finished`.
+ */
+export type ReplaySummary = { path: string; 
+/**
+ * Commands that will be / were invoked again
+ */
+commands: number; 
+/**
+ * Recorded engine responses served by the mock engine
+ */
+engine_responses: number; 
+/**
+ * Length of the recording in milliseconds
+ */
+recorded_ms: number }
+/**
+ * Response of `restore_backup`.
+ */
+export type RestoredBackup = { 
+/**
+ * When the backup was made (Unix seconds)
+ */
+created_at: number; sessions: number; messages: number; 
+/**
+ * Template and rule files written
+ */
+files: number }
+/**
+ * Response of `resync`.
+ */
+export type ResyncSnapshot = { engine: EngineSnapshot; status: FullStatus; requests: ActiveRequest[]; streams: OpenStream[]; events: BufferedEvent[] }
+/**
+ * Kind of data a retention limit applies to.
+ */
+export type RetentionCategory = "history" | "logs" | "metrics"
+/**
+ * Data deleted (or, in a dry run, to be deleted) from one place.
+ */
+export type RetentionItem = { category: RetentionCategory; 
+/**
+ * What was pruned: a file path or a record kind
+ */
+target: string; 
+/**
+ * Files, rows or log entries
+ */
+count: number; 
+/**
+ * Bytes freed, where known
+ */
+bytes: number }
+/**
+ * Response of `run_retention_now`.
+ */
+export type RetentionReport = { dry_run: boolean; items: RetentionItem[]; total_bytes: number }
+/**
+ * Retention limits, persisted under `settings.retention` (0 = keep forever).
+ */
+export type RetentionSettings = { history_days: number; history_max_mb: number; logs_days: number; metrics_days: number }
+/**
+ * State of a run or of one of its steps.
+ */
+export type RunState = "pending" | "running" | "completed" | "failed" | "cancelled"
+/**
+ * Identity of this app instance, as returned by `get_runtime_identity`.
+ */
+export type RuntimeIdentity = { identifier: string; channel: string; 
+/**
+ * `<identifier>.<channel>`, prefix of all runtime artifact names
+ */
+namespace: string; 
+/**
+ * Endpoint of the current (or last started) engine, if any
+ */
+socket_path: string | null }
+/**
+ * Result of a successful `save_artifact`.
+ */
+export type SavedArtifact = { id: string; path: string; size_bytes: number; sha256: string; 
+/**
+ * False if the engine-side copy could not be removed
+ */
+cleaned_up: boolean }
+/**
+ * The engine answered (or failed to answer) a context-menu selection.
+ */
+export type SelectionAnswered = { request_id: string; 
+/**
+ * The /input response, as in `python_input`
+ */
+response: JsonValue | null; error: string | null }
+/**
+ * Text sent from the OS context menu is being asked about.
+ */
+export type SelectionReceived = { 
+/**
+ * Correlation id of the input (also accepted by cancel_request)
+ */
+request_id: string; selection: string }
+/**
+ * A session switched models; engine-side context of earlier messages isn't carried over.
+ */
+export type SessionModelSwitched = { session_id: string; 
+/**
+ * Model that answered the session's last message
+ */
+previous_model: string; model: string }
+/**
+ * Response of `get_session_models`.
+ */
+export type SessionModels = { session_id: string; 
+/**
+ * Model pinned with `set_session_model`, if any
+ */
+model: string | null; 
+/**
+ * Answered messages, oldest first
+ */
+messages: AnsweredMessage[] }
+/**
+ * All persisted backend settings.
+ */
+export type Settings = { engine: EngineSettings; timeouts: TimeoutSettings; telemetry: TelemetrySettings; model: ModelSettings; supervisor: SupervisorSettings; compression: CompressionSettings; socket: SocketConfig; remote: RemoteSettings; moderation: ModerationSettings; jobs: JobSettings; retention: RetentionSettings; storage: StorageSettings; backup: BackupSettings; recycle: RecycleSettings; prefetch: PrefetchSettings; proxy: ProxySettings; analytics: AnalyticsSettings; broker: BrokerSettings; features: FeatureSettings }
+/**
+ * A corrupt settings (or other state) file was restored on load.
+ */
+export type SettingsRecovered = { recovery: Recovery }
+/**
+ * Settings layout version and its JSON Schema, returned by `get_settings_schema`.
+ */
+export type SettingsSchema = { version: number; 
+/**
+ * JSON Schema (draft 7) of the settings object
+ */
+schema: JsonValue }
+/**
+ * How much a finding likely contributes, most first.
+ */
+export type Severity = "high" | "medium" | "low" | 
+/**
+ * Context only, not a cause
+ */
+"info"
+/**
+ * Endpoint settings, persisted under `settings.socket`.
+ */
+export type SocketConfig = { 
+/**
+ * Explicit socket path (or pipe name on Windows); None uses the per-user default
+ */
+path: string | null }
+/**
+ * Platform socket error behind an `EngineError::SocketUnavailable`.
+ */
+export type SocketFailure = 
+/**
+ * Nothing listens on the endpoint (ECONNREFUSED)
+ */
+"refused" | 
+/**
+ * The socket file or named pipe doesn't exist (ENOENT, ERROR_FILE_NOT_FOUND)
+ */
+"not_found" | 
+/**
+ * The endpoint belongs to another user or is locked down (EACCES, EPERM, ERROR_ACCESS_DENIED)
+ */
+"permission_denied" | 
+/**
+ * The engine closed the connection mid-request (EPIPE, ECONNRESET, ERROR_BROKEN_PIPE)
+ */
+"connection_closed" | 
+/**
+ * Every instance of the named pipe is in use (ERROR_PIPE_BUSY)
+ */
+"pipe_busy" | 
+/**
+ * Anything else; the detail has the platform error
+ */
+"other"
+/**
+ * The accessible status sentence changed.
+ */
+export type StatusSummaryChanged = { summary: string }
+/**
+ * Outcome of one check or repair step.
+ */
+export type StepOutcome = "healthy" | 
+/**
+ * Found broken (check only)
+ */
+"broken" | "repaired" | 
+/**
+ * Broken and could not be repaired
+ */
+"failed"
+/**
+ * Progress and result of one step in a run.
+ */
+export type StepRun = { name: string; state: RunState; 
+/**
+ * Attempts made in the latest try of the step
+ */
+attempts: number; output: JsonValue | null; error: string | null }
+export type StopMode = "drain" | "force"
+/**
+ * Options accepted by `stop_engine`.
+ */
+export type StopOptions = { mode?: StopMode; 
+/**
+ * Maximum time to wait for in-flight requests (drain mode only)
+ */
+deadline_ms: number | null }
+/**
+ * Outcome of a `stop_engine` call.
+ */
+export type StopReport = { mode: StopMode; 
+/**
+ * Requests still in flight when the engine was stopped
+ */
+abandoned: number; duration_ms: number }
+/**
+ * Where history is stored.
+ */
+export type StorageBackend = "sqlite" | "jsonl"
+/**
+ * Response of `migrate_storage`.
+ */
+export type StorageMigration = { from: StorageBackend; to: StorageBackend; 
+/**
+ * Session model overrides copied
+ */
+sessions: number; messages: number }
+/**
+ * History storage settings, persisted under `settings.storage`.
+ */
+export type StorageSettings = { 
+/**
+ * Changed by `migrate_storage` only
+ */
+backend: StorageBackend }
+/**
+ * One input sent to the engine and its outcome.
+ */
+export type StoredExchange = { request_id: string; session_id: string | null; input: string; 
+/**
+ * The engine's response, if it answered
+ */
+response: JsonValue | null; 
+/**
+ * Why the input failed, if it did
+ */
+error: string | null; 
+/**
+ * Unix seconds
+ */
+sent_at: number; latency_ms: number }
+/**
+ * A chat session's metadata.
+ */
+export type StoredSession = { id: string; 
+/**
+ * Empty until set, or taken from the first input
+ */
+title: string; 
+/**
+ * Unix seconds
+ */
+created_at: number; 
+/**
+ * Unix seconds of the last answered message (or creation)
+ */
+updated_at: number; 
+/**
+ * Messages answered in the session
+ */
+message_count: number }
+/**
+ * A frame sent over a streaming Channel.
+ */
+export type StreamFrame = { type: "chunk"; stream_id: number; seq: number; data: string } | { type: "end"; stream_id: number; total_chunks: number; checksum: string; error: string | null }
+/**
+ * A malformed or oversized record in an engine stream was skipped.
+ */
+export type StreamRecordSkipped = { 
+/**
+ * Stream the record came from, e.g. "/input" or "/events"
+ */
+endpoint: string; 
+/**
+ * Correlation id of the streamed input, if any
+ */
+request_id: string | null; reason: string }
+/**
+ * A streamed answer was cut off before the engine finished it.
+ */
+export type StreamTruncated = { stream_id: number; request_id: string; received_bytes: number; 
+/**
+ * Length announced by the engine's trailer, if one arrived
+ */
+expected_bytes: number | null; reason: string }
+/**
+ * How thoroughly responses are moderated.
+ */
+export type Strictness = "off" | "relaxed" | "standard" | "strict"
+/**
+ * Auto-restart policy, persisted under `settings.supervisor`.
+ */
+export type SupervisorSettings = { auto_restart: boolean; 
+/**
+ * Restarts allowed within `restart_window_secs` before giving up
+ */
+max_restarts: number; restart_window_secs: number; 
+/**
+ * Delay before the first restart; doubles with each further restart
+ */
+backoff_base_ms: number; backoff_max_ms: number }
+/**
+ * Request trace export (only used when built with the `otel` feature).
+ */
+export type TelemetrySettings = { 
+/**
+ * OTLP/HTTP collector base URL, e.g. http://localhost:4318; None disables export
+ */
+otlp_endpoint: string | null; 
+/**
+ * Fraction of requests traced, 0.0 - 1.0
+ */
+sample_rate: number }
+/**
+ * Temporary files of one owner, as reported by `get_temp_usage`.
+ */
+export type TempOwnerUsage = { 
+/**
+ * Request id, session id, or `artifact-<id>` for a saved artifact
+ */
+owner: string; files: number; bytes: number }
+/**
+ * Response of `get_temp_usage`.
+ */
+export type TempUsage = { files: number; bytes: number; 
+/**
+ * Largest owners first
+ */
+owners: TempOwnerUsage[]; 
+/**
+ * Files of a crashed run deleted at startup
+ */
+recovered_at_startup: number }
+/**
+ * Template to apply to one input (see `InputRoute`).
+ */
+export type TemplateRef = { name: string; 
+/**
+ * Values of the template's placeholders other than `{{input}}`
+ */
+variables?: Partial<{ [key in string]: string }> }
+/**
+ * Templates or filter rules changed on disk and were reloaded.
+ */
+export type TemplatesReloaded = { templates: number; rules: number; 
+/**
+ * Files that failed validation (their last good version stays active)
+ */
+errors: ValidationError[] }
+/**
+ * Snapshot returned by `get_templates`.
+ */
+export type TemplatesSnapshot = { 
+/**
+ * Prompt of each template, by name
+ */
+templates: Partial<{ [key in string]: string }>; rules: FilterRule[]; errors: ValidationError[] }
+/**
+ * Response envelope for timed commands.
+ */
+export type Timed<T> = { data: T; duration_ms: number }
+/**
+ * Response timeouts per endpoint class, in milliseconds.
+ */
+export type TimeoutSettings = { status_ms: number; chat_ms: number; batch_ms: number; control_ms: number }
+/**
+ * Turbo mode window closed and defaults were restored.
+ */
+export type TurboEnded = Record<string, never>
+/**
+ * Turbo mode window opened.
+ */
+export type TurboStarted = { duration_secs: number; threads: number }
+/**
+ * Release channel the engine updates are taken from.
+ */
+export type UpdateChannel = "stable" | "beta" | "nightly"
+/**
+ * Result of checking the selected channel for a newer engine.
+ */
+export type UpdateCheck = { channel: UpdateChannel; current_version: string | null; manifest: UpdateManifest; update_available: boolean; downgrade_blocked: boolean }
+/**
+ * Per-channel manifest published by the update server.
+ */
+export type UpdateManifest = { version: string; url: string; sha256: string; notes?: string | null }
+/**
+ * A file that failed validation; its last good version stays loaded.
+ */
+export type ValidationError = { file: string; error: string }
+/**
+ * Utilization of one worker as reported to the frontend.
+ */
+export type WorkerUtilization = { 
+/**
+ * `interactive` or `batch-<n>`
+ */
+worker: string; busy: boolean; 
+/**
+ * Job running on the worker, if any
+ */
+job_id: string | null; 
+/**
+ * Jobs and interactive inputs started on the worker
+ */
+tasks: number; busy_ms: number; 
+/**
+ * Share of the time since tracking started that the worker was busy (0-1)
+ */
+utilization: number }
+
+/** tauri-specta globals **/
+
+import {
+	invoke as TAURI_INVOKE,
+	Channel as TAURI_CHANNEL,
+} from "@tauri-apps/api/core";
+import * as TAURI_API_EVENT from "@tauri-apps/api/event";
+import { type WebviewWindow as __WebviewWindow__ } from "@tauri-apps/api/webviewWindow";
+
+type __EventObj__<T> = {
+	listen: (
+		cb: TAURI_API_EVENT.EventCallback<T>,
+	) => ReturnType<typeof TAURI_API_EVENT.listen<T>>;
+	once: (
+		cb: TAURI_API_EVENT.EventCallback<T>,
+	) => ReturnType<typeof TAURI_API_EVENT.once<T>>;
+	emit: null extends T
+		? (payload?: T) => ReturnType<typeof TAURI_API_EVENT.emit>
+		: (payload: T) => ReturnType<typeof TAURI_API_EVENT.emit>;
+};
+
+export type Result<T, E> =
+	| { status: "ok"; data: T }
+	| { status: "error"; error: E };
+
+function __makeEvents__<T extends Record<string, any>>(
+	mappings: Record<keyof T, string>,
+) {
+	return new Proxy(
+		{} as unknown as {
+			[K in keyof T]: __EventObj__<T[K]> & {
+				(handle: __WebviewWindow__): __EventObj__<T[K]>;
+			};
+		},
+		{
+			get: (_, event) => {
+				const name = mappings[event as keyof T];
+
+				return new Proxy((() => {}) as any, {
+					apply: (_, __, [window]: [__WebviewWindow__]) => ({
+						listen: (arg: any) => window.listen(name, arg),
+						once: (arg: any) => window.once(name, arg),
+						emit: (arg: any) => window.emit(name, arg),
+					}),
+					get: (_, command: keyof __EventObj__<any>) => {
+						switch (command) {
+							case "listen":
+								return (arg: any) => TAURI_API_EVENT.listen(name, arg);
+							case "once":
+								return (arg: any) => TAURI_API_EVENT.once(name, arg);
+							case "emit":
+								return (arg: any) => TAURI_API_EVENT.emit(name, arg);
+						}
+					},
+				});
+			},
+		},
+	);
+}